@@ -0,0 +1,81 @@
+//! `calculate_series` over arbitrary inputs (NaN/Inf included) and in-range
+//! parameters; every score, probability and component must come out finite
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use chrono::{Months, NaiveDate};
+use libfuzzer_sys::fuzz_target;
+use niv_engine_fuzz::niv::{EconomicData, NIVEngine, NIVResult, NonFinitePolicy};
+
+const MAX_MONTHS: usize = 240;
+
+#[derive(Debug, Arbitrary)]
+struct Month {
+    investment: f64,
+    m2_supply: f64,
+    fed_funds_rate: f64,
+    gdp: f64,
+    capacity_util: f64,
+    yield_spread: f64,
+    cpi_inflation: f64,
+    potential_gdp: Option<f64>,
+    unemployment_rate: Option<f64>,
+    nairu: Option<f64>,
+    gdp_nowcast: Option<f64>,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    eta: u16,     // Mapped into (0, 5], the range simulate accepts
+    epsilon: u16, // Mapped into [0, 1]
+    carry_forward: bool,
+    months: Vec<Month>,
+}
+
+fuzz_target!(|input: Input| {
+    let start = NaiveDate::from_ymd_opt(1990, 1, 1).expect("valid date");
+    let mut data = Vec::with_capacity(input.months.len().min(MAX_MONTHS));
+    for (i, m) in input.months.into_iter().take(MAX_MONTHS).enumerate() {
+        data.push(EconomicData {
+            date: start + Months::new(i as u32),
+            investment: m.investment,
+            m2_supply: m.m2_supply,
+            fed_funds_rate: m.fed_funds_rate,
+            gdp: m.gdp,
+            capacity_util: m.capacity_util,
+            yield_spread: m.yield_spread,
+            cpi_inflation: m.cpi_inflation,
+            rd_investment: None,
+            education_spending: None,
+            potential_gdp: m.potential_gdp,
+            unemployment_rate: m.unemployment_rate,
+            nairu: m.nairu,
+            gdp_nowcast: m.gdp_nowcast,
+            core_cpi_inflation: None,
+            pce_inflation: None,
+            expected_inflation: None,
+            estimated: Vec::new(),
+        });
+    }
+
+    let eta = (input.eta as f64 + 1.0) / (u16::MAX as f64 + 1.0) * 5.0;
+    let epsilon = input.epsilon as f64 / u16::MAX as f64;
+    let policy = if input.carry_forward { NonFinitePolicy::CarryForward } else { NonFinitePolicy::Clamp };
+    let engine = NIVEngine::with_params(eta, epsilon).with_nonfinite_policy(policy);
+    let results = engine.calculate_series(&data);
+    for r in &results {
+        assert!(finite(r), "non-finite result for {}: {:?}", r.date, r);
+    }
+    engine.alert_transitions(&results);
+});
+
+fn finite(r: &NIVResult) -> bool {
+    let c = &r.components;
+    [
+        r.niv_score, r.recession_probability, r.niv_percentile, c.thrust, c.efficiency, c.efficiency_squared,
+        c.slack, c.drag, c.drag_spread, c.drag_real_rate, c.drag_volatility,
+    ]
+    .iter()
+    .all(|v| v.is_finite())
+}
@@ -0,0 +1,45 @@
+//! Custom component expressions: arbitrary source through the registry's
+//! parser, then evaluated over mock history and fed to the engine. Bad
+//! expressions must be rejected, not panic or overflow the stack; accepted
+//! ones must leave every score and probability finite
+
+#![no_main]
+
+use std::sync::Arc;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use niv_engine_fuzz::history;
+use niv_engine_fuzz::niv::NIVEngine;
+use niv_engine_fuzz::registry::{ComponentDef, ComponentRegistry, TermRole, MAX_EXPR_LEN};
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    expr: String,
+    denominator: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let too_long = input.expr.len() > MAX_EXPR_LEN;
+    let def = ComponentDef {
+        name: "fuzzed".to_string(),
+        role: if input.denominator { TermRole::Denominator } else { TermRole::Numerator },
+        expr: input.expr,
+    };
+    let Ok(registry) = ComponentRegistry::compile(vec![def]) else {
+        return;
+    };
+    assert!(!too_long, "expression over MAX_EXPR_LEN accepted");
+
+    let data = history();
+    let terms = registry.evaluate(data);
+    assert_eq!(terms.len(), data.len());
+    for term in terms.iter().flatten() {
+        assert!(term.value.is_none_or(f64::is_finite), "non-finite term {:?}", term);
+    }
+
+    let results = NIVEngine::new().with_registry(Arc::new(registry)).calculate_series(data);
+    for r in &results {
+        assert!(r.niv_score.is_finite() && r.recession_probability.is_finite(), "non-finite result for {}", r.date);
+    }
+});
@@ -0,0 +1,22 @@
+//! Monte Carlo request bodies: deserialize, validate, then run a few draws of
+//! the requested scenario, horizon and sampling over the mock history
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use niv_engine_fuzz::budget::CancelToken;
+use niv_engine_fuzz::montecarlo;
+use niv_engine_fuzz::niv::NIVEngine;
+use niv_engine_fuzz::requests::MonteCarloRequest;
+
+const MAX_DRAWS: usize = 4; // Paths, not draw counts, are what varies
+
+fuzz_target!(|body: &[u8]| {
+    let Ok(request) = serde_json::from_slice::<MonteCarloRequest>(body) else { return };
+    let Ok(mut config) = request.config() else { return };
+    config.draws = config.draws.min(MAX_DRAWS);
+    let history = niv_engine_fuzz::history();
+    let result = montecarlo::run(&NIVEngine::new(), history, &request.scenario, config, &CancelToken::default())
+        .expect("validated configs are never rejected and never cancelled");
+    assert_eq!(result.months.len(), config.horizon_months);
+});
@@ -0,0 +1,22 @@
+//! Simulate request bodies: deserialize, validate, then run the engine the
+//! handler would build over the mock history
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use niv_engine_fuzz::interval;
+use niv_engine_fuzz::niv::{self, NIVEngine};
+use niv_engine_fuzz::requests::SimulateRequest;
+
+fuzz_target!(|body: &[u8]| {
+    let Ok(request) = serde_json::from_slice::<SimulateRequest>(body) else { return };
+    let Ok(uncertainty) = request.uncertainty() else { return };
+    let Ok(engine) = request.engine.build(&NIVEngine::new()) else { return };
+    let results = engine.calculate_series(niv_engine_fuzz::history());
+    engine.alert_transitions(&results);
+    if let Some(u) = uncertainty {
+        let ranges = interval::smooth(&interval::calculate_raw(&engine, niv_engine_fuzz::history(), &u), niv::SMOOTH_WINDOW);
+        assert_eq!(ranges.len(), results.len());
+        assert!(ranges.iter().all(|r| r.niv_score.lo <= r.niv_score.hi), "inverted range");
+    }
+});
@@ -0,0 +1,45 @@
+//! Fuzzing Harness Support
+//!
+//! The request and simulation modules live in the server binary, and depending
+//! on the package would pull in the server's dependencies, so the engine
+//! modules are compiled here from the server's sources by path. They sit at the crate root under the same
+//! names, so their `crate::` imports resolve as they do in the server.
+//!
+//! Targets (nightly and `cargo install cargo-fuzz`; run from the repo root):
+//! - `cargo +nightly fuzz run simulate_request` - simulate bodies: parse, validate, run the engine
+//! - `cargo +nightly fuzz run monte_carlo_request` - Monte Carlo bodies: parse, validate, run a few draws
+//! - `cargo +nightly fuzz run calculate_series` - arbitrary inputs (NaN/Inf included) and parameters through the engine
+//! - `cargo +nightly fuzz run component_expression` - custom component expressions: parse, evaluate, run the engine
+
+#![allow(dead_code)]
+
+#[path = "../../src/budget.rs"]
+pub mod budget;
+#[path = "../../src/fred.rs"]
+pub mod fred; // Mock data only (no `fred` feature here)
+#[path = "../../src/interval.rs"]
+pub mod interval;
+#[path = "../../src/montecarlo.rs"]
+pub mod montecarlo;
+#[path = "../../src/niv.rs"]
+pub mod niv;
+#[path = "../../src/qmc.rs"]
+pub mod qmc;
+#[path = "../../src/registry.rs"]
+pub mod registry;
+#[path = "../../src/requests.rs"]
+pub mod requests;
+#[path = "../../src/scenario.rs"]
+pub mod scenario;
+#[path = "../../src/transform.rs"]
+pub mod transform;
+
+use std::sync::OnceLock;
+
+use niv::EconomicData;
+
+/// Mock history the request targets run against
+pub fn history() -> &'static [EconomicData] {
+    static HISTORY: OnceLock<Vec<EconomicData>> = OnceLock::new();
+    HISTORY.get_or_init(|| fred::mock::generate_mock_data(1995, 2005))
+}
@@ -0,0 +1,175 @@
+//! Historical Analogues
+//!
+//! Finds the months whose component mix (thrust, efficiency, slack, drag)
+//! looks most like the latest month, and reports what happened next.
+//!
+//! - Components are z-scored over the whole history so no single scale
+//!   dominates, then compared by Euclidean distance
+//! - Candidates need a full HORIZON_MONTHS of follow-up, so the latest months
+//!   never match themselves
+//! - Picks are at least MIN_SEPARATION_MONTHS apart, so one episode does not
+//!   fill every slot
+
+use chrono::{Months, NaiveDate};
+use serde::Serialize;
+use statrs::statistics::Statistics;
+
+use crate::niv::{AlertLevel, Component, NIVResult, RecessionPeriods};
+
+/// Months of follow-up reported per analogue
+pub const HORIZON_MONTHS: usize = 12;
+
+/// Analogues returned by default, and at most
+pub const DEFAULT_K: usize = 5;
+pub const MAX_K: usize = 20;
+
+/// Minimum gap between two returned analogues
+const MIN_SEPARATION_MONTHS: usize = 6;
+
+/// What followed an analogue month
+#[derive(Debug, Clone, Serialize)]
+pub struct Outcome {
+    pub recession_probability_change: f64, // At the horizon, minus at the analogue month
+    pub peak_recession_probability: f64,
+    pub min_niv_score: f64,
+    pub alert_level_at_horizon: AlertLevel,
+    pub recession_within_horizon: bool, // NBER recession in any following month
+    pub recession_start: Option<NaiveDate>,
+}
+
+/// One historical month similar to the latest
+#[derive(Debug, Clone, Serialize)]
+pub struct Analogue {
+    pub date: NaiveDate,
+    pub distance: f64, // In z-score units
+    pub niv_score: f64,
+    pub recession_probability: f64,
+    pub alert_level: AlertLevel,
+    pub thrust: f64,
+    pub efficiency: f64,
+    pub slack: f64,
+    pub drag: f64,
+    pub next_12_months: Outcome,
+}
+
+fn outcome(start: &NIVResult, following: &[NIVResult]) -> Outcome {
+    let end = following.last().unwrap_or(start);
+    let horizon_end = start.date.checked_add_months(Months::new(HORIZON_MONTHS as u32)).unwrap_or(start.date);
+    Outcome {
+        recession_probability_change: end.recession_probability - start.recession_probability,
+        peak_recession_probability: following.iter().map(|r| r.recession_probability).fold(0.0, f64::max),
+        min_niv_score: following.iter().map(|r| r.niv_score).fold(f64::INFINITY, f64::min),
+        alert_level_at_horizon: end.alert_level,
+        recession_within_horizon: following.iter().any(|r| RecessionPeriods::is_recession(r.date)),
+        recession_start: RecessionPeriods::known_recessions()
+            .into_iter()
+            .map(|(s, _)| s)
+            .filter(|s| *s > start.date && *s <= horizon_end)
+            .min(),
+    }
+}
+
+/// The `k` months most similar to the last result, nearest first
+pub fn find(results: &[NIVResult], k: usize) -> Vec<Analogue> {
+    let Some(latest) = results.last() else {
+        return Vec::new();
+    };
+    let candidates = results.len().saturating_sub(HORIZON_MONTHS + 1);
+
+    // Per-component mean and scale over the whole history
+    let scales: Vec<(Component, f64, f64)> = Component::all()
+        .into_iter()
+        .map(|component| {
+            let values: Vec<f64> = results.iter().map(|r| r.components.value(component)).collect();
+            let sd = values.iter().std_dev();
+            (component, values.iter().mean(), if sd.is_finite() && sd > 0.0 { sd } else { 1.0 })
+        })
+        .collect();
+    let z = |r: &NIVResult| -> Vec<f64> {
+        scales
+            .iter()
+            .map(|&(component, mean, sd)| (r.components.value(component) - mean) / sd)
+            .collect()
+    };
+
+    let target = z(latest);
+    let mut ranked: Vec<(usize, f64)> = results[..candidates]
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let distance = z(r).iter().zip(&target).map(|(a, b)| (a - b).powi(2)).sum::<f64>().sqrt();
+            (i, distance)
+        })
+        .filter(|(_, d)| d.is_finite())
+        .collect();
+    ranked.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let mut picked: Vec<(usize, f64)> = Vec::with_capacity(k);
+    for (i, distance) in ranked {
+        if picked.len() == k {
+            break;
+        }
+        if picked.iter().all(|&(j, _)| i.abs_diff(j) >= MIN_SEPARATION_MONTHS) {
+            picked.push((i, distance));
+        }
+    }
+
+    picked
+        .into_iter()
+        .map(|(i, distance)| {
+            let r = &results[i];
+            Analogue {
+                date: r.date,
+                distance,
+                niv_score: r.niv_score,
+                recession_probability: r.recession_probability,
+                alert_level: r.alert_level,
+                thrust: r.components.thrust,
+                efficiency: r.components.efficiency,
+                slack: r.components.slack,
+                drag: r.components.drag,
+                next_12_months: outcome(r, &results[i + 1..=i + HORIZON_MONTHS]),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+    use chrono::Datelike;
+
+    #[test]
+    fn test_analogues_are_ranked_separated_and_followed_up() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1970, 2024));
+        let analogues = find(&results, 8);
+
+        assert_eq!(analogues.len(), 8);
+        assert!(analogues.windows(2).all(|w| w[0].distance <= w[1].distance));
+        let cutoff = results[results.len() - 1 - HORIZON_MONTHS].date;
+        assert!(analogues.iter().all(|a| a.date < cutoff));
+        let month = |d: NaiveDate| d.year() * 12 + d.month() as i32;
+        for (i, a) in analogues.iter().enumerate() {
+            for b in &analogues[i + 1..] {
+                assert!((month(a.date) - month(b.date)).abs() >= MIN_SEPARATION_MONTHS as i32);
+            }
+        }
+    }
+
+    #[test]
+    fn test_outcome_flags_following_recession() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2000, 2012));
+        let i = results.iter().position(|r| r.date == NaiveDate::from_ymd_opt(2007, 6, 1).unwrap()).unwrap();
+        let o = outcome(&results[i], &results[i + 1..=i + HORIZON_MONTHS]);
+        assert!(o.recession_within_horizon);
+        assert_eq!(o.recession_start, NaiveDate::from_ymd_opt(2007, 12, 1));
+
+        let i = results.iter().position(|r| r.date == NaiveDate::from_ymd_opt(2004, 1, 1).unwrap()).unwrap();
+        let o = outcome(&results[i], &results[i + 1..=i + HORIZON_MONTHS]);
+        assert!(!o.recession_within_horizon);
+        assert!(o.peak_recession_probability >= results[i + HORIZON_MONTHS].recession_probability);
+        assert!(find(&results[..5], 3).is_empty());
+    }
+}
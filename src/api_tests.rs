@@ -0,0 +1,395 @@
+//! API Response Snapshots
+//!
+//! Drives the full router (middleware included) with `oneshot` against a
+//! fixture state over the mock dataset, and snapshots each endpoint's JSON
+//! with insta, so a change to any client-visible shape shows up as a
+//! snapshot diff. Review and accept intended changes with `cargo insta review`.
+//!
+//! Arrays are trimmed to their first TRIM_ARRAYS items (the shape is the
+//! contract, not every month), and per-run values such as tokens, IDs and
+//! timestamps are redacted. Non-JSON endpoints (export, calendar, widget, SSE
+//! streams) are not covered. The mirror profile is checked for its routes
+//! and caching headers, the demo profile for its limits and watermark, and
+//! the public tier for the embargo on every keyless route, the fitted
+//! calibrations for reuse until the next install, and a dataset with
+//! estimated inputs for its `coverage` flag.
+
+use axum::body::Body;
+use axum::http::{Method, Request};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use super::*;
+
+const TRIM_ARRAYS: usize = 3;
+
+/// Serving state over the mock dataset, without env configuration
+async fn fixture() -> Arc<AppState> {
+    let engine = NIVEngine::new();
+    let dataset = compute_dataset(&engine, None);
+    let validation = engine.validate_against_benchmarks(&dataset.smoothed);
+    let mut checks = selftest::invariants(&dataset.smoothed);
+    checks.extend(selftest::benchmarks(&validation));
+    checks.push(selftest::storage(None));
+
+    let state = Arc::new(AppState {
+        serving: std::sync::RwLock::new(Serving { version: MODEL_VERSION.to_string(), engine: Arc::new(engine) }),
+        cache: Cache::builder().build(),
+        mc_runs: Cache::builder().max_capacity(MC_RUN_BYTES).weigher(|id: &String, run: &Arc<StoredRun>| weigh_run(id, run)).build(),
+        revision: std::sync::RwLock::new(String::new()),
+        inputs: RwLock::new(Vec::new()),
+        raw: RwLock::new(Vec::new()),
+        data: RwLock::new(Vec::new()),
+        validation: RwLock::new(Some(SelfTestReport::new(FailurePolicy::default(), checks))),
+        replays: RwLock::new(HashMap::new()),
+        http: WebhookClient::new(),
+        max_span_months: None,
+        budget: ComputeBudget::default(),
+        jobs: Scheduler::new(LaneConfig { interactive_workers: 2, batch_workers: 1 }), // Not per-machine
+        ready: AtomicBool::new(true),
+        snapshot_path: None,
+        refreshing: tokio::sync::Mutex::new(()),
+        refresh_schedule: None,
+        backpressure: Backpressure::default(),
+        load: LoadMonitor::default(),
+        changelog: RwLock::new(Changelog::default()),
+        deprecations: DeprecationRegistry::default(),
+        labels: RwLock::new(LabelSets::default()),
+        updates: tokio::sync::broadcast::channel(WIDGET_EVENT_BUFFER).0,
+        releases: tokio::sync::Mutex::new(None),
+        calibrations: RwLock::new(None),
+        intraday: RwLock::new(None),
+        #[cfg(feature = "tsdb")]
+        tsdb: None,
+        #[cfg(feature = "fred")]
+        fred_proxy: None,
+        fred_access: FredAccess::Live,
+        canary: RwLock::new(None),
+        canary_refreshes: 3,
+        admin_token: None,
+        api_keys: ApiKeys::default(),
+        share: ShareSigner::new(b"snapshot-fixture"),
+        public: None,
+        preferences: PreferenceStore::default(),
+        incidents: RwLock::new(VecDeque::new()),
+        incident_webhook: None,
+        export_memory: streaming::DEFAULT_EXPORT_MEMORY_BYTES,
+        partial_data: coverage::PartialDataMode::default(),
+        tasks: Tasks::default(),
+    });
+    install_dataset(&state, dataset).await;
+    state
+}
+
+/// The fixture with the public tier on, its last `held` months still under embargo
+async fn public_fixture(held: usize) -> Arc<AppState> {
+    let mut state = fixture().await;
+    let served = state.data.read().await.clone();
+    let tier = PublicTier::new(chrono::Duration::hours(24));
+    let now = chrono::Utc::now();
+    tier.record(now - chrono::Duration::hours(48), &served[..served.len() - held]);
+    tier.record(now, &served);
+    Arc::get_mut(&mut state).expect("fixture is unshared").public = Some(tier);
+    state
+}
+
+/// Status and JSON body of one request through the router
+async fn call(app: &Router, method: Method, uri: &str, body: Option<Value>) -> (u16, Value) {
+    let request = Request::builder().method(method).uri(uri).header(header::CONTENT_TYPE, "application/json");
+    let body = body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty);
+    let response = app.clone().oneshot(request.body(body).expect("request")).await.expect("infallible router");
+    let status = response.status().as_u16();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.expect("body");
+    let value = serde_json::from_slice(&bytes).unwrap_or_else(|e| panic!("{}: non-JSON body ({})", uri, e));
+    (status, value)
+}
+
+fn trimmed(value: Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().take(TRIM_ARRAYS).map(trimmed).collect()),
+        Value::Object(fields) => Value::Object(fields.into_iter().map(|(k, v)| (k, trimmed(v))).collect()),
+        v => v,
+    }
+}
+
+/// Snapshot `{status, body}` under `name`
+fn snapshot(name: &str, (status, body): (u16, Value)) {
+    let mut settings = insta::Settings::clone_current();
+    settings.set_prepend_module_to_snapshot(false);
+    settings.set_snapshot_path("snapshots");
+    settings.set_omit_expression(true);
+    for field in ["token", "url", "expires_at", "timestamp", "uptime_seconds", "started_at", "ended_at"] {
+        settings.add_redaction(&format!(".**.{}", field), format!("[{}]", field));
+    }
+    settings.bind(|| insta::assert_json_snapshot!(name, json!({ "status": status, "body": trimmed(body) })));
+}
+
+#[tokio::test]
+async fn test_read_endpoint_shapes() {
+    let app = router(fixture().await);
+    let cases = [
+        ("root", "/"),
+        ("health", "/health"),
+        ("health_ready", "/health/ready"),
+        ("recessions", "/api/v1/recessions"),
+        ("labels", "/api/v1/labels"),
+        ("changelog", "/api/v1/changelog"),
+        ("pipeline", "/api/v1/pipeline"),
+        ("simulate_bounds", "/api/v1/simulate/bounds"),
+        ("deprecations", "/api/v1/deprecations"),
+        ("validation", "/api/v1/validation"),
+        ("latest", "/api/v1/latest"),
+        ("latest_percentile", "/api/v1/latest?score=percentile"),
+        ("latest_horizon", "/api/v1/latest?horizon=6"),
+        ("latest_fraction", "/api/v1/latest?probability_units=fraction"),
+        ("history_fraction", "/api/v1/history?start=2020-01-01&end=2020-03-01&probability_units=fraction"),
+        ("components_precision", "/api/v1/components?precision=8"),
+        ("history", "/api/v1/history?start=2020-01-01&end=2020-12-01"),
+        ("history_warmup", "/api/v1/history?start=1961-10-01&end=1962-01-01"),
+        ("history_forecast", "/api/v1/history?start=2026-01-01&forecast=3"),
+        ("history_benchmark", "/api/v1/history?start=2020-01-01&end=2020-12-01&series=benchmark"),
+        ("history_tracking", "/api/v1/history?start=2020-01-01&end=2020-03-01&series=tracking"),
+        ("latest_benchmark", "/api/v1/latest?series=benchmark"),
+        ("error_series_smooth", "/api/v1/history?series=benchmark&smooth=6"),
+        ("components", "/api/v1/components"),
+        ("dashboard", "/api/v1/dashboard"),
+        ("analogues", "/api/v1/analogues?k=2"),
+        ("backtest_replication", "/api/v1/backtest/replication"),
+        ("conditional", "/api/v1/conditional?when=drag%3E0.02"),
+        ("regimes", "/api/v1/regimes?clusters=3"),
+        ("regimes_gmm", "/api/v1/regimes?clusters=3&method=gmm"),
+        ("compare", "/api/v1/compare"),
+        ("yield_curve", "/api/v1/yield-curve?start=2000-01-01"),
+        ("term_structure", "/api/v1/term-structure"),
+        ("thrust_inputs", "/api/v1/thrust-inputs?start=2020-01-01&end=2020-06-01"),
+        ("lead_times", "/api/v1/lead-times"),
+        ("lead_times_regime", "/api/v1/lead-times?regime=2&clusters=3"),
+        ("false_alarms", "/api/v1/false-alarms"),
+        ("episodes", "/api/v1/episodes?level=normal&min_duration=2"),
+        ("event_study", "/api/v1/event-study?pre=2&post=1"),
+        ("leaderboard", "/api/v1/research/leaderboard"),
+        ("model_card", "/api/v1/models/NIV-v6-OOS/card"),
+        ("error_unknown_model", "/api/v1/models/NIV-v9/card"),
+        ("synthetic_benchmark", "/api/v1/synthetic-benchmark?economies=2&seed=1"),
+        ("workspace", "/api/v1/workspace"),
+        ("preferences", "/api/v1/preferences"),
+        ("grafana", "/grafana"),
+        ("admin_disabled", "/api/v1/admin/canary"),
+        ("error_invalid_date", "/api/v1/history?start=2020-13-01"),
+        ("error_not_found", "/api/v1/montecarlo/mc-missing"),
+        ("error_unknown_labels", "/api/v1/lead-times?labels=ecri"),
+        ("error_invalid_precision", "/api/v1/history?precision=20"),
+        ("error_invalid_export_format", "/api/v1/export?format=parquet"),
+    ];
+    for (name, uri) in cases {
+        snapshot(name, call(&app, Method::GET, uri, None).await);
+    }
+    #[cfg(feature = "fred")]
+    snapshot("fred_unavailable", call(&app, Method::GET, "/api/v1/fred/T10Y3M", None).await);
+}
+
+#[tokio::test]
+async fn test_compute_endpoint_shapes() {
+    let state = fixture().await;
+    let app = router(state.clone());
+    let range = json!({ "from": "2019-01-01T00:00:00Z", "to": "2020-12-31T00:00:00Z" });
+    let monte_carlo = json!({ "horizon_months": 3, "draws": 20, "seed": 7 });
+    let cases = [
+        ("simulate", "/api/v1/simulate", json!({ "start": "2020-01-01", "end": "2020-06-01" })),
+        (
+            "simulate_uncertainty",
+            "/api/v1/simulate",
+            json!({ "start": "2020-01-01", "end": "2020-03-01", "uncertainty": { "capacity_util": 0.2 } }),
+        ),
+        ("montecarlo", "/api/v1/montecarlo", monte_carlo.clone()),
+        ("at_batch", "/api/v1/at/batch", json!({ "dates": ["2020-04", "2008-09-15", "2020-04-01"] })),
+        ("error_out_of_range_date", "/api/v1/at/batch", json!({ "dates": ["2008-09", "1900-01"] })),
+        ("share", "/api/v1/share", json!({ "kind": "monte_carlo", "request": monte_carlo.clone() })),
+        ("grafana_search", "/grafana/search", json!({ "target": "niv" })),
+        ("grafana_query", "/grafana/query", json!({ "range": range, "targets": [{ "target": "niv_score" }] })),
+        ("grafana_annotations", "/grafana/annotations", json!({ "range": range })),
+        ("error_invalid_draws", "/api/v1/montecarlo", json!({ "draws": 0 })),
+        ("error_unknown_favorite", "/api/v1/montecarlo", json!({ "favorite": "hike" })),
+        (
+            "error_negative_denominator",
+            "/api/v1/simulate",
+            json!({ "denominator": "reject", "weights": { "drag_real_rate": -40.0 } }),
+        ),
+    ];
+    for (name, uri, body) in cases {
+        snapshot(name, call(&app, Method::POST, uri, Some(body)).await);
+    }
+
+    // Stored run by ID, and a share link opening it
+    let (_, run) = call(&app, Method::POST, "/api/v1/montecarlo", Some(monte_carlo.clone())).await;
+    let id = run["id"].as_str().expect("run id");
+    snapshot("montecarlo_by_id", call(&app, Method::GET, &format!("/api/v1/montecarlo/{}", id), None).await);
+    let (_, share) = call(&app, Method::POST, "/api/v1/share", Some(json!({ "kind": "monte_carlo", "request": monte_carlo }))).await;
+    let url = share["url"].as_str().expect("share url");
+    snapshot("shared_montecarlo", call(&app, Method::GET, url, None).await);
+
+    // Preferences are per key; keyless requests cannot store them
+    let keyless = call(&app, Method::PUT, "/api/v1/preferences", Some(json!({ "smooth": 6 }))).await;
+    snapshot("error_preferences_keyless", keyless);
+
+    let (_, replay) = call(&app, Method::POST, "/api/v1/replay/start?from=2020-01&to=2020-06", None).await;
+    let replay_id = replay["id"].as_str().expect("replay id").to_string();
+    for (name, method, uri) in [
+        ("replay_status", Method::GET, format!("/api/v1/replay/{}", replay_id)),
+        ("replay_stop", Method::POST, format!("/api/v1/replay/{}/stop", replay_id)),
+    ] {
+        let (status, mut body) = call(&app, method, &uri, None).await;
+        body["id"] = json!("[id]"); // Random per replay
+        snapshot(name, (status, body));
+    }
+
+    // A link stops once the dataset it was computed on is replaced
+    *state.revision.write().expect("revision lock") = "next-revision".to_string();
+    let (status, stale) = call(&app, Method::GET, url, None).await;
+    assert_eq!((status, stale["code"].as_str()), (410, Some("SHARE_STALE")));
+}
+
+#[tokio::test]
+async fn test_mirror_profile() {
+    let app = mirror_router(fixture().await, 600);
+    let get = |uri: &str| {
+        let request = Request::builder().uri(uri).header(tenancy::API_KEY_HEADER, "unknown-key");
+        app.clone().oneshot(request.body(Body::empty()).expect("request"))
+    };
+
+    // Keys are ignored, so an unknown one is served like any keyless request
+    let latest = get("/api/v1/latest").await.expect("infallible router");
+    assert_eq!(latest.status(), StatusCode::OK);
+    assert_eq!(latest.headers()[header::CACHE_CONTROL], "public, max-age=600, stale-while-revalidate=600");
+    assert!(latest.headers().contains_key(PIPELINE_HEADER));
+    for uri in ["/api/v1/history?start=2020-01-01", "/api/v1/recessions"] {
+        assert_eq!(get(uri).await.expect("infallible router").status(), StatusCode::OK);
+    }
+
+    let error = get("/api/v1/history?start=2020-13-01").await.expect("infallible router");
+    assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(error.headers()[header::CACHE_CONTROL], "no-store");
+    for uri in ["/api/v1/components", "/api/v1/simulate", "/api/v1/admin/canary"] {
+        assert_eq!(get(uri).await.expect("infallible router").status(), StatusCode::NOT_FOUND);
+    }
+}
+
+#[tokio::test]
+async fn test_demo_profile() {
+    let policy = DemoPolicy { limiter: demo::ClientLimiter::new(3, Duration::from_secs(60)), client_ip_header: None };
+    let app = demo_router(fixture().await, policy);
+    let get = |uri: &str| {
+        let request = Request::builder().uri(uri).header(tenancy::API_KEY_HEADER, "unknown-key");
+        app.clone().oneshot(request.body(Body::empty()).expect("request"))
+    };
+    let body = |response: Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.expect("body");
+        serde_json::from_slice::<Value>(&bytes).expect("JSON body")
+    };
+
+    // Health checks are not counted against the limit
+    for _ in 0..5 {
+        assert_eq!(get("/health").await.expect("infallible router").status(), StatusCode::OK);
+    }
+    let latest = get("/api/v1/latest").await.expect("infallible router");
+    assert_eq!(latest.status(), StatusCode::OK);
+    assert_eq!(latest.headers()[demo::DEMO_HEADER], "true");
+    let latest = body(latest).await;
+    assert_eq!(latest["demo"], true);
+    assert!(latest["niv_score"].is_number());
+
+    let admin = get("/api/v1/admin/scheduler").await.expect("infallible router");
+    assert_eq!(admin.status(), StatusCode::FORBIDDEN);
+    assert_eq!(body(admin).await["demo"], true);
+    assert_eq!(get("/api/v1/recessions").await.expect("infallible router").status(), StatusCode::OK);
+
+    let limited = get("/api/v1/latest").await.expect("infallible router");
+    assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(limited.headers().contains_key(header::RETRY_AFTER));
+    assert_eq!(body(limited).await["code"], "RATE_LIMITED");
+}
+
+#[tokio::test]
+async fn test_public_tier_embargo() {
+    let state = public_fixture(3).await;
+    let served = state.data.read().await.clone();
+    let embargoed = served[served.len() - 4].date;
+    let app = router(state);
+
+    let (status, history) = call(&app, Method::GET, "/api/v1/history?start=2020-01-01", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(history["end_date"], embargoed.to_string());
+    let (_, benchmark) = call(&app, Method::GET, "/api/v1/history?series=benchmark", None).await;
+    let last = NaiveDate::parse_from_str(benchmark["end_date"].as_str().unwrap(), "%Y-%m-%d").unwrap();
+    assert!(Product::Benchmark.covers_through(last) <= embargoed);
+    let (_, latest) = call(&app, Method::GET, "/api/v1/latest", None).await;
+    assert_eq!(latest["date"], embargoed.to_string());
+
+    let request = Request::builder().uri("/api/v1/export?format=csv&start=2020-01-01").body(Body::empty()).unwrap();
+    let export = app.clone().oneshot(request).await.expect("infallible router");
+    assert_eq!(export.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(export.into_body(), usize::MAX).await.expect("body");
+    let csv = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(csv.lines().last().unwrap().starts_with(&embargoed.to_string()));
+
+    for (method, uri, body) in [
+        (Method::GET, "/api/v1/components", None),
+        (Method::GET, "/api/v1/history?smooth=6", None),
+        (Method::POST, "/api/v1/at/batch", Some(json!({ "dates": ["2020-01-01"] }))),
+        (Method::POST, "/grafana/query", Some(json!({ "targets": [] }))),
+    ] {
+        let (status, error) = call(&app, method, uri, body).await;
+        assert_eq!((status, error["code"].as_str()), (401, Some("API_KEY_REQUIRED")), "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_calibrations_fitted_once_per_dataset() {
+    let state = fixture().await;
+    let app = router(state.clone());
+
+    let (status, _) = call(&app, Method::GET, "/api/v1/term-structure", None).await;
+    assert_eq!(status, 200);
+    let fitted = state.calibrations.read().await.clone().expect("fitted on first request");
+    call(&app, Method::GET, "/api/v1/term-structure", None).await;
+    assert!(Arc::ptr_eq(&fitted, state.calibrations.read().await.as_ref().unwrap()));
+
+    let engine = NIVEngine::new();
+    install_dataset(&state, compute_dataset(&engine, None)).await;
+    assert!(state.calibrations.read().await.is_none());
+}
+
+#[tokio::test]
+async fn test_estimated_inputs_served_with_coverage() {
+    let state = fixture().await;
+    let engine = NIVEngine::new();
+    let mut inputs = state.inputs.read().await.clone();
+    let end = inputs.last().expect("mock inputs").date;
+    let through = end.checked_sub_months(chrono::Months::new(2)).unwrap();
+    assert_eq!(coverage::fill(&mut inputs, &[(InputSeries::CpiInflation, through)], coverage::PartialDataMode::LastValue), 2);
+    let raw = engine.calculate_raw(&inputs);
+    let smoothed = engine.smooth(&raw, niv::SMOOTH_WINDOW);
+    install_dataset(&state, Dataset { inputs, raw, smoothed }).await;
+
+    let (status, latest) = call(&router(state), Method::GET, "/api/v1/latest", None).await;
+    assert_eq!(status, 200);
+    assert_eq!(latest["coverage"], json!(["cpi_inflation"]));
+}
+
+#[tokio::test]
+async fn test_latest_follows_selected_labels() {
+    let state = fixture().await;
+    let raw = r#"[{"start":"2008-01","end":"2009-06"},{"start":"2020-02","end":"2020-04"}]"#;
+    let set = labels::parse("recent", raw, LabelFormat::Json).unwrap();
+    state.labels.write().await.insert(set).unwrap();
+    let app = router(state);
+
+    let (_, nber) = call(&app, Method::GET, "/api/v1/latest", None).await;
+    let (status, recent) = call(&app, Method::GET, "/api/v1/latest?labels=recent", None).await;
+    assert_eq!(status, 200);
+    let base_rate = |body: &Value| body["probability_decomposition"]["base_rate"].as_f64().unwrap();
+    assert!(base_rate(&recent) < base_rate(&nber));
+    // Two episodes are too few to fit a severity band
+    assert!(nber["expected_severity"].is_object());
+    assert!(recent.get("expected_severity").is_none());
+}
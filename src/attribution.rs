@@ -0,0 +1,138 @@
+//! Probability Attribution
+//!
+//! One-at-a-time attribution of a month's recession probability to the four
+//! NIV components: each component in turn is reset to its historical median
+//! (others held at the month's values) and the probability recomputed. The
+//! contribution is the month's probability minus that counterfactual, so a
+//! positive value means the component pushed risk above a typical month.
+//!
+//! Probabilities go through the engine's link for the month
+//! (`NIVEngine::probability_link`: the configured probability input and the
+//! expansion-age shift), so the actual value is the one the engine serves
+//! for those components. Contributions are not additive; they rank which
+//! component moved the probability, not an exact decomposition.
+//!
+//! `change_contributions` is exact where that is needed: it splits the move
+//! from one set of components to another (today to a forecast month) by
+//! Shapley values, each component's marginal effect averaged over every
+//! order of switching the four, so the parts sum to the total change.
+
+use crate::niv::{Component, NIVComponents, NIVResult};
+
+/// Median of each component over `results` (None when empty)
+pub fn baseline(results: &[NIVResult]) -> Option<NIVComponents> {
+    let median = |f: fn(&NIVComponents) -> f64| {
+        let mut values: Vec<f64> = results.iter().map(|r| f(&r.components)).filter(|v| v.is_finite()).collect();
+        values.sort_by(|a, b| a.total_cmp(b));
+        values.get(values.len() / 2).copied().unwrap_or(0.0)
+    };
+    let mut typical = results.last()?.components.clone();
+    typical.thrust = median(|c| c.thrust);
+    typical.efficiency = median(|c| c.efficiency);
+    typical.efficiency_squared = median(|c| c.efficiency_squared);
+    typical.slack = median(|c| c.slack);
+    typical.drag = median(|c| c.drag);
+    Some(typical)
+}
+
+/// Signed probability contribution of each component against `baseline`,
+/// through `link`
+pub fn contributions(
+    link: impl Fn(&NIVComponents) -> f64,
+    components: &NIVComponents,
+    baseline: &NIVComponents,
+) -> [(Component, f64); 4] {
+    let actual = link(components);
+    Component::all().map(|c| {
+        let mut counterfactual = components.clone();
+        c.copy(baseline, &mut counterfactual);
+        (c, actual - link(&counterfactual))
+    })
+}
+
+/// Shapley split of the change in `link` from `from` to `to`; the
+/// contributions sum to that change
+pub fn change_contributions(
+    link: impl Fn(&NIVComponents) -> f64,
+    from: &NIVComponents,
+    to: &NIVComponents,
+) -> [(Component, f64); 4] {
+    let components = Component::all();
+    let n = components.len();
+    // Probability with the components in `mask` switched to `to`
+    let probability = |mask: usize| {
+        let mut mixed = from.clone();
+        for (i, c) in components.iter().enumerate() {
+            if mask & (1 << i) != 0 {
+                c.copy(to, &mut mixed);
+            }
+        }
+        link(&mixed)
+    };
+    let values: Vec<f64> = (0..1 << n).map(probability).collect();
+    let factorial = |k: usize| (1..=k).product::<usize>() as f64;
+    let mut out = components.map(|c| (c, 0.0));
+    for (i, (_, share)) in out.iter_mut().enumerate() {
+        for mask in (0..1 << n).filter(|m| m & (1 << i) == 0) {
+            let size = (mask as u32).count_ones() as usize;
+            let weight = factorial(size) * factorial(n - size - 1) / factorial(n);
+            *share += weight * (values[mask | (1 << i)] - values[mask]);
+        }
+    }
+    out
+}
+
+/// Component contributing most in the direction of `divergence` (positive:
+/// towards higher probability), or the largest in magnitude when it is zero
+pub fn top_contributor(contributions: &[(Component, f64)], divergence: f64) -> Option<(Component, f64)> {
+    contributions
+        .iter()
+        .copied()
+        .max_by(|a, b| {
+            let score = |d: f64| if divergence > 0.0 { d } else if divergence < 0.0 { -d } else { d.abs() };
+            score(a.1).total_cmp(&score(b.1))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_baseline_month_has_no_contributions() {
+        let engine = NIVEngine::new();
+        let results = engine.calculate_series(&mock::generate_mock_data(1990, 2024));
+        let typical = baseline(&results).unwrap();
+        let link = engine.probability_link(results.last().unwrap().date, &[]);
+
+        for (_, delta) in contributions(&link, &typical, &typical) {
+            assert_eq!(delta, 0.0);
+        }
+        assert!(baseline(&[]).is_none());
+    }
+
+    #[test]
+    fn test_contributions_follow_component_moves() {
+        let engine = NIVEngine::new().with_expansion_age_weight(0.5);
+        let results = engine.calculate_series(&mock::generate_mock_data(1990, 2024));
+        let typical = baseline(&results).unwrap();
+        let link = engine.probability_link(results.last().unwrap().date, &[]);
+
+        let mut stressed = typical.clone();
+        stressed.drag += 0.5;
+        let found = contributions(&link, &stressed, &typical);
+        let drag = found.iter().find(|(c, _)| *c == Component::Drag).unwrap().1;
+        assert!(drag > 0.0);
+        assert_eq!(top_contributor(&found, 1.0), Some((Component::Drag, drag)));
+        assert!(top_contributor(&[], 1.0).is_none());
+
+        let mut moved = stressed.clone();
+        moved.thrust -= 0.3;
+        let split = change_contributions(&link, &typical, &moved);
+        let total = link(&moved) - link(&typical);
+        assert!((split.iter().map(|(_, d)| d).sum::<f64>() - total).abs() < 1e-12);
+        assert!(split.iter().all(|&(c, d)| matches!(c, Component::Drag | Component::Thrust) || d.abs() < 1e-12));
+    }
+}
@@ -0,0 +1,182 @@
+//! Refresh Backpressure
+//!
+//! A scheduled refresh recomputes the whole history and shadows the canary,
+//! competing with request handling for cores. A middleware records the
+//! latency of every interactive data request in a `LoadMonitor`; admin
+//! routes, batch-lane work (Monte Carlo, the leaderboard), upstream fetches
+//! and streams are left out, as their durations say nothing about
+//! contention. Before a scheduled refresh the loop reads a `Pressure` from
+//! it, combining the p95 latency over the last `LATENCY_WINDOW` with the
+//! 1-minute load average per core (`/proc/loadavg`; latency only on other
+//! platforms).
+//!
+//! Under pressure the refresh is deferred in `DEFER_STEP`s up to
+//! `max_defer` (never past the refresh period). After that it runs anyway,
+//! but chunked: the recompute and the canary shadow run as separate
+//! batch-lane jobs, and the shadow is skipped (not counted toward
+//! promotion) if the load is still high once the recompute finishes.
+//! Admin-triggered refreshes are never deferred.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Latency samples older than this are dropped
+pub const LATENCY_WINDOW: Duration = Duration::from_secs(60);
+
+/// Wait between load checks while a refresh is deferred
+pub const DEFER_STEP: Duration = Duration::from_secs(60);
+
+/// Bound on retained samples, whatever the request rate
+const MAX_SAMPLES: usize = 4096;
+
+/// Fewer requests than this in the window never count as latency pressure
+const MIN_SAMPLES: usize = 20;
+
+/// Load-shedding thresholds for the scheduled refresh
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backpressure {
+    pub max_p95: Duration,
+    pub max_load: f64, // 1-minute load average per core
+    pub max_defer: Duration,
+}
+
+impl Default for Backpressure {
+    fn default() -> Self {
+        Self {
+            max_p95: Duration::from_millis(1000),
+            max_load: 0.9,
+            max_defer: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Current load against the thresholds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pressure {
+    Clear,
+    Latency(Duration),
+    Cpu(f64),
+}
+
+impl std::fmt::Display for Pressure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pressure::Clear => write!(f, "no load pressure"),
+            Pressure::Latency(p95) => write!(f, "request p95 {} ms", p95.as_millis()),
+            Pressure::Cpu(load) => write!(f, "load average {:.2} per core", load),
+        }
+    }
+}
+
+/// What the refresh loop does next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Run,
+    Defer,
+    RunChunked,
+}
+
+impl Backpressure {
+    pub fn classify(&self, p95: Option<Duration>, load: Option<f64>) -> Pressure {
+        match (p95, load) {
+            (Some(p95), _) if p95 > self.max_p95 => Pressure::Latency(p95),
+            (_, Some(load)) if load > self.max_load => Pressure::Cpu(load),
+            _ => Pressure::Clear,
+        }
+    }
+
+    /// Next step for a refresh already deferred for `deferred`
+    pub fn step(&self, pressure: Pressure, deferred: Duration, period: Duration) -> Step {
+        if pressure == Pressure::Clear {
+            Step::Run
+        } else if deferred + DEFER_STEP <= self.max_defer.min(period) {
+            Step::Defer
+        } else {
+            Step::RunChunked
+        }
+    }
+}
+
+/// Rolling window of request latencies
+#[derive(Debug, Default)]
+pub struct LoadMonitor {
+    samples: Mutex<VecDeque<(Instant, Duration)>>,
+}
+
+impl LoadMonitor {
+    pub fn record(&self, latency: Duration) {
+        self.record_at(Instant::now(), latency);
+    }
+
+    fn record_at(&self, at: Instant, latency: Duration) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        if samples.len() == MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back((at, latency));
+    }
+
+    /// p95 latency over the window ending at `now`
+    fn p95_at(&self, now: Instant) -> Option<Duration> {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        while samples.front().is_some_and(|(at, _)| now.saturating_duration_since(*at) > LATENCY_WINDOW) {
+            samples.pop_front();
+        }
+        if samples.len() < MIN_SAMPLES {
+            return None;
+        }
+        let mut latencies: Vec<Duration> = samples.iter().map(|(_, l)| *l).collect();
+        latencies.sort();
+        Some(latencies[((latencies.len() - 1) as f64 * 0.95).round() as usize])
+    }
+
+    pub fn pressure(&self, thresholds: &Backpressure) -> Pressure {
+        thresholds.classify(self.p95_at(Instant::now()), load_per_core())
+    }
+}
+
+/// 1-minute load average divided by the core count, where available
+fn load_per_core() -> Option<f64> {
+    let raw = std::fs::read_to_string("/proc/loadavg").ok()?;
+    let load: f64 = raw.split_whitespace().next()?.parse().ok()?;
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    Some(load / cores as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_p95_over_window() {
+        let monitor = LoadMonitor::default();
+        let start = Instant::now();
+        for ms in 1..=100 {
+            monitor.record_at(start, Duration::from_millis(ms));
+        }
+        assert_eq!(monitor.p95_at(start), Some(Duration::from_millis(95)));
+
+        // Old samples age out; too few recent ones are no signal
+        let later = start + LATENCY_WINDOW + Duration::from_secs(1);
+        monitor.record_at(later, Duration::from_secs(5));
+        assert_eq!(monitor.p95_at(later), None);
+    }
+
+    #[test]
+    fn test_defers_then_chunks() {
+        let thresholds = Backpressure { max_defer: Duration::from_secs(180), ..Backpressure::default() };
+        let hour = Duration::from_secs(3600);
+        let slow = thresholds.classify(Some(Duration::from_secs(2)), Some(0.1));
+        assert_eq!(slow, Pressure::Latency(Duration::from_secs(2)));
+        assert_eq!(thresholds.classify(Some(Duration::from_millis(20)), Some(1.5)), Pressure::Cpu(1.5));
+        assert_eq!(thresholds.classify(None, None), Pressure::Clear);
+
+        assert_eq!(thresholds.step(Pressure::Clear, Duration::ZERO, hour), Step::Run);
+        assert_eq!(thresholds.step(slow, Duration::ZERO, hour), Step::Defer);
+        assert_eq!(thresholds.step(slow, Duration::from_secs(120), hour), Step::Defer);
+        assert_eq!(thresholds.step(slow, Duration::from_secs(180), hour), Step::RunChunked);
+        // Never deferred into the next period
+        assert_eq!(thresholds.step(slow, Duration::ZERO, Duration::from_secs(30)), Step::RunChunked);
+    }
+}
@@ -0,0 +1,426 @@
+//! Backtest Analytics
+//!
+//! Evaluates the computed NIV history against a recession chronology (NBER
+//! dates unless another label set is chosen; see labels.rs).
+//!
+//! Lead time: for each recession, the number of months between the first month
+//! inside the lookback window where recession probability was at or above the
+//! threshold, and the recession start. Recessions with no signal in the window
+//! count as misses.
+//!
+//! Episode: a run of consecutive months at or above the threshold, with
+//! whether it began inside a recession and the recession start (if any)
+//! within the lookback window from its first month.
+//!
+//! False alarm: an episode that starts outside a recession and is not
+//! followed by a recession start within the lookback window.
+//!
+//! Warm-up months (before the smoothing window fills) are never scored:
+//! callers evaluate `evaluated(results)`.
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+use crate::niv::NIVResult;
+
+/// Default lookback window before each recession start
+pub const DEFAULT_LOOKBACK_MONTHS: u32 = 24;
+/// Longest lookback the lead-time endpoints accept
+pub const MAX_LOOKBACK_MONTHS: u32 = 60;
+
+/// Width of each lead-time histogram bin
+pub const HISTOGRAM_BIN_MONTHS: u32 = 3;
+
+/// Lead time for a single recession
+#[derive(Debug, Clone, Serialize)]
+pub struct RecessionLeadTime {
+    pub recession_start: NaiveDate,
+    pub first_signal: Option<NaiveDate>,
+    pub lead_months: Option<u32>, // None = missed
+}
+
+/// Histogram bin: [from_months, to_months]
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBin {
+    pub from_months: u32,
+    pub to_months: u32,
+    pub count: usize,
+}
+
+/// Distribution of lead times across all evaluable recessions
+#[derive(Debug, Clone, Serialize)]
+pub struct LeadTimeDistribution {
+    pub lookback_months: u32,
+    pub recessions_evaluated: usize,
+    pub detected: usize,
+    pub missed: usize,
+    pub mean_lead_months: Option<f64>,
+    pub median_lead_months: Option<f64>,
+    pub min_lead_months: Option<u32>,
+    pub max_lead_months: Option<u32>,
+    pub histogram: Vec<HistogramBin>,
+    pub recessions: Vec<RecessionLeadTime>,
+}
+
+/// `results` past their leading warm-up months
+pub fn evaluated(results: &[NIVResult]) -> &[NIVResult] {
+    &results[results.iter().take_while(|r| r.warmup).count()..]
+}
+
+/// Whole months from `from` to `to` (to >= from)
+pub fn months_between(from: NaiveDate, to: NaiveDate) -> i32 {
+    (to.year() - from.year()) * 12 + to.month() as i32 - from.month() as i32
+}
+
+/// Lead time before a single recession start
+pub fn lead_time(results: &[NIVResult], start: NaiveDate, threshold: f64, lookback_months: u32) -> RecessionLeadTime {
+    let first_signal = results
+        .iter()
+        .filter(|r| {
+            let before = months_between(r.date, start);
+            (0..=lookback_months as i32).contains(&before)
+        })
+        .find(|r| r.recession_probability >= threshold)
+        .map(|r| r.date);
+
+    RecessionLeadTime {
+        recession_start: start,
+        first_signal,
+        lead_months: first_signal.map(|d| months_between(d, start) as u32),
+    }
+}
+
+/// Lead-time distribution for every recession in `chronology` whose lookback window is covered by `results`
+pub fn lead_time_distribution_for(
+    results: &[NIVResult],
+    chronology: &[(NaiveDate, NaiveDate)],
+    threshold: f64,
+    lookback_months: u32,
+) -> LeadTimeDistribution {
+    let first_date = results.first().map(|r| r.date);
+    let last_date = results.last().map(|r| r.date);
+
+    let mut recessions: Vec<RecessionLeadTime> = chronology
+        .iter()
+        .filter(|(start, _)| match (first_date, last_date) {
+            (Some(first), Some(last)) => {
+                months_between(first, *start) >= lookback_months as i32 && *start <= last
+            }
+            _ => false,
+        })
+        .map(|(start, _)| lead_time(results, *start, threshold, lookback_months))
+        .collect();
+    recessions.sort_by_key(|r| r.recession_start);
+
+    let mut leads: Vec<u32> = recessions.iter().filter_map(|r| r.lead_months).collect();
+    leads.sort_unstable();
+
+    let mean = (!leads.is_empty())
+        .then(|| leads.iter().sum::<u32>() as f64 / leads.len() as f64);
+    let median = (!leads.is_empty()).then(|| {
+        let n = leads.len();
+        if n % 2 == 1 {
+            leads[n / 2] as f64
+        } else {
+            (leads[n / 2 - 1] + leads[n / 2]) as f64 / 2.0
+        }
+    });
+
+    let histogram = (0..=lookback_months)
+        .step_by(HISTOGRAM_BIN_MONTHS as usize)
+        .map(|from| {
+            let to = (from + HISTOGRAM_BIN_MONTHS - 1).min(lookback_months);
+            HistogramBin {
+                from_months: from,
+                to_months: to,
+                count: leads.iter().filter(|&&l| l >= from && l <= to).count(),
+            }
+        })
+        .collect();
+
+    LeadTimeDistribution {
+        lookback_months,
+        recessions_evaluated: recessions.len(),
+        detected: leads.len(),
+        missed: recessions.len() - leads.len(),
+        mean_lead_months: mean,
+        median_lead_months: median,
+        min_lead_months: leads.first().copied(),
+        max_lead_months: leads.last().copied(),
+        histogram,
+        recessions,
+    }
+}
+
+/// Months within `lead_months` before a recession start or inside a recession
+pub fn recession_labels(dates: &[NaiveDate], chronology: &[(NaiveDate, NaiveDate)], lead_months: u32) -> Vec<bool> {
+    dates
+        .iter()
+        .map(|&d| {
+            chronology.iter().any(|&(start, end)| {
+                let before = months_between(d, start);
+                (0..=lead_months as i32).contains(&before) || (d >= start && d <= end)
+            })
+        })
+        .collect()
+}
+
+/// Mean squared error of probabilities against binary outcomes
+pub fn brier(probs: &[f64], labels: &[bool]) -> Option<f64> {
+    if probs.is_empty() || probs.len() != labels.len() {
+        return None;
+    }
+    let sum: f64 = probs
+        .iter()
+        .zip(labels)
+        .map(|(p, &l)| (p - if l { 1.0 } else { 0.0 }).powi(2))
+        .sum();
+    Some(sum / probs.len() as f64)
+}
+
+/// A run of months at or above a threshold
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Episode {
+    pub start: NaiveDate,
+    pub end: NaiveDate, // Last month at or above the threshold
+    pub months: usize,
+    pub peak_probability: f64,
+    pub peak_date: NaiveDate,
+    pub started_in_recession: bool,
+    pub recession_followed: Option<NaiveDate>, // Recession start within the lookback window from `start`
+    pub window_complete: bool, // false while the lookback window extends past the data
+}
+
+impl Episode {
+    pub fn is_false_alarm(&self) -> bool {
+        !self.started_in_recession && self.recession_followed.is_none()
+    }
+}
+
+/// Every episode at or above `threshold`, in date order
+pub fn alert_episodes(
+    results: &[NIVResult],
+    chronology: &[(NaiveDate, NaiveDate)],
+    threshold: f64,
+    lookback_months: u32,
+) -> Vec<Episode> {
+    let in_recession = |d: NaiveDate| chronology.iter().any(|&(s, e)| d >= s && d <= e);
+    let followed_by = |d: NaiveDate| {
+        chronology
+            .iter()
+            .map(|&(s, _)| s)
+            .filter(|&s| (0..=lookback_months as i32).contains(&months_between(d, s)))
+            .min()
+    };
+    let last = results.last().map(|r| r.date);
+
+    let mut episodes: Vec<Episode> = Vec::new();
+    let mut current: Option<Episode> = None;
+    for r in results {
+        match (r.recession_probability >= threshold, current.as_mut()) {
+            (true, None) => {
+                current = Some(Episode {
+                    start: r.date,
+                    end: r.date,
+                    months: 1,
+                    peak_probability: r.recession_probability,
+                    peak_date: r.date,
+                    started_in_recession: in_recession(r.date),
+                    recession_followed: followed_by(r.date),
+                    window_complete: last.is_some_and(|l| months_between(r.date, l) >= lookback_months as i32),
+                });
+            }
+            (true, Some(episode)) => {
+                episode.end = r.date;
+                episode.months += 1;
+                if r.recession_probability > episode.peak_probability {
+                    episode.peak_probability = r.recession_probability;
+                    episode.peak_date = r.date;
+                }
+            }
+            (false, _) => episodes.extend(current.take()),
+        }
+    }
+    episodes.extend(current);
+    episodes
+}
+
+/// One false-alarm episode
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FalseAlarm {
+    pub start: NaiveDate,
+    pub end: NaiveDate, // Last month at or above the threshold
+    pub months: usize,
+    pub peak_probability: f64,
+    pub peak_date: NaiveDate,
+    pub window_complete: bool, // false while the lookback window extends past the data
+}
+
+/// Signal episodes at `threshold` with no recession start within `lookback_months`
+pub fn false_alarm_episodes(
+    results: &[NIVResult],
+    chronology: &[(NaiveDate, NaiveDate)],
+    threshold: f64,
+    lookback_months: u32,
+) -> Vec<FalseAlarm> {
+    alert_episodes(results, chronology, threshold, lookback_months)
+        .into_iter()
+        .filter(Episode::is_false_alarm)
+        .map(|e| FalseAlarm {
+            start: e.start,
+            end: e.end,
+            months: e.months,
+            peak_probability: e.peak_probability,
+            peak_date: e.peak_date,
+            window_complete: e.window_complete,
+        })
+        .collect()
+}
+
+/// Number of false-alarm episodes (see `false_alarm_episodes`)
+pub fn false_alarms(
+    results: &[NIVResult],
+    chronology: &[(NaiveDate, NaiveDate)],
+    threshold: f64,
+    lookback_months: u32,
+) -> usize {
+    false_alarm_episodes(results, chronology, threshold, lookback_months).len()
+}
+
+/// Area under the ROC curve (Mann-Whitney U with tie correction)
+/// Returns None unless both classes are present
+pub fn auc(scores: &[f64], labels: &[bool]) -> Option<f64> {
+    let mut pairs: Vec<(f64, bool)> = scores.iter().copied().zip(labels.iter().copied()).collect();
+    let positives = pairs.iter().filter(|(_, l)| *l).count();
+    let negatives = pairs.len() - positives;
+    if positives == 0 || negatives == 0 {
+        return None;
+    }
+
+    pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Average ranks over ties
+    let mut rank_sum_pos = 0.0;
+    let mut i = 0;
+    while i < pairs.len() {
+        let mut j = i;
+        while j + 1 < pairs.len() && pairs[j + 1].0 == pairs[i].0 {
+            j += 1;
+        }
+        let avg_rank = (i + j) as f64 / 2.0 + 1.0;
+        rank_sum_pos += pairs[i..=j].iter().filter(|(_, l)| *l).count() as f64 * avg_rank;
+        i = j + 1;
+    }
+
+    let u = rank_sum_pos - (positives * (positives + 1)) as f64 / 2.0;
+    Some(u / (positives * negatives) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::niv::{AlertLevel, NIVComponents, RecessionPeriods};
+    use chrono::Months;
+
+    fn result(date: NaiveDate, prob: f64) -> NIVResult {
+        NIVResult {
+            date,
+            niv_score: 0.0,
+            recession_probability: prob,
+            components: NIVComponents {
+                thrust: 0.0,
+                efficiency: 0.0,
+                efficiency_squared: 0.0,
+                slack: 0.0,
+                drag: 0.0,
+                drag_spread: 0.0,
+                drag_real_rate: 0.0,
+                drag_volatility: 0.0,
+                gdp_nowcast: false,
+                custom: Vec::new(),
+            },
+            alert_level: AlertLevel::from_probability(prob),
+            niv_percentile: 0.0,
+            quality: Vec::new(),
+            coverage: Vec::new(),
+            warmup: false,
+        }
+    }
+
+    /// Monthly series from 2000-01 to 2010-12 with probability driven by `f(date)`
+    fn series(f: impl Fn(NaiveDate) -> f64) -> Vec<NIVResult> {
+        let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        (0..132)
+            .map(|m| {
+                let d = start.checked_add_months(Months::new(m)).unwrap();
+                result(d, f(d))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_months_between() {
+        let a = NaiveDate::from_ymd_opt(2007, 6, 1).unwrap();
+        let b = NaiveDate::from_ymd_opt(2007, 12, 1).unwrap();
+        assert_eq!(months_between(a, b), 6);
+        assert_eq!(months_between(b, a), -6);
+    }
+
+    #[test]
+    fn test_lead_time_detects_first_signal() {
+        // Signal on from 2007-06 onwards -> 6 months before the Dec 2007 start
+        let signal_from = NaiveDate::from_ymd_opt(2007, 6, 1).unwrap();
+        let results = series(|d| if d >= signal_from { 0.8 } else { 0.1 });
+        let gfc = NaiveDate::from_ymd_opt(2007, 12, 1).unwrap();
+
+        let lt = lead_time(&results, gfc, 0.5, 24);
+        assert_eq!(lt.lead_months, Some(6));
+        assert_eq!(lt.first_signal, Some(signal_from));
+    }
+
+    #[test]
+    fn test_distribution_counts_misses() {
+        let results = series(|_| 0.1);
+        let dist = lead_time_distribution_for(&results, &RecessionPeriods::known_recessions(), 0.5, 12);
+
+        // 2001 and 2007 recessions fall inside the covered range
+        assert_eq!(dist.recessions_evaluated, 2);
+        assert_eq!(dist.detected, 0);
+        assert_eq!(dist.missed, 2);
+        assert!(dist.mean_lead_months.is_none());
+        assert_eq!(dist.histogram.iter().map(|b| b.count).sum::<usize>(), 0);
+    }
+
+    #[test]
+    fn test_false_alarms_and_brier() {
+        // One episode ahead of the 2001 recession, one false alarm in 2005
+        let results = series(|d| {
+            let y = d.year();
+            if y == 2000 || y == 2005 { 0.8 } else { 0.1 }
+        });
+        let chronology = RecessionPeriods::known_recessions();
+        assert_eq!(false_alarms(&results, &chronology, 0.5, 24), 1);
+        let ledger = false_alarm_episodes(&results, &chronology, 0.5, 24);
+        assert_eq!((ledger[0].start.year(), ledger[0].months), (2005, 12));
+        assert_eq!(ledger[0].peak_probability, 0.8);
+        assert!(ledger[0].window_complete);
+
+        let episodes = alert_episodes(&results, &chronology, 0.5, 24);
+        assert_eq!(episodes.len(), 2);
+        assert_eq!(episodes[0].recession_followed, Some(NaiveDate::from_ymd_opt(2001, 3, 1).unwrap()));
+        assert!(!episodes[0].started_in_recession && !episodes[0].is_false_alarm());
+        assert!(episodes[1].is_false_alarm());
+
+        assert_eq!(brier(&[1.0, 0.0], &[true, false]), Some(0.0));
+        assert_eq!(brier(&[0.5, 0.5], &[true, false]), Some(0.25));
+        assert_eq!(brier(&[], &[]), None);
+    }
+
+    #[test]
+    fn test_auc() {
+        assert_eq!(auc(&[0.1, 0.2, 0.8, 0.9], &[false, false, true, true]), Some(1.0));
+        assert_eq!(auc(&[0.9, 0.8, 0.2, 0.1], &[false, false, true, true]), Some(0.0));
+        assert_eq!(auc(&[0.5, 0.5], &[false, true]), Some(0.5));
+        assert_eq!(auc(&[0.1, 0.2], &[false, false]), None);
+    }
+}
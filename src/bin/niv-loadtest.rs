@@ -0,0 +1,277 @@
+//! NIV Load Test
+//!
+//! Replays a weighted traffic mix against a running server and reports
+//! latency percentiles per operation (build with `--features loadtest`):
+//!
+//! ```text
+//! niv-loadtest --url http://localhost:8080 --duration 30 --concurrency 16 \
+//!     --mix latest=40,history=25,components=10,compare=10,simulate=10,montecarlo=5
+//! ```
+//!
+//! Each worker loops until the deadline, picking the next operation by weight.
+//! Monte Carlo requests omit the seed so stored runs are not reused. Any
+//! non-2xx status or transport error counts as an error. Error latencies are
+//! excluded from the percentiles but listed per status.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use serde_json::json;
+
+const USAGE: &str = "usage: niv-loadtest [--url URL] [--duration SECS] [--concurrency N] [--mix op=weight,...]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Op {
+    Latest,
+    History,
+    Components,
+    Compare,
+    Simulate,
+    MonteCarlo,
+}
+
+impl Op {
+    const ALL: [Op; 6] = [Op::Latest, Op::History, Op::Components, Op::Compare, Op::Simulate, Op::MonteCarlo];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Op::Latest => "latest",
+            Op::History => "history",
+            Op::Components => "components",
+            Op::Compare => "compare",
+            Op::Simulate => "simulate",
+            Op::MonteCarlo => "montecarlo",
+        }
+    }
+
+    fn request(&self, client: &reqwest::Client, base: &str, rng: &mut impl Rng) -> reqwest::RequestBuilder {
+        match self {
+            Op::Latest => client.get(format!("{}/api/v1/latest", base)),
+            Op::History => client.get(format!("{}/api/v1/history?start=2000-01-01", base)),
+            Op::Components => client.get(format!("{}/api/v1/components", base)),
+            Op::Compare => client.get(format!("{}/api/v1/compare", base)),
+            // Perturbed parameters, like a user dragging a slider
+            Op::Simulate => client.post(format!("{}/api/v1/simulate", base)).json(&json!({
+                "eta": rng.gen_range(1.2..1.8),
+                "start": "2000-01-01",
+            })),
+            Op::MonteCarlo => client.post(format!("{}/api/v1/montecarlo", base)).json(&json!({
+                "horizon_months": 12,
+                "draws": 200,
+            })),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Config {
+    url: String,
+    duration: Duration,
+    concurrency: usize,
+    mix: Vec<(Op, u32)>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:8080".to_string(),
+            duration: Duration::from_secs(30),
+            concurrency: 16,
+            mix: parse_mix("latest=40,history=25,components=10,compare=10,simulate=10,montecarlo=5").expect("default mix"),
+        }
+    }
+}
+
+fn parse_mix(raw: &str) -> Result<Vec<(Op, u32)>, String> {
+    let mix = raw
+        .split(',')
+        .map(|entry| {
+            let (name, weight) = entry.split_once('=').ok_or_else(|| format!("mix entry '{}' must be op=weight", entry))?;
+            let op = Op::ALL
+                .into_iter()
+                .find(|op| op.name() == name.trim())
+                .ok_or_else(|| format!("unknown op '{}' (expected one of: {})", name, Op::ALL.map(|o| o.name()).join(", ")))?;
+            let weight = weight.trim().parse::<u32>().map_err(|_| format!("weight for '{}' must be a whole number", name))?;
+            Ok((op, weight))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if mix.iter().all(|(_, w)| *w == 0) {
+        return Err("mix needs at least one positive weight".to_string());
+    }
+    Ok(mix)
+}
+
+fn parse_args(args: &[String]) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut rest = args.iter();
+    while let Some(flag) = rest.next() {
+        let mut value = || rest.next().ok_or_else(|| format!("{} needs a value\n{}", flag, USAGE));
+        match flag.as_str() {
+            "--url" => config.url = value()?.trim_end_matches('/').to_string(),
+            "--duration" => {
+                let secs = value()?.parse::<u64>().ok().filter(|s| *s > 0).ok_or("--duration must be a positive number of seconds")?;
+                config.duration = Duration::from_secs(secs);
+            }
+            "--concurrency" => {
+                config.concurrency = value()?.parse::<usize>().ok().filter(|n| *n > 0).ok_or("--concurrency must be positive")?;
+            }
+            "--mix" => config.mix = parse_mix(value()?)?,
+            other => return Err(format!("unknown argument '{}'\n{}", other, USAGE)),
+        }
+    }
+    Ok(config)
+}
+
+/// Nearest-rank percentile of a sorted slice
+fn percentile(sorted: &[Duration], q: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    sorted[(((sorted.len() - 1) as f64) * q).round() as usize]
+}
+
+#[derive(Debug, Default)]
+struct Samples {
+    latencies: Vec<Duration>,        // Successful requests
+    errors: BTreeMap<String, usize>,     // Status code or "transport"
+}
+
+async fn worker(client: reqwest::Client, config: Config, deadline: Instant) -> BTreeMap<Op, Samples> {
+    let mut rng = rand::rngs::StdRng::from_entropy();
+    let total: u32 = config.mix.iter().map(|(_, w)| w).sum();
+    let mut samples: BTreeMap<Op, Samples> = BTreeMap::new();
+    while Instant::now() < deadline {
+        let mut pick = rng.gen_range(0..total);
+        let op = config
+            .mix
+            .iter()
+            .find(|(_, w)| {
+                let hit = pick < *w;
+                pick = pick.saturating_sub(*w);
+                hit
+            })
+            .map(|(op, _)| *op)
+            .expect("pick below total weight");
+
+        let started = Instant::now();
+        let outcome = op.request(&client, &config.url, &mut rng).send().await;
+        let entry = samples.entry(op).or_default();
+        match outcome {
+            Ok(response) if response.status().is_success() => {
+                let _ = response.bytes().await; // Include the body transfer
+                entry.latencies.push(started.elapsed());
+            }
+            Ok(response) => *entry.errors.entry(response.status().as_u16().to_string()).or_default() += 1,
+            Err(_) => *entry.errors.entry("transport".to_string()).or_default() += 1,
+        }
+    }
+    samples
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = parse_args(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .expect("HTTP client");
+
+    // Fail fast rather than measuring connection errors for the whole run
+    match client.get(format!("{}/health/ready", config.url)).send().await {
+        Ok(r) if r.status().is_success() => {}
+        Ok(r) => {
+            eprintln!("{} is not ready ({})", config.url, r.status());
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{} is unreachable: {}", config.url, e);
+            std::process::exit(1);
+        }
+    }
+
+    println!(
+        "Load test: {} workers for {}s against {}",
+        config.concurrency,
+        config.duration.as_secs(),
+        config.url
+    );
+    let started = Instant::now();
+    let deadline = started + config.duration;
+    let workers: Vec<_> = (0..config.concurrency)
+        .map(|_| tokio::spawn(worker(client.clone(), config.clone(), deadline)))
+        .collect();
+
+    let mut merged: BTreeMap<Op, Samples> = BTreeMap::new();
+    for handle in workers {
+        for (op, samples) in handle.await.expect("worker panicked") {
+            let entry = merged.entry(op).or_default();
+            entry.latencies.extend(samples.latencies);
+            for (status, n) in samples.errors {
+                *entry.errors.entry(status).or_default() += n;
+            }
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let ms = |d: Duration| format!("{:.1}", d.as_secs_f64() * 1000.0);
+    println!(
+        "\n{:<12} {:>8} {:>8} {:>9} {:>9} {:>9} {:>9}  errors",
+        "op", "ok", "req/s", "p50 ms", "p90 ms", "p99 ms", "max ms"
+    );
+    let (mut ok, mut failed) = (0, 0);
+    for (op, samples) in &mut merged {
+        samples.latencies.sort();
+        let errors: usize = samples.errors.values().sum();
+        ok += samples.latencies.len();
+        failed += errors;
+        let detail = samples.errors.iter().map(|(s, n)| format!("{}×{}", n, s)).collect::<Vec<_>>().join(" ");
+        println!(
+            "{:<12} {:>8} {:>8.1} {:>9} {:>9} {:>9} {:>9}  {}",
+            op.name(),
+            samples.latencies.len(),
+            samples.latencies.len() as f64 / elapsed,
+            ms(percentile(&samples.latencies, 0.50)),
+            ms(percentile(&samples.latencies, 0.90)),
+            ms(percentile(&samples.latencies, 0.99)),
+            ms(samples.latencies.last().copied().unwrap_or_default()),
+            if errors == 0 { "-".to_string() } else { detail },
+        );
+    }
+    println!("\n{} ok, {} errors, {:.1} req/s over {:.1}s", ok, failed, ok as f64 / elapsed, elapsed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(&[]), Ok(Config::default()));
+        let config = parse_args(&args(&["--url", "http://x:9/", "--duration", "5", "--mix", "latest=3,simulate=1"])).unwrap();
+        assert_eq!(config.url, "http://x:9");
+        assert_eq!(config.duration, Duration::from_secs(5));
+        assert_eq!(config.mix, vec![(Op::Latest, 3), (Op::Simulate, 1)]);
+
+        assert!(parse_args(&args(&["--concurrency", "0"])).is_err());
+        assert!(parse_args(&args(&["--mix", "latest=0"])).is_err());
+        assert!(parse_args(&args(&["--mix", "export=1"])).is_err());
+        assert!(parse_args(&args(&["--duration"])).is_err());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let sorted: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&sorted, 0.5), Duration::from_millis(51));
+        assert_eq!(percentile(&sorted, 0.99), Duration::from_millis(99));
+        assert_eq!(percentile(&[], 0.9), Duration::ZERO);
+    }
+}
@@ -0,0 +1,178 @@
+//! Parameter Bounds
+//!
+//! `/api/v1/simulate/bounds` lists every tunable request parameter so a UI
+//! can generate its sliders: the range the API accepts, the validated
+//! default, the serving engine's value where it has one, and a slider range.
+//! The ranges are the constants request validation checks against, so the
+//! two cannot drift.
+//!
+//! Weights are only required to be finite. Their slider spans 0 to
+//! WEIGHT_SLIDER_MAX, widened to cover the default and serving values;
+//! negative weights are accepted but flip a term's sign.
+
+use serde::Serialize;
+
+use crate::backtest::{DEFAULT_LOOKBACK_MONTHS, MAX_LOOKBACK_MONTHS};
+use crate::niv::{
+    AlertLevel, ComponentWeights, NIVEngine, ThrustScaling, EPSILON, ETA, MAX_EXPANSION_AGE_WEIGHT,
+    MAX_ROLLING_STD_WINDOW, MAX_SMOOTH_WINDOW, MIN_ROLLING_STD_WINDOW, SMOOTH_WINDOW, THRUST_SCALE,
+};
+use crate::requests::{DEFAULT_MC_DRAWS, DEFAULT_MC_HORIZON, MAX_EPSILON, MAX_ETA, MAX_MC_DRAWS, MAX_MC_HORIZON};
+use crate::research::ROLLING_THRUST_WINDOW;
+
+/// Upper end of the weight sliders
+pub const WEIGHT_SLIDER_MAX: f64 = 2.0;
+
+/// Upper end of the fixed thrust divisor slider
+const DIVISOR_SLIDER_MAX: f64 = 50.0;
+
+/// One tunable parameter
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Bound {
+    pub name: String, // Request field, dotted for nested ones (e.g. "weights.drag_spread")
+    pub endpoint: &'static str,
+    pub min: Option<f64>, // None when unbounded
+    pub max: Option<f64>,
+    pub min_exclusive: bool,
+    pub integer: bool,
+    pub default: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serving: Option<f64>, // The serving engine's value, for engine parameters
+    pub slider: [f64; 2],
+}
+
+fn bounded(name: &str, endpoint: &'static str, (min, max): (f64, f64), default: f64) -> Bound {
+    Bound {
+        name: name.to_string(),
+        endpoint,
+        min: Some(min),
+        max: Some(max),
+        min_exclusive: false,
+        integer: false,
+        default,
+        serving: None,
+        slider: [min, max],
+    }
+}
+
+fn count(name: &str, endpoint: &'static str, max: usize, default: usize) -> Bound {
+    Bound { integer: true, ..bounded(name, endpoint, (1.0, max as f64), default as f64) }
+}
+
+fn weight(name: &str, default: f64, serving: f64) -> Bound {
+    Bound {
+        name: format!("weights.{}", name),
+        endpoint: "simulate",
+        min: None,
+        max: None,
+        min_exclusive: false,
+        integer: false,
+        default,
+        serving: Some(serving),
+        slider: [default.min(serving).min(0.0), default.max(serving).max(WEIGHT_SLIDER_MAX)],
+    }
+}
+
+fn weights(w: ComponentWeights) -> [(&'static str, f64); 7] {
+    [
+        ("thrust_dg", w.thrust_dg),
+        ("thrust_da", w.thrust_da),
+        ("thrust_dr", w.thrust_dr),
+        ("thrust_m2_accel", w.thrust_m2_accel),
+        ("drag_spread", w.drag_spread),
+        ("drag_real_rate", w.drag_real_rate),
+        ("drag_volatility", w.drag_volatility),
+    ]
+}
+
+/// Every tunable parameter, with `serving` supplying the engine's current values
+pub fn bounds(serving: &NIVEngine) -> Vec<Bound> {
+    let (divisor, window) = match serving.thrust_scaling() {
+        ThrustScaling::Fixed { divisor } => (Some(divisor), None),
+        ThrustScaling::RollingStd { window } => (None, Some(window as f64)),
+    };
+    let mut bounds = vec![
+        Bound {
+            min_exclusive: true,
+            serving: Some(serving.eta()),
+            ..bounded("eta", "simulate", (0.0, MAX_ETA), ETA)
+        },
+        Bound { serving: Some(serving.epsilon()), ..bounded("epsilon", "simulate", (0.0, MAX_EPSILON), EPSILON) },
+        Bound {
+            serving: Some(serving.expansion_age_weight()),
+            ..bounded("expansion_age_weight", "simulate", (-MAX_EXPANSION_AGE_WEIGHT, MAX_EXPANSION_AGE_WEIGHT), 0.0)
+        },
+        Bound {
+            min_exclusive: true,
+            max: None,
+            serving: divisor,
+            slider: [0.0, DIVISOR_SLIDER_MAX],
+            ..bounded("thrust_scaling.divisor", "simulate", (0.0, f64::INFINITY), THRUST_SCALE)
+        },
+        Bound {
+            integer: true,
+            serving: window,
+            ..bounded(
+                "thrust_scaling.window",
+                "simulate",
+                (MIN_ROLLING_STD_WINDOW as f64, MAX_ROLLING_STD_WINDOW as f64),
+                ROLLING_THRUST_WINDOW as f64,
+            )
+        },
+    ];
+    let defaults = weights(ComponentWeights::default());
+    for ((name, default), (_, current)) in defaults.into_iter().zip(weights(serving.weights())) {
+        bounds.push(weight(name, default, current));
+    }
+    bounds.extend([
+        count("smooth", "history", MAX_SMOOTH_WINDOW, SMOOTH_WINDOW),
+        bounded("threshold", "lead-times", (0.0, 100.0), AlertLevel::Warning.threshold() * 100.0),
+        count("lookback", "lead-times", MAX_LOOKBACK_MONTHS as usize, DEFAULT_LOOKBACK_MONTHS as usize),
+        count("draws", "montecarlo", MAX_MC_DRAWS, DEFAULT_MC_DRAWS),
+        count("horizon_months", "montecarlo", MAX_MC_HORIZON, DEFAULT_MC_HORIZON),
+    ]);
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::{EngineSpec, RequestError};
+
+    fn build(json: serde_json::Value) -> Result<NIVEngine, RequestError> {
+        serde_json::from_value::<EngineSpec>(json).unwrap().build(&NIVEngine::new())
+    }
+
+    #[test]
+    fn test_bounds_match_validation() {
+        let bounds = bounds(&NIVEngine::new());
+        let find = |name: &str| bounds.iter().find(|b| b.name == name).unwrap().clone();
+
+        let eta = find("eta");
+        assert!(build(serde_json::json!({ "eta": eta.max })).is_ok());
+        assert!(build(serde_json::json!({ "eta": eta.max.unwrap() + 0.01 })).is_err());
+        assert!(build(serde_json::json!({ "eta": eta.min })).is_err()); // Exclusive
+        let age = find("expansion_age_weight");
+        assert!(build(serde_json::json!({ "expansion_age_weight": age.min })).is_ok());
+        assert!(build(serde_json::json!({ "expansion_age_weight": age.max.unwrap() + 0.1 })).is_err());
+        let window = find("thrust_scaling.window");
+        let rolling = |w: f64| serde_json::json!({ "thrust_scaling": { "mode": "rolling_std", "window": w as usize } });
+        assert!(build(rolling(window.max.unwrap())).is_ok());
+        assert!(build(rolling(window.min.unwrap() - 1.0)).is_err());
+    }
+
+    #[test]
+    fn test_sliders_cover_default_and_serving() {
+        let custom = ComponentWeights { drag_spread: 3.5, thrust_dr: -0.5, ..ComponentWeights::default() };
+        let bounds = bounds(&NIVEngine::new().with_weights(custom));
+
+        assert_eq!(bounds.iter().filter(|b| b.name.starts_with("weights.")).count(), 7);
+        for b in &bounds {
+            let value = b.serving.unwrap_or(b.default);
+            assert!(b.slider[0] <= b.default && b.default <= b.slider[1], "{}", b.name);
+            assert!(b.slider[0] <= value && value <= b.slider[1], "{}", b.name);
+        }
+        let spread = bounds.iter().find(|b| b.name == "weights.drag_spread").unwrap();
+        assert_eq!((spread.serving, spread.slider), (Some(3.5), [0.0, 3.5]));
+    }
+}
@@ -0,0 +1,352 @@
+//! Compute Budget and Cancellation
+//!
+//! Expensive endpoints (Monte Carlo, synthetic benchmark, variant leaderboard)
+//! run on the blocking pool. To keep one caller from monopolising a shared
+//! deployment:
+//! - Each request's cost is estimated in engine-months (months of data pushed
+//!   through `calculate_series`) and rejected up front if it exceeds the
+//!   budget (`NIV_COMPUTE_BUDGET`)
+//! - The blocking task polls a `CancelToken` between units of work; the
+//!   handler holds a `CancelGuard` that trips the token when it is dropped,
+//!   which happens when the client disconnects
+//! - Elapsed compute time is measured and reported per request
+//!
+//! Jobs run in one of two lanes with separate worker limits. Batch jobs
+//! (backtests, benchmarks, Monte Carlo) are capped below the core count, so
+//! interactive requests (history re-smoothing) always find a free worker even
+//! when the batch lane is saturated.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+/// Default per-request budget in engine-months; admits every request the
+/// endpoint-level limits allow (e.g. 5000 draws × 72 months)
+pub const DEFAULT_COMPUTE_BUDGET: u64 = 500_000;
+
+/// Per-request compute budget
+#[derive(Debug, Clone, Copy)]
+pub struct ComputeBudget {
+    pub max_units: u64,
+}
+
+impl Default for ComputeBudget {
+    fn default() -> Self {
+        Self { max_units: DEFAULT_COMPUTE_BUDGET }
+    }
+}
+
+/// Estimated cost above the budget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetExceeded {
+    pub units: u64,
+    pub max_units: u64,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "request needs ~{} engine-months of compute; the budget is {}",
+            self.units, self.max_units
+        )
+    }
+}
+
+impl ComputeBudget {
+    /// Reject work estimated above the budget before it starts
+    pub fn check(&self, units: u64) -> Result<u64, BudgetExceeded> {
+        if units > self.max_units {
+            Err(BudgetExceeded { units, max_units: self.max_units })
+        } else {
+            Ok(units)
+        }
+    }
+}
+
+/// Cost estimates in engine-months
+pub mod cost {
+    use crate::montecarlo::CONTEXT_MONTHS;
+
+    pub fn monte_carlo(draws: usize, horizon_months: usize) -> u64 {
+        // Scenario path plus one projection per draw
+        (draws as u64 + 1) * (CONTEXT_MONTHS + horizon_months) as u64
+    }
+
+    pub fn synthetic_benchmark(economies: usize, months: usize) -> u64 {
+        economies as u64 * months as u64
+    }
+
+    pub fn leaderboard(variants: usize, months: usize) -> u64 {
+        variants as u64 * months as u64
+    }
+}
+
+/// Work stopped because the caller went away
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cancelled;
+
+/// Cooperative cancellation flag polled by blocking work
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// `Err(Cancelled)` once the token has been tripped
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Trips its token when dropped (e.g. the handler future is dropped on disconnect)
+pub struct CancelGuard(CancelToken);
+
+impl CancelGuard {
+    pub fn new() -> Self {
+        Self(CancelToken::default())
+    }
+
+    pub fn token(&self) -> CancelToken {
+        self.0.clone()
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Blocking job failure
+#[derive(Debug)]
+pub enum JobError {
+    Cancelled,
+    Failed(String),
+}
+
+impl std::fmt::Display for JobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobError::Cancelled => write!(f, "cancelled"),
+            JobError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Result of a metered blocking job
+pub struct Metered<T> {
+    pub value: T,
+    pub elapsed: Duration,
+    pub queued: Duration, // Time spent waiting for a worker in its lane
+}
+
+/// Run cancellable work on the blocking pool, measuring its compute time
+/// Dropping the returned future cancels the work at its next checkpoint
+pub async fn run_blocking<T, F>(work: F) -> Result<Metered<T>, JobError>
+where
+    T: Send + 'static,
+    F: FnOnce(&CancelToken) -> Result<T, Cancelled> + Send + 'static,
+{
+    let guard = CancelGuard::new();
+    let token = guard.token();
+
+    let outcome = tokio::task::spawn_blocking(move || {
+        let started = Instant::now();
+        work(&token).map(|value| Metered { value, elapsed: started.elapsed(), queued: Duration::ZERO })
+    })
+    .await;
+
+    drop(guard);
+    match outcome {
+        Ok(Ok(metered)) => Ok(metered),
+        Ok(Err(Cancelled)) => Err(JobError::Cancelled),
+        Err(e) => Err(JobError::Failed(e.to_string())),
+    }
+}
+
+/// Scheduling lane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lane {
+    Interactive,
+    Batch,
+}
+
+/// Worker limits per lane
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LaneConfig {
+    pub interactive_workers: usize,
+    pub batch_workers: usize,
+}
+
+impl Default for LaneConfig {
+    /// Batch gets every core but one; interactive may use them all
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        Self {
+            interactive_workers: cores,
+            batch_workers: cores.saturating_sub(1).max(1),
+        }
+    }
+}
+
+/// Occupancy of one lane
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LaneStats {
+    pub lane: Lane,
+    pub workers: usize,
+    pub busy: usize,
+}
+
+/// Two-lane scheduler over the blocking pool
+pub struct Scheduler {
+    config: LaneConfig,
+    interactive: Semaphore,
+    batch: Semaphore,
+}
+
+impl Scheduler {
+    pub fn new(config: LaneConfig) -> Self {
+        let config = LaneConfig {
+            interactive_workers: config.interactive_workers.max(1),
+            batch_workers: config.batch_workers.max(1),
+        };
+        Self {
+            config,
+            interactive: Semaphore::new(config.interactive_workers),
+            batch: Semaphore::new(config.batch_workers),
+        }
+    }
+
+    fn lane(&self, lane: Lane) -> (&Semaphore, usize) {
+        match lane {
+            Lane::Interactive => (&self.interactive, self.config.interactive_workers),
+            Lane::Batch => (&self.batch, self.config.batch_workers),
+        }
+    }
+
+    pub fn stats(&self) -> Vec<LaneStats> {
+        [Lane::Interactive, Lane::Batch]
+            .into_iter()
+            .map(|lane| {
+                let (semaphore, workers) = self.lane(lane);
+                LaneStats { lane, workers, busy: workers - semaphore.available_permits() }
+            })
+            .collect()
+    }
+
+    /// Wait for a worker in `lane`, then run the job as `run_blocking` does
+    pub async fn run<T, F>(&self, lane: Lane, work: F) -> Result<Metered<T>, JobError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&CancelToken) -> Result<T, Cancelled> + Send + 'static,
+    {
+        let waiting = Instant::now();
+        let _permit = self
+            .lane(lane)
+            .0
+            .acquire()
+            .await
+            .map_err(|e| JobError::Failed(e.to_string()))?;
+        let queued = waiting.elapsed();
+        run_blocking(work).await.map(|m| Metered { queued, ..m })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_check() {
+        let budget = ComputeBudget { max_units: 1000 };
+        assert_eq!(budget.check(1000), Ok(1000));
+        assert_eq!(budget.check(1001), Err(BudgetExceeded { units: 1001, max_units: 1000 }));
+    }
+
+    #[test]
+    fn test_guard_cancels_on_drop() {
+        let guard = CancelGuard::new();
+        let token = guard.token();
+        assert_eq!(token.check(), Ok(()));
+        drop(guard);
+        assert_eq!(token.check(), Err(Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_job_stops_at_checkpoint() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let job = run_blocking(move |token| {
+            let mut iterations = 0u64;
+            while token.check().is_ok() {
+                iterations += 1;
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            tx.send(iterations).ok();
+            Err::<(), _>(Cancelled)
+        });
+
+        // Simulate a client disconnect: drop the handler future mid-flight
+        let _ = tokio::time::timeout(Duration::from_millis(20), job).await;
+        let iterations = tokio::task::spawn_blocking(move || rx.recv_timeout(Duration::from_secs(5)))
+            .await
+            .unwrap()
+            .expect("job should observe cancellation");
+        assert!(iterations > 0);
+    }
+
+    #[tokio::test]
+    async fn test_interactive_lane_runs_while_batch_is_saturated() {
+        let scheduler = Arc::new(Scheduler::new(LaneConfig { interactive_workers: 1, batch_workers: 1 }));
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+
+        // Occupy the only batch worker until released
+        let batch = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move {
+                scheduler
+                    .run(Lane::Batch, move |_| {
+                        release_rx.recv().ok();
+                        Ok(())
+                    })
+                    .await
+            })
+        };
+        while scheduler.stats()[1].busy == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // A second batch job queues; an interactive job does not
+        let queued_batch = tokio::time::timeout(
+            Duration::from_millis(50),
+            scheduler.run(Lane::Batch, |_| Ok(())),
+        )
+        .await;
+        assert!(queued_batch.is_err());
+        let interactive = tokio::time::timeout(
+            Duration::from_secs(5),
+            scheduler.run(Lane::Interactive, |_| Ok(42)),
+        )
+        .await
+        .expect("interactive lane should not wait on batch work");
+        assert_eq!(interactive.unwrap().value, 42);
+
+        release_tx.send(()).unwrap();
+        assert!(batch.await.unwrap().is_ok());
+        assert!(scheduler.stats().iter().all(|s| s.busy == 0));
+    }
+}
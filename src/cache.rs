@@ -0,0 +1,99 @@
+//! Cache Policies
+//!
+//! Every kind of cached item has a TTL policy, looked up by the item part of
+//! its key (see namespace.rs):
+//!
+//! | Item             | Kept |
+//! |------------------|------|
+//! | `series`         | until the dataset is replaced |
+//! | `series:{benchmark,tracking}` | until the dataset is replaced |
+//! | `smooth:{window}`| until unread for 1 hour |
+//! | anything else    | until unread for 1 hour |
+//!
+//! Every item is computed from its key's namespace alone, and a namespace's
+//! dataset never changes, so an entry cannot go stale: recomputing it would
+//! return the same result. Entries are therefore never revalidated, and
+//! expiry only bounds memory. An idle entry is evicted once nobody has read
+//! it for its period, which each read restarts, so an entry in use is never
+//! dropped from under its readers and only a first or idle request computes.
+
+use std::time::{Duration, Instant};
+
+use crate::namespace::CacheKey;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// How long an item is kept
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TtlPolicy {
+    /// Held until invalidated (a dataset swap)
+    Pinned,
+    /// Evicted once unread for this long
+    Idle(Duration),
+}
+
+/// Policy per item prefix, first match wins
+pub const POLICIES: [(&str, TtlPolicy); 2] = [("series", TtlPolicy::Pinned), ("smooth:", TtlPolicy::Idle(HOUR))];
+
+/// Policy for items no prefix matches
+pub const DEFAULT_POLICY: TtlPolicy = TtlPolicy::Idle(HOUR);
+
+pub fn policy(item: &str) -> TtlPolicy {
+    POLICIES
+        .iter()
+        .find(|(prefix, _)| item.starts_with(prefix))
+        .map_or(DEFAULT_POLICY, |(_, policy)| *policy)
+}
+
+impl TtlPolicy {
+    /// Time from the last write or read to eviction, None when pinned
+    pub fn lifetime(&self) -> Option<Duration> {
+        match self {
+            TtlPolicy::Pinned => None,
+            TtlPolicy::Idle(idle) => Some(*idle),
+        }
+    }
+}
+
+/// Per-entry expiry for moka from the item's policy; a write or read restarts it
+pub struct PolicyExpiry;
+
+impl<V> moka::Expiry<CacheKey, V> for PolicyExpiry {
+    fn expire_after_create(&self, key: &CacheKey, _value: &V, _created_at: Instant) -> Option<Duration> {
+        policy(&key.1).lifetime()
+    }
+
+    fn expire_after_update(&self, key: &CacheKey, _value: &V, _updated_at: Instant, _remaining: Option<Duration>) -> Option<Duration> {
+        policy(&key.1).lifetime()
+    }
+
+    fn expire_after_read(&self, key: &CacheKey, _value: &V, _read_at: Instant, _remaining: Option<Duration>, _last_modified_at: Instant) -> Option<Duration> {
+        policy(&key.1).lifetime()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::namespace::Namespace;
+
+    #[test]
+    fn test_policies_by_item() {
+        assert_eq!(policy("series"), TtlPolicy::Pinned);
+        assert_eq!(policy("series:tracking").lifetime(), None);
+        assert_eq!(policy("smooth:12").lifetime(), Some(HOUR));
+        assert_eq!(policy("other"), DEFAULT_POLICY);
+    }
+
+    #[test]
+    fn test_reads_restart_idle_expiry() {
+        use moka::Expiry;
+
+        let now = Instant::now();
+        let smooth = Namespace::new("NIV-v6", "r1").key("smooth:12");
+        let series = Namespace::new("NIV-v6", "r1").key("series");
+        // A read with no time left grants another full period rather than evicting
+        assert_eq!(PolicyExpiry.expire_after_read(&smooth, &(), now, Some(Duration::ZERO), now), Some(HOUR));
+        assert_eq!(PolicyExpiry.expire_after_read(&series, &(), now, None, now), None);
+    }
+}
@@ -0,0 +1,219 @@
+//! iCalendar Feed
+//!
+//! Builds `/calendar.ics` (RFC 5545) so analysts get reminders for:
+//! - scheduled dataset refreshes (NIV_REFRESH_SECS), as timed events
+//! - FRED release dates for the input series, as all-day events
+//! - projected alert-threshold crossings: for each alert level above the
+//!   current one, the first term-structure horizon whose calibrated
+//!   probability reaches the level's threshold (see calibration.rs)
+//!
+//! Events are regenerated on every request; UIDs are derived from their
+//! content so calendar clients update rather than duplicate them.
+
+use chrono::{DateTime, Months, NaiveDate, Utc};
+
+use crate::calibration::TermStructurePoint;
+use crate::fred::ReleaseDate;
+use crate::niv::{AlertLevel, NIVResult};
+
+/// How far ahead refreshes and releases are listed
+pub const LOOKAHEAD_DAYS: i64 = 90;
+
+/// Cap on listed refreshes (short intervals would flood the calendar)
+const MAX_REFRESH_EVENTS: usize = 30;
+
+/// Longest content line before folding (octets, excluding CRLF)
+const FOLD_AT: usize = 75;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventTime {
+    Day(NaiveDate),
+    At(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub uid: String,
+    pub start: EventTime,
+    pub summary: String,
+    pub description: String,
+}
+
+/// Upcoming ticks of a refresh loop started at `started` with `period`
+pub fn refresh_events(started: DateTime<Utc>, period: chrono::Duration, now: DateTime<Utc>) -> Vec<Event> {
+    if period <= chrono::Duration::zero() {
+        return Vec::new();
+    }
+    let until = now + chrono::Duration::days(LOOKAHEAD_DAYS);
+    let elapsed = (now - started).num_seconds().max(0) / period.num_seconds().max(1);
+    (elapsed + 1..)
+        .map(|k| started + period * k as i32)
+        .take_while(|at| *at <= until)
+        .take(MAX_REFRESH_EVENTS)
+        .map(|at| Event {
+            uid: format!("refresh-{}@niv-engine", at.timestamp()),
+            start: EventTime::At(at),
+            summary: "NIV dataset refresh".to_string(),
+            description: "Scheduled recomputation of the NIV dataset (NIV_REFRESH_SECS)".to_string(),
+        })
+        .collect()
+}
+
+/// One all-day event per FRED release date within the lookahead
+pub fn release_events(releases: &[ReleaseDate], today: NaiveDate) -> Vec<Event> {
+    let until = today + chrono::Duration::days(LOOKAHEAD_DAYS);
+    releases
+        .iter()
+        .filter(|r| r.date >= today && r.date <= until)
+        .map(|r| Event {
+            uid: format!("release-{}-{}@niv-engine", r.date, slug(&r.release)),
+            start: EventTime::Day(r.date),
+            summary: format!("FRED release: {}", r.release),
+            description: format!("Updates NIV inputs: {}", r.series.join(", ")),
+        })
+        .collect()
+}
+
+/// Projected first crossing of each alert threshold above the latest level
+pub fn crossing_events(point: &TermStructurePoint, latest: &NIVResult) -> Vec<Event> {
+    [AlertLevel::Elevated, AlertLevel::Warning, AlertLevel::Critical]
+        .into_iter()
+        .filter(|level| latest.recession_probability < level.threshold())
+        .filter_map(|level| {
+            let hit = point.curve.iter().find(|h| h.probability >= level.threshold())?;
+            let date = point.date.checked_add_months(Months::new(hit.horizon_months))?;
+            Some(Event {
+                uid: format!("crossing-{}-{:?}@niv-engine", point.date, level).to_lowercase(),
+                start: EventTime::Day(date),
+                summary: format!("NIV projected: {} threshold ({:.0}%)", level.label(), level.threshold() * 100.0),
+                description: format!(
+                    "As of {}, the calibrated probability of a recession starting within {} months is {:.1}%, \
+                     at or above the {} threshold.",
+                    point.date,
+                    hit.horizon_months,
+                    hit.probability * 100.0,
+                    level.label()
+                ),
+            })
+        })
+        .collect()
+}
+
+fn slug(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+/// Escape TEXT values (RFC 5545 §3.3.11)
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line at FOLD_AT octets without splitting a character
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > FOLD_AT {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Serialize events as a VCALENDAR
+pub fn render(events: &[Event], stamp: DateTime<Utc>) -> String {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//NIV Engine//Calendar//EN",
+        "CALSCALE:GREGORIAN",
+        "X-WR-CALNAME:NIV releases and alerts",
+    ] {
+        fold(line, &mut out);
+    }
+    for event in events {
+        let start = match &event.start {
+            EventTime::Day(d) => format!("DTSTART;VALUE=DATE:{}", d.format("%Y%m%d")),
+            EventTime::At(at) => format!("DTSTART:{}", at.format("%Y%m%dT%H%M%SZ")),
+        };
+        fold("BEGIN:VEVENT", &mut out);
+        fold(&format!("UID:{}", event.uid), &mut out);
+        fold(&format!("DTSTAMP:{}", stamp), &mut out);
+        fold(&start, &mut out);
+        fold(&format!("SUMMARY:{}", escape(&event.summary)), &mut out);
+        fold(&format!("DESCRIPTION:{}", escape(&event.description)), &mut out);
+        fold("END:VEVENT", &mut out);
+    }
+    fold("END:VCALENDAR", &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calibration::HorizonProbability;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_events_from_schedule_releases_and_projection() {
+        let started = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let now = started + chrono::Duration::hours(30);
+        let refreshes = refresh_events(started, chrono::Duration::days(1), now);
+        assert_eq!(refreshes.len(), 30);
+        assert_eq!(refreshes[0].start, EventTime::At(started + chrono::Duration::days(2)));
+
+        let today = now.date_naive();
+        let releases = [
+            ReleaseDate { date: today - chrono::Duration::days(1), release: "CPI".into(), series: vec!["CPIAUCSL"] },
+            ReleaseDate { date: today + chrono::Duration::days(10), release: "G.17".into(), series: vec!["INDPRO", "TCU"] },
+        ];
+        let listed = release_events(&releases, today);
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].description.contains("INDPRO, TCU"));
+
+        let mut latest = NIVEngine::new().calculate_series(&mock::generate_mock_data(2020, 2024)).pop().unwrap();
+        latest.recession_probability = 0.2;
+        let point = TermStructurePoint {
+            date: NaiveDate::from_ymd_opt(2024, 12, 1).unwrap(),
+            niv_score: 50.0,
+            curve: vec![
+                HorizonProbability { horizon_months: 3, probability: 0.30 },
+                HorizonProbability { horizon_months: 12, probability: 0.55 },
+                HorizonProbability { horizon_months: 24, probability: 0.60 },
+            ],
+        };
+        let crossings = crossing_events(&point, &latest);
+        // Elevated by 3 months, Warning by 12, Critical never
+        assert_eq!(crossings.len(), 2);
+        assert_eq!(crossings[1].start, EventTime::Day(NaiveDate::from_ymd_opt(2025, 12, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_render_escapes_and_folds() {
+        let event = Event {
+            uid: "x@niv-engine".into(),
+            start: EventTime::Day(NaiveDate::from_ymd_opt(2026, 3, 4).unwrap()),
+            summary: "a, b; c".into(),
+            description: "é".repeat(60),
+        };
+        let ics = render(&[event], Utc::now());
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("SUMMARY:a\\, b\\; c\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20260304\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= FOLD_AT));
+        assert!(ics.contains("\r\n é"));
+    }
+}
@@ -0,0 +1,319 @@
+//! Horizon-Specific Calibration
+//!
+//! The engine's sigmoid maps NIV to a single "recession risk" number with no
+//! stated horizon. This module fits one logistic model per horizon h:
+//!
+//!   P(recession starts within h months | NIV_t) = 1 / (1 + exp(-(a_h + b_h × NIV_t)))
+//!
+//! against the NBER chronology, and combines them into a term structure
+//! (3, 6, 12, 24 months) in the style of the NY Fed yield-curve model.
+//!
+//! Fitting notes:
+//! - Months inside a recession are excluded (the recession has already started)
+//! - Months whose full horizon runs past the end of the data are excluded
+//!   (their outcome is not yet known)
+//! - Calibrations are in-sample over the whole history
+//! - Probabilities are made non-decreasing across horizons, since a recession
+//!   starting within 6 months also starts within 12
+//!
+//! `decompose` sets a headline probability against the unconditional base
+//! rate for a horizon: the share of the same eligible months followed by a
+//! recession start within h months. The remainder is what the signal adds.
+//!
+//! `fit_economy` calibrates the served mapping per economy: each economy has
+//! its own chronology, base rate and NIV scale, so its logistic is fitted on
+//! its own history rather than borrowed from the US sigmoid. Model cards
+//! report it next to the mapping the engine serves. Only the US has a
+//! chronology today; other economies get one here as their datasets land.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::backtest::{self, months_between};
+use crate::niv::{NIVResult, RecessionPeriods};
+
+/// Horizons reported on the term structure (months)
+pub const TERM_STRUCTURE_HORIZONS: [u32; 4] = [3, 6, 12, 24];
+
+/// Default and longest horizon for the base-rate decomposition (months)
+pub const DEFAULT_DECOMPOSITION_HORIZON: u32 = 12;
+pub const MAX_DECOMPOSITION_HORIZON: u32 = 24;
+
+/// Newton-Raphson iterations for the logistic fit
+const MAX_ITERATIONS: usize = 50;
+
+/// Ridge penalty keeping the fit finite under perfect separation
+const RIDGE: f64 = 1e-4;
+
+/// Horizon of the per-economy calibration on model cards (months)
+pub const ECONOMY_HORIZON: u32 = 12;
+
+/// Fitted logistic model for one horizon
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HorizonCalibration {
+    pub horizon_months: u32,
+    pub intercept: f64,
+    pub slope: f64,      // Per NIV point; negative = higher NIV, lower risk
+    pub samples: usize,
+    pub positives: usize,
+}
+
+impl HorizonCalibration {
+    /// P(recession starts within the horizon) for a NIV score
+    pub fn probability(&self, niv_score: f64) -> f64 {
+        sigmoid(self.intercept + self.slope * niv_score)
+    }
+}
+
+/// Probability for one horizon
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HorizonProbability {
+    pub horizon_months: u32,
+    pub probability: f64,
+}
+
+/// Term structure at one date
+#[derive(Debug, Clone, Serialize)]
+pub struct TermStructurePoint {
+    pub date: NaiveDate,
+    pub niv_score: f64,
+    pub curve: Vec<HorizonProbability>,
+}
+
+/// Headline probability split into base rate and signal
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ProbabilityDecomposition {
+    pub horizon_months: u32,
+    pub base_rate: f64, // Unconditional P(start within the horizon)
+    pub signal: f64,    // Headline minus base rate
+    pub samples: usize,
+}
+
+/// An economy's fitted mapping against the one the engine serves, over the
+/// months with an observable outcome
+#[derive(Debug, Clone, Serialize)]
+pub struct EconomyCalibration {
+    pub economy: String,
+    pub base_rate: f64,
+    pub fitted: HorizonCalibration,
+    pub brier_served: Option<f64>, // Served probabilities on the same months
+    pub brier_fitted: Option<f64>,
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// Whether a recession starts within (0, horizon] months after `date`
+/// None when `date` is inside a recession or the outcome is not yet observable
+fn starts_within(
+    date: NaiveDate,
+    last_date: NaiveDate,
+    chronology: &[(NaiveDate, NaiveDate)],
+    horizon: u32,
+) -> Option<bool> {
+    if chronology.iter().any(|&(s, e)| date >= s && date <= e) {
+        return None;
+    }
+    let hit = chronology
+        .iter()
+        .any(|&(s, _)| (1..=horizon as i32).contains(&months_between(date, s)));
+    if hit {
+        Some(true)
+    } else if months_between(date, last_date) >= horizon as i32 {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Fit P(start within `horizon`) on the results' NIV scores
+pub fn fit(results: &[NIVResult], chronology: &[(NaiveDate, NaiveDate)], horizon: u32) -> HorizonCalibration {
+    let last_date = results.last().map(|r| r.date).unwrap_or_default();
+    let samples: Vec<(f64, f64)> = results
+        .iter()
+        .filter_map(|r| {
+            starts_within(r.date, last_date, chronology, horizon)
+                .map(|y| (r.niv_score, if y { 1.0 } else { 0.0 }))
+        })
+        .collect();
+    let positives = samples.iter().filter(|(_, y)| *y > 0.5).count();
+
+    // Newton-Raphson on (intercept, slope)
+    let (mut a, mut b) = (0.0_f64, 0.0_f64);
+    for _ in 0..MAX_ITERATIONS {
+        let (mut ga, mut gb) = (-RIDGE * a, -RIDGE * b);
+        let (mut haa, mut hab, mut hbb) = (RIDGE, 0.0, RIDGE);
+        for &(x, y) in &samples {
+            let p = sigmoid(a + b * x);
+            let w = p * (1.0 - p);
+            ga += y - p;
+            gb += (y - p) * x;
+            haa += w;
+            hab += w * x;
+            hbb += w * x * x;
+        }
+        let det = haa * hbb - hab * hab;
+        if det.abs() < 1e-12 {
+            break;
+        }
+        let da = (hbb * ga - hab * gb) / det;
+        let db = (haa * gb - hab * ga) / det;
+        a += da;
+        b += db;
+        if da.abs() < 1e-9 && db.abs() < 1e-9 {
+            break;
+        }
+    }
+
+    HorizonCalibration {
+        horizon_months: horizon,
+        intercept: a,
+        slope: b,
+        samples: samples.len(),
+        positives,
+    }
+}
+
+/// `probability` relative to the historical base rate for `horizon`; None
+/// when no month in `results` has an observable outcome
+pub fn decompose(
+    results: &[NIVResult],
+    chronology: &[(NaiveDate, NaiveDate)],
+    horizon: u32,
+    probability: f64,
+) -> Option<ProbabilityDecomposition> {
+    let last_date = results.last()?.date;
+    let outcomes: Vec<bool> = results
+        .iter()
+        .filter_map(|r| starts_within(r.date, last_date, chronology, horizon))
+        .collect();
+    if outcomes.is_empty() {
+        return None;
+    }
+    let base_rate = outcomes.iter().filter(|y| **y).count() as f64 / outcomes.len() as f64;
+    Some(ProbabilityDecomposition {
+        horizon_months: horizon,
+        base_rate,
+        signal: probability - base_rate,
+        samples: outcomes.len(),
+    })
+}
+
+/// Recession chronology of an economy; None for economies without one
+pub fn chronology(economy: &str) -> Option<Vec<(NaiveDate, NaiveDate)>> {
+    match economy {
+        "us" => Some(RecessionPeriods::known_recessions()),
+        _ => None,
+    }
+}
+
+/// Fit `economy`'s mapping at ECONOMY_HORIZON on its own history; None
+/// without a chronology or any month with an observable outcome
+pub fn fit_economy(economy: &str, results: &[NIVResult]) -> Option<EconomyCalibration> {
+    let chronology = chronology(economy)?;
+    let last_date = results.last()?.date;
+    let (observed, labels): (Vec<&NIVResult>, Vec<bool>) = results
+        .iter()
+        .filter_map(|r| starts_within(r.date, last_date, &chronology, ECONOMY_HORIZON).map(|y| (r, y)))
+        .unzip();
+    if observed.is_empty() {
+        return None;
+    }
+    let fitted = fit(results, &chronology, ECONOMY_HORIZON);
+    let served: Vec<f64> = observed.iter().map(|r| r.recession_probability).collect();
+    let refit: Vec<f64> = observed.iter().map(|r| fitted.probability(r.niv_score)).collect();
+    Some(EconomyCalibration {
+        economy: economy.to_string(),
+        base_rate: fitted.positives as f64 / fitted.samples as f64,
+        fitted,
+        brier_served: backtest::brier(&served, &labels),
+        brier_fitted: backtest::brier(&refit, &labels),
+    })
+}
+
+/// Calibrations for every term-structure horizon
+pub fn fit_all(results: &[NIVResult], chronology: &[(NaiveDate, NaiveDate)]) -> Vec<HorizonCalibration> {
+    TERM_STRUCTURE_HORIZONS
+        .iter()
+        .map(|&h| fit(results, chronology, h))
+        .collect()
+}
+
+/// Term structure for one result, non-decreasing across horizons
+pub fn term_structure(calibrations: &[HorizonCalibration], result: &NIVResult) -> TermStructurePoint {
+    let mut floor = 0.0_f64;
+    let curve = calibrations
+        .iter()
+        .map(|c| {
+            floor = floor.max(c.probability(result.niv_score));
+            HorizonProbability { horizon_months: c.horizon_months, probability: floor }
+        })
+        .collect();
+
+    TermStructurePoint {
+        date: result.date,
+        niv_score: result.niv_score,
+        curve,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::{NIVEngine, RecessionPeriods};
+
+    #[test]
+    fn test_starts_within_labels() {
+        let chronology = RecessionPeriods::known_recessions();
+        let last = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+        let d = |y, m| NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+
+        // GFC starts Dec 2007
+        assert_eq!(starts_within(d(2007, 9), last, &chronology, 3), Some(true));
+        assert_eq!(starts_within(d(2007, 8), last, &chronology, 3), Some(false));
+        assert_eq!(starts_within(d(2008, 6), last, &chronology, 3), None);
+        // Outcome not yet known at the end of the data
+        assert_eq!(starts_within(d(2024, 6), last, &chronology, 12), None);
+    }
+
+    #[test]
+    fn test_term_structure_is_monotone() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1960, 2024));
+        let calibrations = fit_all(&results, &RecessionPeriods::known_recessions());
+
+        assert_eq!(calibrations.len(), TERM_STRUCTURE_HORIZONS.len());
+        assert!(calibrations.iter().all(|c| c.intercept.is_finite() && c.slope.is_finite()));
+        // Longer horizons have more positive months
+        assert!(calibrations.windows(2).all(|w| w[0].positives <= w[1].positives));
+
+        // The base rate is the fitted sample's positive share
+        let twelve = calibrations.iter().find(|c| c.horizon_months == 12).unwrap();
+        let split = decompose(&results, &RecessionPeriods::known_recessions(), 12, 0.35).unwrap();
+        assert_eq!(split.samples, twelve.samples);
+        assert!((split.base_rate - twelve.positives as f64 / twelve.samples as f64).abs() < 1e-12);
+        assert!((split.base_rate + split.signal - 0.35).abs() < 1e-12);
+        assert!(decompose(&[], &RecessionPeriods::known_recessions(), 12, 0.35).is_none());
+
+        // labels= swaps the chronology: fewer recessions, a lower base rate
+        let raw = r#"[{"start":"2008-01","end":"2009-06"},{"start":"2020-02","end":"2020-04"}]"#;
+        let set = crate::labels::parse("recent", raw, crate::labels::LabelFormat::Json).unwrap();
+        let relabelled = decompose(&results, &set.chronology(), 12, 0.35).unwrap();
+        assert!(relabelled.base_rate < split.base_rate);
+        assert!((relabelled.base_rate + relabelled.signal - 0.35).abs() < 1e-12);
+
+        // The US economy calibration is the 12-month fit on its own chronology
+        let us = fit_economy("us", &results).unwrap();
+        assert_eq!((us.fitted.samples, us.fitted.positives), (twelve.samples, twelve.positives));
+        assert!((us.base_rate - split.base_rate).abs() < 1e-12);
+        assert!(us.brier_served.is_some() && us.brier_fitted.is_some());
+        assert!(fit_economy("gb", &results).is_none());
+
+        for r in results.iter().step_by(25) {
+            let point = term_structure(&calibrations, r);
+            assert!(point.curve.windows(2).all(|w| w[0].probability <= w[1].probability));
+            assert!(point.curve.iter().all(|h| (0.0..=1.0).contains(&h.probability)));
+        }
+    }
+}
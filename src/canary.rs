@@ -0,0 +1,224 @@
+//! Canary Computation
+//!
+//! A registered candidate model runs in shadow mode: on every refresh it is
+//! computed over the same inputs as the serving model and the two histories
+//! are compared (recession-probability deltas, alert-level disagreements).
+//! Promotion is allowed once the candidate has been compared over the
+//! required number of refreshes, and takes effect only once a refresh with
+//! the candidate succeeds; a failed one leaves the serving model and the
+//! candidate in place.
+//!
+//! The candidate's history from its latest shadow run is kept with it, so
+//! its model card reads that rather than recomputing per request.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::niv::{NIVEngine, NIVResult};
+
+/// Refreshes a candidate must shadow before promotion (NIV_CANARY_REFRESHES)
+pub const DEFAULT_CANARY_REFRESHES: usize = 3;
+
+/// Comparisons kept per candidate
+const MAX_COMPARISONS: usize = 50;
+
+/// Disagreement dates listed per comparison (most recent)
+const MAX_DISAGREEMENT_DATES: usize = 12;
+
+/// Longest candidate version; versions end up in headers, filenames and HTML
+pub const MAX_VERSION_LEN: usize = 64;
+
+/// A candidate version is 1-MAX_VERSION_LEN characters of `[A-Za-z0-9._-]`
+pub fn valid_version(version: &str) -> bool {
+    (1..=MAX_VERSION_LEN).contains(&version.len())
+        && version.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+}
+
+/// What produced a comparison
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    Registration, // Baseline at registration; does not count towards promotion
+    Refresh,
+}
+
+/// Candidate vs serving model over one dataset
+#[derive(Debug, Clone, Serialize)]
+pub struct Comparison {
+    pub trigger: Trigger,
+    pub compared_at: DateTime<Utc>,
+    pub months: usize,
+    pub mean_abs_delta: f64,  // Recession probability, 0-1
+    pub max_abs_delta: f64,
+    pub max_delta_date: Option<NaiveDate>,
+    pub latest_delta: f64,    // Candidate minus serving at the latest month
+    pub alert_disagreements: usize,
+    pub disagreement_dates: Vec<NaiveDate>,
+}
+
+/// Compare two histories month by month (months present in both)
+pub fn compare(serving: &[NIVResult], candidate: &[NIVResult], trigger: Trigger) -> Comparison {
+    let mut months = 0;
+    let mut total = 0.0;
+    let mut max_abs_delta = 0.0;
+    let mut max_delta_date = None;
+    let mut latest_delta = 0.0;
+    let mut disagreements = Vec::new();
+
+    let mut candidate_iter = candidate.iter().peekable();
+    for s in serving {
+        while candidate_iter.peek().is_some_and(|c| c.date < s.date) {
+            candidate_iter.next();
+        }
+        let Some(c) = candidate_iter.peek().filter(|c| c.date == s.date) else {
+            continue;
+        };
+
+        let delta = c.recession_probability - s.recession_probability;
+        months += 1;
+        total += delta.abs();
+        if delta.abs() > max_abs_delta {
+            max_abs_delta = delta.abs();
+            max_delta_date = Some(s.date);
+        }
+        latest_delta = delta;
+        if c.alert_level != s.alert_level {
+            disagreements.push(s.date);
+        }
+    }
+
+    let alert_disagreements = disagreements.len();
+    let disagreement_dates = disagreements.split_off(alert_disagreements.saturating_sub(MAX_DISAGREEMENT_DATES));
+    Comparison {
+        trigger,
+        compared_at: Utc::now(),
+        months,
+        mean_abs_delta: if months > 0 { total / months as f64 } else { 0.0 },
+        max_abs_delta,
+        max_delta_date,
+        latest_delta,
+        alert_disagreements,
+        disagreement_dates,
+    }
+}
+
+/// Candidate model in shadow mode
+pub struct Canary {
+    pub version: String,
+    pub engine: Arc<NIVEngine>,
+    pub registered_at: DateTime<Utc>,
+    comparisons: Vec<Comparison>,
+    results: Arc<Vec<NIVResult>>, // Candidate history from the latest shadow run
+}
+
+impl Canary {
+    pub fn new(version: String, engine: NIVEngine) -> Self {
+        Self {
+            version,
+            engine: Arc::new(engine),
+            registered_at: Utc::now(),
+            comparisons: Vec::new(),
+            results: Arc::new(Vec::new()),
+        }
+    }
+
+    /// Record a shadow run: its comparison and the candidate history it compared
+    pub fn record(&mut self, comparison: Comparison, results: Vec<NIVResult>) {
+        self.results = Arc::new(results);
+        self.comparisons.push(comparison);
+        if self.comparisons.len() > MAX_COMPARISONS {
+            self.comparisons.remove(0);
+        }
+    }
+
+    /// Candidate history from the latest shadow run
+    pub fn results(&self) -> Arc<Vec<NIVResult>> {
+        self.results.clone()
+    }
+
+    /// Refreshes shadowed so far
+    pub fn refreshes(&self) -> usize {
+        self.comparisons
+            .iter()
+            .filter(|c| c.trigger == Trigger::Refresh)
+            .count()
+    }
+
+    pub fn status(&self, required_refreshes: usize) -> CanaryStatus {
+        CanaryStatus {
+            version: self.version.clone(),
+            registered_at: self.registered_at,
+            refreshes_observed: self.refreshes(),
+            refreshes_required: required_refreshes,
+            promotable: self.refreshes() >= required_refreshes,
+            comparisons: self.comparisons.clone(),
+        }
+    }
+}
+
+/// Canary state as reported by the admin endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct CanaryStatus {
+    pub version: String,
+    pub registered_at: DateTime<Utc>,
+    pub refreshes_observed: usize,
+    pub refreshes_required: usize,
+    pub promotable: bool,
+    pub comparisons: Vec<Comparison>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    #[test]
+    fn test_compare_identical_and_shifted_models() {
+        let data = mock::generate_mock_data(1990, 2020);
+        let serving = NIVEngine::new().calculate_series(&data);
+
+        let same = compare(&serving, &serving, Trigger::Refresh);
+        assert_eq!(same.months, serving.len());
+        assert_eq!(same.max_abs_delta, 0.0);
+        assert_eq!(same.alert_disagreements, 0);
+
+        let candidate = NIVEngine::with_params(2.5, crate::niv::EPSILON).calculate_series(&data);
+        let shifted = compare(&serving, &candidate, Trigger::Refresh);
+        assert!(shifted.max_abs_delta > 0.0);
+        assert!(shifted.mean_abs_delta <= shifted.max_abs_delta);
+        assert!(shifted.disagreement_dates.len() <= MAX_DISAGREEMENT_DATES);
+        assert!(shifted.disagreement_dates.len() <= shifted.alert_disagreements);
+    }
+
+    #[test]
+    fn test_promotion_counts_refreshes_only() {
+        let data = mock::generate_mock_data(2000, 2010);
+        let results = NIVEngine::new().calculate_series(&data);
+        let mut canary = Canary::new("v7-test".to_string(), NIVEngine::new());
+
+        canary.record(compare(&results, &results, Trigger::Registration), results.clone());
+        assert_eq!(canary.refreshes(), 0);
+        for _ in 0..2 {
+            canary.record(compare(&results, &results, Trigger::Refresh), results.clone());
+        }
+        assert!(!canary.status(3).promotable);
+        canary.record(compare(&results, &results, Trigger::Refresh), results[1..].to_vec());
+        let status = canary.status(3);
+        assert!(status.promotable);
+        assert_eq!(status.comparisons.len(), 4);
+        assert_eq!(canary.results().len(), results.len() - 1);
+    }
+
+    #[test]
+    fn test_valid_version() {
+        assert!(valid_version("v7-test_2.1"));
+        assert!(valid_version(&"a".repeat(MAX_VERSION_LEN)));
+        assert!(!valid_version(""));
+        assert!(!valid_version(&"a".repeat(MAX_VERSION_LEN + 1)));
+        for bad in ["v7 test", "v7\"", "<b>v7</b>", "v7\r\nX-Injected: 1", "v7/../x"] {
+            assert!(!valid_version(bad), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Methodology Changelog
+//!
+//! Structured, append-only record of model/method changes so consumers can
+//! audit drift over time. An entry is written when:
+//! - the server starts with a configuration that differs from the last entry
+//!   (env specs, custom components, a new model version, or a new
+//!   `niv::METHOD_REVISION`)
+//! - a canary model is promoted
+//!
+//! Each entry carries the full parameter set, the parameters that changed
+//! since the previous entry, and backtest metrics with their deltas.
+//! Entries persist as JSON lines in NIV_CHANGELOG_FILE; without it the log
+//! only covers the running process.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::backtest;
+use crate::niv::{AlertLevel, NIVResult, RecessionPeriods};
+use crate::research::LABEL_HORIZON_MONTHS;
+
+/// What caused the entry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeTrigger {
+    Startup,
+    Promotion,
+}
+
+/// One parameter that differs from the previous entry (dotted path)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParameterChange {
+    pub parameter: String,
+    pub from: Option<Value>,
+    pub to: Option<Value>,
+}
+
+/// In-sample backtest metrics against the NBER chronology
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Validation {
+    pub auc: Option<f64>,
+    pub brier: Option<f64>,
+    pub false_alarms: usize, // At the Warning threshold
+    pub mean_recession_probability: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidationDelta {
+    pub metric: String,
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+    pub delta: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    pub model_version: String,
+    pub date: DateTime<Utc>,
+    pub trigger: ChangeTrigger,
+    pub previous_version: Option<String>,
+    pub changes: Vec<ParameterChange>,
+    pub validation: Validation,
+    pub validation_deltas: Vec<ValidationDelta>,
+    pub parameters: Map<String, Value>,
+}
+
+/// Backtest metrics for `results` (warm-up months excluded)
+pub fn validation(results: &[NIVResult]) -> Validation {
+    let results = backtest::evaluated(results);
+    let chronology = RecessionPeriods::known_recessions();
+    let dates: Vec<_> = results.iter().map(|r| r.date).collect();
+    let probs: Vec<f64> = results.iter().map(|r| r.recession_probability).collect();
+    let labels = backtest::recession_labels(&dates, &chronology, LABEL_HORIZON_MONTHS);
+    Validation {
+        auc: backtest::auc(&probs, &labels),
+        brier: backtest::brier(&probs, &labels),
+        false_alarms: backtest::false_alarms(
+            results,
+            &chronology,
+            AlertLevel::Warning.threshold(),
+            LABEL_HORIZON_MONTHS,
+        ),
+        mean_recession_probability: if probs.is_empty() { 0.0 } else { probs.iter().sum::<f64>() / probs.len() as f64 },
+    }
+}
+
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&path, v, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+/// Leaf-level differences between two parameter sets, sorted by path
+pub fn diff(before: &Map<String, Value>, after: &Map<String, Value>) -> Vec<ParameterChange> {
+    let (mut old, mut new) = (Vec::new(), Vec::new());
+    flatten("", &Value::Object(before.clone()), &mut old);
+    flatten("", &Value::Object(after.clone()), &mut new);
+
+    let mut paths: Vec<&String> = old.iter().chain(&new).map(|(p, _)| p).collect();
+    paths.sort();
+    paths.dedup();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let from = old.iter().find(|(p, _)| p == path).map(|(_, v)| v.clone());
+            let to = new.iter().find(|(p, _)| p == path).map(|(_, v)| v.clone());
+            (from != to).then(|| ParameterChange { parameter: path.clone(), from, to })
+        })
+        .collect()
+}
+
+fn deltas(before: &Validation, after: &Validation) -> Vec<ValidationDelta> {
+    let metric = |name: &str, b: Option<f64>, a: Option<f64>| ValidationDelta {
+        metric: name.to_string(),
+        before: b,
+        after: a,
+        delta: b.zip(a).map(|(b, a)| a - b),
+    };
+    vec![
+        metric("auc", before.auc, after.auc),
+        metric("brier", before.brier, after.brier),
+        metric("false_alarms", Some(before.false_alarms as f64), Some(after.false_alarms as f64)),
+        metric(
+            "mean_recession_probability",
+            Some(before.mean_recession_probability),
+            Some(after.mean_recession_probability),
+        ),
+    ]
+}
+
+/// Append-only changelog, optionally backed by a JSON-lines file
+#[derive(Debug, Default)]
+pub struct Changelog {
+    path: Option<PathBuf>,
+    entries: Vec<ChangelogEntry>,
+}
+
+impl Changelog {
+    /// Read existing entries from `path` (a missing file starts empty)
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .enumerate()
+                .map(|(i, l)| serde_json::from_str(l).map_err(|e| format!("{} line {}: {}", path.display(), i + 1, e)))
+                .collect::<Result<Vec<_>, _>>()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        Ok(Self { path: Some(path), entries })
+    }
+
+    pub fn entries(&self) -> &[ChangelogEntry] {
+        &self.entries
+    }
+
+    /// Record the current model unless it matches the last entry; returns
+    /// the new entry, if any
+    pub fn record(
+        &mut self,
+        model_version: &str,
+        trigger: ChangeTrigger,
+        parameters: Map<String, Value>,
+        validation: Validation,
+    ) -> Result<Option<&ChangelogEntry>, String> {
+        let last = self.entries.last();
+        if last.is_some_and(|l| l.model_version == model_version && l.parameters == parameters) {
+            return Ok(None);
+        }
+        let entry = ChangelogEntry {
+            model_version: model_version.to_string(),
+            date: Utc::now(),
+            trigger,
+            previous_version: last.map(|l| l.model_version.clone()),
+            changes: diff(last.map(|l| &l.parameters).unwrap_or(&Map::new()), &parameters),
+            validation_deltas: last.map(|l| deltas(&l.validation, &validation)).unwrap_or_default(),
+            validation,
+            parameters,
+        };
+
+        if let Some(path) = &self.path {
+            let line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut f| writeln!(f, "{}", line))
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        self.entries.push(entry);
+        Ok(self.entries.last())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::{NIVEngine, SlackSpec};
+
+    #[test]
+    fn test_record_diffs_parameters_and_skips_unchanged() {
+        let data = mock::generate_mock_data(1970, 2024);
+        let base = NIVEngine::new();
+        let alt = NIVEngine::new().with_slack_spec(SlackSpec::OutputGap);
+        let mut log = Changelog::default();
+
+        let first = log
+            .record("v1", ChangeTrigger::Startup, base.parameters(), validation(&base.calculate_series(&data)))
+            .unwrap()
+            .unwrap();
+        assert!(first.previous_version.is_none());
+        assert!(first.validation_deltas.is_empty());
+        assert!(first.validation.auc.is_some());
+
+        let again = log.record("v1", ChangeTrigger::Startup, base.parameters(), validation(&base.calculate_series(&data)));
+        assert!(again.unwrap().is_none());
+
+        let second = log
+            .record("v2", ChangeTrigger::Promotion, alt.parameters(), validation(&alt.calculate_series(&data)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.previous_version.as_deref(), Some("v1"));
+        assert_eq!(second.changes.len(), 1);
+        assert_eq!(second.changes[0].parameter, "slack");
+        assert_eq!(second.validation_deltas.len(), 4);
+        assert_eq!(log.entries().len(), 2);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("niv-changelog-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let engine = NIVEngine::new();
+        let results = engine.calculate_series(&mock::generate_mock_data(2000, 2010));
+
+        let mut log = Changelog::load(path.clone()).unwrap();
+        log.record("v1", ChangeTrigger::Startup, engine.parameters(), validation(&results)).unwrap();
+        let reloaded = Changelog::load(path.clone()).unwrap();
+        assert_eq!(reloaded.entries().len(), 1);
+        assert_eq!(reloaded.entries()[0].parameters, engine.parameters());
+
+        std::fs::write(&path, "not json\n").unwrap();
+        assert!(Changelog::load(path.clone()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
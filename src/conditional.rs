@@ -0,0 +1,222 @@
+//! Conditional Outcome Statistics
+//!
+//! Answers "historically, when drag was above X and slack above Y, how often
+//! did a recession start within the next N months?" over the full history.
+//!
+//! Conditions are written `component op value` and joined with commas, e.g.
+//! `drag>0.02,slack>=0.25`. A month counts only if:
+//! - every condition holds
+//! - it is not already inside an NBER recession (one cannot "start")
+//! - the full horizon after it lies within the history
+//!
+//! The same eligibility rules give the unconditional base rate, so the two
+//! rates are directly comparable.
+
+use chrono::{Months, NaiveDate};
+use serde::Serialize;
+
+use crate::niv::{Component, NIVComponents, NIVResult, RecessionPeriods};
+
+/// Default and maximum look-ahead
+pub const DEFAULT_HORIZON_MONTHS: usize = 12;
+pub const MAX_HORIZON_MONTHS: usize = 36;
+
+/// Comparison in a condition
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub enum Op {
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = ">=")]
+    Ge,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = "<=")]
+    Le,
+}
+
+impl Op {
+    fn holds(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Op::Gt => lhs > rhs,
+            Op::Ge => lhs >= rhs,
+            Op::Lt => lhs < rhs,
+            Op::Le => lhs <= rhs,
+        }
+    }
+}
+
+/// One component threshold, e.g. `drag>0.02`
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct Condition {
+    pub component: Component,
+    pub op: Op,
+    pub value: f64,
+}
+
+impl Condition {
+    pub fn holds(&self, c: &NIVComponents) -> bool {
+        self.op.holds(c.value(self.component), self.value)
+    }
+}
+
+impl std::str::FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Two-character operators first so ">=" is not read as ">"
+        let (at, op, len) = [(">=", Op::Ge), ("<=", Op::Le), (">", Op::Gt), ("<", Op::Lt)]
+            .into_iter()
+            .find_map(|(token, op)| s.find(token).map(|at| (at, op, token.len())))
+            .ok_or_else(|| format!("condition '{}' needs one of >, >=, <, <=", s))?;
+
+        let component = match s[..at].trim().to_ascii_lowercase().as_str() {
+            "thrust" => Component::Thrust,
+            "efficiency" => Component::Efficiency,
+            "slack" => Component::Slack,
+            "drag" => Component::Drag,
+            other => {
+                return Err(format!(
+                    "unknown component '{}' (expected thrust, efficiency, slack or drag)",
+                    other
+                ))
+            }
+        };
+        let value: f64 = s[at + len..]
+            .trim()
+            .parse()
+            .ok()
+            .filter(|v: &f64| v.is_finite())
+            .ok_or_else(|| format!("condition '{}' needs a finite number after the operator", s))?;
+
+        Ok(Condition { component, op, value })
+    }
+}
+
+/// Parse a comma-separated condition list
+pub fn parse(conditions: &str) -> Result<Vec<Condition>, String> {
+    conditions
+        .split(',')
+        .filter(|c| !c.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Recession-start frequency among months meeting the conditions
+#[derive(Debug, Clone, Serialize)]
+pub struct ConditionalStats {
+    pub matching_months: usize,
+    pub recession_followed: usize,
+    pub probability: Option<f64>, // None when no month matched
+    pub eligible_months: usize,
+    pub base_rate: Option<f64>,
+    pub recessions_hit: Vec<NaiveDate>, // Distinct recession starts that followed a match
+    pub first_match: Option<NaiveDate>,
+    pub last_match: Option<NaiveDate>,
+}
+
+fn recession_start_within(date: NaiveDate, horizon_end: NaiveDate) -> Option<NaiveDate> {
+    RecessionPeriods::known_recessions()
+        .into_iter()
+        .map(|(s, _)| s)
+        .filter(|s| *s > date && *s <= horizon_end)
+        .min()
+}
+
+/// Evaluate the conditions over `results` (date-sorted)
+///
+/// `regime` restricts both the matches and the base rate to months carrying
+/// that label (labels parallel to `results`, see `regimes::cluster`).
+pub fn evaluate(
+    results: &[NIVResult],
+    conditions: &[Condition],
+    horizon_months: usize,
+    regime: Option<(&[usize], usize)>,
+) -> ConditionalStats {
+    let last = results.last().map(|r| r.date);
+    let (mut eligible, mut eligible_hits, mut matching, mut matching_hits) = (0, 0, 0, 0);
+    let mut recessions_hit: Vec<NaiveDate> = Vec::new();
+    let (mut first_match, mut last_match) = (None, None);
+
+    for (i, r) in results.iter().enumerate() {
+        if regime.is_some_and(|(labels, id)| labels.get(i) != Some(&id)) {
+            continue;
+        }
+        let Some(horizon_end) = r.date.checked_add_months(Months::new(horizon_months as u32)) else {
+            continue;
+        };
+        if last.is_some_and(|last| horizon_end > last) || RecessionPeriods::is_recession(r.date) {
+            continue;
+        }
+        let start = recession_start_within(r.date, horizon_end);
+        eligible += 1;
+        eligible_hits += start.is_some() as usize;
+
+        if conditions.iter().all(|c| c.holds(&r.components)) {
+            matching += 1;
+            first_match.get_or_insert(r.date);
+            last_match = Some(r.date);
+            if let Some(start) = start {
+                matching_hits += 1;
+                if !recessions_hit.contains(&start) {
+                    recessions_hit.push(start);
+                }
+            }
+        }
+    }
+
+    let rate = |hits: usize, n: usize| (n > 0).then(|| hits as f64 / n as f64);
+    ConditionalStats {
+        matching_months: matching,
+        recession_followed: matching_hits,
+        probability: rate(matching_hits, matching),
+        eligible_months: eligible,
+        base_rate: rate(eligible_hits, eligible),
+        recessions_hit,
+        first_match,
+        last_match,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_parse_conditions() {
+        let parsed = parse("drag>0.02, slack>=0.25,Thrust<-0.1").unwrap();
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[0], Condition { component: Component::Drag, op: Op::Gt, value: 0.02 });
+        assert_eq!(parsed[1].op, Op::Ge);
+        assert_eq!(parsed[2].component, Component::Thrust);
+        assert_eq!(parsed[2].value, -0.1);
+
+        assert!(parse("drag=0.02").is_err());
+        assert!(parse("vibes>1").is_err());
+        assert!(parse("drag>x").is_err());
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_against_base_rate() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1970, 2024));
+        let all = evaluate(&results, &[], 12, None);
+        assert_eq!(all.matching_months, all.eligible_months);
+        assert_eq!(all.probability, all.base_rate);
+        assert!(all.recessions_hit.len() >= 5);
+
+        let stressed = evaluate(&results, &parse("drag>0.02").unwrap(), 12, None);
+        assert!(stressed.matching_months < all.matching_months);
+        assert!(stressed.recession_followed <= stressed.matching_months);
+
+        let none = evaluate(&results, &parse("slack>10").unwrap(), 12, None);
+        assert_eq!(none.matching_months, 0);
+        assert_eq!(none.probability, None);
+        assert!(none.base_rate.is_some());
+
+        let labels: Vec<usize> = (0..results.len()).map(|i| i % 2).collect();
+        let even = evaluate(&results, &[], 12, Some((&labels, 0)));
+        assert!(even.eligible_months < all.eligible_months && even.eligible_months > 0);
+    }
+}
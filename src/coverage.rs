@@ -0,0 +1,153 @@
+//! Partial Data
+//!
+//! FRED series are published on different schedules, so the latest months
+//! often have some required series and not others. A series is observed
+//! through its last observation plus its publication period (the gap to
+//! the observation before: a month for monthly series, a quarter for
+//! quarterly ones); months after that are the ragged edge.
+//!
+//! `NIV_PARTIAL_DATA` decides what happens there:
+//! - `off` (default): the dataset ends at the last month every required
+//!   series covers
+//! - `last_value`: the missing series carries its last observed value
+//! - `trend`: the missing series follows a least-squares line through its
+//!   last TREND_MONTHS observed months
+//!
+//! Estimated months list the series in `EconomicData::estimated`, and
+//! results carry them as `coverage` (any month in the smoothing window once
+//! smoothed), so a partial NIV is never mistaken for a complete one.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::backtest::months_between;
+use crate::niv::{EconomicData, InputSeries};
+
+/// Observed months the trend estimate is fitted on
+pub const TREND_MONTHS: usize = 12;
+
+/// How the ragged edge is handled
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PartialDataMode {
+    #[default]
+    Off,
+    LastValue,
+    Trend,
+}
+
+impl std::str::FromStr for PartialDataMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Ok(PartialDataMode::Off),
+            "last_value" => Ok(PartialDataMode::LastValue),
+            "trend" => Ok(PartialDataMode::Trend),
+            other => Err(format!("unknown partial data mode '{}' (expected off | last_value | trend)", other)),
+        }
+    }
+}
+
+/// Last month a date-sorted series covers: its last observation plus the
+/// gap to the one before, less a month
+pub fn observed_through(series: &[(NaiveDate, f64)]) -> Option<NaiveDate> {
+    let (last, _) = *series.last()?;
+    let period = match series {
+        [.., (before, _), _] => months_between(*before, last).max(1),
+        _ => 1,
+    };
+    last.checked_add_months(chrono::Months::new(period as u32 - 1))
+}
+
+/// Apply `mode` to the months of `data` (sorted) after each series'
+/// `observed_through` date; returns the number of months estimated
+pub fn fill(data: &mut Vec<EconomicData>, observed: &[(InputSeries, NaiveDate)], mode: PartialDataMode) -> usize {
+    if mode == PartialDataMode::Off {
+        if let Some(end) = observed.iter().map(|(_, through)| *through).min() {
+            data.retain(|d| d.date <= end);
+        }
+        return 0;
+    }
+    for &(series, through) in observed {
+        let slot = InputSeries::ALL.iter().position(|s| *s == series).expect("every series is in ALL");
+        let history: Vec<f64> = data
+            .iter_mut()
+            .filter(|d| d.date <= through)
+            .map(|d| *d.required_mut()[slot])
+            .collect();
+        let Some(&last) = history.last() else {
+            continue; // Nothing observed to estimate from
+        };
+        let recent = &history[history.len().saturating_sub(TREND_MONTHS)..];
+        let (slope, intercept) = trend(recent);
+        for (ahead, d) in data.iter_mut().filter(|d| d.date > through).enumerate() {
+            *d.required_mut()[slot] = match mode {
+                PartialDataMode::Trend => intercept + slope * (recent.len() + ahead) as f64,
+                _ => last,
+            };
+            d.estimated.push(series);
+        }
+    }
+    for d in data.iter_mut() {
+        d.estimated.sort();
+    }
+    data.iter().filter(|d| !d.estimated.is_empty()).count()
+}
+
+/// Least-squares (slope, intercept) of `values` against 0, 1, 2, ...
+fn trend(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (cov, var) = values.iter().enumerate().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        let dx = x as f64 - mean_x;
+        (cov + dx * (y - mean_y), var + dx * dx)
+    });
+    let slope = if var > 0.0 { cov / var } else { 0.0 };
+    (slope, mean_y - slope * mean_x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    fn date(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    #[test]
+    fn test_observed_through_follows_publication_period() {
+        let monthly = [(date(2026, 4), 1.0), (date(2026, 5), 1.0)];
+        let quarterly = [(date(2026, 1), 1.0), (date(2026, 4), 1.0)];
+        assert_eq!(observed_through(&monthly), Some(date(2026, 5)));
+        assert_eq!(observed_through(&quarterly), Some(date(2026, 6)));
+        assert_eq!(observed_through(&[(date(2026, 1), 1.0)]), Some(date(2026, 1)));
+        assert_eq!(observed_through(&[]), None);
+        assert_eq!("TREND".parse::<PartialDataMode>(), Ok(PartialDataMode::Trend));
+        assert!("carry".parse::<PartialDataMode>().is_err());
+    }
+
+    #[test]
+    fn test_fill_estimates_or_truncates_the_ragged_edge() {
+        let mut data = mock::generate_mock_data(2020, 2021);
+        for (i, d) in data.iter_mut().enumerate() {
+            d.capacity_util = 70.0 + i as f64;
+        }
+        let observed = [(InputSeries::CapacityUtil, date(2021, 9)), (InputSeries::Gdp, date(2021, 12))];
+
+        let mut off = data.clone();
+        assert_eq!(fill(&mut off, &observed, PartialDataMode::Off), 0);
+        assert_eq!(off.last().unwrap().date, date(2021, 9));
+
+        let mut carried = data.clone();
+        assert_eq!(fill(&mut carried, &observed, PartialDataMode::LastValue), 3);
+        assert_eq!((carried[23].capacity_util, &carried[23].estimated[..]), (90.0, &[InputSeries::CapacityUtil][..]));
+        assert!(carried[20].estimated.is_empty());
+
+        let mut trended = data;
+        fill(&mut trended, &observed, PartialDataMode::Trend);
+        assert!((trended[23].capacity_util - 93.0).abs() < 1e-9);
+    }
+}
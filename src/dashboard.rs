@@ -0,0 +1,192 @@
+//! Component Dashboard
+//!
+//! One compact reading per component for the latest month, for the landing
+//! page widget:
+//! - value and z-score against the trailing 10 years (ZSCORE_WINDOW months,
+//!   including the latest)
+//! - 3-month change and its direction (flat when under a tenth of the
+//!   trailing standard deviation)
+//! - a coarse signal from the interpretation bands, which also give the
+//!   text interpretations in `/api/v1/latest`, so the two cannot disagree
+
+use serde::Serialize;
+use statrs::statistics::Statistics;
+
+use crate::niv::{Component, NIVResult};
+
+/// Trailing window for z-scores (10 years)
+pub const ZSCORE_WINDOW: usize = 120;
+
+/// Months over which the trend is measured
+pub const TREND_MONTHS: usize = 3;
+
+/// Changes below this fraction of the trailing std dev count as flat
+const FLAT_THRESHOLD: f64 = 0.1;
+
+/// Direction of the 3-month change
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+    Rising,
+    Flat,
+    Falling,
+}
+
+/// What the component's level means for the outlook
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Signal {
+    Supportive,
+    Neutral,
+    Caution,
+    Stress,
+}
+
+impl Signal {
+    pub fn for_component(component: Component, value: f64) -> Self {
+        band(component, value).signal
+    }
+}
+
+/// One interpretation band: values above `above` (and below the previous
+/// band) read as `signal` and `text`
+struct Band {
+    above: f64,
+    signal: Signal,
+    text: &'static str,
+}
+
+const THRUST_BANDS: [Band; 5] = [
+    Band { above: 0.7, signal: Signal::Supportive, text: "🚀 Strong expansion impulse (M2 + Investment surging)" },
+    Band { above: 0.3, signal: Signal::Supportive, text: "📈 Moderate growth impulse" },
+    Band { above: -0.3, signal: Signal::Neutral, text: "➡️ Neutral monetary/fiscal stance" },
+    Band { above: -0.7, signal: Signal::Caution, text: "📉 Moderate contraction pressure" },
+    Band { above: f64::NEG_INFINITY, signal: Signal::Stress, text: "⚠️ Strong contraction pressure (tightening cycle)" },
+];
+
+const EFFICIENCY_BANDS: [Band; 4] = [
+    Band { above: 0.18, signal: Signal::Supportive, text: "💪 High productive investment (18%+ of GDP)" },
+    Band { above: 0.15, signal: Signal::Neutral, text: "✅ Healthy investment levels" },
+    Band { above: 0.12, signal: Signal::Caution, text: "⚠️ Below-average investment" },
+    Band { above: f64::NEG_INFINITY, signal: Signal::Stress, text: "🚨 Weak investment - hollow growth risk (GFC signal)" },
+];
+
+// Both high slack (recession) and very low slack (overheating) are risks
+const SLACK_BANDS: [Band; 4] = [
+    Band { above: 0.30, signal: Signal::Stress, text: "🔴 High unused capacity (30%+) - recession signal" },
+    Band { above: 0.22, signal: Signal::Caution, text: "🟡 Elevated slack - room to grow" },
+    Band { above: 0.15, signal: Signal::Neutral, text: "🟢 Normal capacity utilization" },
+    Band { above: f64::NEG_INFINITY, signal: Signal::Caution, text: "🔥 Economy running hot - overheating risk" },
+];
+
+const DRAG_BANDS: [Band; 4] = [
+    Band { above: 0.03, signal: Signal::Stress, text: "🚨 CRITICAL: High friction - liquidity stress" },
+    Band { above: 0.02, signal: Signal::Caution, text: "⚠️ Elevated drag - watch closely" },
+    Band { above: 0.01, signal: Signal::Neutral, text: "🟡 Moderate friction levels" },
+    Band { above: f64::NEG_INFINITY, signal: Signal::Supportive, text: "🟢 Low friction - smooth capital flow" },
+];
+
+/// Band `value` falls in; the last band also takes NaN
+fn band(component: Component, value: f64) -> &'static Band {
+    let bands: &'static [Band] = match component {
+        Component::Thrust => &THRUST_BANDS,
+        Component::Efficiency => &EFFICIENCY_BANDS,
+        Component::Slack => &SLACK_BANDS,
+        Component::Drag => &DRAG_BANDS,
+    };
+    bands.iter().find(|b| value > b.above).unwrap_or(&bands[bands.len() - 1])
+}
+
+/// Text interpretation of a component's level, as in `/api/v1/latest`
+pub fn interpretation(component: Component, value: f64) -> &'static str {
+    band(component, value).text
+}
+
+/// Latest-month reading for one component
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentReading {
+    pub component: Component,
+    pub value: f64,
+    pub z_score: Option<f64>, // None without variation in the window
+    pub change_3m: Option<f64>,
+    pub trend: Trend,
+    pub signal: Signal,
+}
+
+/// Readings for every component at the last result
+pub fn readings(results: &[NIVResult]) -> Vec<ComponentReading> {
+    let Some(latest) = results.last() else {
+        return Vec::new();
+    };
+    let window = &results[results.len().saturating_sub(ZSCORE_WINDOW)..];
+
+    Component::all()
+        .into_iter()
+        .map(|component| {
+            let current = latest.components.value(component);
+            let history: Vec<f64> = window.iter().map(|r| r.components.value(component)).collect();
+            let (mean, sd) = (history.iter().mean(), history.iter().std_dev());
+            let spread = (sd.is_finite() && sd > 0.0).then_some(sd);
+
+            let change_3m = results
+                .len()
+                .checked_sub(TREND_MONTHS + 1)
+                .map(|i| current - results[i].components.value(component));
+            let trend = match (change_3m, spread) {
+                (Some(change), Some(sd)) if change.abs() >= FLAT_THRESHOLD * sd => {
+                    if change > 0.0 { Trend::Rising } else { Trend::Falling }
+                }
+                _ => Trend::Flat,
+            };
+
+            ComponentReading {
+                component,
+                value: current,
+                z_score: spread.map(|sd| (current - mean) / sd),
+                change_3m,
+                trend,
+                signal: Signal::for_component(component, current),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_readings_cover_components_with_zscores() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2000, 2024));
+        let latest = readings(&results);
+
+        assert_eq!(latest.len(), 4);
+        assert_eq!(latest[0].component, Component::Thrust);
+        assert_eq!(latest[3].value, results.last().unwrap().components.drag);
+        assert!(latest.iter().all(|r| r.z_score.is_some_and(f64::is_finite)));
+        assert!(latest.iter().all(|r| r.change_3m.is_some()));
+        assert!(readings(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_trend_and_signal() {
+        let mut results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2010, 2020));
+        let n = results.len();
+        for (i, r) in results.iter_mut().enumerate() {
+            r.components.drag = 0.01 + 0.0001 * (i % 12) as f64;
+        }
+        results[n - 1].components.drag = 0.035;
+        let drag = readings(&results).into_iter().find(|r| r.component == Component::Drag).unwrap();
+        assert_eq!(drag.trend, Trend::Rising);
+        assert_eq!(drag.signal, Signal::Stress);
+        assert!(drag.z_score.unwrap() > 3.0);
+
+        assert_eq!(Signal::for_component(Component::Slack, 0.10), Signal::Caution);
+        assert_eq!(Signal::for_component(Component::Thrust, 0.0), Signal::Neutral);
+        assert_eq!(interpretation(Component::Thrust, 0.0), "➡️ Neutral monetary/fiscal stance");
+        assert_eq!(interpretation(Component::Drag, 0.025), "⚠️ Elevated drag - watch closely");
+        assert_eq!(Signal::for_component(Component::Drag, f64::NAN), Signal::Supportive);
+    }
+}
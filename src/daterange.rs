@@ -0,0 +1,179 @@
+//! Date Range Validation
+//!
+//! Shared `start`/`end` handling for endpoints that slice the history.
+//! Dates are compared by month, so "2008-01-15" and "2008-01" both mean
+//! January 2008. Missing bounds default to the available data range.
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+use crate::backtest::months_between;
+
+/// Inclusive range of monthly dates
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    /// Number of months covered, counting both ends
+    pub fn months(&self) -> u32 {
+        (months_between(self.start, self.end) + 1).max(0) as u32
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.start && date <= self.end
+    }
+}
+
+/// Why a requested range was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum RangeError {
+    InvalidDate { field: &'static str, value: String },
+    Inverted { fields: [&'static str; 2], start: NaiveDate, end: NaiveDate },
+    OutOfRange { field: &'static str, value: NaiveDate, available: DateRange },
+    SpanTooLarge { months: u32, max_months: u32 },
+}
+
+impl RangeError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RangeError::InvalidDate { .. } => "INVALID_DATE",
+            RangeError::Inverted { .. } => "INVALID_RANGE",
+            RangeError::OutOfRange { .. } => "OUT_OF_RANGE",
+            RangeError::SpanTooLarge { .. } => "SPAN_TOO_LARGE",
+        }
+    }
+
+    /// Malformed requests vs well-formed requests the data cannot satisfy
+    pub fn is_unprocessable(&self) -> bool {
+        matches!(self, RangeError::OutOfRange { .. } | RangeError::SpanTooLarge { .. })
+    }
+}
+
+impl std::fmt::Display for RangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeError::InvalidDate { field, value } => {
+                write!(f, "{} '{}' must be YYYY-MM or YYYY-MM-DD", field, value)
+            }
+            RangeError::Inverted { fields, start, end } => {
+                write!(f, "{} {} is after {} {}", fields[0], start, fields[1], end)
+            }
+            RangeError::OutOfRange { field, value, available } => write!(
+                f,
+                "{} {} is outside the available data ({} to {})",
+                field, value, available.start, available.end
+            ),
+            RangeError::SpanTooLarge { months, max_months } => {
+                write!(f, "range spans {} months; the maximum is {}", months, max_months)
+            }
+        }
+    }
+}
+
+/// Parse a month like "2006-01" or a full date "2006-01-15"
+pub fn parse_date(raw: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&format!("{}-01", raw), "%Y-%m-%d"))
+        .ok()
+}
+
+fn month_start(d: NaiveDate) -> NaiveDate {
+    d.with_day(1).unwrap_or(d)
+}
+
+/// Validate optional `start`/`end` against `available` and an optional span limit
+/// `fields` names the two bounds in error messages (e.g. `["from", "to"]`)
+pub fn resolve(
+    start: Option<&str>,
+    end: Option<&str>,
+    fields: [&'static str; 2],
+    available: DateRange,
+    max_span_months: Option<u32>,
+) -> Result<DateRange, RangeError> {
+    let parse = |raw: Option<&str>, field: &'static str| -> Result<Option<NaiveDate>, RangeError> {
+        raw.map(|r| {
+            parse_date(r.trim())
+                .map(month_start)
+                .ok_or_else(|| RangeError::InvalidDate { field, value: r.to_string() })
+        })
+        .transpose()
+    };
+    let start = parse(start, fields[0])?;
+    let end = parse(end, fields[1])?;
+
+    if let (Some(s), Some(e)) = (start, end) {
+        if s > e {
+            return Err(RangeError::Inverted { fields, start: s, end: e });
+        }
+    }
+    for (field, value) in [(fields[0], start), (fields[1], end)] {
+        if let Some(value) = value.filter(|v| !available.contains(*v)) {
+            return Err(RangeError::OutOfRange { field, value, available });
+        }
+    }
+
+    let range = DateRange {
+        start: start.unwrap_or(available.start),
+        end: end.unwrap_or(available.end),
+    };
+    if let Some(max_months) = max_span_months.filter(|max| range.months() > *max) {
+        return Err(RangeError::SpanTooLarge { months: range.months(), max_months });
+    }
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: [&str; 2] = ["start", "end"];
+
+    fn d(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    fn available() -> DateRange {
+        DateRange { start: d(1961, 1), end: d(2026, 12) }
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let jan = d(2006, 1);
+        assert_eq!(parse_date("2006-01"), Some(jan));
+        assert_eq!(parse_date("2006-01-01"), Some(jan));
+        assert_eq!(parse_date("Jan 2006"), None);
+    }
+
+    #[test]
+    fn test_resolve_defaults_and_month_matching() {
+        assert_eq!(resolve(None, None, BOUNDS, available(), None), Ok(available()));
+
+        // Mid-month end date inside the last available month is accepted
+        let r = resolve(Some("2007-06"), Some("2026-12-31"), BOUNDS, available(), None).unwrap();
+        assert_eq!(r, DateRange { start: d(2007, 6), end: d(2026, 12) });
+        assert_eq!(DateRange { start: d(2007, 1), end: d(2007, 12) }.months(), 12);
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let err = |s, e, max| resolve(s, e, BOUNDS, available(), max).unwrap_err();
+
+        assert_eq!(err(Some("2008-13-01"), None, None).code(), "INVALID_DATE");
+        assert_eq!(err(Some("2009-01"), Some("2008-01"), None).code(), "INVALID_RANGE");
+
+        let out = err(Some("1950-01"), None, None);
+        assert_eq!(out.code(), "OUT_OF_RANGE");
+        assert!(out.is_unprocessable());
+
+        assert_eq!(
+            err(Some("2000-01"), Some("2009-12"), Some(60)),
+            RangeError::SpanTooLarge { months: 120, max_months: 60 }
+        );
+        // Open-ended ranges count up to the end of the data
+        assert_eq!(err(Some("2000-01"), None, Some(60)).code(), "SPAN_TOO_LARGE");
+        assert!(resolve(Some("2000-01"), Some("2004-12"), BOUNDS, available(), Some(60)).is_ok());
+    }
+}
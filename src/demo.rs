@@ -0,0 +1,166 @@
+//! Public Demo
+//!
+//! `NIV_ROUTER_PROFILE=demo` serves the API from canned data, so a public
+//! demo instance can run from the same binary:
+//! - the dataset is loaded once at startup (NIV_SNAPSHOT_FILE when it
+//!   matches the engine, as usual) and never refreshed: FRED_API_KEY,
+//!   NIV_REFRESH_SECS and NIV_INTRADAY_SECS are ignored, so nothing reaches
+//!   FRED
+//! - admin endpoints are disabled (NIV_ADMIN_TOKEN is ignored)
+//! - every request is anonymous (X-API-Key is dropped, as on the mirror)
+//!   and each client may make NIV_DEMO_RPM requests per minute (default
+//!   DEFAULT_DEMO_RPM); beyond that it gets 429 RATE_LIMITED with
+//!   Retry-After. Health checks are not counted.
+//! - JSON object responses carry `"demo": true`, and every response the
+//!   `X-NIV-Demo: true` header
+//!
+//! A client is the peer address, or the last address in the header named by
+//! NIV_DEMO_CLIENT_IP_HEADER (e.g. `Fly-Client-IP`) when a proxy in front
+//! sets one: the proxy appends the address it saw, so anything before it
+//! came from the client. IPv6 clients are counted per /64, the block one
+//! host usually holds. At most MAX_CLIENTS are tracked; once that many are
+//! active, new clients are refused until windows expire.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+
+/// Requests per client per minute unless NIV_DEMO_RPM says otherwise
+pub const DEFAULT_DEMO_RPM: usize = 30;
+
+/// Marks every demo response
+pub const DEMO_HEADER: &str = "x-niv-demo";
+
+/// Clients tracked at once; idle ones are pruned to make room
+const MAX_CLIENTS: usize = 10_000;
+
+/// IPv6 prefix counted as one client
+const IPV6_CLIENT_PREFIX: u32 = 64;
+
+/// How the demo profile identifies and limits clients
+pub struct DemoPolicy {
+    pub limiter: ClientLimiter,
+    pub client_ip_header: Option<String>, // NIV_DEMO_CLIENT_IP_HEADER
+}
+
+/// Sliding-window request limit per client
+pub struct ClientLimiter {
+    limit: usize,
+    window: Duration,
+    clients: Mutex<HashMap<IpAddr, VecDeque<Instant>>>,
+}
+
+impl ClientLimiter {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self { limit, window, clients: Mutex::new(HashMap::new()) }
+    }
+
+    /// Take one of `client`'s slots, returning the slots left; Err(wait)
+    /// when its window is full
+    pub fn try_acquire(&self, client: IpAddr, now: Instant) -> Result<usize, Duration> {
+        let client = client_key(client);
+        let mut clients = self.clients.lock().expect("client limiter lock");
+        if clients.len() >= MAX_CLIENTS && !clients.contains_key(&client) {
+            clients.retain(|_, hits| hits.back().is_some_and(|&t| now.duration_since(t) < self.window));
+            if clients.len() >= MAX_CLIENTS {
+                return Err(self.window); // Every tracked client is active
+            }
+        }
+        let hits = clients.entry(client).or_default();
+        while hits.front().is_some_and(|&t| now.duration_since(t) >= self.window) {
+            hits.pop_front();
+        }
+        if hits.len() >= self.limit {
+            let oldest = *hits.front().expect("full window is non-empty");
+            return Err(self.window.saturating_sub(now.duration_since(oldest)));
+        }
+        hits.push_back(now);
+        Ok(self.limit - hits.len())
+    }
+}
+
+/// Address the limiter counts: IPv6 masked to its /64
+fn client_key(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => ip,
+        IpAddr::V6(v6) => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & (u128::MAX << (128 - IPV6_CLIENT_PREFIX)))),
+    }
+}
+
+/// The requesting client: the last address in `trusted_header` (the one
+/// the proxy appended) when set and parseable, else the peer (unspecified
+/// when unknown)
+pub fn client_ip(headers: &HeaderMap, trusted_header: Option<&str>, peer: Option<IpAddr>) -> IpAddr {
+    trusted_header
+        .and_then(|name| headers.get_all(name).iter().next_back())
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|last| last.trim().parse().ok())
+        .or(peer)
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+}
+
+/// `body` with `"demo": true` added, when it is a JSON object
+pub fn watermark(body: &[u8]) -> Option<Vec<u8>> {
+    let mut value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    value.as_object_mut()?.insert("demo".to_string(), serde_json::Value::Bool(true));
+    serde_json::to_vec(&value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_is_per_client_window() {
+        let limiter = ClientLimiter::new(2, Duration::from_secs(60));
+        let (a, b): (IpAddr, IpAddr) = ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+        let t0 = Instant::now();
+
+        assert_eq!(limiter.try_acquire(a, t0), Ok(1));
+        assert_eq!(limiter.try_acquire(a, t0 + Duration::from_secs(10)), Ok(0));
+        assert_eq!(limiter.try_acquire(a, t0 + Duration::from_secs(20)), Err(Duration::from_secs(40)));
+        assert_eq!(limiter.try_acquire(b, t0 + Duration::from_secs(20)), Ok(1));
+        assert_eq!(limiter.try_acquire(a, t0 + Duration::from_secs(60)), Ok(0));
+    }
+
+    #[test]
+    fn test_ipv6_counted_per_64_and_clients_capped() {
+        let limiter = ClientLimiter::new(1, Duration::from_secs(60));
+        let t0 = Instant::now();
+        let host: IpAddr = "2001:db8:1:2::1".parse().unwrap();
+        let neighbour: IpAddr = "2001:db8:1:2:ffff::9".parse().unwrap();
+
+        assert_eq!(limiter.try_acquire(host, t0), Ok(0));
+        assert!(limiter.try_acquire(neighbour, t0).is_err());
+        assert_eq!(limiter.try_acquire("2001:db8:1:3::1".parse().unwrap(), t0), Ok(0));
+
+        for i in 0..MAX_CLIENTS as u32 {
+            let _ = limiter.try_acquire(IpAddr::V4(Ipv4Addr::from(i)), t0);
+        }
+        assert_eq!(limiter.clients.lock().unwrap().len(), MAX_CLIENTS);
+        assert_eq!(limiter.try_acquire("192.0.2.1".parse().unwrap(), t0), Err(Duration::from_secs(60)));
+        assert_eq!(limiter.try_acquire("192.0.2.1".parse().unwrap(), t0 + Duration::from_secs(60)), Ok(0));
+    }
+
+    #[test]
+    fn test_client_ip_and_watermark() {
+        let mut headers = HeaderMap::new();
+        headers.insert("fly-client-ip", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        let peer: IpAddr = "10.0.0.9".parse().unwrap();
+
+        assert_eq!(client_ip(&headers, Some("Fly-Client-IP"), Some(peer)), "10.0.0.1".parse::<IpAddr>().unwrap());
+        headers.append("fly-client-ip", "198.51.100.4".parse().unwrap());
+        assert_eq!(client_ip(&headers, Some("Fly-Client-IP"), Some(peer)), "198.51.100.4".parse::<IpAddr>().unwrap());
+        assert_eq!(client_ip(&headers, None, Some(peer)), peer);
+        assert_eq!(client_ip(&HeaderMap::new(), Some("fly-client-ip"), None), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        let marked = watermark(br#"{"niv_score":1.5}"#).unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(&marked).unwrap(), serde_json::json!({"niv_score": 1.5, "demo": true}));
+        assert_eq!(watermark(b"[1,2]"), None);
+        assert_eq!(watermark(b"date,niv\n"), None);
+    }
+}
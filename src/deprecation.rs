@@ -0,0 +1,142 @@
+//! Endpoint Deprecation Registry
+//!
+//! Soft deprecation for endpoints whose shapes are about to change: they keep
+//! answering, but every response carries
+//! - `Deprecation: @<unix seconds>` (RFC 9745), when the endpoint was or will
+//!   be deprecated
+//! - `Sunset: <HTTP-date>` (RFC 8594), when it may stop answering
+//! - `Link: <successor>; rel="successor-version"` and, with `docs`,
+//!   `Link: <docs>; rel="deprecation"`
+//!
+//! The registry is a JSON file (NIV_DEPRECATIONS_FILE):
+//!
+//! ```json
+//! { "deprecations": [
+//!     { "path": "/api/v1/replay/:id", "deprecated_at": "2026-11-01", "sunset": "2027-05-01",
+//!       "successor": "/api/v2/replay/:id", "note": "status moves under `state`" }
+//! ] }
+//! ```
+//!
+//! Paths use the router's syntax; `:name` segments match any value. The
+//! registry is served at `/api/v1/deprecations` for client tooling.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Deprecation {
+    pub path: String,
+    pub deprecated_at: NaiveDate,
+    pub sunset: Option<NaiveDate>,
+    pub successor: Option<String>,
+    pub docs: Option<String>,
+    pub note: Option<String>,
+}
+
+impl Deprecation {
+    fn matches(&self, path: &str) -> bool {
+        let (pattern, actual) = (self.path.trim_end_matches('/'), path.trim_end_matches('/'));
+        pattern.split('/').count() == actual.split('/').count()
+            && pattern
+                .split('/')
+                .zip(actual.split('/'))
+                .all(|(p, a)| p == a || (p.starts_with(':') && !a.is_empty()))
+    }
+
+    /// Response headers announcing this deprecation
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let midnight = |d: NaiveDate| d.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc();
+        let mut headers = vec![("deprecation", format!("@{}", midnight(self.deprecated_at).timestamp()))];
+        if let Some(sunset) = self.sunset {
+            headers.push(("sunset", midnight(sunset).format("%a, %d %b %Y %H:%M:%S GMT").to_string()));
+        }
+        if let Some(successor) = &self.successor {
+            headers.push(("link", format!("<{}>; rel=\"successor-version\"", successor)));
+        }
+        if let Some(docs) = &self.docs {
+            headers.push(("link", format!("<{}>; rel=\"deprecation\"", docs)));
+        }
+        headers
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegistryFile {
+    deprecations: Vec<Deprecation>,
+}
+
+/// Deprecated endpoints; empty unless NIV_DEPRECATIONS_FILE is set
+#[derive(Debug, Clone, Default)]
+pub struct DeprecationRegistry {
+    entries: Vec<Deprecation>,
+}
+
+impl DeprecationRegistry {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&raw)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let file: RegistryFile = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+        for d in &file.deprecations {
+            if !d.path.starts_with('/') {
+                return Err(format!("path '{}' must start with '/'", d.path));
+            }
+            if d.sunset.is_some_and(|s| s < d.deprecated_at) {
+                return Err(format!("{}: sunset precedes deprecated_at", d.path));
+            }
+        }
+        Ok(Self { entries: file.deprecations })
+    }
+
+    pub fn entries(&self) -> &[Deprecation] {
+        &self.entries
+    }
+
+    /// The deprecation covering a request path, if any
+    pub fn find(&self, path: &str) -> Option<&Deprecation> {
+        self.entries.iter().find(|d| d.matches(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"{ "deprecations": [
+        { "path": "/api/v1/compare", "deprecated_at": "2026-11-01", "sunset": "2027-05-01",
+          "successor": "/api/v2/compare", "docs": "https://example.org/v2" },
+        { "path": "/api/v1/replay/:id", "deprecated_at": "2026-12-01" }
+    ] }"#;
+
+    #[test]
+    fn test_find_matches_exact_and_parameterized_paths() {
+        let registry = DeprecationRegistry::parse(FILE).unwrap();
+
+        assert_eq!(registry.find("/api/v1/compare/").unwrap().path, "/api/v1/compare");
+        assert!(registry.find("/api/v1/replay/abc").is_some());
+        assert!(registry.find("/api/v1/replay/abc/events").is_none());
+        assert!(registry.find("/api/v1/latest").is_none());
+    }
+
+    #[test]
+    fn test_headers_and_validation() {
+        let registry = DeprecationRegistry::parse(FILE).unwrap();
+        let headers = registry.find("/api/v1/compare").unwrap().headers();
+
+        assert_eq!(headers[0], ("deprecation", "@1793491200".to_string()));
+        assert_eq!(headers[1], ("sunset", "Sat, 01 May 2027 00:00:00 GMT".to_string()));
+        assert_eq!(headers[2].1, "</api/v2/compare>; rel=\"successor-version\"");
+        assert_eq!(headers.len(), 4);
+        assert_eq!(registry.find("/api/v1/replay/x").unwrap().headers().len(), 1);
+
+        assert!(DeprecationRegistry::parse(r#"{"deprecations":[{"path":"x","deprecated_at":"2026-01-01"}]}"#).is_err());
+        assert!(DeprecationRegistry::parse(
+            r#"{"deprecations":[{"path":"/x","deprecated_at":"2026-01-01","sunset":"2025-01-01"}]}"#
+        )
+        .is_err());
+    }
+}
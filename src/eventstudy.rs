@@ -0,0 +1,170 @@
+//! Event Study
+//!
+//! Aligns the NIV score, recession probability and components on each
+//! recession start (offset 0) and summarises every offset from `-pre` to
+//! `+post` months across recessions: mean, standard deviation and the
+//! p10/p90 band. Only recessions whose whole window lies inside the history
+//! contribute, so every offset averages the same episodes; the rest are
+//! listed as skipped.
+
+use std::collections::HashMap;
+
+use chrono::{Months, NaiveDate};
+use serde::Serialize;
+
+use crate::montecarlo::percentile;
+use crate::niv::NIVResult;
+
+pub const DEFAULT_PRE_MONTHS: u32 = 24;
+pub const DEFAULT_POST_MONTHS: u32 = 12;
+pub const MAX_PRE_MONTHS: u32 = 60;
+pub const MAX_POST_MONTHS: u32 = 36;
+
+type Series = (&'static str, fn(&NIVResult) -> f64);
+
+const SERIES: [Series; 6] = [
+    ("niv_score", |r| r.niv_score),
+    ("recession_probability", |r| r.recession_probability), // 0-1; the API reports it in the request's units
+    ("thrust", |r| r.components.thrust),
+    ("efficiency", |r| r.components.efficiency),
+    ("slack", |r| r.components.slack),
+    ("drag", |r| r.components.drag),
+];
+
+/// Cross-recession summary at one offset from the start
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventBand {
+    pub offset: i32, // Months relative to the recession start
+    pub mean: f64,
+    pub std: f64,
+    pub p10: f64,
+    pub p90: f64,
+}
+
+/// Average path of one series
+#[derive(Debug, Clone, Serialize)]
+pub struct EventPath {
+    pub series: &'static str,
+    pub bands: Vec<EventBand>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventStudy {
+    pub events: Vec<NaiveDate>,  // Recession starts averaged over
+    pub skipped: Vec<NaiveDate>, // Starts whose window is not fully covered
+    pub paths: Vec<EventPath>,   // Empty when no recession qualifies
+}
+
+/// Align `results` on the starts in `chronology` over `pre` months before to
+/// `post` months after
+pub fn study(results: &[NIVResult], chronology: &[(NaiveDate, NaiveDate)], pre: u32, post: u32) -> EventStudy {
+    let index: HashMap<NaiveDate, usize> = results.iter().enumerate().map(|(i, r)| (r.date, i)).collect();
+    let mut starts: Vec<NaiveDate> = chronology.iter().map(|(start, _)| *start).collect();
+    starts.sort();
+
+    let (mut events, mut skipped, mut windows) = (Vec::new(), Vec::new(), Vec::new());
+    for start in starts {
+        let first = start.checked_sub_months(Months::new(pre)).and_then(|d| index.get(&d));
+        let last = start.checked_add_months(Months::new(post)).and_then(|d| index.get(&d));
+        match (first, last) {
+            // Contiguous months only: a gap in the series would misalign offsets
+            (Some(&first), Some(&last)) if last >= first && last - first == (pre + post) as usize => {
+                events.push(start);
+                windows.push(&results[first..=last]);
+            }
+            _ => skipped.push(start),
+        }
+    }
+
+    let paths = if windows.is_empty() {
+        Vec::new()
+    } else {
+        SERIES
+            .iter()
+            .map(|(name, value)| EventPath {
+                series: name,
+                bands: (0..=(pre + post) as usize)
+                    .map(|step| {
+                        let mut values: Vec<f64> = windows.iter().map(|w| value(&w[step])).collect();
+                        values.sort_by(f64::total_cmp);
+                        let mean = values.iter().sum::<f64>() / values.len() as f64;
+                        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+                        EventBand {
+                            offset: step as i32 - pre as i32,
+                            mean,
+                            std: variance.sqrt(),
+                            p10: percentile(&values, 0.1),
+                            p90: percentile(&values, 0.9),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect()
+    };
+    EventStudy { events, skipped, paths }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::niv::{AlertLevel, NIVComponents};
+
+    fn month(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    /// Monthly results from Jan 2000 whose score counts months since then
+    fn series(months: u32) -> Vec<NIVResult> {
+        (0..months)
+            .map(|i| NIVResult {
+                date: month(2000, 1).checked_add_months(Months::new(i)).unwrap(),
+                niv_score: i as f64,
+                recession_probability: 0.0,
+                components: NIVComponents {
+                    thrust: 0.0,
+                    efficiency: 0.0,
+                    efficiency_squared: 0.0,
+                    slack: 0.0,
+                    drag: 0.0,
+                    drag_spread: 0.0,
+                    drag_real_rate: 0.0,
+                    drag_volatility: 0.0,
+                    gdp_nowcast: false,
+                    custom: Vec::new(),
+                },
+                alert_level: AlertLevel::Normal,
+                niv_percentile: 50.0,
+                quality: Vec::new(),
+                coverage: Vec::new(),
+                warmup: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_aligns_paths_on_starts() {
+        let results = series(120);
+        // Starts 24 and 48 months in: scores 24 and 48 at offset 0
+        let chronology = [(month(2002, 1), month(2002, 6)), (month(2004, 1), month(2004, 6))];
+        let study = study(&results, &chronology, 12, 6);
+
+        assert_eq!(study.events, vec![month(2002, 1), month(2004, 1)]);
+        let score = &study.paths[0];
+        assert_eq!(score.bands.len(), 19);
+        assert_eq!(score.bands[0], EventBand { offset: -12, mean: 24.0, std: 12.0, p10: 12.0, p90: 36.0 });
+        assert_eq!(score.bands[12].offset, 0);
+        assert_eq!(score.bands[12].mean, 36.0);
+    }
+
+    #[test]
+    fn test_skips_uncovered_windows() {
+        let results = series(36);
+        let chronology = [(month(2000, 2), month(2000, 9)), (month(2001, 6), month(2001, 9)), (month(2002, 10), month(2003, 1))];
+        let single = study(&results, &chronology, 3, 3);
+
+        assert_eq!(single.events, vec![month(2001, 6)]);
+        assert_eq!(single.skipped, vec![month(2000, 2), month(2002, 10)]);
+        assert!(single.paths.iter().all(|p| p.bands.iter().all(|b| b.std == 0.0)));
+        assert!(study(&results, &[], 24, 12).paths.is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+//! Excel Workbook Export
+//!
+//! Builds an .xlsx for slide-deck use:
+//! - Data: date, NIV score, recession probability, alert level, recession flag
+//! - Components: date, thrust, efficiency, slack, drag, recession flag
+//! - Charts: NIV score, recession probability and components, each over
+//!   grey NBER recession bars, under a title naming the model and the
+//!   pipeline hash the data came from
+//!
+//! Recession shading is a gap-less column series of the 0/1 recession flag on
+//! a hidden secondary axis fixed to [0, 1], the usual Excel idiom.
+
+use chrono::Datelike;
+use rust_xlsxwriter::{
+    Chart, ChartFormat, ChartSolidFill, ChartType, ExcelDateTime, Format, Workbook, Worksheet, XlsxError,
+};
+
+use crate::labels::LabelSet;
+use crate::niv::NIVResult;
+
+pub const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+const DATA_SHEET: &str = "Data";
+const COMPONENTS_SHEET: &str = "Components";
+const CHARTS_SHEET: &str = "Charts";
+const RECESSION_FILL: &str = "#D9D9D9";
+
+fn write_header(sheet: &mut Worksheet, headers: &[&str], bold: &Format) -> Result<(), XlsxError> {
+    for (col, name) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *name, bold)?;
+        sheet.set_column_width(col as u16, if col == 0 { 12 } else { 16 })?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn write_date(sheet: &mut Worksheet, row: u32, result: &NIVResult, format: &Format) -> Result<(), XlsxError> {
+    let d = result.date;
+    let date = ExcelDateTime::from_ymd(d.year() as u16, d.month() as u8, d.day() as u8)?;
+    sheet.write_datetime_with_format(row, 0, &date, format)?;
+    Ok(())
+}
+
+/// Line chart of `columns` on `sheet` over recession bars from `flag_column`
+fn shaded_chart(title: &str, sheet: &str, columns: &[(u16, &str)], flag_column: u16, last_row: u32) -> Chart {
+    let mut chart = Chart::new(ChartType::Line);
+    chart.title().set_name(title);
+    for &(col, name) in columns {
+        chart
+            .add_series()
+            .set_name(name)
+            .set_categories((sheet, 1, 0, last_row, 0))
+            .set_values((sheet, 1, col, last_row, col));
+    }
+    chart.x_axis().set_date_axis(true).set_num_format("yyyy");
+    chart.set_width(960).set_height(360);
+
+    let mut bars = Chart::new(ChartType::Column);
+    bars.add_series()
+        .set_name("Recession")
+        .set_categories((sheet, 1, 0, last_row, 0))
+        .set_values((sheet, 1, flag_column, last_row, flag_column))
+        .set_secondary_axis(true)
+        .set_gap(0)
+        .set_format(ChartFormat::new().set_solid_fill(ChartSolidFill::new().set_color(RECESSION_FILL)));
+    bars.y2_axis().set_min(0).set_max(1).set_hidden(true);
+    chart.combine(&bars);
+    chart
+}
+
+/// Serialize `results` (date-sorted) as an .xlsx workbook, flagging and
+/// shading the months `recessions` covers
+pub fn workbook(results: &[NIVResult], recessions: &LabelSet, model_version: &str, pipeline: &str) -> Result<Vec<u8>, XlsxError> {
+    let mut book = Workbook::new();
+    let bold = Format::new().set_bold();
+    let date_format = Format::new().set_num_format("yyyy-mm-dd");
+    let last_row = results.len().max(1) as u32;
+
+    let sheet = book.add_worksheet().set_name(DATA_SHEET)?;
+    write_header(
+        sheet,
+        &["Date", "NIV Score", "Recession Probability (%)", "Alert Level", "Recession"],
+        &bold,
+    )?;
+    for (i, r) in results.iter().enumerate() {
+        let row = i as u32 + 1;
+        write_date(sheet, row, r, &date_format)?;
+        sheet.write_number(row, 1, r.niv_score)?;
+        sheet.write_number(row, 2, r.recession_probability * 100.0)?;
+        sheet.write_string(row, 3, r.alert_level.label())?;
+        sheet.write_number(row, 4, recessions.contains(r.date) as u8)?;
+    }
+
+    let sheet = book.add_worksheet().set_name(COMPONENTS_SHEET)?;
+    write_header(sheet, &["Date", "Thrust", "Efficiency", "Slack", "Drag", "Recession"], &bold)?;
+    for (i, r) in results.iter().enumerate() {
+        let row = i as u32 + 1;
+        write_date(sheet, row, r, &date_format)?;
+        sheet.write_number(row, 1, r.components.thrust)?;
+        sheet.write_number(row, 2, r.components.efficiency)?;
+        sheet.write_number(row, 3, r.components.slack)?;
+        sheet.write_number(row, 4, r.components.drag)?;
+        sheet.write_number(row, 5, recessions.contains(r.date) as u8)?;
+    }
+
+    let charts = [
+        shaded_chart("NIV Score", DATA_SHEET, &[(1, "NIV Score")], 4, last_row),
+        shaded_chart("Recession Probability (%)", DATA_SHEET, &[(2, "Recession Probability")], 4, last_row),
+        shaded_chart(
+            "Components",
+            COMPONENTS_SHEET,
+            &[(1, "Thrust"), (2, "Efficiency"), (3, "Slack"), (4, "Drag")],
+            5,
+            last_row,
+        ),
+    ];
+    let sheet = book.add_worksheet().set_name(CHARTS_SHEET)?;
+    sheet.write_string_with_format(0, 0, format!("NIV {} - grey bars mark NBER recessions", model_version), &bold)?;
+    sheet.write_string(1, 0, format!("Pipeline {}", pipeline))?;
+    for (i, chart) in charts.iter().enumerate() {
+        sheet.insert_chart(2 + 20 * i as u32, 0, chart)?;
+    }
+
+    book.save_to_buffer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::labels::LabelSets;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_workbook_is_a_zip_with_all_sheets() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2005, 2012));
+        let nber = LabelSets::default().get(None).unwrap();
+        let bytes = workbook(&results, &nber, "test", "abc123").unwrap();
+
+        assert!(bytes.starts_with(b"PK"));
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("xl/charts/chart3.xml"));
+        assert!(workbook(&[], &nber, "test", "abc123").is_ok());
+    }
+}
@@ -0,0 +1,174 @@
+//! Response Format
+//!
+//! How a request wants numbers written, from two query parameters on any
+//! endpoint:
+//! - `precision=<0-12>`: JSON responses round each value to a per-field
+//!   default (2 decimals for scores and percentages, 4 for components, 6 for
+//!   P²) through `round`; the parameter overrides the decimals for every
+//!   rounded value in that response, so small drag values survive
+//! - `probability_units=percent|fraction`: probabilities are percentages
+//!   (35.12), the v1 convention, unless fractions (0.3512) are asked for or
+//!   set in the key's preferences. Fractions keep two more decimals so the two
+//!   forms carry the same digits. This covers headline values, ranges,
+//!   forecast fans, Monte Carlo draws, differences between probabilities and
+//!   echoed thresholds; query inputs such as `threshold=` stay in percent
+//!
+//! The router scopes a `Format` over the handler. Bodies written after the
+//! handler returns (the CSV export producer, replay SSE events) run outside
+//! that scope, so they capture `Format::current()` and carry it along. The
+//! workbook, the time-series push and snapshots always carry full precision,
+//! and webhooks carry unrounded fractions.
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+
+/// Largest accepted `precision=`
+pub const MAX_PRECISION: u32 = 12;
+
+/// How probabilities are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbabilityUnits {
+    #[default]
+    Percent,
+    Fraction,
+}
+
+impl std::str::FromStr for ProbabilityUnits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "percent" => Ok(Self::Percent),
+            "fraction" => Ok(Self::Fraction),
+            other => Err(format!("unknown probability_units '{}' (expected percent or fraction)", other)),
+        }
+    }
+}
+
+/// A request's number format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Format {
+    pub precision: Option<u32>, // Overrides every default number of decimals
+    pub units: ProbabilityUnits,
+}
+
+tokio::task_local! {
+    static FORMAT: Format;
+}
+
+impl Format {
+    /// The format scoped over the current request (the default outside one)
+    pub fn current() -> Self {
+        FORMAT.try_with(|f| *f).unwrap_or_default()
+    }
+
+    /// Run `f` with this format
+    pub async fn scope<F: Future>(self, f: F) -> F::Output {
+        FORMAT.scope(self, f).await
+    }
+
+    /// `digits`, unless the request set a precision
+    pub fn decimals(&self, digits: u32) -> u32 {
+        self.precision.unwrap_or(digits)
+    }
+
+    /// `v` to this format's precision, or `digits` decimals when none was set
+    pub fn round(&self, v: f64, digits: u32) -> f64 {
+        let scale = 10f64.powi(self.decimals(digits) as i32);
+        (v * scale).round() / scale
+    }
+
+    /// A 0-1 probability in this format's units, with `percent_digits`
+    /// decimals as a percentage
+    pub fn probability_to(&self, p: f64, percent_digits: u32) -> f64 {
+        match self.units {
+            ProbabilityUnits::Percent => self.round(p * 100.0, percent_digits),
+            ProbabilityUnits::Fraction => self.round(p, percent_digits + 2),
+        }
+    }
+}
+
+/// `v` to the request's precision, or `digits` decimals when none was asked for
+pub fn round(v: f64, digits: u32) -> f64 {
+    Format::current().round(v, digits)
+}
+
+/// A 0-1 probability in the request's units, rounded
+pub fn probability(p: f64) -> f64 {
+    probability_to(p, 2)
+}
+
+/// As `probability`, with `percent_digits` decimals as a percentage
+pub fn probability_to(p: f64, percent_digits: u32) -> f64 {
+    Format::current().probability_to(p, percent_digits)
+}
+
+/// Value of `key=` in a query string, if present
+fn query_param<'a>(query: Option<&'a str>, key: &str) -> Option<&'a str> {
+    query.into_iter().flat_map(|q| q.split('&')).find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// The `precision` parameter of a query string, if present
+pub fn precision_from_query(query: Option<&str>) -> Result<Option<u32>, String> {
+    let Some(raw) = query_param(query, "precision") else {
+        return Ok(None);
+    };
+    match raw.parse::<u32>() {
+        Ok(digits) if digits <= MAX_PRECISION => Ok(Some(digits)),
+        _ => Err(format!("precision must be an integer from 0 to {}", MAX_PRECISION)),
+    }
+}
+
+/// The `probability_units` parameter of a query string, if present
+pub fn units_from_query(query: Option<&str>) -> Result<Option<ProbabilityUnits>, String> {
+    query_param(query, "probability_units").map(str::parse).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_query() {
+        assert_eq!(precision_from_query(None), Ok(None));
+        assert_eq!(precision_from_query(Some("start=2020-01-01&limit=5")), Ok(None));
+        assert_eq!(precision_from_query(Some("start=2020-01-01&precision=8")), Ok(Some(8)));
+        assert!(precision_from_query(Some("precision=13")).is_err());
+        assert!(precision_from_query(Some("precision=-1")).is_err());
+        assert!(precision_from_query(Some("precision=")).is_err());
+
+        assert_eq!(units_from_query(Some("start=2020-01-01")), Ok(None));
+        assert_eq!(units_from_query(Some("probability_units=fraction&limit=5")), Ok(Some(ProbabilityUnits::Fraction)));
+        assert_eq!(units_from_query(Some("probability_units=percent")), Ok(Some(ProbabilityUnits::Percent)));
+        assert!(units_from_query(Some("probability_units=basis_points")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scope_overrides_defaults() {
+        let drag = 0.000_123_456;
+        let precise = |digits| Format { precision: Some(digits), ..Format::default() };
+        assert_eq!(round(drag, 4), 0.0001);
+        assert_eq!(precise(8).scope(async { round(drag, 4) }).await, 0.00012346);
+        assert_eq!(precise(0).scope(async { round(12.6, 2) }).await, 13.0);
+        assert_eq!(Format::default().scope(async { round(12.345, 2) }).await, 12.35);
+    }
+
+    #[tokio::test]
+    async fn test_units_keep_digits() {
+        let fraction = Format { units: ProbabilityUnits::Fraction, ..Format::default() };
+        assert_eq!(probability(0.351234), 35.12);
+        assert_eq!(fraction.scope(async { probability(0.351234) }).await, 0.3512);
+        assert_eq!(Format::default().scope(async { probability(0.351234) }).await, 35.12);
+    }
+
+    #[tokio::test]
+    async fn test_captured_format_outlives_the_scope() {
+        let fraction = Format { precision: Some(6), units: ProbabilityUnits::Fraction };
+        let captured = fraction.scope(async { Format::current() }).await;
+        assert_eq!(Format::current(), Format::default());
+        assert_eq!(captured, fraction);
+        assert_eq!(captured.probability_to(0.3512346, 2), 0.351235);
+    }
+}
@@ -8,16 +8,59 @@
 //! - TCU: Total Capacity Utilization (Slack)
 //! - T10Y3M: 10Y-3M Treasury Spread (Drag - Inversion penalty)
 //! - CPIAUCSL: CPI for Inflation (Drag - Real rate calculation)
+//!
+//! Optional (never fail the fetch):
+//! - Y694RC1Q027SBEA: Real R&D Investment, quarterly (custom components;
+//!   GPDIC1 already includes it as intellectual property products)
+//! - G160291A027NBEA: Government Education Expenditures, annual, nominal
+//!   (Efficiency, `EfficiencySpec::Observed`)
+//! - GDPDEF: GDP Implicit Price Deflator, quarterly (deflates education
+//!   spending into GDPC1's chained dollars)
+//!
+//! Optional (used by the gap-based `SlackSpec`s):
+//! - GDPPOT: CBO Real Potential GDP, quarterly (Slack - output gap)
+//! - UNRATE: Unemployment Rate (Slack - unemployment gap)
+//! - NROU: CBO Noncyclical Rate of Unemployment, quarterly (Slack - unemployment gap)
+//!
+//! Optional (used by the alternative `InflationSpec`s):
+//! - CPILFESL: Core CPI, ex food and energy (Drag - real rate)
+//! - PCEPI: PCE Price Index (Drag - real rate)
+//! - T5YIE: 5-Year Breakeven Inflation Rate, daily (Drag - ex-ante real rate)
+//!
+//! Optional (monthly GDP nowcast between GDPC1 releases, see nowcast.rs):
+//! - INDPRO: Industrial Production Index
+//! - RRSFS: Advance Real Retail and Food Services Sales
+//!
+//! Months past the end of a required series are truncated or estimated per
+//! the partial data mode (see coverage.rs).
+//!
+//! Release calendars (`upcoming_releases`, for `/calendar.ics`) come from the
+//! series/release and release/dates endpoints.
 
-use chrono::{Datelike, NaiveDate};
+use chrono::NaiveDate;
+#[cfg(feature = "fred")]
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
+#[cfg(feature = "fred")]
 use std::collections::HashMap;
+#[cfg(feature = "fred")]
 use std::env;
 
+#[cfg(feature = "fred")]
+use crate::coverage::{self, PartialDataMode};
 use crate::niv::EconomicData;
+#[cfg(feature = "fred")]
+use crate::niv::InputSeries;
+#[cfg(feature = "fred")]
+use crate::nowcast;
 
 const FRED_BASE_URL: &str = "https://api.stlouisfed.org/fred/series/observations";
+#[cfg(feature = "fred")]
+const FRED_SERIES_RELEASE_URL: &str = "https://api.stlouisfed.org/fred/series/release";
+#[cfg(feature = "fred")]
+const FRED_RELEASE_DATES_URL: &str = "https://api.stlouisfed.org/fred/release/dates";
+#[cfg(feature = "fred")]
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(20); // Per FRED call
 
 /// FRED API response structure
 #[derive(Debug, Deserialize)]
@@ -25,12 +68,61 @@ struct FredResponse {
     observations: Vec<FredObservation>,
 }
 
+/// FRED error response body
+#[cfg(feature = "fred")]
+#[derive(Debug, Deserialize)]
+struct FredErrorResponse {
+    error_message: String,
+}
+
+/// Whether a FRED 400 message says the series does not exist, as opposed to
+/// a rejected key or parameter
+#[cfg_attr(not(feature = "fred"), allow(dead_code))]
+fn is_unknown_series(message: &str) -> bool {
+    message.to_ascii_lowercase().contains("series does not exist")
+}
+
 #[derive(Debug, Deserialize)]
 struct FredObservation {
     date: String,
     value: String,
 }
 
+/// FRED series/release response (the release publishing a series)
+#[cfg(feature = "fred")]
+#[derive(Debug, Deserialize)]
+struct SeriesReleaseResponse {
+    releases: Vec<FredRelease>,
+}
+
+#[cfg(feature = "fred")]
+#[derive(Debug, Deserialize)]
+struct FredRelease {
+    id: u32,
+    name: String,
+}
+
+/// FRED release/dates response
+#[cfg(feature = "fred")]
+#[derive(Debug, Deserialize)]
+struct ReleaseDatesResponse {
+    release_dates: Vec<FredReleaseDate>,
+}
+
+#[cfg(feature = "fred")]
+#[derive(Debug, Deserialize)]
+struct FredReleaseDate {
+    date: String,
+}
+
+/// Scheduled publication of a FRED release carrying input series
+#[derive(Debug, Clone)]
+pub struct ReleaseDate {
+    pub date: NaiveDate,
+    pub release: String,
+    pub series: Vec<&'static str>,
+}
+
 /// FRED series IDs
 #[derive(Debug, Clone, Copy)]
 pub enum FredSeries {
@@ -40,7 +132,18 @@ pub enum FredSeries {
     RealGDP,         // GDPC1
     CapacityUtil,    // TCU
     YieldSpread,     // T10Y3M
-    CPI,             // CPIAUCSL
+    Cpi,             // CPIAUCSL
+    RdInvestment,    // Y694RC1Q027SBEA (optional)
+    Education,       // G160291A027NBEA (optional)
+    GdpDeflator,     // GDPDEF (optional)
+    PotentialGdp,    // GDPPOT (optional)
+    Unemployment,    // UNRATE (optional)
+    Nairu,           // NROU (optional)
+    IndustrialProduction, // INDPRO (optional)
+    RetailSales,     // RRSFS (optional)
+    CoreCpi,         // CPILFESL (optional)
+    Pce,             // PCEPI (optional)
+    Breakeven,       // T5YIE (optional)
 }
 
 impl FredSeries {
@@ -52,7 +155,18 @@ impl FredSeries {
             FredSeries::RealGDP => "GDPC1",
             FredSeries::CapacityUtil => "TCU",
             FredSeries::YieldSpread => "T10Y3M",
-            FredSeries::CPI => "CPIAUCSL",
+            FredSeries::Cpi => "CPIAUCSL",
+            FredSeries::RdInvestment => "Y694RC1Q027SBEA",
+            FredSeries::Education => "G160291A027NBEA",
+            FredSeries::GdpDeflator => "GDPDEF",
+            FredSeries::PotentialGdp => "GDPPOT",
+            FredSeries::Unemployment => "UNRATE",
+            FredSeries::Nairu => "NROU",
+            FredSeries::IndustrialProduction => "INDPRO",
+            FredSeries::RetailSales => "RRSFS",
+            FredSeries::CoreCpi => "CPILFESL",
+            FredSeries::Pce => "PCEPI",
+            FredSeries::Breakeven => "T5YIE",
         }
     }
 
@@ -64,31 +178,69 @@ impl FredSeries {
             FredSeries::RealGDP,
             FredSeries::CapacityUtil,
             FredSeries::YieldSpread,
-            FredSeries::CPI,
+            FredSeries::Cpi,
         ]
     }
+
+    /// Series that enrich the data when available but are not required
+    pub fn optional() -> Vec<FredSeries> {
+        vec![
+            FredSeries::RdInvestment,
+            FredSeries::Education,
+            FredSeries::GdpDeflator,
+            FredSeries::PotentialGdp,
+            FredSeries::Unemployment,
+            FredSeries::Nairu,
+            FredSeries::IndustrialProduction,
+            FredSeries::RetailSales,
+            FredSeries::CoreCpi,
+            FredSeries::Pce,
+            FredSeries::Breakeven,
+        ]
+    }
+}
+
+/// HTTP client whose calls give up after REQUEST_TIMEOUT
+#[cfg(feature = "fred")]
+fn http_client() -> Client {
+    Client::builder().timeout(REQUEST_TIMEOUT).build().expect("HTTP client")
+}
+
+/// Whether the server may call FRED at all; offline (the demo profile)
+/// nothing reaches it, whatever FRED_API_KEY says
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FredAccess {
+    #[default]
+    Live,
+    Offline,
 }
 
 /// FRED API Client
+#[cfg(feature = "fred")]
 pub struct FredClient {
     client: Client,
     api_key: String,
 }
 
+#[cfg(feature = "fred")]
 impl FredClient {
-    pub fn new() -> Result<Self, FredError> {
+    /// Client with FRED_API_KEY, unless `access` is offline
+    pub fn new(access: FredAccess) -> Result<Self, FredError> {
+        if access == FredAccess::Offline {
+            return Err(FredError::Offline);
+        }
         let api_key = env::var("FRED_API_KEY")
             .map_err(|_| FredError::MissingApiKey)?;
 
         Ok(Self {
-            client: Client::new(),
+            client: http_client(),
             api_key,
         })
     }
 
     pub fn with_api_key(api_key: String) -> Self {
         Self {
-            client: Client::new(),
+            client: http_client(),
             api_key,
         }
     }
@@ -99,11 +251,21 @@ impl FredClient {
         series: FredSeries,
         start_date: Option<NaiveDate>,
         end_date: Option<NaiveDate>,
+    ) -> Result<Vec<(NaiveDate, f64)>, FredError> {
+        self.fetch_series_id(series.series_id(), start_date, end_date).await
+    }
+
+    /// Fetch any FRED series by ID (see `valid_series_id`)
+    pub async fn fetch_series_id(
+        &self,
+        series_id: &str,
+        start_date: Option<NaiveDate>,
+        end_date: Option<NaiveDate>,
     ) -> Result<Vec<(NaiveDate, f64)>, FredError> {
         let mut url = format!(
             "{}?series_id={}&api_key={}&file_type=json",
             FRED_BASE_URL,
-            series.series_id(),
+            series_id,
             self.api_key
         );
 
@@ -115,12 +277,22 @@ impl FredClient {
             url.push_str(&format!("&observation_end={}", end));
         }
 
+        // Errors drop the URL: it carries the API key
         let response = self.client
             .get(&url)
             .send()
             .await
-            .map_err(|e| FredError::NetworkError(e.to_string()))?;
-
+            .map_err(|e| FredError::NetworkError(e.without_url().to_string()))?;
+
+        // FRED answers 400 Bad Request for series IDs it does not know, but
+        // also for a rejected key; only the first is the caller's mistake
+        if response.status() == reqwest::StatusCode::BAD_REQUEST {
+            let message = response.json::<FredErrorResponse>().await.map(|e| e.error_message).unwrap_or_default();
+            if is_unknown_series(&message) {
+                return Err(FredError::UnknownSeries(series_id.to_string()));
+            }
+            return Err(FredError::ApiError(format!("FRED API returned status: 400 Bad Request ({})", message.trim())));
+        }
         if !response.status().is_success() {
             return Err(FredError::ApiError(format!(
                 "FRED API returned status: {}",
@@ -131,7 +303,7 @@ impl FredClient {
         let fred_response: FredResponse = response
             .json()
             .await
-            .map_err(|e| FredError::ParseError(e.to_string()))?;
+            .map_err(|e| FredError::ParseError(e.without_url().to_string()))?;
 
         let mut data = Vec::new();
         for obs in fred_response.observations {
@@ -153,11 +325,76 @@ impl FredClient {
         Ok(data)
     }
 
+    /// Cheap reachability check: the last year of real GDP
+    pub async fn ping(&self) -> Result<usize, FredError> {
+        let since = chrono::Utc::now().date_naive() - chrono::Duration::days(365);
+        self.fetch_series(FredSeries::RealGDP, Some(since), None)
+            .await
+            .map(|observations| observations.len())
+    }
+
+    /// Scheduled release dates on or after `from` for every input series,
+    /// one entry per (release, date); series without a release are skipped
+    pub async fn upcoming_releases(&self, from: NaiveDate) -> Result<Vec<ReleaseDate>, FredError> {
+        let mut releases: Vec<(FredRelease, Vec<&'static str>)> = Vec::new();
+        for series in FredSeries::all().into_iter().chain(FredSeries::optional()) {
+            let url = format!(
+                "{}?series_id={}&api_key={}&file_type=json",
+                FRED_SERIES_RELEASE_URL,
+                series.series_id(),
+                self.api_key
+            );
+            let response: SeriesReleaseResponse = self.get_json(&url).await?;
+            let Some(release) = response.releases.into_iter().next() else {
+                continue;
+            };
+            match releases.iter_mut().find(|(r, _)| r.id == release.id) {
+                Some((_, ids)) => ids.push(series.series_id()),
+                None => releases.push((release, vec![series.series_id()])),
+            }
+        }
+
+        let mut upcoming = Vec::new();
+        for (release, series) in releases {
+            let url = format!(
+                "{}?release_id={}&api_key={}&file_type=json&realtime_start={}&realtime_end=9999-12-31\
+                 &include_release_dates_with_no_data=true&sort_order=asc",
+                FRED_RELEASE_DATES_URL, release.id, self.api_key, from
+            );
+            let response: ReleaseDatesResponse = self.get_json(&url).await?;
+            for d in response.release_dates {
+                let date = NaiveDate::parse_from_str(&d.date, "%Y-%m-%d")
+                    .map_err(|e| FredError::ParseError(e.to_string()))?;
+                if date >= from {
+                    upcoming.push(ReleaseDate { date, release: release.name.clone(), series: series.clone() });
+                }
+            }
+        }
+        upcoming.sort_by_key(|r| r.date);
+        Ok(upcoming)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, FredError> {
+        let response = self.client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| FredError::NetworkError(e.without_url().to_string()))?;
+        if !response.status().is_success() {
+            return Err(FredError::ApiError(format!(
+                "FRED API returned status: {}",
+                response.status()
+            )));
+        }
+        response.json().await.map_err(|e| FredError::ParseError(e.without_url().to_string()))
+    }
+
     /// Fetch all series and merge into EconomicData
     pub async fn fetch_all(
         &self,
         start_date: Option<NaiveDate>,
         end_date: Option<NaiveDate>,
+        partial: PartialDataMode,
     ) -> Result<Vec<EconomicData>, FredError> {
         // Fetch all series concurrently
         let (investment, m2, fed_funds, gdp, capacity, spread, cpi) = tokio::try_join!(
@@ -167,9 +404,58 @@ impl FredClient {
             self.fetch_series(FredSeries::RealGDP, start_date, end_date),
             self.fetch_series(FredSeries::CapacityUtil, start_date, end_date),
             self.fetch_series(FredSeries::YieldSpread, start_date, end_date),
-            self.fetch_series(FredSeries::CPI, start_date, end_date),
+            self.fetch_series(FredSeries::Cpi, start_date, end_date),
         )?;
 
+        // Optional efficiency/slack/nowcast/inflation series: log and continue without them on failure
+        let (rd, education, deflator, potential, unemployment, nairu, ip, retail, core_cpi, pce, breakeven) = tokio::join!(
+            self.fetch_series(FredSeries::RdInvestment, start_date, end_date),
+            self.fetch_series(FredSeries::Education, start_date, end_date),
+            self.fetch_series(FredSeries::GdpDeflator, start_date, end_date),
+            self.fetch_series(FredSeries::PotentialGdp, start_date, end_date),
+            self.fetch_series(FredSeries::Unemployment, start_date, end_date),
+            self.fetch_series(FredSeries::Nairu, start_date, end_date),
+            self.fetch_series(FredSeries::IndustrialProduction, start_date, end_date),
+            self.fetch_series(FredSeries::RetailSales, start_date, end_date),
+            self.fetch_series(FredSeries::CoreCpi, start_date, end_date),
+            self.fetch_series(FredSeries::Pce, start_date, end_date),
+            self.fetch_series(FredSeries::Breakeven, start_date, end_date),
+        );
+        let rd = Self::optional_series(FredSeries::RdInvestment, rd);
+        let education = Self::optional_series(FredSeries::Education, education);
+        let deflator = Self::optional_series(FredSeries::GdpDeflator, deflator);
+        let potential = Self::optional_series(FredSeries::PotentialGdp, potential);
+        let unemployment = Self::optional_series(FredSeries::Unemployment, unemployment);
+        let nairu = Self::optional_series(FredSeries::Nairu, nairu);
+        let proxies = nowcast::Proxies {
+            industrial_production: Self::optional_series(FredSeries::IndustrialProduction, ip),
+            retail_sales: Self::optional_series(FredSeries::RetailSales, retail),
+        };
+        let core_cpi_map: HashMap<NaiveDate, f64> =
+            Self::optional_series(FredSeries::CoreCpi, core_cpi).into_iter().collect();
+        let pce_map: HashMap<NaiveDate, f64> = Self::optional_series(FredSeries::Pce, pce).into_iter().collect();
+        let breakeven = Self::optional_series(FredSeries::Breakeven, breakeven);
+        let mut gdp_releases = gdp.clone();
+        gdp_releases.sort_by_key(|(d, _)| *d);
+
+        // Last month each required series covers, for the ragged edge
+        let observed: Vec<(InputSeries, NaiveDate)> = [
+            (InputSeries::Investment, &investment),
+            (InputSeries::M2Supply, &m2),
+            (InputSeries::FedFundsRate, &fed_funds),
+            (InputSeries::Gdp, &gdp_releases),
+            (InputSeries::CapacityUtil, &capacity),
+            (InputSeries::YieldSpread, &spread),
+            (InputSeries::CpiInflation, &cpi),
+        ]
+        .into_iter()
+        .filter_map(|(series, observations)| {
+            let mut observations = observations.clone();
+            observations.sort_by_key(|(d, _)| *d);
+            coverage::observed_through(&observations).map(|through| (series, through))
+        })
+        .collect();
+
         // Convert to hashmaps for merging
         let investment_map: HashMap<NaiveDate, f64> = investment.into_iter().collect();
         let m2_map: HashMap<NaiveDate, f64> = m2.into_iter().collect();
@@ -210,8 +496,22 @@ impl FredClient {
                 .or_else(|| Self::find_nearest(&cpi_map, date))
                 .unwrap_or(last_values.cpi);
 
+            // Quarterly/annual series carry forward from their latest observation
+            let rd_investment = Self::latest_on_or_before(&rd, date);
+            // Nominal education spending in GDPDEF's base-year dollars, as GDPC1
+            let education_spending = Self::latest_on_or_before(&education, date)
+                .zip(Self::latest_on_or_before(&deflator, date).filter(|index| *index > 0.0))
+                .map(|(nominal, index)| nominal / index * 100.0);
+            let potential_gdp = Self::latest_on_or_before(&potential, date);
+            let unemployment_rate = Self::latest_on_or_before(&unemployment, date);
+            let nairu = Self::latest_on_or_before(&nairu, date);
+
             // Calculate YoY inflation from CPI
             let inflation = Self::calculate_yoy_change(&cpi_map, date).unwrap_or(2.5);
+            let core_cpi_inflation = Self::calculate_yoy_change(&core_cpi_map, date);
+            let pce_inflation = Self::calculate_yoy_change(&pce_map, date);
+            // Daily breakeven: latest trading day at or before the month
+            let expected_inflation = Self::latest_on_or_before(&breakeven, date);
 
             // Update last values
             last_values = LastValues {
@@ -238,12 +538,55 @@ impl FredClient {
                 capacity_util: cap,
                 yield_spread: spr,
                 cpi_inflation: inflation,
+                rd_investment,
+                education_spending,
+                potential_gdp,
+                unemployment_rate,
+                nairu,
+                gdp_nowcast: None,
+                core_cpi_inflation,
+                pce_inflation,
+                expected_inflation,
+                estimated: Vec::new(),
             });
         }
 
+        let estimated_months = coverage::fill(&mut result, &observed, partial);
+        if estimated_months > 0 {
+            tracing::warn!("{} months estimate at least one input ({:?} partial data)", estimated_months, partial);
+        }
+
+        let nowcast_months = nowcast::fill(&mut result, &gdp_releases, &proxies);
+        if nowcast_months > 0 {
+            tracing::info!("GDP nowcast covers {} months after the latest GDPC1 release", nowcast_months);
+        }
+
         Ok(result)
     }
 
+    /// Unwrap an optional series, treating errors as "unavailable"
+    fn optional_series(
+        series: FredSeries,
+        fetched: Result<Vec<(NaiveDate, f64)>, FredError>,
+    ) -> Vec<(NaiveDate, f64)> {
+        match fetched {
+            Ok(mut data) => {
+                data.sort_by_key(|(d, _)| *d);
+                data
+            }
+            Err(e) => {
+                tracing::warn!("Optional series {} unavailable: {}", series.series_id(), e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Latest observation at or before `date` in a date-sorted series
+    fn latest_on_or_before(series: &[(NaiveDate, f64)], date: NaiveDate) -> Option<f64> {
+        let idx = series.partition_point(|(d, _)| *d <= date);
+        idx.checked_sub(1).map(|i| series[i].1)
+    }
+
     /// Find nearest date value in a hashmap
     fn find_nearest(map: &HashMap<NaiveDate, f64>, target: NaiveDate) -> Option<f64> {
         let mut closest: Option<(i64, f64)> = None;
@@ -294,6 +637,8 @@ struct LastValues {
 #[derive(Debug)]
 pub enum FredError {
     MissingApiKey,
+    Offline,
+    UnknownSeries(String),
     NetworkError(String),
     ApiError(String),
     ParseError(String),
@@ -303,6 +648,8 @@ impl std::fmt::Display for FredError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FredError::MissingApiKey => write!(f, "FRED_API_KEY environment variable not set"),
+            FredError::Offline => write!(f, "FRED access is disabled on this server"),
+            FredError::UnknownSeries(id) => write!(f, "FRED has no series '{}'", id),
             FredError::NetworkError(e) => write!(f, "Network error: {}", e),
             FredError::ApiError(e) => write!(f, "FRED API error: {}", e),
             FredError::ParseError(e) => write!(f, "Parse error: {}", e),
@@ -454,7 +801,7 @@ pub mod mock {
                 }
 
                 // 2020 COVID specific
-                if year == 2020 && (month >= 3 && month <= 5) {
+                if year == 2020 && (3..=5).contains(&month) {
                     investment *= 0.70;
                     gdp *= 0.90;
                     capacity = 64.0 + (month - 3) as f64 * 3.0;
@@ -474,6 +821,16 @@ pub mod mock {
                     capacity_util: capacity,
                     yield_spread,
                     cpi_inflation,
+                    rd_investment: None,
+                    education_spending: None,
+                    potential_gdp: None,
+                    unemployment_rate: None,
+                    nairu: None,
+                    gdp_nowcast: None,
+                    core_cpi_inflation: None,
+                    pce_inflation: None,
+                    expected_inflation: None,
+                    estimated: Vec::new(),
                 });
             }
         }
@@ -498,7 +855,7 @@ pub mod mock {
             // 1973-75 Oil Crisis
             (1973, 11..=12) | (1974, 1..=12) | (1975, 1..=3) |
             // 1969-70
-            (1969, 12..=12) | (1970, 1..=11)
+            (1969, 12) | (1970, 1..=11)
         )
     }
 }
@@ -506,6 +863,16 @@ pub mod mock {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Datelike;
+
+    #[test]
+    fn test_only_missing_series_is_unknown() {
+        assert!(is_unknown_series("Bad Request.  The series does not exist."));
+        assert!(!is_unknown_series(
+            "Bad Request.  The value for variable api_key is not registered.  Read https://fred.stlouisfed.org/docs/api/api_key.html for more information."
+        ));
+        assert!(!is_unknown_series(""));
+    }
 
     #[test]
     fn test_mock_data_generation() {
@@ -0,0 +1,192 @@
+//! FRED Passthrough
+//!
+//! Serves arbitrary FRED series at `/api/v1/fred/{series_id}` so browser
+//! what-if tools can chart raw inputs without holding the FRED key:
+//! - full series are cached per ID for CACHE_TTL, bounded at CACHE_BYTES of
+//!   observations; date filters slice the cache
+//! - unknown series and upstream failures are cached for FAILURE_TTL so a
+//!   repeated bad ID does not spend a request each time
+//! - concurrent misses for one series share a single upstream request
+//! - upstream requests are rate limited (NIV_FRED_PROXY_RPM, default
+//!   DEFAULT_REQUESTS_PER_MINUTE, below FRED's 120/minute per key so the
+//!   server's own refreshes keep headroom); over the limit callers get a
+//!   429 with Retry-After instead of spending the key's quota
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use moka::future::Cache;
+
+use crate::fred::{FredClient, FredError};
+
+pub const DEFAULT_REQUESTS_PER_MINUTE: usize = 100;
+pub const CACHE_TTL: Duration = Duration::from_secs(3600);
+pub const FAILURE_TTL: Duration = Duration::from_secs(60);
+const CACHE_BYTES: u64 = 64 * 1024 * 1024;
+const FAILURE_CAPACITY: u64 = 1000; // Series IDs
+const MAX_SERIES_ID_LEN: usize = 32;
+
+pub type Observations = Arc<Vec<(NaiveDate, f64)>>;
+
+/// FRED series IDs are upper-case alphanumerics and underscores
+pub fn valid_series_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_SERIES_ID_LEN
+        && id.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+}
+
+/// Approximate bytes held by one cached series, for the cache weigher
+fn weigh(series_id: &str, observations: &Observations) -> u32 {
+    let bytes = series_id.len() + observations.len() * std::mem::size_of::<(NaiveDate, f64)>();
+    u32::try_from(bytes).unwrap_or(u32::MAX)
+}
+
+/// Sliding-window limit on upstream requests
+pub struct RateLimiter {
+    limit: usize,
+    window: Duration,
+    hits: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self { limit, window, hits: Mutex::new(VecDeque::new()) }
+    }
+
+    /// Take a slot, returning the slots left; Err(wait) when the window is full
+    pub fn try_acquire(&self, now: Instant) -> Result<usize, Duration> {
+        let mut hits = self.hits.lock().expect("rate limiter lock");
+        while hits.front().is_some_and(|&t| now.duration_since(t) >= self.window) {
+            hits.pop_front();
+        }
+        if hits.len() >= self.limit {
+            let oldest = *hits.front().expect("full window is non-empty");
+            return Err(self.window.saturating_sub(now.duration_since(oldest)));
+        }
+        hits.push_back(now);
+        Ok(self.limit - hits.len())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ProxyError {
+    RateLimited(Duration), // Retry after
+    NotFound(String),
+    Upstream(String),
+}
+
+impl std::fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyError::RateLimited(wait) => {
+                write!(f, "FRED request quota exhausted; retry in {}s", wait.as_secs().max(1))
+            }
+            ProxyError::NotFound(e) | ProxyError::Upstream(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProxyError {}
+
+/// Cached, rate-limited access to FRED series
+pub struct FredProxy {
+    client: FredClient,
+    cache: Cache<String, Observations>,
+    failures: Cache<String, ProxyError>,
+    limiter: RateLimiter,
+}
+
+impl FredProxy {
+    pub fn new(client: FredClient, requests_per_minute: usize) -> Self {
+        Self {
+            client,
+            cache: Cache::builder()
+                .max_capacity(CACHE_BYTES)
+                .weigher(|id: &String, observations: &Observations| weigh(id, observations))
+                .time_to_live(CACHE_TTL)
+                .build(),
+            failures: Cache::builder().max_capacity(FAILURE_CAPACITY).time_to_live(FAILURE_TTL).build(),
+            limiter: RateLimiter::new(requests_per_minute, Duration::from_secs(60)),
+        }
+    }
+
+    /// Full history of `series_id` and whether it came from the cache
+    pub async fn series(&self, series_id: &str) -> Result<(Observations, bool), ProxyError> {
+        if let Some(cached) = self.cache.get(series_id).await {
+            return Ok((cached, true));
+        }
+        if let Some(failure) = self.failures.get(series_id).await {
+            return Err(failure);
+        }
+        let observations = self
+            .cache
+            .try_get_with(series_id.to_string(), async {
+                self.limiter.try_acquire(Instant::now()).map_err(ProxyError::RateLimited)?;
+                self.client
+                    .fetch_series_id(series_id, None, None)
+                    .await
+                    .map(Arc::new)
+                    .map_err(|e| match e {
+                        FredError::UnknownSeries(_) => ProxyError::NotFound(e.to_string()),
+                        e => ProxyError::Upstream(e.to_string()),
+                    })
+            })
+            .await
+            .map_err(|e| (*e).clone());
+        match observations {
+            Ok(observations) => Ok((observations, false)),
+            Err(ProxyError::RateLimited(wait)) => Err(ProxyError::RateLimited(wait)),
+            Err(e) => {
+                // Held briefly: a fix upstream or a new series shows up soon
+                self.failures.insert(series_id.to_string(), e.clone()).await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_series_id() {
+        assert!(valid_series_id("T10Y3M"));
+        assert!(valid_series_id("Y694RC1Q027SBEA"));
+        assert!(valid_series_id("BAMLH0A0HYM2_X"));
+        assert!(!valid_series_id(""));
+        assert!(!valid_series_id("gdp"));
+        assert!(!valid_series_id("GDP&api_key=x"));
+        assert!(!valid_series_id(&"A".repeat(33)));
+    }
+
+    #[test]
+    fn test_weigh_counts_observations() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let small = weigh("GDP", &Arc::new(vec![(date, 1.0)]));
+        let large = weigh("GDP", &Arc::new(vec![(date, 1.0); 1000]));
+        assert!(large > small * 500);
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_served_from_cache() {
+        let proxy = FredProxy::new(FredClient::with_api_key("test".to_string()), 0);
+        proxy.failures.insert("NOPE".to_string(), ProxyError::NotFound("FRED has no series 'NOPE'".to_string())).await;
+
+        // A zero quota would answer 429 if the lookup reached upstream
+        assert!(matches!(proxy.series("NOPE").await, Err(ProxyError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_rate_limiter_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let t0 = Instant::now();
+
+        assert_eq!(limiter.try_acquire(t0), Ok(1));
+        assert_eq!(limiter.try_acquire(t0 + Duration::from_secs(10)), Ok(0));
+        assert_eq!(limiter.try_acquire(t0 + Duration::from_secs(20)), Err(Duration::from_secs(40)));
+        assert_eq!(limiter.try_acquire(t0 + Duration::from_secs(60)), Ok(0));
+    }
+}
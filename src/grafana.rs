@@ -0,0 +1,196 @@
+//! Grafana Datasource Facade
+//!
+//! Speaks the SimpleJSON protocol (also readable by the Infinity plugin) so
+//! NIV can be charted in an existing Grafana without custom panels:
+//! - GET  /grafana           - connection test
+//! - POST /grafana/search    - metric names, filtered by prefix
+//! - POST /grafana/query     - time series as [value, epoch_ms] pairs
+//! - POST /grafana/annotations - NBER recessions as region annotations
+//!
+//! Probabilities are in percent, matching the REST API.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::niv::{NIVResult, RecessionPeriods};
+
+/// Metrics offered to Grafana
+pub const METRICS: [&str; 9] = [
+    "niv_score",
+    "niv_percentile",
+    "recession_probability",
+    "thrust",
+    "efficiency",
+    "slack",
+    "drag",
+    "fed_probability", // Yield-curve benchmark, as in /api/v1/compare
+    "is_recession",    // 1 inside an NBER recession, else 0
+];
+
+#[derive(Debug, Deserialize)]
+pub struct TimeRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Target {
+    #[serde(default)]
+    pub target: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    pub range: Option<TimeRange>,
+    #[serde(default)]
+    pub targets: Vec<Target>,
+    pub max_data_points: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchRequest {
+    #[serde(default)]
+    pub target: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnnotationRequest {
+    pub range: Option<TimeRange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimeSeries {
+    pub target: String,
+    pub datapoints: Vec<(f64, i64)>, // [value, epoch ms]
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    pub time: i64,
+    pub time_end: i64,
+    pub is_region: bool,
+    pub title: String,
+    pub tags: Vec<&'static str>,
+}
+
+/// Metric value for one month; `fed_probability` is the benchmark (0-1)
+pub fn value(metric: &str, r: &NIVResult, fed_probability: f64) -> Option<f64> {
+    Some(match metric {
+        "niv_score" => r.niv_score,
+        "niv_percentile" => r.niv_percentile,
+        "recession_probability" => r.recession_probability * 100.0,
+        "thrust" => r.components.thrust,
+        "efficiency" => r.components.efficiency,
+        "slack" => r.components.slack,
+        "drag" => r.components.drag,
+        "fed_probability" => fed_probability * 100.0,
+        "is_recession" => RecessionPeriods::is_recession(r.date) as u8 as f64,
+        _ => return None,
+    })
+}
+
+/// Metric names starting with `prefix`
+pub fn search(prefix: &str) -> Vec<&'static str> {
+    METRICS.into_iter().filter(|m| m.starts_with(prefix)).collect()
+}
+
+fn epoch_ms(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0).map_or(0, |t| t.and_utc().timestamp_millis())
+}
+
+fn in_range(range: Option<&TimeRange>, date: NaiveDate) -> bool {
+    range.is_none_or(|r| {
+        let t = epoch_ms(date);
+        t >= r.from.timestamp_millis() && t <= r.to.timestamp_millis()
+    })
+}
+
+/// Answer a query; `fed` is parallel to `results`. Errors name an unknown metric.
+pub fn query(request: &QueryRequest, results: &[NIVResult], fed: &[f64]) -> Result<Vec<TimeSeries>, String> {
+    request
+        .targets
+        .iter()
+        .filter(|t| !t.target.is_empty())
+        .map(|t| {
+            if !METRICS.contains(&t.target.as_str()) {
+                return Err(format!("unknown metric '{}' (expected one of {})", t.target, METRICS.join(", ")));
+            }
+            let mut datapoints: Vec<(f64, i64)> = results
+                .iter()
+                .zip(fed)
+                .filter(|(r, _)| in_range(request.range.as_ref(), r.date))
+                .filter_map(|(r, &f)| value(&t.target, r, f).map(|v| (v, epoch_ms(r.date))))
+                .collect();
+            // Keep the most recent points when Grafana asks for fewer
+            if let Some(max) = request.max_data_points.filter(|&m| m > 0 && m < datapoints.len()) {
+                datapoints.drain(..datapoints.len() - max);
+            }
+            Ok(TimeSeries { target: t.target.clone(), datapoints })
+        })
+        .collect()
+}
+
+/// NBER recessions overlapping the range, as shaded regions
+pub fn annotations(request: &AnnotationRequest) -> Vec<Annotation> {
+    RecessionPeriods::known_recessions()
+        .into_iter()
+        .map(|(start, end)| (epoch_ms(start), epoch_ms(end), start))
+        .filter(|&(time, time_end, _)| {
+            request
+                .range
+                .as_ref()
+                .is_none_or(|r| time <= r.to.timestamp_millis() && time_end >= r.from.timestamp_millis())
+        })
+        .map(|(time, time_end, start)| Annotation {
+            time,
+            time_end,
+            is_region: true,
+            title: format!("NBER recession ({})", start.format("%b %Y")),
+            tags: vec!["recession"],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_query_filters_range_and_rejects_unknown_metrics() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2005, 2012));
+        let fed = vec![0.25; results.len()];
+        let request: QueryRequest = serde_json::from_value(serde_json::json!({
+            "range": { "from": "2008-01-01T00:00:00Z", "to": "2008-12-31T00:00:00Z" },
+            "targets": [{ "target": "drag", "refId": "A" }, { "target": "fed_probability", "refId": "B" }],
+            "maxDataPoints": 500
+        }))
+        .unwrap();
+
+        let series = query(&request, &results, &fed).unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].datapoints.len(), 12);
+        assert_eq!(series[1].datapoints[0].0, 25.0);
+        assert_eq!(series[0].datapoints[0].1, epoch_ms(NaiveDate::from_ymd_opt(2008, 1, 1).unwrap()));
+
+        let bad: QueryRequest = serde_json::from_value(serde_json::json!({ "targets": [{ "target": "vibes" }] })).unwrap();
+        assert!(query(&bad, &results, &fed).is_err());
+    }
+
+    #[test]
+    fn test_search_and_annotations() {
+        assert_eq!(search("niv"), vec!["niv_score", "niv_percentile"]);
+        assert_eq!(search("").len(), METRICS.len());
+
+        let request: AnnotationRequest = serde_json::from_value(serde_json::json!({
+            "range": { "from": "2008-06-01T00:00:00Z", "to": "2021-01-01T00:00:00Z" }
+        }))
+        .unwrap();
+        let regions = annotations(&request);
+        assert_eq!(regions.len(), 2); // Great Recession and COVID
+        assert!(regions.iter().all(|a| a.is_region && a.time_end > a.time));
+    }
+}
@@ -0,0 +1,192 @@
+//! Refresh Incidents
+//!
+//! A refresh computes the new dataset before installing it. These checks run
+//! on it first; when any fails the swap is blocked, the serving data stays as
+//! it was, and an incident records why:
+//! - the self-test invariants (selftest.rs) over the new results
+//! - non-finite inputs: the series and months where a value was NaN or ±Inf
+//! - lost history: months the installed inputs have that the new ones lack
+//!
+//! Each incident carries the failed checks, the offending series and months,
+//! and a suggested action. The newest MAX_INCIDENTS are kept in memory for
+//! `/api/v1/admin/incidents`; each is logged and, with NIV_INCIDENT_WEBHOOK
+//! set, POSTed there as JSON.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::niv::{EconomicData, NIVResult};
+use crate::replay::WebhookClient;
+use crate::selftest;
+
+/// Incidents kept for the admin endpoint
+pub const MAX_INCIDENTS: usize = 50;
+
+/// Months listed per offending series; the count is always complete
+const MAX_LISTED_MONTHS: usize = 24;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FailedCheck {
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// A series and the months that tripped a check
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Offending {
+    pub series: String,
+    pub check: String,
+    pub count: usize,
+    pub months: Vec<NaiveDate>, // First MAX_LISTED_MONTHS
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Incident {
+    pub id: String,
+    pub at: DateTime<Utc>,
+    pub model_version: String,
+    pub summary: String,
+    pub failures: Vec<FailedCheck>,
+    pub offending: Vec<Offending>,
+    pub suggested_action: String,
+}
+
+/// FRED IDs of the required inputs, in `required_values` order
+const REQUIRED_SERIES: [&str; 7] = ["GPDIC1", "M2SL", "FEDFUNDS", "GDPC1", "TCU", "T10Y3M", "CPIAUCSL"];
+
+fn required_values(d: &EconomicData) -> [f64; 7] {
+    [d.investment, d.m2_supply, d.fed_funds_rate, d.gdp, d.capacity_util, d.yield_spread, d.cpi_inflation]
+}
+
+fn offending(series: &str, check: &str, mut months: Vec<NaiveDate>) -> Offending {
+    let count = months.len();
+    months.truncate(MAX_LISTED_MONTHS);
+    Offending { series: series.to_string(), check: check.to_string(), count, months }
+}
+
+/// Run the swap checks on a refresh's `inputs` and `results` against the
+/// `installed` inputs
+pub fn check(installed: &[EconomicData], inputs: &[EconomicData], results: &[NIVResult]) -> (Vec<FailedCheck>, Vec<Offending>) {
+    let mut failures: Vec<FailedCheck> = selftest::invariants(results)
+        .into_iter()
+        .filter(|c| !c.passed)
+        .map(|c| FailedCheck { name: c.name, expected: c.expected, actual: c.actual })
+        .collect();
+    let mut found = Vec::new();
+
+    for (i, name) in REQUIRED_SERIES.iter().enumerate() {
+        let months: Vec<NaiveDate> =
+            inputs.iter().filter(|d| !required_values(d)[i].is_finite()).map(|d| d.date).collect();
+        if !months.is_empty() {
+            found.push(offending(name, "Finite inputs", months));
+        }
+    }
+    if !found.is_empty() {
+        failures.push(FailedCheck {
+            name: "Finite inputs".to_string(),
+            expected: "Every required input finite".to_string(),
+            actual: format!("{} series with non-finite values", found.len()),
+        });
+    }
+
+    let present: HashSet<NaiveDate> = inputs.iter().map(|d| d.date).collect();
+    let lost: Vec<NaiveDate> = installed.iter().map(|d| d.date).filter(|d| !present.contains(d)).collect();
+    if !lost.is_empty() {
+        failures.push(FailedCheck {
+            name: "History retained".to_string(),
+            expected: "Every installed month still present".to_string(),
+            actual: format!("{} months missing", lost.len()),
+        });
+        found.push(offending("*", "History retained", lost));
+    }
+    (failures, found)
+}
+
+fn suggested_action(failures: &[FailedCheck]) -> String {
+    let failed = |name: &str| failures.iter().any(|f| f.name == name);
+    if failed("Finite inputs") {
+        "Check the listed FRED series for missing or malformed observations in the listed months, then refresh again"
+    } else if failed("History retained") {
+        "The source returned a shorter history; check the FRED observation_start and series availability before refreshing"
+    } else {
+        "The engine produced out-of-bounds results on these inputs; compare them with the serving inputs and run the self-test"
+    }
+    .to_string()
+}
+
+impl Incident {
+    /// An incident when any check failed; None lets the swap proceed
+    pub fn new(model_version: &str, at: DateTime<Utc>, failures: Vec<FailedCheck>, offending: Vec<Offending>) -> Option<Self> {
+        if failures.is_empty() {
+            return None;
+        }
+        let names: Vec<&str> = failures.iter().map(|f| f.name.as_str()).collect();
+        Some(Self {
+            id: format!("inc-{}-{:04x}", at.format("%Y%m%dT%H%M%S"), rand::random::<u16>()),
+            at,
+            model_version: model_version.to_string(),
+            summary: format!("Refresh blocked: {} failed", names.join(", ")),
+            suggested_action: suggested_action(&failures),
+            failures,
+            offending,
+        })
+    }
+}
+
+/// POST the incident to the webhook, if any; a failed delivery is logged
+/// and returned
+#[cfg(feature = "webhooks")]
+pub async fn notify(http: &WebhookClient, webhook: Option<&str>, incident: &Incident) -> Result<(), String> {
+    let Some(url) = webhook else { return Ok(()) };
+    let failure = match http.post(url).json(incident).send().await {
+        Ok(resp) if resp.status().is_success() => return Ok(()),
+        Ok(resp) => format!("Incident webhook {} returned {}", url, resp.status()),
+        Err(e) => format!("Incident webhook {} failed: {}", url, e),
+    };
+    tracing::warn!("{}", failure);
+    Err(failure)
+}
+
+/// Built without the `webhooks` feature: the incident is only logged
+#[cfg(not(feature = "webhooks"))]
+pub async fn notify(_http: &WebhookClient, _webhook: Option<&str>, _incident: &Incident) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_clean_refresh_passes() {
+        let inputs = mock::generate_mock_data(2000, 2010);
+        let results = NIVEngine::new().calculate_series(&inputs);
+        let (failures, offending) = check(&inputs, &inputs, &results);
+
+        assert!(failures.is_empty() && offending.is_empty());
+        assert!(Incident::new("v", Utc::now(), failures, offending).is_none());
+    }
+
+    #[test]
+    fn test_blocked_refresh_names_series_and_months() {
+        let installed = mock::generate_mock_data(2000, 2010);
+        let mut inputs = installed[..installed.len() - 2].to_vec();
+        inputs[5].yield_spread = f64::NAN;
+        let results = NIVEngine::new().calculate_series(&inputs);
+        let (failures, offending) = check(&installed, &inputs, &results);
+
+        let spread = offending.iter().find(|o| o.series == "T10Y3M").unwrap();
+        assert_eq!((spread.count, spread.months[0]), (1, inputs[5].date));
+        let lost = offending.iter().find(|o| o.check == "History retained").unwrap();
+        assert_eq!(lost.months, installed[installed.len() - 2..].iter().map(|d| d.date).collect::<Vec<_>>());
+
+        let incident = Incident::new("v", Utc::now(), failures, offending).unwrap();
+        assert!(incident.summary.contains("Finite inputs") && incident.summary.contains("History retained"));
+        assert!(incident.suggested_action.contains("FRED series"));
+    }
+}
@@ -0,0 +1,315 @@
+//! Interval Propagation
+//!
+//! Quick uncertainty ranges without resampling: per-input measurement
+//! uncertainty (a ± half-width in each series' own units, e.g. ±0.2pp on TCU)
+//! is carried through the master formula with interval arithmetic, giving an
+//! NIV and recession-probability range for every month.
+//!
+//! Each component is monotone in each of its inputs, so its range is taken
+//! from the engine's own component functions at the interval endpoints; the
+//! components then combine through u × P² / (X + F + ε)^η with interval
+//! operations. Ranges are conservative: errors in different inputs and months
+//! are assumed to line up in the worst direction. Registry terms and the
+//! thrust scale enter as points. Smoothed ranges are window means of the raw
+//! endpoints, aligned with `NIVEngine::smooth`.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Sub};
+
+use crate::niv::{
+    self, DenominatorPolicy, EconomicData, NIVEngine, ProbabilityInput, M2_ACCEL_LAG, NIV_CLAMP,
+};
+use crate::registry::TermRole;
+
+/// Closed interval [lo, hi]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    pub const UNBOUNDED: Interval = Interval { lo: f64::NEG_INFINITY, hi: f64::INFINITY };
+
+    pub fn point(value: f64) -> Self {
+        Self { lo: value, hi: value }
+    }
+
+    pub fn around(value: f64, half_width: f64) -> Self {
+        Self { lo: value - half_width, hi: value + half_width }
+    }
+
+    /// Smallest interval holding every value (NaNs ignored)
+    fn hull(values: impl IntoIterator<Item = f64>) -> Self {
+        let (lo, hi) = values
+            .into_iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        if lo <= hi { Self { lo, hi } } else { Self::UNBOUNDED }
+    }
+
+    /// Image under a non-decreasing function
+    fn map(self, f: impl Fn(f64) -> f64) -> Self {
+        Self { lo: f(self.lo), hi: f(self.hi) }
+    }
+
+    fn scale(self, k: f64) -> Self {
+        self.mul(Self::point(k))
+    }
+
+    fn mul(self, other: Self) -> Self {
+        // 0 × ∞ is 0 here: a zero weight removes an unbounded term
+        let times = |a: f64, b: f64| if a == 0.0 || b == 0.0 { 0.0 } else { a * b };
+        Self::hull([
+            times(self.lo, other.lo),
+            times(self.lo, other.hi),
+            times(self.hi, other.lo),
+            times(self.hi, other.hi),
+        ])
+    }
+
+    /// Unbounded when the divisor contains zero
+    fn div(self, other: Self) -> Self {
+        if other.lo > 0.0 || other.hi < 0.0 {
+            Self::hull([self.lo / other.lo, self.lo / other.hi, self.hi / other.lo, self.hi / other.hi])
+        } else {
+            Self::UNBOUNDED
+        }
+    }
+
+    fn square(self) -> Self {
+        if self.lo >= 0.0 {
+            Self { lo: self.lo.powi(2), hi: self.hi.powi(2) }
+        } else if self.hi <= 0.0 {
+            Self { lo: self.hi.powi(2), hi: self.lo.powi(2) }
+        } else {
+            Self { lo: 0.0, hi: self.lo.powi(2).max(self.hi.powi(2)) }
+        }
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Self) -> Self {
+        Self { lo: self.lo + other.lo, hi: self.hi + other.hi }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Self) -> Self {
+        Self { lo: self.lo - other.hi, hi: self.hi - other.lo }
+    }
+}
+
+/// Measurement uncertainty: ± half-width per input series, in its own units
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct InputUncertainty {
+    pub investment: f64,     // GPDIC1, billions of chained dollars
+    pub m2_supply: f64,      // M2SL, billions of dollars
+    pub fed_funds_rate: f64, // FEDFUNDS, percentage points
+    pub gdp: f64,            // Efficiency denominator (reported GDP or the nowcast), billions
+    pub capacity_util: f64,  // TCU, percentage points
+    pub yield_spread: f64,   // T10Y3M, percentage points
+    pub inflation: f64,      // The engine's inflation measure, percentage points
+}
+
+impl InputUncertainty {
+    pub fn is_valid(&self) -> bool {
+        [
+            self.investment, self.m2_supply, self.fed_funds_rate, self.gdp,
+            self.capacity_util, self.yield_spread, self.inflation,
+        ]
+        .iter()
+        .all(|w| w.is_finite() && *w >= 0.0)
+    }
+}
+
+/// NIV and probability range for one month
+#[derive(Debug, Clone, Serialize)]
+pub struct IntervalResult {
+    pub date: NaiveDate,
+    pub niv_score: Interval,
+    // None under ProbabilityInput::Percentile, whose rank transform has no interval form
+    pub recession_probability: Option<Interval>,
+}
+
+/// % change from `base` to `current`; zero for a non-positive base, as in the engine
+fn growth(current: Interval, base: Interval) -> Interval {
+    let pct = |c: f64, b: f64| ((c - b) / b) * 100.0;
+    if base.lo > 0.0 {
+        Interval { lo: pct(current.lo, base.hi), hi: pct(current.hi, base.lo) }
+    } else if base.hi <= 0.0 {
+        Interval::point(0.0)
+    } else {
+        Interval::UNBOUNDED
+    }
+}
+
+/// Raw per-month ranges, aligned with `engine.calculate_raw(data)`
+pub fn calculate_raw(engine: &NIVEngine, data: &[EconomicData], u: &InputUncertainty) -> Vec<IntervalResult> {
+    if data.len() < 13 {
+        return Vec::new();
+    }
+    let extended = engine.compute_extended_data(data);
+    let (data, _) = niv::sanitize_inputs(data);
+    let w = engine.weights();
+    let (eps, eta) = (engine.epsilon(), engine.eta());
+    let power = |base: f64| match engine.denominator_policy() {
+        DenominatorPolicy::Reflect if base < 0.0 => -(-base).max(eps).powf(eta),
+        _ => base.max(eps).powf(eta),
+    };
+    let investment = |j: usize| Interval::around(data[j].investment, u.investment);
+    let m2 = |j: usize| Interval::around(data[j].m2_supply, u.m2_supply);
+    let fed_funds = |j: usize| Interval::around(data[j].fed_funds_rate, u.fed_funds_rate);
+    let spread = |j: usize| Interval::around(data[j].yield_spread, u.yield_spread);
+
+    extended
+        .iter()
+        .enumerate()
+        .map(|(k, ext)| {
+            let i = k + 12;
+            let d = &ext.base;
+
+            // Thrust: growth rates from the bracketing months, then tanh (increasing)
+            let dg = growth(investment(i), investment(i - 1));
+            let da = growth(m2(i), m2(i - 12));
+            let m2_accel = match i.checked_sub(M2_ACCEL_LAG + 12) {
+                Some(j) if data[j].m2_supply > 0.0 => da - growth(m2(i - M2_ACCEL_LAG), m2(j)),
+                _ => Interval::point(0.0),
+            };
+            let dr = fed_funds(i) - fed_funds(i - 1);
+            let input = dg.scale(w.thrust_dg) + da.scale(w.thrust_da) - dr.scale(w.thrust_dr)
+                + m2_accel.scale(w.thrust_m2_accel);
+            let thrust = input.map(|x| (x / ext.thrust_scale).tanh());
+
+            // Efficiency: numerator increasing in investment, over the GDP range
+            let with_investment = |x: f64| engine.efficiency_numerator(&EconomicData { investment: x, ..d.clone() });
+            let numerator = investment(i).map(with_investment);
+            let gdp = Interval::around(engine.gdp(d).0, u.gdp);
+            let efficiency = if gdp.lo > 0.0 {
+                numerator.div(gdp)
+            } else if gdp.hi <= 0.0 {
+                Interval::point(0.0)
+            } else {
+                Interval::UNBOUNDED
+            };
+
+            // Slack: monotone in TCU and GDP, so the corners bound it
+            let tcu = Interval::around(d.capacity_util, u.capacity_util);
+            let slack = Interval::hull([tcu.lo, tcu.hi].into_iter().flat_map(|c| {
+                [gdp.lo, gdp.hi].map(|g| engine.slack(&EconomicData { capacity_util: c, gdp: g, ..d.clone() }))
+            }));
+
+            // Drag: spread penalty non-increasing in the level and the 12-month change
+            let level = spread(i);
+            let change = level - spread(i - 12);
+            let spec = engine.spread_spec();
+            let drag_spread = Interval::hull(
+                [level.lo, level.hi].into_iter().flat_map(|s| [change.lo, change.hi].map(|c| spec.penalty(s, c))),
+            );
+            let real_rate = fed_funds(i) - Interval::around(engine.inflation(d), u.inflation);
+            let drag_real_rate = real_rate.map(|r| r.max(0.0) / 100.0);
+            // A ±u shift per month moves the sample std dev by at most u·√(n/(n-1))
+            let sigma_shift = u.fed_funds_rate * (12.0f64 / 11.0).sqrt();
+            let sigma = Interval { lo: (ext.sigma_r - sigma_shift).max(0.0), hi: ext.sigma_r + sigma_shift };
+            let drag_volatility = sigma.map(|s| s / 100.0);
+            let drag = drag_spread.scale(w.drag_spread)
+                + drag_real_rate.scale(w.drag_real_rate)
+                + drag_volatility.scale(w.drag_volatility);
+
+            // Master formula; registry terms are points
+            let mut top = thrust.mul(efficiency.square());
+            let mut base = slack + drag + Interval::point(eps);
+            for term in &ext.custom {
+                match (term.role, term.value) {
+                    (TermRole::Numerator, Some(v)) => top = top.scale(v),
+                    (TermRole::Denominator, Some(v)) => base = base + Interval::point(v),
+                    (_, None) => {}
+                }
+            }
+            let niv_score = top.div(base.map(power)).map(|v| (v * 1000.0).clamp(-NIV_CLAMP, NIV_CLAMP));
+
+            // Probability falls as the score rises; the expansion-age shift is monotone
+            let recession_probability = (engine.probability_input() == ProbabilityInput::Score).then(|| Interval {
+                lo: engine.expansion_adjusted(niv::probability_from_score(niv_score.hi), d.date),
+                hi: engine.expansion_adjusted(niv::probability_from_score(niv_score.lo), d.date),
+            });
+
+            IntervalResult { date: d.date, niv_score, recession_probability }
+        })
+        .collect()
+}
+
+/// Window means of the raw ranges; the first `window - 1` months pass through
+pub fn smooth(raw: &[IntervalResult], window: usize) -> Vec<IntervalResult> {
+    if window <= 1 || raw.len() < window {
+        return raw.to_vec();
+    }
+    let mean = |items: &[IntervalResult], get: fn(&IntervalResult) -> Option<Interval>| {
+        let n = items.len() as f64;
+        items.iter().map(get).try_fold(Interval::point(0.0), |acc, v| Some(acc + v?)).map(|sum| Interval {
+            lo: sum.lo / n,
+            hi: sum.hi / n,
+        })
+    };
+    raw.iter()
+        .enumerate()
+        .map(|(i, r)| {
+            if i + 1 < window {
+                return r.clone();
+            }
+            let items = &raw[i + 1 - window..=i];
+            IntervalResult {
+                date: r.date,
+                niv_score: mean(items, |r| Some(r.niv_score)).expect("score range"),
+                recession_probability: mean(items, |r| r.recession_probability),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    #[test]
+    fn test_zero_uncertainty_reproduces_the_engine() {
+        let engine = NIVEngine::new();
+        let data = mock::generate_mock_data(1990, 2020);
+        let raw = engine.calculate_raw(&data);
+        let ranges = calculate_raw(&engine, &data, &InputUncertainty::default());
+
+        assert_eq!(ranges.len(), raw.len());
+        for (range, point) in ranges.iter().zip(&raw) {
+            assert_eq!(range.date, point.date);
+            assert!((range.niv_score.lo - point.niv_score).abs() < 1e-9, "{}", point.date);
+            assert!((range.niv_score.hi - point.niv_score).abs() < 1e-9, "{}", point.date);
+        }
+        assert!(!InputUncertainty { capacity_util: -0.1, ..Default::default() }.is_valid());
+        assert!(serde_json::from_str::<InputUncertainty>(r#"{"tcu":0.2}"#).is_err());
+    }
+
+    #[test]
+    fn test_ranges_contain_the_point_and_widen_with_uncertainty() {
+        let engine = NIVEngine::new();
+        let data = mock::generate_mock_data(1990, 2020);
+        let smoothed = engine.calculate_series(&data);
+        let narrow = InputUncertainty { capacity_util: 0.2, yield_spread: 0.05, ..Default::default() };
+        let wide = InputUncertainty { capacity_util: 0.5, yield_spread: 0.1, fed_funds_rate: 0.05, ..narrow };
+        let narrow = smooth(&calculate_raw(&engine, &data, &narrow), niv::SMOOTH_WINDOW);
+        let wide = smooth(&calculate_raw(&engine, &data, &wide), niv::SMOOTH_WINDOW);
+
+        for ((point, n), w) in smoothed.iter().zip(&narrow).zip(&wide) {
+            assert!(n.niv_score.lo - 1e-9 <= point.niv_score && point.niv_score <= n.niv_score.hi + 1e-9);
+            assert!(w.niv_score.lo <= n.niv_score.lo + 1e-9 && n.niv_score.hi <= w.niv_score.hi + 1e-9);
+            let p = n.recession_probability.expect("score probability");
+            assert!(p.lo <= p.hi && (0.0..=1.0).contains(&p.lo) && (0.0..=1.0).contains(&p.hi));
+        }
+        assert!(narrow.iter().any(|r| r.niv_score.hi - r.niv_score.lo > 0.01));
+    }
+}
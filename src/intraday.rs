@@ -0,0 +1,137 @@
+//! Intraday Provisional Value
+//!
+//! The official NIV is monthly. With NIV_INTRADAY_SECS set and a market-data
+//! provider configured (today the FRED passthrough: daily T10Y3M and DFF,
+//! which needs FRED_API_KEY), the server recomputes on that interval with the
+//! month-to-date averages of the daily spread and fed funds rate in place of
+//! the latest month's T10Y3M and FEDFUNDS. This moves the spread and volatility
+//! drag terms (and everything downstream of them) before the monthly data
+//! lands. Other inputs carry forward from the last month. The result is
+//! reported on `/api/v1/latest` as `provisional_intraday`; it never replaces
+//! the official value.
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+
+use crate::niv::{EconomicData, NIVEngine};
+
+/// Recompute hourly unless configured otherwise
+pub const DEFAULT_INTRADAY_SECS: u64 = 3600;
+
+/// Daily FRED series standing in for the monthly spread and fed funds inputs
+pub const SPREAD_SERIES: &str = "T10Y3M";
+pub const FED_FUNDS_SERIES: &str = "DFF";
+
+/// Month-to-date market averages
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketTick {
+    pub month: NaiveDate, // First of the month
+    pub observed_through: NaiveDate,
+    pub days: usize, // Spread observations averaged
+    pub yield_spread: f64,
+    pub fed_funds_rate: f64,
+}
+
+/// A provisional result for `tick.month`
+#[derive(Debug, Clone)]
+pub struct Provisional {
+    pub as_of: DateTime<Utc>,
+    pub tick: MarketTick,
+    pub niv_score: f64,
+    pub recession_probability: f64,
+    pub drag: f64,
+    pub drag_spread: f64,
+    pub drag_volatility: f64,
+}
+
+fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("day 1 exists")
+}
+
+/// Averages over the month of the latest spread observation; None when
+/// either series has no observations in that month
+pub fn month_to_date(spread: &[(NaiveDate, f64)], fed_funds: &[(NaiveDate, f64)]) -> Option<MarketTick> {
+    let (observed_through, _) = *spread.last()?;
+    let month = month_start(observed_through);
+    let mean = |series: &[(NaiveDate, f64)]| {
+        let values: Vec<f64> = series.iter().filter(|(d, _)| month_start(*d) == month).map(|(_, v)| *v).collect();
+        (!values.is_empty()).then(|| (values.iter().sum::<f64>() / values.len() as f64, values.len()))
+    };
+    let (yield_spread, days) = mean(spread)?;
+    let (fed_funds_rate, _) = mean(fed_funds)?;
+    Some(MarketTick { month, observed_through, days, yield_spread, fed_funds_rate })
+}
+
+/// Recompute `inputs` with `tick` as the spread and fed funds of its month
+/// (appended, carrying the other inputs forward, when it is past the last
+/// month); None when the tick is older than the inputs
+pub fn provisional(engine: &NIVEngine, inputs: &[EconomicData], tick: MarketTick, as_of: DateTime<Utc>) -> Option<Provisional> {
+    let last = inputs.last()?;
+    if tick.month < last.date {
+        return None;
+    }
+    let mut inputs = inputs.to_vec();
+    if tick.month > last.date {
+        inputs.push(EconomicData { date: tick.month, ..last.clone() });
+    }
+    let month = inputs.last_mut().expect("non-empty");
+    month.yield_spread = tick.yield_spread;
+    month.fed_funds_rate = tick.fed_funds_rate;
+
+    let result = engine.calculate_series(&inputs).pop()?;
+    Some(Provisional {
+        as_of,
+        tick,
+        niv_score: result.niv_score,
+        recession_probability: result.recession_probability,
+        drag: result.components.drag,
+        drag_spread: result.components.drag_spread,
+        drag_volatility: result.components.drag_volatility,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    fn day(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_month_to_date() {
+        let spread = [(day(2, 28), 0.5), (day(3, 1), -0.2), (day(3, 4), -0.4)];
+        let fed_funds = [(day(3, 1), 5.3), (day(3, 2), 5.3), (day(3, 3), 5.4)];
+        let tick = month_to_date(&spread, &fed_funds).unwrap();
+
+        assert_eq!((tick.month, tick.observed_through, tick.days), (day(3, 1), day(3, 4), 2));
+        assert!((tick.yield_spread + 0.3).abs() < 1e-12);
+        assert!((tick.fed_funds_rate - 16.0 / 3.0).abs() < 1e-12);
+        assert_eq!(month_to_date(&spread, &[(day(2, 28), 5.3)]), None);
+        assert_eq!(month_to_date(&[], &fed_funds), None);
+    }
+
+    #[test]
+    fn test_provisional_moves_drag() {
+        let engine = NIVEngine::new();
+        let inputs = mock::generate_mock_data(2000, 2010);
+        let last = inputs.last().unwrap().clone();
+        let official = engine.calculate_series(&inputs).pop().unwrap();
+        let tick = |month, spread| MarketTick {
+            month,
+            observed_through: month,
+            days: 1,
+            yield_spread: spread,
+            fed_funds_rate: last.fed_funds_rate,
+        };
+
+        let inverted = provisional(&engine, &inputs, tick(last.date, -2.0), Utc::now()).unwrap();
+        assert!(inverted.drag_spread > official.components.drag_spread);
+        assert!(inverted.recession_probability >= official.recession_probability);
+
+        let next = last.date.checked_add_months(chrono::Months::new(1)).unwrap();
+        assert_eq!(provisional(&engine, &inputs, tick(next, last.yield_spread), Utc::now()).unwrap().tick.month, next);
+        let stale = last.date.checked_sub_months(chrono::Months::new(1)).unwrap();
+        assert!(provisional(&engine, &inputs, tick(stale, 0.0), Utc::now()).is_none());
+    }
+}
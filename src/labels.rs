@@ -0,0 +1,271 @@
+//! Recession Label Sets
+//!
+//! Named recession chronologies for evaluation. `nber` (the official dates in
+//! `RecessionPeriods`) is built in; others (ECRI, a national committee, a
+//! user's bear markets) are imported from CSV or JSON:
+//!
+//! ```text
+//! start,end,name
+//! 2001-03,2001-11,Dot-com
+//! 2008-01-01,2009-06-01,
+//! ```
+//!
+//! ```json
+//! [{ "start": "2001-03", "end": "2001-11", "name": "Dot-com" }]
+//! ```
+//!
+//! Dates are `YYYY-MM` or `YYYY-MM-DD`, normalised to the first of the month
+//! to line up with the monthly series; `name` is optional. Periods must not
+//! overlap. Sets load at startup from NIV_LABELS_DIR (`<name>.csv` or
+//! `<name>.json`) and can be replaced at runtime through the admin API;
+//! history, recessions, lead-times, false-alarms, the leaderboard and the
+//! export pick one with `labels=<name>`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::daterange;
+use crate::niv::{DatedRecession, RecessionPeriods};
+
+/// The built-in chronology, used when a request names none
+pub const DEFAULT_LABELS: &str = "nber";
+
+const MAX_LABEL_SETS: usize = 32;
+const MAX_PERIODS: usize = 500;
+
+/// One labelled episode
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LabeledPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// A named chronology, periods in date order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LabelSet {
+    pub name: String,
+    pub builtin: bool,
+    pub periods: Vec<LabeledPeriod>,
+}
+
+impl LabelSet {
+    /// `(start, end)` ranges, as the backtests take them
+    pub fn chronology(&self) -> Vec<(NaiveDate, NaiveDate)> {
+        self.periods.iter().map(|p| (p.start, p.end)).collect()
+    }
+
+    /// Periods dated by announcement: the NBER's own dates for the built-in
+    /// set, the default lags for imported ones
+    pub fn dated(&self) -> Vec<DatedRecession> {
+        if self.builtin {
+            return RecessionPeriods::announced();
+        }
+        self.periods.iter().map(|p| DatedRecession::lagged(p.start, p.end)).collect()
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.periods.iter().any(|p| date >= p.start && date <= p.end)
+    }
+
+    fn nber() -> Self {
+        let mut periods: Vec<LabeledPeriod> = RecessionPeriods::known_recessions()
+            .into_iter()
+            .map(|(start, end)| LabeledPeriod { start, end, name: None })
+            .collect();
+        periods.sort_by_key(|p| p.start);
+        Self { name: DEFAULT_LABELS.to_string(), builtin: true, periods }
+    }
+}
+
+/// Import format of a label set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PeriodRecord {
+    start: String,
+    end: String,
+    name: Option<String>,
+}
+
+/// `YYYY-MM` or `YYYY-MM-DD`, as the first of that month
+fn parse_month(raw: &str) -> Option<NaiveDate> {
+    daterange::parse_date(raw.trim())?.with_day(1)
+}
+
+fn valid_name(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_' || b == b'-')
+}
+
+/// Parse and validate an imported set
+pub fn parse(name: &str, raw: &str, format: LabelFormat) -> Result<LabelSet, String> {
+    if !valid_name(name) {
+        return Err(format!("label set name '{}' must be 1-32 characters of a-z, 0-9, '_' or '-'", name));
+    }
+    if name == DEFAULT_LABELS {
+        return Err(format!("'{}' is built in and cannot be replaced", DEFAULT_LABELS));
+    }
+    let records: Vec<PeriodRecord> = match format {
+        LabelFormat::Json => serde_json::from_str(raw).map_err(|e| format!("invalid JSON: {}", e))?,
+        LabelFormat::Csv => csv_records(raw)?,
+    };
+    if records.is_empty() || records.len() > MAX_PERIODS {
+        return Err(format!("a label set needs 1 to {} periods", MAX_PERIODS));
+    }
+
+    let mut periods = records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let date = |raw: &str| parse_month(raw).ok_or_else(|| format!("period {}: invalid date '{}'", i + 1, raw));
+            let (start, end) = (date(&record.start)?, date(&record.end)?);
+            if end < start {
+                return Err(format!("period {}: end {} is before start {}", i + 1, end, start));
+            }
+            let name = record.name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+            Ok(LabeledPeriod { start, end, name })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    periods.sort_by_key(|p| p.start);
+    if let Some(pair) = periods.windows(2).find(|pair| pair[1].start <= pair[0].end) {
+        return Err(format!("periods starting {} and {} overlap", pair[0].start, pair[1].start));
+    }
+    Ok(LabelSet { name: name.to_string(), builtin: false, periods })
+}
+
+/// Rows of `start,end[,name]` under that header
+fn csv_records(raw: &str) -> Result<Vec<PeriodRecord>, String> {
+    let mut lines = raw.lines().map(str::trim).filter(|l| !l.is_empty());
+    let header: Vec<String> = lines
+        .next()
+        .ok_or("empty CSV")?
+        .split(',')
+        .map(|h| h.trim().to_ascii_lowercase())
+        .collect();
+    if header[..] != ["start", "end"] && header[..] != ["start", "end", "name"] {
+        return Err("CSV header must be 'start,end' or 'start,end,name'".to_string());
+    }
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let fields: Vec<&str> = line.splitn(header.len(), ',').collect();
+            if fields.len() < 2 {
+                return Err(format!("CSV row {}: expected start,end", i + 2));
+            }
+            Ok(PeriodRecord {
+                start: fields[0].to_string(),
+                end: fields[1].to_string(),
+                name: fields.get(2).map(|n| n.trim_matches('"').to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Label sets by name; always holds `nber`
+#[derive(Debug, Clone)]
+pub struct LabelSets {
+    sets: BTreeMap<String, Arc<LabelSet>>,
+}
+
+impl Default for LabelSets {
+    fn default() -> Self {
+        Self { sets: BTreeMap::from([(DEFAULT_LABELS.to_string(), Arc::new(LabelSet::nber()))]) }
+    }
+}
+
+impl LabelSets {
+    /// Built-in set plus every `<name>.csv` / `<name>.json` in `dir`
+    pub fn load_dir(dir: &Path) -> Result<Self, String> {
+        let mut sets = Self::default();
+        let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        for entry in entries {
+            let path = entry.map_err(|e| format!("{}: {}", dir.display(), e))?.path();
+            let format = match path.extension().and_then(|e| e.to_str()) {
+                Some("csv") => LabelFormat::Csv,
+                Some("json") => LabelFormat::Json,
+                _ => continue,
+            };
+            let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+            let raw = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let set = parse(name, &raw, format).map_err(|e| format!("{}: {}", path.display(), e))?;
+            sets.insert(set).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        Ok(sets)
+    }
+
+    /// `None` selects the default set
+    pub fn get(&self, name: Option<&str>) -> Option<Arc<LabelSet>> {
+        self.sets.get(name.unwrap_or(DEFAULT_LABELS)).cloned()
+    }
+
+    pub fn all(&self) -> impl Iterator<Item = &Arc<LabelSet>> {
+        self.sets.values()
+    }
+
+    /// Add or replace a parsed set
+    pub fn insert(&mut self, set: LabelSet) -> Result<(), String> {
+        if !self.sets.contains_key(&set.name) && self.sets.len() >= MAX_LABEL_SETS {
+            return Err(format!("at most {} label sets", MAX_LABEL_SETS));
+        }
+        self.sets.insert(set.name.clone(), Arc::new(set));
+        Ok(())
+    }
+
+    /// Remove an imported set; Ok(false) if there was none
+    pub fn remove(&mut self, name: &str) -> Result<bool, String> {
+        if name == DEFAULT_LABELS {
+            return Err(format!("'{}' is built in and cannot be removed", DEFAULT_LABELS));
+        }
+        Ok(self.sets.remove(name).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    #[test]
+    fn test_csv_and_json_imports_agree() {
+        let csv = "start,end,name\n2008-01,2009-06-15,Great Recession\n2001-03-01,2001-11,\n";
+        let json = r#"[{"start":"2001-03","end":"2001-11"},{"start":"2008-01-01","end":"2009-06","name":"Great Recession"}]"#;
+        let from_csv = parse("ecri", csv, LabelFormat::Csv).unwrap();
+        let from_json = parse("ecri", json, LabelFormat::Json).unwrap();
+
+        assert_eq!(from_csv, from_json);
+        assert_eq!(from_csv.chronology(), vec![(date(2001, 3), date(2001, 11)), (date(2008, 1), date(2009, 6))]);
+        assert_eq!(from_csv.periods[1].name.as_deref(), Some("Great Recession"));
+        assert!(from_csv.contains(date(2009, 6)) && !from_csv.contains(date(2009, 7)));
+    }
+
+    #[test]
+    fn test_rejects_bad_sets() {
+        let csv = |body: &str| parse("custom", &format!("start,end\n{}", body), LabelFormat::Csv);
+        assert!(csv("2001-11,2001-03").unwrap_err().contains("before start"));
+        assert!(csv("2001-03,2001-11\n2001-10,2002-02").unwrap_err().contains("overlap"));
+        assert!(csv("2001-13,2002-01").unwrap_err().contains("invalid date"));
+        assert!(parse("nber", "start,end\n2001-03,2001-11", LabelFormat::Csv).is_err());
+        assert!(parse("Bad Name", "start,end\n2001-03,2001-11", LabelFormat::Csv).is_err());
+
+        let mut sets = LabelSets::default();
+        assert!(sets.remove(DEFAULT_LABELS).is_err());
+        sets.insert(csv("2001-03,2001-11").unwrap()).unwrap();
+        assert_eq!(sets.get(Some("custom")).unwrap().periods.len(), 1);
+        assert_eq!(sets.get(None).unwrap().name, DEFAULT_LABELS);
+        assert_eq!(sets.remove("custom"), Ok(true));
+    }
+}
@@ -0,0 +1,19 @@
+//! NIV Core
+//!
+//! The engine without the server: inputs, components, the NIV score and its
+//! recession probability (niv.rs), plus the modules it is built from and
+//! tested against. The single-point functions `compute_components`,
+//! `compute_niv` and `probability_from_score` need no engine or series.
+//!
+//! The `fred` feature adds the live FRED client; without it only the mock
+//! generator is built, so `--no-default-features` leaves no HTTP stack here.
+
+pub mod backtest;
+pub mod coverage;
+#[cfg_attr(not(feature = "fred"), allow(dead_code))] // Response types are only read by the live client
+pub mod fred;
+pub mod niv;
+#[cfg(feature = "fred")]
+pub mod nowcast;
+pub mod registry;
+pub mod transform;
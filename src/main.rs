@@ -4,51 +4,376 @@
 //! AUC 0.849 vs Fed Yield Curve 0.840 in Out-of-Sample testing
 //!
 //! Endpoints:
-//! - GET /api/v1/latest - Current NIV score and recession probability
-//! - GET /api/v1/history - Historical NIV data (1960-present)
+//! - GET /api/v1/latest - Current NIV score and recession probability; ?horizon=12 sets the base-rate decomposition horizon;
+//!   `expected_severity` (GDP decline and slack increase were a recession to start); the decomposition, severity and
+//!   expansion age follow ?labels=; `provisional_intraday` when NIV_INTRADAY_SECS is set
+//! - GET /api/v1/history - Historical NIV data (1960-present), optionally filtered by ?regime=; ?forecast=12 appends the Monte Carlo p10/p50/p90 fan (with severity bands
+//!   and the expected probability change split across the components' projected paths)
+//! - ?series=benchmark|tracking on latest and history - the quarterly benchmark NIV (reported GDP, released quarters)
+//!   or the monthly tracking NIV (GDP nowcast between releases); see products.rs
+//! - POST /api/v1/at/batch - Full results (components, percentile, probability) for a list of specific months
+//! - GET /api/v1/labels - Recession label sets (built-in `nber` plus imports); latest, history, recessions, lead-times,
+//!   false-alarms, the leaderboard and the export take ?labels=<name>
 //! - GET /api/v1/components - Current component breakdown with drag subcomponents
-//! - GET /api/v1/compare - NIV vs Fed Yield Curve comparison
-//! - GET /api/v1/validation - Run OOS validation checks
+//! - GET /api/v1/dashboard - Latest value, 10-year z-score, 3-month trend and signal per component
+//! - GET /api/v1/analogues - Most similar historical months (component space) and what followed
+//! - GET /api/v1/conditional?when=drag>0.02,slack>0.25 - Recession frequency after months meeting the conditions
+//! - GET /api/v1/regimes?clusters=4&method=kmeans - k-means or GMM (method=gmm) regimes in component space, centroids and current regime
+//! - GET /api/v1/export?format=xlsx - Excel workbook (data, components, charts with recession shading)
+//! - GET /api/v1/export?format=csv - History as CSV, streamed in chunks (see streaming.rs)
+//! - GET /widget - Embeddable HTML widget (score, gauge, 24-month sparkline); reloads on refresh via /widget/events (SSE)
+//! - GET /calendar.ics - iCal feed of scheduled refreshes, FRED input releases and projected threshold crossings
+//! - GET /grafana, POST /grafana/search|query|annotations - SimpleJSON/Infinity datasource for Grafana
+//! - GET /api/v1/changelog - Model/method changes with parameter diffs and validation deltas
+//! - GET /api/v1/pipeline - Pipeline hash over (dataset revision, engine config, model and code version) and its parts
+//! - GET /api/v1/deprecations - Deprecated endpoints with sunset dates and successors
+//! - GET /api/v1/compare - NIV vs Fed Yield Curve comparison, with the component driving each month's divergence
+//! - GET /api/v1/yield-curve - T10Y3M history with inversion episodes (depth, subsequent recession)
+//! - GET /api/v1/fred/:series_id - Any FRED series through the server's key, cache and rate limit
+//! - GET /api/v1/validation - Startup self-test results (invariants, benchmarks, storage, provider)
+//! - GET /api/v1/thrust-inputs - Monthly thrust inputs (dG, dA, dr, M2 acceleration) and the resulting thrust
+//! - GET /api/v1/term-structure - P(recession starts within 3/6/12/24 months), current and historical
+//! - GET /api/v1/lead-times - Distribution of months of warning before past recessions; ?regime= limits it to recessions entered from that regime
+//! - GET /api/v1/false-alarms - Every threshold crossing not followed by a recession (duration, peak probability)
+//! - GET /api/v1/episodes?level=critical&min_duration=3 - Runs at or above an alert level (duration, peak, whether a recession followed)
+//! - GET /api/v1/event-study?pre=24&post=12 - NIV, probability and component paths aligned on recession starts (mean, std, p10/p90)
+//! - GET /api/v1/models/:id/card - Model card for the serving model or the canary (inputs, formula terms, parameters,
+//!   windows, limitations, validation), generated from the engine config
+//! - GET /api/v1/backtest/replication.csv - Tidy per-month backtest (prediction, label, fold) for replication;
+//!   GET /api/v1/backtest/replication describes it (schema, pipeline hash, per-fold metrics; see replication.rs)
+//! - GET /api/v1/research/leaderboard - Backtest metrics for every registered engine variant; ?regime= scores one regime
+//! - GET /api/v1/synthetic-benchmark - Detection power against synthetic economies with known recessions
+//! - POST /api/v1/simulate - Recompute history with custom parameters, including alert transitions (and NIV/probability ranges given input `uncertainty`)
+//! - GET /api/v1/simulate/bounds - Accepted range, default, serving value and slider range of every tunable parameter
+//! - POST /api/v1/montecarlo - Scenario-conditioned Monte Carlo over the future path (pseudo, halton or sobol sampling)
+//! - GET /api/v1/montecarlo/:id - A previous Monte Carlo run by ID, with every draw's probability per month
+//! - POST /api/v1/share - Signed, expiring read-only link to a simulate/montecarlo request
+//! - GET /s/:token - Shared result (re-run against current data)
+//! - GET /api/v1/workspace - Workspace of the presented API key
+//! - GET|PUT|DELETE /api/v1/preferences - Per-key defaults (weights, probability units, smoothing, favorite scenarios) for requests that omit them
+//! - POST /api/v1/replay/start - Replay history as a live feed (SSE + webhook), scoped to the caller's workspace
+//! - GET /api/v1/replay/:id - Replay status
+//! - GET /api/v1/replay/:id/events - Replay event stream (SSE)
+//! - POST /api/v1/replay/:id/stop - Stop a replay
+//! - POST /api/v1/admin/refresh - Recompute the dataset now (admin)
+//! - POST /api/v1/admin/canary - Register a candidate model in shadow mode (admin)
+//! - GET /api/v1/admin/canary - Candidate vs serving comparisons (admin)
+//! - DELETE /api/v1/admin/canary - Discard the candidate (admin)
+//! - POST /api/v1/admin/canary/promote - Promote the candidate after enough shadow refreshes (admin)
+//! - PUT /api/v1/admin/labels/:name - Import a recession label set from CSV (text/csv) or JSON (admin)
+//! - DELETE /api/v1/admin/labels/:name - Remove an imported label set (admin)
+//! - GET /api/v1/admin/cache - Cache entries and stored Monte Carlo runs per (economy, model, revision) namespace (admin)
+//! - GET /api/v1/admin/incidents - Refreshes whose swap checks failed: checks, offending series/months, suggested action (admin)
+//! - GET /api/v1/admin/scheduler - Background tasks (startup load, refresh, intraday, TSDB export, incident webhook, replay):
+//!   schedule, last run and duration, next run, last error, what a due run is waiting on (admin; see tasks.rs)
+//! - POST /api/v1/admin/validation/live - Run the invariant and benchmark checks on freshly fetched FRED data and report
+//!   where they diverge from the embedded dataset (admin; needs FRED_API_KEY)
+//! - POST /api/v1/admin/notifications/dry-run - Evaluate notification rules over historical alert transitions without sending (admin)
 //! - GET /health - Health check
+//! - GET /health/ready - Readiness probe (503 until the dataset has loaded)
+//!
+//! Any JSON endpoint, the CSV export and replay SSE events take ?precision=0-12 to override the default rounding and
+//! ?probability_units=percent|fraction for how probabilities are reported (default percent); see format.rs.
+//! Every response carries the pipeline hash in X-NIV-Pipeline (see namespace.rs).
+//!
+//! Configuration (environment):
+//! - PORT - Listen port (default 8080)
+//! - NIV_SLACK_SPEC - capacity_utilization (default) | output_gap | unemployment_gap
+//! - NIV_INFLATION_SPEC - cpi (default) | core_cpi | pce | expected: inflation measure in the real-rate drag
+//! - NIV_SPREAD_SPEC - inversion (default) | level | change_12m: how the term spread enters the drag
+//! - NIV_GDP_SPEC - reported (default) | nowcast: efficiency denominator between quarterly GDP releases
+//! - NIV_NONFINITE_POLICY - clamp (default) | carry_forward: replacement for NaN/Inf components and scores (flagged per month)
+//! - NIV_WARMUP - flag (default) | trim: the first 11 smoothed months (before the 12-month window fills) are marked
+//!   `warmup: true` or dropped; backtests never score them
+//! - NIV_EXPANSION_AGE_WEIGHT - Probability logit shift per year of expansion age beyond 60 months (default 0 = off, |w| <= 2)
+//! - NIV_COMPONENTS_FILE - JSON file of custom components (see registry.rs)
+//! - NIV_PIPELINES_FILE - JSON file of per-series transformations deriving the engine inputs (see transform.rs)
+//! - NIV_PARTIAL_DATA - off (default) | last_value | trend: in inputs fetched from FRED (admin live validation),
+//!   months past the end of a required series are dropped, or computed from an estimate and flagged in `coverage`
+//!   (see coverage.rs). The embedded dataset has no ragged edge, so served months never need an estimate
+//! - NIV_CHANGELOG_FILE - JSON-lines methodology changelog; appended on config changes and promotions
+//! - NIV_LABELS_DIR - Recession label sets to import at startup, one `<name>.csv` or `<name>.json` per set
+//! - NIV_DEPRECATIONS_FILE - JSON registry of deprecated endpoints; matching responses carry Deprecation/Sunset/Link headers
+//! - NIV_TSDB_CONFIG - TOML file describing a TSDB to receive each refresh's results (see tsdb.rs)
+//! - NIV_SNAPSHOT_FILE - Arrow IPC snapshot of the dataset; loaded at startup, written when missing or stale
+//! - NIV_SELFTEST_POLICY - warn (default) | refuse: whether a failed startup self-test blocks serving
+//! - NIV_REFRESH_SECS - Recompute the dataset on this interval (default: only on admin request)
+//! - NIV_REFRESH_MAX_P95_MS / NIV_REFRESH_MAX_LOAD / NIV_REFRESH_MAX_DEFER_SECS - Defer scheduled refreshes while data
+//!   request p95 latency or load average per core is above these (default 1000 ms, 0.9, up to 3600 s; see backpressure.rs)
+//! - NIV_ADMIN_TOKEN - Bearer token for /api/v1/admin/* (admin endpoints are disabled without it)
+//! - NIV_INCIDENT_WEBHOOK - URL receiving a JSON POST for each blocked refresh (see incident.rs)
+//! - NIV_API_KEYS_FILE - JSON map of API keys (X-API-Key) to workspaces; keyless requests share `default`; unset, X-API-Key is ignored (see tenancy.rs)
+//! - NIV_PREFERENCES_FILE - JSON file persisting per-key preference profiles (default: kept until restart; see preferences.rs)
+//! - NIV_SHARE_SECRET - HMAC key for share links (default: random per process, so links end on restart)
+//! - NIV_PUBLIC_EMBARGO_HOURS - Serve keyless requests with 5-point probability buckets, this many hours late, and only
+//!   on latest, history, export and the widget (see public.rs)
+//! - NIV_INTRADAY_SECS - Recompute a provisional value from month-to-date daily T10Y3M/DFF on this interval (needs FRED_API_KEY; see intraday.rs)
+//! - NIV_ROUTER_PROFILE - full (default) | mirror: only latest, history and recessions (plus health), keyless and
+//!   cacheable, for serving behind a CDN while the full API stays internal
+//!   | demo: the full API over canned data for a public demo, with live FRED, refreshes and admin endpoints disabled,
+//!   rate limited per client and marked `demo: true` (see demo.rs)
+//! - NIV_DEMO_RPM - Requests per client per minute on the demo profile (default 30)
+//! - NIV_DEMO_CLIENT_IP_HEADER - Header a fronting proxy sets to the client address (e.g. Fly-Client-IP), for demo rate limits
+//! - NIV_MIRROR_MAX_AGE_SECS - Cache-Control max-age (and stale-while-revalidate) of mirror responses (default 3600)
+//! - NIV_EXPORT_MEMORY_BYTES - Per-request memory ceiling for exports (default 16 MiB; see streaming.rs)
+//! - NIV_CANARY_REFRESHES - Shadow refreshes a candidate needs before promotion (default 3)
+//! - NIV_COMPUTE_BUDGET - Per-request compute budget in engine-months (Monte Carlo, benchmarks)
+//! - NIV_INTERACTIVE_WORKERS / NIV_BATCH_WORKERS - Worker limits for the interactive and batch job lanes
+//! - NIV_MAX_SPAN_MONTHS - Longest start/end span for simulate, term-structure history and replay
+//! - NIV_FRED_PROXY_RPM - Upstream FRED requests per minute for /api/v1/fred (default 100)
+//!
+//! CLI:
+//! - niv-engine publish --out <dir> - Write latest/history/compare/recessions JSON for static hosting and exit
+//!
+//! Cargo features (on by default unless noted; `--no-default-features` builds a minimal server):
+//! - fred - FRED API client (reqwest); without it the provider self-test is skipped, /api/v1/fred is rejected and NIV_INTRADAY_SECS is ignored
+//! - webhooks - Replay webhook delivery (reqwest); without it `webhook` is rejected
+//! - snapshot - Arrow IPC dataset snapshots (arrow); without it the dataset is always computed
+//! - xlsx - Excel workbook export (rust_xlsxwriter); without it `/api/v1/export` is rejected
+//! - tsdb - Line-protocol push of each refresh to a TSDB (reqwest, toml); without it NIV_TSDB_CONFIG is ignored
+//! - loadtest (off by default) - `niv-loadtest` binary: replays a read/simulation traffic mix against a server and reports latency percentiles
 
-mod niv;
-mod fred;
+mod analogues;
+#[cfg(test)]
+mod api_tests;
+mod attribution;
+mod backpressure;
+mod bounds;
+mod budget;
+mod calendar;
+mod cache;
+mod calibration;
+mod canary;
+mod changelog;
+mod conditional;
+mod dashboard;
+mod daterange;
+mod demo;
+mod deprecation;
+mod eventstudy;
+#[cfg(feature = "xlsx")]
+mod export;
+mod notifications;
+mod publish;
+mod qmc;
+#[cfg(feature = "fred")]
+mod fred_proxy;
+mod format;
+mod grafana;
+mod incident;
+mod interval;
+#[cfg_attr(not(feature = "fred"), allow(dead_code))] // Market data comes through the FRED passthrough
+mod intraday;
+mod labels;
+mod modelcard;
+mod montecarlo;
+mod namespace;
+mod preferences;
+mod products;
+mod public;
+mod regimes;
+mod replication;
+mod replay;
+mod requests;
+mod research;
+mod scenario;
+mod selftest;
+mod severity;
+mod share;
+mod streaming;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+mod synthetic;
+mod tasks;
+mod tenancy;
+#[cfg(feature = "tsdb")]
+mod tsdb;
+mod widget;
+mod yieldcurve;
+
+// Engine modules, from the library target (lib.rs)
+use niv_core::{backtest, coverage, fred, niv, registry, transform};
 
 use axum::{
-    extract::{Query, State},
-    http::StatusCode,
+    body::Body,
+    extract::{ConnectInfo, Extension, Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    response::sse::{Event, KeepAlive, Sse},
     response::Json,
-    routing::get,
+    routing::{get, post},
     Router,
 };
 use chrono::{Datelike, NaiveDate};
 use moka::future::Cache;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::niv::{AlertLevel, NIVComponents, NIVEngine, NIVResult, ValidationResult};
-use crate::fred::mock;
+use crate::analogues::Analogue;
+use crate::backpressure::{Backpressure, LoadMonitor, Pressure, Step};
+use crate::backtest::{Episode, FalseAlarm, LeadTimeDistribution};
+use crate::regimes::Regime;
+use crate::conditional::{Condition, ConditionalStats};
+use crate::canary::{Canary, CanaryStatus, Trigger};
+use crate::changelog::{ChangeTrigger, Changelog, ChangelogEntry};
+use crate::budget::{ComputeBudget, JobError, Lane, LaneConfig, LaneStats, Scheduler};
+use crate::dashboard::ComponentReading;
+use crate::daterange::{DateRange, RangeError};
+use crate::demo::DemoPolicy;
+use crate::deprecation::{Deprecation, DeprecationRegistry};
+use crate::niv::{
+    AlertLevel, Component, ComponentWeights, Dataset, DenominatorPolicy, EconomicData, EfficiencySpec, GdpSpec, InflationSpec, InputSeries, NIVEngine, NIVResult, NonFinitePolicy,
+    ProbabilityInput, QualityFlag, ScoreMode, SlackSpec, SpreadSpec, ThrustScaling, WarmupPolicy,
+};
+use crate::fred::{mock, FredAccess, ReleaseDate};
+use crate::labels::{LabelFormat, LabelSet, LabelSets};
+use crate::modelcard::{ModelCard, ModelStatus};
+use crate::montecarlo::{MonteCarloConfig, MonteCarloResult, RunError};
+use crate::namespace::{CacheKey, Namespace, NamespaceUsage};
+use crate::notifications::NotificationRule;
+use crate::format::{probability, round, Format};
+use crate::preferences::{PreferenceStore, Preferences};
+use crate::incident::Incident;
+use crate::products::Product;
+use crate::public::PublicTier;
+use crate::severity::{SeverityBand, SeverityModel};
+use crate::qmc::Sampling;
+use crate::registry::{ComponentDef, ComponentRegistry, CustomTerm};
+use crate::replay::{ReplayHandle, ReplayStatus, WebhookClient};
+use crate::requests::{BatchAtRequest, EngineSpec, MonteCarloRequest, RequestError, SimulateRequest};
+use crate::scenario::Scenario;
+use crate::selftest::{FailurePolicy, SelfTestReport};
+use crate::share::{Claims, ShareError, ShareKind, ShareSigner};
+use crate::synthetic::{SyntheticBenchmark, SyntheticConfig};
+use crate::tasks::{Schedule, Tasks};
+use crate::tenancy::{ApiKeys, Workspace};
 
 /// Application state
 struct AppState {
-    engine: NIVEngine,
-    cache: Cache<String, CachedData>,
+    serving: std::sync::RwLock<Serving>,
+    cache: Cache<CacheKey, CachedData>, // Serving series ("series"), benchmark and tracking ("series:{product}") and re-smoothed history ("smooth:{window}"); TTLs per cache.rs
+    mc_runs: Cache<String, Arc<StoredRun>>, // Monte Carlo results by run ID, bounded at MC_RUN_BYTES
+    revision: std::sync::RwLock<String>, // Digest of the installed inputs
+    inputs: RwLock<Vec<EconomicData>>,
+    raw: RwLock<Vec<NIVResult>>,      // Unsmoothed results, for request-time smoothing
     data: RwLock<Vec<NIVResult>>,
-    validation: RwLock<Option<ValidationResult>>,
+    validation: RwLock<Option<SelfTestReport>>,
+    replays: RwLock<HashMap<(Workspace, String), ReplayHandle>>,
+    http: WebhookClient,
+    max_span_months: Option<u32>,
+    budget: ComputeBudget,
+    jobs: Scheduler,
+    ready: AtomicBool, // Set once the background data load completes
+    snapshot_path: Option<std::path::PathBuf>,
+    refreshing: tokio::sync::Mutex<()>, // Serializes refreshes
+    refresh_schedule: Option<(chrono::DateTime<chrono::Utc>, Duration)>, // Loop start and period
+    backpressure: Backpressure, // When scheduled refreshes defer
+    load: LoadMonitor, // Interactive request latencies
+    changelog: RwLock<Changelog>, // NIV_CHANGELOG_FILE
+    deprecations: DeprecationRegistry, // NIV_DEPRECATIONS_FILE
+    labels: RwLock<LabelSets>, // NIV_LABELS_DIR plus admin imports
+    updates: tokio::sync::broadcast::Sender<NaiveDate>, // Latest date, sent whenever a dataset is installed
+    releases: tokio::sync::Mutex<Option<ReleaseCalendar>>, // FRED release calendar, by fetch day
+    calibrations: RwLock<Option<Arc<Vec<calibration::HorizonCalibration>>>>, // Fitted from `data`; cleared on install
+    intraday: RwLock<Option<intraday::Provisional>>, // Latest NIV_INTRADAY_SECS recompute
+    #[cfg(feature = "tsdb")]
+    tsdb: Option<(reqwest::Client, tsdb::TsdbConfig)>, // NIV_TSDB_CONFIG
+    #[cfg(feature = "fred")]
+    fred_proxy: Option<fred_proxy::FredProxy>, // None without FRED_API_KEY, or offline
+    fred_access: FredAccess, // Offline on the demo profile
+    canary: RwLock<Option<Canary>>,
+    canary_refreshes: usize,
+    admin_token: Option<String>,
+    api_keys: ApiKeys, // NIV_API_KEYS_FILE
+    share: ShareSigner, // NIV_SHARE_SECRET
+    public: Option<PublicTier>, // NIV_PUBLIC_EMBARGO_HOURS; None serves keyless requests in full
+    preferences: PreferenceStore, // NIV_PREFERENCES_FILE
+    incidents: RwLock<VecDeque<Incident>>, // Blocked refreshes, newest last
+    incident_webhook: Option<String>, // NIV_INCIDENT_WEBHOOK
+    export_memory: usize, // NIV_EXPORT_MEMORY_BYTES
+    partial_data: coverage::PartialDataMode, // NIV_PARTIAL_DATA
+    tasks: Tasks, // Background task runs (see tasks.rs)
+}
+
+/// Routes served: the full API, the read-only mirror for a CDN, or the
+/// public demo
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RouterProfile {
+    Full,
+    Mirror,
+    Demo,
+}
+
+/// Mirror Cache-Control max-age unless configured otherwise
+const DEFAULT_MIRROR_MAX_AGE_SECS: u64 = 3600;
+
+/// Serving model; replaced when a canary is promoted
+struct Serving {
+    version: String,
+    engine: Arc<NIVEngine>,
+}
+
+impl AppState {
+    fn engine(&self) -> Arc<NIVEngine> {
+        self.serving.read().expect("serving lock").engine.clone()
+    }
+
+    fn model_version(&self) -> String {
+        self.serving.read().expect("serving lock").version.clone()
+    }
+
+    /// Stored preferences of the request's workspace (none without one)
+    fn preferences(&self, workspace: Option<&Workspace>) -> Preferences {
+        workspace.map(|w| self.preferences.get(w)).unwrap_or_default()
+    }
+
+    /// Coarsened, embargoed results for a keyless request when the public tier is on
+    fn public_view(&self, workspace: Option<&Workspace>) -> Option<Arc<Vec<NIVResult>>> {
+        let public = self.public.as_ref()?;
+        workspace.filter(|w| w.is_keyless())?;
+        public.visible(chrono::Utc::now())
+    }
+
+    /// Namespace of the serving model over the installed dataset
+    fn namespace(&self) -> Namespace {
+        Namespace::new(&self.model_version(), &self.revision.read().expect("revision lock"))
+    }
+
+    /// Hash of the installed dataset, serving engine and code version
+    fn pipeline_hash(&self) -> String {
+        self.namespace().pipeline_hash(&self.engine().parameters())
+    }
 }
 
 /// Cached computation results
 #[derive(Clone)]
+#[allow(dead_code)]
 struct CachedData {
-    results: Vec<NIVResult>,
+    results: Arc<Vec<NIVResult>>,
     computed_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Monte Carlo run kept for re-fetching by ID
+struct StoredRun {
+    result: MonteCarloResult,
+    namespace: Namespace,
+}
+
+/// Cache weight of a stored run: its approximate size in bytes
+fn weigh_run(id: &str, run: &StoredRun) -> u32 {
+    u32::try_from(id.len() + run.result.approx_bytes()).unwrap_or(u32::MAX)
+}
+
 /// Query parameters for history endpoint
 #[derive(Debug, Deserialize)]
 struct HistoryQuery {
@@ -56,26 +381,509 @@ struct HistoryQuery {
     end: Option<String>,    // YYYY-MM-DD
     #[serde(default = "default_limit")]
     limit: usize,
+    #[serde(default)]
+    score: ScoreMode,       // raw | percentile
+    smooth: Option<usize>,  // Smoothing window in months (default 12)
+    regime: Option<usize>,  // Keep only months in this regime (see /api/v1/regimes)
+    #[serde(default = "default_clusters")]
+    clusters: usize,        // Regimes fitted when filtering by `regime`
+    #[serde(default)]
+    method: regimes::Method, // kmeans | gmm, when filtering by `regime`
+    forecast: Option<usize>, // Append the baseline Monte Carlo fan for this many months
+    labels: Option<String>, // Recession label set for `is_recession` (default nber)
+    series: Option<Product>, // benchmark | tracking (see products.rs); the served series when omitted
+}
+
+fn default_clusters() -> usize {
+    regimes::DEFAULT_CLUSTERS
+}
+
+/// Query parameters for latest endpoint
+#[derive(Debug, Deserialize)]
+struct LatestQuery {
+    #[serde(default)]
+    score: ScoreMode,
+    #[serde(default = "default_decomposition_horizon")]
+    horizon: u32, // Base-rate horizon for the probability decomposition
+    series: Option<Product>, // benchmark | tracking (see products.rs); the served series when omitted
+    labels: Option<String>, // Label set dating the expansion age; nber when omitted
+}
+
+fn default_decomposition_horizon() -> u32 {
+    calibration::DEFAULT_DECOMPOSITION_HORIZON
 }
 
 fn default_limit() -> usize {
     1000
 }
 
+/// Query parameters for term-structure endpoint
+#[derive(Debug, Deserialize)]
+struct TermStructureQuery {
+    #[serde(default)]
+    history: bool,          // Include the historical curves
+    start: Option<String>,  // YYYY-MM-DD
+    end: Option<String>,    // YYYY-MM-DD
+}
+
+/// Query parameters for the lead-time and false-alarm endpoints
+#[derive(Debug, Deserialize)]
+struct LeadTimeQuery {
+    #[serde(default = "default_lead_level")]
+    level: AlertLevel,
+    threshold: Option<f64>, // Percent, overrides `level`
+    #[serde(default = "default_lookback")]
+    lookback: u32,          // Months before each recession start (evaluation window)
+    labels: Option<String>, // Recession label set (default nber)
+    regime: Option<usize>,  // Score only this regime (see regimes::RegimeFilter)
+    #[serde(default = "default_clusters")]
+    clusters: usize,
+    #[serde(default)]
+    method: regimes::Method,
+}
+
+/// Query parameters for the alert-episode search
+#[derive(Debug, Deserialize)]
+struct EpisodesQuery {
+    #[serde(default = "default_lead_level")]
+    level: AlertLevel,      // Episodes at or above this level
+    #[serde(default = "default_min_duration")]
+    min_duration: usize,    // Shortest episode returned, in months
+    #[serde(default = "default_lookback")]
+    lookback: u32,          // Months after each episode start searched for a recession
+    labels: Option<String>, // Recession label set (default nber)
+}
+
+fn default_min_duration() -> usize {
+    1
+}
+
+/// Query parameters for the event-study endpoint
+#[derive(Debug, Deserialize)]
+struct EventStudyQuery {
+    #[serde(default = "default_event_pre")]
+    pre: u32,  // Months before each recession start
+    #[serde(default = "default_event_post")]
+    post: u32, // Months after
+    labels: Option<String>, // Recession label set (default nber)
+}
+
+fn default_event_pre() -> u32 {
+    eventstudy::DEFAULT_PRE_MONTHS
+}
+
+fn default_event_post() -> u32 {
+    eventstudy::DEFAULT_POST_MONTHS
+}
+
+/// Query parameters for the analogues endpoint
+#[derive(Debug, Deserialize)]
+struct AnaloguesQuery {
+    #[serde(default = "default_analogues")]
+    k: usize,
+}
+
+fn default_analogues() -> usize {
+    analogues::DEFAULT_K
+}
+
+/// Query parameters for the conditional-outcomes endpoint
+#[derive(Debug, Deserialize)]
+struct ConditionalQuery {
+    when: Option<String>, // e.g. "drag>0.02,slack>0.25"
+    #[serde(default = "default_conditional_horizon")]
+    horizon: usize,
+    regime: Option<usize>, // Restrict to months in this regime
+    #[serde(default = "default_clusters")]
+    clusters: usize,
+    #[serde(default)]
+    method: regimes::Method,
+}
+
+/// Query parameters for the regimes endpoint
+#[derive(Debug, Deserialize)]
+struct RegimesQuery {
+    #[serde(default = "default_clusters")]
+    clusters: usize,
+    #[serde(default)]
+    method: regimes::Method, // kmeans | gmm
+}
+
+fn default_conditional_horizon() -> usize {
+    conditional::DEFAULT_HORIZON_MONTHS
+}
+
+/// Query parameters for the export endpoint
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    #[serde(default = "default_export_format")]
+    format: String, // xlsx or csv
+    start: Option<String>,
+    end: Option<String>,
+    labels: Option<String>, // Recession label set for the recession flags and shading (default nber)
+}
+
+fn default_export_format() -> String {
+    "xlsx".to_string()
+}
+
+/// Query parameters for the thrust-inputs endpoint
+#[derive(Debug, Deserialize)]
+struct ThrustInputsQuery {
+    start: Option<String>, // YYYY-MM-DD
+    end: Option<String>,   // YYYY-MM-DD
+}
+
+fn default_lead_level() -> AlertLevel {
+    AlertLevel::Warning
+}
+
+fn default_lookback() -> u32 {
+    backtest::DEFAULT_LOOKBACK_MONTHS
+}
+
+/// Tunable parameters with their accepted and slider ranges
+#[derive(Serialize)]
+struct BoundsResponse {
+    parameters: Vec<bounds::Bound>,
+    model_version: String,
+}
+
+/// What produced the served output, hashed
+#[derive(Serialize)]
+struct PipelineResponse {
+    pipeline_hash: String,
+    economy: String,
+    model_version: String,
+    dataset_revision: String,
+    crate_version: &'static str,
+    parameters: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Methodology changelog
+#[derive(Serialize)]
+struct ChangelogResponse {
+    count: usize,
+    entries: Vec<ChangelogEntry>,
+}
+
+/// A notification the rules would have sent
+#[derive(Serialize)]
+struct FiringResponse {
+    rule: String,
+    channel: notifications::Channel,
+    date: String,
+    from: AlertLevel,
+    to: AlertLevel,
+    recession_probability: f64,
+    trigger: Component,
+}
+
+#[derive(Serialize)]
+struct RuleOutcomeResponse {
+    rule: String,
+    fired: usize,
+    suppressed: usize,
+    last_fired: Option<String>,
+}
+
+#[derive(Serialize)]
+struct NotificationDryRunResponse {
+    start_date: String,
+    end_date: String,
+    transitions: usize,
+    rules: Vec<RuleOutcomeResponse>,
+    timeline: Vec<FiringResponse>, // Oldest first
+    model_version: String,
+}
+
+/// Self-test checks on the embedded vs live data
+#[derive(Serialize)]
+struct LiveValidationResponse {
+    embedded_months: usize,
+    live_months: usize,
+    embedded_validation: changelog::Validation,
+    live_validation: changelog::Validation,
+    #[serde(flatten)]
+    report: selftest::SensitivityReport,
+    model_version: String,
+}
+
+#[derive(Serialize)]
+struct IncidentsResponse {
+    count: usize,
+    incidents: Vec<Incident>,
+}
+
+#[derive(Serialize)]
+struct SchedulerResponse {
+    checked_at: String,
+    ready: bool, // Whether the startup load has completed
+    tasks: Vec<tasks::TaskStatus>,
+    lanes: Vec<LaneStats>, // Job lanes the tasks compute on
+}
+
+#[derive(Serialize)]
+struct WorkspaceResponse {
+    workspace: Workspace,
+    replays: usize,
+}
+
+#[derive(Serialize)]
+struct PreferencesResponse {
+    workspace: Workspace,
+    preferences: Preferences,
+}
+
+/// Deprecated endpoints
+#[derive(Serialize)]
+struct DeprecationsResponse {
+    count: usize,
+    deprecations: Vec<Deprecation>,
+}
+
+/// Historical months most similar to the latest
+#[derive(Serialize)]
+struct AnaloguesResponse {
+    date: String, // Latest month being matched
+    k: usize,
+    horizon_months: usize,
+    analogues: Vec<Analogue>,
+    model_version: String,
+}
+
+/// Component-space regimes and the regime of every month
+#[derive(Serialize)]
+struct RegimesResponse {
+    clusters: usize,
+    method: regimes::Method,
+    current_date: String,
+    current_regime: Option<usize>,
+    regimes: Vec<Regime>, // Ordered healthiest first (by mean NIV score)
+    labels: Vec<RegimeLabel>,
+    model_version: String,
+}
+
+#[derive(Serialize)]
+struct RegimeLabel {
+    date: String,
+    regime: usize,
+}
+
+/// How often a recession followed months meeting the conditions
+#[derive(Serialize)]
+struct ConditionalResponse {
+    conditions: Vec<Condition>,
+    horizon_months: usize,
+    latest_date: String,
+    latest_matches: bool, // Whether the latest month meets the conditions
+    #[serde(flatten)]
+    stats: ConditionalStats, // Probabilities in percent
+    model_version: String,
+}
+
+/// Latest-month component readings for the landing-page widget
+#[derive(Serialize)]
+struct DashboardResponse {
+    date: String,
+    niv_score: f64,
+    recession_probability: f64, // Percent
+    alert_level: AlertLevel,
+    zscore_window_months: usize,
+    trend_months: usize,
+    components: Vec<ComponentReading>,
+    model_version: String,
+}
+
+/// Monthly thrust inputs under the serving engine
+#[derive(Serialize)]
+struct ThrustInputsResponse {
+    count: usize,
+    weights: ComponentWeights,
+    m2_accel_lag_months: usize,
+    model_version: String,
+    data: Vec<ThrustInputPoint>,
+}
+
+#[derive(Serialize)]
+struct ThrustInputPoint {
+    date: String,
+    dg: f64,           // Investment, monthly %
+    da: f64,           // M2, YoY %
+    dr: f64,           // Fed funds, monthly change (pp)
+    m2_accel: f64,     // Change in dA over the lag (pp); negative = deceleration
+    thrust_input: f64, // Weighted sum before scaling
+    thrust_scale: f64,
+    thrust: f64,
+}
+
+/// Recession-probability term structure response (probabilities in percent)
+#[derive(Serialize)]
+struct TermStructureResponse {
+    horizons: Vec<u32>,
+    calibrations: Vec<calibration::HorizonCalibration>,
+    current: Option<calibration::TermStructurePoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    history: Option<Vec<calibration::TermStructurePoint>>,
+    model_version: String,
+}
+
+/// Variant leaderboard response
+#[derive(Serialize)]
+struct LeaderboardResponse {
+    threshold: f64, // Percent
+    lookback_months: u32,
+    label_horizon_months: u32,
+    labels: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    regime: Option<usize>,
+    variants: Vec<research::VariantScore>,
+    model_version: String,
+}
+
+/// Query parameters for synthetic benchmark endpoint
+#[derive(Debug, Deserialize)]
+struct SyntheticBenchmarkQuery {
+    #[serde(default = "default_synthetic_economies")]
+    economies: usize,
+    seed: Option<u64>,
+    signal_strength: Option<f64>,
+    noise: Option<f64>,
+    recession_hazard: Option<f64>,
+    lead_months: Option<usize>,
+    #[serde(default = "default_lead_level")]
+    level: AlertLevel,
+}
+
+fn default_synthetic_economies() -> usize {
+    10
+}
+
+/// Request body for share link creation
+#[derive(Debug, Deserialize)]
+struct ShareRequest {
+    kind: ShareKind,
+    request: serde_json::Value, // Body for the endpoint named by `kind`
+    #[serde(default = "default_share_ttl")]
+    ttl_hours: u32,
+}
+
+fn default_share_ttl() -> u32 {
+    share::DEFAULT_TTL_HOURS
+}
+
+/// Request body for the notification dry run
+#[derive(Debug, Deserialize)]
+struct NotificationDryRunRequest {
+    rules: Vec<NotificationRule>,
+    start: Option<String>, // YYYY-MM-DD
+    end: Option<String>,   // YYYY-MM-DD
+}
+
+/// Request body for canary registration
+#[derive(Debug, Deserialize)]
+struct CanaryRequest {
+    version: String,
+    #[serde(flatten)]
+    engine: EngineSpec,
+}
+
+/// Query parameters for the yield curve endpoint
+#[derive(Debug, Deserialize)]
+struct YieldCurveQuery {
+    start: Option<String>,  // YYYY-MM-DD
+    end: Option<String>,    // YYYY-MM-DD
+}
+
+/// Query parameters for the FRED passthrough
+#[derive(Debug, Deserialize)]
+struct FredSeriesQuery {
+    start: Option<String>,  // YYYY-MM-DD
+    end: Option<String>,    // YYYY-MM-DD
+}
+
+/// Query parameters for replay start
+#[derive(Debug, Deserialize)]
+struct ReplayQuery {
+    #[serde(default = "default_replay_speed")]
+    speed: String,          // "12x" = 12 historical months per minute
+    from: Option<String>,   // YYYY-MM or YYYY-MM-DD
+    to: Option<String>,
+    webhook: Option<String>,
+}
+
+fn default_replay_speed() -> String {
+    "12x".to_string()
+}
+
 /// API Response types
 #[derive(Serialize)]
 struct LatestResponse {
     date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series: Option<Product>,
     niv_score: f64,
+    score: ScoreMode,
     recession_probability: f64,
     alert_level: AlertLevel,
     alert_color: String,
     alert_label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    probability_decomposition: Option<DecompositionResponse>,
+    expansion_age_months: Option<u32>, // As known at the date, per the label set (niv::expansion_age)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provisional_intraday: Option<IntradayResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected_severity: Option<SeverityResponse>,
     components: ComponentsResponse,
     vs_fed: FedComparisonResponse,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    quality: Vec<QualityFlag>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    coverage: Vec<InputSeries>, // Inputs estimated under NIV_PARTIAL_DATA
     model_version: String,
 }
 
+/// How deep a recession starting now would be, fitted on past episodes
+#[derive(Serialize)]
+struct SeverityResponse {
+    gdp_decline: BandResponse,    // Peak-to-trough real GDP, percent
+    slack_increase: BandResponse, // Slack component, percentage points
+    episodes: usize,              // Recessions the fit used
+}
+
+#[derive(Serialize)]
+struct BandResponse {
+    expected: f64,
+    p10: f64,
+    p90: f64,
+}
+
+/// Month-to-date recompute from market data; not the official value
+#[derive(Serialize)]
+struct IntradayResponse {
+    as_of: String,
+    month: String,
+    observed_through: String,
+    trading_days: usize,
+    yield_spread: f64,   // Month-to-date mean of daily T10Y3M
+    fed_funds_rate: f64, // Month-to-date mean of daily DFF
+    niv_score: f64,
+    recession_probability: f64,
+    drag: f64,
+    drag_spread: f64,
+    drag_volatility: f64,
+}
+
+/// Headline probability against the unconditional base rate (percent)
+#[derive(Serialize)]
+struct DecompositionResponse {
+    horizon_months: u32,
+    base_rate: f64,
+    signal: f64, // Percentage points above (+) or below (-) the base rate
+    samples: usize,
+}
+
 #[derive(Serialize)]
 struct ComponentsResponse {
     // Main components
@@ -88,6 +896,11 @@ struct ComponentsResponse {
     drag_spread: f64,
     drag_real_rate: f64,
     drag_volatility: f64,
+    // Efficiency denominator used the monthly GDP nowcast
+    gdp_nowcast: bool,
+    // Registry-defined terms
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    custom: Vec<CustomTerm>,
     // Interpretations
     interpretation: ComponentInterpretation,
 }
@@ -115,10 +928,53 @@ struct FedComparisonResponse {
 #[derive(Serialize)]
 struct HistoryResponse {
     count: usize,
+    score: ScoreMode,
+    smooth_window: usize,
     start_date: String,
     end_date: String,
+    labels: String,
     model_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series: Option<Product>,
     data: Vec<HistoryDataPoint>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    forecast: Option<ForecastFan>,
+}
+
+/// Forward p10/p50/p90 fan from the baseline Monte Carlo
+#[derive(Serialize)]
+struct ForecastFan {
+    id: String, // Full run at /api/v1/montecarlo/:id
+    horizon_months: usize,
+    draws: usize,
+    seed: u64,
+    months: Vec<ForecastPoint>,
+}
+
+#[derive(Serialize)]
+struct ForecastPoint {
+    date: String,
+    p10: f64,
+    p50: f64,
+    p90: f64,
+    expected: f64, // Baseline path without shocks
+    change: f64,   // `expected` minus the latest observed probability
+    drivers: ForecastDrivers,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    severity: Option<SeverityResponse>, // At the median probability
+}
+
+/// `change` split across the components' projected paths (Shapley, see
+/// attribution.rs) through the engine's link at the forecast month; `other`
+/// is what the components leave unexplained (smoothing, the change in
+/// expansion age since today)
+#[derive(Serialize)]
+struct ForecastDrivers {
+    thrust: f64,
+    efficiency: f64,
+    slack: f64,
+    drag: f64,
+    other: f64,
 }
 
 #[derive(Serialize)]
@@ -128,372 +984,4184 @@ struct HistoryDataPoint {
     recession_probability: f64,
     alert_level: AlertLevel,
     is_recession: bool,
+    expansion_age_months: Option<u32>, // As known at the date, per the label set (niv::expansion_age)
     // Include components for charting
     thrust: f64,
     efficiency: f64,
     slack: f64,
     drag: f64,
+    // Non-finite values replaced in the window
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    quality: Vec<QualityFlag>,
+    // Inputs estimated in the window (partial data)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    coverage: Vec<InputSeries>,
+    // Before the smoothing window fills (see WarmupPolicy)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    warmup: bool,
+    // [low, high] under the simulate request's input uncertainty
+    #[serde(skip_serializing_if = "Option::is_none")]
+    niv_score_range: Option<[f64; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recession_probability_range: Option<[f64; 2]>,
 }
 
+/// One requested month in full
 #[derive(Serialize)]
-struct HealthResponse {
-    status: String,
-    version: String,
+struct AtResult {
+    requested: String, // The date as sent
+    #[serde(flatten)]
+    point: HistoryDataPoint,
+    niv_percentile: f64,
+    efficiency_squared: f64,
+    drag_spread: f64,
+    drag_real_rate: f64,
+    drag_volatility: f64,
+    gdp_nowcast: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    custom: Vec<CustomTerm>,
+}
+
+#[derive(Serialize)]
+struct BatchAtResponse {
+    count: usize,
+    score: ScoreMode,
+    labels: String,
+    results: Vec<AtResult>,
     model_version: String,
-    data_points: usize,
-    last_update: String,
-    validation_passed: Option<bool>,
 }
 
 #[derive(Serialize)]
-struct ErrorResponse {
-    error: String,
-    code: String,
+struct SimulateResponse {
+    parameters: SimulationParameters,
+    count: usize,
+    start_date: String,
+    end_date: String,
+    data: Vec<HistoryDataPoint>,
+    transitions: Vec<AlertTransitionResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    negative_denominator: Option<NegativeDenominatorReport>,
+    model_version: String,
 }
 
-const MODEL_VERSION: &str = "NIV-v6-OOS";
-const MODEL_AUC: f64 = 0.849;
+/// Months in the simulated range whose denominator base was negative
+#[derive(Serialize)]
+struct NegativeDenominatorReport {
+    policy: DenominatorPolicy,
+    months: usize,
+    first: String,
+    last: String,
+}
+
+#[derive(Serialize)]
+struct SimulationParameters {
+    eta: f64,
+    epsilon: f64,
+    weights: ComponentWeights,
+    probability_input: ProbabilityInput,
+    thrust_scaling: ThrustScaling,
+    efficiency: EfficiencySpec,
+    gdp: GdpSpec,
+    inflation: InflationSpec,
+    spread: SpreadSpec,
+    slack: SlackSpec,
+    nonfinite: NonFinitePolicy,
+    denominator: DenominatorPolicy,
+    warmup: WarmupPolicy,
+    expansion_age_weight: f64,
+    components: Vec<ComponentDef>,
+}
+
+#[derive(Serialize)]
+struct AlertTransitionResponse {
+    date: String,
+    from: AlertLevel,
+    to: AlertLevel,
+    recession_probability: f64,
+    trigger: Component,
+    trigger_delta: f64,
+}
+
+#[derive(Serialize)]
+struct HealthResponse {
+    status: String,
+    version: String,
+    model_version: String,
+    data_points: usize,
+    last_update: String,
+    validation_passed: Option<bool>,
+    ready: bool,
+    lanes: Vec<LaneStats>,
+}
+
+#[derive(Serialize)]
+struct ReadyResponse {
+    ready: bool,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+    code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    valid_range: Option<DateRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_span_months: Option<u32>,
+}
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+/// JSON response carrying the job's compute and queue time (`x-compute-ms`, `x-queue-ms`)
+type Timed<T> = ([(&'static str, String); 2], Json<T>);
+
+fn timed<T>(body: T, elapsed: Duration, queued: Duration) -> Timed<T> {
+    (
+        [
+            ("x-compute-ms", elapsed.as_millis().to_string()),
+            ("x-queue-ms", queued.as_millis().to_string()),
+        ],
+        Json(body),
+    )
+}
+
+fn api_error(status: StatusCode, code: &str, error: impl Into<String>) -> ApiError {
+    (status, Json(ErrorResponse {
+        error: error.into(),
+        code: code.to_string(),
+        valid_range: None,
+        max_span_months: None,
+    }))
+}
+
+/// 400 for malformed ranges, 422 for ranges the data cannot satisfy
+fn range_error(e: RangeError, available: DateRange) -> ApiError {
+    let status = if e.is_unprocessable() {
+        StatusCode::UNPROCESSABLE_ENTITY
+    } else {
+        StatusCode::BAD_REQUEST
+    };
+    let max_span_months = match e {
+        RangeError::SpanTooLarge { max_months, .. } => Some(max_months),
+        _ => None,
+    };
+    (status, Json(ErrorResponse {
+        error: e.to_string(),
+        code: e.code().to_string(),
+        valid_range: Some(available),
+        max_span_months,
+    }))
+}
+
+/// Reject requests estimated above the compute budget before they start
+fn request_error(e: RequestError) -> ApiError {
+    api_error(StatusCode::BAD_REQUEST, e.code, e.message)
+}
+
+fn check_budget(state: &AppState, units: u64) -> Result<(), ApiError> {
+    state.budget.check(units).map(|_| ()).map_err(|e| {
+        api_error(StatusCode::UNPROCESSABLE_ENTITY, "BUDGET_EXCEEDED", e.to_string())
+    })
+}
+
+fn job_error(e: JobError, code: &str) -> ApiError {
+    match e {
+        // The client has gone; nobody reads this response
+        JobError::Cancelled => api_error(StatusCode::SERVICE_UNAVAILABLE, "CANCELLED", "request cancelled"),
+        JobError::Failed(msg) => api_error(StatusCode::INTERNAL_SERVER_ERROR, code, msg),
+    }
+}
+
+/// Validate a request's start/end against the computed history
+fn resolve_range(
+    data: &[NIVResult],
+    start: Option<&str>,
+    end: Option<&str>,
+    fields: [&'static str; 2],
+    max_span_months: Option<u32>,
+) -> Result<DateRange, ApiError> {
+    let available = match (data.first(), data.last()) {
+        (Some(first), Some(last)) => DateRange { start: first.date, end: last.date },
+        _ => return Err(api_error(StatusCode::SERVICE_UNAVAILABLE, "NO_DATA", "No data available")),
+    };
+    daterange::resolve(start, end, fields, available, max_span_months).map_err(|e| range_error(e, available))
+}
+
+/// Metadata of the replication CSV
+#[derive(Serialize)]
+struct ReplicationResponse {
+    schema_version: &'static str,
+    csv_file: String,
+    csv_sha256: String,
+    columns: Vec<ReplicationColumn>,
+    pipeline_hash: String,
+    model_version: String,
+    crate_version: &'static str,
+    parameters: serde_json::Map<String, serde_json::Value>,
+    labels: String,
+    label_horizon_months: u32,
+    fold_scheme: &'static str,
+    reported_auc: f64,
+    overall: replication::Metrics,
+    folds: Vec<replication::Fold>,
+}
+
+#[derive(Serialize)]
+struct ReplicationColumn {
+    name: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    description: &'static str,
+}
+
+#[derive(Serialize)]
+struct LeadTimeResponse {
+    threshold: f64,
+    labels: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    regime: Option<usize>,
+    #[serde(flatten)]
+    distribution: LeadTimeDistribution,
+    model_version: String,
+}
+
+/// Paths aligned on recession starts
+#[derive(Serialize)]
+struct EventStudyResponse {
+    pre_months: u32,
+    post_months: u32,
+    labels: String,
+    #[serde(flatten)]
+    study: eventstudy::EventStudy, // Probability bands in percent
+    model_version: String,
+}
+
+/// Historical false alarms
+#[derive(Serialize)]
+struct FalseAlarmsResponse {
+    threshold: f64,
+    lookback_months: u32,
+    labels: String,
+    count: usize,
+    months_in_false_alarm: usize,
+    false_alarms: Vec<FalseAlarm>, // peak_probability in the request's units
+    model_version: String,
+}
+
+#[derive(Serialize)]
+struct EpisodesResponse {
+    level: AlertLevel,
+    threshold: f64,
+    min_duration_months: usize,
+    lookback_months: u32,
+    labels: String,
+    count: usize,
+    episodes: Vec<Episode>,
+    model_version: String,
+}
+
+#[derive(Serialize)]
+struct SyntheticBenchmarkResponse {
+    threshold: f64,
+    #[serde(flatten)]
+    benchmark: SyntheticBenchmark,
+    model_version: String,
+}
+
+#[derive(Serialize)]
+struct MonteCarloResponse {
+    id: String,
+    cached: bool,
+    scenario: String,
+    horizon_months: usize,
+    draws: usize,
+    seed: u64,
+    sampling: Sampling,
+    starting_alert_level: AlertLevel,
+    prob_critical_within_horizon: f64,
+    months: Vec<MonteCarloMonth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distribution: Option<Vec<Vec<f64>>>, // Per month, every draw's probability (sorted); by ID only
+    dataset_revision: String,
+    model_version: String,
+}
+
+/// T10Y3M history and inversion episodes
+#[derive(Serialize)]
+struct YieldCurveResponse {
+    series: &'static str,
+    count: usize,
+    current_spread: f64,
+    inverted_now: bool,
+    episodes_followed_by_recession: usize,
+    data: Vec<YieldCurvePoint>,
+    episodes: Vec<InversionEpisodeResponse>,
+}
+
+#[derive(Serialize)]
+struct YieldCurvePoint {
+    date: String,
+    spread: f64,
+    inverted: bool,
+    is_recession: bool,
+}
+
+#[derive(Serialize)]
+struct InversionEpisodeResponse {
+    start: String,
+    end: String,
+    months: usize,
+    max_depth: f64,
+    trough_date: String,
+    ongoing: bool,
+    recession: Option<FollowingRecessionResponse>,
+}
+
+#[derive(Serialize)]
+struct FollowingRecessionResponse {
+    name: String,
+    start: String,
+    end: String,
+    lead_months: i32,
+}
+
+#[derive(Serialize)]
+struct FredSeriesResponse {
+    series_id: String,
+    count: usize,
+    cached: bool,
+    observations: Vec<FredObservationResponse>,
+}
+
+#[derive(Serialize)]
+struct FredObservationResponse {
+    date: String,
+    value: f64,
+}
+
+#[derive(Serialize)]
+struct ShareResponse {
+    kind: ShareKind,
+    token: String,
+    url: String,
+    expires_at: String,
+}
+
+#[derive(Serialize)]
+struct RefreshResponse {
+    refreshed_at: String,
+    data_points: usize,
+    canary: Option<CanaryStatus>,
+    model_version: String,
+}
+
+#[derive(Serialize)]
+struct CacheResponse {
+    namespaces: Vec<NamespaceUsage>,
+    cache_entries: u64,
+    monte_carlo_runs: u64,
+}
+
+#[derive(Serialize)]
+struct PromotionResponse {
+    promoted: String,
+    previous: String,
+    data_points: usize,
+    model_version: String,
+}
+
+#[derive(Serialize)]
+struct MonteCarloMonth {
+    date: String,
+    scenario_probability: f64,
+    p10: f64,
+    p50: f64,
+    p90: f64,
+    prob_critical_by_month: f64,
+}
+
+const MODEL_VERSION: &str = "NIV-v6-OOS";
+const MODEL_AUC: f64 = 0.849;
 const FED_AUC: f64 = 0.840;
+const FORECAST_SEED: u64 = 2008; // Baseline fan on /api/v1/history
+const MC_RUN_BYTES: u64 = 128 * 1024 * 1024; // Stored Monte Carlo runs, by approx_bytes
+const MC_RUN_TTL: Duration = Duration::from_secs(24 * 3600);
+const MAX_SYNTHETIC_ECONOMIES: usize = 100;
+const WIDGET_EVENT_BUFFER: usize = 16;
+const RELEASE_CALENDAR_TIMEOUT: Duration = Duration::from_secs(60); // Whole calendar fetch
+const RELEASE_CALENDAR_RETRY: Duration = Duration::from_secs(5 * 60); // After a failed fetch
+
+#[tokio::main]
+async fn main() {
+    // Initialize tracing
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::EnvFilter::new(
+            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
+        ))
+        .init();
+
+    // `publish --out <dir>` writes the static JSON bundle instead of serving
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let publish_dir = publish::parse_args(&args).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+
+    tracing::info!("Starting NIV Engine API Server {}", MODEL_VERSION);
+    tracing::info!("OOS Performance: AUC {} vs Fed Yield Curve {}", MODEL_AUC, FED_AUC);
+
+    // Slack specification (NIV_SLACK_SPEC: capacity_utilization | output_gap | unemployment_gap)
+    let slack_spec = match std::env::var("NIV_SLACK_SPEC") {
+        Ok(raw) => raw.parse::<SlackSpec>().unwrap_or_else(|e| {
+            tracing::warn!("{}; using capacity_utilization", e);
+            SlackSpec::default()
+        }),
+        Err(_) => SlackSpec::default(),
+    };
+    tracing::info!("Slack specification: {:?}", slack_spec);
+
+    // Efficiency denominator (NIV_GDP_SPEC: reported | nowcast)
+    let gdp_spec = match std::env::var("NIV_GDP_SPEC") {
+        Ok(raw) => raw.parse::<GdpSpec>().unwrap_or_else(|e| {
+            tracing::warn!("{}; using reported", e);
+            GdpSpec::default()
+        }),
+        Err(_) => GdpSpec::default(),
+    };
+    tracing::info!("GDP specification: {:?}", gdp_spec);
+
+    // Real-rate inflation measure (NIV_INFLATION_SPEC: cpi | core_cpi | pce | expected)
+    let inflation_spec = match std::env::var("NIV_INFLATION_SPEC") {
+        Ok(raw) => raw.parse::<InflationSpec>().unwrap_or_else(|e| {
+            tracing::warn!("{}; using cpi", e);
+            InflationSpec::default()
+        }),
+        Err(_) => InflationSpec::default(),
+    };
+    tracing::info!("Inflation specification: {:?} ({})", inflation_spec, inflation_spec.series());
+
+    // Term-spread drag (NIV_SPREAD_SPEC: inversion | level | change_12m)
+    let spread_spec = match std::env::var("NIV_SPREAD_SPEC") {
+        Ok(raw) => raw.parse::<SpreadSpec>().unwrap_or_else(|e| {
+            tracing::warn!("{}; using inversion", e);
+            SpreadSpec::default()
+        }),
+        Err(_) => SpreadSpec::default(),
+    };
+    tracing::info!("Spread specification: {:?}", spread_spec);
+
+    // Non-finite component policy (NIV_NONFINITE_POLICY: clamp | carry_forward)
+    let nonfinite_policy = match std::env::var("NIV_NONFINITE_POLICY") {
+        Ok(raw) => raw.parse::<NonFinitePolicy>().unwrap_or_else(|e| {
+            tracing::warn!("{}; using clamp", e);
+            NonFinitePolicy::default()
+        }),
+        Err(_) => NonFinitePolicy::default(),
+    };
+    tracing::info!("Non-finite policy: {:?}", nonfinite_policy);
+
+    // Smoothing warm-up (NIV_WARMUP: flag | trim)
+    let warmup_policy = match std::env::var("NIV_WARMUP") {
+        Ok(raw) => raw.parse::<WarmupPolicy>().unwrap_or_else(|e| {
+            tracing::warn!("{}; using flag", e);
+            WarmupPolicy::default()
+        }),
+        Err(_) => WarmupPolicy::default(),
+    };
+    tracing::info!("Warm-up policy: {:?}", warmup_policy);
+
+    // Ragged edge of live FRED data (NIV_PARTIAL_DATA: off | last_value | trend)
+    let partial_data = match std::env::var("NIV_PARTIAL_DATA") {
+        Ok(raw) => raw.parse::<coverage::PartialDataMode>().unwrap_or_else(|e| {
+            tracing::warn!("{}; using off", e);
+            coverage::PartialDataMode::default()
+        }),
+        Err(_) => coverage::PartialDataMode::default(),
+    };
+    tracing::info!("Partial data mode: {:?}", partial_data);
+
+    // Expansion-age conditioning (NIV_EXPANSION_AGE_WEIGHT, logit per year beyond the pivot)
+    let expansion_age_weight = match std::env::var("NIV_EXPANSION_AGE_WEIGHT") {
+        Ok(raw) => raw.parse::<f64>().ok().filter(|w| w.abs() <= niv::MAX_EXPANSION_AGE_WEIGHT).unwrap_or_else(|| {
+            tracing::warn!("Ignoring invalid NIV_EXPANSION_AGE_WEIGHT '{}'", raw);
+            0.0
+        }),
+        Err(_) => 0.0,
+    };
+    tracing::info!("Expansion-age weight: {}", expansion_age_weight);
+
+    // Custom components (NIV_COMPONENTS_FILE); a bad file is fatal rather than silently ignored
+    let registry = match std::env::var("NIV_COMPONENTS_FILE") {
+        Ok(path) => match ComponentRegistry::load(&path) {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::error!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => ComponentRegistry::default(),
+    };
+    for def in registry.definitions() {
+        tracing::info!("Custom component {} ({:?}): {}", def.name, def.role, def.expr);
+    }
+
+    // Input transformations (NIV_PIPELINES_FILE); a bad file is fatal like a bad registry
+    let pipelines = match std::env::var("NIV_PIPELINES_FILE") {
+        Ok(path) => match transform::Pipelines::load(&path) {
+            Ok(p) => p,
+            Err(e) => {
+                tracing::error!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        Err(_) => transform::Pipelines::default(),
+    };
+    if pipelines != transform::Pipelines::default() {
+        tracing::info!("Input pipelines: {}", serde_json::to_string(&pipelines).unwrap_or_default());
+    }
+
+    // Initialize engine and compute initial data
+    let engine = NIVEngine::new()
+        .with_gdp_spec(gdp_spec)
+        .with_inflation_spec(inflation_spec)
+        .with_spread_spec(spread_spec)
+        .with_slack_spec(slack_spec)
+        .with_nonfinite_policy(nonfinite_policy)
+        .with_warmup_policy(warmup_policy)
+        .with_expansion_age_weight(expansion_age_weight)
+        .with_registry(Arc::new(registry))
+        .with_pipelines(Arc::new(pipelines));
+    let snapshot_path = std::env::var("NIV_SNAPSHOT_FILE").ok().map(std::path::PathBuf::from);
+    #[cfg(not(feature = "snapshot"))]
+    let snapshot_path = snapshot_path.and_then(|path| {
+        tracing::warn!("Ignoring NIV_SNAPSHOT_FILE={}: built without the snapshot feature", path.display());
+        None::<std::path::PathBuf>
+    });
+
+    // Methodology changelog (NIV_CHANGELOG_FILE); an unreadable log is fatal rather than silently restarted
+    let changelog = match std::env::var("NIV_CHANGELOG_FILE") {
+        Ok(path) => Changelog::load(path.into()).unwrap_or_else(|e| {
+            tracing::error!("Changelog: {}", e);
+            std::process::exit(1);
+        }),
+        Err(_) => Changelog::default(),
+    };
+
+    // Endpoint deprecations (NIV_DEPRECATIONS_FILE); a bad registry is fatal rather than silently dropped
+    let deprecations = match std::env::var("NIV_DEPRECATIONS_FILE") {
+        Ok(path) => DeprecationRegistry::load(&path).unwrap_or_else(|e| {
+            tracing::error!("Deprecations: {}", e);
+            std::process::exit(1);
+        }),
+        Err(_) => DeprecationRegistry::default(),
+    };
+
+    // Recession label sets (NIV_LABELS_DIR); a bad set is fatal rather than silently dropped
+    let labels = match std::env::var("NIV_LABELS_DIR") {
+        Ok(dir) => LabelSets::load_dir(std::path::Path::new(&dir)).unwrap_or_else(|e| {
+            tracing::error!("Label sets: {}", e);
+            std::process::exit(1);
+        }),
+        Err(_) => LabelSets::default(),
+    };
+    tracing::info!("Recession label sets: {}", labels.all().map(|s| s.name.as_str()).collect::<Vec<_>>().join(", "));
+
+    // TSDB export (NIV_TSDB_CONFIG); like the components file, a bad config is fatal
+    #[cfg(feature = "tsdb")]
+    let tsdb_config = std::env::var("NIV_TSDB_CONFIG").ok().map(|path| match tsdb::TsdbConfig::load(&path) {
+        Ok(config) => {
+            tracing::info!("TSDB export enabled (measurement {})", config.measurement);
+            config
+        }
+        Err(e) => {
+            tracing::error!("{}", e);
+            std::process::exit(1);
+        }
+    });
+    #[cfg(not(feature = "tsdb"))]
+    if let Ok(path) = std::env::var("NIV_TSDB_CONFIG") {
+        tracing::warn!("Ignoring NIV_TSDB_CONFIG={}: built without the tsdb feature", path);
+    }
+
+    // Self-test failure policy (NIV_SELFTEST_POLICY: warn | refuse)
+    let selftest_policy = match std::env::var("NIV_SELFTEST_POLICY") {
+        Ok(raw) => raw.parse::<FailurePolicy>().unwrap_or_else(|e| {
+            tracing::warn!("{}; using warn", e);
+            FailurePolicy::default()
+        }),
+        Err(_) => FailurePolicy::default(),
+    };
+
+    // Per-item TTLs (see cache.rs)
+    let cache: Cache<CacheKey, CachedData> = Cache::builder().expire_after(cache::PolicyExpiry).build();
+
+    let max_span_months = std::env::var("NIV_MAX_SPAN_MONTHS").ok().and_then(|raw| {
+        let parsed = raw.parse::<u32>().ok().filter(|m| *m > 0);
+        if parsed.is_none() {
+            tracing::warn!("Ignoring invalid NIV_MAX_SPAN_MONTHS '{}'", raw);
+        }
+        parsed
+    });
+
+    let budget = match std::env::var("NIV_COMPUTE_BUDGET") {
+        Ok(raw) => match raw.parse::<u64>() {
+            Ok(max_units) if max_units > 0 => ComputeBudget { max_units },
+            _ => {
+                tracing::warn!("Ignoring invalid NIV_COMPUTE_BUDGET '{}'", raw);
+                ComputeBudget::default()
+            }
+        },
+        Err(_) => ComputeBudget::default(),
+    };
+    tracing::info!("Compute budget: {} engine-months per request", budget.max_units);
+
+    // Job lanes (NIV_INTERACTIVE_WORKERS, NIV_BATCH_WORKERS)
+    let lane_defaults = LaneConfig::default();
+    let positive = |var: &str, default: usize| match std::env::var(var) {
+        Ok(raw) => raw.parse::<usize>().ok().filter(|n| *n > 0).unwrap_or_else(|| {
+            tracing::warn!("Ignoring invalid {} '{}'", var, raw);
+            default
+        }),
+        Err(_) => default,
+    };
+    let lanes = LaneConfig {
+        interactive_workers: positive("NIV_INTERACTIVE_WORKERS", lane_defaults.interactive_workers),
+        batch_workers: positive("NIV_BATCH_WORKERS", lane_defaults.batch_workers),
+    };
+    tracing::info!(
+        "Job lanes: {} interactive, {} batch workers",
+        lanes.interactive_workers, lanes.batch_workers
+    );
+
+    // Refresh schedule and canary promotion (NIV_REFRESH_SECS, NIV_CANARY_REFRESHES)
+    let refresh_secs = std::env::var("NIV_REFRESH_SECS").ok().and_then(|raw| {
+        let parsed = raw.parse::<u64>().ok().filter(|s| *s > 0);
+        if parsed.is_none() {
+            tracing::warn!("Ignoring invalid NIV_REFRESH_SECS '{}'", raw);
+        }
+        parsed
+    });
+    let canary_refreshes = positive("NIV_CANARY_REFRESHES", canary::DEFAULT_CANARY_REFRESHES);
+    // Router profile (NIV_ROUTER_PROFILE: full | mirror | demo) and mirror caching
+    let router_profile = match std::env::var("NIV_ROUTER_PROFILE").as_deref() {
+        Ok("mirror") => RouterProfile::Mirror,
+        Ok("demo") => RouterProfile::Demo,
+        Ok("full") | Err(_) => RouterProfile::Full,
+        Ok(other) => {
+            tracing::warn!("Ignoring invalid NIV_ROUTER_PROFILE '{}'; serving the full API", other);
+            RouterProfile::Full
+        }
+    };
+    let mirror_max_age = positive("NIV_MIRROR_MAX_AGE_SECS", DEFAULT_MIRROR_MAX_AGE_SECS as usize) as u64;
+    if router_profile == RouterProfile::Mirror {
+        tracing::info!("Router profile: read-only mirror, cached for {}s", mirror_max_age);
+    }
+    // Demo profile: canned data only, so nothing may reach FRED (every FRED
+    // client is built with fred_access) and the dataset is never refreshed
+    let demo = router_profile == RouterProfile::Demo;
+    let demo_rpm = positive("NIV_DEMO_RPM", demo::DEFAULT_DEMO_RPM);
+    let fred_access = if demo { FredAccess::Offline } else { FredAccess::Live };
+    if demo {
+        tracing::info!(
+            "Router profile: public demo, {} requests per client per minute; live FRED, refreshes and admin endpoints disabled",
+            demo_rpm
+        );
+    }
+    let refresh_secs = refresh_secs.filter(|_| !demo);
+    let export_memory = positive("NIV_EXPORT_MEMORY_BYTES", streaming::DEFAULT_EXPORT_MEMORY_BYTES);
+    tracing::info!("Export memory ceiling: {} bytes per request", export_memory);
+    let intraday_secs = std::env::var("NIV_INTRADAY_SECS").ok().map(|raw| {
+        raw.parse::<u64>().ok().filter(|s| *s > 0).unwrap_or_else(|| {
+            tracing::warn!("Ignoring invalid NIV_INTRADAY_SECS '{}'", raw);
+            intraday::DEFAULT_INTRADAY_SECS
+        })
+    });
+
+    // Refresh backpressure (NIV_REFRESH_MAX_P95_MS, NIV_REFRESH_MAX_LOAD, NIV_REFRESH_MAX_DEFER_SECS)
+    let backpressure_defaults = Backpressure::default();
+    let max_load = match std::env::var("NIV_REFRESH_MAX_LOAD") {
+        Ok(raw) => raw.parse::<f64>().ok().filter(|l| *l > 0.0 && l.is_finite()).unwrap_or_else(|| {
+            tracing::warn!("Ignoring invalid NIV_REFRESH_MAX_LOAD '{}'", raw);
+            backpressure_defaults.max_load
+        }),
+        Err(_) => backpressure_defaults.max_load,
+    };
+    let backpressure = Backpressure {
+        max_p95: Duration::from_millis(
+            positive("NIV_REFRESH_MAX_P95_MS", backpressure_defaults.max_p95.as_millis() as usize) as u64,
+        ),
+        max_load,
+        max_defer: Duration::from_secs(
+            positive("NIV_REFRESH_MAX_DEFER_SECS", backpressure_defaults.max_defer.as_secs() as usize) as u64,
+        ),
+    };
+    if refresh_secs.is_some() {
+        tracing::info!(
+            "Refresh backpressure: p95 above {} ms or load above {:.2}/core defers up to {}s",
+            backpressure.max_p95.as_millis(),
+            backpressure.max_load,
+            backpressure.max_defer.as_secs()
+        );
+    }
+    let admin_token = std::env::var("NIV_ADMIN_TOKEN").ok().filter(|t| !t.is_empty() && !demo);
+    if admin_token.is_none() {
+        tracing::info!("NIV_ADMIN_TOKEN not set; admin endpoints disabled");
+    }
+
+    // FRED passthrough: needs FRED_API_KEY; NIV_FRED_PROXY_RPM caps upstream requests
+    let fred_proxy_rpm = std::env::var("NIV_FRED_PROXY_RPM").ok().and_then(|raw| {
+        let parsed = raw.parse::<usize>().ok().filter(|n| *n > 0);
+        if parsed.is_none() {
+            tracing::warn!("Ignoring invalid NIV_FRED_PROXY_RPM '{}'", raw);
+        }
+        parsed
+    });
+    #[cfg(feature = "fred")]
+    let fred_proxy = fred::FredClient::new(fred_access).ok().map(|client| {
+        fred_proxy::FredProxy::new(client, fred_proxy_rpm.unwrap_or(fred_proxy::DEFAULT_REQUESTS_PER_MINUTE))
+    });
+    #[cfg(not(feature = "fred"))]
+    if fred_proxy_rpm.is_some() {
+        tracing::warn!("Ignoring NIV_FRED_PROXY_RPM: built without the fred feature");
+    }
+
+    // API keys → workspaces (NIV_API_KEYS_FILE); a bad file is fatal rather than leaving teams unisolated
+    let api_keys = match std::env::var("NIV_API_KEYS_FILE") {
+        Ok(path) => {
+            let keys = ApiKeys::load(&path).unwrap_or_else(|e| {
+                tracing::error!("API keys: {}", e);
+                std::process::exit(1);
+            });
+            tracing::info!("Loaded {} API keys", keys.len());
+            keys
+        }
+        Err(_) => ApiKeys::default(),
+    };
+
+    // Preference profiles (NIV_PREFERENCES_FILE); a bad file is fatal rather than silently dropping profiles
+    let preferences = match std::env::var("NIV_PREFERENCES_FILE") {
+        Ok(path) => {
+            let store = PreferenceStore::load(path.into()).unwrap_or_else(|e| {
+                tracing::error!("Preferences: {}", e);
+                std::process::exit(1);
+            });
+            tracing::info!("Loaded {} preference profiles", store.len());
+            store
+        }
+        Err(_) => PreferenceStore::default(),
+    };
+
+    let incident_webhook = std::env::var("NIV_INCIDENT_WEBHOOK").ok().filter(|url| !url.is_empty());
+    #[cfg(not(feature = "webhooks"))]
+    if incident_webhook.is_some() {
+        tracing::warn!("Ignoring NIV_INCIDENT_WEBHOOK: built without the webhooks feature");
+    }
+
+    let share = match std::env::var("NIV_SHARE_SECRET") {
+        Ok(secret) if !secret.is_empty() => ShareSigner::new(secret.as_bytes()),
+        _ => {
+            tracing::info!("NIV_SHARE_SECRET not set; share links last until restart");
+            ShareSigner::random()
+        }
+    };
+
+    let public = std::env::var("NIV_PUBLIC_EMBARGO_HOURS").ok().and_then(|raw| match raw.parse::<u32>() {
+        Ok(hours) => {
+            tracing::info!(
+                "Public tier: keyless probabilities in {}-point buckets, {}h embargo",
+                public::BUCKET_POINTS, hours
+            );
+            Some(PublicTier::new(chrono::Duration::hours(hours.into())))
+        }
+        Err(_) => {
+            tracing::warn!("Ignoring invalid NIV_PUBLIC_EMBARGO_HOURS '{}'", raw);
+            None
+        }
+    });
+
+    let state = Arc::new(AppState {
+        serving: std::sync::RwLock::new(Serving {
+            version: MODEL_VERSION.to_string(),
+            engine: Arc::new(engine),
+        }),
+        cache,
+        mc_runs: Cache::builder()
+            .max_capacity(MC_RUN_BYTES)
+            .weigher(|id: &String, run: &Arc<StoredRun>| weigh_run(id, run))
+            .time_to_live(MC_RUN_TTL)
+            .build(),
+        revision: std::sync::RwLock::new(String::new()),
+        inputs: RwLock::new(Vec::new()),
+        raw: RwLock::new(Vec::new()),
+        data: RwLock::new(Vec::new()),
+        validation: RwLock::new(None),
+        ready: AtomicBool::new(false),
+        replays: RwLock::new(HashMap::new()),
+        http: WebhookClient::new(),
+        max_span_months,
+        budget,
+        jobs: Scheduler::new(lanes),
+        snapshot_path,
+        refreshing: tokio::sync::Mutex::new(()),
+        refresh_schedule: refresh_secs.map(|secs| (chrono::Utc::now(), Duration::from_secs(secs))),
+        backpressure,
+        load: LoadMonitor::default(),
+        releases: tokio::sync::Mutex::new(None),
+        calibrations: RwLock::new(None),
+        intraday: RwLock::new(None),
+        changelog: RwLock::new(changelog),
+        deprecations,
+        labels: RwLock::new(labels),
+        updates: tokio::sync::broadcast::channel(WIDGET_EVENT_BUFFER).0,
+        #[cfg(feature = "tsdb")]
+        tsdb: tsdb_config.map(|config| (reqwest::Client::new(), config)),
+        #[cfg(feature = "fred")]
+        fred_proxy,
+        fred_access,
+        canary: RwLock::new(None),
+        canary_refreshes,
+        admin_token,
+        api_keys,
+        share,
+        public,
+        preferences,
+        incidents: RwLock::new(VecDeque::new()),
+        incident_webhook,
+        export_memory,
+        partial_data,
+        tasks: Tasks::default(),
+    });
+
+    state.tasks.register(tasks::DATASET_LOAD, Schedule::Startup);
+    state.tasks.register(tasks::REFRESH, refresh_secs.map_or(Schedule::OnDemand, |secs| Schedule::Every { secs }));
+    state.tasks.register(tasks::INTRADAY, Schedule::Disabled);
+    #[cfg(feature = "tsdb")]
+    let tsdb_schedule = if state.tsdb.is_some() { Schedule::OnDemand } else { Schedule::Disabled };
+    #[cfg(not(feature = "tsdb"))]
+    let tsdb_schedule = Schedule::Disabled;
+    state.tasks.register(tasks::TSDB_EXPORT, tsdb_schedule);
+    let incident_schedule = if state.incident_webhook.is_some() { Schedule::OnDemand } else { Schedule::Disabled };
+    state.tasks.register(tasks::INCIDENT_WEBHOOK, incident_schedule);
+    state.tasks.register(tasks::REPLAY, Schedule::OnDemand);
+
+    if let Some(out) = publish_dir {
+        let code = match publish_bundle(state, &out).await {
+            Ok(()) => 0,
+            Err(e) => {
+                tracing::error!("Publish failed: {}", e);
+                1
+            }
+        };
+        std::process::exit(code);
+    }
+
+    // Load data in the background; the listener starts immediately and data
+    // endpoints answer 503 until the dataset is ready
+    tokio::spawn(load_data(state.clone(), selftest_policy));
+    if let Some(secs) = refresh_secs {
+        tokio::spawn(refresh_loop(state.clone(), Duration::from_secs(secs)));
+    }
+    if let Some(secs) = intraday_secs {
+        #[cfg(feature = "fred")]
+        if state.fred_proxy.is_some() {
+            tracing::info!("Intraday provisional recompute every {}s", secs);
+            state.tasks.register(tasks::INTRADAY, Schedule::Every { secs });
+            tokio::spawn(intraday_loop(state.clone(), Duration::from_secs(secs)));
+        } else {
+            tracing::warn!("Ignoring NIV_INTRADAY_SECS: market data needs FRED_API_KEY");
+        }
+        #[cfg(not(feature = "fred"))]
+        tracing::warn!("Ignoring NIV_INTRADAY_SECS ({}s): built without the fred feature", secs);
+    }
+
+    let app = match router_profile {
+        RouterProfile::Full => router(state),
+        RouterProfile::Mirror => mirror_router(state, mirror_max_age),
+        RouterProfile::Demo => demo_router(
+            state,
+            DemoPolicy {
+                limiter: demo::ClientLimiter::new(demo_rpm, Duration::from_secs(60)),
+                client_ip_header: std::env::var("NIV_DEMO_CLIENT_IP_HEADER").ok().filter(|h| !h.is_empty()),
+            },
+        ),
+    };
+
+    // Get port from environment or default
+    let port = std::env::var("PORT")
+        .unwrap_or_else(|_| "8080".to_string())
+        .parse::<u16>()
+        .unwrap_or(8080);
+
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    tracing::info!("Listening on http://{}", addr);
+
+    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+    // Peer addresses identify demo clients
+    axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await.unwrap();
+}
+
+/// Every route, with the workspace, readiness, admin and deprecation layers
+fn router(state: Arc<AppState>) -> Router {
+    let admin_routes = Router::new()
+        .route("/api/v1/admin/refresh", post(admin_refresh))
+        .route("/api/v1/admin/canary", post(register_canary).get(get_canary).delete(discard_canary))
+        .route("/api/v1/admin/canary/promote", post(promote_canary))
+        .route("/api/v1/admin/cache", get(get_cache_usage))
+        .route("/api/v1/admin/incidents", get(get_incidents))
+        .route("/api/v1/admin/scheduler", get(get_scheduler))
+        .route("/api/v1/admin/notifications/dry-run", post(notification_dry_run))
+        .route("/api/v1/admin/validation/live", post(live_validation))
+        .route("/api/v1/admin/labels/:name", axum::routing::put(put_label_set).delete(delete_label_set))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin));
+
+    // Interactive requests: their latencies drive refresh backpressure
+    let interactive_routes = Router::new()
+        .route("/api/v1/latest", get(get_latest))
+        .route("/api/v1/history", get(get_history))
+        .route("/api/v1/at/batch", post(get_at_batch))
+        .route("/api/v1/components", get(get_components))
+        .route("/api/v1/dashboard", get(get_dashboard))
+        .route("/api/v1/analogues", get(get_analogues))
+        .route("/api/v1/conditional", get(get_conditional))
+        .route("/api/v1/regimes", get(get_regimes))
+        .route("/widget", get(get_widget))
+        .route("/grafana/search", post(grafana_search))
+        .route("/grafana/query", post(grafana_query))
+        .route("/grafana/annotations", post(grafana_annotations))
+        .route("/api/v1/compare", get(get_comparison))
+        .route("/api/v1/yield-curve", get(get_yield_curve))
+        .route("/api/v1/term-structure", get(get_term_structure))
+        .route("/api/v1/thrust-inputs", get(get_thrust_inputs))
+        .route("/api/v1/lead-times", get(get_lead_times))
+        .route("/api/v1/false-alarms", get(get_false_alarms))
+        .route("/api/v1/episodes", get(get_episodes))
+        .route("/api/v1/event-study", get(get_event_study))
+        .route("/api/v1/backtest/replication", get(get_replication))
+        .route("/api/v1/backtest/replication.csv", get(get_replication_csv))
+        .route("/api/v1/simulate", post(simulate))
+        .route("/api/v1/simulate/bounds", get(get_simulate_bounds))
+        .route("/api/v1/montecarlo/:id", get(get_monte_carlo))
+        .route("/api/v1/workspace", get(get_workspace))
+        .route("/api/v1/preferences", get(get_preferences).put(put_preferences).delete(delete_preferences))
+        .route("/api/v1/replay/start", post(start_replay))
+        .route("/api/v1/replay/:id", get(get_replay))
+        .route("/api/v1/replay/:id/stop", post(stop_replay))
+        .route_layer(middleware::from_fn_with_state(state.clone(), record_latency));
+
+    // Batch-lane work, upstream fetches and streams, which would skew the latencies
+    let data_routes = interactive_routes
+        .route("/api/v1/export", get(export_history))
+        .route("/calendar.ics", get(get_calendar))
+        .route("/widget/events", get(widget_events))
+        .route("/api/v1/fred/:series_id", get(get_fred_series))
+        .route("/api/v1/research/leaderboard", get(get_leaderboard))
+        .route("/api/v1/models/:id/card", get(get_model_card))
+        .route("/api/v1/montecarlo", post(run_monte_carlo))
+        .route("/api/v1/share", post(create_share))
+        .route("/s/:token", get(get_shared))
+        .route("/api/v1/replay/:id/events", get(replay_events))
+        .route_layer(middleware::from_fn_with_state(state.clone(), public_scope))
+        .merge(admin_routes)
+        .route_layer(middleware::from_fn_with_state(state.clone(), resolve_workspace))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_ready));
+
+    Router::new()
+        .route("/", get(root))
+        .route("/health", get(health))
+        .route("/grafana", get(health))
+        .route("/health/ready", get(health_ready))
+        .route("/api/v1/recessions", get(get_recessions))
+        .route("/api/v1/labels", get(get_label_sets))
+        .route("/api/v1/changelog", get(get_changelog))
+        .route("/api/v1/pipeline", get(get_pipeline))
+        .route("/api/v1/deprecations", get(get_deprecations))
+        .route("/api/v1/validation", get(get_validation))
+        .route("/api/v1/synthetic-benchmark", get(get_synthetic_benchmark))
+        .merge(data_routes)
+        .layer(middleware::from_fn_with_state(state.clone(), response_format))
+        .layer(middleware::from_fn_with_state(state.clone(), deprecation_headers))
+        .layer(middleware::from_fn_with_state(state.clone(), pipeline_header))
+        .layer(cors())
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+/// The read-only mirror profile (NIV_ROUTER_PROFILE=mirror): latest, history
+/// and recessions only, served as keyless requests with `max_age` caching so
+/// a CDN can front them
+fn mirror_router(state: Arc<AppState>, max_age: u64) -> Router {
+    let data_routes = Router::new()
+        .route("/api/v1/latest", get(get_latest))
+        .route("/api/v1/history", get(get_history))
+        .route_layer(middleware::from_fn_with_state(state.clone(), record_latency))
+        .route_layer(middleware::from_fn_with_state(state.clone(), public_scope))
+        .route_layer(middleware::from_fn_with_state(state.clone(), resolve_workspace))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_ready));
+
+    Router::new()
+        .route("/health", get(health))
+        .route("/health/ready", get(health_ready))
+        .route("/api/v1/recessions", get(get_recessions))
+        .merge(data_routes)
+        .layer(middleware::from_fn_with_state(state.clone(), response_format))
+        .layer(middleware::from_fn_with_state(state.clone(), pipeline_header))
+        .layer(middleware::from_fn_with_state(max_age, mirror_cache))
+        .layer(cors())
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+/// The public demo profile (NIV_ROUTER_PROFILE=demo): every route, behind
+/// `demo_guard`
+fn demo_router(state: Arc<AppState>, policy: DemoPolicy) -> Router {
+    router(state).layer(middleware::from_fn_with_state(Arc::new(policy), demo_guard))
+}
+
+fn cors() -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any)
+        .expose_headers([
+            header::HeaderName::from_static("deprecation"),
+            header::HeaderName::from_static("sunset"),
+            header::LINK,
+            header::HeaderName::from_static(PIPELINE_HEADER),
+        ])
+}
+
+/// Load the dataset from the snapshot (NIV_SNAPSHOT_FILE) when it matches
+/// this engine, otherwise compute it (and write the snapshot)
+#[cfg(feature = "snapshot")]
+fn load_dataset(engine: &NIVEngine, snapshot_path: Option<&std::path::Path>) -> Dataset {
+    let snapshot = snapshot_path.and_then(|path| match snapshot::load(path, engine) {
+        Ok(dataset) => Some(dataset),
+        Err(e) => {
+            tracing::info!("Not using snapshot {}: {}", path.display(), e);
+            None
+        }
+    });
+    if let Some(dataset) = snapshot {
+        tracing::info!("Loaded {} NIV data points from snapshot", dataset.smoothed.len());
+        return dataset;
+    }
+    compute_dataset(engine, snapshot_path)
+}
+
+/// Built without the snapshot feature: always compute
+#[cfg(not(feature = "snapshot"))]
+fn load_dataset(engine: &NIVEngine, snapshot_path: Option<&std::path::Path>) -> Dataset {
+    compute_dataset(engine, snapshot_path)
+}
+
+/// Compute the dataset from source, writing the snapshot when configured
+fn compute_dataset(engine: &NIVEngine, snapshot_path: Option<&std::path::Path>) -> Dataset {
+    let inputs = mock::generate_mock_data(1960, 2026);
+    let raw = engine.calculate_raw(&inputs);
+    let smoothed = engine.smooth(&raw, niv::SMOOTH_WINDOW);
+    tracing::info!("Computed {} NIV data points", smoothed.len());
+
+    let dataset = Dataset { inputs, raw, smoothed };
+    #[cfg(feature = "snapshot")]
+    if let Some(path) = snapshot_path {
+        match snapshot::write(path, engine, &dataset) {
+            Ok(()) => tracing::info!("Wrote snapshot {}", path.display()),
+            Err(e) => tracing::warn!("Could not write snapshot {}: {}", path.display(), e),
+        }
+    }
+    #[cfg(not(feature = "snapshot"))]
+    let _ = snapshot_path;
+    dataset
+}
+
+/// Background startup: load or compute the dataset, run the self-test, then
+/// mark the server ready unless the policy refuses
+async fn load_data(state: Arc<AppState>, policy: FailurePolicy) {
+    let run = state.tasks.start(tasks::DATASET_LOAD);
+    let loading = std::time::Instant::now();
+    let shared = state.clone();
+    let loaded = tokio::task::spawn_blocking(move || {
+        let dataset = load_dataset(&shared.engine(), shared.snapshot_path.as_deref());
+        let validation = shared.engine().validate_against_benchmarks(&dataset.smoothed);
+        (dataset, validation)
+    })
+    .await;
+    let (dataset, validation) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            tracing::error!("Data load failed: {}", e);
+            run.finish(Err(e.to_string()));
+            return;
+        }
+    };
+
+    // Run the self-test on startup
+    let mut checks = data_checks(&dataset.smoothed, &validation);
+    checks.push(selftest::storage(state.snapshot_path.as_deref()));
+    checks.push(selftest::provider(state.fred_access).await);
+    let report = SelfTestReport::new(policy, checks);
+
+    if report.passed {
+        tracing::info!("✅ Self-test PASSED");
+    } else {
+        tracing::warn!("⚠️ Self-test FAILED - check calculation logic and dependencies");
+    }
+    for check in &report.checks {
+        let status = if check.passed { "✓" } else { "✗" };
+        tracing::info!(
+            "  {} [{:?}] {}: {} (expected: {})",
+            status, check.category, check.name, check.actual, check.expected
+        );
+    }
+
+    let serving = report.serving;
+    install_dataset(&state, dataset).await;
+    export_to_tsdb(&state);
+    record_change(&state, ChangeTrigger::Startup).await;
+    *state.validation.write().await = Some(report);
+    if !serving {
+        tracing::error!("Refusing to serve: self-test failed under the refuse policy");
+        run.finish(Err("self-test failed under the refuse policy".to_string()));
+        return;
+    }
+    state.ready.store(true, Ordering::Release);
+    run.finish(Ok(()));
+    tracing::info!("Dataset ready in {} ms", loading.elapsed().as_millis());
+}
+
+/// Invariant and benchmark checks, the self-test checks that depend on the data
+fn data_checks(results: &[NIVResult], validation: &niv::ValidationResult) -> Vec<selftest::SelfTestCheck> {
+    let mut checks = selftest::invariants(results);
+    checks.extend(selftest::benchmarks(validation));
+    checks
+}
+
+/// Swap in a new dataset and drop everything memoized from the old one
+async fn install_dataset(state: &AppState, dataset: Dataset) {
+    state.cache.invalidate_all();
+    *state.revision.write().expect("revision lock") = dataset_revision(&dataset.inputs);
+    state.cache.insert(state.namespace().key("series"), CachedData {
+        results: Arc::new(dataset.smoothed.clone()),
+        computed_at: chrono::Utc::now(),
+    }).await;
+
+    *state.inputs.write().await = dataset.inputs;
+    *state.raw.write().await = dataset.raw;
+    let latest = dataset.smoothed.last().map(|r| r.date);
+    if let Some(public) = &state.public {
+        public.record(chrono::Utc::now(), &dataset.smoothed);
+    }
+    *state.data.write().await = dataset.smoothed;
+    *state.calibrations.write().await = None; // After the swap; see fitted_calibrations
+    if let Some(date) = latest {
+        let _ = state.updates.send(date); // No subscribers is fine
+    }
+}
+
+/// Short digest of the model inputs; changes whenever any input value does
+fn dataset_revision(inputs: &[EconomicData]) -> String {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(serde_json::to_vec(inputs).unwrap_or_default());
+    digest[..6].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Recompute the dataset from source with the serving model and shadow the
+/// canary over the same inputs; returns the number of data points
+///
+/// `chunked` (a refresh the load could not wait out) runs the two as
+/// separate batch-lane jobs and skips the shadow if the load persists.
+async fn refresh(state: &Arc<AppState>, chunked: bool) -> Result<usize, String> {
+    refresh_as(state, chunked, None).await
+}
+
+/// Refresh, computing with `promoting` instead of the serving model when
+/// given; it replaces the serving model only once its dataset passes the
+/// swap checks and is installed with it
+async fn refresh_as(state: &Arc<AppState>, chunked: bool, promoting: Option<Serving>) -> Result<usize, String> {
+    let run = state.tasks.start(tasks::REFRESH);
+    let refreshed = refresh_dataset(state, chunked, promoting).await;
+    run.finish(refreshed.as_ref().map(|_| ()).map_err(|e| e.clone()));
+    refreshed
+}
+
+async fn refresh_dataset(state: &Arc<AppState>, chunked: bool, promoting: Option<Serving>) -> Result<usize, String> {
+    let _refreshing = state.refreshing.lock().await;
+    let engine = promoting.as_ref().map_or_else(|| state.engine(), |s| s.engine.clone());
+    // A candidate being promoted is not shadowed against itself
+    let candidate = match promoting {
+        Some(_) => None,
+        None => state.canary.read().await.as_ref().map(|c| (c.version.clone(), c.engine.clone())),
+    };
+    let path = state.snapshot_path.clone();
+
+    let (dataset, shadow) = if chunked {
+        let dataset = state
+            .jobs
+            .run(Lane::Batch, move |_| Ok(compute_dataset(&engine, path.as_deref())))
+            .await
+            .map_err(|e| e.to_string())?
+            .value;
+        let shadow = match (candidate, state.load.pressure(&state.backpressure)) {
+            (None, _) => None,
+            (Some((version, _)), pressure) if pressure != Pressure::Clear => {
+                tracing::warn!("Skipping canary {} shadow for this refresh: {}", version, pressure);
+                None
+            }
+            (Some((version, engine)), _) => {
+                let inputs = dataset.inputs.clone();
+                let results = state
+                    .jobs
+                    .run(Lane::Batch, move |_| Ok(engine.calculate_series(&inputs)))
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .value;
+                Some((version, results))
+            }
+        };
+        (dataset, shadow)
+    } else {
+        tokio::task::spawn_blocking(move || {
+            let dataset = compute_dataset(&engine, path.as_deref());
+            let shadow = candidate.map(|(version, engine)| (version, engine.calculate_series(&dataset.inputs)));
+            (dataset, shadow)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    };
+
+    // Swap checks: a failure keeps the serving data and records an incident
+    let (failures, offending) = incident::check(&state.inputs.read().await, &dataset.inputs, &dataset.smoothed);
+    if let Some(incident) = Incident::new(&state.model_version(), chrono::Utc::now(), failures, offending) {
+        let message = format!("{} ({})", incident.summary, incident.id);
+        record_incident(state, incident).await;
+        return Err(message);
+    }
+
+    if let Some((version, results)) = shadow {
+        let comparison = canary::compare(&dataset.smoothed, &results, Trigger::Refresh);
+        tracing::info!(
+            "Canary {}: mean |Δp| {:.4}, max |Δp| {:.4}, {} alert disagreements",
+            version, comparison.mean_abs_delta, comparison.max_abs_delta, comparison.alert_disagreements
+        );
+        // The candidate may have been replaced while computing
+        if let Some(canary) = state.canary.write().await.as_mut().filter(|c| c.version == version) {
+            canary.record(comparison, results);
+        }
+    }
+
+    let points = dataset.smoothed.len();
+    if let Some(serving) = promoting {
+        *state.serving.write().expect("serving lock") = serving;
+    }
+    install_dataset(state, dataset).await;
+    export_to_tsdb(state);
+    Ok(points)
+}
+
+/// Keep, log and push an incident
+async fn record_incident(state: &Arc<AppState>, incident: Incident) {
+    tracing::error!("{} ({}): {}", incident.summary, incident.id, incident.suggested_action);
+    if state.incident_webhook.is_some() {
+        let run = state.tasks.start(tasks::INCIDENT_WEBHOOK);
+        run.finish(incident::notify(&state.http, state.incident_webhook.as_deref(), &incident).await);
+    }
+    let mut incidents = state.incidents.write().await;
+    incidents.push_back(incident);
+    while incidents.len() > incident::MAX_INCIDENTS {
+        incidents.pop_front();
+    }
+}
+
+/// Push the installed results to the configured TSDB in the background
+#[cfg(feature = "tsdb")]
+fn export_to_tsdb(state: &Arc<AppState>) {
+    if state.tsdb.is_none() {
+        return;
+    }
+    let state = state.clone();
+    tokio::spawn(async move {
+        let Some((client, config)) = &state.tsdb else {
+            return;
+        };
+        let run = state.tasks.start(tasks::TSDB_EXPORT);
+        let lines = tsdb::line_protocol(config, &state.data.read().await, &state.model_version());
+        match tsdb::push(client, config, &lines).await {
+            Ok(points) => {
+                tracing::info!("Exported {} points to TSDB", points);
+                run.finish(Ok(()));
+            }
+            Err(e) => {
+                tracing::error!("{}", e);
+                run.finish(Err(e.to_string()));
+            }
+        }
+    });
+}
+
+#[cfg(not(feature = "tsdb"))]
+fn export_to_tsdb(_state: &Arc<AppState>) {}
+
+/// Compute the dataset and write the read-only endpoints as static JSON (see publish.rs)
+async fn publish_bundle(state: Arc<AppState>, out: &std::path::Path) -> Result<(), String> {
+    let shared = state.clone();
+    let dataset = tokio::task::spawn_blocking(move || load_dataset(&shared.engine(), shared.snapshot_path.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?;
+    install_dataset(&state, dataset).await;
+
+    let Json(latest) = get_latest(State(state.clone()), None, Query(LatestQuery { score: ScoreMode::Raw, horizon: default_decomposition_horizon(), series: None, labels: None }))
+        .await
+        .map_err(|(status, Json(e))| format!("latest: {} {}", status, e.error))?;
+    let history_query = HistoryQuery {
+        start: None,
+        end: None,
+        limit: usize::MAX,
+        score: ScoreMode::Raw,
+        smooth: None,
+        regime: None,
+        clusters: default_clusters(),
+        method: regimes::Method::default(),
+        forecast: None,
+        labels: None,
+        series: None,
+    };
+    let Json(history) = get_history(State(state.clone()), None, Query(history_query))
+        .await
+        .map_err(|(status, Json(e))| format!("history: {} {}", status, e.error))?;
+    let Json(compare) = get_comparison(State(state.clone()))
+        .await
+        .map_err(|status| format!("compare: {}", status))?;
+    let Json(recessions) = get_recessions(State(state.clone()), Query(LabelsQuery { labels: None }))
+        .await
+        .map_err(|(status, Json(e))| format!("recessions: {} {}", status, e.error))?;
+
+    let Json(pipeline) = get_pipeline(State(state.clone())).await;
+
+    let [latest_file, history_file, compare_file, recessions_file, pipeline_file] = publish::FILES;
+    for path in [
+        publish::write_json(out, latest_file, &latest)?,
+        publish::write_json(out, history_file, &history)?,
+        publish::write_json(out, compare_file, &compare)?,
+        publish::write_json(out, recessions_file, &recessions)?,
+        publish::write_json(out, pipeline_file, &pipeline)?,
+    ] {
+        tracing::info!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// Append a changelog entry if the serving model differs from the last one
+async fn record_change(state: &Arc<AppState>, trigger: ChangeTrigger) {
+    let validation = changelog::validation(&state.data.read().await);
+    let parameters = state.engine().parameters();
+    let version = state.model_version();
+    match state.changelog.write().await.record(&version, trigger, parameters, validation) {
+        Ok(Some(entry)) => tracing::info!("Changelog: {} ({} parameter changes)", version, entry.changes.len()),
+        Ok(None) => {}
+        Err(e) => tracing::error!("Changelog write failed: {}", e),
+    }
+}
+
+/// Periodic refresh (NIV_REFRESH_SECS), deferred while the server is under load
+async fn refresh_loop(state: Arc<AppState>, period: Duration) {
+    let mut ticker = tokio::time::interval(period);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    ticker.tick().await; // The first tick completes immediately
+    loop {
+        ticker.tick().await;
+        if !state.ready.load(Ordering::Acquire) {
+            state.tasks.waiting(tasks::REFRESH, Some("dataset not loaded".to_string()));
+            continue;
+        }
+        let mut deferred = Duration::ZERO;
+        let chunked = loop {
+            let pressure = state.load.pressure(&state.backpressure);
+            match state.backpressure.step(pressure, deferred, period) {
+                Step::Run => break false,
+                Step::Defer => {
+                    tracing::info!("Deferring refresh: {}", pressure);
+                    state.tasks.waiting(tasks::REFRESH, Some(format!("deferred {}s: {}", deferred.as_secs(), pressure)));
+                    tokio::time::sleep(backpressure::DEFER_STEP).await;
+                    deferred += backpressure::DEFER_STEP;
+                }
+                Step::RunChunked => {
+                    tracing::warn!("Refreshing under load after {}s deferred: {}", deferred.as_secs(), pressure);
+                    break true;
+                }
+            }
+        };
+        match refresh(&state, chunked).await {
+            Ok(points) => tracing::info!("Refreshed {} NIV data points", points),
+            Err(e) => tracing::error!("Refresh failed: {}", e),
+        }
+    }
+}
+
+/// Recompute the provisional value from month-to-date market data on a fixed
+/// interval; a failed fetch keeps the previous value
+#[cfg(feature = "fred")]
+async fn intraday_loop(state: Arc<AppState>, period: Duration) {
+    let mut ticker = tokio::time::interval(period);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        ticker.tick().await;
+        if !state.ready.load(Ordering::Acquire) {
+            state.tasks.waiting(tasks::INTRADAY, Some("dataset not loaded".to_string()));
+            continue;
+        }
+        let Some(proxy) = &state.fred_proxy else { return };
+        let run = state.tasks.start(tasks::INTRADAY);
+        let market = tokio::try_join!(proxy.series(intraday::SPREAD_SERIES), proxy.series(intraday::FED_FUNDS_SERIES));
+        let tick = match market {
+            Ok(((spread, _), (fed_funds, _))) => intraday::month_to_date(&spread, &fed_funds),
+            Err(e) => {
+                tracing::warn!("Intraday market data: {}", e);
+                run.finish(Err(format!("market data: {}", e)));
+                continue;
+            }
+        };
+        let Some(tick) = tick else {
+            tracing::warn!("Intraday market data: no observations this month");
+            run.finish(Err("no market data observations this month".to_string()));
+            continue;
+        };
+        let shared = state.clone();
+        let computed = state
+            .jobs
+            .run(Lane::Batch, move |_cancel| {
+                let inputs = shared.inputs.blocking_read();
+                Ok(intraday::provisional(&shared.engine(), &inputs, tick, chrono::Utc::now()))
+            })
+            .await
+            .map(|job| job.value);
+        match computed {
+            Ok(Some(provisional)) => {
+                *state.intraday.write().await = Some(provisional);
+                run.finish(Ok(()));
+            }
+            Ok(None) => {
+                tracing::info!("Intraday market data is older than the installed inputs");
+                run.finish(Ok(()));
+            }
+            Err(e) => {
+                tracing::warn!("Intraday recompute failed: {}", e);
+                run.finish(Err(e.to_string()));
+            }
+        }
+    }
+}
+
+/// Admin endpoints require `Authorization: Bearer $NIV_ADMIN_TOKEN`
+async fn require_admin(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let Some(token) = &state.admin_token else {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "ADMIN_DISABLED",
+            "admin endpoints are disabled; set NIV_ADMIN_TOKEN",
+        ));
+    };
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !presented.is_some_and(|presented| tokens_match(presented, token)) {
+        return Err(api_error(StatusCode::UNAUTHORIZED, "UNAUTHORIZED", "missing or invalid admin token"));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Token comparison in constant time: digests of equal length, compared
+/// without an early exit, so timing reveals neither a prefix nor the length
+fn tokens_match(presented: &str, token: &str) -> bool {
+    use sha2::{Digest, Sha256};
+    let (a, b) = (Sha256::digest(presented.as_bytes()), Sha256::digest(token.as_bytes()));
+    a.iter().zip(b.iter()).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Attach the caller's workspace (from X-API-Key) to the request; 401 on an unknown key
+async fn resolve_workspace(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let key = request.headers().get(tenancy::API_KEY_HEADER).and_then(|v| v.to_str().ok());
+    let workspace = state
+        .api_keys
+        .resolve(key)
+        .ok_or_else(|| api_error(StatusCode::UNAUTHORIZED, "INVALID_API_KEY", "unknown API key"))?;
+    request.extensions_mut().insert(workspace);
+    Ok(next.run(request).await)
+}
+
+/// Keyless requests while the public tier is on: only the routes that serve
+/// the embargoed view (public::PUBLIC_ROUTES)
+async fn public_scope(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Result<Response, ApiError> {
+    let keyless = request.extensions().get::<Workspace>().is_some_and(Workspace::is_keyless);
+    if state.public.is_some() && keyless && !public::PUBLIC_ROUTES.contains(&request.uri().path()) {
+        return Err(api_error(
+            StatusCode::UNAUTHORIZED,
+            "API_KEY_REQUIRED",
+            "this endpoint needs an X-API-Key while the public tier is on",
+        ));
+    }
+    Ok(next.run(request).await)
+}
+
+/// Mirror requests: drop any API key, so every response is the keyless one
+/// and safe to share from a CDN, and mark successes cacheable for `max_age`
+async fn mirror_cache(State(max_age): State<u64>, mut request: Request, next: Next) -> Response {
+    request.headers_mut().remove(tenancy::API_KEY_HEADER);
+    let mut response = next.run(request).await;
+    let cache_control = if response.status().is_success() {
+        format!("public, max-age={0}, stale-while-revalidate={0}", max_age)
+    } else {
+        "no-store".to_string()
+    };
+    if let Ok(value) = header::HeaderValue::from_str(&cache_control) {
+        response.headers_mut().insert(header::CACHE_CONTROL, value);
+    }
+    response
+}
+
+/// Demo requests: drop any API key and count the request against its
+/// client's limit (health checks aside), then mark the response as demo
+async fn demo_guard(State(policy): State<Arc<DemoPolicy>>, mut request: Request, next: Next) -> Response {
+    request.headers_mut().remove(tenancy::API_KEY_HEADER);
+    let response = if request.uri().path().starts_with("/health") {
+        next.run(request).await
+    } else {
+        let peer = request.extensions().get::<ConnectInfo<std::net::SocketAddr>>().map(|c| c.0.ip());
+        let client = demo::client_ip(request.headers(), policy.client_ip_header.as_deref(), peer);
+        match policy.limiter.try_acquire(client, std::time::Instant::now()) {
+            Ok(_) => next.run(request).await,
+            Err(wait) => {
+                let retry_after = wait.as_secs().max(1).to_string();
+                let error = api_error(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "RATE_LIMITED",
+                    format!("demo requests are limited per client; retry in {}s", retry_after),
+                );
+                ([(header::RETRY_AFTER, retry_after)], error).into_response()
+            }
+        }
+    };
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+    let (mut parts, body) = response.into_parts();
+    parts.headers.insert(demo::DEMO_HEADER, header::HeaderValue::from_static("true"));
+    if !is_json {
+        return Response::from_parts(parts, body);
+    }
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => return api_error(StatusCode::INTERNAL_SERVER_ERROR, "RESPONSE_FAILED", e.to_string()).into_response(),
+    };
+    let body = match demo::watermark(&bytes) {
+        Some(marked) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(marked)
+        }
+        None => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
+
+/// Record interactive request latencies for refresh backpressure
+async fn record_latency(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let started = std::time::Instant::now();
+    let response = next.run(request).await;
+    state.load.record(started.elapsed());
+    response
+}
+
+/// Scope the `precision=` and `probability_units=` query parameters over the
+/// request (see format.rs); units fall back to the key's preferences
+async fn response_format(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Result<Response, ApiError> {
+    let precision = format::precision_from_query(request.uri().query())
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "INVALID_PRECISION", e))?;
+    let units = match format::units_from_query(request.uri().query())
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "INVALID_PROBABILITY_UNITS", e))?
+    {
+        Some(units) => Some(units),
+        None => {
+            // Unknown keys are rejected later by resolve_workspace
+            let key = request.headers().get(tenancy::API_KEY_HEADER).and_then(|v| v.to_str().ok());
+            state.preferences(state.api_keys.resolve(key).as_ref()).probability_units
+        }
+    };
+    let format = Format { precision, units: units.unwrap_or_default() };
+    Ok(format.scope(next.run(request)).await)
+}
+
+/// 503 on data endpoints until the background load has finished
+async fn require_ready(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if !state.ready.load(Ordering::Acquire) {
+        let refused = state.validation.read().await.as_ref().is_some_and(|r| !r.serving);
+        return Err(if refused {
+            api_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "SELFTEST_FAILED",
+                "startup self-test failed; see /api/v1/validation",
+            )
+        } else {
+            api_error(StatusCode::SERVICE_UNAVAILABLE, "NOT_READY", "data is still loading")
+        });
+    }
+    Ok(next.run(request).await)
+}
+
+/// Attach Deprecation/Sunset/Link headers to responses from deprecated endpoints
+async fn deprecation_headers(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let deprecation = state.deprecations.find(request.uri().path()).cloned();
+    let mut response = next.run(request).await;
+    for (name, value) in deprecation.iter().flat_map(|d| d.headers()) {
+        if let Ok(value) = header::HeaderValue::from_str(&value) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
+}
+
+/// Response header carrying the pipeline hash
+const PIPELINE_HEADER: &str = "x-niv-pipeline";
+
+/// Stamp every response with the pipeline hash as of the request
+async fn pipeline_header(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let hash = state.pipeline_hash();
+    let mut response = next.run(request).await;
+    if let Ok(value) = header::HeaderValue::from_str(&hash) {
+        response.headers_mut().insert(PIPELINE_HEADER, value);
+    }
+    response
+}
+
+/// Root endpoint
+async fn root(State(state): State<Arc<AppState>>) -> Json<serde_json::Value> {
+    let slack_spec = state.engine().slack_spec();
+    let gdp_spec = state.engine().gdp_spec();
+    let inflation_spec = state.engine().inflation_spec();
+    let spread_spec = state.engine().spread_spec();
+    Json(serde_json::json!({
+        "name": "NIV Engine API",
+        "version": "1.0.0",
+        "model_version": state.model_version(),
+        "description": "National Impact Velocity - Physics-based Macro Crisis Detection",
+        "performance": {
+            "niv_auc": MODEL_AUC,
+            "fed_yield_curve_auc": FED_AUC,
+            "outperformance": format!("+{:.1}%", (MODEL_AUC - FED_AUC) / FED_AUC * 100.0)
+        },
+        "formula": {
+            "master": "NIV_t = (u_t × P_t²) / (X_t + F_t)^η",
+            "thrust": "u = tanh(1.0*dG + 1.0*dA - 0.7*dr)",
+            "efficiency": "P = (Investment × 1.15) / GDP",
+            "slack": slack_spec.formula(),
+            "drag": "F = 0.4*s_t + 0.4*(r-π) + 0.2*σ_r",
+            "spread": spread_spec.formula(),
+            "parameters": {
+                "eta": 1.5,
+                "epsilon": 0.001
+            },
+            "specification": {
+                "slack": slack_spec,
+                "gdp": gdp_spec,
+                "inflation": inflation_spec,
+                "spread": spread_spec,
+                "nonfinite": state.engine().nonfinite_policy()
+            },
+            "custom_components": state.engine().registry().definitions()
+        },
+        "endpoints": {
+            "latest": "/api/v1/latest",
+            "history": "/api/v1/history",
+            "components": "/api/v1/components",
+            "compare": "/api/v1/compare",
+            "recessions": "/api/v1/recessions",
+            "validation": "/api/v1/validation",
+            "term_structure": "/api/v1/term-structure",
+            "lead_times": "/api/v1/lead-times",
+            "episodes": "/api/v1/episodes",
+            "event_study": "/api/v1/event-study",
+            "leaderboard": "/api/v1/research/leaderboard",
+            "synthetic_benchmark": "/api/v1/synthetic-benchmark",
+            "simulate": "POST /api/v1/simulate",
+            "montecarlo": "POST /api/v1/montecarlo",
+            "replay": "POST /api/v1/replay/start?speed=12x&from=2006-01",
+            "health": "/health"
+        },
+        "documentation": "https://regenerationism.ai/methodology"
+    }))
+}
+
+/// Health check endpoint
+async fn health(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
+    let data = state.data.read().await;
+    let validation = state.validation.read().await;
+
+    let last_date = data.last()
+        .map(|d| d.date.to_string())
+        .unwrap_or_else(|| "N/A".to_string());
+
+    let ready = state.ready.load(Ordering::Acquire);
+    Json(HealthResponse {
+        status: if ready { "healthy" } else { "starting" }.to_string(),
+        version: "1.0.0".to_string(),
+        model_version: state.model_version(),
+        data_points: data.len(),
+        last_update: last_date,
+        validation_passed: validation.as_ref().map(|v| v.passed),
+        ready,
+        lanes: state.jobs.stats(),
+    })
+}
+
+/// Readiness probe: 200 once the dataset has loaded, 503 before
+async fn health_ready(State(state): State<Arc<AppState>>) -> (StatusCode, Json<ReadyResponse>) {
+    let ready = state.ready.load(Ordering::Acquire);
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(ReadyResponse { ready }))
+}
+
+/// Get latest NIV score
+async fn get_latest(
+    State(state): State<Arc<AppState>>,
+    workspace: Option<Extension<Workspace>>,
+    Query(params): Query<LatestQuery>,
+) -> Result<Json<LatestResponse>, ApiError> {
+    if !(1..=calibration::MAX_DECOMPOSITION_HORIZON).contains(&params.horizon) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_HORIZON",
+            format!("horizon must be between 1 and {} months", calibration::MAX_DECOMPOSITION_HORIZON),
+        ));
+    }
+    let recessions = label_set(&state, params.labels.as_deref()).await?;
+    let public = state.public_view(workspace.as_deref());
+    let product = match params.series {
+        Some(product) => Some(product_series(&state, product, public.as_deref().map(Vec::as_slice)).await?),
+        None => None,
+    };
+    let served = state.data.read().await;
+    let data: &[NIVResult] = match (&product, &public) {
+        (Some(cached), _) => &cached.results,
+        (None, Some(results)) => results,
+        (None, None) => &served,
+    };
+    // Base rates, severity and the intraday tick are per month
+    let monthly = params.series != Some(Product::Benchmark);
+
+    let latest = data.last()
+        .ok_or_else(|| api_error(StatusCode::INTERNAL_SERVER_ERROR, "NO_DATA", "No data available"))?;
+    let decomposition = monthly
+        .then(|| {
+            calibration::decompose(
+                data,
+                &recessions.chronology(),
+                params.horizon,
+                latest.recession_probability,
+            )
+        })
+        .flatten();
+
+    // Interpret components
+    let interpretation = ComponentInterpretation {
+        thrust_status: dashboard::interpretation(Component::Thrust, latest.components.thrust).to_string(),
+        efficiency_status: dashboard::interpretation(Component::Efficiency, latest.components.efficiency).to_string(),
+        slack_status: dashboard::interpretation(Component::Slack, latest.components.slack).to_string(),
+        drag_status: dashboard::interpretation(Component::Drag, latest.components.drag).to_string(),
+        formula: format!(
+            "NIV = ({:.3} × {:.6}) / ({:.3} + {:.4})^1.5 = {:.2}",
+            latest.components.thrust,
+            latest.components.efficiency_squared,
+            latest.components.slack,
+            latest.components.drag,
+            latest.niv_score
+        ),
+    };
+
+    // Compare with Fed yield curve signal
+    let inputs = state.inputs.read().await;
+    let inversion = inversion_penalties(state.engine().spread_spec(), std::slice::from_ref(latest), &inputs)[0];
+    let severity = monthly
+        .then(|| SeverityModel::fit(data, &inputs, &recessions.chronology()))
+        .flatten();
+
+    // Provisional value for the latest month or later; the public tier goes without
+    let intraday = match public {
+        Some(_) => None,
+        None => state.intraday.read().await.clone().filter(|p| monthly && p.tick.month >= latest.date),
+    };
+    let niv_signal = if latest.recession_probability > 0.5 { "RECESSION RISK" } else { "EXPANSION" };
+    let yield_curve_signal = if inversion > 0.0 { "INVERTED" } else { "NORMAL" };
+
+    Ok(Json(LatestResponse {
+        date: latest.date.to_string(),
+        series: params.series,
+        niv_score: round(score_value(latest, params.score), 2),
+        score: params.score,
+        recession_probability: probability(latest.recession_probability),
+        alert_level: latest.alert_level,
+        alert_color: latest.alert_level.color().to_string(),
+        alert_label: latest.alert_level.label().to_string(),
+        probability_decomposition: decomposition.map(|d| DecompositionResponse {
+            horizon_months: d.horizon_months,
+            base_rate: probability(d.base_rate),
+            signal: probability(d.signal),
+            samples: d.samples,
+        }),
+        expansion_age_months: niv::expansion_age(latest.date, &recessions.dated()),
+        expected_severity: severity.map(|s| severity_response(&s, latest.recession_probability)),
+        provisional_intraday: intraday.map(|p| IntradayResponse {
+            as_of: p.as_of.to_rfc3339(),
+            month: p.tick.month.to_string(),
+            observed_through: p.tick.observed_through.to_string(),
+            trading_days: p.tick.days,
+            yield_spread: round(p.tick.yield_spread, 2),
+            fed_funds_rate: round(p.tick.fed_funds_rate, 2),
+            niv_score: round(p.niv_score, 2),
+            recession_probability: probability(p.recession_probability),
+            drag: round(p.drag, 4),
+            drag_spread: round(p.drag_spread, 4),
+            drag_volatility: round(p.drag_volatility, 4),
+        }),
+        components: ComponentsResponse {
+            thrust: round(latest.components.thrust, 4),
+            efficiency: round(latest.components.efficiency, 4),
+            efficiency_squared: round(latest.components.efficiency_squared, 6),
+            slack: round(latest.components.slack, 4),
+            drag: round(latest.components.drag, 4),
+            drag_spread: round(latest.components.drag_spread, 4),
+            drag_real_rate: round(latest.components.drag_real_rate, 4),
+            drag_volatility: round(latest.components.drag_volatility, 4),
+            gdp_nowcast: latest.components.gdp_nowcast,
+            custom: latest.components.custom.clone(),
+            interpretation,
+        },
+        quality: latest.quality.clone(),
+        coverage: latest.coverage.clone(),
+        vs_fed: FedComparisonResponse {
+            niv_signal: niv_signal.to_string(),
+            yield_curve_signal: yield_curve_signal.to_string(),
+            agreement: (latest.recession_probability > 0.5) == (inversion > 0.0),
+            niv_lead_months: 6,
+            niv_auc: MODEL_AUC,
+            fed_auc: FED_AUC,
+        },
+        model_version: state.model_version(),
+    }))
+}
+
+/// Get historical NIV data
+async fn get_history(
+    State(state): State<Arc<AppState>>,
+    workspace: Option<Extension<Workspace>>,
+    Query(params): Query<HistoryQuery>,
+) -> Result<Json<HistoryResponse>, ApiError> {
+    let window = params
+        .smooth
+        .or_else(|| state.preferences(workspace.as_deref()).smooth)
+        .unwrap_or(niv::SMOOTH_WINDOW);
+    if window == 0 || window > niv::MAX_SMOOTH_WINDOW {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_SMOOTH",
+            format!("smooth must be between 1 and {} months", niv::MAX_SMOOTH_WINDOW),
+        ));
+    }
+    if params.series.is_some() && (window != niv::SMOOTH_WINDOW || params.forecast.is_some()) {
+        // Products are published at the default smoothing; the fan extends the served series
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_SERIES",
+            format!("series requires the default smoothing window ({} months) and no forecast", niv::SMOOTH_WINDOW),
+        ));
+    }
+    let public = state.public_view(workspace.as_deref());
+    if public.is_some() && (window != niv::SMOOTH_WINDOW || params.forecast.is_some()) {
+        // The public tier only keeps the served series
+        return Err(api_error(
+            StatusCode::UNAUTHORIZED,
+            "API_KEY_REQUIRED",
+            "smooth and forecast need an X-API-Key while the public tier is on",
+        ));
+    }
+    let forecast = match params.forecast {
+        Some(months) => Some(forecast_fan(&state, months, window).await?),
+        None => None,
+    };
+    let resmoothed = match params.series {
+        Some(product) => Some(product_series(&state, product, public.as_deref().map(Vec::as_slice)).await?),
+        None if window == niv::SMOOTH_WINDOW => None,
+        None => Some(smoothed_history(&state, window).await?),
+    };
+    let default_data = state.data.read().await;
+    let data: &[NIVResult] = match (&resmoothed, &public) {
+        (Some(cached), _) => &cached.results,
+        (None, Some(results)) => results,
+        (None, None) => &default_data,
+    };
+
+    // Validate date filters
+    let range = resolve_range(data, params.start.as_deref(), params.end.as_deref(), ["start", "end"], None)?;
+    let labels = match params.regime {
+        Some(regime) => Some(regime_labels(data, params.clusters, params.method, regime)?),
+        None => None,
+    };
+    let recessions = label_set(&state, params.labels.as_deref()).await?;
+
+    // Filter data
+    let filtered: Vec<_> = data.iter()
+        .enumerate()
+        .filter(|(i, _)| labels.as_ref().is_none_or(|(labels, regime)| labels[*i] == *regime))
+        .map(|(_, d)| d)
+        .filter(|d| range.contains(d.date))
+        .take(params.limit)
+        .map(|d| history_point(d, params.score, &recessions))
+        .collect();
+
+    let start = filtered.first().map(|d| d.date.clone()).unwrap_or_default();
+    let end = filtered.last().map(|d| d.date.clone()).unwrap_or_default();
+
+    Ok(Json(HistoryResponse {
+        count: filtered.len(),
+        score: params.score,
+        smooth_window: window,
+        start_date: start,
+        end_date: end,
+        labels: recessions.name.clone(),
+        model_version: state.model_version(),
+        series: params.series,
+        data: filtered,
+        forecast,
+    }))
+}
+
+/// Baseline Monte Carlo fan over the next `months`; seeded with FORECAST_SEED
+/// so every chart load shares one stored run
+async fn forecast_fan(state: &Arc<AppState>, months: usize, window: usize) -> Result<ForecastFan, ApiError> {
+    if months == 0 || months > requests::MAX_MC_HORIZON {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_FORECAST",
+            format!("forecast must be between 1 and {} months", requests::MAX_MC_HORIZON),
+        ));
+    }
+    if window != niv::SMOOTH_WINDOW {
+        // Simulated paths use the engine's smoothing; the fan would not join the series
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_FORECAST",
+            format!("forecast requires the default smoothing window ({} months)", niv::SMOOTH_WINDOW),
+        ));
+    }
+    let config = MonteCarloConfig {
+        horizon_months: months,
+        draws: requests::DEFAULT_MC_DRAWS,
+        seed: FORECAST_SEED,
+        sampling: Sampling::Pseudo,
+    };
+    let (id, stored, _) = stored_monte_carlo(state, Scenario::baseline(), config).await?;
+    let result = &stored.result;
+    let data = state.data.read().await;
+    let inputs = state.inputs.read().await;
+    let severity = SeverityModel::fit(&data, &inputs, &niv::RecessionPeriods::known_recessions());
+    let engine = state.engine();
+    let expected = montecarlo::project_results(&engine, &inputs, &Scenario::baseline().project(&inputs, months));
+    let latest = data
+        .last()
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "NO_DATA", "No data available"))?;
+    let scores: Vec<f64> = state.raw.read().await.iter().map(|r| r.niv_score).collect();
+    let drivers = |r: &NIVResult| {
+        let change = r.recession_probability - latest.recession_probability;
+        let link = engine.probability_link(r.date, &scores);
+        let split = attribution::change_contributions(&link, &latest.components, &r.components);
+        let share = |c: Component| split.iter().find(|(s, _)| *s == c).map_or(0.0, |(_, d)| *d);
+        ForecastDrivers {
+            thrust: probability(share(Component::Thrust)),
+            efficiency: probability(share(Component::Efficiency)),
+            slack: probability(share(Component::Slack)),
+            drag: probability(share(Component::Drag)),
+            other: probability(change - split.iter().map(|(_, d)| d).sum::<f64>()),
+        }
+    };
+    Ok(ForecastFan {
+        id,
+        horizon_months: result.horizon_months,
+        draws: result.draws,
+        seed: result.seed,
+        months: result
+            .months
+            .iter()
+            .zip(&expected)
+            .map(|(m, r)| ForecastPoint {
+                date: m.date.to_string(),
+                p10: probability(m.p10),
+                p50: probability(m.p50),
+                p90: probability(m.p90),
+                expected: probability(r.recession_probability),
+                change: probability(r.recession_probability - latest.recession_probability),
+                drivers: drivers(r),
+                severity: severity.as_ref().map(|s| severity_response(s, m.p50)),
+            })
+            .collect(),
+    })
+}
+
+/// Severity bands at `probability`, rounded
+fn severity_response(model: &SeverityModel, probability: f64) -> SeverityResponse {
+    let band = |b: SeverityBand| BandResponse { expected: round(b.expected, 2), p10: round(b.p10, 2), p90: round(b.p90, 2) };
+    let forecast = model.predict(probability);
+    SeverityResponse {
+        gdp_decline: band(forecast.gdp_decline),
+        slack_increase: band(forecast.slack_increase),
+        episodes: model.episodes,
+    }
+}
+
+/// Validated cluster count for the regime endpoints
+fn validate_clusters(clusters: usize) -> Result<usize, ApiError> {
+    if clusters == 0 || clusters > regimes::MAX_CLUSTERS {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_CLUSTERS",
+            format!("clusters must be between 1 and {}", regimes::MAX_CLUSTERS),
+        ));
+    }
+    Ok(clusters)
+}
+
+/// Regime label per result, plus the validated regime to filter on
+fn regime_labels(
+    data: &[NIVResult],
+    clusters: usize,
+    method: regimes::Method,
+    regime: usize,
+) -> Result<(Vec<usize>, usize), ApiError> {
+    let clusters = validate_clusters(clusters)?;
+    if regime >= clusters {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_REGIME",
+            format!("regime must be below clusters ({})", clusters),
+        ));
+    }
+    Ok((regimes::cluster(data, clusters, method).labels, regime))
+}
+
+/// Months of `regime` in the served history, for the backtest endpoints
+fn regime_filter(
+    data: &[NIVResult],
+    clusters: usize,
+    method: regimes::Method,
+    regime: Option<usize>,
+) -> Result<Option<regimes::RegimeFilter>, ApiError> {
+    let Some(regime) = regime else {
+        return Ok(None);
+    };
+    let (labels, regime) = regime_labels(data, clusters, method, regime)?;
+    Ok(Some(regimes::RegimeFilter::new(data, &labels, regime)))
+}
+
+/// History re-smoothed over `window` months, memoized in the cache
+async fn smoothed_history(state: &Arc<AppState>, window: usize) -> Result<CachedData, ApiError> {
+    let key = state.namespace().key(format!("smooth:{}", window));
+    if let Some(cached) = state.cache.get(&key).await {
+        return Ok(cached);
+    }
+    let cached = smooth_history(state, window).await?;
+    state.cache.insert(key, cached.clone()).await;
+    Ok(cached)
+}
+
+/// The benchmark or tracking series (see products.rs), computed on first
+/// use and held until the dataset is replaced; `visible` (the public tier's
+/// embargoed view) limits it to the months that view shows
+async fn product_series(
+    state: &Arc<AppState>,
+    product: Product,
+    visible: Option<&[NIVResult]>,
+) -> Result<CachedData, ApiError> {
+    let key = state.namespace().key(product.item());
+    let cached = match state.cache.get(&key).await {
+        Some(cached) => cached,
+        None => {
+            let shared = state.clone();
+            let job = state
+                .jobs
+                .run(Lane::Interactive, move |_| {
+                    let inputs = shared.inputs.blocking_read();
+                    Ok(product.compute(&shared.engine(), &inputs))
+                })
+                .await
+                .map_err(|e| job_error(e, "SERIES_FAILED"))?;
+            let cached = CachedData { results: Arc::new(job.value), computed_at: chrono::Utc::now() };
+            state.cache.insert(key, cached.clone()).await;
+            cached
+        }
+    };
+    let Some(visible) = visible else {
+        return Ok(cached);
+    };
+    let results = match visible.last() {
+        Some(last) => product.through(&cached.results, last.date),
+        None => Vec::new(),
+    };
+    let results = results
+        .into_iter()
+        .map(|r| NIVResult { recession_probability: public::bucket(r.recession_probability), ..r })
+        .collect();
+    Ok(CachedData { results: Arc::new(results), computed_at: cached.computed_at })
+}
+
+/// Smooth the raw history over `window` months on the interactive lane
+async fn smooth_history(state: &Arc<AppState>, window: usize) -> Result<CachedData, ApiError> {
+    let shared = state.clone();
+    let job = state
+        .jobs
+        .run(Lane::Interactive, move |_| {
+            let raw = shared.raw.blocking_read();
+            Ok(shared.engine().smooth(&raw, window))
+        })
+        .await
+        .map_err(|e| job_error(e, "SMOOTHING_FAILED"))?;
+    Ok(CachedData {
+        results: Arc::new(job.value),
+        computed_at: chrono::Utc::now(),
+    })
+}
+
+/// NIV score as requested: raw value or rolling historical percentile
+fn score_value(d: &NIVResult, mode: ScoreMode) -> f64 {
+    match mode {
+        ScoreMode::Raw => d.niv_score,
+        ScoreMode::Percentile => d.niv_percentile,
+    }
+}
+
+fn history_point(d: &NIVResult, score: ScoreMode, recessions: &LabelSet) -> HistoryDataPoint {
+    HistoryDataPoint {
+        date: d.date.to_string(),
+        niv_score: round(score_value(d, score), 2),
+        recession_probability: probability(d.recession_probability),
+        alert_level: d.alert_level,
+        is_recession: recessions.contains(d.date),
+        expansion_age_months: niv::expansion_age(d.date, &recessions.dated()),
+        thrust: round(d.components.thrust, 4),
+        efficiency: round(d.components.efficiency, 4),
+        slack: round(d.components.slack, 4),
+        drag: round(d.components.drag, 4),
+        quality: d.quality.clone(),
+        coverage: d.coverage.clone(),
+        warmup: d.warmup,
+        niv_score_range: None,
+        recession_probability_range: None,
+    }
+}
+
+/// Full results for each requested month, in request order
+async fn get_at_batch(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<BatchAtRequest>,
+) -> Result<Json<BatchAtResponse>, ApiError> {
+    request.validate().map_err(request_error)?;
+    let recessions = label_set(&state, request.labels.as_deref()).await?;
+    let data = state.data.read().await;
+
+    let results = request
+        .dates
+        .iter()
+        .map(|raw| {
+            let month = resolve_range(&data, Some(raw), Some(raw), ["dates", "dates"], None)?.start;
+            let d = data
+                .binary_search_by_key(&month, |d| d.date)
+                .map(|i| &data[i])
+                .map_err(|_| api_error(StatusCode::UNPROCESSABLE_ENTITY, "NO_DATA_FOR_DATE", format!("no result for {}", month)))?;
+            Ok(AtResult {
+                requested: raw.clone(),
+                point: history_point(d, request.score, &recessions),
+                niv_percentile: round(d.niv_percentile, 2),
+                efficiency_squared: round(d.components.efficiency_squared, 6),
+                drag_spread: round(d.components.drag_spread, 4),
+                drag_real_rate: round(d.components.drag_real_rate, 4),
+                drag_volatility: round(d.components.drag_volatility, 4),
+                gdp_nowcast: d.components.gdp_nowcast,
+                custom: d.components.custom.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    Ok(Json(BatchAtResponse {
+        count: results.len(),
+        score: request.score,
+        labels: recessions.name.clone(),
+        results,
+        model_version: state.model_version(),
+    }))
+}
+
+/// Refusal under DenominatorPolicy::Reject
+fn negative_denominator_error(months: usize, first: NaiveDate) -> ApiError {
+    api_error(
+        StatusCode::BAD_REQUEST,
+        "NEGATIVE_DENOMINATOR",
+        format!(
+            "slack + drag + epsilon is negative in {} month(s) from {}; (X + F)^eta is undefined (denominator policy: reject)",
+            months, first
+        ),
+    )
+}
+
+/// Parameter ranges for generating UI controls
+async fn get_simulate_bounds(State(state): State<Arc<AppState>>) -> Json<BoundsResponse> {
+    Json(BoundsResponse { parameters: bounds::bounds(&state.engine()), model_version: state.model_version() })
+}
+
+/// Recompute history with custom engine parameters
+async fn simulate(
+    State(state): State<Arc<AppState>>,
+    workspace: Option<Extension<Workspace>>,
+    Json(mut req): Json<SimulateRequest>,
+) -> Result<Json<SimulateResponse>, ApiError> {
+    if req.engine.weights.is_none() {
+        req.engine.weights = state.preferences(workspace.as_deref()).weights;
+    }
+    let uncertainty = req.uncertainty().map_err(request_error)?;
+    let engine = req.engine.build(&state.engine()).map_err(request_error)?;
+
+    let range = resolve_range(
+        &state.data.read().await,
+        req.start.as_deref(),
+        req.end.as_deref(),
+        ["start", "end"],
+        state.max_span_months,
+    )?;
+    let in_range = |d: NaiveDate| range.contains(d);
+
+    let inputs = state.inputs.read().await;
+    let raw = engine.calculate_raw(&inputs);
+    let ranges = uncertainty
+        .map(|u| interval::smooth(&interval::calculate_raw(&engine, &inputs, &u), niv::SMOOTH_WINDOW));
+    drop(inputs);
+
+    let negative: Vec<NaiveDate> = raw
+        .iter()
+        .filter(|r| in_range(r.date) && r.quality.contains(&QualityFlag::NegativeDenominator))
+        .map(|r| r.date)
+        .collect();
+    if engine.denominator_policy() == DenominatorPolicy::Reject {
+        if let Some(first) = negative.first() {
+            return Err(negative_denominator_error(negative.len(), *first));
+        }
+    }
+    let results = engine.smooth(&raw, niv::SMOOTH_WINDOW);
+
+    let transitions: Vec<AlertTransitionResponse> = engine
+        .alert_transitions(&results)
+        .into_iter()
+        .filter(|t| in_range(t.date))
+        .map(|t| AlertTransitionResponse {
+            date: t.date.to_string(),
+            from: t.from,
+            to: t.to,
+            recession_probability: probability(t.recession_probability),
+            trigger: t.trigger,
+            trigger_delta: round(t.trigger_delta, 4),
+        })
+        .collect();
+
+    let recessions = label_set(&state, None).await?;
+    let data: Vec<HistoryDataPoint> = results
+        .iter()
+        .filter(|r| in_range(r.date))
+        .map(|r| {
+            let mut point = history_point(r, ScoreMode::Raw, &recessions);
+            // Ranges cover every month; trimmed warm-up months have no result
+            let range = ranges.as_ref().and_then(|ranges| {
+                ranges.binary_search_by_key(&r.date, |range| range.date).ok().map(|i| &ranges[i])
+            });
+            if let Some(range) = range {
+                point.niv_score_range = Some([round(range.niv_score.lo, 2), round(range.niv_score.hi, 2)]);
+                point.recession_probability_range =
+                    range.recession_probability.map(|p| [probability(p.lo), probability(p.hi)]);
+            }
+            point
+        })
+        .collect();
+
+    Ok(Json(SimulateResponse {
+        parameters: SimulationParameters {
+            eta: engine.eta(),
+            epsilon: engine.epsilon(),
+            weights: engine.weights(),
+            probability_input: engine.probability_input(),
+            thrust_scaling: engine.thrust_scaling(),
+            efficiency: engine.efficiency_spec(),
+            gdp: engine.gdp_spec(),
+            inflation: engine.inflation_spec(),
+            spread: engine.spread_spec(),
+            slack: engine.slack_spec(),
+            nonfinite: engine.nonfinite_policy(),
+            denominator: engine.denominator_policy(),
+            warmup: engine.warmup_policy(),
+            expansion_age_weight: engine.expansion_age_weight(),
+            components: engine.registry().definitions(),
+        },
+        count: data.len(),
+        start_date: data.first().map(|d| d.date.clone()).unwrap_or_default(),
+        end_date: data.last().map(|d| d.date.clone()).unwrap_or_default(),
+        data,
+        transitions,
+        negative_denominator: negative.first().zip(negative.last()).map(|(first, last)| NegativeDenominatorReport {
+            policy: engine.denominator_policy(),
+            months: negative.len(),
+            first: first.to_string(),
+            last: last.to_string(),
+        }),
+        model_version: state.model_version(),
+    }))
+}
+
+/// Get current component breakdown
+async fn get_components(State(state): State<Arc<AppState>>) -> Result<Json<ComponentsResponse>, StatusCode> {
+    let data = state.data.read().await;
+
+    let latest = data.last()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let interpretation = ComponentInterpretation {
+        thrust_status: dashboard::interpretation(Component::Thrust, latest.components.thrust).to_string(),
+        efficiency_status: dashboard::interpretation(Component::Efficiency, latest.components.efficiency).to_string(),
+        slack_status: dashboard::interpretation(Component::Slack, latest.components.slack).to_string(),
+        drag_status: dashboard::interpretation(Component::Drag, latest.components.drag).to_string(),
+        formula: format!(
+            "NIV = ({:.3} × {:.6}) / ({:.3} + {:.4})^1.5",
+            latest.components.thrust,
+            latest.components.efficiency_squared,
+            latest.components.slack,
+            latest.components.drag
+        ),
+    };
+
+    Ok(Json(ComponentsResponse {
+        thrust: round(latest.components.thrust, 4),
+        efficiency: round(latest.components.efficiency, 4),
+        efficiency_squared: round(latest.components.efficiency_squared, 6),
+        slack: round(latest.components.slack, 4),
+        drag: round(latest.components.drag, 4),
+        drag_spread: round(latest.components.drag_spread, 4),
+        drag_real_rate: round(latest.components.drag_real_rate, 4),
+        drag_volatility: round(latest.components.drag_volatility, 4),
+        gdp_nowcast: latest.components.gdp_nowcast,
+        custom: latest.components.custom.clone(),
+        interpretation,
+    }))
+}
+
+/// Compact per-component summary of the latest month
+async fn get_dashboard(State(state): State<Arc<AppState>>) -> Result<Json<DashboardResponse>, ApiError> {
+    let data = state.data.read().await;
+    let latest = data
+        .last()
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "NO_DATA", "No data available"))?;
+
+    let components = dashboard::readings(&data)
+        .into_iter()
+        .map(|r| ComponentReading {
+            value: round(r.value, 4),
+            z_score: r.z_score.map(|z| round(z, 2)),
+            change_3m: r.change_3m.map(|c| round(c, 4)),
+            ..r
+        })
+        .collect();
+
+    Ok(Json(DashboardResponse {
+        date: latest.date.to_string(),
+        niv_score: round(latest.niv_score, 2),
+        recession_probability: probability(latest.recession_probability),
+        alert_level: latest.alert_level,
+        zscore_window_months: dashboard::ZSCORE_WINDOW,
+        trend_months: dashboard::TREND_MONTHS,
+        components,
+        model_version: state.model_version(),
+    }))
+}
+
+/// Nearest historical months to the latest in component space
+async fn get_analogues(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<AnaloguesQuery>,
+) -> Result<Json<AnaloguesResponse>, ApiError> {
+    if params.k == 0 || params.k > analogues::MAX_K {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_K",
+            format!("k must be between 1 and {}", analogues::MAX_K),
+        ));
+    }
+    let data = state.data.read().await;
+    let latest = data
+        .last()
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "NO_DATA", "No data available"))?;
+
+    let found = analogues::find(&data, params.k)
+        .into_iter()
+        .map(|a| Analogue {
+            distance: round(a.distance, 4),
+            niv_score: round(a.niv_score, 2),
+            recession_probability: probability(a.recession_probability),
+            thrust: round(a.thrust, 4),
+            efficiency: round(a.efficiency, 4),
+            slack: round(a.slack, 4),
+            drag: round(a.drag, 4),
+            next_12_months: analogues::Outcome {
+                recession_probability_change: probability(a.next_12_months.recession_probability_change),
+                peak_recession_probability: probability(a.next_12_months.peak_recession_probability),
+                min_niv_score: round(a.next_12_months.min_niv_score, 2),
+                ..a.next_12_months
+            },
+            ..a
+        })
+        .collect();
+
+    Ok(Json(AnaloguesResponse {
+        date: latest.date.to_string(),
+        k: params.k,
+        horizon_months: analogues::HORIZON_MONTHS,
+        analogues: found,
+        model_version: state.model_version(),
+    }))
+}
+
+/// Download history as an Excel workbook with prebuilt charts, or as streamed CSV
+async fn export_history(
+    State(state): State<Arc<AppState>>,
+    workspace: Option<Extension<Workspace>>,
+    Query(params): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    if params.format != "xlsx" && params.format != "csv" {
+        return Err(api_error(StatusCode::BAD_REQUEST, "INVALID_FORMAT", "format must be xlsx or csv"));
+    }
+    if params.format == "xlsx" && !cfg!(feature = "xlsx") {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "XLSX_DISABLED",
+            "this server was built without Excel export support",
+        ));
+    }
+    let recessions = label_set(&state, params.labels.as_deref()).await?;
+    let public = state.public_view(workspace.as_deref());
+    let served = state.data.read().await;
+    let data: &[NIVResult] = match &public {
+        Some(results) => results,
+        None => &served,
+    };
+    let range = resolve_range(data, params.start.as_deref(), params.end.as_deref(), ["start", "end"], None)?;
+    if params.format == "csv" {
+        drop(served);
+        return Ok(csv_response(state, range, public, recessions));
+    }
+    let rows = data.iter().filter(|d| range.contains(d.date)).count();
+    if !streaming::xlsx_fits(rows, state.export_memory) {
+        return Err(api_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "EXPORT_TOO_LARGE",
+            format!(
+                "a workbook of {} months exceeds the {}-byte export ceiling; narrow start/end or use format=csv",
+                rows, state.export_memory
+            ),
+        ));
+    }
+    let results: Vec<NIVResult> = data.iter().filter(|d| range.contains(d.date)).cloned().collect();
+    let pipeline = state.pipeline_hash();
+    drop(served);
+
+    workbook_response(&state, results, recessions, pipeline).await
+}
+
+/// CSV body fed chunk by chunk from the installed dataset, pinned to the
+/// pipeline it started on, or from the public tier's `public` view
+fn csv_response(
+    state: Arc<AppState>,
+    range: DateRange,
+    public: Option<Arc<Vec<NIVResult>>>,
+    recessions: Arc<LabelSet>,
+) -> Response {
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<String, String>>(1);
+    let pipeline = state.pipeline_hash();
+    let rows = streaming::chunk_rows(state.export_memory);
+    let disposition = streaming::attachment(&streaming::filename(&state.model_version(), &pipeline, "csv"), "csv");
+    let format = Format::current(); // The producer outlives the request scope
+
+    tokio::spawn(async move {
+        if tx.send(Ok(streaming::csv_header(format))).await.is_err() {
+            return;
+        }
+        let mut from = range.start;
+        loop {
+            let chunk = {
+                let served = state.data.read().await;
+                let data: &[NIVResult] = match &public {
+                    Some(results) => results,
+                    None => &served,
+                };
+                if public.is_none() && state.pipeline_hash() != pipeline {
+                    Err("dataset or model changed during export".to_string())
+                } else {
+                    let first = data.partition_point(|d| d.date < from);
+                    Ok(data[first..].iter().take_while(|d| d.date <= range.end).take(rows).cloned().collect::<Vec<_>>())
+                }
+            };
+            let chunk = match chunk {
+                Ok(chunk) if chunk.is_empty() => return,
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    tracing::warn!("CSV export aborted: {}", e);
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            let Some(next) = chunk.last().and_then(|r| r.date.checked_add_months(chrono::Months::new(1))) else {
+                return;
+            };
+            if tx.send(Ok(streaming::csv_chunk(&chunk, format, &recessions))).await.is_err() {
+                return; // Client went away
+            }
+            from = next;
+        }
+    });
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, streaming::CONTENT_TYPE)
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .body(Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx)))
+        .expect("static headers are valid")
+}
+
+#[cfg(feature = "xlsx")]
+async fn workbook_response(
+    state: &Arc<AppState>,
+    results: Vec<NIVResult>,
+    recessions: Arc<LabelSet>,
+    pipeline: String,
+) -> Result<Response, ApiError> {
+    let version = state.model_version();
+    let disposition = streaming::attachment(&streaming::filename(&version, &pipeline, "xlsx"), "xlsx");
+    let job = state
+        .jobs
+        .run(Lane::Batch, move |_| Ok(export::workbook(&results, &recessions, &version, &pipeline).map_err(|e| e.to_string())))
+        .await
+        .map_err(|e| job_error(e, "EXPORT_FAILED"))?;
+    let bytes = job
+        .value
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "EXPORT_FAILED", e))?;
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, export::CONTENT_TYPE)
+        .header(header::CONTENT_DISPOSITION, disposition)
+        .body(bytes.into())
+        .expect("static headers are valid"))
+}
+
+#[cfg(not(feature = "xlsx"))]
+async fn workbook_response(
+    _state: &Arc<AppState>,
+    _results: Vec<NIVResult>,
+    _recessions: Arc<LabelSet>,
+    _pipeline: String,
+) -> Result<Response, ApiError> {
+    unreachable!("export is rejected without the xlsx feature")
+}
+
+/// Grafana metric discovery
+async fn grafana_search(body: Option<Json<grafana::SearchRequest>>) -> Json<Vec<&'static str>> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    Json(grafana::search(&request.target))
+}
+
+/// Grafana time series query
+async fn grafana_query(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<grafana::QueryRequest>,
+) -> Result<Json<Vec<grafana::TimeSeries>>, ApiError> {
+    let data = state.data.read().await;
+    let inversions = inversion_penalties(state.engine().spread_spec(), &data, &state.inputs.read().await);
+    let fed: Vec<f64> = data.iter().zip(inversions).map(|(d, inversion)| fed_probability(d, inversion)).collect();
+    grafana::query(&request, &data, &fed)
+        .map(Json)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "UNKNOWN_METRIC", e))
+}
+
+/// Grafana annotations: NBER recessions as shaded regions
+async fn grafana_annotations(Json(request): Json<grafana::AnnotationRequest>) -> Json<Vec<grafana::Annotation>> {
+    Json(grafana::annotations(&request))
+}
+
+/// Methodology changes, newest first
+async fn get_changelog(State(state): State<Arc<AppState>>) -> Json<ChangelogResponse> {
+    let changelog = state.changelog.read().await;
+    Json(ChangelogResponse {
+        count: changelog.entries().len(),
+        entries: changelog.entries().iter().rev().cloned().collect(),
+    })
+}
+
+/// The pipeline hash and what goes into it
+async fn get_pipeline(State(state): State<Arc<AppState>>) -> Json<PipelineResponse> {
+    let namespace = state.namespace();
+    let parameters = state.engine().parameters();
+    Json(PipelineResponse {
+        pipeline_hash: namespace.pipeline_hash(&parameters),
+        economy: namespace.economy,
+        model_version: namespace.model,
+        dataset_revision: namespace.revision,
+        crate_version: env!("CARGO_PKG_VERSION"),
+        parameters,
+    })
+}
+
+/// Deprecation registry, for client tooling
+async fn get_deprecations(State(state): State<Arc<AppState>>) -> Json<DeprecationsResponse> {
+    Json(DeprecationsResponse {
+        count: state.deprecations.entries().len(),
+        deprecations: state.deprecations.entries().to_vec(),
+    })
+}
+
+/// Self-contained HTML widget for iframes; keyless embeds get the public tier
+async fn get_widget(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+) -> Result<Response, ApiError> {
+    let html = match state.public_view(Some(&workspace)) {
+        Some(results) => widget::render(&results, &state.model_version()),
+        None => widget::render(&state.data.read().await, &state.model_version()),
+    }
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "NO_DATA", "No data available"))?;
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(html.into())
+        .expect("static headers are valid"))
+}
+
+/// SSE stream with an `update` event whenever a refresh installs new data;
+/// keyless subscribers under the public tier hear of it once the embargo passes
+async fn widget_events(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let embargo = state
+        .public
+        .as_ref()
+        .filter(|_| workspace.is_keyless())
+        .and_then(|public| public.embargo().to_std().ok())
+        .unwrap_or_default();
+    let mut updates = state.updates.subscribe();
+    let (tx, rx) = tokio::sync::mpsc::channel::<NaiveDate>(16);
+    tokio::spawn(async move {
+        loop {
+            let date = tokio::select! {
+                _ = tx.closed() => return, // Client went away
+                received = updates.recv() => match received {
+                    Ok(date) => date,
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+                },
+            };
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(embargo).await;
+                let _ = tx.send(date).await;
+            });
+        }
+    });
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx)
+        .map(|date| Ok(Event::default().event("update").data(date.to_string())));
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// iCalendar feed of upcoming refreshes, input releases and projected crossings
+async fn get_calendar(State(state): State<Arc<AppState>>) -> Result<Response, ApiError> {
+    let now = chrono::Utc::now();
+    let mut events = Vec::new();
+    if let Some((started, period)) = state.refresh_schedule {
+        let period = chrono::Duration::from_std(period).unwrap_or(chrono::Duration::zero());
+        events.extend(calendar::refresh_events(started, period, now));
+    }
+    events.extend(calendar::release_events(&release_calendar(&state, now.date_naive()).await, now.date_naive()));
+
+    let data = state.data.read().await;
+    if let Some(latest) = data.last() {
+        let calibrations = fitted_calibrations(&state, &data).await;
+        let point = calibration::term_structure(&calibrations, latest);
+        events.extend(calendar::crossing_events(&point, latest));
+    }
+
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "text/calendar; charset=utf-8")
+        .header(header::CONTENT_DISPOSITION, "inline; filename=\"niv.ics\"")
+        .body(calendar::render(&events, now).into())
+        .expect("static headers are valid"))
+}
+
+/// Release calendar as last fetched
+struct ReleaseCalendar {
+    fetched: NaiveDate,
+    retry_at: Option<std::time::Instant>, // Set when the fetch failed
+    releases: Vec<ReleaseDate>,
+}
+
+/// Per-horizon calibrations of the serving dataset, fitted once per install
+///
+/// `data` is the caller's read guard on `state.data`. Storing while it is
+/// held means a fit of a dataset being replaced lands before the install's
+/// clear, never after it.
+async fn fitted_calibrations(state: &AppState, data: &[NIVResult]) -> Arc<Vec<calibration::HorizonCalibration>> {
+    if let Some(fitted) = state.calibrations.read().await.as_ref() {
+        return fitted.clone();
+    }
+    let mut slot = state.calibrations.write().await;
+    if let Some(fitted) = slot.as_ref() {
+        return fitted.clone(); // Fitted while we waited
+    }
+    let fitted = Arc::new(calibration::fit_all(data, &niv::RecessionPeriods::known_recessions()));
+    *slot = Some(fitted.clone());
+    fitted
+}
+
+/// FRED release dates for the input series, fetched at most once a day
+///
+/// One fetch runs at a time; concurrent requests wait for it and share its
+/// result. A failed fetch serves an empty calendar and is retried after
+/// RELEASE_CALENDAR_RETRY rather than the next day.
+async fn release_calendar(state: &Arc<AppState>, today: NaiveDate) -> Vec<ReleaseDate> {
+    let mut cached = state.releases.lock().await;
+    if let Some(calendar) = cached.as_ref() {
+        if calendar.fetched == today && calendar.retry_at.is_none_or(|at| std::time::Instant::now() < at) {
+            return calendar.releases.clone();
+        }
+    }
+    let fetched = tokio::time::timeout(RELEASE_CALENDAR_TIMEOUT, fetch_release_calendar(state.fred_access, today))
+        .await
+        .unwrap_or_else(|_| {
+            tracing::warn!("FRED release calendar timed out after {}s", RELEASE_CALENDAR_TIMEOUT.as_secs());
+            None
+        });
+    let retry_at = fetched.is_none().then(|| std::time::Instant::now() + RELEASE_CALENDAR_RETRY);
+    let releases = fetched.unwrap_or_default();
+    *cached = Some(ReleaseCalendar { fetched: today, retry_at, releases: releases.clone() });
+    releases
+}
+
+/// Upcoming releases; None when FRED could not be reached
+#[cfg(feature = "fred")]
+async fn fetch_release_calendar(access: FredAccess, today: NaiveDate) -> Option<Vec<ReleaseDate>> {
+    let Ok(client) = fred::FredClient::new(access) else {
+        return Some(Vec::new()); // No FRED_API_KEY, or offline: the feed carries refreshes and projections only
+    };
+    client
+        .upcoming_releases(today)
+        .await
+        .map_err(|e| tracing::warn!("FRED release calendar unavailable: {}", e))
+        .ok()
+}
+
+#[cfg(not(feature = "fred"))]
+async fn fetch_release_calendar(_access: FredAccess, _today: NaiveDate) -> Option<Vec<ReleaseDate>> {
+    Some(Vec::new())
+}
+
+/// Raw FRED series, proxied through the server's key
+async fn get_fred_series(
+    State(state): State<Arc<AppState>>,
+    Path(series_id): Path<String>,
+    Query(params): Query<FredSeriesQuery>,
+) -> Result<Response, ApiError> {
+    if !cfg!(feature = "fred") {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "FRED_DISABLED",
+            "this server was built without FRED support",
+        ));
+    }
+    let series_id = series_id.to_uppercase();
+    if !valid_series_id(&series_id) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_SERIES_ID",
+            "series_id must be a FRED series ID (letters, digits and underscores)",
+        ));
+    }
+
+    let (observations, cached) = match fred_series(&state, &series_id).await {
+        Ok(found) => found,
+        Err(response) => return Ok(response),
+    };
+    let (Some(first), Some(last)) = (observations.first(), observations.last()) else {
+        return Err(api_error(StatusCode::NOT_FOUND, "NO_DATA", format!("{} has no observations", series_id)));
+    };
+    let available = DateRange { start: first.0, end: last.0 };
+    let range = daterange::resolve(params.start.as_deref(), params.end.as_deref(), ["start", "end"], available, None)
+        .map_err(|e| range_error(e, available))?;
+
+    let observations: Vec<FredObservationResponse> = observations
+        .iter()
+        .filter(|(date, _)| range.contains(*date))
+        .map(|(date, value)| FredObservationResponse { date: date.to_string(), value: *value })
+        .collect();
+    Ok(Json(FredSeriesResponse { series_id, count: observations.len(), cached, observations }).into_response())
+}
+
+#[cfg(feature = "fred")]
+fn valid_series_id(id: &str) -> bool {
+    fred_proxy::valid_series_id(id)
+}
+
+#[cfg(not(feature = "fred"))]
+fn valid_series_id(_id: &str) -> bool {
+    unreachable!("the FRED passthrough is rejected without the fred feature")
+}
+
+/// Full series from the proxy cache or FRED; Err is the response to send as-is
+#[cfg(feature = "fred")]
+async fn fred_series(state: &Arc<AppState>, series_id: &str) -> Result<(Arc<Vec<(NaiveDate, f64)>>, bool), Response> {
+    use fred_proxy::ProxyError;
+
+    let Some(proxy) = &state.fred_proxy else {
+        return Err(api_error(StatusCode::SERVICE_UNAVAILABLE, "FRED_UNAVAILABLE", "FRED_API_KEY is not configured")
+            .into_response());
+    };
+    proxy.series(series_id).await.map_err(|e| match e {
+        ProxyError::RateLimited(wait) => {
+            let retry_after = wait.as_secs().max(1).to_string();
+            let error = api_error(StatusCode::TOO_MANY_REQUESTS, "FRED_RATE_LIMITED", e.to_string());
+            ([(header::RETRY_AFTER, retry_after)], error).into_response()
+        }
+        ProxyError::NotFound(_) => api_error(StatusCode::NOT_FOUND, "SERIES_NOT_FOUND", e.to_string()).into_response(),
+        ProxyError::Upstream(_) => api_error(StatusCode::BAD_GATEWAY, "FRED_UNAVAILABLE", e.to_string()).into_response(),
+    })
+}
+
+#[cfg(not(feature = "fred"))]
+async fn fred_series(_state: &Arc<AppState>, _series_id: &str) -> Result<(Arc<Vec<(NaiveDate, f64)>>, bool), Response> {
+    unreachable!("the FRED passthrough is rejected without the fred feature")
+}
+
+/// Cluster history into component-space regimes
+async fn get_regimes(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<RegimesQuery>,
+) -> Result<Json<RegimesResponse>, ApiError> {
+    let clusters = validate_clusters(params.clusters)?;
+    let data = state.data.read().await;
+    let latest = data
+        .last()
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "NO_DATA", "No data available"))?;
+
+    let fitted = regimes::cluster(&data, clusters, params.method);
+    Ok(Json(RegimesResponse {
+        clusters,
+        method: params.method,
+        current_date: latest.date.to_string(),
+        current_regime: fitted.current(),
+        regimes: fitted
+            .regimes
+            .iter()
+            .map(|r| Regime {
+                thrust: round(r.thrust, 4),
+                efficiency: round(r.efficiency, 4),
+                slack: round(r.slack, 4),
+                drag: round(r.drag, 4),
+                mean_niv_score: round(r.mean_niv_score, 2),
+                in_recession_share: round(r.in_recession_share, 4),
+                recession_within_horizon_share: round(r.recession_within_horizon_share, 4),
+                ..r.clone()
+            })
+            .collect(),
+        labels: data
+            .iter()
+            .zip(&fitted.labels)
+            .map(|(r, &regime)| RegimeLabel { date: r.date.to_string(), regime })
+            .collect(),
+        model_version: state.model_version(),
+    }))
+}
+
+/// Historical recession frequency given component conditions
+async fn get_conditional(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ConditionalQuery>,
+) -> Result<Json<ConditionalResponse>, ApiError> {
+    let conditions = conditional::parse(params.when.as_deref().unwrap_or_default())
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "INVALID_CONDITION", e))?;
+    if conditions.is_empty() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_CONDITION",
+            "when must list at least one condition, e.g. when=drag>0.02,slack>0.25",
+        ));
+    }
+    if params.horizon == 0 || params.horizon > conditional::MAX_HORIZON_MONTHS {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_HORIZON",
+            format!("horizon must be between 1 and {} months", conditional::MAX_HORIZON_MONTHS),
+        ));
+    }
+    let data = state.data.read().await;
+    let latest = data
+        .last()
+        .ok_or_else(|| api_error(StatusCode::SERVICE_UNAVAILABLE, "NO_DATA", "No data available"))?;
+
+    let labels = match params.regime {
+        Some(regime) => Some(regime_labels(&data, params.clusters, params.method, regime)?),
+        None => None,
+    };
+    let stats = conditional::evaluate(
+        &data,
+        &conditions,
+        params.horizon,
+        labels.as_ref().map(|(labels, regime)| (labels.as_slice(), *regime)),
+    );
+    Ok(Json(ConditionalResponse {
+        latest_date: latest.date.to_string(),
+        latest_matches: conditions.iter().all(|c| c.holds(&latest.components)),
+        conditions,
+        horizon_months: params.horizon,
+        stats: ConditionalStats {
+            probability: stats.probability.map(probability),
+            base_rate: stats.base_rate.map(probability),
+            ..stats
+        },
+        model_version: state.model_version(),
+    }))
+}
+
+/// Yield-curve inversion penalty per result, for the Fed comparison signals
+///
+/// Under the default spread spec this is the served spread drag. The other
+/// specs put a signed penalty in the drag, so the inversion is read from the
+/// month's input spread instead.
+fn inversion_penalties(spec: SpreadSpec, results: &[NIVResult], inputs: &[EconomicData]) -> Vec<f64> {
+    if spec == SpreadSpec::Inversion {
+        return results.iter().map(|r| r.components.drag_spread).collect();
+    }
+    results
+        .iter()
+        .map(|r| {
+            inputs
+                .binary_search_by_key(&r.date, |d| d.date)
+                .map(|i| SpreadSpec::Inversion.penalty(inputs[i].yield_spread, 0.0))
+                .unwrap_or(0.0)
+        })
+        .collect()
+}
+
+/// Get NIV vs Fed comparison data
+async fn get_comparison(State(state): State<Arc<AppState>>) -> Result<Json<Vec<ComparisonPoint>>, StatusCode> {
+    let data = state.data.read().await;
+
+    // Get last 120 months (10 years)
+    let window = &data[data.len().saturating_sub(120)..];
+    let engine = state.engine();
+    let inversions = inversion_penalties(engine.spread_spec(), window, &state.inputs.read().await);
+    let typical = attribution::baseline(&data);
+    let raw = state.raw.read().await;
+    let scores: Vec<f64> = raw.iter().map(|r| r.niv_score).collect();
+    let recent: Vec<ComparisonPoint> = window.iter()
+        .zip(inversions)
+        .map(|(d, inversion)| {
+            let fed = fed_probability(d, inversion);
+            let divergence = d.recession_probability - fed;
+            let link = engine.probability_link(d.date, &scores[..raw.partition_point(|r| r.date < d.date)]);
+            let top = typical.as_ref().and_then(|typical| {
+                attribution::top_contributor(&attribution::contributions(&link, &d.components, typical), divergence)
+            });
+            ComparisonPoint {
+                date: d.date.to_string(),
+                niv_probability: probability(d.recession_probability),
+                fed_probability: probability(fed),
+                divergence: probability(divergence),
+                top_contributor: top.map(|(c, _)| c),
+                contribution: top.map(|(_, delta)| probability(delta)),
+                is_recession: niv::RecessionPeriods::is_recession(d.date),
+            }
+        })
+        .collect();
+
+    Ok(Json(recent))
+}
+
+/// Fed-style benchmark probability based on yield curve inversion
+fn fed_probability(d: &NIVResult, inversion: f64) -> f64 {
+    if inversion > 0.0 {
+        // Inverted yield curve
+        0.6 + inversion * 50.0
+    } else {
+        // Normal yield curve
+        0.2 + d.components.drag * 2.0
+    }.clamp(0.0, 1.0)
+}
+
+/// T10Y3M history with detected inversion episodes
+async fn get_yield_curve(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<YieldCurveQuery>,
+) -> Result<Json<YieldCurveResponse>, ApiError> {
+    let inputs = state.inputs.read().await;
+    let (Some(first), Some(last)) = (inputs.first(), inputs.last()) else {
+        return Err(api_error(StatusCode::SERVICE_UNAVAILABLE, "NO_DATA", "No data available"));
+    };
+    let available = DateRange { start: first.date, end: last.date };
+    let range = daterange::resolve(params.start.as_deref(), params.end.as_deref(), ["start", "end"], available, None)
+        .map_err(|e| range_error(e, available))?;
+
+    // Episodes come from the full history so ones straddling `start` keep their true extent
+    let spreads: Vec<(NaiveDate, f64)> = inputs.iter().map(|d| (d.date, d.yield_spread)).collect();
+    drop(inputs);
+    let episodes: Vec<InversionEpisodeResponse> = yieldcurve::episodes(&spreads, &niv::RecessionPeriods::known_recessions())
+        .into_iter()
+        .filter(|e| e.end >= range.start && e.start <= range.end)
+        .map(|e| InversionEpisodeResponse {
+            start: e.start.to_string(),
+            end: e.end.to_string(),
+            months: e.months,
+            max_depth: round(e.max_depth, 4),
+            trough_date: e.trough_date.to_string(),
+            ongoing: e.ongoing,
+            recession: e.recession.map(|r| FollowingRecessionResponse {
+                name: recession_name(r.start),
+                start: r.start.to_string(),
+                end: r.end.to_string(),
+                lead_months: r.lead_months,
+            }),
+        })
+        .collect();
+
+    let current_spread = spreads.last().map(|(_, s)| *s).unwrap_or_default();
+    let data: Vec<YieldCurvePoint> = spreads
+        .iter()
+        .filter(|(date, _)| range.contains(*date))
+        .map(|&(date, spread)| YieldCurvePoint {
+            date: date.to_string(),
+            spread: round(spread, 4),
+            inverted: spread < 0.0,
+            is_recession: niv::RecessionPeriods::is_recession(date),
+        })
+        .collect();
+
+    Ok(Json(YieldCurveResponse {
+        series: "T10Y3M",
+        count: data.len(),
+        current_spread: round(current_spread, 4),
+        inverted_now: current_spread < 0.0,
+        episodes_followed_by_recession: episodes.iter().filter(|e| e.recession.is_some()).count(),
+        data,
+        episodes,
+    }))
+}
+
+#[derive(Serialize)]
+struct ComparisonPoint {
+    date: String,
+    niv_probability: f64,
+    fed_probability: f64,
+    divergence: f64,                   // niv_probability - fed_probability
+    top_contributor: Option<Component>, // Component pushing NIV furthest in the divergence's direction
+    contribution: Option<f64>,         // Its signed probability delta vs. a typical (median) month
+    is_recession: bool,
+}
+
+/// Get recession periods of a label set, newest first
+async fn get_recessions(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LabelsQuery>,
+) -> Result<Json<Vec<RecessionPeriod>>, ApiError> {
+    let set = label_set(&state, params.labels.as_deref()).await?;
+    let periods: Vec<RecessionPeriod> = set
+        .periods
+        .iter()
+        .rev()
+        .map(|p| RecessionPeriod {
+            start: p.start.to_string(),
+            end: p.end.to_string(),
+            name: p.name.clone().unwrap_or_else(|| recession_name(p.start)),
+        })
+        .collect();
+
+    Ok(Json(periods))
+}
+
+/// Query parameter selecting a recession label set
+#[derive(Debug, Deserialize)]
+struct LabelsQuery {
+    labels: Option<String>,
+}
+
+/// Named label set, or 400 listing the available ones
+async fn label_set(state: &AppState, name: Option<&str>) -> Result<Arc<LabelSet>, ApiError> {
+    let sets = state.labels.read().await;
+    sets.get(name).ok_or_else(|| {
+        let available: Vec<&str> = sets.all().map(|s| s.name.as_str()).collect();
+        api_error(
+            StatusCode::BAD_REQUEST,
+            "UNKNOWN_LABELS",
+            format!("no label set '{}' (available: {})", name.unwrap_or_default(), available.join(", ")),
+        )
+    })
+}
+
+/// Every recession label set
+async fn get_label_sets(State(state): State<Arc<AppState>>) -> Json<Vec<LabelSet>> {
+    Json(state.labels.read().await.all().map(|s| LabelSet::clone(s)).collect())
+}
+
+/// Import (or replace) a label set; CSV with `Content-Type: text/csv`, else JSON
+async fn put_label_set(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    headers: axum::http::HeaderMap,
+    body: String,
+) -> Result<Json<LabelSet>, ApiError> {
+    let csv = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/csv"));
+    let format = if csv { LabelFormat::Csv } else { LabelFormat::Json };
+    let invalid = |e: String| api_error(StatusCode::BAD_REQUEST, "INVALID_LABELS", e);
+    let set = labels::parse(&name, &body, format).map_err(invalid)?;
+    tracing::info!("Imported label set {} ({} periods)", set.name, set.periods.len());
+    state.labels.write().await.insert(set.clone()).map_err(invalid)?;
+    Ok(Json(set))
+}
+
+/// Remove an imported label set
+async fn delete_label_set(State(state): State<Arc<AppState>>, Path(name): Path<String>) -> Result<StatusCode, ApiError> {
+    match state.labels.write().await.remove(&name) {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err(api_error(StatusCode::NOT_FOUND, "UNKNOWN_LABELS", format!("no label set '{}'", name))),
+        Err(e) => Err(api_error(StatusCode::BAD_REQUEST, "INVALID_LABELS", e)),
+    }
+}
+
+#[derive(Serialize)]
+struct RecessionPeriod {
+    start: String,
+    end: String,
+    name: String,
+}
+
+/// Get startup self-test results
+async fn get_validation(State(state): State<Arc<AppState>>) -> Json<Option<SelfTestReport>> {
+    let validation = state.validation.read().await;
+    Json(validation.clone())
+}
+
+/// Get the distribution of lead times before past recessions
+async fn get_lead_times(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LeadTimeQuery>,
+) -> Result<Json<LeadTimeResponse>, ApiError> {
+    let threshold = lead_threshold(&params)?;
+
+    let recessions = label_set(&state, params.labels.as_deref()).await?;
+    let data = state.data.read().await;
+    let filter = regime_filter(&data, params.clusters, params.method, params.regime)?;
+    let chronology = recessions.chronology();
+    let chronology = filter.as_ref().map_or(chronology.clone(), |f| f.recessions(&chronology));
+    let distribution = backtest::lead_time_distribution_for(
+        backtest::evaluated(&data),
+        &chronology,
+        threshold,
+        params.lookback,
+    );
+
+    Ok(Json(LeadTimeResponse {
+        threshold: probability(threshold),
+        labels: recessions.name.clone(),
+        regime: params.regime,
+        distribution,
+        model_version: state.model_version(),
+    }))
+}
+
+/// Backtest rows against the chosen label set, with the CSV's file name
+async fn replication_rows(
+    state: &Arc<AppState>,
+    labels: Option<&str>,
+) -> Result<(Vec<replication::Row>, Arc<LabelSet>, String), ApiError> {
+    let recessions = label_set(state, labels).await?;
+    let data = state.data.read().await;
+    let rows = replication::rows(backtest::evaluated(&data), &recessions.chronology(), research::LABEL_HORIZON_MONTHS);
+    let filename = format!("backtest-{}", streaming::filename(&state.model_version(), &state.pipeline_hash(), "csv"));
+    Ok((rows, recessions, filename))
+}
+
+/// Schema, provenance and per-fold metrics of the replication CSV
+async fn get_replication(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LabelsQuery>,
+) -> Result<Json<ReplicationResponse>, ApiError> {
+    let (rows, recessions, csv_file) = replication_rows(&state, params.labels.as_deref()).await?;
+    let rounded = |m: replication::Metrics| replication::Metrics {
+        auc: m.auc.map(|v| round(v, 4)),
+        brier: m.brier.map(|v| round(v, 4)),
+        ..m
+    };
+    let parameters = state.engine().parameters();
+
+    Ok(Json(ReplicationResponse {
+        schema_version: replication::SCHEMA_VERSION,
+        csv_file,
+        csv_sha256: replication::sha256(&replication::csv(&rows)),
+        columns: replication::COLUMNS
+            .iter()
+            .map(|&(name, kind, description)| ReplicationColumn { name, kind, description })
+            .collect(),
+        pipeline_hash: state.namespace().pipeline_hash(&parameters),
+        model_version: state.model_version(),
+        crate_version: env!("CARGO_PKG_VERSION"),
+        parameters,
+        labels: recessions.name.clone(),
+        label_horizon_months: research::LABEL_HORIZON_MONTHS,
+        fold_scheme: "recession_blocks",
+        reported_auc: MODEL_AUC,
+        overall: rounded(replication::metrics(&rows)),
+        folds: replication::folds(&rows, &recessions.chronology())
+            .into_iter()
+            .map(|f| replication::Fold { metrics: rounded(f.metrics), ..f })
+            .collect(),
+    }))
+}
+
+/// The replication CSV itself
+async fn get_replication_csv(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LabelsQuery>,
+) -> Result<Response, ApiError> {
+    let (rows, _, filename) = replication_rows(&state, params.labels.as_deref()).await?;
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, streaming::CONTENT_TYPE)
+        .header(header::CONTENT_DISPOSITION, streaming::attachment(&filename, "csv"))
+        .body(replication::csv(&rows).into())
+        .expect("static headers are valid"))
+}
+
+/// Episodes above the threshold with no recession starting within the lookback window
+async fn get_false_alarms(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LeadTimeQuery>,
+) -> Result<Json<FalseAlarmsResponse>, ApiError> {
+    let threshold = lead_threshold(&params)?;
+
+    let recessions = label_set(&state, params.labels.as_deref()).await?;
+    let data = state.data.read().await;
+    let chronology = recessions.chronology();
+    let false_alarms: Vec<FalseAlarm> = backtest::false_alarm_episodes(backtest::evaluated(&data), &chronology, threshold, params.lookback)
+        .into_iter()
+        .map(|a| FalseAlarm { peak_probability: probability(a.peak_probability), ..a })
+        .collect();
+
+    Ok(Json(FalseAlarmsResponse {
+        threshold: probability(threshold),
+        lookback_months: params.lookback,
+        labels: recessions.name.clone(),
+        count: false_alarms.len(),
+        months_in_false_alarm: false_alarms.iter().map(|a| a.months).sum(),
+        false_alarms,
+        model_version: state.model_version(),
+    }))
+}
+
+/// Contiguous runs at or above an alert level, and whether a recession followed
+async fn get_episodes(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EpisodesQuery>,
+) -> Result<Json<EpisodesResponse>, ApiError> {
+    if params.min_duration == 0 {
+        return Err(api_error(StatusCode::BAD_REQUEST, "INVALID_DURATION", "min_duration must be at least 1 month"));
+    }
+    if params.lookback == 0 || params.lookback > backtest::MAX_LOOKBACK_MONTHS {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_LOOKBACK",
+            format!("lookback must be between 1 and {} months", backtest::MAX_LOOKBACK_MONTHS),
+        ));
+    }
+    let threshold = params.level.threshold();
+
+    let recessions = label_set(&state, params.labels.as_deref()).await?;
+    let data = state.data.read().await;
+    let episodes: Vec<Episode> = backtest::alert_episodes(backtest::evaluated(&data), &recessions.chronology(), threshold, params.lookback)
+        .into_iter()
+        .filter(|e| e.months >= params.min_duration)
+        .map(|e| Episode { peak_probability: probability(e.peak_probability), ..e })
+        .collect();
+
+    Ok(Json(EpisodesResponse {
+        level: params.level,
+        threshold: probability(threshold),
+        min_duration_months: params.min_duration,
+        lookback_months: params.lookback,
+        labels: recessions.name.clone(),
+        count: episodes.len(),
+        episodes,
+        model_version: state.model_version(),
+    }))
+}
+
+/// NIV, probability and component paths from `pre` months before to `post`
+/// months after each recession start, averaged across recessions
+async fn get_event_study(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<EventStudyQuery>,
+) -> Result<Json<EventStudyResponse>, ApiError> {
+    if params.pre > eventstudy::MAX_PRE_MONTHS || params.post > eventstudy::MAX_POST_MONTHS {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_WINDOW",
+            format!(
+                "pre must be at most {} and post at most {} months",
+                eventstudy::MAX_PRE_MONTHS,
+                eventstudy::MAX_POST_MONTHS
+            ),
+        ));
+    }
+    let recessions = label_set(&state, params.labels.as_deref()).await?;
+    let data = state.data.read().await;
+    let mut study = eventstudy::study(&data, &recessions.chronology(), params.pre, params.post);
+    for path in study.paths.iter_mut() {
+        let value: fn(f64) -> f64 =
+            if path.series == "recession_probability" { |v| format::probability_to(v, 4) } else { |v| round(v, 4) };
+        for band in path.bands.iter_mut() {
+            (band.mean, band.std, band.p10, band.p90) = (value(band.mean), value(band.std), value(band.p10), value(band.p90));
+        }
+    }
+
+    Ok(Json(EventStudyResponse {
+        pre_months: params.pre,
+        post_months: params.post,
+        labels: recessions.name.clone(),
+        study,
+        model_version: state.model_version(),
+    }))
+}
+
+/// Thrust inputs per month, including the M2 acceleration (second derivative) term
+async fn get_thrust_inputs(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ThrustInputsQuery>,
+) -> Result<Json<ThrustInputsResponse>, ApiError> {
+    let range = resolve_range(
+        &state.data.read().await,
+        params.start.as_deref(),
+        params.end.as_deref(),
+        ["start", "end"],
+        None,
+    )?;
+    let engine = state.engine();
+    let extended = engine.compute_extended_data(&state.inputs.read().await);
+
+    let data: Vec<ThrustInputPoint> = extended
+        .iter()
+        .filter(|e| range.contains(e.base.date))
+        .map(|e| {
+            let thrust_input = engine.thrust_input(e);
+            ThrustInputPoint {
+                date: e.base.date.to_string(),
+                dg: round(e.dg, 4),
+                da: round(e.da, 4),
+                dr: round(e.dr, 4),
+                m2_accel: round(e.m2_accel, 4),
+                thrust_input: round(thrust_input, 4),
+                thrust_scale: round(e.thrust_scale, 4),
+                thrust: round((thrust_input / e.thrust_scale).tanh(), 4),
+            }
+        })
+        .collect();
+
+    Ok(Json(ThrustInputsResponse {
+        count: data.len(),
+        weights: engine.weights(),
+        m2_accel_lag_months: niv::M2_ACCEL_LAG,
+        model_version: state.model_version(),
+        data,
+    }))
+}
+
+/// Term structure of recession-start probabilities from per-horizon calibrations
+async fn get_term_structure(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<TermStructureQuery>,
+) -> Result<Json<TermStructureResponse>, ApiError> {
+    let data = state.data.read().await;
+    let range = resolve_range(&data, params.start.as_deref(), params.end.as_deref(), ["start", "end"], state.max_span_months)?;
+    let calibrations = fitted_calibrations(&state, &data).await;
+
+    let report = |mut p: calibration::TermStructurePoint| {
+        p.niv_score = round(p.niv_score, 2);
+        for h in p.curve.iter_mut() {
+            h.probability = probability(h.probability);
+        }
+        p
+    };
+
+    let history = params.history.then(|| {
+        data.iter()
+            .filter(|d| range.contains(d.date))
+            .map(|d| report(calibration::term_structure(&calibrations, d)))
+            .collect()
+    });
+
+    Ok(Json(TermStructureResponse {
+        horizons: calibration::TERM_STRUCTURE_HORIZONS.to_vec(),
+        current: data.last().map(|d| report(calibration::term_structure(&calibrations, d))),
+        calibrations: calibrations.to_vec(),
+        history,
+        model_version: state.model_version(),
+    }))
+}
+
+/// Validate lead-time parameters and resolve the probability threshold
+fn lead_threshold(params: &LeadTimeQuery) -> Result<f64, ApiError> {
+    let threshold = match params.threshold {
+        Some(t) if !(0.0..=100.0).contains(&t) => {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "INVALID_THRESHOLD",
+                "threshold must be a percentage between 0 and 100",
+            ));
+        }
+        Some(t) => t / 100.0,
+        None => params.level.threshold(),
+    };
+    if params.lookback == 0 || params.lookback > backtest::MAX_LOOKBACK_MONTHS {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_LOOKBACK",
+            format!("lookback must be between 1 and {} months", backtest::MAX_LOOKBACK_MONTHS),
+        ));
+    }
+    Ok(threshold)
+}
+
+/// Model card for the serving model or the registered candidate
+async fn get_model_card(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ModelCard>, ApiError> {
+    if id == state.model_version() {
+        let engine = state.engine();
+        let data = state.data.read().await;
+        let validation = state.validation.read().await;
+        return Ok(Json(modelcard::card(&id, ModelStatus::Serving, &engine, &data, validation.as_ref())));
+    }
+
+    // The candidate's history from its latest shadow run (registration or refresh)
+    let candidate = state.canary.read().await.as_ref().filter(|c| c.version == id).map(|c| (c.engine.clone(), c.results()));
+    let (engine, results) = candidate.ok_or_else(|| {
+        api_error(
+            StatusCode::NOT_FOUND,
+            "MODEL_NOT_FOUND",
+            format!("no model '{}'; the serving model is {}", id, state.model_version()),
+        )
+    })?;
+    Ok(Json(modelcard::card(&id, ModelStatus::Candidate, &engine, &results, None)))
+}
+
+/// Backtest leaderboard across engine variants
+async fn get_leaderboard(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<LeadTimeQuery>,
+) -> Result<Timed<LeaderboardResponse>, ApiError> {
+    let threshold = lead_threshold(&params)?;
+    let lookback = params.lookback;
+    let recessions = label_set(&state, params.labels.as_deref()).await?;
+    let chronology = recessions.chronology();
+    let inputs = state.inputs.read().await.clone();
+    let variants = research::variants(&state.engine().registry().definitions());
+    check_budget(&state, budget::cost::leaderboard(variants.len(), inputs.len()))?;
+    let filter = regime_filter(&state.data.read().await, params.clusters, params.method, params.regime)?;
+
+    let job = state.jobs.run(Lane::Batch, move |cancel| {
+        research::leaderboard(&variants, &inputs, &chronology, threshold, lookback, filter.as_ref(), cancel)
+    })
+    .await
+    .map_err(|e| job_error(e, "LEADERBOARD_FAILED"))?;
+
+    Ok(timed(LeaderboardResponse {
+        threshold: probability(threshold),
+        lookback_months: lookback,
+        label_horizon_months: research::LABEL_HORIZON_MONTHS,
+        labels: recessions.name.clone(),
+        regime: params.regime,
+        variants: job.value,
+        model_version: state.model_version(),
+    }, job.elapsed, job.queued))
+}
+
+/// Measure detection power against synthetic economies with known recessions
+async fn get_synthetic_benchmark(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SyntheticBenchmarkQuery>,
+) -> Result<Timed<SyntheticBenchmarkResponse>, ApiError> {
+    if params.economies == 0 || params.economies > MAX_SYNTHETIC_ECONOMIES {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_ECONOMIES",
+            format!("economies must be between 1 and {}", MAX_SYNTHETIC_ECONOMIES),
+        ));
+    }
+    if params.recession_hazard.is_some_and(|h| !(h > 0.0 && h <= 1.0)) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_HAZARD",
+            "recession_hazard must be in (0, 1]",
+        ));
+    }
+
+    let defaults = SyntheticConfig::default();
+    let config = SyntheticConfig {
+        seed: params.seed.unwrap_or(defaults.seed),
+        signal_strength: params.signal_strength.unwrap_or(defaults.signal_strength),
+        noise: params.noise.unwrap_or(defaults.noise).max(0.0),
+        recession_hazard: params.recession_hazard.unwrap_or(defaults.recession_hazard),
+        lead_months: params.lead_months.unwrap_or(defaults.lead_months).clamp(1, 36),
+        ..defaults
+    };
+    let threshold = params.level.threshold();
+    let economies = params.economies;
+    check_budget(&state, budget::cost::synthetic_benchmark(economies, config.months))?;
+
+    let shared = state.clone();
+    let job = state.jobs.run(Lane::Batch, move |cancel| {
+        synthetic::benchmark(&shared.engine(), &config, economies, threshold, cancel)
+    })
+    .await
+    .map_err(|e| job_error(e, "BENCHMARK_FAILED"))?;
+
+    Ok(timed(SyntheticBenchmarkResponse {
+        threshold: probability(threshold),
+        benchmark: job.value,
+        model_version: state.model_version(),
+    }, job.elapsed, job.queued))
+}
+
+/// Run a scenario-conditioned Monte Carlo over the next N months
+async fn run_monte_carlo(
+    State(state): State<Arc<AppState>>,
+    workspace: Option<Extension<Workspace>>,
+    Json(req): Json<MonteCarloRequest>,
+) -> Result<Timed<MonteCarloResponse>, ApiError> {
+    let config = req.config().map_err(request_error)?;
+    let scenario = match &req.favorite {
+        Some(name) => favorite_scenario(&state.preferences(workspace.as_deref()), name)?,
+        None => req.scenario,
+    };
+    let (id, stored, timing) = stored_monte_carlo(&state, scenario, config).await?;
+    let (elapsed, queued) = timing.unwrap_or_default();
+    Ok(timed(monte_carlo_response(id, &stored, timing.is_none(), false), elapsed, queued))
+}
+
+/// A saved scenario by name
+fn favorite_scenario(prefs: &Preferences, name: &str) -> Result<Scenario, ApiError> {
+    prefs.scenarios.get(name).cloned().ok_or_else(|| {
+        api_error(StatusCode::BAD_REQUEST, "UNKNOWN_FAVORITE", format!("no favorite scenario '{}' in your preferences", name))
+    })
+}
 
-#[tokio::main]
-async fn main() {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "info".into()),
-        ))
-        .init();
+/// Run ID and stored result for a Monte Carlo run, running it unless already
+/// stored; the timing is (elapsed, queued) when it ran, None when it was stored
+async fn stored_monte_carlo(
+    state: &Arc<AppState>,
+    scenario: Scenario,
+    config: MonteCarloConfig,
+) -> Result<(String, Arc<StoredRun>, Option<(Duration, Duration)>), ApiError> {
+    let namespace = state.namespace();
+    let id = montecarlo::run_id(&scenario, &config, &namespace.to_string());
+    if let Some(stored) = state.mc_runs.get(&id).await {
+        return Ok((id, stored, None));
+    }
+    check_budget(state, budget::cost::monte_carlo(config.draws, config.horizon_months))?;
 
-    tracing::info!("Starting NIV Engine API Server {}", MODEL_VERSION);
-    tracing::info!("OOS Performance: AUC {} vs Fed Yield Curve {}", MODEL_AUC, FED_AUC);
+    let shared = state.clone();
+    let job = state.jobs.run(Lane::Batch, move |cancel| {
+        let history = shared.inputs.blocking_read();
+        match montecarlo::run(&shared.engine(), &history, &scenario, config, cancel) {
+            Err(RunError::Cancelled) => Err(budget::Cancelled),
+            outcome => Ok(outcome),
+        }
+    })
+    .await
+    .map_err(|e| job_error(e, "MONTE_CARLO_FAILED"))?;
+    let result = job.value.map_err(|e| api_error(StatusCode::BAD_REQUEST, "INVALID_SAMPLING", e.to_string()))?;
 
-    // Initialize engine and compute initial data
-    let engine = NIVEngine::new();
-    let mock_data = mock::generate_mock_data(1960, 2026);
-    let initial_results = engine.calculate_series(&mock_data);
+    let stored = Arc::new(StoredRun { result, namespace });
+    state.mc_runs.insert(id.clone(), stored.clone()).await;
+    Ok((id, stored, Some((job.elapsed, job.queued))))
+}
 
-    tracing::info!("Computed {} NIV data points", initial_results.len());
+/// A stored Monte Carlo run, including the full per-month distribution
+async fn get_monte_carlo(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<MonteCarloResponse>, ApiError> {
+    let stored = state.mc_runs.get(&id).await.ok_or_else(|| {
+        api_error(
+            StatusCode::NOT_FOUND,
+            "MONTE_CARLO_NOT_FOUND",
+            format!("No Monte Carlo run '{}' (runs are kept for {} hours)", id, MC_RUN_TTL.as_secs() / 3600),
+        )
+    })?;
+    Ok(Json(monte_carlo_response(id, &stored, true, true)))
+}
 
-    // Run validation on startup
-    let validation = engine.validate_against_benchmarks(&initial_results);
-    if validation.passed {
-        tracing::info!("✅ OOS Validation PASSED");
-    } else {
-        tracing::warn!("⚠️ OOS Validation FAILED - check calculation logic");
+fn monte_carlo_response(id: String, stored: &StoredRun, cached: bool, distribution: bool) -> MonteCarloResponse {
+    let result = &stored.result;
+    MonteCarloResponse {
+        id,
+        cached,
+        scenario: result.scenario.clone(),
+        horizon_months: result.horizon_months,
+        draws: result.draws,
+        seed: result.seed,
+        sampling: result.sampling,
+        starting_alert_level: result.starting_alert_level,
+        prob_critical_within_horizon: probability(result.prob_critical_within_horizon),
+        months: result.months
+            .iter()
+            .map(|m| MonteCarloMonth {
+                date: m.date.to_string(),
+                scenario_probability: probability(m.scenario_probability),
+                p10: probability(m.p10),
+                p50: probability(m.p50),
+                p90: probability(m.p90),
+                prob_critical_by_month: probability(m.prob_critical_by_month),
+            })
+            .collect(),
+        distribution: distribution.then(|| {
+            result.distribution.iter().map(|draws| draws.iter().map(|p| probability(*p)).collect()).collect()
+        }),
+        dataset_revision: stored.namespace.revision.clone(),
+        model_version: stored.namespace.model.clone(),
     }
-    for check in &validation.checks {
-        let status = if check.passed { "✓" } else { "✗" };
-        tracing::info!("  {} {}: {} (expected: {})", status, check.name, check.actual, check.expected);
+}
+
+/// Sign a simulate/montecarlo request into an expiring share link
+async fn create_share(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+    Json(mut req): Json<ShareRequest>,
+) -> Result<Json<ShareResponse>, ApiError> {
+    if req.ttl_hours == 0 || req.ttl_hours > share::MAX_TTL_HOURS {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_TTL",
+            format!("ttl_hours must be between 1 and {}", share::MAX_TTL_HOURS),
+        ));
+    }
+    let invalid = |e: serde_json::Error| api_error(StatusCode::BAD_REQUEST, "INVALID_SHARE_REQUEST", e.to_string());
+    // Links carry the sharer's preferences resolved, not the viewer's
+    let prefs = state.preferences(Some(&workspace));
+    match req.kind {
+        ShareKind::Simulation => {
+            let sim = serde_json::from_value::<SimulateRequest>(req.request.clone()).map_err(invalid)?;
+            if let (None, Some(weights), Some(body)) = (sim.engine.weights, prefs.weights, req.request.as_object_mut()) {
+                body.insert("weights".into(), serde_json::to_value(weights).expect("weights serializes"));
+            }
+        }
+        ShareKind::MonteCarlo => {
+            let mc = serde_json::from_value::<MonteCarloRequest>(req.request.clone()).map_err(invalid)?;
+            if let (Some(name), Some(body)) = (&mc.favorite, req.request.as_object_mut()) {
+                let scenario = favorite_scenario(&prefs, name)?;
+                body.remove("favorite");
+                body.insert("scenario".into(), serde_json::to_value(scenario).expect("scenario serializes"));
+            }
+            // Pin the seed so everyone opening the link sees the same draws
+            if let (None, Some(body)) = (mc.seed, req.request.as_object_mut()) {
+                body.insert("seed".into(), rand::random::<u64>().into());
+            }
+        }
     }
 
-    // Create cache with 1 hour TTL
-    let cache: Cache<String, CachedData> = Cache::builder()
-        .time_to_live(Duration::from_secs(3600))
-        .build();
+    let pipeline = state.pipeline_hash();
+    if req.kind == ShareKind::MonteCarlo {
+        // Run it now, so opening the link serves the stored run
+        let mc = serde_json::from_value::<MonteCarloRequest>(req.request.clone()).map_err(invalid)?;
+        let config = mc.config().map_err(request_error)?;
+        stored_monte_carlo(&state, mc.scenario, config).await?;
+    }
+    let claims = Claims {
+        kind: req.kind,
+        request: req.request,
+        pipeline,
+        expires_at: chrono::Utc::now() + chrono::Duration::hours(req.ttl_hours.into()),
+    };
+    let token = state.share.sign(&claims);
+    Ok(Json(ShareResponse {
+        kind: claims.kind,
+        url: format!("/s/{}", token),
+        token,
+        expires_at: claims.expires_at.to_rfc3339(),
+    }))
+}
 
-    // Store initial data in cache
-    cache.insert("niv_data".to_string(), CachedData {
-        results: initial_results.clone(),
-        computed_at: chrono::Utc::now(),
-    }).await;
+/// Read-only view of a shared result, on the pipeline it was shared from
+async fn get_shared(State(state): State<Arc<AppState>>, Path(token): Path<String>) -> Result<Response, ApiError> {
+    let claims = state.share.verify(&token, chrono::Utc::now()).map_err(|e| match e {
+        ShareError::Expired(_) => api_error(StatusCode::GONE, "SHARE_EXPIRED", e.to_string()),
+        _ => api_error(StatusCode::NOT_FOUND, "SHARE_NOT_FOUND", e.to_string()),
+    })?;
+    if claims.pipeline != state.pipeline_hash() {
+        return Err(api_error(
+            StatusCode::GONE,
+            "SHARE_STALE",
+            format!("the dataset or model this link was computed on (pipeline {}) is no longer served", claims.pipeline),
+        ));
+    }
+    let invalid = |e: serde_json::Error| api_error(StatusCode::BAD_REQUEST, "INVALID_SHARE_REQUEST", e.to_string());
+    Ok(match claims.kind {
+        ShareKind::Simulation => {
+            let req = serde_json::from_value(claims.request).map_err(invalid)?;
+            simulate(State(state), None, Json(req)).await?.into_response()
+        }
+        ShareKind::MonteCarlo => {
+            let req = serde_json::from_value(claims.request).map_err(invalid)?;
+            run_monte_carlo(State(state), None, Json(req)).await?.into_response()
+        }
+    })
+}
 
-    let state = Arc::new(AppState {
-        engine,
-        cache,
-        data: RwLock::new(initial_results),
-        validation: RwLock::new(Some(validation)),
-    });
+/// Recompute the dataset now (and shadow the canary)
+async fn admin_refresh(State(state): State<Arc<AppState>>) -> Result<Json<RefreshResponse>, ApiError> {
+    let data_points = refresh(&state, false)
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "REFRESH_FAILED", e))?;
+    let canary = state.canary.read().await.as_ref().map(|c| c.status(state.canary_refreshes));
 
-    // Configure CORS
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    Ok(Json(RefreshResponse {
+        refreshed_at: chrono::Utc::now().to_rfc3339(),
+        data_points,
+        canary,
+        model_version: state.model_version(),
+    }))
+}
 
-    // Build router
-    let app = Router::new()
-        .route("/", get(root))
-        .route("/health", get(health))
-        .route("/api/v1/latest", get(get_latest))
-        .route("/api/v1/history", get(get_history))
-        .route("/api/v1/components", get(get_components))
-        .route("/api/v1/compare", get(get_comparison))
-        .route("/api/v1/recessions", get(get_recessions))
-        .route("/api/v1/validation", get(get_validation))
-        .layer(cors)
-        .layer(TraceLayer::new_for_http())
-        .with_state(state);
+/// The data checks on the served dataset and on freshly fetched FRED data,
+/// with every check whose outcome differs
+async fn live_validation(State(state): State<Arc<AppState>>) -> Result<Json<LiveValidationResponse>, ApiError> {
+    if !cfg!(feature = "fred") {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "FRED_DISABLED",
+            "this server was built without FRED support",
+        ));
+    }
+    let inputs = fetch_live_inputs(state.fred_access, state.partial_data).await?;
+    let engine = state.engine();
+    let embedded = state.data.read().await.clone();
+    let model_version = state.model_version();
+    let job = state
+        .jobs
+        .run(Lane::Batch, move |_| {
+            let live = engine.smooth(&engine.calculate_raw(&inputs), niv::SMOOTH_WINDOW);
+            let embedded_checks = data_checks(&embedded, &engine.validate_against_benchmarks(&embedded));
+            let live_checks = data_checks(&live, &engine.validate_against_benchmarks(&live));
+            Ok(LiveValidationResponse {
+                embedded_months: embedded.len(),
+                live_months: live.len(),
+                embedded_validation: changelog::validation(&embedded),
+                live_validation: changelog::validation(&live),
+                report: selftest::sensitivity(&embedded_checks, &live_checks),
+                model_version,
+            })
+        })
+        .await
+        .map_err(|e| job_error(e, "VALIDATION_FAILED"))?;
 
-    // Get port from environment or default
-    let port = std::env::var("PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse::<u16>()
-        .unwrap_or(8080);
+    if job.value.report.embedded_only_passes > 0 {
+        tracing::warn!("{} self-test checks pass only on embedded data", job.value.report.embedded_only_passes);
+    }
+    Ok(Json(job.value))
+}
 
-    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
-    tracing::info!("Listening on http://{}", addr);
+#[cfg(feature = "fred")]
+async fn fetch_live_inputs(access: FredAccess, partial: coverage::PartialDataMode) -> Result<Vec<EconomicData>, ApiError> {
+    let client = fred::FredClient::new(access).map_err(|e| {
+        let message = match e {
+            fred::FredError::Offline => e.to_string(),
+            _ => "FRED_API_KEY is not configured".to_string(),
+        };
+        api_error(StatusCode::SERVICE_UNAVAILABLE, "FRED_UNAVAILABLE", message)
+    })?;
+    let inputs = client
+        .fetch_all(None, None, partial)
+        .await
+        .map_err(|e| api_error(StatusCode::BAD_GATEWAY, "FRED_UNAVAILABLE", e.to_string()))?;
+    if inputs.is_empty() {
+        return Err(api_error(StatusCode::BAD_GATEWAY, "FRED_UNAVAILABLE", "FRED returned no observations"));
+    }
+    Ok(inputs)
+}
 
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+#[cfg(not(feature = "fred"))]
+async fn fetch_live_inputs(_access: FredAccess, _partial: coverage::PartialDataMode) -> Result<Vec<EconomicData>, ApiError> {
+    unreachable!("live validation is rejected without the fred feature")
 }
 
-/// Root endpoint
-async fn root() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "name": "NIV Engine API",
-        "version": "1.0.0",
-        "model_version": MODEL_VERSION,
-        "description": "National Impact Velocity - Physics-based Macro Crisis Detection",
-        "performance": {
-            "niv_auc": MODEL_AUC,
-            "fed_yield_curve_auc": FED_AUC,
-            "outperformance": format!("+{:.1}%", (MODEL_AUC - FED_AUC) / FED_AUC * 100.0)
-        },
-        "formula": {
-            "master": "NIV_t = (u_t × P_t²) / (X_t + F_t)^η",
-            "thrust": "u = tanh(1.0*dG + 1.0*dA - 0.7*dr)",
-            "efficiency": "P = (Investment × 1.15) / GDP",
-            "slack": "X = 1 - (TCU/100)",
-            "drag": "F = 0.4*s_t + 0.4*(r-π) + 0.2*σ_r",
-            "parameters": {
-                "eta": 1.5,
-                "epsilon": 0.001
-            }
-        },
-        "endpoints": {
-            "latest": "/api/v1/latest",
-            "history": "/api/v1/history",
-            "components": "/api/v1/components",
-            "compare": "/api/v1/compare",
-            "recessions": "/api/v1/recessions",
-            "validation": "/api/v1/validation",
-            "health": "/health"
-        },
-        "documentation": "https://regenerationism.ai/methodology"
+/// What the rules would have sent over the served history; nothing is sent
+async fn notification_dry_run(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<NotificationDryRunRequest>,
+) -> Result<Json<NotificationDryRunResponse>, ApiError> {
+    notifications::validate(&req.rules).map_err(|e| api_error(StatusCode::BAD_REQUEST, "INVALID_RULES", e))?;
+    let data = state.data.read().await;
+    let range = resolve_range(&data, req.start.as_deref(), req.end.as_deref(), ["start", "end"], None)?;
+    let transitions: Vec<niv::AlertTransition> =
+        state.engine().alert_transitions(&data).into_iter().filter(|t| range.contains(t.date)).collect();
+    drop(data);
+
+    let (outcomes, timeline) = notifications::dry_run(&req.rules, &transitions);
+    Ok(Json(NotificationDryRunResponse {
+        start_date: range.start.to_string(),
+        end_date: range.end.to_string(),
+        transitions: transitions.len(),
+        rules: outcomes
+            .into_iter()
+            .map(|o| RuleOutcomeResponse {
+                rule: o.rule,
+                fired: o.fired,
+                suppressed: o.suppressed,
+                last_fired: o.last_fired.map(|d| d.to_string()),
+            })
+            .collect(),
+        timeline: timeline
+            .into_iter()
+            .map(|f| FiringResponse {
+                rule: f.rule,
+                channel: f.channel,
+                date: f.date.to_string(),
+                from: f.from,
+                to: f.to,
+                recession_probability: probability(f.recession_probability),
+                trigger: f.trigger,
+            })
+            .collect(),
+        model_version: state.model_version(),
     }))
 }
 
-/// Health check endpoint
-async fn health(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
-    let data = state.data.read().await;
-    let validation = state.validation.read().await;
+/// Blocked refreshes, newest first
+async fn get_incidents(State(state): State<Arc<AppState>>) -> Json<IncidentsResponse> {
+    let incidents: Vec<Incident> = state.incidents.read().await.iter().rev().cloned().collect();
+    Json(IncidentsResponse { count: incidents.len(), incidents })
+}
 
-    let last_date = data.last()
-        .map(|d| d.date.to_string())
-        .unwrap_or_else(|| "N/A".to_string());
+/// Background tasks and their recent runs
+async fn get_scheduler(State(state): State<Arc<AppState>>) -> Json<SchedulerResponse> {
+    let now = chrono::Utc::now();
+    Json(SchedulerResponse {
+        checked_at: now.to_rfc3339(),
+        ready: state.ready.load(Ordering::Acquire),
+        tasks: state.tasks.list(now),
+        lanes: state.jobs.stats(),
+    })
+}
 
-    Json(HealthResponse {
-        status: "healthy".to_string(),
-        version: "1.0.0".to_string(),
-        model_version: MODEL_VERSION.to_string(),
-        data_points: data.len(),
-        last_update: last_date,
-        validation_passed: validation.as_ref().map(|v| v.passed),
+/// Cache and stored-run sizes per namespace
+async fn get_cache_usage(State(state): State<Arc<AppState>>) -> Json<CacheResponse> {
+    state.cache.run_pending_tasks().await;
+    state.mc_runs.run_pending_tasks().await;
+    let entries = state.cache.iter().map(|(key, cached)| (key.0.clone(), cached.results.len()));
+    let runs = state.mc_runs.iter().map(|(_, stored)| stored.namespace.clone());
+    Json(CacheResponse {
+        namespaces: namespace::usage(&state.namespace(), entries, runs),
+        cache_entries: state.cache.entry_count(),
+        monte_carlo_runs: state.mc_runs.entry_count(),
     })
 }
 
-/// Get latest NIV score
-async fn get_latest(State(state): State<Arc<AppState>>) -> Result<Json<LatestResponse>, StatusCode> {
-    let data = state.data.read().await;
+/// Register a candidate model in shadow mode, replacing any existing candidate
+async fn register_canary(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CanaryRequest>,
+) -> Result<(StatusCode, Json<CanaryStatus>), ApiError> {
+    let version = req.version.trim().to_string();
+    if !canary::valid_version(&version) || version == state.model_version() {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_VERSION",
+            "version must be 1-64 characters of A-Z, a-z, 0-9, '.', '_' or '-' and differ from the serving model",
+        ));
+    }
+    let mut canary = Canary::new(version, req.engine.build(&state.engine()).map_err(request_error)?);
 
-    let latest = data.last()
-        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Baseline comparison over the current inputs
+    let inputs = state.inputs.read().await.clone();
+    let engine = canary.engine.clone();
+    let job = state
+        .jobs
+        .run(Lane::Batch, move |_| Ok(engine.calculate_series(&inputs)))
+        .await
+        .map_err(|e| job_error(e, "CANARY_FAILED"))?;
+    if canary.engine.denominator_policy() == DenominatorPolicy::Reject {
+        // Smoothed flags cover every raw month in their window; warm-up months are not evaluated
+        let negative = backtest::evaluated(&job.value)
+            .iter()
+            .filter(|r| r.quality.contains(&QualityFlag::NegativeDenominator));
+        if let Some(first) = negative.clone().next() {
+            return Err(negative_denominator_error(negative.count(), first.date));
+        }
+    }
+    canary.record(canary::compare(&state.data.read().await, &job.value, Trigger::Registration), job.value);
 
-    // Interpret components
-    let interpretation = ComponentInterpretation {
-        thrust_status: interpret_thrust(latest.components.thrust),
-        efficiency_status: interpret_efficiency(latest.components.efficiency),
-        slack_status: interpret_slack(latest.components.slack),
-        drag_status: interpret_drag(latest.components.drag),
-        formula: format!(
-            "NIV = ({:.3} × {:.6}) / ({:.3} + {:.4})^1.5 = {:.2}",
-            latest.components.thrust,
-            latest.components.efficiency_squared,
-            latest.components.slack,
-            latest.components.drag,
-            latest.niv_score
-        ),
-    };
+    let status = canary.status(state.canary_refreshes);
+    tracing::info!("Registered canary {}", status.version);
+    *state.canary.write().await = Some(canary);
+    Ok((StatusCode::CREATED, Json(status)))
+}
 
-    // Compare with Fed yield curve signal
-    let niv_signal = if latest.recession_probability > 0.5 { "RECESSION RISK" } else { "EXPANSION" };
-    let yield_curve_signal = if latest.components.drag_spread > 0.0 { "INVERTED" } else { "NORMAL" };
+fn no_canary() -> ApiError {
+    api_error(StatusCode::NOT_FOUND, "NO_CANARY", "no candidate model is registered")
+}
 
-    Ok(Json(LatestResponse {
-        date: latest.date.to_string(),
-        niv_score: round2(latest.niv_score),
-        recession_probability: round2(latest.recession_probability * 100.0),
-        alert_level: latest.alert_level,
-        alert_color: latest.alert_level.color().to_string(),
-        alert_label: latest.alert_level.label().to_string(),
-        components: ComponentsResponse {
-            thrust: round4(latest.components.thrust),
-            efficiency: round4(latest.components.efficiency),
-            efficiency_squared: round6(latest.components.efficiency_squared),
-            slack: round4(latest.components.slack),
-            drag: round4(latest.components.drag),
-            drag_spread: round4(latest.components.drag_spread),
-            drag_real_rate: round4(latest.components.drag_real_rate),
-            drag_volatility: round4(latest.components.drag_volatility),
-            interpretation,
-        },
-        vs_fed: FedComparisonResponse {
-            niv_signal: niv_signal.to_string(),
-            yield_curve_signal: yield_curve_signal.to_string(),
-            agreement: (latest.recession_probability > 0.5) == (latest.components.drag_spread > 0.0),
-            niv_lead_months: 6,
-            niv_auc: MODEL_AUC,
-            fed_auc: FED_AUC,
-        },
-        model_version: MODEL_VERSION.to_string(),
-    }))
+/// Candidate vs serving comparisons
+async fn get_canary(State(state): State<Arc<AppState>>) -> Result<Json<CanaryStatus>, ApiError> {
+    let canary = state.canary.read().await;
+    let canary = canary.as_ref().ok_or_else(no_canary)?;
+    Ok(Json(canary.status(state.canary_refreshes)))
 }
 
-/// Get historical NIV data
-async fn get_history(
-    State(state): State<Arc<AppState>>,
-    Query(params): Query<HistoryQuery>,
-) -> Result<Json<HistoryResponse>, StatusCode> {
-    let data = state.data.read().await;
+/// Discard the candidate model
+async fn discard_canary(State(state): State<Arc<AppState>>) -> Result<StatusCode, ApiError> {
+    let discarded = state.canary.write().await.take().ok_or_else(no_canary)?;
+    tracing::info!("Discarded canary {}", discarded.version);
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    // Parse date filters
-    let start_date = params.start
-        .and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok());
-    let end_date = params.end
-        .and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok());
+/// Promote the candidate to serving once it has shadowed enough refreshes
+async fn promote_canary(State(state): State<Arc<AppState>>) -> Result<Json<PromotionResponse>, ApiError> {
+    let candidate = {
+        let canary = state.canary.read().await;
+        let candidate = canary.as_ref().ok_or_else(no_canary)?;
+        let refreshes = candidate.refreshes();
+        if refreshes < state.canary_refreshes {
+            return Err(api_error(
+                StatusCode::CONFLICT,
+                "CANARY_NOT_READY",
+                format!(
+                    "candidate has shadowed {} of {} required refreshes",
+                    refreshes, state.canary_refreshes
+                ),
+            ));
+        }
+        Serving { version: candidate.version.clone(), engine: candidate.engine.clone() }
+    };
 
-    // Filter data
-    let filtered: Vec<_> = data.iter()
-        .filter(|d| {
-            let after_start = start_date.map(|s| d.date >= s).unwrap_or(true);
-            let before_end = end_date.map(|e| d.date <= e).unwrap_or(true);
-            after_start && before_end
-        })
-        .take(params.limit)
-        .map(|d| HistoryDataPoint {
-            date: d.date.to_string(),
-            niv_score: round2(d.niv_score),
-            recession_probability: round2(d.recession_probability * 100.0),
-            alert_level: d.alert_level,
-            is_recession: niv::RecessionPeriods::is_recession(d.date),
-            thrust: round4(d.components.thrust),
-            efficiency: round4(d.components.efficiency),
-            slack: round4(d.components.slack),
-            drag: round4(d.components.drag),
-        })
-        .collect();
+    // The serving model stays in place unless the candidate's refresh succeeds
+    let previous = state.model_version();
+    let promoted = candidate.version.clone();
+    let data_points = refresh_as(&state, false, Some(candidate))
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "REFRESH_FAILED", e))?;
+    let mut canary = state.canary.write().await;
+    if canary.as_ref().is_some_and(|c| c.version == promoted) {
+        *canary = None;
+    }
+    drop(canary);
+    tracing::info!("Promoted {} (was {})", promoted, previous);
+    record_change(&state, ChangeTrigger::Promotion).await;
+    Ok(Json(PromotionResponse {
+        promoted,
+        previous,
+        data_points,
+        model_version: state.model_version(),
+    }))
+}
 
-    let start = filtered.first().map(|d| d.date.clone()).unwrap_or_default();
-    let end = filtered.last().map(|d| d.date.clone()).unwrap_or_default();
+/// Workspace of the presented API key
+async fn get_workspace(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+) -> Json<WorkspaceResponse> {
+    let replays = state.replays.read().await.keys().filter(|(w, _)| *w == workspace).count();
+    Json(WorkspaceResponse { workspace, replays })
+}
 
-    Ok(Json(HistoryResponse {
-        count: filtered.len(),
-        start_date: start,
-        end_date: end,
-        model_version: MODEL_VERSION.to_string(),
-        data: filtered,
-    }))
+/// Stored preferences of the presented API key's workspace
+async fn get_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+) -> Json<PreferencesResponse> {
+    let preferences = state.preferences.get(&workspace);
+    Json(PreferencesResponse { workspace, preferences })
 }
 
-/// Get current component breakdown
-async fn get_components(State(state): State<Arc<AppState>>) -> Result<Json<ComponentsResponse>, StatusCode> {
-    let data = state.data.read().await;
+/// Replace the workspace's preferences
+async fn put_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+    Json(preferences): Json<Preferences>,
+) -> Result<Json<PreferencesResponse>, ApiError> {
+    require_key(&workspace)?;
+    preferences
+        .validate()
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, "INVALID_PREFERENCES", e))?;
+    state
+        .preferences
+        .set(&workspace, Some(preferences.clone()))
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "PREFERENCES_NOT_SAVED", e))?;
+    Ok(Json(PreferencesResponse { workspace, preferences }))
+}
 
-    let latest = data.last()
-        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+/// Clear the workspace's preferences
+async fn delete_preferences(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+) -> Result<StatusCode, ApiError> {
+    require_key(&workspace)?;
+    state
+        .preferences
+        .set(&workspace, None)
+        .await
+        .map_err(|e| api_error(StatusCode::INTERNAL_SERVER_ERROR, "PREFERENCES_NOT_SAVED", e))?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    let interpretation = ComponentInterpretation {
-        thrust_status: interpret_thrust(latest.components.thrust),
-        efficiency_status: interpret_efficiency(latest.components.efficiency),
-        slack_status: interpret_slack(latest.components.slack),
-        drag_status: interpret_drag(latest.components.drag),
-        formula: format!(
-            "NIV = ({:.3} × {:.6}) / ({:.3} + {:.4})^1.5",
-            latest.components.thrust,
-            latest.components.efficiency_squared,
-            latest.components.slack,
-            latest.components.drag
+/// Preferences are stored per API key; keyless requests share `default`
+fn require_key(workspace: &Workspace) -> Result<(), ApiError> {
+    if workspace.is_keyless() {
+        return Err(api_error(StatusCode::UNAUTHORIZED, "API_KEY_REQUIRED", "preferences need an X-API-Key"));
+    }
+    Ok(())
+}
+
+/// Start replaying history as a live feed
+async fn start_replay(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+    Query(params): Query<ReplayQuery>,
+) -> Result<Json<ReplayStatus>, ApiError> {
+    let speed = replay::parse_speed(&params.speed).ok_or_else(|| {
+        api_error(
+            StatusCode::BAD_REQUEST,
+            "INVALID_SPEED",
+            format!("speed must look like '12x' (1 to {} months per minute)", replay::MAX_SPEED),
+        )
+    })?;
+    if params.webhook.is_some() && !cfg!(feature = "webhooks") {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "WEBHOOKS_DISABLED",
+            "this server was built without webhook support",
+        ));
+    }
+    let webhook = match &params.webhook {
+        Some(url) => Some(
+            replay::webhook(url)
+                .await
+                .map_err(|e| api_error(StatusCode::BAD_REQUEST, "INVALID_WEBHOOK", e))?,
         ),
+        None => None,
     };
 
-    Ok(Json(ComponentsResponse {
-        thrust: round4(latest.components.thrust),
-        efficiency: round4(latest.components.efficiency),
-        efficiency_squared: round6(latest.components.efficiency_squared),
-        slack: round4(latest.components.slack),
-        drag: round4(latest.components.drag),
-        drag_spread: round4(latest.components.drag_spread),
-        drag_real_rate: round4(latest.components.drag_real_rate),
-        drag_volatility: round4(latest.components.drag_volatility),
-        interpretation,
-    }))
-}
-
-/// Get NIV vs Fed comparison data
-async fn get_comparison(State(state): State<Arc<AppState>>) -> Result<Json<Vec<ComparisonPoint>>, StatusCode> {
     let data = state.data.read().await;
+    let range = resolve_range(&data, params.from.as_deref(), params.to.as_deref(), ["from", "to"], state.max_span_months)?;
+    let in_range = |d: NaiveDate| range.contains(d);
+    let first = data.iter().position(|r| in_range(r.date));
+    let steps: Vec<NIVResult> = data.iter().filter(|r| in_range(r.date)).cloned().collect();
+    let previous = first.and_then(|i| i.checked_sub(1)).map(|i| data[i].alert_level);
+    drop(data);
 
-    // Get last 120 months (10 years)
-    let recent: Vec<ComparisonPoint> = data.iter()
-        .rev()
-        .take(120)
-        .rev()
-        .map(|d| {
-            // Fed probability based on yield curve inversion
-            let fed_prob = if d.components.drag_spread > 0.0 {
-                // Inverted yield curve
-                0.6 + d.components.drag_spread * 50.0
-            } else {
-                // Normal yield curve
-                0.2 + d.components.drag * 2.0
-            }.clamp(0.0, 1.0);
+    if steps.is_empty() {
+        return Err(api_error(StatusCode::BAD_REQUEST, "EMPTY_RANGE", "No data in the requested range"));
+    }
 
-            ComparisonPoint {
-                date: d.date.to_string(),
-                niv_probability: round2(d.recession_probability * 100.0),
-                fed_probability: round2(fed_prob * 100.0),
-                is_recession: niv::RecessionPeriods::is_recession(d.date),
-            }
-        })
-        .collect();
+    let id = format!("{:016x}", rand::random::<u64>());
+    let handle = replay::start(
+        id.clone(),
+        steps,
+        previous,
+        speed,
+        webhook,
+        state.tasks.start(tasks::REPLAY),
+    );
+    let status = handle.status.read().await.clone();
+    let mut replays = state.replays.write().await;
+    // Evict replays that ended more than replay::RETENTION ago
+    let now = chrono::Utc::now();
+    let mut expired = Vec::new();
+    for (key, replay) in replays.iter() {
+        if replay.expired(now).await {
+            expired.push(key.clone());
+        }
+    }
+    for key in expired {
+        replays.remove(&key);
+    }
+    replays.insert((workspace.clone(), id.clone()), handle);
+    drop(replays);
 
-    Ok(Json(recent))
+    tracing::info!("Replay {} ({}) started at {}x from {}", id, workspace.0, speed, status.from);
+    Ok(Json(status))
 }
 
-#[derive(Serialize)]
-struct ComparisonPoint {
-    date: String,
-    niv_probability: f64,
-    fed_probability: f64,
-    is_recession: bool,
+/// Get replay status
+async fn get_replay(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+    Path(id): Path<String>,
+) -> Result<Json<ReplayStatus>, ApiError> {
+    let replays = state.replays.read().await;
+    let handle = replays.get(&(workspace, id.clone())).ok_or_else(|| replay_not_found(&id))?;
+    let status = handle.status.read().await.clone();
+    Ok(Json(status))
 }
 
-/// Get recession periods
-async fn get_recessions() -> Json<Vec<RecessionPeriod>> {
-    let periods: Vec<RecessionPeriod> = niv::RecessionPeriods::known_recessions()
-        .iter()
-        .map(|(start, end)| RecessionPeriod {
-            start: start.to_string(),
-            end: end.to_string(),
-            name: recession_name(*start),
-        })
-        .collect();
+/// Stream replay events as Server-Sent Events
+async fn replay_events(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    let replays = state.replays.read().await;
+    let handle = replays.get(&(workspace, id.clone())).ok_or_else(|| replay_not_found(&id))?;
+    let format = Format::current(); // Events are serialized after the request scope ends
+    let stream = BroadcastStream::new(handle.subscribe())
+        .filter_map(|msg| msg.ok())
+        .map(move |event| Event::default().event(event.name()).json_data(event.formatted(format)));
 
-    Json(periods)
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
 }
 
-#[derive(Serialize)]
-struct RecessionPeriod {
-    start: String,
-    end: String,
-    name: String,
+/// Stop a running replay
+async fn stop_replay(
+    State(state): State<Arc<AppState>>,
+    Extension(workspace): Extension<Workspace>,
+    Path(id): Path<String>,
+) -> Result<Json<ReplayStatus>, ApiError> {
+    let replays = state.replays.read().await;
+    let handle = replays.get(&(workspace, id.clone())).ok_or_else(|| replay_not_found(&id))?;
+    handle.stop().await;
+    let status = handle.status.read().await.clone();
+    Ok(Json(status))
 }
 
-/// Get validation results
-async fn get_validation(State(state): State<Arc<AppState>>) -> Json<Option<ValidationResult>> {
-    let validation = state.validation.read().await;
-    Json(validation.clone())
+fn replay_not_found(id: &str) -> ApiError {
+    api_error(StatusCode::NOT_FOUND, "REPLAY_NOT_FOUND", format!("No replay with id {}", id))
 }
 
 fn recession_name(start: NaiveDate) -> String {
@@ -504,58 +5172,9 @@ fn recession_name(start: NaiveDate) -> String {
         1990 => "Early 1990s Recession".to_string(),
         1981 | 1982 => "1981-82 Recession (Volcker)".to_string(),
         1980 => "1980 Recession".to_string(),
-        1973 | 1974 | 1975 => "1973-75 Oil Crisis Recession".to_string(),
+        1973..=1975 => "1973-75 Oil Crisis Recession".to_string(),
         1969 | 1970 => "1969-70 Recession".to_string(),
         _ => format!("{} Recession", start.year()),
     }
 }
 
-// Helper functions
-fn round2(v: f64) -> f64 {
-    (v * 100.0).round() / 100.0
-}
-
-fn round4(v: f64) -> f64 {
-    (v * 10000.0).round() / 10000.0
-}
-
-fn round6(v: f64) -> f64 {
-    (v * 1000000.0).round() / 1000000.0
-}
-
-fn interpret_thrust(v: f64) -> String {
-    match v {
-        v if v > 0.7 => "🚀 Strong expansion impulse (M2 + Investment surging)".to_string(),
-        v if v > 0.3 => "📈 Moderate growth impulse".to_string(),
-        v if v > -0.3 => "➡️ Neutral monetary/fiscal stance".to_string(),
-        v if v > -0.7 => "📉 Moderate contraction pressure".to_string(),
-        _ => "⚠️ Strong contraction pressure (tightening cycle)".to_string(),
-    }
-}
-
-fn interpret_efficiency(v: f64) -> String {
-    match v {
-        v if v > 0.18 => "💪 High productive investment (18%+ of GDP)".to_string(),
-        v if v > 0.15 => "✅ Healthy investment levels".to_string(),
-        v if v > 0.12 => "⚠️ Below-average investment".to_string(),
-        _ => "🚨 Weak investment - hollow growth risk (GFC signal)".to_string(),
-    }
-}
-
-fn interpret_slack(v: f64) -> String {
-    match v {
-        v if v > 0.30 => "🔴 High unused capacity (30%+) - recession signal".to_string(),
-        v if v > 0.22 => "🟡 Elevated slack - room to grow".to_string(),
-        v if v > 0.15 => "🟢 Normal capacity utilization".to_string(),
-        _ => "🔥 Economy running hot - overheating risk".to_string(),
-    }
-}
-
-fn interpret_drag(v: f64) -> String {
-    match v {
-        v if v > 0.03 => "🚨 CRITICAL: High friction - liquidity stress".to_string(),
-        v if v > 0.02 => "⚠️ Elevated drag - watch closely".to_string(),
-        v if v > 0.01 => "🟡 Moderate friction levels".to_string(),
-        _ => "🟢 Low friction - smooth capital flow".to_string(),
-    }
-}
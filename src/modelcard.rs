@@ -0,0 +1,310 @@
+//! Model Cards
+//!
+//! Machine-readable description of a model, generated from its engine
+//! configuration and the validation subsystem so it cannot drift from what
+//! is computed: the FRED inputs the specs read, each formula term with the
+//! engine's weights and parameters filled in, the parameter set (as in the
+//! changelog), the data and validation windows, limitations that follow from
+//! the configuration, the economy's probability calibration, and the latest
+//! backtest metrics and self-test outcome.
+
+use chrono::Months;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::calibration::{self, EconomyCalibration};
+use crate::changelog::{self, Validation};
+use crate::daterange::DateRange;
+use crate::niv::{
+    EfficiencySpec, GdpSpec, NIVEngine, NIVResult, NonFinitePolicy, ProbabilityInput, SlackSpec, ThrustScaling,
+    EXPANSION_AGE_PIVOT_MONTHS, NIV_CLAMP, PERCENTILE_PROB_MIDPOINT, PERCENTILE_PROB_SCALE, PERCENTILE_WINDOW,
+    R_D_MULTIPLIER, SMOOTH_WINDOW,
+};
+use crate::namespace::ECONOMY;
+use crate::research::LABEL_HORIZON_MONTHS;
+use crate::selftest::SelfTestReport;
+
+/// Whether the card describes the served model or the shadow candidate
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelStatus {
+    Serving,
+    Candidate,
+}
+
+/// A FRED series the model reads
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CardInput {
+    pub series: &'static str,
+    pub used_for: &'static str,
+}
+
+/// One term of the formula with the engine's values filled in
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FormulaTerm {
+    pub symbol: String,
+    pub name: String,
+    pub expression: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Windows {
+    pub data: Option<DateRange>,
+    pub validation: Option<DateRange>, // Months whose recession outcome is known
+    pub smoothing_months: usize,
+    pub percentile_months: usize,
+    pub label_horizon_months: u32,
+}
+
+/// Startup self-test outcome, in brief
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SelfTestSummary {
+    pub passed: bool,
+    pub checks: usize,
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelCard {
+    pub model_id: String,
+    pub status: ModelStatus,
+    pub economy: String,
+    pub inputs: Vec<CardInput>,
+    pub formula: Vec<FormulaTerm>,
+    pub parameters: Map<String, Value>,
+    pub windows: Windows,
+    pub limitations: Vec<String>,
+    pub validation: Validation, // In-sample, against the NBER chronology
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probability_calibration: Option<EconomyCalibration>, // Fitted on the economy's own chronology
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_test: Option<SelfTestSummary>,
+}
+
+/// Card for `engine`, validated over `results` (its computed history)
+pub fn card(
+    model_id: &str,
+    status: ModelStatus,
+    engine: &NIVEngine,
+    results: &[NIVResult],
+    self_test: Option<&SelfTestReport>,
+) -> ModelCard {
+    ModelCard {
+        model_id: model_id.to_string(),
+        status,
+        economy: ECONOMY.to_string(),
+        inputs: inputs(engine),
+        formula: formula(engine),
+        parameters: engine.parameters(),
+        windows: windows(results),
+        limitations: limitations(engine),
+        validation: changelog::validation(results),
+        probability_calibration: calibration::fit_economy(ECONOMY, results),
+        self_test: self_test.map(|report| SelfTestSummary {
+            passed: report.passed,
+            checks: report.checks.len(),
+            failed: report.checks.iter().filter(|c| !c.passed).map(|c| c.name.clone()).collect(),
+        }),
+    }
+}
+
+fn inputs(engine: &NIVEngine) -> Vec<CardInput> {
+    let input = |series, used_for| CardInput { series, used_for };
+    let mut inputs = vec![
+        input("GPDIC1", "thrust (dG) and efficiency numerator"),
+        input("M2SL", "thrust (dA)"),
+        input("FEDFUNDS", "thrust (dr), real rate and volatility drag"),
+        input("GDPC1", "efficiency denominator"),
+        input("T10Y3M", "spread drag"),
+        input(engine.inflation_spec().series(), "real rate drag (inflation)"),
+    ];
+    if engine.efficiency_spec() == EfficiencySpec::Observed {
+        inputs.push(input("G160291A027NBEA", "efficiency numerator (education)"));
+        inputs.push(input("GDPDEF", "deflates education spending"));
+    }
+    if engine.gdp_spec() == GdpSpec::Nowcast {
+        inputs.push(input("INDPRO", "monthly GDP nowcast"));
+        inputs.push(input("RRSFS", "monthly GDP nowcast"));
+    }
+    match engine.slack_spec() {
+        SlackSpec::CapacityUtilization => inputs.push(input("TCU", "slack")),
+        SlackSpec::OutputGap => inputs.push(input("GDPPOT", "slack (output gap)")),
+        SlackSpec::UnemploymentGap => {
+            inputs.push(input("UNRATE", "slack (unemployment gap)"));
+            inputs.push(input("NROU", "slack (unemployment gap)"));
+        }
+    }
+    inputs
+}
+
+fn formula(engine: &NIVEngine) -> Vec<FormulaTerm> {
+    let term = |symbol: &str, name: &str, expression: String| FormulaTerm {
+        symbol: symbol.to_string(),
+        name: name.to_string(),
+        expression,
+    };
+    let w = engine.weights();
+    let gdp = match engine.gdp_spec() {
+        GdpSpec::Reported => "GDPC1",
+        GdpSpec::Nowcast => "GDP nowcast",
+    };
+    let scale = match engine.thrust_scaling() {
+        ThrustScaling::Fixed { divisor } => format!("{}", divisor),
+        ThrustScaling::RollingStd { window } => format!("rolling std over {} months", window),
+    };
+
+    let mut terms = vec![
+        term(
+            "NIV",
+            "NIV score",
+            format!(
+                "NIV = clamp(1000 × u × P² / max(X + F + ε, ε)^η, ±{}) with η = {}, ε = {}",
+                NIV_CLAMP,
+                engine.eta(),
+                engine.epsilon()
+            ),
+        ),
+        term(
+            "u",
+            "thrust",
+            format!(
+                "u = tanh(({}*dG + {}*dA - {}*dr + {}*dM2_accel) / {})",
+                w.thrust_dg, w.thrust_da, w.thrust_dr, w.thrust_m2_accel, scale
+            ),
+        ),
+        term(
+            "P",
+            "efficiency",
+            match engine.efficiency_spec() {
+                EfficiencySpec::Proxy => format!("P = (GPDIC1 × {}) / {}", R_D_MULTIPLIER, gdp),
+                EfficiencySpec::Observed => format!("P = (GPDIC1 + education / GDPDEF × 100) / {}", gdp),
+            },
+        ),
+        term("X", "slack", engine.slack_spec().formula().to_string()),
+        term(
+            "F",
+            "drag",
+            format!(
+                "F = {}*s + {}*max(0, FEDFUNDS - {})/100 + {}*σ(FEDFUNDS, 12m)/100",
+                w.drag_spread,
+                w.drag_real_rate,
+                engine.inflation_spec().series(),
+                w.drag_volatility
+            ),
+        ),
+        term("s", "spread penalty", engine.spread_spec().formula().to_string()),
+        term(
+            "p",
+            "recession probability",
+            match engine.probability_input() {
+                ProbabilityInput::Score => "p = 1 - 1/(1 + e^(-NIV/10))".to_string(),
+                ProbabilityInput::Percentile => format!(
+                    "p = 1/(1 + e^((percentile - {})/{})) over a {}-month rolling percentile",
+                    PERCENTILE_PROB_MIDPOINT, PERCENTILE_PROB_SCALE, PERCENTILE_WINDOW
+                ),
+            },
+        ),
+    ];
+    if engine.expansion_age_weight() != 0.0 {
+        terms.push(term(
+            "p'",
+            "expansion-age adjusted probability",
+            format!(
+                "logit(p') = logit(p) + {} × (months since the last announced recession end, held through known recessions - {})/12",
+                engine.expansion_age_weight(),
+                EXPANSION_AGE_PIVOT_MONTHS
+            ),
+        ));
+    }
+    for def in engine.registry().definitions() {
+        terms.push(term(&def.name, &format!("custom {:?} term", def.role).to_lowercase(), def.expr.clone()));
+    }
+    terms
+}
+
+fn windows(results: &[NIVResult]) -> Windows {
+    let data = results.first().zip(results.last()).map(|(first, last)| DateRange { start: first.date, end: last.date });
+    let validation = data.and_then(|range| {
+        let end = range.end.checked_sub_months(Months::new(LABEL_HORIZON_MONTHS))?;
+        (end >= range.start).then_some(DateRange { start: range.start, end })
+    });
+    Windows {
+        data,
+        validation,
+        smoothing_months: SMOOTH_WINDOW,
+        percentile_months: PERCENTILE_WINDOW,
+        label_horizon_months: LABEL_HORIZON_MONTHS,
+    }
+}
+
+fn limitations(engine: &NIVEngine) -> Vec<String> {
+    let mut limitations = vec![
+        "Validation is in-sample: the parameters were chosen on the history the metrics are computed over".to_string(),
+        "Inputs are latest-vintage FRED data, not the real-time vintages available at each date".to_string(),
+        format!(
+            "Recession labels are NBER dates, announced months after the fact; the last {} months have no known outcome",
+            LABEL_HORIZON_MONTHS
+        ),
+    ];
+    if engine.gdp_spec() == GdpSpec::Reported {
+        limitations.push("GDP is quarterly and carried forward between releases, so efficiency lags within each quarter".to_string());
+    }
+    if engine.probability_input() == ProbabilityInput::Score {
+        limitations.push(
+            "The probability has no stated horizon; /api/v1/term-structure gives horizon-calibrated probabilities".to_string(),
+        );
+    }
+    if engine.nonfinite_policy() == NonFinitePolicy::CarryForward {
+        limitations.push("Non-finite months repeat the previous month's values (flagged per month)".to_string());
+    }
+    if engine.expansion_age_weight() != 0.0 {
+        limitations.push("The expansion-age weight is set by configuration, not fitted".to_string());
+    }
+    if !engine.registry().definitions().is_empty() {
+        limitations.push("Custom components are outside the validated v6 specification".to_string());
+    }
+    limitations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::InflationSpec;
+
+    #[test]
+    fn test_card_follows_engine_config() {
+        let engine = NIVEngine::new();
+        let results = engine.calculate_series(&mock::generate_mock_data(1990, 2020));
+        let card = card("NIV-v6", ModelStatus::Serving, &engine, &results, None);
+
+        assert!(card.inputs.iter().any(|i| i.series == "TCU"));
+        assert_eq!(card.formula[0].symbol, "NIV");
+        assert!(card.formula[1].expression.contains("0.7*dr"));
+        assert_eq!(card.parameters, engine.parameters());
+        let windows = card.windows;
+        assert_eq!(windows.validation.unwrap().end, windows.data.unwrap().end - Months::new(LABEL_HORIZON_MONTHS));
+        assert!(card.self_test.is_none());
+        let calibration = card.probability_calibration.unwrap();
+        assert_eq!((card.economy.as_str(), calibration.economy.as_str()), ("us", "us"));
+        assert_eq!(calibration.fitted.horizon_months, calibration::ECONOMY_HORIZON);
+    }
+
+    #[test]
+    fn test_card_reflects_alternative_specs() {
+        let engine = NIVEngine::new()
+            .with_slack_spec(SlackSpec::UnemploymentGap)
+            .with_inflation_spec(InflationSpec::Pce)
+            .with_gdp_spec(GdpSpec::Nowcast)
+            .with_expansion_age_weight(0.3);
+        let card = card("candidate", ModelStatus::Candidate, &engine, &[], None);
+        let series: Vec<&str> = card.inputs.iter().map(|i| i.series).collect();
+
+        assert!(series.contains(&"UNRATE") && series.contains(&"PCEPI") && series.contains(&"INDPRO"));
+        assert!(!series.contains(&"TCU") && !series.contains(&"CPIAUCSL"));
+        assert!(card.formula.iter().any(|t| t.symbol == "p'"));
+        assert!(!card.limitations.iter().any(|l| l.contains("carried forward")));
+        assert!(card.windows.data.is_none());
+        assert!(card.probability_calibration.is_none());
+    }
+}
@@ -0,0 +1,332 @@
+//! Scenario-Conditioned Monte Carlo
+//!
+//! Answers "what is the probability the alert reaches Critical within N months
+//! under this scenario?":
+//! 1. Project the scenario path (deterministic shocks, see `scenario`)
+//! 2. Add random-walk residuals to every input, with per-series volatility
+//!    estimated from the last 10 years of monthly changes
+//! 3. Run the engine over each simulated path and count Critical crossings
+//!
+//! Residual shocks are pseudo-random by default; `Sampling::Halton` and
+//! `Sampling::Sobol` use low-discrepancy sequences (see `qmc`), which give
+//! tighter estimates for the same number of draws.
+//!
+//! A run is fully determined by its scenario, config and input data, so
+//! `run_id` hashes those into an ID that results can be cached under.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use statrs::statistics::Statistics;
+
+use crate::budget::{CancelToken, Cancelled};
+use crate::niv::{AlertLevel, EconomicData, NIVEngine, NIVResult};
+use crate::qmc::{Sampler, Sampling, TooManyDimensions};
+use crate::scenario::{Scenario, ShockTarget};
+
+/// Months of history prepended to each path (YoY lookback + smoothing window)
+pub const CONTEXT_MONTHS: usize = 36;
+
+/// Months of history used to estimate residual volatility
+const VOLATILITY_WINDOW: usize = 120;
+
+/// Monte Carlo run parameters
+#[derive(Debug, Clone, Copy)]
+pub struct MonteCarloConfig {
+    pub horizon_months: usize,
+    pub draws: usize,
+    pub seed: u64,
+    pub sampling: Sampling,
+}
+
+/// Why a run ended without a result
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RunError {
+    Cancelled,
+    Sampling(TooManyDimensions), // The horizon needs more dimensions than the sampling supports
+}
+
+impl From<Cancelled> for RunError {
+    fn from(_: Cancelled) -> Self {
+        RunError::Cancelled
+    }
+}
+
+impl From<TooManyDimensions> for RunError {
+    fn from(e: TooManyDimensions) -> Self {
+        RunError::Sampling(e)
+    }
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::Cancelled => write!(f, "cancelled"),
+            RunError::Sampling(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Per-series residual volatility (monthly)
+/// Level series: std dev of log changes. Rate series: std dev of pp changes.
+#[derive(Debug, Clone)]
+pub struct ResidualModel {
+    sigmas: Vec<(ShockTarget, f64)>,
+}
+
+impl ResidualModel {
+    pub fn estimate(history: &[EconomicData]) -> Self {
+        let start = history.len().saturating_sub(VOLATILITY_WINDOW + 1);
+        let window = &history[start..];
+
+        let sigmas = ShockTarget::all()
+            .iter()
+            .map(|&target| {
+                let changes: Vec<f64> = window
+                    .windows(2)
+                    .filter_map(|w| {
+                        let (prev, curr) = (target.get(&w[0]), target.get(&w[1]));
+                        if target.is_level() {
+                            (prev > 0.0 && curr > 0.0).then(|| (curr / prev).ln())
+                        } else {
+                            Some(curr - prev)
+                        }
+                    })
+                    .collect();
+                let sigma = if changes.len() > 1 { changes.std_dev() } else { 0.0 };
+                (target, if sigma.is_finite() { sigma } else { 0.0 })
+            })
+            .collect();
+
+        Self { sigmas }
+    }
+
+    pub fn sigma(&self, target: ShockTarget) -> f64 {
+        self.sigmas
+            .iter()
+            .find(|(t, _)| *t == target)
+            .map(|(_, s)| *s)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Distribution summary for one projected month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthBand {
+    pub date: NaiveDate,
+    pub scenario_probability: f64, // Deterministic scenario path (no residuals)
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub prob_critical_by_month: f64, // P(Critical reached at or before this month)
+}
+
+/// Result of a scenario-conditioned Monte Carlo run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloResult {
+    pub scenario: String,
+    pub horizon_months: usize,
+    pub draws: usize,
+    pub seed: u64,
+    pub sampling: Sampling,
+    pub starting_alert_level: AlertLevel,
+    pub prob_critical_within_horizon: f64,
+    pub months: Vec<MonthBand>,
+    pub distribution: Vec<Vec<f64>>, // Every draw's probability per projected month, sorted
+}
+
+impl MonteCarloResult {
+    /// Approximate heap bytes held, dominated by the per-draw distribution
+    pub fn approx_bytes(&self) -> usize {
+        self.scenario.len()
+            + self.months.len() * std::mem::size_of::<MonthBand>()
+            + self.distribution.iter().map(|d| std::mem::size_of::<Vec<f64>>() + d.len() * 8).sum::<usize>()
+    }
+}
+
+/// ID of the run of `scenario` with `config` against dataset `revision`
+pub fn run_id(scenario: &Scenario, config: &MonteCarloConfig, revision: &str) -> String {
+    let key = serde_json::json!([scenario, config.horizon_months, config.draws, config.seed, config.sampling, revision]);
+    let digest = Sha256::digest(key.to_string().as_bytes());
+    format!("mc-{}", digest[..8].iter().map(|b| format!("{:02x}", b)).collect::<String>())
+}
+
+/// Run the engine over `history` extended by the scenario path
+/// Returns only the projected months
+pub fn project_results(engine: &NIVEngine, history: &[EconomicData], path: &[EconomicData]) -> Vec<NIVResult> {
+    let start = history.len().saturating_sub(CONTEXT_MONTHS);
+    let mut context = history[start..].to_vec();
+    context.extend_from_slice(path);
+
+    let results = engine.calculate_series(&context);
+    let skip = results.len().saturating_sub(path.len());
+    results.into_iter().skip(skip).collect()
+}
+
+/// Run the scenario-conditioned Monte Carlo, checking `cancel` between draws
+pub fn run(
+    engine: &NIVEngine,
+    history: &[EconomicData],
+    scenario: &Scenario,
+    config: MonteCarloConfig,
+    cancel: &CancelToken,
+) -> Result<MonteCarloResult, RunError> {
+    let horizon = config.horizon_months;
+    let shocked_path = scenario.project(history, horizon);
+    let scenario_results = project_results(engine, history, &shocked_path);
+
+    let residuals = ResidualModel::estimate(history);
+    let targets = ShockTarget::all();
+    let mut sampler = Sampler::new(config.sampling, targets.len(), shocked_path.len(), config.seed)?;
+
+    // probabilities[month][draw]
+    let mut probabilities: Vec<Vec<f64>> = vec![Vec::with_capacity(config.draws); horizon];
+    // first_critical[draw] = first projected month index reaching Critical
+    let mut first_critical: Vec<Option<usize>> = Vec::with_capacity(config.draws);
+
+    for _ in 0..config.draws {
+        cancel.check()?;
+        let mut path = shocked_path.clone();
+        let draw = sampler.next_draw();
+        for (t, target) in targets.into_iter().enumerate() {
+            let sigma = residuals.sigma(target);
+            let mut cumulative = 0.0;
+            for (month, point) in path.iter_mut().enumerate() {
+                cumulative += sigma * draw[sampler.index(t, month)];
+                let value = target.get(point);
+                let perturbed = if target.is_level() {
+                    value * cumulative.exp()
+                } else {
+                    value + cumulative
+                };
+                target.set(point, perturbed);
+            }
+        }
+
+        let results = project_results(engine, history, &path);
+        for (month, r) in results.iter().enumerate().take(horizon) {
+            probabilities[month].push(r.recession_probability);
+        }
+        first_critical.push(
+            results.iter().position(|r| r.alert_level == AlertLevel::Critical),
+        );
+    }
+
+    let draws = config.draws.max(1) as f64;
+    for probs in &mut probabilities {
+        probs.sort_by(|a, b| a.total_cmp(b));
+    }
+    let months = scenario_results
+        .iter()
+        .enumerate()
+        .map(|(month, r)| {
+            let probs = &probabilities[month];
+            let reached = first_critical
+                .iter()
+                .filter(|f| f.map(|m| m <= month).unwrap_or(false))
+                .count();
+            MonthBand {
+                date: r.date,
+                scenario_probability: r.recession_probability,
+                p10: percentile(probs, 0.10),
+                p50: percentile(probs, 0.50),
+                p90: percentile(probs, 0.90),
+                prob_critical_by_month: reached as f64 / draws,
+            }
+        })
+        .collect();
+
+    let prob_critical = first_critical.iter().filter(|f| f.is_some()).count() as f64 / draws;
+    let context_start = history.len().saturating_sub(CONTEXT_MONTHS);
+    let starting_alert_level = engine
+        .calculate_series(&history[context_start..])
+        .last()
+        .map(|r| r.alert_level)
+        .unwrap_or(AlertLevel::Normal);
+
+    Ok(MonteCarloResult {
+        scenario: scenario.name.clone(),
+        horizon_months: horizon,
+        draws: config.draws,
+        seed: config.seed,
+        sampling: config.sampling,
+        starting_alert_level,
+        prob_critical_within_horizon: prob_critical,
+        months,
+        distribution: probabilities,
+    })
+}
+
+/// Nearest-rank percentile of a sorted slice
+pub fn percentile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted.len() - 1) as f64 * q).round() as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::scenario::Shock;
+
+    fn config(draws: usize) -> MonteCarloConfig {
+        MonteCarloConfig { horizon_months: 12, draws, seed: 7, sampling: Sampling::Pseudo }
+    }
+
+    #[test]
+    fn test_run_is_deterministic_for_seed() {
+        let engine = NIVEngine::new();
+        let history = mock::generate_mock_data(2000, 2019);
+        let a = run(&engine, &history, &Scenario::baseline(), config(50), &CancelToken::default()).unwrap();
+        let b = run(&engine, &history, &Scenario::baseline(), config(50), &CancelToken::default()).unwrap();
+
+        assert_eq!(a.months.len(), 12);
+        assert_eq!(a.prob_critical_within_horizon, b.prob_critical_within_horizon);
+        assert_eq!(a.months[11].p50, b.months[11].p50);
+        assert_eq!(a.distribution, b.distribution);
+        assert_eq!(a.distribution[11].len(), 50);
+        assert!(a.approx_bytes() >= 12 * 50 * 8);
+
+        let id = run_id(&Scenario::baseline(), &config(50), "rev-a");
+        assert_eq!(id, run_id(&Scenario::baseline(), &config(50), "rev-a"));
+        assert_ne!(id, run_id(&Scenario::baseline(), &config(50), "rev-b"));
+        assert_ne!(id, run_id(&Scenario::baseline(), &config(51), "rev-a"));
+    }
+
+    #[test]
+    fn test_bands_are_ordered_and_cumulative() {
+        let engine = NIVEngine::new();
+        let history = mock::generate_mock_data(2000, 2019);
+        let result = run(&engine, &history, &Scenario::baseline(), config(100), &CancelToken::default()).unwrap();
+
+        let mut prev = 0.0;
+        for m in &result.months {
+            assert!(m.p10 <= m.p50 && m.p50 <= m.p90);
+            assert!(m.prob_critical_by_month >= prev);
+            prev = m.prob_critical_by_month;
+        }
+        assert!((prev - result.prob_critical_within_horizon).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_adverse_scenario_raises_critical_probability() {
+        let engine = NIVEngine::new();
+        let history = mock::generate_mock_data(2000, 2019);
+        let crunch = Scenario {
+            name: "credit crunch".to_string(),
+            shocks: vec![
+                Shock { target: ShockTarget::Investment, magnitude: -30.0, start_month: 1, duration_months: None },
+                Shock { target: ShockTarget::M2Supply, magnitude: -15.0, start_month: 1, duration_months: None },
+                Shock { target: ShockTarget::YieldSpread, magnitude: -2.0, start_month: 1, duration_months: None },
+            ],
+        };
+        let base = run(&engine, &history, &Scenario::baseline(), config(100), &CancelToken::default()).unwrap();
+        let adverse = run(&engine, &history, &crunch, config(100), &CancelToken::default()).unwrap();
+
+        assert!(adverse.prob_critical_within_horizon >= base.prob_critical_within_horizon);
+        assert!(adverse.months[11].scenario_probability > base.months[11].scenario_probability);
+    }
+}
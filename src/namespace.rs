@@ -0,0 +1,138 @@
+//! Cache Namespaces
+//!
+//! Memoized results depend on the economy, the serving model and the input
+//! dataset. Cache keys carry a `Namespace` of all three, so a result computed
+//! under one is never served under another, including work that finishes
+//! after a refresh or promotion has swapped the serving state. Stored Monte
+//! Carlo runs record their namespace and hash it into the run ID.
+//!
+//! `usage` summarises entries per namespace for the admin cache endpoint;
+//! anything outside the current namespace is unreachable and waits out its
+//! TTL.
+//!
+//! `pipeline_hash` extends the namespace with the engine parameters and the
+//! crate version into one SHA-256: equal hashes mean the same inputs went
+//! through the same configuration and code, so two parties can check they
+//! hold byte-identical output. Every response carries it in the
+//! `X-NIV-Pipeline` header, and exports and publish bundles record it.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// The economy served (the only one until multi-economy datasets land)
+pub const ECONOMY: &str = "us";
+
+/// Economy, model version and dataset revision a cached value belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct Namespace {
+    pub economy: String,
+    pub model: String,
+    pub revision: String,
+}
+
+/// Cache key: namespace plus the item within it (e.g. "smooth:12")
+pub type CacheKey = (Namespace, String);
+
+impl Namespace {
+    pub fn new(model: &str, revision: &str) -> Self {
+        Self { economy: ECONOMY.to_string(), model: model.to_string(), revision: revision.to_string() }
+    }
+
+    pub fn key(&self, item: impl Into<String>) -> CacheKey {
+        (self.clone(), item.into())
+    }
+
+    /// Hex SHA-256 over the namespace, the engine `parameters` (as in the
+    /// changelog) and the crate version
+    pub fn pipeline_hash(&self, parameters: &Map<String, Value>) -> String {
+        use sha2::{Digest, Sha256};
+        let pipeline = serde_json::json!({
+            "economy": self.economy,
+            "model": self.model,
+            "revision": self.revision,
+            "parameters": parameters,
+            "crate_version": env!("CARGO_PKG_VERSION"),
+        });
+        let digest = Sha256::digest(serde_json::to_vec(&pipeline).unwrap_or_default());
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl std::fmt::Display for Namespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}/{}", self.economy, self.model, self.revision)
+    }
+}
+
+/// Entries held for one namespace
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NamespaceUsage {
+    #[serde(flatten)]
+    pub namespace: Namespace,
+    pub current: bool,
+    pub entries: usize,
+    pub points: usize, // Results held across the entries
+    pub monte_carlo_runs: usize,
+}
+
+/// Per-namespace totals over cache entries (namespace, points) and stored
+/// runs; the current namespace comes first and is listed even when empty
+pub fn usage(
+    current: &Namespace,
+    entries: impl IntoIterator<Item = (Namespace, usize)>,
+    runs: impl IntoIterator<Item = Namespace>,
+) -> Vec<NamespaceUsage> {
+    let mut totals: BTreeMap<Namespace, NamespaceUsage> = BTreeMap::new();
+    totals.entry(current.clone()).or_insert_with(|| empty(current, current));
+    for (namespace, points) in entries {
+        let usage = totals.entry(namespace.clone()).or_insert_with(|| empty(&namespace, current));
+        usage.entries += 1;
+        usage.points += points;
+    }
+    for namespace in runs {
+        totals.entry(namespace.clone()).or_insert_with(|| empty(&namespace, current)).monte_carlo_runs += 1;
+    }
+    let mut usage: Vec<NamespaceUsage> = totals.into_values().collect();
+    usage.sort_by_key(|u| !u.current);
+    usage
+}
+
+fn empty(namespace: &Namespace, current: &Namespace) -> NamespaceUsage {
+    NamespaceUsage { namespace: namespace.clone(), current: namespace == current, entries: 0, points: 0, monte_carlo_runs: 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_differ_across_namespaces() {
+        let serving = Namespace::new("v6", "abc123");
+        assert_eq!(serving.to_string(), "us/v6/abc123");
+        assert_eq!(serving.key("smooth:12"), Namespace::new("v6", "abc123").key("smooth:12"));
+        assert_ne!(serving.key("smooth:12"), Namespace::new("v7", "abc123").key("smooth:12"));
+        assert_ne!(serving.key("smooth:12"), Namespace::new("v6", "def456").key("smooth:12"));
+
+        let parameters = |eta: f64| serde_json::json!({ "eta": eta }).as_object().cloned().unwrap();
+        let hash = serving.pipeline_hash(&parameters(1.5));
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, Namespace::new("v6", "abc123").pipeline_hash(&parameters(1.5)));
+        assert_ne!(hash, serving.pipeline_hash(&parameters(1.6)));
+        assert_ne!(hash, Namespace::new("v6", "def456").pipeline_hash(&parameters(1.5)));
+    }
+
+    #[test]
+    fn test_usage_per_namespace() {
+        let current = Namespace::new("v6", "new");
+        let old = Namespace::new("v6", "old");
+        let usage = usage(&current, [(old.clone(), 10), (old.clone(), 5)], [old.clone(), old.clone()]);
+
+        assert_eq!(usage.len(), 2);
+        assert!(usage[0].current);
+        assert_eq!((usage[0].entries, usage[0].monte_carlo_runs), (0, 0));
+        assert_eq!((usage[1].entries, usage[1].points, usage[1].monte_carlo_runs), (2, 15, 2));
+        assert!(!usage[1].current);
+    }
+}
@@ -18,17 +18,57 @@
 use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
 use statrs::statistics::Statistics;
+use std::borrow::Cow;
+use std::sync::{Arc, OnceLock};
+
+use crate::registry::{ComponentRegistry, CustomTerm, TermRole};
+use crate::transform::Pipelines;
 
 /// Global Parameters - IMMUTABLE
 pub const ETA: f64 = 1.5;           // Friction exponent (nonlinearity)
 pub const EPSILON: f64 = 0.001;     // Safety floor for division-by-zero
 pub const SMOOTH_WINDOW: usize = 12; // 12-month smoothing window
+pub const NIV_CLAMP: f64 = 100.0;   // NIV scores are clamped to ±NIV_CLAMP
+pub const MAX_SMOOTH_WINDOW: usize = 60; // Longest request-time smoothing window
 pub const R_D_MULTIPLIER: f64 = 1.15; // R&D/Education proxy for efficiency
+pub const SLACK_NEUTRAL: f64 = 0.20; // TCU-equivalent slack at a closed output gap (TCU ≈ 80%)
+pub const OKUN_COEFFICIENT: f64 = 2.0; // Output gap per point of unemployment gap
+pub const THRUST_SCALE: f64 = 10.0; // Default divisor applied to the thrust input before tanh
+pub const MIN_THRUST_SCALE: f64 = 0.1; // Floor for data-driven thrust scales
+pub const MIN_ROLLING_STD_WINDOW: usize = 2; // Shortest rolling_std thrust scaling window
+pub const MAX_ROLLING_STD_WINDOW: usize = 600; // Longest rolling_std thrust scaling window
+pub const PERCENTILE_WINDOW: usize = 240; // 20-year rolling window for percentile scores
+pub const COMPONENT_LIMIT: f64 = 1e6; // Components beyond ±COMPONENT_LIMIT are treated as non-finite
+
+/// Revision of the computation itself, for changes no parameter captures.
+/// Recorded in `NIVEngine::parameters`, so a bump writes a changelog entry
+/// and invalidates stored snapshots.
+/// - 1: the original v6 engine
+/// - 2: a negative denominator base is floored at ε (`DenominatorPolicy::Clamp`)
+///   instead of producing a non-finite score
+pub const METHOD_REVISION: u32 = 2;
+
+/// Percentile-to-probability mapping (used when ProbabilityInput::Percentile)
+/// The 25th percentile maps to 50% recession probability
+pub const PERCENTILE_PROB_MIDPOINT: f64 = 25.0;
+pub const PERCENTILE_PROB_SCALE: f64 = 7.5;
+
+/// Expansion-age conditioning: the probability logit moves by the engine's
+/// weight per year of expansion beyond the pivot (0 weight = off)
+pub const EXPANSION_AGE_PIVOT_MONTHS: u32 = 60;
+pub const MAX_EXPANSION_AGE_WEIGHT: f64 = 2.0;
+
+/// Months from a peak (trough) to its announcement where no date is on
+/// record: roughly the NBER's median lags since 1980
+pub const PEAK_ANNOUNCEMENT_LAG_MONTHS: u32 = 7;
+pub const TROUGH_ANNOUNCEMENT_LAG_MONTHS: u32 = 15;
 
 /// Thrust weights - raw growth rates fed into tanh
 pub const THRUST_DG_WEIGHT: f64 = 1.0;  // Investment growth weight
 pub const THRUST_DA_WEIGHT: f64 = 1.0;  // M2 growth weight
 pub const THRUST_DR_WEIGHT: f64 = 0.7;  // Fed funds change weight
+pub const THRUST_M2_ACCEL_WEIGHT: f64 = 0.0; // M2 acceleration weight (off in v6)
+pub const M2_ACCEL_LAG: usize = 6; // Months over which the change in dA is measured
 
 /// Drag weights
 pub const DRAG_SPREAD_WEIGHT: f64 = 0.4;    // Yield curve inversion penalty
@@ -47,6 +87,118 @@ pub struct EconomicData {
     pub capacity_util: f64,   // TCU - Total Capacity Utilization
     pub yield_spread: f64,    // T10Y3M - 10Y-3M Treasury Spread
     pub cpi_inflation: f64,   // CPIAUCSL YoY % change
+    #[serde(default)]
+    pub rd_investment: Option<f64>,      // Y694RC1Q027SBEA - Real R&D investment
+    #[serde(default)]
+    pub education_spending: Option<f64>, // G160291A027NBEA deflated by GDPDEF - Real government education expenditures
+    #[serde(default)]
+    pub potential_gdp: Option<f64>,      // GDPPOT - CBO Real Potential GDP
+    #[serde(default)]
+    pub unemployment_rate: Option<f64>,  // UNRATE - Unemployment Rate
+    #[serde(default)]
+    pub nairu: Option<f64>,              // NROU - CBO Noncyclical Rate of Unemployment
+    #[serde(default)]
+    pub gdp_nowcast: Option<f64>,        // Monthly GDP proxy after the latest GDPC1 release (see nowcast.rs)
+    #[serde(default)]
+    pub core_cpi_inflation: Option<f64>, // CPILFESL YoY % change
+    #[serde(default)]
+    pub pce_inflation: Option<f64>,      // PCEPI YoY % change
+    #[serde(default)]
+    pub expected_inflation: Option<f64>, // T5YIE - 5-Year Breakeven Inflation Rate
+    // Required series estimated for this month rather than observed (see coverage.rs)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub estimated: Vec<InputSeries>,
+}
+
+/// A required input series, as named in coverage flags
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum InputSeries {
+    Investment,
+    M2Supply,
+    FedFundsRate,
+    Gdp,
+    CapacityUtil,
+    YieldSpread,
+    CpiInflation,
+}
+
+impl InputSeries {
+    /// In `EconomicData::required_mut` order
+    pub const ALL: [InputSeries; 7] = [
+        InputSeries::Investment,
+        InputSeries::M2Supply,
+        InputSeries::FedFundsRate,
+        InputSeries::Gdp,
+        InputSeries::CapacityUtil,
+        InputSeries::YieldSpread,
+        InputSeries::CpiInflation,
+    ];
+}
+
+impl EconomicData {
+    /// Required series in `InputSeries::ALL` order
+    pub fn required_mut(&mut self) -> [&mut f64; 7] {
+        [
+            &mut self.investment, &mut self.m2_supply, &mut self.fed_funds_rate, &mut self.gdp,
+            &mut self.capacity_util, &mut self.yield_spread, &mut self.cpi_inflation,
+        ]
+    }
+
+    fn optional_mut(&mut self) -> [&mut Option<f64>; 9] {
+        [
+            &mut self.rd_investment, &mut self.education_spending, &mut self.potential_gdp,
+            &mut self.unemployment_rate, &mut self.nairu, &mut self.gdp_nowcast,
+            &mut self.core_cpi_inflation, &mut self.pce_inflation, &mut self.expected_inflation,
+        ]
+    }
+
+    /// Whether every present series value is finite
+    pub fn is_finite(&self) -> bool {
+        [
+            self.investment, self.m2_supply, self.fed_funds_rate, self.gdp,
+            self.capacity_util, self.yield_spread, self.cpi_inflation,
+        ]
+        .iter()
+        .all(|v| v.is_finite())
+            && [
+                self.rd_investment, self.education_spending, self.potential_gdp,
+                self.unemployment_rate, self.nairu, self.gdp_nowcast,
+                self.core_cpi_inflation, self.pce_inflation, self.expected_inflation,
+            ]
+            .iter()
+            .flatten()
+            .all(|v| v.is_finite())
+    }
+}
+
+/// Copy of `data` with non-finite values replaced: required series carry the
+/// last finite value forward (zero before any), optional series become
+/// missing so their fallbacks apply. Also returns which months were touched.
+pub fn sanitize_inputs(data: &[EconomicData]) -> (Cow<'_, [EconomicData]>, Vec<bool>) {
+    if data.iter().all(EconomicData::is_finite) {
+        return (Cow::Borrowed(data), vec![false; data.len()]);
+    }
+    let mut clean = data.to_vec();
+    let mut replaced = vec![false; data.len()];
+    let mut last = [0.0; 7];
+    for (d, hit) in clean.iter_mut().zip(replaced.iter_mut()) {
+        for (v, last) in d.required_mut().into_iter().zip(last.iter_mut()) {
+            if v.is_finite() {
+                *last = *v;
+            } else {
+                *v = *last;
+                *hit = true;
+            }
+        }
+        for v in d.optional_mut() {
+            if v.is_some_and(|x| !x.is_finite()) {
+                *v = None;
+                *hit = true;
+            }
+        }
+    }
+    (Cow::Owned(clean), replaced)
 }
 
 /// Extended economic data with growth rates calculated
@@ -56,7 +208,12 @@ pub struct ExtendedEconomicData {
     pub dg: f64,              // Monthly % change in Investment (GPDIC1)
     pub da: f64,              // 12-month % change in M2 (M2SL) - Critical: detected 2020 crash
     pub dr: f64,              // Monthly change in Fed Funds Rate
+    pub m2_accel: f64,        // Change in dA over M2_ACCEL_LAG months (pp) - negative = money growth decelerating
     pub sigma_r: f64,         // 12-month rolling std dev of Fed Funds - handles 2022 volatility
+    pub spread_change: f64,   // 12-month change in T10Y3M (percentage points)
+    pub thrust_scale: f64,    // Divisor applied to the thrust input before tanh
+    pub custom: Vec<CustomTerm>, // Registry terms for this month
+    pub quality: Vec<QualityFlag>, // Inputs or terms replaced for being non-finite
 }
 
 /// Computed NIV components
@@ -68,9 +225,27 @@ pub struct NIVComponents {
     pub slack: f64,           // X - 1 - (TCU/100)
     pub drag: f64,            // F - 0.4*spread + 0.4*real_rate + 0.2*volatility
     // Drag subcomponents for transparency
-    pub drag_spread: f64,     // s_t - Inversion penalty
+    pub drag_spread: f64,     // s_t - Spread penalty (inversion by default, see SpreadSpec)
     pub drag_real_rate: f64,  // r_t - π_t - Real rate component
     pub drag_volatility: f64, // σ_r - Fed Funds volatility
+    // Provenance: the efficiency denominator used the monthly GDP nowcast
+    #[serde(default)]
+    pub gdp_nowcast: bool,
+    // Registry-defined terms (see registry.rs)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<CustomTerm>,
+}
+
+impl NIVComponents {
+    /// Headline value of one component
+    pub fn value(&self, component: Component) -> f64 {
+        match component {
+            Component::Thrust => self.thrust,
+            Component::Efficiency => self.efficiency,
+            Component::Slack => self.slack,
+            Component::Drag => self.drag,
+        }
+    }
 }
 
 /// Full NIV result for a single period
@@ -81,6 +256,351 @@ pub struct NIVResult {
     pub recession_probability: f64,
     pub components: NIVComponents,
     pub alert_level: AlertLevel,
+    #[serde(default)]
+    pub niv_percentile: f64,  // Rolling historical percentile of niv_score (0-100)
+    // Non-finite values replaced this month (any month in the window once smoothed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quality: Vec<QualityFlag>,
+    // Required inputs estimated this month (any month in the window once smoothed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub coverage: Vec<InputSeries>,
+    // Before the smoothing window fills: the month is passed through unsmoothed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub warmup: bool,
+}
+
+/// A value the formula could not take as-is: a non-finite value (NaN, ±Inf,
+/// or a component or registry term beyond ±COMPONENT_LIMIT, which would
+/// overflow smoothing) that was replaced rather than propagated, or a negative
+/// denominator base handled under the DenominatorPolicy
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QualityFlag {
+    #[serde(rename = "non_finite_input")]
+    Input, // A raw input series; last finite value carried forward
+    #[serde(rename = "non_finite_term")]
+    Term, // A registry term; treated as unavailable
+    #[serde(rename = "non_finite_thrust")]
+    Thrust,
+    #[serde(rename = "non_finite_efficiency")]
+    Efficiency,
+    #[serde(rename = "non_finite_slack")]
+    Slack,
+    #[serde(rename = "non_finite_drag")]
+    Drag, // The drag or any of its subcomponents
+    #[serde(rename = "non_finite_score")]
+    Score,
+    #[serde(rename = "negative_denominator")]
+    NegativeDenominator, // X + F + ε (+ registry terms) below zero
+}
+
+impl QualityFlag {
+    pub const ALL: [QualityFlag; 8] = [
+        QualityFlag::Input,
+        QualityFlag::Term,
+        QualityFlag::Thrust,
+        QualityFlag::Efficiency,
+        QualityFlag::Slack,
+        QualityFlag::Drag,
+        QualityFlag::Score,
+        QualityFlag::NegativeDenominator,
+    ];
+}
+
+fn in_range(value: f64) -> bool {
+    value.abs() <= COMPONENT_LIMIT // False for NaN
+}
+
+fn flag(quality: &mut Vec<QualityFlag>, flag: QualityFlag) {
+    if !quality.contains(&flag) {
+        quality.push(flag);
+    }
+}
+
+/// What replaces a component or score that computes to NaN/±Inf (or beyond
+/// ±COMPONENT_LIMIT)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NonFinitePolicy {
+    /// Clamped to ±COMPONENT_LIMIT (±NIV_CLAMP for the score); NaN → 0
+    #[default]
+    Clamp,
+    /// The previous month's value (zero in the first month)
+    CarryForward,
+}
+
+impl NonFinitePolicy {
+    /// Replacement for a non-finite `value`
+    pub fn replace(&self, value: f64, previous: Option<f64>, limit: f64) -> f64 {
+        match self {
+            NonFinitePolicy::Clamp if value.is_nan() => 0.0,
+            NonFinitePolicy::Clamp => value.clamp(-limit, limit),
+            NonFinitePolicy::CarryForward => previous.unwrap_or(0.0),
+        }
+    }
+}
+
+/// How the master formula treats a negative denominator base, for which
+/// (X + F)^η is undefined at fractional η. Signed spread specs, negative
+/// drag weights and denominator registry terms can all produce one.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DenominatorPolicy {
+    /// Floor the base at ε (v6 default): the month reads as maximally tight
+    #[default]
+    Clamp,
+    /// Odd extension -|base|^η: the score changes sign with the base
+    Reflect,
+    /// Computed as Clamp, but simulations and canary registrations whose
+    /// range contains a negative base are refused
+    Reject,
+}
+
+/// What `smooth` does with the first `window - 1` months, which have too
+/// little history to average and pass through unsmoothed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmupPolicy {
+    /// Kept and marked `warmup`; backtests skip them
+    #[default]
+    Flag,
+    /// Dropped: smoothed series start once the window is full
+    Trim,
+}
+
+impl std::str::FromStr for WarmupPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "flag" => Ok(WarmupPolicy::Flag),
+            "trim" => Ok(WarmupPolicy::Trim),
+            other => Err(format!("unknown warm-up policy '{}'", other)),
+        }
+    }
+}
+
+impl std::str::FromStr for NonFinitePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "clamp" => Ok(NonFinitePolicy::Clamp),
+            "carry_forward" | "carry" => Ok(NonFinitePolicy::CarryForward),
+            other => Err(format!("unknown non-finite policy '{}'", other)),
+        }
+    }
+}
+
+/// Inputs with their raw and smoothed results, as served
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    pub inputs: Vec<EconomicData>,
+    pub raw: Vec<NIVResult>,
+    pub smoothed: Vec<NIVResult>,
+}
+
+/// Which score read endpoints report
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreMode {
+    #[default]
+    Raw,
+    Percentile,
+}
+
+/// How the thrust input is scaled before tanh
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ThrustScaling {
+    /// Constant divisor (v6 default: 10)
+    Fixed { divisor: f64 },
+    /// Trailing standard deviation of the weighted thrust input, so the
+    /// tanh argument is a volatility-adjusted z-like value regardless of weights
+    RollingStd { window: usize },
+}
+
+impl Default for ThrustScaling {
+    fn default() -> Self {
+        ThrustScaling::Fixed { divisor: THRUST_SCALE }
+    }
+}
+
+impl ThrustScaling {
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            ThrustScaling::Fixed { divisor } => divisor.is_finite() && divisor > 0.0,
+            ThrustScaling::RollingStd { window } => (MIN_ROLLING_STD_WINDOW..=MAX_ROLLING_STD_WINDOW).contains(&window),
+        }
+    }
+}
+
+/// How the efficiency numerator accounts for R&D and education
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EfficiencySpec {
+    /// Investment × R_D_MULTIPLIER (v6 default)
+    #[default]
+    Proxy,
+    /// Investment + real education spending; falls back to the multiplier
+    /// for months without it. R&D is not added: GPDIC1 already includes it
+    Observed,
+}
+
+/// Which GDP series forms the efficiency denominator
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GdpSpec {
+    /// Latest quarterly GDPC1 release, carried forward (v6 default)
+    #[default]
+    Reported,
+    /// Monthly nowcast between quarterly releases; reported GDP otherwise
+    Nowcast,
+}
+
+impl std::str::FromStr for GdpSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "reported" | "gdpc1" => Ok(GdpSpec::Reported),
+            "nowcast" => Ok(GdpSpec::Nowcast),
+            other => Err(format!("unknown GDP spec '{}'", other)),
+        }
+    }
+}
+
+/// Which inflation measure deflates the Fed Funds rate in the real-rate drag
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InflationSpec {
+    /// Headline CPI YoY (v6 default)
+    #[default]
+    Cpi,
+    /// Core CPI (ex food and energy) YoY
+    CoreCpi,
+    /// PCE price index YoY
+    Pce,
+    /// 5-year breakeven inflation (ex-ante real rate)
+    Expected,
+}
+
+impl InflationSpec {
+    /// FRED series behind the measure
+    pub fn series(&self) -> &'static str {
+        match self {
+            InflationSpec::Cpi => "CPIAUCSL",
+            InflationSpec::CoreCpi => "CPILFESL",
+            InflationSpec::Pce => "PCEPI",
+            InflationSpec::Expected => "T5YIE",
+        }
+    }
+}
+
+impl std::str::FromStr for InflationSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "cpi" | "cpiaucsl" => Ok(InflationSpec::Cpi),
+            "core_cpi" | "cpilfesl" => Ok(InflationSpec::CoreCpi),
+            "pce" | "pcepi" => Ok(InflationSpec::Pce),
+            "expected" | "breakeven" | "t5yie" => Ok(InflationSpec::Expected),
+            other => Err(format!("unknown inflation spec '{}'", other)),
+        }
+    }
+}
+
+/// How the term spread enters the drag
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpreadSpec {
+    /// max(0, -T10Y3M): only an inverted curve creates drag (v6 default)
+    #[default]
+    Inversion,
+    /// -T10Y3M: continuous in the level, so flattening adds drag before inversion
+    Level,
+    /// -(T10Y3M - T10Y3M 12 months ago): drag from flattening, relief from steepening
+    #[serde(rename = "change_12m")]
+    Change12m,
+}
+
+impl SpreadSpec {
+    /// Human-readable formula for model metadata
+    pub fn formula(&self) -> &'static str {
+        match self {
+            SpreadSpec::Inversion => "s = max(0, -T10Y3M)/100",
+            SpreadSpec::Level => "s = -T10Y3M/100",
+            SpreadSpec::Change12m => "s = -(T10Y3M - T10Y3M[t-12])/100",
+        }
+    }
+
+    /// Spread penalty for a month, given the spread and its 12-month change
+    pub fn penalty(&self, spread: f64, change_12m: f64) -> f64 {
+        match self {
+            SpreadSpec::Inversion => spread.min(0.0).abs() / 100.0,
+            SpreadSpec::Level => -spread / 100.0,
+            SpreadSpec::Change12m => -change_12m / 100.0,
+        }
+    }
+}
+
+impl std::str::FromStr for SpreadSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "inversion" => Ok(SpreadSpec::Inversion),
+            "level" => Ok(SpreadSpec::Level),
+            "change_12m" | "change" => Ok(SpreadSpec::Change12m),
+            other => Err(format!("unknown spread spec '{}'", other)),
+        }
+    }
+}
+
+/// Which measure of economic headroom feeds the slack term
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SlackSpec {
+    /// 1 - TCU/100 (v6 default; manufacturing-weighted)
+    #[default]
+    CapacityUtilization,
+    /// SLACK_NEUTRAL + (GDPPOT - GDP) / GDPPOT
+    OutputGap,
+    /// SLACK_NEUTRAL + OKUN_COEFFICIENT × (UNRATE - NROU) / 100
+    UnemploymentGap,
+}
+
+impl SlackSpec {
+    /// Human-readable formula for model metadata
+    pub fn formula(&self) -> &'static str {
+        match self {
+            SlackSpec::CapacityUtilization => "X = 1 - (TCU/100)",
+            SlackSpec::OutputGap => "X = 0.20 + (GDPPOT - GDP)/GDPPOT",
+            SlackSpec::UnemploymentGap => "X = 0.20 + 2.0*(UNRATE - NROU)/100",
+        }
+    }
+}
+
+impl std::str::FromStr for SlackSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "capacity_utilization" | "tcu" => Ok(SlackSpec::CapacityUtilization),
+            "output_gap" | "gdppot" => Ok(SlackSpec::OutputGap),
+            "unemployment_gap" | "nairu" => Ok(SlackSpec::UnemploymentGap),
+            other => Err(format!("unknown slack spec '{}'", other)),
+        }
+    }
+}
+
+/// Input to the recession probability model
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProbabilityInput {
+    #[default]
+    Score,      // sigmoid of raw NIV
+    Percentile, // sigmoid of the rolling NIV percentile - robust to efficiency² scale
 }
 
 /// Alert levels based on recession probability
@@ -103,6 +623,16 @@ impl AlertLevel {
         }
     }
 
+    /// Lowest recession probability at which this level applies
+    pub fn threshold(&self) -> f64 {
+        match self {
+            AlertLevel::Normal => 0.0,
+            AlertLevel::Elevated => 0.30,
+            AlertLevel::Warning => 0.50,
+            AlertLevel::Critical => 0.70,
+        }
+    }
+
     pub fn color(&self) -> &'static str {
         match self {
             AlertLevel::Normal => "#22c55e",   // Green
@@ -122,10 +652,108 @@ impl AlertLevel {
     }
 }
 
+/// Component which drove an alert transition
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Component {
+    Thrust,
+    Efficiency,
+    Slack,
+    Drag,
+}
+
+impl Component {
+    pub fn all() -> [Component; 4] {
+        [Component::Thrust, Component::Efficiency, Component::Slack, Component::Drag]
+    }
+
+    /// Copy this component's value(s) from `from` into `to`
+    pub fn copy(&self, from: &NIVComponents, to: &mut NIVComponents) {
+        match self {
+            Component::Thrust => to.thrust = from.thrust,
+            Component::Efficiency => {
+                to.efficiency = from.efficiency;
+                to.efficiency_squared = from.efficiency_squared;
+            }
+            Component::Slack => to.slack = from.slack,
+            Component::Drag => to.drag = from.drag,
+        }
+    }
+}
+
+/// Alert level change between consecutive periods
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertTransition {
+    pub date: NaiveDate,
+    pub from: AlertLevel,
+    pub to: AlertLevel,
+    pub recession_probability: f64,
+    pub trigger: Component,
+    pub trigger_delta: f64, // NIV change attributable to the trigger component
+}
+
+/// Thrust and drag weights (defaults are the OOS-validated constants)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComponentWeights {
+    pub thrust_dg: f64,
+    pub thrust_da: f64,
+    pub thrust_dr: f64,
+    pub thrust_m2_accel: f64,
+    pub drag_spread: f64,
+    pub drag_real_rate: f64,
+    pub drag_volatility: f64,
+}
+
+impl Default for ComponentWeights {
+    fn default() -> Self {
+        Self {
+            thrust_dg: THRUST_DG_WEIGHT,
+            thrust_da: THRUST_DA_WEIGHT,
+            thrust_dr: THRUST_DR_WEIGHT,
+            thrust_m2_accel: THRUST_M2_ACCEL_WEIGHT,
+            drag_spread: DRAG_SPREAD_WEIGHT,
+            drag_real_rate: DRAG_REAL_RATE_WEIGHT,
+            drag_volatility: DRAG_VOLATILITY_WEIGHT,
+        }
+    }
+}
+
+impl ComponentWeights {
+    pub fn is_finite(&self) -> bool {
+        [
+            self.thrust_dg,
+            self.thrust_da,
+            self.thrust_dr,
+            self.thrust_m2_accel,
+            self.drag_spread,
+            self.drag_real_rate,
+            self.drag_volatility,
+        ]
+        .iter()
+        .all(|w| w.is_finite())
+    }
+}
+
 /// NIV Calculation Engine v6 - Production Grade
+#[derive(Clone)]
 pub struct NIVEngine {
     eta: f64,
     epsilon: f64,
+    weights: ComponentWeights,
+    probability_input: ProbabilityInput,
+    thrust_scaling: ThrustScaling,
+    efficiency_spec: EfficiencySpec,
+    gdp_spec: GdpSpec,
+    inflation_spec: InflationSpec,
+    spread_spec: SpreadSpec,
+    slack_spec: SlackSpec,
+    nonfinite_policy: NonFinitePolicy,
+    denominator_policy: DenominatorPolicy,
+    warmup_policy: WarmupPolicy,
+    expansion_age_weight: f64,
+    registry: Arc<ComponentRegistry>,
+    pipelines: Arc<Pipelines>,
 }
 
 impl NIVEngine {
@@ -133,16 +761,222 @@ impl NIVEngine {
         Self {
             eta: ETA,
             epsilon: EPSILON,
+            weights: ComponentWeights::default(),
+            probability_input: ProbabilityInput::default(),
+            thrust_scaling: ThrustScaling::default(),
+            efficiency_spec: EfficiencySpec::default(),
+            gdp_spec: GdpSpec::default(),
+            inflation_spec: InflationSpec::default(),
+            spread_spec: SpreadSpec::default(),
+            slack_spec: SlackSpec::default(),
+            nonfinite_policy: NonFinitePolicy::default(),
+            denominator_policy: DenominatorPolicy::default(),
+            warmup_policy: WarmupPolicy::default(),
+            expansion_age_weight: 0.0,
+            registry: Arc::default(),
+            pipelines: Arc::default(),
         }
     }
 
     pub fn with_params(eta: f64, epsilon: f64) -> Self {
-        Self { eta, epsilon }
+        Self {
+            eta,
+            epsilon,
+            weights: ComponentWeights::default(),
+            probability_input: ProbabilityInput::default(),
+            thrust_scaling: ThrustScaling::default(),
+            efficiency_spec: EfficiencySpec::default(),
+            gdp_spec: GdpSpec::default(),
+            inflation_spec: InflationSpec::default(),
+            spread_spec: SpreadSpec::default(),
+            slack_spec: SlackSpec::default(),
+            nonfinite_policy: NonFinitePolicy::default(),
+            denominator_policy: DenominatorPolicy::default(),
+            warmup_policy: WarmupPolicy::default(),
+            expansion_age_weight: 0.0,
+            registry: Arc::default(),
+            pipelines: Arc::default(),
+        }
+    }
+
+    pub fn with_weights(mut self, weights: ComponentWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_probability_input(mut self, input: ProbabilityInput) -> Self {
+        self.probability_input = input;
+        self
+    }
+
+    pub fn with_thrust_scaling(mut self, scaling: ThrustScaling) -> Self {
+        self.thrust_scaling = scaling;
+        self
+    }
+
+    pub fn thrust_scaling(&self) -> ThrustScaling {
+        self.thrust_scaling
+    }
+
+    pub fn with_efficiency_spec(mut self, spec: EfficiencySpec) -> Self {
+        self.efficiency_spec = spec;
+        self
+    }
+
+    pub fn efficiency_spec(&self) -> EfficiencySpec {
+        self.efficiency_spec
+    }
+
+    pub fn with_gdp_spec(mut self, spec: GdpSpec) -> Self {
+        self.gdp_spec = spec;
+        self
+    }
+
+    pub fn gdp_spec(&self) -> GdpSpec {
+        self.gdp_spec
+    }
+
+    pub fn with_inflation_spec(mut self, spec: InflationSpec) -> Self {
+        self.inflation_spec = spec;
+        self
+    }
+
+    pub fn inflation_spec(&self) -> InflationSpec {
+        self.inflation_spec
+    }
+
+    pub fn with_spread_spec(mut self, spec: SpreadSpec) -> Self {
+        self.spread_spec = spec;
+        self
+    }
+
+    pub fn spread_spec(&self) -> SpreadSpec {
+        self.spread_spec
+    }
+
+    pub fn with_slack_spec(mut self, spec: SlackSpec) -> Self {
+        self.slack_spec = spec;
+        self
+    }
+
+    pub fn slack_spec(&self) -> SlackSpec {
+        self.slack_spec
+    }
+
+    pub fn with_nonfinite_policy(mut self, policy: NonFinitePolicy) -> Self {
+        self.nonfinite_policy = policy;
+        self
+    }
+
+    pub fn nonfinite_policy(&self) -> NonFinitePolicy {
+        self.nonfinite_policy
+    }
+
+    pub fn with_denominator_policy(mut self, policy: DenominatorPolicy) -> Self {
+        self.denominator_policy = policy;
+        self
+    }
+
+    pub fn denominator_policy(&self) -> DenominatorPolicy {
+        self.denominator_policy
+    }
+
+    pub fn with_warmup_policy(mut self, policy: WarmupPolicy) -> Self {
+        self.warmup_policy = policy;
+        self
+    }
+
+    pub fn warmup_policy(&self) -> WarmupPolicy {
+        self.warmup_policy
+    }
+
+    /// Logit shift per year of expansion age beyond EXPANSION_AGE_PIVOT_MONTHS
+    pub fn with_expansion_age_weight(mut self, weight: f64) -> Self {
+        self.expansion_age_weight = weight;
+        self
+    }
+
+    pub fn expansion_age_weight(&self) -> f64 {
+        self.expansion_age_weight
+    }
+
+    /// `probability` for `date` with the expansion-age term applied
+    pub fn expansion_adjusted(&self, probability: f64, date: NaiveDate) -> f64 {
+        expansion_age_adjusted(probability, expansion_age(date, &RecessionPeriods::announced()), self.expansion_age_weight)
+    }
+
+    pub fn with_registry(mut self, registry: Arc<ComponentRegistry>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    pub fn registry(&self) -> &Arc<ComponentRegistry> {
+        &self.registry
+    }
+
+    /// Transformations deriving dG, dA, dr and ΔS (see transform.rs)
+    pub fn with_pipelines(mut self, pipelines: Arc<Pipelines>) -> Self {
+        self.pipelines = pipelines;
+        self
+    }
+
+    pub fn pipelines(&self) -> &Arc<Pipelines> {
+        &self.pipelines
+    }
+
+    /// Every specification setting as a JSON object; the changelog, model
+    /// card, pipeline hash and snapshot fingerprint are all derived from it
+    pub fn parameters(&self) -> serde_json::Map<String, serde_json::Value> {
+        let value = serde_json::json!({
+            "method_revision": METHOD_REVISION,
+            "eta": self.eta(),
+            "epsilon": self.epsilon(),
+            "weights": self.weights(),
+            "probability_input": self.probability_input(),
+            "thrust_scaling": self.thrust_scaling(),
+            "efficiency": self.efficiency_spec(),
+            "gdp": self.gdp_spec(),
+            "inflation": self.inflation_spec(),
+            "spread": self.spread_spec(),
+            "slack": self.slack_spec(),
+            "nonfinite": self.nonfinite_policy(),
+            "denominator": self.denominator_policy(),
+            "warmup": self.warmup_policy(),
+            "expansion_age_weight": self.expansion_age_weight(),
+            "components": self.registry().definitions(),
+            "pipelines": self.pipelines().as_ref(),
+        });
+        match value {
+            serde_json::Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        }
+    }
+
+    pub fn probability_input(&self) -> ProbabilityInput {
+        self.probability_input
+    }
+
+    pub fn eta(&self) -> f64 {
+        self.eta
+    }
+
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    pub fn weights(&self) -> ComponentWeights {
+        self.weights
     }
 
     /// Calculate NIV for a time series with proper growth rate calculations
     /// This is the main entry point for production use
     pub fn calculate_series(&self, data: &[EconomicData]) -> Vec<NIVResult> {
+        let raw_results = self.calculate_raw(data);
+        self.smooth(&raw_results, SMOOTH_WINDOW)
+    }
+
+    /// Unsmoothed per-month results (growth rates, components, probability)
+    pub fn calculate_raw(&self, data: &[EconomicData]) -> Vec<NIVResult> {
         if data.len() < 13 {
             tracing::warn!("Need at least 13 months of data for YoY calculations");
             return Vec::new();
@@ -151,40 +985,88 @@ impl NIVEngine {
         // First pass: Calculate growth rates and volatility
         let extended = self.compute_extended_data(data);
 
-        // Second pass: Calculate raw NIV components
-        let raw_results: Vec<NIVResult> = extended.iter()
-            .map(|d| self.calculate_single(d))
-            .collect();
+        // Second pass: Calculate raw NIV components (in order: CarryForward
+        // replaces non-finite values with the previous month's)
+        let mut raw_results: Vec<NIVResult> = Vec::with_capacity(extended.len());
+        for d in &extended {
+            let result = self.calculate_single(d, raw_results.last());
+            raw_results.push(result);
+        }
 
-        // Third pass: Apply 12-month smoothing
-        self.apply_smoothing(&raw_results)
+        if self.probability_input == ProbabilityInput::Percentile {
+            let scores: Vec<f64> = raw_results.iter().map(|r| r.niv_score).collect();
+            for (r, pct) in raw_results.iter_mut().zip(rolling_percentile(&scores, PERCENTILE_WINDOW)) {
+                r.recession_probability = percentile_probability(pct);
+                r.alert_level = AlertLevel::from_probability(r.recession_probability);
+            }
+        }
+
+        if self.expansion_age_weight != 0.0 {
+            let recessions = RecessionPeriods::announced();
+            for r in raw_results.iter_mut() {
+                let age = expansion_age(r.date, &recessions);
+                r.recession_probability = expansion_age_adjusted(r.recession_probability, age, self.expansion_age_weight);
+                r.alert_level = AlertLevel::from_probability(r.recession_probability);
+            }
+        }
+
+        raw_results
     }
 
-    /// Compute extended data with growth rates
-    fn compute_extended_data(&self, data: &[EconomicData]) -> Vec<ExtendedEconomicData> {
-        let mut extended = Vec::with_capacity(data.len() - 12);
+    /// Smooth raw results over `window` months and attach rolling percentiles;
+    /// the warm-up months are marked or dropped per the warm-up policy
+    pub fn smooth(&self, raw_results: &[NIVResult], window: usize) -> Vec<NIVResult> {
+        // Third pass: Apply rolling smoothing (12 months by default)
+        let mut smoothed = self.apply_smoothing(raw_results, window);
+        for r in smoothed.iter_mut().take(window.saturating_sub(1)) {
+            r.warmup = true;
+        }
+
+        // Fourth pass: Percentile of the smoothed score within its trailing history
+        let scores: Vec<f64> = smoothed.iter().map(|r| r.niv_score).collect();
+        for (r, pct) in smoothed.iter_mut().zip(rolling_percentile(&scores, PERCENTILE_WINDOW)) {
+            r.niv_percentile = pct;
+        }
+
+        if self.warmup_policy == WarmupPolicy::Trim {
+            smoothed.retain(|r| !r.warmup);
+        }
+        smoothed
+    }
+
+    /// Compute extended data with growth rates (the configured pipelines)
+    /// Non-finite inputs and registry terms are replaced and flagged; the
+    /// first 12 months only seed growth rates, so shorter input yields none
+    pub fn compute_extended_data(&self, data: &[EconomicData]) -> Vec<ExtendedEconomicData> {
+        let (data, replaced) = sanitize_inputs(data);
+        let data = &*data;
+        let derived = self.pipelines.derive(data);
+        let mut extended = Vec::with_capacity(data.len().saturating_sub(12));
+        let mut custom = if self.registry.is_empty() {
+            Vec::new()
+        } else {
+            self.registry.evaluate(data)
+        };
 
         for i in 12..data.len() {
             let current = &data[i];
-            let prev_month = &data[i - 1];
-            let year_ago = &data[i - 12];
 
-            // dG: Monthly % change in Real Private Investment (GPDIC1)
-            let dg = if prev_month.investment > 0.0 {
-                ((current.investment - prev_month.investment) / prev_month.investment) * 100.0
-            } else {
-                0.0
-            };
+            // dG: Monthly % change in Real Private Investment (GPDIC1) by default
+            let dg = derived.dg[i].unwrap_or(0.0);
 
-            // dA: 12-month % change in M2 Money Supply - CRITICAL: detected 2020 crash
-            let da = if year_ago.m2_supply > 0.0 {
-                ((current.m2_supply - year_ago.m2_supply) / year_ago.m2_supply) * 100.0
-            } else {
-                0.0
-            };
+            // dA: 12-month % change in M2 Money Supply by default - CRITICAL: detected 2020 crash
+            let da = derived.da[i].unwrap_or(0.0);
 
-            // dr: Monthly change in Fed Funds Rate (percentage points)
-            let dr = current.fed_funds_rate - prev_month.fed_funds_rate;
+            // d²A: change in dA since M2_ACCEL_LAG months ago (second derivative of M2)
+            // Zero until the lagged rate is available
+            let m2_accel = i
+                .checked_sub(M2_ACCEL_LAG)
+                .and_then(|j| derived.da[j])
+                .map(|lagged| da - lagged)
+                .unwrap_or(0.0);
+
+            // dr: Monthly change in Fed Funds Rate (percentage points) by default
+            let dr = derived.dr[i].unwrap_or(0.0);
 
             // σ_r: 12-month rolling standard deviation of Fed Funds
             // CRITICAL: This handles the 2022 inflation/volatility paradox
@@ -194,22 +1076,68 @@ impl NIVEngine {
                 .collect();
             let sigma_r = fed_funds_window.std_dev();
 
+            // ΔS: 12-month change in the term spread by default (negative = flattening)
+            let spread_change = derived.spread_change[i].unwrap_or(0.0);
+
+            let mut quality = Vec::new();
+            if replaced[i] {
+                quality.push(QualityFlag::Input);
+            }
+            let mut terms = custom.get_mut(i).map(std::mem::take).unwrap_or_default();
+            for term in terms.iter_mut().filter(|t| t.value.is_some_and(|v| !in_range(v))) {
+                term.value = None;
+                flag(&mut quality, QualityFlag::Term);
+            }
+
             extended.push(ExtendedEconomicData {
                 base: current.clone(),
                 dg,
                 da,
                 dr,
+                m2_accel,
                 sigma_r,
+                spread_change,
+                thrust_scale: THRUST_SCALE,
+                custom: terms,
+                quality,
             });
         }
 
+        match self.thrust_scaling {
+            ThrustScaling::Fixed { divisor } => {
+                for e in extended.iter_mut() {
+                    e.thrust_scale = divisor;
+                }
+            }
+            ThrustScaling::RollingStd { window } => {
+                // Trailing std dev of the weighted input; fixed scale until the window fills
+                let inputs: Vec<f64> = extended.iter().map(|e| self.thrust_input(e)).collect();
+                for (i, e) in extended.iter_mut().enumerate() {
+                    if i + 1 >= window {
+                        let sd = inputs[(i + 1 - window)..=i].std_dev();
+                        e.thrust_scale = if sd.is_finite() { sd.max(MIN_THRUST_SCALE) } else { THRUST_SCALE };
+                    }
+                }
+            }
+        }
+
         extended
     }
 
-    /// Calculate NIV for a single data point with extended data
-    fn calculate_single(&self, data: &ExtendedEconomicData) -> NIVResult {
-        let components = self.compute_components(data);
-        let niv_score = self.compute_niv(&components);
+    /// Calculate NIV for a single data point with extended data; `previous`
+    /// is the prior month's raw result, for NonFinitePolicy::CarryForward
+    fn calculate_single(&self, data: &ExtendedEconomicData, previous: Option<&NIVResult>) -> NIVResult {
+        let mut quality = data.quality.clone();
+        let mut components = self.compute_components(data);
+        self.guard_components(&mut components, previous.map(|p| &p.components), &mut quality);
+        if self.denominator_base(&components) < 0.0 {
+            flag(&mut quality, QualityFlag::NegativeDenominator);
+        }
+        let mut niv_score = self.compute_niv(&components);
+        if !niv_score.is_finite() {
+            niv_score = self.nonfinite_policy.replace(niv_score, previous.map(|p| p.niv_score), NIV_CLAMP);
+            flag(&mut quality, QualityFlag::Score);
+        }
         let recession_probability = self.compute_recession_probability(niv_score);
         let alert_level = AlertLevel::from_probability(recession_probability);
 
@@ -219,32 +1147,127 @@ impl NIVEngine {
             recession_probability,
             components,
             alert_level,
+            niv_percentile: 0.0,
+            quality,
+            coverage: data.base.estimated.clone(),
+            warmup: false,
+        }
+    }
+
+    /// Replace non-finite components under the engine's NonFinitePolicy,
+    /// recomputing P² and the drag from their replaced inputs
+    fn guard_components(
+        &self,
+        c: &mut NIVComponents,
+        previous: Option<&NIVComponents>,
+        quality: &mut Vec<QualityFlag>,
+    ) {
+        let policy = self.nonfinite_policy;
+        let mut guard = |value: &mut f64, prev: Option<f64>, f: QualityFlag| {
+            if !in_range(*value) {
+                *value = policy.replace(*value, prev, COMPONENT_LIMIT);
+                flag(quality, f);
+            }
+        };
+        guard(&mut c.thrust, previous.map(|p| p.thrust), QualityFlag::Thrust);
+        guard(&mut c.efficiency, previous.map(|p| p.efficiency), QualityFlag::Efficiency);
+        c.efficiency_squared = c.efficiency.powi(2);
+        guard(&mut c.efficiency_squared, previous.map(|p| p.efficiency_squared), QualityFlag::Efficiency);
+        guard(&mut c.slack, previous.map(|p| p.slack), QualityFlag::Slack);
+        guard(&mut c.drag_spread, previous.map(|p| p.drag_spread), QualityFlag::Drag);
+        guard(&mut c.drag_real_rate, previous.map(|p| p.drag_real_rate), QualityFlag::Drag);
+        guard(&mut c.drag_volatility, previous.map(|p| p.drag_volatility), QualityFlag::Drag);
+        c.drag = self.drag(c.drag_spread, c.drag_real_rate, c.drag_volatility);
+        guard(&mut c.drag, previous.map(|p| p.drag), QualityFlag::Drag);
+    }
+
+    /// F = w_s*s_t + w_r*(r_t - π_t) + w_σ*σ_r
+    fn drag(&self, spread: f64, real_rate: f64, volatility: f64) -> f64 {
+        self.weights.drag_spread * spread
+            + self.weights.drag_real_rate * real_rate
+            + self.weights.drag_volatility * volatility
+    }
+
+    /// Weighted thrust input before scaling: w_dg*dG + w_dA*dA - w_dr*dr + w_d²A*d²A
+    pub fn thrust_input(&self, data: &ExtendedEconomicData) -> f64 {
+        self.weights.thrust_dg * data.dg
+            + self.weights.thrust_da * data.da
+            - self.weights.thrust_dr * data.dr
+            + self.weights.thrust_m2_accel * data.m2_accel
+    }
+
+    /// Investment adjusted for R&D/education under the engine's efficiency spec
+    pub fn efficiency_numerator(&self, data: &EconomicData) -> f64 {
+        match (self.efficiency_spec, data.education_spending) {
+            (EfficiencySpec::Observed, Some(education)) => data.investment + education,
+            _ => data.investment * R_D_MULTIPLIER,
+        }
+    }
+
+    /// Efficiency denominator under the engine's GDP spec, and whether it is the nowcast
+    pub fn gdp(&self, data: &EconomicData) -> (f64, bool) {
+        match (self.gdp_spec, data.gdp_nowcast) {
+            (GdpSpec::Nowcast, Some(nowcast)) => (nowcast, true),
+            _ => (data.gdp, false),
+        }
+    }
+
+    /// Inflation under the engine's inflation spec; headline CPI when the series is missing
+    pub fn inflation(&self, data: &EconomicData) -> f64 {
+        let measure = match self.inflation_spec {
+            InflationSpec::Cpi => None,
+            InflationSpec::CoreCpi => data.core_cpi_inflation,
+            InflationSpec::Pce => data.pce_inflation,
+            InflationSpec::Expected => data.expected_inflation,
+        };
+        measure.unwrap_or(data.cpi_inflation)
+    }
+
+    /// Slack under the engine's slack spec; TCU when the gap series are missing
+    pub fn slack(&self, data: &EconomicData) -> f64 {
+        let gap = match self.slack_spec {
+            SlackSpec::CapacityUtilization => None,
+            SlackSpec::OutputGap => data
+                .potential_gdp
+                .filter(|p| *p > 0.0)
+                .map(|p| (p - data.gdp) / p),
+            SlackSpec::UnemploymentGap => data
+                .unemployment_rate
+                .zip(data.nairu)
+                .map(|(u, n)| OKUN_COEFFICIENT * (u - n) / 100.0),
+        };
+        match gap {
+            // Floor keeps the denominator positive in overheated economies
+            Some(gap) => (SLACK_NEUTRAL + gap).max(0.0),
+            None => 1.0 - (data.capacity_util / 100.0),
         }
     }
 
     /// Compute NIV components using exact superprompt formulas
-    fn compute_components(&self, data: &ExtendedEconomicData) -> NIVComponents {
+    pub fn compute_components(&self, data: &ExtendedEconomicData) -> NIVComponents {
         // ═══════════════════════════════════════════════════════════════════
         // THRUST (u): tanh(1.0*dG + 1.0*dA - 0.7*dr)
         // The Kinetic Impulse - DO NOT normalize inputs to [0,1]
         // Feed raw growth rates into tanh
+        // Optional M2 acceleration term (thrust_m2_accel weight, 0 in v6)
         // ═══════════════════════════════════════════════════════════════════
-        let thrust_input = THRUST_DG_WEIGHT * data.dg
-                         + THRUST_DA_WEIGHT * data.da
-                         - THRUST_DR_WEIGHT * data.dr;
+        let thrust_input = self.thrust_input(data);
 
         // Scale for tanh to work effectively (growth rates can be large)
-        // Divide by 10 to bring typical values into [-5, 5] range for tanh
-        let thrust = (thrust_input / 10.0).tanh();
+        // Default divides by 10; see ThrustScaling for the data-driven option
+        let thrust = (thrust_input / data.thrust_scale).tanh();
 
         // ═══════════════════════════════════════════════════════════════════
         // EFFICIENCY (P): (Investment × 1.15) / GDP
         // The 1.15 multiplier accounts for R&D/Education proxies
+        // (EfficiencySpec::Observed uses the actual series where available;
+        // GdpSpec::Nowcast divides by the monthly GDP proxy between releases)
         // This term is SQUARED in the master equation - punishes "hollow growth"
         // (GDP rising without investment), which predicted the 2008 GFC
         // ═══════════════════════════════════════════════════════════════════
-        let efficiency = if data.base.gdp > 0.0 {
-            (data.base.investment * R_D_MULTIPLIER) / data.base.gdp
+        let (gdp, gdp_nowcast) = self.gdp(&data.base);
+        let efficiency = if gdp > 0.0 {
+            self.efficiency_numerator(&data.base) / gdp
         } else {
             0.0
         };
@@ -253,8 +1276,9 @@ impl NIVEngine {
         // ═══════════════════════════════════════════════════════════════════
         // SLACK (X): 1 - (TCU / 100)
         // Economic Headroom - higher slack = more room to grow
+        // SlackSpec selects a gap-based alternative (falls back to TCU)
         // ═══════════════════════════════════════════════════════════════════
-        let slack = 1.0 - (data.base.capacity_util / 100.0);
+        let slack = self.slack(&data.base);
 
         // ═══════════════════════════════════════════════════════════════════
         // DRAG (F): 0.4*s_t + 0.4*(r_t - π_t) + 0.2*σ_r
@@ -262,15 +1286,14 @@ impl NIVEngine {
         // ═══════════════════════════════════════════════════════════════════
 
         // s_t (Spread Penalty): If T10Y3M < 0 (Inverted), value is abs(T10Y3M). Else 0.
-        let drag_spread = if data.base.yield_spread < 0.0 {
-            data.base.yield_spread.abs() / 100.0 // Normalize to proportion
-        } else {
-            0.0
-        };
+        // SpreadSpec selects a continuous treatment (level or 12-month change),
+        // which can go negative: a steep or steepening curve relieves drag
+        let drag_spread = self.spread_spec.penalty(data.base.yield_spread, data.spread_change);
 
         // r_t - π_t (Real Rate): FEDFUNDS - CPIAUCSL (YoY %)
+        // InflationSpec swaps in core CPI, PCE or breakeven inflation (falls back to CPI)
         // Use max(0, Real_Rate) - only positive real rates create drag
-        let real_rate = data.base.fed_funds_rate - data.base.cpi_inflation;
+        let real_rate = data.base.fed_funds_rate - self.inflation(&data.base);
         let drag_real_rate = real_rate.max(0.0) / 100.0; // Normalize
 
         // σ_r (Volatility): 12-month rolling std dev of FEDFUNDS
@@ -278,9 +1301,7 @@ impl NIVEngine {
         let drag_volatility = data.sigma_r / 100.0; // Normalize
 
         // Combined drag with exact weights
-        let drag = DRAG_SPREAD_WEIGHT * drag_spread
-                 + DRAG_REAL_RATE_WEIGHT * drag_real_rate
-                 + DRAG_VOLATILITY_WEIGHT * drag_volatility;
+        let drag = self.drag(drag_spread, drag_real_rate, drag_volatility);
 
         NIVComponents {
             thrust,
@@ -291,27 +1312,43 @@ impl NIVEngine {
             drag_spread,
             drag_real_rate,
             drag_volatility,
+            gdp_nowcast,
+            custom: data.custom.clone(),
         }
     }
 
-    /// Compute NIV score from components using Master Formula
-    /// NIV_t = (u_t × P_t²) / (X_t + F_t)^η
-    fn compute_niv(&self, components: &NIVComponents) -> f64 {
-        let numerator = components.thrust * components.efficiency_squared;
-
-        // Apply EPSILON safety floor to denominator
-        let denominator_base = components.slack + components.drag + self.epsilon;
-        let denominator = denominator_base.powf(self.eta);
+    fn denominator_base(&self, components: &NIVComponents) -> f64 {
+        denominator_base(components, self.epsilon)
+    }
 
-        if denominator.abs() < 1e-15 {
-            return 0.0;
+    /// Compute NIV score from components using Master Formula
+    /// NIV_t = (u_t × P_t²) / (X_t + F_t)^η, under the engine's DenominatorPolicy
+    pub fn compute_niv(&self, components: &NIVComponents) -> f64 {
+        // Apply EPSILON safety floor to denominator; a negative base (signed
+        // spread specs, negative weights, registry terms) follows the policy
+        let denominator_base = self.denominator_base(components);
+        match self.denominator_policy {
+            DenominatorPolicy::Reflect if denominator_base < 0.0 => {
+                scaled_score(numerator(components), -(-denominator_base).max(self.epsilon).powf(self.eta))
+            }
+            _ => compute_niv(components, self.eta, self.epsilon),
         }
+    }
 
-        // Scale to intuitive range (roughly -100 to +100)
-        let raw_niv = numerator / denominator;
-
-        // Multiply by 1000 to get meaningful numbers (efficiency_squared is very small)
-        (raw_niv * 1000.0).clamp(-100.0, 100.0)
+    /// The engine's link from components to recession probability in month
+    /// `date`: the configured ProbabilityInput (Percentile ranks the score
+    /// among `history`, the preceding months' scores) and the expansion-age
+    /// shift, as `calculate_raw` applies them
+    pub fn probability_link<'a>(&'a self, date: NaiveDate, history: &'a [f64]) -> impl Fn(&NIVComponents) -> f64 + 'a {
+        let age = expansion_age(date, &RecessionPeriods::announced());
+        move |components| {
+            let score = self.compute_niv(components);
+            let probability = match self.probability_input {
+                ProbabilityInput::Score => self.compute_recession_probability(score),
+                ProbabilityInput::Percentile => percentile_probability(trailing_percentile(history, score, PERCENTILE_WINDOW)),
+            };
+            expansion_age_adjusted(probability, age, self.expansion_age_weight)
+        }
     }
 
     /// Convert NIV score to recession probability
@@ -320,68 +1357,152 @@ impl NIVEngine {
     /// This is a sigmoid transformation where:
     /// - Negative NIV → Higher recession probability (approaching 1)
     /// - Positive NIV → Lower recession probability (approaching 0)
-    fn compute_recession_probability(&self, niv_score: f64) -> f64 {
-        // Note: The sign in the exponent is CRITICAL
-        // -NIV/10 means: negative NIV → positive exponent → small denominator → high probability
-        let prob = 1.0 / (1.0 + (-niv_score / 10.0).exp());
-
-        // Invert because high NIV = good (low recession risk)
-        // Low NIV = bad (high recession risk)
-        1.0 - prob
+    pub fn compute_recession_probability(&self, niv_score: f64) -> f64 {
+        probability_from_score(niv_score)
     }
 
     /// Apply rolling window smoothing
-    fn apply_smoothing(&self, results: &[NIVResult]) -> Vec<NIVResult> {
+    /// The first `window - 1` months are passed through unsmoothed. Means are
+    /// summed afresh over each window (at most MAX_SMOOTH_WINDOW months): a
+    /// running float sum drifts over a long series. Flag counts are integers
+    /// and run incrementally.
+    fn apply_smoothing(&self, results: &[NIVResult], window: usize) -> Vec<NIVResult> {
         let n = results.len();
-        if n < SMOOTH_WINDOW {
+        if window <= 1 || n < window {
             return results.to_vec();
         }
 
+        // niv, prob, thrust, efficiency, efficiency², slack, drag, spread, real rate, volatility
+        let fields = |r: &NIVResult| -> [f64; 10] {
+            let c = &r.components;
+            [
+                r.niv_score, r.recession_probability, c.thrust, c.efficiency, c.efficiency_squared,
+                c.slack, c.drag, c.drag_spread, c.drag_real_rate, c.drag_volatility,
+            ]
+        };
+        let terms = results[0].components.custom.len();
+        // Months in the window whose efficiency used the GDP nowcast
+        let mut nowcast_months = 0usize;
+        // Months in the window carrying each quality flag, and each estimated input
+        let mut flagged = [0usize; QualityFlag::ALL.len()];
+        let mut estimated = [0usize; InputSeries::ALL.len()];
+
         let mut smoothed = Vec::with_capacity(n);
 
         for i in 0..n {
-            if i < SMOOTH_WINDOW - 1 {
+            nowcast_months += results[i].components.gdp_nowcast as usize;
+            for f in &results[i].quality {
+                flagged[*f as usize] += 1;
+            }
+            for s in &results[i].coverage {
+                estimated[*s as usize] += 1;
+            }
+            if i >= window {
+                let leaving = &results[i - window];
+                nowcast_months -= leaving.components.gdp_nowcast as usize;
+                for f in &leaving.quality {
+                    flagged[*f as usize] -= 1;
+                }
+                for s in &leaving.coverage {
+                    estimated[*s as usize] -= 1;
+                }
+            }
+
+            if i + 1 < window {
                 smoothed.push(results[i].clone());
                 continue;
             }
 
-            // Calculate smoothed values over window
-            let window_start = i + 1 - SMOOTH_WINDOW;
-            let window = &results[window_start..=i];
-
-            let avg_niv: f64 = window.iter().map(|r| r.niv_score).sum::<f64>() / SMOOTH_WINDOW as f64;
-            let avg_prob: f64 = window.iter().map(|r| r.recession_probability).sum::<f64>() / SMOOTH_WINDOW as f64;
-
-            let avg_thrust: f64 = window.iter().map(|r| r.components.thrust).sum::<f64>() / SMOOTH_WINDOW as f64;
-            let avg_efficiency: f64 = window.iter().map(|r| r.components.efficiency).sum::<f64>() / SMOOTH_WINDOW as f64;
-            let avg_efficiency_sq: f64 = window.iter().map(|r| r.components.efficiency_squared).sum::<f64>() / SMOOTH_WINDOW as f64;
-            let avg_slack: f64 = window.iter().map(|r| r.components.slack).sum::<f64>() / SMOOTH_WINDOW as f64;
-            let avg_drag: f64 = window.iter().map(|r| r.components.drag).sum::<f64>() / SMOOTH_WINDOW as f64;
-            let avg_drag_spread: f64 = window.iter().map(|r| r.components.drag_spread).sum::<f64>() / SMOOTH_WINDOW as f64;
-            let avg_drag_real: f64 = window.iter().map(|r| r.components.drag_real_rate).sum::<f64>() / SMOOTH_WINDOW as f64;
-            let avg_drag_vol: f64 = window.iter().map(|r| r.components.drag_volatility).sum::<f64>() / SMOOTH_WINDOW as f64;
+            let in_window = &results[i + 1 - window..=i];
+            let mut sums = [0.0; 10];
+            // Registry terms: sum and count of available values per term
+            let mut custom_sums = vec![(0.0, 0usize); terms];
+            for r in in_window {
+                for (sum, v) in sums.iter_mut().zip(fields(r)) {
+                    *sum += v;
+                }
+                for (acc, t) in custom_sums.iter_mut().zip(&r.components.custom) {
+                    if let Some(v) = t.value {
+                        acc.0 += v;
+                        acc.1 += 1;
+                    }
+                }
+            }
+            let avg = sums.map(|sum| sum / window as f64);
+            let custom = results[i].components.custom.iter().zip(&custom_sums)
+                .map(|(term, &(sum, count))| CustomTerm {
+                    value: (count > 0).then(|| sum / count as f64),
+                    ..term.clone()
+                })
+                .collect();
 
             smoothed.push(NIVResult {
                 date: results[i].date,
-                niv_score: avg_niv,
-                recession_probability: avg_prob,
+                niv_score: avg[0],
+                recession_probability: avg[1],
                 components: NIVComponents {
-                    thrust: avg_thrust,
-                    efficiency: avg_efficiency,
-                    efficiency_squared: avg_efficiency_sq,
-                    slack: avg_slack,
-                    drag: avg_drag,
-                    drag_spread: avg_drag_spread,
-                    drag_real_rate: avg_drag_real,
-                    drag_volatility: avg_drag_vol,
+                    thrust: avg[2],
+                    efficiency: avg[3],
+                    efficiency_squared: avg[4],
+                    slack: avg[5],
+                    drag: avg[6],
+                    drag_spread: avg[7],
+                    drag_real_rate: avg[8],
+                    drag_volatility: avg[9],
+                    gdp_nowcast: nowcast_months > 0,
+                    custom,
                 },
-                alert_level: AlertLevel::from_probability(avg_prob),
+                alert_level: AlertLevel::from_probability(avg[1]),
+                niv_percentile: 0.0,
+                quality: QualityFlag::ALL.into_iter().filter(|f| flagged[*f as usize] > 0).collect(),
+                coverage: InputSeries::ALL.into_iter().filter(|s| estimated[*s as usize] > 0).collect(),
+                warmup: false,
             });
         }
 
         smoothed
     }
 
+    /// Detect alert level changes between consecutive results
+    ///
+    /// The trigger is the component whose one-at-a-time move from the previous
+    /// month's value shifts NIV the most in the direction of the transition
+    /// (towards risk for escalations, away from it for de-escalations).
+    pub fn alert_transitions(&self, results: &[NIVResult]) -> Vec<AlertTransition> {
+        results
+            .windows(2)
+            .filter(|w| w[0].alert_level != w[1].alert_level)
+            .map(|w| {
+                let (prev, curr) = (&w[0], &w[1]);
+                let escalation = curr.recession_probability > prev.recession_probability;
+                let base = self.compute_niv(&prev.components);
+
+                let (trigger, trigger_delta) = Component::all()
+                    .iter()
+                    .map(|&c| {
+                        let mut swapped = prev.components.clone();
+                        c.copy(&curr.components, &mut swapped);
+                        (c, self.compute_niv(&swapped) - base)
+                    })
+                    // Lower NIV = higher risk
+                    .max_by(|a, b| {
+                        let score = |d: f64| if escalation { -d } else { d };
+                        score(a.1).total_cmp(&score(b.1))
+                    })
+                    .unwrap_or((Component::Thrust, 0.0));
+
+                AlertTransition {
+                    date: curr.date,
+                    from: prev.alert_level,
+                    to: curr.alert_level,
+                    recession_probability: curr.recession_probability,
+                    trigger,
+                    trigger_delta,
+                }
+            })
+            .collect()
+    }
+
     /// Validate calculation against known benchmarks
     /// Returns true if validation passes
     pub fn validate_against_benchmarks(&self, results: &[NIVResult]) -> ValidationResult {
@@ -457,6 +1578,149 @@ impl Default for NIVEngine {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// Single-point API: the formula as free functions, for computing one month
+// without an engine or a series. Units:
+// - growth rates (dg, da) in percent; dr, m2_accel, spread_change, sigma_r
+//   and rates in percentage points; levels in FRED units
+// - thrust u: dimensionless, in [-1, 1]
+// - efficiency P: investment/GDP ratio (P² enters the formula)
+// - slack X and drag F: fractions (0.20 = 20% headroom)
+// - NIV: points, clamped to ±NIV_CLAMP (positive = expansionary)
+// - probability: 0-1
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Components for one month under the v6 specification: default weights,
+/// Investment × 1.15 efficiency over reported GDP, CPI real rate, inversion
+/// spread penalty, TCU slack. Registry terms in `data` pass through. Use an
+/// NIVEngine for other specifications.
+pub fn compute_components(data: &ExtendedEconomicData) -> NIVComponents {
+    static V6: OnceLock<NIVEngine> = OnceLock::new();
+    V6.get_or_init(NIVEngine::new).compute_components(data)
+}
+
+/// Master formula: NIV = 1000 × u × P² / max(X + F + ε, ε)^η, clamped to
+/// ±NIV_CLAMP, with any registry terms applied (η > 0, ε ≥ 0)
+pub fn compute_niv(components: &NIVComponents, eta: f64, epsilon: f64) -> f64 {
+    scaled_score(numerator(components), denominator_base(components, epsilon).max(epsilon).powf(eta))
+}
+
+/// Recession probability (0-1) from an NIV score: 1 - 1/(1 + e^(-NIV/10)),
+/// the default ProbabilityInput::Score mapping
+pub fn probability_from_score(niv_score: f64) -> f64 {
+    // Note: The sign in the exponent is CRITICAL
+    // -NIV/10 means: negative NIV → positive exponent → small denominator → high probability
+    let prob = 1.0 / (1.0 + (-niv_score / 10.0).exp());
+
+    // Invert because high NIV = good (low recession risk)
+    // Low NIV = bad (high recession risk)
+    1.0 - prob
+}
+
+/// u × P², times any numerator registry terms
+fn numerator(components: &NIVComponents) -> f64 {
+    let mut numerator = components.thrust * components.efficiency_squared;
+
+    // Registry terms; unavailable months leave the formula unchanged
+    for term in &components.custom {
+        if let (TermRole::Numerator, Some(v)) = (term.role, term.value) {
+            numerator *= v;
+        }
+    }
+    numerator
+}
+
+/// X + F + ε plus any denominator registry terms, before the exponent
+fn denominator_base(components: &NIVComponents, epsilon: f64) -> f64 {
+    components
+        .custom
+        .iter()
+        .filter(|t| t.role == TermRole::Denominator)
+        .filter_map(|t| t.value)
+        .fold(components.slack + components.drag + epsilon, |base, v| base + v)
+}
+
+/// numerator / denominator in NIV points
+fn scaled_score(numerator: f64, denominator: f64) -> f64 {
+    if denominator.abs() < 1e-15 {
+        return 0.0;
+    }
+
+    // Scale to intuitive range (roughly -100 to +100)
+    let raw_niv = numerator / denominator;
+
+    // Multiply by 1000 to get meaningful numbers (efficiency_squared is very small)
+    (raw_niv * 1000.0).clamp(-NIV_CLAMP, NIV_CLAMP)
+}
+
+/// Percentile rank (0-100) of each value within its trailing `window` (expanding at the start)
+/// Ties count half, so a constant series sits at the 50th percentile
+pub fn rolling_percentile(values: &[f64], window: usize) -> Vec<f64> {
+    (0..values.len()).map(|i| trailing_percentile(&values[..i], values[i], window)).collect()
+}
+
+/// Percentile rank (0-100) of `x` within itself and the last `window - 1`
+/// of `preceding`
+pub fn trailing_percentile(preceding: &[f64], x: f64, window: usize) -> f64 {
+    let history = &preceding[preceding.len().saturating_sub(window.max(1) - 1)..];
+    let below = history.iter().filter(|&&v| v < x).count() as f64;
+    let equal = history.iter().filter(|&&v| v == x).count() as f64 + 1.0;
+    (below + 0.5 * equal) / (history.len() + 1) as f64 * 100.0
+}
+
+/// Map a NIV percentile to recession probability (low percentile = high risk)
+pub fn percentile_probability(percentile: f64) -> f64 {
+    1.0 / (1.0 + ((percentile - PERCENTILE_PROB_MIDPOINT) / PERCENTILE_PROB_SCALE).exp())
+}
+
+/// A recession with the dates its peak and trough were announced
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DatedRecession {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub start_announced: NaiveDate,
+    pub end_announced: NaiveDate,
+}
+
+impl DatedRecession {
+    /// `start`..`end`, announced after the default lags
+    pub fn lagged(start: NaiveDate, end: NaiveDate) -> Self {
+        let after = |date: NaiveDate, months| date.checked_add_months(chrono::Months::new(months)).unwrap_or(date);
+        Self {
+            start,
+            end,
+            start_announced: after(start, PEAK_ANNOUNCEMENT_LAG_MONTHS),
+            end_announced: after(end, TROUGH_ANNOUNCEMENT_LAG_MONTHS),
+        }
+    }
+}
+
+/// Months of expansion as known at `date`: since the last recession end
+/// announced by then, held at its value at the peak while a recession is
+/// known to be under way (peak announced, trough not yet). None before the
+/// first announced end
+pub fn expansion_age(date: NaiveDate, recessions: &[DatedRecession]) -> Option<u32> {
+    let as_of = recessions
+        .iter()
+        .find(|r| r.start_announced <= date && date < r.end_announced)
+        .map_or(date, |r| r.start);
+    let end = recessions.iter().filter(|r| r.end_announced <= date && r.end < as_of).map(|r| r.end).max()?;
+    Some(((as_of.year() - end.year()) * 12 + as_of.month() as i32 - end.month() as i32) as u32)
+}
+
+/// Shift the probability logit by `weight` per year of `age_months` beyond
+/// EXPANSION_AGE_PIVOT_MONTHS; unknown ages and a zero weight leave it as is
+pub fn expansion_age_adjusted(probability: f64, age_months: Option<u32>, weight: f64) -> f64 {
+    match age_months {
+        Some(age) if weight != 0.0 => {
+            let p = probability.clamp(1e-12, 1.0 - 1e-12);
+            let shift = weight * (age as f64 - EXPANSION_AGE_PIVOT_MONTHS as f64) / 12.0;
+            1.0 / (1.0 + (-((p / (1.0 - p)).ln() + shift)).exp())
+        }
+        _ => probability,
+    }
+}
+
 /// Validation result structure
 #[derive(Debug, Clone, Serialize)]
 pub struct ValidationResult {
@@ -506,6 +1770,27 @@ impl RecessionPeriods {
         ]
     }
 
+    /// The known recessions dated by announcement: the NBER committee's
+    /// peak and trough announcements since 1980, the default lags before
+    pub fn announced() -> Vec<DatedRecession> {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).expect("valid announcement date");
+        let announcements = [
+            (day(2020, 2, 1), day(2020, 6, 8), day(2021, 7, 19)),
+            (day(2007, 12, 1), day(2008, 12, 1), day(2010, 9, 20)),
+            (day(2001, 3, 1), day(2001, 11, 26), day(2003, 7, 17)),
+            (day(1990, 7, 1), day(1991, 4, 25), day(1992, 12, 22)),
+            (day(1981, 7, 1), day(1982, 1, 6), day(1983, 7, 8)),
+            (day(1980, 1, 1), day(1980, 6, 3), day(1981, 7, 8)),
+        ];
+        Self::known_recessions()
+            .into_iter()
+            .map(|(start, end)| match announcements.iter().find(|(peak, _, _)| *peak == start) {
+                Some(&(_, start_announced, end_announced)) => DatedRecession { start, end, start_announced, end_announced },
+                None => DatedRecession::lagged(start, end),
+            })
+            .collect()
+    }
+
     /// Check if a date falls within a recession period
     pub fn is_recession(date: NaiveDate) -> bool {
         Self::known_recessions()
@@ -529,11 +1814,26 @@ mod tests {
                 capacity_util: 78.5,
                 yield_spread: -0.5, // Inverted
                 cpi_inflation: 3.2,
+                rd_investment: None,
+                education_spending: None,
+                potential_gdp: None,
+                unemployment_rate: None,
+                nairu: None,
+                gdp_nowcast: None,
+                core_cpi_inflation: None,
+                pce_inflation: None,
+                expected_inflation: None,
+                estimated: Vec::new(),
             },
             dg: 0.5,      // 0.5% monthly investment growth
             da: 4.0,      // 4% YoY M2 growth
             dr: 0.0,      // No change in fed funds
+            m2_accel: -1.5, // YoY M2 growth down 1.5pp over 6 months
             sigma_r: 1.2, // 1.2% volatility
+            spread_change: -0.8, // Flattened 0.8pp over the year
+            thrust_scale: THRUST_SCALE,
+            custom: Vec::new(),
+            quality: Vec::new(),
         }
     }
 
@@ -600,7 +1900,7 @@ mod tests {
         // NIV = (thrust * efficiency_squared) / (slack + drag + epsilon)^eta
         // Should produce a finite, reasonable score
         assert!(niv.is_finite());
-        assert!(niv >= -100.0 && niv <= 100.0, "NIV was {}", niv);
+        assert!((-100.0..=100.0).contains(&niv), "NIV was {}", niv);
     }
 
     #[test]
@@ -626,6 +1926,365 @@ mod tests {
         assert_eq!(AlertLevel::from_probability(0.4), AlertLevel::Elevated);
         assert_eq!(AlertLevel::from_probability(0.6), AlertLevel::Warning);
         assert_eq!(AlertLevel::from_probability(0.8), AlertLevel::Critical);
+
+        for level in [AlertLevel::Elevated, AlertLevel::Warning, AlertLevel::Critical] {
+            assert_eq!(AlertLevel::from_probability(level.threshold()), level);
+        }
+    }
+
+    #[test]
+    fn test_custom_weights_change_thrust() {
+        let data = sample_extended_data();
+        let weights = ComponentWeights { thrust_da: 0.0, ..ComponentWeights::default() };
+        let engine = NIVEngine::new().with_weights(weights);
+        let components = engine.compute_components(&data);
+
+        // thrust_input = 1.0*0.5 + 0.0*4.0 - 0.7*0.0 = 0.5 -> tanh(0.05) ≈ 0.05
+        assert!((components.thrust - 0.05).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_alert_transitions_identify_trigger() {
+        let engine = NIVEngine::new();
+        let data = sample_extended_data();
+        let calm = engine.calculate_single(&data, None);
+
+        // Same month with thrust collapsing -> escalation driven by thrust
+        let mut stressed_data = data.clone();
+        stressed_data.base.date = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        stressed_data.da = -40.0;
+        let stressed = engine.calculate_single(&stressed_data, None);
+        assert_ne!(calm.alert_level, stressed.alert_level);
+
+        let transitions = engine.alert_transitions(&[calm.clone(), stressed, calm]);
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0].trigger, Component::Thrust);
+        assert!(transitions[0].trigger_delta < 0.0);
+        assert_eq!(transitions[1].trigger, Component::Thrust);
+        assert!(transitions[1].trigger_delta > 0.0);
+    }
+
+    #[test]
+    fn test_observed_efficiency_falls_back_to_multiplier() {
+        let proxy = NIVEngine::new();
+        let observed = NIVEngine::new().with_efficiency_spec(EfficiencySpec::Observed);
+
+        // No education data: identical to the proxy, R&D or not
+        let mut data = sample_extended_data();
+        data.base.rd_investment = Some(700.0);
+        assert_eq!(
+            observed.compute_components(&data).efficiency,
+            proxy.compute_components(&data).efficiency
+        );
+
+        // Education replaces the multiplier; R&D is already in investment
+        data.base.education_spending = Some(300.0);
+        let c = observed.compute_components(&data);
+        assert!((c.efficiency - 4300.0 / 28000.0).abs() < 1e-12);
+        assert_eq!(
+            proxy.compute_components(&data).efficiency,
+            4000.0 * R_D_MULTIPLIER / 28000.0
+        );
+    }
+
+    #[test]
+    fn test_gdp_nowcast_spec_flags_provenance() {
+        let reported = NIVEngine::new();
+        let nowcast = NIVEngine::new().with_gdp_spec(GdpSpec::Nowcast);
+
+        // No nowcast for the month: reported GDP either way
+        let mut data = sample_extended_data();
+        let c = nowcast.compute_components(&data);
+        assert!(!c.gdp_nowcast);
+        assert_eq!(c.efficiency, reported.compute_components(&data).efficiency);
+
+        data.base.gdp_nowcast = Some(28000.0 * 1.01);
+        let c = nowcast.compute_components(&data);
+        assert!(c.gdp_nowcast);
+        assert!((c.efficiency - 4000.0 * R_D_MULTIPLIER / (28000.0 * 1.01)).abs() < 1e-12);
+        assert!(!reported.compute_components(&data).gdp_nowcast);
+
+        assert_eq!("NOWCAST".parse::<GdpSpec>(), Ok(GdpSpec::Nowcast));
+        assert!("gdi".parse::<GdpSpec>().is_err());
+    }
+
+    #[test]
+    fn test_inflation_specs() {
+        // Fed funds 5.25: real rate 2.05 under headline CPI (3.2)
+        let mut data = sample_extended_data();
+        let cpi_drag = NIVEngine::new().compute_components(&data).drag_real_rate;
+        assert!((cpi_drag - 0.0205).abs() < 1e-12);
+
+        // Missing series fall back to headline CPI
+        for spec in [InflationSpec::CoreCpi, InflationSpec::Pce, InflationSpec::Expected] {
+            let engine = NIVEngine::new().with_inflation_spec(spec);
+            assert_eq!(engine.compute_components(&data).drag_real_rate, cpi_drag);
+        }
+
+        data.base.core_cpi_inflation = Some(3.75);
+        data.base.expected_inflation = Some(2.25);
+        let core = NIVEngine::new().with_inflation_spec(InflationSpec::CoreCpi);
+        assert!((core.compute_components(&data).drag_real_rate - 0.015).abs() < 1e-12);
+        let expected = NIVEngine::new().with_inflation_spec(InflationSpec::Expected);
+        assert!((expected.compute_components(&data).drag_real_rate - 0.03).abs() < 1e-12);
+
+        assert_eq!("core-cpi".parse::<InflationSpec>(), Ok(InflationSpec::CoreCpi));
+        assert_eq!("PCEPI".parse::<InflationSpec>(), Ok(InflationSpec::Pce));
+        assert!("gdp_deflator".parse::<InflationSpec>().is_err());
+    }
+
+    #[test]
+    fn test_spread_specs() {
+        // Spread -0.5, flattened 0.8pp over the year
+        let data = sample_extended_data();
+        let penalty = |spec| NIVEngine::new().with_spread_spec(spec).compute_components(&data).drag_spread;
+        assert!((penalty(SpreadSpec::Inversion) - 0.005).abs() < 1e-12);
+        assert!((penalty(SpreadSpec::Level) - 0.005).abs() < 1e-12);
+        assert!((penalty(SpreadSpec::Change12m) - 0.008).abs() < 1e-12);
+
+        // A steep curve relieves drag under the continuous specs only
+        assert_eq!(SpreadSpec::Inversion.penalty(2.0, 1.0), 0.0);
+        assert!(SpreadSpec::Level.penalty(2.0, 1.0) < 0.0);
+        assert!(SpreadSpec::Change12m.penalty(2.0, 1.0) < 0.0);
+
+        assert_eq!("change-12m".parse::<SpreadSpec>(), Ok(SpreadSpec::Change12m));
+        assert!("slope".parse::<SpreadSpec>().is_err());
+    }
+
+    #[test]
+    fn test_m2_acceleration_term() {
+        // Off by default
+        let data = sample_extended_data();
+        let base = NIVEngine::new().compute_components(&data).thrust;
+        let weights = ComponentWeights { thrust_m2_accel: 1.0, ..ComponentWeights::default() };
+        let engine = NIVEngine::new().with_weights(weights);
+        assert!(engine.compute_components(&data).thrust < base);
+
+        // M2 growing 12%/yr then flat: YoY growth decelerates
+        let mut series = crate::fred::mock::generate_mock_data(2010, 2013);
+        for (i, d) in series.iter_mut().enumerate() {
+            d.m2_supply = 10_000.0 * 1.01f64.powi(i.min(24) as i32);
+        }
+        let extended = engine.compute_extended_data(&series);
+        assert_eq!(extended[M2_ACCEL_LAG - 1].m2_accel, 0.0); // lagged YoY not yet available
+        assert!(extended[M2_ACCEL_LAG + 1].m2_accel.abs() < 1e-9); // steady growth
+        assert!(extended[18].m2_accel < -5.0); // 6 months after M2 flattens
+    }
+
+    #[test]
+    fn test_slack_specs() {
+        let mut data = sample_extended_data();
+        let tcu_slack = 1.0 - 78.5 / 100.0;
+
+        // Gap specs fall back to TCU without their series
+        for spec in [SlackSpec::OutputGap, SlackSpec::UnemploymentGap] {
+            let engine = NIVEngine::new().with_slack_spec(spec);
+            assert!((engine.compute_components(&data).slack - tcu_slack).abs() < 1e-12);
+        }
+
+        // 2% output gap
+        data.base.potential_gdp = Some(28000.0 / 0.98);
+        let engine = NIVEngine::new().with_slack_spec(SlackSpec::OutputGap);
+        assert!((engine.compute_components(&data).slack - 0.22).abs() < 1e-9);
+
+        // 1pt unemployment gap -> 2% output gap via Okun
+        data.base.unemployment_rate = Some(5.5);
+        data.base.nairu = Some(4.5);
+        let engine = NIVEngine::new().with_slack_spec(SlackSpec::UnemploymentGap);
+        assert!((engine.compute_components(&data).slack - 0.22).abs() < 1e-9);
+
+        assert_eq!("output-gap".parse::<SlackSpec>(), Ok(SlackSpec::OutputGap));
+        assert!("phillips".parse::<SlackSpec>().is_err());
+    }
+
+    #[test]
+    fn test_registry_terms_enter_formula() {
+        use crate::registry::ComponentDef;
+
+        let data = crate::fred::mock::generate_mock_data(1990, 2020);
+        let registry = |role: TermRole, expr: &str| {
+            Arc::new(ComponentRegistry::compile(vec![ComponentDef {
+                name: "extra".to_string(),
+                role,
+                expr: expr.to_string(),
+            }]).unwrap())
+        };
+
+        let base = NIVEngine::new().calculate_series(&data);
+
+        // Neutral terms leave the score unchanged
+        let neutral = NIVEngine::new()
+            .with_registry(registry(TermRole::Denominator, "0"))
+            .calculate_series(&data);
+        assert_eq!(base.last().unwrap().niv_score, neutral.last().unwrap().niv_score);
+        assert_eq!(neutral.last().unwrap().components.custom[0].value, Some(0.0));
+
+        // Extra friction pulls NIV towards zero
+        let damped = NIVEngine::new()
+            .with_registry(registry(TermRole::Denominator, "1"))
+            .calculate_series(&data);
+        let (b, d) = (base.last().unwrap().niv_score, damped.last().unwrap().niv_score);
+        assert!(d.abs() < b.abs(), "base {} vs damped {}", b, d);
+
+        // Scaling the numerator by -1 flips the raw score
+        let flipped = NIVEngine::new().with_registry(registry(TermRole::Numerator, "-1"));
+        let ext = flipped.compute_extended_data(&data);
+        let c = flipped.compute_components(&ext[100]);
+        let plain = NIVComponents { custom: Vec::new(), ..c.clone() };
+        assert!((flipped.compute_niv(&c) + NIVEngine::new().compute_niv(&plain)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_thrust_scaling_modes() {
+        let data = crate::fred::mock::generate_mock_data(1990, 2020);
+
+        // Explicit fixed divisor of 10 reproduces the default engine
+        let default = NIVEngine::new().calculate_series(&data);
+        let fixed = NIVEngine::new()
+            .with_thrust_scaling(ThrustScaling::Fixed { divisor: THRUST_SCALE })
+            .calculate_series(&data);
+        assert_eq!(default.last().unwrap().niv_score, fixed.last().unwrap().niv_score);
+
+        // Rolling std keeps thrust bounded and differs from the fixed divisor
+        let rolling = NIVEngine::new()
+            .with_thrust_scaling(ThrustScaling::RollingStd { window: 60 })
+            .calculate_series(&data);
+        assert_eq!(rolling.len(), default.len());
+        assert!(rolling.iter().all(|r| r.components.thrust.abs() <= 1.0));
+        assert_ne!(rolling.last().unwrap().components.thrust, default.last().unwrap().components.thrust);
+
+        assert!(!ThrustScaling::Fixed { divisor: 0.0 }.is_valid());
+        assert!(!ThrustScaling::RollingStd { window: 1 }.is_valid());
+    }
+
+    #[test]
+    fn test_smoothing_window() {
+        let engine = NIVEngine::new();
+        let data = crate::fred::mock::generate_mock_data(1990, 2020);
+        let raw = engine.calculate_raw(&data);
+
+        // Default window reproduces calculate_series
+        let series = engine.calculate_series(&data);
+        let resmoothed = engine.smooth(&raw, SMOOTH_WINDOW);
+        assert_eq!(series.len(), resmoothed.len());
+        assert!((series[200].niv_score - resmoothed[200].niv_score).abs() < 1e-9);
+
+        // Smoothed values match a direct window mean
+        let smoothed = engine.smooth(&raw, 3);
+        let direct = raw[198..=200].iter().map(|r| r.niv_score).sum::<f64>() / 3.0;
+        assert!((smoothed[200].niv_score - direct).abs() < 1e-9);
+
+        // Window of one is the raw series
+        assert_eq!(engine.smooth(&raw, 1)[200].niv_score, raw[200].niv_score);
+    }
+
+    #[test]
+    fn test_smoothing_does_not_drift_over_long_series() {
+        let engine = NIVEngine::new();
+        let data = crate::fred::mock::generate_mock_data(1900, 2020);
+        let mut raw = engine.calculate_raw(&data);
+        // Large alternating values: any running-sum residue would survive
+        for (i, r) in raw.iter_mut().enumerate() {
+            r.components.thrust = if i % 2 == 0 { 1e12 } else { 1e-3 * i as f64 };
+        }
+
+        let window = MAX_SMOOTH_WINDOW;
+        let smoothed = engine.smooth(&raw, window);
+        for i in (window - 1..raw.len()).step_by(97).chain([raw.len() - 1]) {
+            let direct = raw[i + 1 - window..=i].iter().map(|r| r.components.thrust).sum::<f64>() / window as f64;
+            assert_eq!(smoothed[i].components.thrust, direct, "month {}", i);
+        }
+    }
+
+    #[test]
+    fn test_warmup_flagged_or_trimmed() {
+        let data = crate::fred::mock::generate_mock_data(1990, 2000);
+        let flagged = NIVEngine::new().calculate_series(&data);
+        let trimmed = NIVEngine::new().with_warmup_policy(WarmupPolicy::Trim).calculate_series(&data);
+
+        assert_eq!(flagged.iter().filter(|r| r.warmup).count(), SMOOTH_WINDOW - 1);
+        assert!(flagged[..SMOOTH_WINDOW - 1].iter().all(|r| r.warmup));
+        assert_eq!(trimmed.len(), flagged.len() - (SMOOTH_WINDOW - 1));
+        assert!(trimmed.iter().all(|r| !r.warmup));
+        assert_eq!(trimmed[0].date, flagged[SMOOTH_WINDOW - 1].date);
+        assert_eq!(trimmed[0].niv_percentile, flagged[SMOOTH_WINDOW - 1].niv_percentile);
+        assert_eq!(crate::backtest::evaluated(&flagged).len(), trimmed.len());
+        assert_eq!("TRIM".parse::<WarmupPolicy>(), Ok(WarmupPolicy::Trim));
+    }
+
+    #[test]
+    fn test_rolling_percentile() {
+        let pct = rolling_percentile(&[1.0, 2.0, 3.0, 0.0], 3);
+        assert_eq!(pct[0], 50.0);                      // Only itself
+        assert!((pct[2] - 100.0 * 2.5 / 3.0).abs() < 1e-9); // Highest of [1, 2, 3]
+        assert!((pct[3] - 100.0 * 0.5 / 3.0).abs() < 1e-9); // Lowest of [2, 3, 0]
+    }
+
+    #[test]
+    fn test_percentile_probability_input() {
+        assert!((percentile_probability(PERCENTILE_PROB_MIDPOINT) - 0.5).abs() < 1e-12);
+        assert!(percentile_probability(5.0) > 0.9);
+        assert!(percentile_probability(80.0) < 0.01);
+
+        let data = crate::fred::mock::generate_mock_data(1990, 2020);
+        let engine = NIVEngine::new().with_probability_input(ProbabilityInput::Percentile);
+        let results = engine.calculate_series(&data);
+        assert!(results.iter().all(|r| (0.0..=1.0).contains(&r.recession_probability)));
+        assert!(results.iter().all(|r| (0.0..=100.0).contains(&r.niv_percentile)));
+    }
+
+    #[test]
+    fn test_expansion_age_conditioning() {
+        let date = |y, m| NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+        let nber = RecessionPeriods::announced();
+        // Before the Dec 2008 peak announcement the expansion still runs from Nov 2001
+        assert_eq!(expansion_age(date(2008, 6), &nber), Some(79));
+        // Then held at its length at the Dec 2007 peak until the trough is announced (Sep 2010)
+        assert_eq!(expansion_age(date(2009, 1), &nber), Some(73));
+        assert_eq!(expansion_age(date(2010, 6), &nber), Some(73));
+        assert_eq!(expansion_age(date(2010, 10), &nber), Some(16));
+        assert_eq!(expansion_age(date(2024, 4), &nber), Some(48));
+        assert_eq!(expansion_age(date(1950, 1), &nber), None);
+        // Without announcement dates the default lags apply
+        let lagged = [DatedRecession::lagged(date(2001, 3), date(2001, 11))];
+        assert_eq!(expansion_age(date(2002, 6), &lagged), None);
+        assert_eq!(expansion_age(date(2003, 2), &lagged), Some(15));
+
+        // At the pivot and with no weight the probability is unchanged
+        assert!((expansion_age_adjusted(0.3, Some(60), 0.5) - 0.3).abs() < 1e-12);
+        assert_eq!(expansion_age_adjusted(0.3, Some(120), 0.0), 0.3);
+        assert!(expansion_age_adjusted(0.3, Some(120), 0.5) > 0.3);
+        assert!(expansion_age_adjusted(0.3, Some(12), 0.5) < 0.3);
+
+        let data = crate::fred::mock::generate_mock_data(1990, 2020);
+        let base = NIVEngine::new().calculate_raw(&data);
+        let aged = NIVEngine::new().with_expansion_age_weight(0.5).calculate_raw(&data);
+        let late = base.iter().zip(&aged).find(|(b, _)| b.date == date(2000, 3)).unwrap(); // 108 months in
+        assert!(late.1.recession_probability > late.0.recession_probability);
+    }
+
+    #[test]
+    fn test_extended_data_needs_a_year_of_history() {
+        let engine = NIVEngine::new();
+        let data = crate::fred::mock::generate_mock_data(2000, 2001);
+        assert!(engine.compute_extended_data(&[]).is_empty());
+        assert!(engine.compute_extended_data(&data[..5]).is_empty());
+        assert!(engine.compute_extended_data(&data[..12]).is_empty());
+        assert_eq!(engine.compute_extended_data(&data[..13]).len(), 1);
+    }
+
+    #[test]
+    fn test_probability_link_matches_raw_results() {
+        let data = crate::fred::mock::generate_mock_data(1990, 2024);
+        for input in [ProbabilityInput::Score, ProbabilityInput::Percentile] {
+            let engine = NIVEngine::new().with_probability_input(input).with_expansion_age_weight(0.5);
+            let raw = engine.calculate_raw(&data);
+            let scores: Vec<f64> = raw.iter().map(|r| r.niv_score).collect();
+            for (i, r) in raw.iter().enumerate() {
+                let link = engine.probability_link(r.date, &scores[..i]);
+                assert!((link(&r.components) - r.recession_probability).abs() < 1e-12, "{:?} at {}", input, r.date);
+            }
+        }
     }
 
     #[test]
@@ -643,11 +2302,26 @@ mod tests {
                 capacity_util: 100.0, // Full capacity = zero slack
                 yield_spread: 2.0,    // Positive spread = zero spread drag
                 cpi_inflation: 5.0,   // Higher than fed funds = negative real rate
+                rd_investment: None,
+                education_spending: None,
+                potential_gdp: None,
+                unemployment_rate: None,
+                nairu: None,
+                gdp_nowcast: None,
+                core_cpi_inflation: None,
+                pce_inflation: None,
+                expected_inflation: None,
+                estimated: Vec::new(),
             },
             dg: 0.0,
             da: 0.0,
             dr: 0.0,
+            m2_accel: 0.0,
             sigma_r: 0.0, // Zero volatility
+            spread_change: 0.0,
+            thrust_scale: THRUST_SCALE,
+            custom: Vec::new(),
+            quality: Vec::new(),
         };
 
         let components = engine.compute_components(&data);
@@ -656,4 +2330,102 @@ mod tests {
         // Should not panic and should produce finite result
         assert!(niv.is_finite());
     }
+
+    #[test]
+    fn test_nonfinite_inputs_are_replaced_and_flagged() {
+        let engine = NIVEngine::new();
+        let mut data = crate::fred::mock::generate_mock_data(1990, 2020);
+        let clean = engine.calculate_raw(&data);
+        data[200].investment = f64::NAN;
+        data[200].gdp = f64::INFINITY;
+        data[201].core_cpi_inflation = Some(f64::NAN);
+
+        let raw = engine.calculate_raw(&data);
+        let finite = |r: &NIVResult| {
+            let c = &r.components;
+            [r.niv_score, r.recession_probability, c.thrust, c.efficiency, c.slack, c.drag]
+                .iter()
+                .all(|v| v.is_finite())
+        };
+        assert!(raw.iter().all(finite));
+        let at = |results: &[NIVResult], date: NaiveDate| results.iter().position(|r| r.date == date).unwrap();
+        let month = at(&raw, data[200].date);
+        assert_eq!(raw[month].quality, vec![QualityFlag::Input]);
+        assert_eq!(raw[month + 1].quality, vec![QualityFlag::Input]);
+        assert!(raw[month + 2].quality.is_empty());
+        // Carrying investment forward from the prior month: zero investment growth
+        assert_eq!(raw[month].components.efficiency, clean[month - 1].components.efficiency);
+
+        // Smoothed months inherit the flags of any month in their window
+        let smoothed = engine.smooth(&raw, SMOOTH_WINDOW);
+        let month = at(&smoothed, data[200].date);
+        assert!(smoothed.iter().all(finite));
+        assert!(!smoothed[month + SMOOTH_WINDOW].quality.is_empty());
+        assert!(smoothed[month + SMOOTH_WINDOW + 1].quality.is_empty());
+    }
+
+    #[test]
+    fn test_nonfinite_policy() {
+        let mut data = sample_extended_data();
+        data.dg = f64::INFINITY;
+        data.da = f64::NEG_INFINITY; // inf - inf: NaN thrust
+        data.base.investment = f64::MAX; // Overflows P
+        data.base.gdp = 0.5;
+
+        let clamp = NIVEngine::new();
+        let result = clamp.calculate_single(&data, None);
+        assert_eq!(result.components.thrust, 0.0);
+        assert_eq!(result.components.efficiency, COMPONENT_LIMIT);
+        assert_eq!(result.quality, vec![QualityFlag::Thrust, QualityFlag::Efficiency]);
+        assert!(result.niv_score.is_finite() && result.recession_probability.is_finite());
+
+        let carry = NIVEngine::new().with_nonfinite_policy(NonFinitePolicy::CarryForward);
+        let previous = carry.calculate_single(&sample_extended_data(), None);
+        let result = carry.calculate_single(&data, Some(&previous));
+        assert_eq!(result.components.thrust, previous.components.thrust);
+        assert_eq!(result.components.efficiency_squared, previous.components.efficiency_squared);
+        assert_eq!(result.niv_score, previous.niv_score);
+
+        assert_eq!("carry-forward".parse::<NonFinitePolicy>(), Ok(NonFinitePolicy::CarryForward));
+        assert!("drop".parse::<NonFinitePolicy>().is_err());
+    }
+
+    #[test]
+    fn test_denominator_policy() {
+        let mut data = sample_extended_data();
+        data.base.capacity_util = 100.0; // No slack
+        // A negative real-rate weight drives X + F + ε to about -0.8
+        let weights = ComponentWeights { drag_real_rate: -40.0, ..ComponentWeights::default() };
+        let result = |policy| {
+            NIVEngine::new()
+                .with_weights(weights)
+                .with_denominator_policy(policy)
+                .calculate_single(&data, None)
+        };
+
+        let clamp = result(DenominatorPolicy::Clamp);
+        let reflect = result(DenominatorPolicy::Reflect);
+        let reject = result(DenominatorPolicy::Reject);
+        assert_eq!(clamp.niv_score, NIV_CLAMP);
+        assert!(reflect.niv_score < 0.0 && reflect.niv_score > -NIV_CLAMP);
+        assert_eq!(reject.niv_score, clamp.niv_score);
+        for r in [&clamp, &reflect, &reject] {
+            assert_eq!(r.quality, vec![QualityFlag::NegativeDenominator]);
+        }
+        assert!(NIVEngine::new().calculate_single(&sample_extended_data(), None).quality.is_empty());
+    }
+
+    #[test]
+    fn test_single_point_functions_match_the_engine() {
+        let engine = NIVEngine::new();
+        let data = crate::fred::mock::generate_mock_data(1990, 2020);
+        for (ext, result) in engine.compute_extended_data(&data).iter().zip(engine.calculate_raw(&data)).step_by(25) {
+            let components = compute_components(ext);
+            let score = compute_niv(&components, ETA, EPSILON);
+            assert_eq!(score, result.niv_score);
+            assert_eq!(probability_from_score(score), result.recession_probability);
+        }
+        assert_eq!(probability_from_score(0.0), 0.5);
+        assert!(probability_from_score(-20.0) > 0.8 && probability_from_score(20.0) < 0.2);
+    }
 }
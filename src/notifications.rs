@@ -0,0 +1,217 @@
+//! Notification Rules
+//!
+//! A rule says which alert transitions should notify a channel:
+//!
+//! ```json
+//! { "name": "desk-escalations", "channel": "slack", "min_level": "warning",
+//!   "direction": "escalation", "min_probability": 55, "cooldown_months": 6,
+//!   "triggers": ["drag"] }
+//! ```
+//!
+//! A transition matches when it moves in the rule's direction across
+//! `min_level` (an escalation ends at or above it, a de-escalation starts at
+//! or above it and ends below), its probability is at least
+//! `min_probability` percent, and its driving component is one of
+//! `triggers` (any when omitted). A match within `cooldown_months` of the
+//! rule's last notification is suppressed.
+//!
+//! `dry_run` evaluates rules over historical transitions and reports what
+//! would have fired when, without sending anything, for tuning rules before
+//! they are enabled (`POST /api/v1/admin/notifications/dry-run`).
+
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::months_between;
+use crate::niv::{AlertLevel, AlertTransition, Component};
+
+pub const MAX_RULES: usize = 20;
+const MAX_RULE_NAME_LEN: usize = 64;
+
+/// Where a rule's notifications go (a label here; nothing is sent)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    #[default]
+    Webhook,
+    Slack,
+}
+
+/// Which way a transition must move
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    #[default]
+    Escalation,
+    DeEscalation,
+    Any,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationRule {
+    pub name: String,
+    #[serde(default)]
+    pub channel: Channel,
+    #[serde(default = "default_min_level")]
+    pub min_level: AlertLevel,
+    #[serde(default)]
+    pub direction: Direction,
+    pub min_probability: Option<f64>, // Percent
+    #[serde(default)]
+    pub cooldown_months: u32,
+    pub triggers: Option<Vec<Component>>,
+}
+
+fn default_min_level() -> AlertLevel {
+    AlertLevel::Warning
+}
+
+impl NotificationRule {
+    fn matches(&self, t: &AlertTransition) -> bool {
+        let level = self.min_level.threshold();
+        let (from, to) = (t.from.threshold(), t.to.threshold());
+        let escalation = from < level && to >= level;
+        let de_escalation = from >= level && to < level;
+        let moved = match self.direction {
+            Direction::Escalation => escalation,
+            Direction::DeEscalation => de_escalation,
+            Direction::Any => escalation || de_escalation,
+        };
+        moved
+            && self.min_probability.is_none_or(|p| t.recession_probability * 100.0 >= p)
+            && self.triggers.as_ref().is_none_or(|c| c.contains(&t.trigger))
+    }
+}
+
+/// Check a rule set: 1-MAX_RULES rules with unique names and percent thresholds
+pub fn validate(rules: &[NotificationRule]) -> Result<(), String> {
+    if rules.is_empty() || rules.len() > MAX_RULES {
+        return Err(format!("between 1 and {} rules required", MAX_RULES));
+    }
+    let mut names = HashSet::new();
+    for rule in rules {
+        if rule.name.is_empty() || rule.name.len() > MAX_RULE_NAME_LEN {
+            return Err(format!("rule name '{}' must be 1-{} characters", rule.name, MAX_RULE_NAME_LEN));
+        }
+        if !names.insert(rule.name.as_str()) {
+            return Err(format!("duplicate rule name '{}'", rule.name));
+        }
+        if rule.min_probability.is_some_and(|p| !(0.0..=100.0).contains(&p)) {
+            return Err(format!("rule '{}': min_probability must be a percentage between 0 and 100", rule.name));
+        }
+    }
+    Ok(())
+}
+
+/// A notification that would have been sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct Firing {
+    pub rule: String,
+    pub channel: Channel,
+    pub date: NaiveDate,
+    pub from: AlertLevel,
+    pub to: AlertLevel,
+    pub recession_probability: f64,
+    pub trigger: Component,
+}
+
+/// One rule's dry-run outcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleOutcome {
+    pub rule: String,
+    pub fired: usize,
+    pub suppressed: usize, // Matches inside the cooldown
+    pub last_fired: Option<NaiveDate>,
+}
+
+/// Evaluate `rules` over `transitions` (oldest first): per-rule totals and
+/// every firing in date order
+pub fn dry_run(rules: &[NotificationRule], transitions: &[AlertTransition]) -> (Vec<RuleOutcome>, Vec<Firing>) {
+    let mut timeline = Vec::new();
+    let outcomes = rules
+        .iter()
+        .map(|rule| {
+            let mut outcome = RuleOutcome { rule: rule.name.clone(), fired: 0, suppressed: 0, last_fired: None };
+            for t in transitions.iter().filter(|t| rule.matches(t)) {
+                let cooling = outcome
+                    .last_fired
+                    .is_some_and(|last| months_between(last, t.date) < rule.cooldown_months as i32);
+                if cooling {
+                    outcome.suppressed += 1;
+                    continue;
+                }
+                outcome.fired += 1;
+                outcome.last_fired = Some(t.date);
+                timeline.push(Firing {
+                    rule: rule.name.clone(),
+                    channel: rule.channel,
+                    date: t.date,
+                    from: t.from,
+                    to: t.to,
+                    recession_probability: t.recession_probability,
+                    trigger: t.trigger,
+                });
+            }
+            outcome
+        })
+        .collect();
+    timeline.sort_by_key(|f| f.date);
+    (outcomes, timeline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(month: u32, from: AlertLevel, to: AlertLevel, p: f64) -> AlertTransition {
+        AlertTransition {
+            date: NaiveDate::from_ymd_opt(2008, month, 1).unwrap(),
+            from,
+            to,
+            recession_probability: p,
+            trigger: Component::Drag,
+            trigger_delta: -1.0,
+        }
+    }
+
+    fn rule(json: &str) -> NotificationRule {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_rules_match_direction_and_cooldown() {
+        use AlertLevel::*;
+        let transitions = [
+            transition(1, Normal, Elevated, 0.35),
+            transition(2, Elevated, Warning, 0.55),
+            transition(3, Warning, Elevated, 0.45),
+            transition(4, Elevated, Critical, 0.75),
+            transition(9, Critical, Normal, 0.10),
+        ];
+        let rules = [
+            rule(r#"{"name":"up"}"#),
+            rule(r#"{"name":"up-cooled","cooldown_months":6}"#),
+            rule(r#"{"name":"down","direction":"de_escalation","channel":"slack"}"#),
+            rule(r#"{"name":"strong","direction":"any","min_probability":70,"triggers":["drag"]}"#),
+            rule(r#"{"name":"thrust","triggers":["thrust"]}"#),
+        ];
+        let (outcomes, timeline) = dry_run(&rules, &transitions);
+        let fired: Vec<(usize, usize)> = outcomes.iter().map(|o| (o.fired, o.suppressed)).collect();
+
+        assert_eq!(fired, [(2, 0), (1, 1), (2, 0), (1, 0), (0, 0)]);
+        assert!(timeline.windows(2).all(|w| w[0].date <= w[1].date));
+        assert_eq!(timeline.iter().filter(|f| f.channel == Channel::Slack).count(), 2);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(&[rule(r#"{"name":"a"}"#)]).is_ok());
+        assert!(validate(&[]).is_err());
+        assert!(validate(&[rule(r#"{"name":"a"}"#), rule(r#"{"name":"a"}"#)]).is_err());
+        assert!(validate(&[rule(r#"{"name":"a","min_probability":150}"#)]).is_err());
+        assert!(serde_json::from_str::<NotificationRule>(r#"{"name":"a","level":"warning"}"#).is_err());
+    }
+}
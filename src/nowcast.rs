@@ -0,0 +1,138 @@
+//! Monthly GDP Nowcast
+//!
+//! Real GDP (GDPC1) is quarterly and published about a month after the
+//! quarter ends, so without a proxy the efficiency denominator carries the
+//! last release forward for up to four months. Between releases the last
+//! published quarter is extended with a blend of monthly activity series:
+//!
+//!   GDP_t = GDP_q × (IP_t / IP_q)^w_ip × (RS_t / RS_q)^w_rs
+//!
+//! - IP: Industrial Production (INDPRO)
+//! - RS: Real Retail Sales (RRSFS)
+//! - `_q`: the proxy's average over the last published quarter
+//!
+//! Months with only one proxy available use it alone (weights renormalised).
+//! Results computed from the nowcast are flagged via `NIVComponents::gdp_nowcast`.
+
+use chrono::{Months, NaiveDate};
+
+use crate::niv::EconomicData;
+
+/// Blend weights (roughly goods vs consumption shares of monthly activity)
+pub const IP_WEIGHT: f64 = 0.5;
+pub const RETAIL_WEIGHT: f64 = 0.5;
+
+/// Monthly proxy series, date-sorted
+#[derive(Debug, Clone, Default)]
+pub struct Proxies {
+    pub industrial_production: Vec<(NaiveDate, f64)>,
+    pub retail_sales: Vec<(NaiveDate, f64)>,
+}
+
+impl Proxies {
+    fn weighted(&self) -> [(&[(NaiveDate, f64)], f64); 2] {
+        [
+            (&self.industrial_production, IP_WEIGHT),
+            (&self.retail_sales, RETAIL_WEIGHT),
+        ]
+    }
+}
+
+/// Mean of the observations in [from, to)
+fn average(series: &[(NaiveDate, f64)], from: NaiveDate, to: NaiveDate) -> Option<f64> {
+    let values: Vec<f64> = series
+        .iter()
+        .filter(|(d, v)| *d >= from && *d < to && *v > 0.0)
+        .map(|(_, v)| *v)
+        .collect();
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn value_at(series: &[(NaiveDate, f64)], date: NaiveDate) -> Option<f64> {
+    series
+        .binary_search_by_key(&date, |(d, _)| *d)
+        .ok()
+        .map(|i| series[i].1)
+        .filter(|v| *v > 0.0)
+}
+
+/// Set `gdp_nowcast` on months after the latest quarterly release
+///
+/// `releases` are GDPC1 observations (dated at the start of their quarter).
+/// Returns the number of months nowcast.
+pub fn fill(data: &mut [EconomicData], releases: &[(NaiveDate, f64)], proxies: &Proxies) -> usize {
+    let Some(&(quarter_start, anchor_gdp)) = releases.iter().max_by_key(|(d, _)| *d) else {
+        return 0;
+    };
+    let Some(quarter_end) = quarter_start.checked_add_months(Months::new(3)) else {
+        return 0;
+    };
+    let anchors: Vec<_> = proxies
+        .weighted()
+        .into_iter()
+        .map(|(series, weight)| (series, weight, average(series, quarter_start, quarter_end)))
+        .collect();
+
+    let mut filled = 0;
+    for d in data.iter_mut().filter(|d| d.date >= quarter_end) {
+        let (mut log_growth, mut total_weight) = (0.0, 0.0);
+        for &(series, weight, anchor) in &anchors {
+            if let (Some(anchor), Some(current)) = (anchor, value_at(series, d.date)) {
+                log_growth += weight * (current / anchor).ln();
+                total_weight += weight;
+            }
+        }
+        if total_weight > 0.0 {
+            d.gdp_nowcast = Some(anchor_gdp * (log_growth / total_weight).exp());
+            filled += 1;
+        }
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    fn month(year: i32, month: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, 1).unwrap()
+    }
+
+    #[test]
+    fn test_fill_extends_last_quarter_with_proxies() {
+        let mut data: Vec<_> = mock::generate_mock_data(2024, 2024);
+        let releases = [(month(2024, 1), 22_000.0), (month(2024, 4), 22_200.0)];
+        // Flat through Q2, then IP +2% and retail sales +4% in July
+        let flat = |level: f64, july: f64| -> Vec<(NaiveDate, f64)> {
+            (1..=12).map(|m| (month(2024, m), if m >= 7 { july } else { level })).collect()
+        };
+        let proxies = Proxies {
+            industrial_production: flat(100.0, 102.0),
+            retail_sales: flat(200.0, 208.0),
+        };
+
+        let filled = fill(&mut data, &releases, &proxies);
+        assert_eq!(filled, 6);
+        assert!(data.iter().filter(|d| d.date < month(2024, 7)).all(|d| d.gdp_nowcast.is_none()));
+
+        let july = data.iter().find(|d| d.date == month(2024, 7)).unwrap();
+        let expected = 22_200.0 * (0.5 * 1.02f64.ln() + 0.5 * 1.04f64.ln()).exp();
+        assert!((july.gdp_nowcast.unwrap() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fill_uses_available_proxy_alone() {
+        let mut data: Vec<_> = mock::generate_mock_data(2024, 2024);
+        let releases = [(month(2024, 4), 22_200.0)];
+        let proxies = Proxies {
+            industrial_production: (4..=8).map(|m| (month(2024, m), 100.0 + (m as f64 - 5.0))).collect(),
+            retail_sales: Vec::new(),
+        };
+
+        assert_eq!(fill(&mut data, &releases, &proxies), 2);
+        let august = data.iter().find(|d| d.date == month(2024, 8)).unwrap();
+        assert!((august.gdp_nowcast.unwrap() - 22_200.0 * 1.03).abs() < 1e-6);
+        assert_eq!(fill(&mut data, &[], &proxies), 0);
+    }
+}
@@ -0,0 +1,181 @@
+//! Preference Profiles
+//!
+//! Defaults stored per API key's workspace with `PUT /api/v1/preferences`
+//! and applied to requests that leave the parameter out:
+//! - `weights` - engine weights for simulate
+//! - `probability_units` - units of every JSON response (see format.rs)
+//! - `smooth` - history smoothing window
+//! - `scenarios` - favourite Monte Carlo scenarios by name, run with
+//!   `"favorite": "<name>"` in place of `scenario`
+//!
+//! ```json
+//! { "probability_units": "fraction", "smooth": 6,
+//!   "scenarios": { "hike": { "shocks": [{ "target": "fed_funds_rate", "magnitude": 1.0 }] } } }
+//! ```
+//!
+//! Requests without a key share the `default` workspace and cannot store a
+//! profile. With NIV_PREFERENCES_FILE set, profiles load from it at startup
+//! and the file (a JSON object of workspace → profile) is rewritten on every
+//! change, off the async runtime, before the change takes effect: a failed
+//! write leaves the stored profile as it was. Otherwise they last until
+//! restart. Share links carry the resolved
+//! parameters, so a link shows everyone the same result.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::niv::{ComponentWeights, MAX_SMOOTH_WINDOW};
+use crate::scenario::Scenario;
+use crate::tenancy::Workspace;
+use crate::format::ProbabilityUnits;
+
+pub const MAX_FAVORITES: usize = 20;
+const MAX_FAVORITE_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preferences {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weights: Option<ComponentWeights>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probability_units: Option<ProbabilityUnits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smooth: Option<usize>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub scenarios: BTreeMap<String, Scenario>,
+}
+
+impl Preferences {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.weights.is_some_and(|w| !w.is_finite()) {
+            return Err("weights must be finite numbers".to_string());
+        }
+        if self.smooth.is_some_and(|s| s == 0 || s > MAX_SMOOTH_WINDOW) {
+            return Err(format!("smooth must be between 1 and {} months", MAX_SMOOTH_WINDOW));
+        }
+        if self.scenarios.len() > MAX_FAVORITES {
+            return Err(format!("at most {} favorite scenarios", MAX_FAVORITES));
+        }
+        if let Some(name) = self.scenarios.keys().find(|n| n.is_empty() || n.len() > MAX_FAVORITE_NAME_LEN) {
+            return Err(format!("favorite name '{}' must be 1-{} characters", name, MAX_FAVORITE_NAME_LEN));
+        }
+        Ok(())
+    }
+}
+
+/// Profiles by workspace, persisted to NIV_PREFERENCES_FILE when set
+#[derive(Debug, Default)]
+pub struct PreferenceStore {
+    path: Option<PathBuf>,
+    profiles: RwLock<HashMap<Workspace, Preferences>>,
+    writer: tokio::sync::Mutex<()>, // One change at a time, so the file and the map agree
+}
+
+impl PreferenceStore {
+    /// Profiles from `path` (none when the file does not exist yet)
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let profiles: BTreeMap<String, Preferences> = match std::fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).map_err(|e| format!("{}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        for (workspace, prefs) in &profiles {
+            prefs.validate().map_err(|e| format!("{}: {}: {}", path.display(), workspace, e))?;
+        }
+        Ok(Self {
+            path: Some(path),
+            profiles: RwLock::new(profiles.into_iter().map(|(w, p)| (Workspace(w), p)).collect()),
+            writer: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.profiles.read().expect("preferences lock").len()
+    }
+
+    /// The workspace's profile (empty when none is stored)
+    pub fn get(&self, workspace: &Workspace) -> Preferences {
+        self.profiles.read().expect("preferences lock").get(workspace).cloned().unwrap_or_default()
+    }
+
+    /// Replace the workspace's profile; None removes it. With a file, the
+    /// change is written first and applied only once that succeeds
+    pub async fn set(&self, workspace: &Workspace, prefs: Option<Preferences>) -> Result<(), String> {
+        let _writing = self.writer.lock().await;
+        if let Some(path) = &self.path {
+            let json = {
+                let profiles = self.profiles.read().expect("preferences lock");
+                let mut sorted: BTreeMap<&str, &Preferences> = profiles.iter().map(|(w, p)| (w.0.as_str(), p)).collect();
+                match &prefs {
+                    Some(prefs) => sorted.insert(workspace.0.as_str(), prefs),
+                    None => sorted.remove(workspace.0.as_str()),
+                };
+                serde_json::to_vec_pretty(&sorted).map_err(|e| e.to_string())?
+            };
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || {
+                let staging = path.with_extension("tmp");
+                std::fs::write(&staging, json)
+                    .and_then(|()| std::fs::rename(&staging, &path))
+                    .map_err(|e| format!("{}: {}", path.display(), e))
+            })
+            .await
+            .map_err(|e| e.to_string())??;
+        }
+        let mut profiles = self.profiles.write().expect("preferences lock");
+        match prefs {
+            Some(prefs) => profiles.insert(workspace.clone(), prefs),
+            None => profiles.remove(workspace),
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let parse = |raw: &str| serde_json::from_str::<Preferences>(raw);
+        assert!(parse(r#"{"smooth":6,"probability_units":"fraction","scenarios":{"base":{}}}"#).unwrap().validate().is_ok());
+        assert!(parse(r#"{"smooth":0}"#).unwrap().validate().is_err());
+        assert!(parse(r#"{"scenarios":{"":{}}}"#).unwrap().validate().is_err());
+        assert!(parse(r#"{"probability_units":"basis_points"}"#).is_err());
+        assert!(parse(r#"{"precision":4}"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_store_round_trip() {
+        let path = std::env::temp_dir().join(format!("niv-preferences-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let desk = Workspace("desk".into());
+        let prefs = Preferences { smooth: Some(6), ..Preferences::default() };
+
+        let store = PreferenceStore::load(path.clone()).unwrap();
+        assert_eq!(store.len(), 0);
+        store.set(&desk, Some(prefs)).await.unwrap();
+        let reloaded = PreferenceStore::load(path.clone()).unwrap();
+        assert_eq!(reloaded.get(&desk).smooth, Some(6));
+        assert_eq!(reloaded.get(&Workspace::default()).smooth, None);
+
+        reloaded.set(&desk, None).await.unwrap();
+        assert_eq!(PreferenceStore::load(path.clone()).unwrap().len(), 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_keeps_the_profile() {
+        let dir = std::env::temp_dir().join(format!("niv-preferences-missing-{}", std::process::id()));
+        let store = PreferenceStore::load(dir.join("preferences.json")).unwrap();
+        let desk = Workspace("desk".into());
+
+        // The directory does not exist, so the write fails
+        assert!(store.set(&desk, Some(Preferences { smooth: Some(6), ..Preferences::default() })).await.is_err());
+        assert_eq!(store.get(&desk).smooth, None);
+        assert_eq!(store.len(), 0);
+    }
+}
@@ -0,0 +1,164 @@
+//! Benchmark and Tracking Series
+//!
+//! The NIV is published as two products, selected with `series=` on
+//! GET /api/v1/history and GET /api/v1/latest:
+//!
+//! | Series      | Frequency | GDP denominator | Dated |
+//! |-------------|-----------|-----------------|-------|
+//! | `benchmark` | quarterly | reported GDPC1 (`GdpSpec::Reported`) | first day of the quarter, as GDPC1 |
+//! | `tracking`  | monthly   | the proxy nowcast between releases (`GdpSpec::Nowcast`) | each month |
+//!
+//! A benchmark quarter is the quarter's final month of the reported-GDP
+//! series, published once its GDP is released: all three months are in and
+//! none of them runs past the latest GDPC1 release (a nowcast month, see
+//! nowcast.rs) or estimates GDP on the ragged edge (coverage.rs). The
+//! tracking series covers every month and is revised into the benchmark as
+//! releases arrive. Months without a nowcast (no proxies fetched) use
+//! reported GDP, so without live FRED data the tracking series equals the
+//! reported monthly series.
+//!
+//! Every other engine parameter follows the serving engine. Without
+//! `series`, the endpoints serve the configured monthly series
+//! (NIV_GDP_SPEC) as before.
+//!
+//! A benchmark point is dated at its quarter's start but carries the
+//! quarter's final month, so anything cut off at a month (the public
+//! tier's embargo) goes by `covers_through`: a quarter appears only once
+//! its final month does.
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::niv::{EconomicData, GdpSpec, InputSeries, NIVEngine, NIVResult, SMOOTH_WINDOW};
+
+/// An official NIV product
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Product {
+    Benchmark,
+    Tracking,
+}
+
+impl Product {
+    /// Cache item holding the product's series (pinned with "series")
+    pub fn item(&self) -> &'static str {
+        match self {
+            Product::Benchmark => "series:benchmark",
+            Product::Tracking => "series:tracking",
+        }
+    }
+
+    pub fn gdp_spec(&self) -> GdpSpec {
+        match self {
+            Product::Benchmark => GdpSpec::Reported,
+            Product::Tracking => GdpSpec::Nowcast,
+        }
+    }
+
+    /// Last month a point dated `date` draws on
+    pub fn covers_through(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Product::Benchmark => date.checked_add_months(chrono::Months::new(2)).unwrap_or(date),
+            Product::Tracking => date,
+        }
+    }
+
+    /// The points of `results` that draw on nothing after `last`
+    pub fn through(&self, results: &[NIVResult], last: NaiveDate) -> Vec<NIVResult> {
+        results.iter().filter(|r| self.covers_through(r.date) <= last).cloned().collect()
+    }
+
+    /// The product's series over `inputs`, with `serving`'s other parameters
+    pub fn compute(&self, serving: &NIVEngine, inputs: &[EconomicData]) -> Vec<NIVResult> {
+        let engine = serving.clone().with_gdp_spec(self.gdp_spec());
+        let monthly = engine.smooth(&engine.calculate_raw(inputs), SMOOTH_WINDOW);
+        match self {
+            Product::Benchmark => quarterly(&monthly, inputs),
+            Product::Tracking => monthly,
+        }
+    }
+}
+
+/// First day of `date`'s quarter
+fn quarter_start(date: NaiveDate) -> NaiveDate {
+    NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1).expect("valid quarter start")
+}
+
+/// One result per released quarter of `monthly`: its final month, dated
+/// at the quarter start
+fn quarterly(monthly: &[NIVResult], inputs: &[EconomicData]) -> Vec<NIVResult> {
+    let unreleased: Vec<NaiveDate> = inputs
+        .iter()
+        .filter(|d| d.gdp_nowcast.is_some() || d.estimated.contains(&InputSeries::Gdp))
+        .map(|d| quarter_start(d.date))
+        .collect();
+    monthly
+        .chunk_by(|a, b| quarter_start(a.date) == quarter_start(b.date))
+        .filter(|months| months.len() == 3 && !unreleased.contains(&quarter_start(months[0].date)))
+        .map(|months| NIVResult { date: quarter_start(months[0].date), ..months[2].clone() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    fn date(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    #[test]
+    fn test_benchmark_is_released_quarters_of_reported_series() {
+        let mut inputs = mock::generate_mock_data(2000, 2010);
+        inputs.retain(|d| d.date <= date(2010, 8)); // Q3 2010 incomplete
+        let engine = NIVEngine::new();
+        let monthly = engine.calculate_series(&inputs);
+        let benchmark = Product::Benchmark.compute(&engine, &inputs);
+
+        assert_eq!(benchmark.first().unwrap().date, date(2001, 1));
+        assert_eq!(benchmark.last().unwrap().date, date(2010, 4));
+        assert!(benchmark.iter().all(|r| r.date.month() % 3 == 1));
+        let june = monthly.iter().find(|r| r.date == date(2010, 6)).unwrap();
+        assert_eq!(benchmark.last().unwrap().niv_score, june.niv_score);
+
+        // A nowcast month keeps its quarter out until GDP is released
+        inputs.iter_mut().filter(|d| d.date >= date(2010, 4)).for_each(|d| d.gdp_nowcast = Some(d.gdp));
+        let benchmark = Product::Benchmark.compute(&engine, &inputs);
+        assert_eq!(benchmark.last().unwrap().date, date(2010, 1));
+    }
+
+    #[test]
+    fn test_cut_off_goes_by_the_quarters_final_month() {
+        let inputs = mock::generate_mock_data(2000, 2010);
+        let benchmark = Product::Benchmark.compute(&NIVEngine::new(), &inputs);
+
+        // Q2 2010 is dated April but carries June
+        let through_may = Product::Benchmark.through(&benchmark, date(2010, 5));
+        assert_eq!(through_may.last().unwrap().date, date(2010, 1));
+        let through_june = Product::Benchmark.through(&benchmark, date(2010, 6));
+        assert_eq!(through_june.last().unwrap().date, date(2010, 4));
+
+        let tracking = Product::Tracking.compute(&NIVEngine::new(), &inputs);
+        assert_eq!(Product::Tracking.through(&tracking, date(2010, 5)).last().unwrap().date, date(2010, 5));
+    }
+
+    #[test]
+    fn test_tracking_uses_the_nowcast_between_releases() {
+        let mut inputs = mock::generate_mock_data(2000, 2010);
+        for d in inputs.iter_mut().filter(|d| d.date >= date(2010, 7)) {
+            d.gdp_nowcast = Some(d.gdp * 1.02);
+        }
+        let engine = NIVEngine::new();
+        let reported = engine.calculate_series(&inputs);
+        let tracking = Product::Tracking.compute(&engine, &inputs);
+
+        assert_eq!(tracking.len(), reported.len());
+        let nowcast: Vec<_> = tracking.iter().filter(|r| r.components.gdp_nowcast).map(|r| r.date).collect();
+        assert_eq!((nowcast.first(), nowcast.len()), (Some(&date(2010, 7)), 6));
+        assert_eq!(tracking[0].niv_score, reported[0].niv_score);
+        let efficiency = |results: &[NIVResult]| results.last().unwrap().components.efficiency;
+        assert!(efficiency(&tracking) < efficiency(&reported));
+        assert_eq!(serde_json::from_str::<Product>(r#""benchmark""#).unwrap(), Product::Benchmark);
+    }
+}
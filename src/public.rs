@@ -0,0 +1,114 @@
+//! Public Embed Tier
+//!
+//! With NIV_PUBLIC_EMBARGO_HOURS set, requests without an API key get a
+//! coarsened view: the recession probability rounded to BUCKET_POINTS
+//! percentage points, and each installed dataset only once it has been
+//! installed for the embargo. Requests with a key see full precision and
+//! every refresh immediately, so a deployment can give the embed away and
+//! sell the keys.
+//!
+//! Keyless requests may only reach the routes in PUBLIC_ROUTES, each of
+//! which serves the coarsened view: latest, history (default smoothing, no
+//! forecast; `series=` products only through the embargoed month), export,
+//! and the widget, whose `update` events arrive once the embargo has passed.
+//! Every other data route answers 401 API_KEY_REQUIRED.
+//!
+//! The first dataset after startup is public at once; there is no older
+//! one to fall back to.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::niv::NIVResult;
+
+/// Public probabilities are multiples of this many percentage points
+pub const BUCKET_POINTS: f64 = 5.0;
+
+/// A 0-1 probability rounded to the nearest bucket
+pub fn bucket(p: f64) -> f64 {
+    let step = BUCKET_POINTS / 100.0;
+    ((p / step).round() * step).clamp(0.0, 1.0)
+}
+
+/// Data routes a keyless request may reach while the tier is on
+pub const PUBLIC_ROUTES: [&str; 5] = ["/api/v1/latest", "/api/v1/history", "/api/v1/export", "/widget", "/widget/events"];
+
+/// A bucketed dataset and when it was installed
+type Install = (DateTime<Utc>, Arc<Vec<NIVResult>>);
+
+/// Bucketed copies of recent datasets, oldest first
+pub struct PublicTier {
+    embargo: Duration,
+    installs: Mutex<VecDeque<Install>>,
+}
+
+impl PublicTier {
+    pub fn new(embargo: Duration) -> Self {
+        Self { embargo, installs: Mutex::new(VecDeque::new()) }
+    }
+
+    /// How long an installed dataset stays private
+    pub fn embargo(&self) -> Duration {
+        self.embargo
+    }
+
+    /// Keep a coarsened copy of a dataset installed at `at`
+    pub fn record(&self, at: DateTime<Utc>, results: &[NIVResult]) {
+        let coarse = results
+            .iter()
+            .map(|r| NIVResult { recession_probability: bucket(r.recession_probability), ..r.clone() })
+            .collect();
+        let mut installs = self.installs.lock().expect("public tier lock");
+        installs.push_back((at, Arc::new(coarse)));
+        // Older entries are superseded once a later one is past the embargo
+        while installs.get(1).is_some_and(|(next, _)| *next <= at - self.embargo) {
+            installs.pop_front();
+        }
+    }
+
+    /// The newest dataset past its embargo at `now` (the oldest kept before any is)
+    pub fn visible(&self, now: DateTime<Utc>) -> Option<Arc<Vec<NIVResult>>> {
+        let installs = self.installs.lock().expect("public tier lock");
+        installs
+            .iter()
+            .rev()
+            .find(|(at, _)| *at <= now - self.embargo)
+            .or(installs.front())
+            .map(|(_, results)| results.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_bucket() {
+        assert_eq!(bucket(0.0), 0.0);
+        assert!((bucket(0.1249) - 0.10).abs() < 1e-12);
+        assert!((bucket(0.1251) - 0.15).abs() < 1e-12);
+        assert!((bucket(0.99) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_embargo_delays_installs() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2000, 2010));
+        let (older, newer) = (&results[..results.len() - 1], &results[..]);
+        let tier = PublicTier::new(Duration::hours(24));
+        let t0 = Utc::now();
+
+        tier.record(t0, older);
+        assert_eq!(tier.visible(t0).unwrap().len(), older.len()); // Startup dataset is public at once
+        tier.record(t0 + Duration::hours(1), newer);
+        assert_eq!(tier.visible(t0 + Duration::hours(2)).unwrap().len(), older.len());
+        assert_eq!(tier.visible(t0 + Duration::hours(25)).unwrap().len(), newer.len());
+
+        let step = BUCKET_POINTS / 100.0;
+        let visible = tier.visible(t0 + Duration::hours(25)).unwrap();
+        assert!(visible.iter().all(|r| ((r.recession_probability / step) - (r.recession_probability / step).round()).abs() < 1e-9));
+    }
+}
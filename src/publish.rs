@@ -0,0 +1,74 @@
+//! Static Site Bundle
+//!
+//! `niv-engine publish --out dir/` computes the dataset once, writes the
+//! read-only endpoints as pre-rendered JSON and exits, so the public site can
+//! be served from a CDN with the API left to handle simulations:
+//!
+//! | File            | Endpoint                 |
+//! |-----------------|--------------------------|
+//! | latest.json     | GET /api/v1/latest       |
+//! | history.json    | GET /api/v1/history (full history) |
+//! | compare.json    | GET /api/v1/compare      |
+//! | recessions.json | GET /api/v1/recessions   |
+//! | pipeline.json   | GET /api/v1/pipeline     |
+//!
+//! The files are produced by the same handlers as the API, so the shapes match.
+//! pipeline.json carries the pipeline hash, so a reader of the published site
+//! can check it against the API (X-NIV-Pipeline) or another publication.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Bundle file names, in write order
+pub const FILES: [&str; 5] = ["latest.json", "history.json", "compare.json", "recessions.json", "pipeline.json"];
+
+/// Output directory when invoked as `publish --out <dir>`, None to serve
+pub fn parse_args(args: &[String]) -> Result<Option<PathBuf>, String> {
+    match args {
+        [] => Ok(None),
+        [command, rest @ ..] if command == "publish" => match rest {
+            [flag, dir] if flag == "--out" => Ok(Some(PathBuf::from(dir))),
+            [flag] if flag.starts_with("--out=") => Ok(Some(PathBuf::from(&flag["--out=".len()..]))),
+            _ => Err("usage: niv-engine publish --out <dir>".to_string()),
+        },
+        [other, ..] => Err(format!("unknown command '{}' (expected: publish --out <dir>)", other)),
+    }
+}
+
+/// Write `value` as JSON (compact, like the API) to `dir/name`, creating `dir` if needed
+pub fn write_json(dir: &Path, name: &str, value: &impl Serialize) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let path = dir.join(name);
+    let json = serde_json::to_vec(value).map_err(|e| format!("{}: {}", name, e))?;
+    std::fs::write(&path, json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(&[]), Ok(None));
+        assert_eq!(parse_args(&args(&["publish", "--out", "site/"])), Ok(Some(PathBuf::from("site/"))));
+        assert_eq!(parse_args(&args(&["publish", "--out=dist"])), Ok(Some(PathBuf::from("dist"))));
+        assert!(parse_args(&args(&["publish"])).is_err());
+        assert!(parse_args(&args(&["serve"])).is_err());
+    }
+
+    #[test]
+    fn test_write_json_creates_directory() {
+        let dir = std::env::temp_dir().join(format!("niv-publish-{}", std::process::id())).join("nested");
+        let path = write_json(&dir, "x.json", &serde_json::json!({ "a": [1, 2] })).unwrap();
+
+        let back: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(back["a"][1], 2);
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,282 @@
+//! Low-Discrepancy Sampling
+//!
+//! Standard-normal draws for the Monte Carlo, one vector per draw:
+//! - `pseudo`: StdRng, the original behaviour (same draws for the same seed)
+//! - `halton`: radical inverses in the first `dims` primes, with seeded digit
+//!   permutations so high dimensions do not correlate
+//! - `sobol`: base-2 digital sequence; direction numbers come from primitive
+//!   polynomials found by search, with odd initial values drawn from a fixed
+//!   seed, and each run applies a seeded digital shift
+//!
+//! Uniforms map to normals through the inverse CDF. Quasi-random dimensions
+//! are laid out month-major, so the early months that every later month
+//! depends on get the best-distributed coordinates.
+
+use std::sync::OnceLock;
+
+use rand::distributions::Distribution;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ContinuousCDF, Normal};
+
+use crate::requests::MAX_MC_HORIZON;
+use crate::scenario::ShockTarget;
+
+/// Seed for the Sobol initial direction numbers (fixed: the sequence itself
+/// must not change between runs, only its shift)
+const DIRECTION_SEED: u64 = 0x5EED_50B0;
+
+/// Enough Sobol dimensions for every shocked series over the longest horizon
+const MAX_SOBOL_DIMS: usize = ShockTarget::COUNT * MAX_MC_HORIZON;
+
+const BITS: usize = 32;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Sampling {
+    #[default]
+    Pseudo,
+    Halton,
+    Sobol,
+}
+
+impl Sampling {
+    /// Largest dimension count supported
+    pub fn max_dims(&self) -> usize {
+        match self {
+            Sampling::Sobol => MAX_SOBOL_DIMS,
+            _ => usize::MAX,
+        }
+    }
+}
+
+/// More dimensions requested than the sequence supports
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TooManyDimensions {
+    pub sampling: Sampling,
+    pub dims: usize,
+    pub max: usize,
+}
+
+impl std::fmt::Display for TooManyDimensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} sampling supports at most {} dimensions, {} requested", self.sampling, self.max, self.dims)
+    }
+}
+
+impl std::error::Error for TooManyDimensions {}
+
+/// Primitive polynomials over GF(2) in increasing degree, as bit masks
+/// (bit s = x^s ... bit 0 = 1); x + 1 first
+fn primitive_polynomials(count: usize) -> Vec<(u32, u32)> {
+    let mut found = Vec::with_capacity(count);
+    let mut degree = 1;
+    while found.len() < count {
+        let period = (1u64 << degree) - 1;
+        for middle in 0..(1u32 << (degree - 1)) {
+            let poly = (1 << degree) | (middle << 1) | 1;
+            // Primitive iff x has multiplicative order 2^degree - 1 modulo poly
+            let mut r = 1u32;
+            let mut order = 0u64;
+            loop {
+                r <<= 1;
+                if r & (1 << degree) != 0 {
+                    r ^= poly;
+                }
+                order += 1;
+                if r == 1 || order > period {
+                    break;
+                }
+            }
+            if r == 1 && order == period {
+                found.push((degree, poly));
+                if found.len() == count {
+                    break;
+                }
+            }
+        }
+        degree += 1;
+    }
+    found
+}
+
+/// Direction numbers for MAX_SOBOL_DIMS dimensions
+fn sobol_directions() -> &'static [[u32; BITS]] {
+    static DIRECTIONS: OnceLock<Vec<[u32; BITS]>> = OnceLock::new();
+    DIRECTIONS.get_or_init(|| {
+        let mut rng = StdRng::seed_from_u64(DIRECTION_SEED);
+        let mut directions = Vec::with_capacity(MAX_SOBOL_DIMS);
+        directions.push(std::array::from_fn(|k| 1u32 << (BITS - 1 - k)));
+
+        for (degree, poly) in primitive_polynomials(MAX_SOBOL_DIMS - 1) {
+            let s = degree as usize;
+            let mut v = [0u32; BITS];
+            for (k, slot) in v.iter_mut().enumerate().take(s) {
+                let m = rng.gen_range(0..(1u32 << k)) * 2 + 1; // Odd, below 2^(k+1)
+                *slot = m << (BITS - 1 - k);
+            }
+            for k in s..BITS {
+                let mut value = v[k - s] ^ (v[k - s] >> s);
+                for l in 1..s {
+                    if (poly >> (s - l)) & 1 == 1 {
+                        value ^= v[k - l];
+                    }
+                }
+                v[k] = value;
+            }
+            directions.push(v);
+        }
+        directions
+    })
+}
+
+/// First `count` primes
+fn primes(count: usize) -> Vec<u64> {
+    let mut found: Vec<u64> = Vec::with_capacity(count);
+    let mut n = 2;
+    while found.len() < count {
+        if found.iter().take_while(|&&p| p * p <= n).all(|&p| n % p != 0) {
+            found.push(n);
+        }
+        n += 1;
+    }
+    found
+}
+
+enum Source {
+    Pseudo(Box<StdRng>),
+    Halton { bases: Vec<u64>, permutations: Vec<Vec<u64>> },
+    Sobol { shifts: Vec<u32> },
+}
+
+/// Produces one vector of `dims` standard normals per draw
+pub struct Sampler {
+    source: Source,
+    dims: usize,
+    series: usize,
+    index: u64,
+    normal: Normal,
+}
+
+impl Sampler {
+    /// `series` × `months` dimensions; `dims` must not exceed `sampling.max_dims()`
+    pub fn new(sampling: Sampling, series: usize, months: usize, seed: u64) -> Result<Self, TooManyDimensions> {
+        let dims = series * months;
+        if dims > sampling.max_dims() {
+            return Err(TooManyDimensions { sampling, dims, max: sampling.max_dims() });
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let source = match sampling {
+            Sampling::Pseudo => Source::Pseudo(Box::new(rng)),
+            Sampling::Halton => {
+                let bases = primes(dims);
+                let permutations = bases
+                    .iter()
+                    .map(|&b| {
+                        let mut digits: Vec<u64> = (1..b).collect();
+                        digits.shuffle(&mut rng);
+                        std::iter::once(0).chain(digits).collect() // Keep 0 fixed: finite expansions stay finite
+                    })
+                    .collect();
+                Source::Halton { bases, permutations }
+            }
+            Sampling::Sobol => Source::Sobol { shifts: (0..dims).map(|_| rng.gen()).collect() },
+        };
+        Ok(Self { source, dims, series, index: 0, normal: Normal::new(0.0, 1.0).expect("standard normal") })
+    }
+
+    /// Position of (series, month) in a draw
+    pub fn index(&self, series: usize, month: usize) -> usize {
+        match self.source {
+            // Series-major, the order the pseudo-random draws were always consumed in
+            Source::Pseudo(_) => series * (self.dims / self.series.max(1)) + month,
+            _ => month * self.series + series,
+        }
+    }
+
+    /// Next draw
+    pub fn next_draw(&mut self) -> Vec<f64> {
+        self.index += 1; // Skip point 0 (the origin)
+        let n = self.index;
+        let uniform_to_normal = |normal: &Normal, u: f64| normal.inverse_cdf(u.clamp(1e-12, 1.0 - 1e-12));
+        match &mut self.source {
+            Source::Pseudo(rng) => (0..self.dims).map(|_| self.normal.sample(rng.as_mut())).collect(),
+            Source::Halton { bases, permutations } => bases
+                .iter()
+                .zip(permutations.iter())
+                .map(|(&base, perm)| {
+                    let (mut i, mut f, mut u) = (n, 1.0, 0.0);
+                    while i > 0 {
+                        f /= base as f64;
+                        u += perm[(i % base) as usize] as f64 * f;
+                        i /= base;
+                    }
+                    uniform_to_normal(&self.normal, u)
+                })
+                .collect(),
+            Source::Sobol { shifts } => {
+                let directions = sobol_directions();
+                shifts
+                    .iter()
+                    .enumerate()
+                    .map(|(d, &shift)| {
+                        let x = (0..BITS)
+                            .filter(|k| (n >> k) & 1 == 1)
+                            .fold(shift, |acc, k| acc ^ directions[d][k]);
+                        uniform_to_normal(&self.normal, (x as f64 + 0.5) / (1u64 << BITS) as f64)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitive_polynomials_and_sobol_first_dimension() {
+        // x+1, x²+x+1, x³+x+1, x³+x²+1
+        assert_eq!(primitive_polynomials(4), vec![(1, 0b11), (2, 0b111), (3, 0b1011), (3, 0b1101)]);
+        assert_eq!(primitive_polynomials(MAX_SOBOL_DIMS).len(), MAX_SOBOL_DIMS);
+
+        // Unshifted first dimension is the van der Corput sequence: 1/2, 1/4, 3/4, ...
+        let v = sobol_directions()[0];
+        let point = |n: u64| (0..BITS).filter(|k| (n >> k) & 1 == 1).fold(0u32, |acc, k| acc ^ v[k]);
+        assert_eq!([point(1), point(2), point(3)], [1 << 31, 1 << 30, 3 << 30]);
+    }
+
+    #[test]
+    fn test_quasi_random_means_beat_pseudo() {
+        // Mean of each coordinate over 256 draws: low-discrepancy error is far smaller
+        let worst_mean = |sampling| {
+            let mut sampler = Sampler::new(sampling, 7, 12, 3).unwrap();
+            let mut sums = vec![0.0; 84];
+            for _ in 0..256 {
+                for (s, z) in sums.iter_mut().zip(sampler.next_draw()) {
+                    *s += z;
+                }
+            }
+            sums.iter().map(|s| (s / 256.0f64).abs()).fold(0.0, f64::max)
+        };
+        let (pseudo, halton, sobol) = (worst_mean(Sampling::Pseudo), worst_mean(Sampling::Halton), worst_mean(Sampling::Sobol));
+
+        assert!(sobol < pseudo / 2.0, "sobol {} vs pseudo {}", sobol, pseudo);
+        assert!(halton < pseudo, "halton {} vs pseudo {}", halton, pseudo);
+
+        let sampler = Sampler::new(Sampling::Sobol, 7, 12, 3).unwrap();
+        assert_eq!((sampler.index(0, 1), sampler.index(1, 0)), (7, 1));
+        assert_eq!(Sampler::new(Sampling::Pseudo, 7, 12, 3).unwrap().index(1, 0), 12);
+    }
+
+    #[test]
+    fn test_sobol_covers_every_series_over_the_longest_horizon() {
+        assert!(Sampler::new(Sampling::Sobol, ShockTarget::COUNT, MAX_MC_HORIZON, 3).is_ok());
+        let err = Sampler::new(Sampling::Sobol, ShockTarget::COUNT, MAX_MC_HORIZON + 1, 3).err().unwrap();
+        assert_eq!((err.dims, err.max), (MAX_SOBOL_DIMS + ShockTarget::COUNT, MAX_SOBOL_DIMS));
+        assert!(Sampler::new(Sampling::Halton, ShockTarget::COUNT, MAX_MC_HORIZON + 1, 3).is_ok());
+    }
+}
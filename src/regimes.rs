@@ -0,0 +1,367 @@
+//! Economic Regime Clustering
+//!
+//! Groups historical months by their component mix (thrust, efficiency,
+//! slack, drag) over z-scored components:
+//! 1. Standardize each component over the whole history
+//! 2. Seed centroids with k-means++ (fixed seed, so labels are reproducible)
+//! 3. Alternate assignment / update until labels stop changing
+//! 4. `Method::Gmm` only: fit a diagonal-covariance Gaussian mixture by EM,
+//!    starting from the k-means clusters, and label each month with its most
+//!    likely component. Unlike k-means it allows regimes of different spread,
+//!    e.g. a tight expansion cluster beside a diffuse recession one.
+//!
+//! Clusters are renumbered by descending mean NIV score, so regime 0 is the
+//! healthiest mix and the highest id the most stressed.
+//!
+//! `RegimeFilter` restricts backtests to one regime: metrics over its months,
+//! lead times over the recessions entered from it (the month before the
+//! start is in the regime).
+
+use std::collections::HashSet;
+
+use chrono::{Months, NaiveDate};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use statrs::statistics::Statistics;
+
+use crate::backtest;
+use crate::niv::{Component, NIVResult, RecessionPeriods};
+
+/// Clusters fitted by default, and at most
+pub const DEFAULT_CLUSTERS: usize = 4;
+pub const MAX_CLUSTERS: usize = 8;
+
+/// Months ahead for the per-regime recession rate
+pub const OUTCOME_HORIZON_MONTHS: u32 = 12;
+
+const MAX_ITERATIONS: usize = 100;
+const SEED: u64 = 0x4e49_5652;
+
+/// Variance floor for mixture components, in z-score units squared
+const GMM_VARIANCE_FLOOR: f64 = 1e-3;
+
+/// EM stops once the mean log-likelihood improves by less than this
+const GMM_TOLERANCE: f64 = 1e-8;
+
+/// Clustering algorithm
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Method {
+    #[default]
+    Kmeans,
+    Gmm,
+}
+
+/// One regime's centre and track record
+#[derive(Debug, Clone, Serialize)]
+pub struct Regime {
+    pub id: usize,
+    pub months: usize,
+    pub thrust: f64, // Centroid, in component units
+    pub efficiency: f64,
+    pub slack: f64,
+    pub drag: f64,
+    pub mean_niv_score: f64,
+    pub in_recession_share: f64, // Months inside an NBER recession
+    pub recession_within_horizon_share: f64, // Months in or within 12 months before a recession
+}
+
+/// Fitted clusters and the regime of every month
+#[derive(Debug, Clone)]
+pub struct Regimes {
+    pub regimes: Vec<Regime>,
+    pub labels: Vec<usize>, // Parallel to the clustered results
+}
+
+impl Regimes {
+    /// Regime of the last clustered month
+    pub fn current(&self) -> Option<usize> {
+        self.labels.last().copied()
+    }
+}
+
+/// Months of one regime, by date, for restricting backtests to it
+#[derive(Debug, Clone)]
+pub struct RegimeFilter {
+    months: HashSet<NaiveDate>,
+}
+
+impl RegimeFilter {
+    /// `labels` parallel to `results`, as in `Regimes::labels`
+    pub fn new(results: &[NIVResult], labels: &[usize], regime: usize) -> Self {
+        let months = results.iter().zip(labels).filter(|(_, &l)| l == regime).map(|(r, _)| r.date).collect();
+        Self { months }
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.months.contains(&date)
+    }
+
+    /// Recessions entered from the regime: the month before the start is in it
+    pub fn recessions(&self, chronology: &[(NaiveDate, NaiveDate)]) -> Vec<(NaiveDate, NaiveDate)> {
+        chronology
+            .iter()
+            .filter(|(start, _)| start.checked_sub_months(Months::new(1)).is_some_and(|before| self.contains(before)))
+            .copied()
+            .collect()
+    }
+}
+
+fn distance2(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+fn nearest(point: &[f64], centroids: &[Vec<f64>]) -> usize {
+    (0..centroids.len())
+        .min_by(|&i, &j| distance2(point, &centroids[i]).total_cmp(&distance2(point, &centroids[j])))
+        .unwrap_or(0)
+}
+
+/// k-means++ seeding: each new centroid drawn with probability ∝ squared distance
+fn seed_centroids(points: &[Vec<f64>], k: usize, rng: &mut StdRng) -> Vec<Vec<f64>> {
+    let mut centroids = vec![points[rng.gen_range(0..points.len())].clone()];
+    while centroids.len() < k {
+        let weights: Vec<f64> = points
+            .iter()
+            .map(|p| centroids.iter().map(|c| distance2(p, c)).fold(f64::INFINITY, f64::min))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            break; // Fewer distinct points than clusters
+        }
+        let mut target = rng.gen::<f64>() * total;
+        let pick = weights
+            .iter()
+            .position(|w| {
+                target -= w;
+                target <= 0.0
+            })
+            .unwrap_or(points.len() - 1);
+        centroids.push(points[pick].clone());
+    }
+    centroids
+}
+
+/// Lloyd iterations from k-means++ seeds; the centroids and each point's cluster
+fn kmeans(points: &[Vec<f64>], k: usize) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut centroids = seed_centroids(points, k.min(points.len()), &mut rng);
+    let mut labels: Vec<usize> = points.iter().map(|p| nearest(p, &centroids)).collect();
+    for _ in 0..MAX_ITERATIONS {
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&Vec<f64>> = points.iter().zip(&labels).filter(|(_, &l)| l == c).map(|(p, _)| p).collect();
+            if !members.is_empty() {
+                for (d, x) in centroid.iter_mut().enumerate() {
+                    *x = members.iter().map(|p| p[d]).sum::<f64>() / members.len() as f64;
+                }
+            }
+        }
+        let next: Vec<usize> = points.iter().map(|p| nearest(p, &centroids)).collect();
+        if next == labels {
+            break;
+        }
+        labels = next;
+    }
+    (centroids, labels)
+}
+
+/// Diagonal Gaussian mixture fitted by EM from the k-means clusters; each
+/// point's most likely component
+fn gmm(points: &[Vec<f64>], centroids: &[Vec<f64>], labels: &[usize]) -> Vec<usize> {
+    let (n, k, dims) = (points.len(), centroids.len(), centroids.first().map_or(0, Vec::len));
+    let mut means = centroids.to_vec();
+    let mut variances = vec![vec![1.0; dims]; k];
+    let mut weights = vec![1.0 / k as f64; k];
+    for c in 0..k {
+        let members: Vec<&Vec<f64>> = points.iter().zip(labels).filter(|(_, &l)| l == c).map(|(p, _)| p).collect();
+        if !members.is_empty() {
+            weights[c] = members.len() as f64 / n as f64;
+            for d in 0..dims {
+                let var = members.iter().map(|p| (p[d] - means[c][d]).powi(2)).sum::<f64>() / members.len() as f64;
+                variances[c][d] = var.max(GMM_VARIANCE_FLOOR);
+            }
+        }
+    }
+
+    let log_density = |p: &[f64], mean: &[f64], var: &[f64]| -> f64 {
+        (0..dims)
+            .map(|d| -0.5 * ((2.0 * std::f64::consts::PI * var[d]).ln() + (p[d] - mean[d]).powi(2) / var[d]))
+            .sum()
+    };
+    let mut responsibilities = vec![vec![0.0; k]; n];
+    let mut previous = f64::NEG_INFINITY;
+    for _ in 0..MAX_ITERATIONS {
+        // E-step, in log space
+        let mut log_likelihood = 0.0;
+        for (p, resp) in points.iter().zip(responsibilities.iter_mut()) {
+            for c in 0..k {
+                resp[c] = weights[c].ln() + log_density(p, &means[c], &variances[c]);
+            }
+            let max = resp.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let total = max + resp.iter().map(|l| (l - max).exp()).sum::<f64>().ln();
+            for l in resp.iter_mut() {
+                *l = (*l - total).exp();
+            }
+            log_likelihood += total;
+        }
+        let log_likelihood = log_likelihood / n as f64;
+        if log_likelihood - previous < GMM_TOLERANCE {
+            break;
+        }
+        previous = log_likelihood;
+
+        // M-step; a component that lost every point keeps its parameters
+        for c in 0..k {
+            let mass: f64 = responsibilities.iter().map(|r| r[c]).sum();
+            if mass <= f64::EPSILON {
+                continue;
+            }
+            weights[c] = mass / n as f64;
+            for d in 0..dims {
+                let mean = points.iter().zip(&responsibilities).map(|(p, r)| r[c] * p[d]).sum::<f64>() / mass;
+                let var = points.iter().zip(&responsibilities).map(|(p, r)| r[c] * (p[d] - mean).powi(2)).sum::<f64>() / mass;
+                means[c][d] = mean;
+                variances[c][d] = var.max(GMM_VARIANCE_FLOOR);
+            }
+        }
+    }
+
+    responsibilities
+        .iter()
+        .map(|r| (0..k).max_by(|&a, &b| r[a].total_cmp(&r[b])).unwrap_or(0))
+        .collect()
+}
+
+/// Cluster `results` into (at most) `k` regimes
+pub fn cluster(results: &[NIVResult], k: usize, method: Method) -> Regimes {
+    if results.is_empty() || k == 0 {
+        return Regimes { regimes: Vec::new(), labels: Vec::new() };
+    }
+
+    let scales: Vec<(f64, f64)> = Component::all()
+        .into_iter()
+        .map(|component| {
+            let values: Vec<f64> = results.iter().map(|r| r.components.value(component)).collect();
+            let sd = values.iter().std_dev();
+            (values.iter().mean(), if sd.is_finite() && sd > 0.0 { sd } else { 1.0 })
+        })
+        .collect();
+    let points: Vec<Vec<f64>> = results
+        .iter()
+        .map(|r| {
+            Component::all()
+                .into_iter()
+                .zip(&scales)
+                .map(|(component, &(mean, sd))| (r.components.value(component) - mean) / sd)
+                .map(|z| if z.is_finite() { z } else { 0.0 })
+                .collect()
+        })
+        .collect();
+
+    let (centroids, mut labels) = kmeans(&points, k);
+    if method == Method::Gmm {
+        labels = gmm(&points, &centroids, &labels);
+    }
+
+    let dates: Vec<_> = results.iter().map(|r| r.date).collect();
+    let ahead = backtest::recession_labels(&dates, &RecessionPeriods::known_recessions(), OUTCOME_HORIZON_MONTHS);
+    let mut regimes: Vec<Regime> = (0..centroids.len())
+        .filter_map(|c| {
+            let members: Vec<usize> = (0..results.len()).filter(|&i| labels[i] == c).collect();
+            if members.is_empty() {
+                return None;
+            }
+            let n = members.len() as f64;
+            let mean = |f: &dyn Fn(&NIVResult) -> f64| members.iter().map(|&i| f(&results[i])).sum::<f64>() / n;
+            let share = |f: &dyn Fn(usize) -> bool| members.iter().filter(|&&i| f(i)).count() as f64 / n;
+            Some(Regime {
+                id: c,
+                months: members.len(),
+                thrust: mean(&|r| r.components.thrust),
+                efficiency: mean(&|r| r.components.efficiency),
+                slack: mean(&|r| r.components.slack),
+                drag: mean(&|r| r.components.drag),
+                mean_niv_score: mean(&|r| r.niv_score),
+                in_recession_share: share(&|i| RecessionPeriods::is_recession(results[i].date)),
+                recession_within_horizon_share: share(&|i| ahead[i]),
+            })
+        })
+        .collect();
+
+    // Renumber by descending mean NIV score
+    regimes.sort_by(|a, b| b.mean_niv_score.total_cmp(&a.mean_niv_score));
+    let mut renumber = vec![0; centroids.len()];
+    for (new_id, regime) in regimes.iter_mut().enumerate() {
+        renumber[regime.id] = new_id;
+        regime.id = new_id;
+    }
+    let labels = labels.into_iter().map(|l| renumber[l]).collect();
+
+    Regimes { regimes, labels }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_cluster_labels_every_month_and_orders_regimes() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1970, 2024));
+        let fitted = cluster(&results, 4, Method::Kmeans);
+
+        assert_eq!(fitted.labels.len(), results.len());
+        assert_eq!(fitted.regimes.len(), 4);
+        assert_eq!(fitted.regimes.iter().map(|r| r.months).sum::<usize>(), results.len());
+        assert!(fitted.regimes.iter().enumerate().all(|(i, r)| r.id == i));
+        assert!(fitted.regimes.windows(2).all(|w| w[0].mean_niv_score >= w[1].mean_niv_score));
+        assert!(fitted.current().is_some_and(|c| c < 4));
+
+        // The most stressed regime sees recessions more often than the healthiest
+        let (best, worst) = (&fitted.regimes[0], &fitted.regimes[3]);
+        assert!(worst.recession_within_horizon_share >= best.recession_within_horizon_share);
+    }
+
+    #[test]
+    fn test_cluster_is_deterministic_and_handles_small_inputs() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2000, 2010));
+        assert_eq!(cluster(&results, 3, Method::Kmeans).labels, cluster(&results, 3, Method::Kmeans).labels);
+
+        let few = cluster(&results[..2], 5, Method::Kmeans);
+        assert!(few.regimes.len() <= 2);
+        assert!(cluster(&[], 3, Method::Kmeans).labels.is_empty());
+        assert!(cluster(&results[..1], 3, Method::Gmm).labels == vec![0]);
+    }
+
+    #[test]
+    fn test_gmm_labels_every_month_deterministically() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1970, 2024));
+        let fitted = cluster(&results, 4, Method::Gmm);
+
+        assert_eq!(fitted.labels, cluster(&results, 4, Method::Gmm).labels);
+        assert_eq!(fitted.labels.len(), results.len());
+        assert_eq!(fitted.regimes.iter().map(|r| r.months).sum::<usize>(), results.len());
+        assert!(fitted.regimes.windows(2).all(|w| w[0].mean_niv_score >= w[1].mean_niv_score));
+        assert_eq!("\"gmm\"".parse::<serde_json::Value>().ok().and_then(|v| serde_json::from_value(v).ok()), Some(Method::Gmm));
+    }
+
+    #[test]
+    fn test_regime_filter_recessions_entered_from_regime() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1970, 2024));
+        let fitted = cluster(&results, 4, Method::Kmeans);
+        let chronology = RecessionPeriods::known_recessions();
+
+        let mut entered = 0;
+        for regime in 0..fitted.regimes.len() {
+            let filter = RegimeFilter::new(&results, &fitted.labels, regime);
+            let months = results.iter().filter(|r| filter.contains(r.date)).count();
+            assert_eq!(months, fitted.regimes[regime].months);
+            entered += filter.recessions(&chronology).len();
+        }
+        // Every recession after the first clustered month is entered from exactly one regime
+        let first = results[0].date;
+        assert_eq!(entered, chronology.iter().filter(|(start, _)| *start > first).count());
+    }
+}
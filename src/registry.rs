@@ -0,0 +1,647 @@
+//! Custom Component Registry
+//!
+//! Deployments can define extra NIV terms in a small expression DSL without
+//! recompiling. Each component is evaluated over the input series and enters
+//! the master formula in one of two roles:
+//! - `numerator`: multiplies u × P²
+//! - `denominator`: adds to (X + F) before the η exponent
+//!
+//! Months where a term is unavailable (missing optional series, rolling
+//! window not yet filled, division by zero) leave the formula unchanged.
+//!
+//! Grammar:
+//! ```text
+//! expr    := term (('+' | '-') term)*
+//! term    := unary (('*' | '/') unary)*
+//! unary   := '-' unary | power
+//! power   := primary ('^' unary)?
+//! primary := number | series | func '(' args ')' | '(' expr ')'
+//! ```
+//!
+//! Series: every `EconomicData` field (`investment`, `m2_supply`,
+//! `fed_funds_rate`, `gdp`, `capacity_util`, `yield_spread`, `cpi_inflation`,
+//! `rd_investment`, `education_spending`, `potential_gdp`,
+//! `unemployment_rate`, `nairu`, `gdp_nowcast`, `core_cpi_inflation`,
+//! `pce_inflation`, `expected_inflation`).
+//!
+//! Functions: `lag(x, n)`, `diff(x, n)`, `pct_change(x, n)`, `mean(x, n)`,
+//! `std(x, n)`, `rolling_min(x, n)`, `rolling_max(x, n)`, `abs(x)`, `tanh(x)`,
+//! `ln(x)`, `min(a, b)`, `max(a, b)`. Window lengths are integer literals.
+//!
+//! Expressions are at most MAX_EXPR_LEN bytes and nest at most MAX_DEPTH
+//! levels (parentheses, calls, negation and powers), so a request-supplied
+//! expression cannot exhaust the parser's or the evaluator's stack.
+//!
+//! Loaded at startup from the JSON file named by `NIV_COMPONENTS_FILE`:
+//! ```json
+//! { "components": [
+//!     { "name": "housing_drag", "role": "denominator",
+//!       "expr": "max(0, -pct_change(investment, 12)) / 100" }
+//! ] }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use statrs::statistics::Statistics;
+
+use crate::niv::EconomicData;
+
+/// Longest rolling window or lag accepted in expressions
+pub const MAX_WINDOW: usize = 600;
+
+/// Maximum components in one registry
+pub const MAX_COMPONENTS: usize = 32;
+
+/// Longest expression accepted, in bytes
+pub const MAX_EXPR_LEN: usize = 1024;
+
+/// Deepest nesting accepted in an expression
+pub const MAX_DEPTH: usize = 32;
+
+/// Where a custom term enters the master formula
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TermRole {
+    Numerator,
+    Denominator,
+}
+
+impl TermRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TermRole::Numerator => "numerator",
+            TermRole::Denominator => "denominator",
+        }
+    }
+}
+
+/// Component definition as written in config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentDef {
+    pub name: String,
+    pub role: TermRole,
+    pub expr: String,
+}
+
+/// Evaluated custom term for one month
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CustomTerm {
+    pub name: String,
+    pub role: TermRole,
+    pub value: Option<f64>, // None = unavailable this month
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Series {
+    Investment,
+    M2Supply,
+    FedFundsRate,
+    Gdp,
+    CapacityUtil,
+    YieldSpread,
+    CpiInflation,
+    RdInvestment,
+    EducationSpending,
+    PotentialGdp,
+    UnemploymentRate,
+    Nairu,
+    GdpNowcast,
+    CoreCpiInflation,
+    PceInflation,
+    ExpectedInflation,
+}
+
+impl Series {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "investment" => Series::Investment,
+            "m2_supply" => Series::M2Supply,
+            "fed_funds_rate" => Series::FedFundsRate,
+            "gdp" => Series::Gdp,
+            "capacity_util" => Series::CapacityUtil,
+            "yield_spread" => Series::YieldSpread,
+            "cpi_inflation" => Series::CpiInflation,
+            "rd_investment" => Series::RdInvestment,
+            "education_spending" => Series::EducationSpending,
+            "potential_gdp" => Series::PotentialGdp,
+            "unemployment_rate" => Series::UnemploymentRate,
+            "nairu" => Series::Nairu,
+            "gdp_nowcast" => Series::GdpNowcast,
+            "core_cpi_inflation" => Series::CoreCpiInflation,
+            "pce_inflation" => Series::PceInflation,
+            "expected_inflation" => Series::ExpectedInflation,
+            _ => return None,
+        })
+    }
+
+    /// Value for one month; NaN when an optional series is missing
+    fn get(&self, d: &EconomicData) -> f64 {
+        match self {
+            Series::Investment => d.investment,
+            Series::M2Supply => d.m2_supply,
+            Series::FedFundsRate => d.fed_funds_rate,
+            Series::Gdp => d.gdp,
+            Series::CapacityUtil => d.capacity_util,
+            Series::YieldSpread => d.yield_spread,
+            Series::CpiInflation => d.cpi_inflation,
+            Series::RdInvestment => d.rd_investment.unwrap_or(f64::NAN),
+            Series::EducationSpending => d.education_spending.unwrap_or(f64::NAN),
+            Series::PotentialGdp => d.potential_gdp.unwrap_or(f64::NAN),
+            Series::UnemploymentRate => d.unemployment_rate.unwrap_or(f64::NAN),
+            Series::Nairu => d.nairu.unwrap_or(f64::NAN),
+            Series::GdpNowcast => d.gdp_nowcast.unwrap_or(f64::NAN),
+            Series::CoreCpiInflation => d.core_cpi_inflation.unwrap_or(f64::NAN),
+            Series::PceInflation => d.pce_inflation.unwrap_or(f64::NAN),
+            Series::ExpectedInflation => d.expected_inflation.unwrap_or(f64::NAN),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rolling {
+    Lag,
+    Diff,
+    PctChange,
+    Mean,
+    Std,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unary {
+    Neg,
+    Abs,
+    Tanh,
+    Ln,
+}
+
+/// Parsed expression
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Num(f64),
+    Series(Series),
+    Unary(Unary, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Min(Box<Expr>, Box<Expr>),
+    Max(Box<Expr>, Box<Expr>),
+    Rolling(Rolling, Box<Expr>, usize),
+}
+
+impl Expr {
+    /// Evaluate over the whole input, one value per month
+    fn eval(&self, data: &[EconomicData]) -> Vec<f64> {
+        match self {
+            Expr::Num(n) => vec![*n; data.len()],
+            Expr::Series(s) => data.iter().map(|d| s.get(d)).collect(),
+            Expr::Unary(op, e) => {
+                let f = |x: f64| match op {
+                    Unary::Neg => -x,
+                    Unary::Abs => x.abs(),
+                    Unary::Tanh => x.tanh(),
+                    Unary::Ln => x.ln(),
+                };
+                e.eval(data).into_iter().map(f).collect()
+            }
+            Expr::Binary(op, l, r) => {
+                let f = |a: f64, b: f64| match op {
+                    BinOp::Add => a + b,
+                    BinOp::Sub => a - b,
+                    BinOp::Mul => a * b,
+                    BinOp::Div => a / b,
+                    BinOp::Pow => a.powf(b),
+                };
+                l.eval(data).into_iter().zip(r.eval(data)).map(|(a, b)| f(a, b)).collect()
+            }
+            Expr::Min(l, r) => l.eval(data).into_iter().zip(r.eval(data)).map(|(a, b)| a.min(b)).collect(),
+            Expr::Max(l, r) => l.eval(data).into_iter().zip(r.eval(data)).map(|(a, b)| a.max(b)).collect(),
+            Expr::Rolling(op, e, n) => rolling(*op, &e.eval(data), *n),
+        }
+    }
+}
+
+/// Apply a rolling/lag operator; NaN until the window is filled
+fn rolling(op: Rolling, values: &[f64], n: usize) -> Vec<f64> {
+    (0..values.len())
+        .map(|i| match op {
+            Rolling::Lag | Rolling::Diff | Rolling::PctChange => {
+                let Some(j) = i.checked_sub(n) else { return f64::NAN };
+                let (now, then) = (values[i], values[j]);
+                match op {
+                    Rolling::Lag => then,
+                    Rolling::Diff => now - then,
+                    _ => (now - then) / then * 100.0,
+                }
+            }
+            Rolling::Mean | Rolling::Std | Rolling::Min | Rolling::Max => {
+                if i + 1 < n {
+                    return f64::NAN;
+                }
+                let window = &values[(i + 1 - n)..=i];
+                match op {
+                    Rolling::Mean => window.iter().sum::<f64>() / n as f64,
+                    Rolling::Std => window.std_dev(),
+                    // NaN-propagating, unlike f64::min/max
+                    Rolling::Min => window.iter().copied().reduce(|a, b| if a.is_nan() || a < b { a } else { b }).unwrap_or(f64::NAN),
+                    _ => window.iter().copied().reduce(|a, b| if a.is_nan() || a > b { a } else { b }).unwrap_or(f64::NAN),
+                }
+            }
+        })
+        .collect()
+}
+
+/// Registry and expression errors
+#[derive(Debug, PartialEq)]
+pub enum RegistryError {
+    Parse { component: String, message: String },
+    Invalid(String),
+    Io(String),
+}
+
+impl std::fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegistryError::Parse { component, message } => write!(f, "Component '{}': {}", component, message),
+            RegistryError::Invalid(e) => write!(f, "Invalid component registry: {}", e),
+            RegistryError::Io(e) => write!(f, "Component registry I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Ident(String),
+    Op(char),
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(Token::Num(text.parse().map_err(|_| format!("bad number '{}'", text))?));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over the token stream
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize, // Open unary levels; every recursion passes through one
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), String> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}'", op))
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                BinOp::Add
+            } else if self.eat('-') {
+                BinOp::Sub
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                BinOp::Mul
+            } else if self.eat('/') {
+                BinOp::Div
+            } else {
+                return Ok(lhs);
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(format!("expression nested more than {} levels deep", MAX_DEPTH));
+        }
+        self.depth += 1;
+        let result = self.nested_unary();
+        self.depth -= 1;
+        result
+    }
+
+    fn nested_unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            return Ok(Expr::Unary(Unary::Neg, Box::new(self.unary()?)));
+        }
+        let base = self.primary()?;
+        if self.eat('^') {
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token::Num(n)) => {
+                self.pos += 1;
+                Ok(Expr::Num(n))
+            }
+            Some(Token::Op('(')) => {
+                self.pos += 1;
+                let e = self.expr()?;
+                self.expect(')')?;
+                Ok(e)
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+                if self.eat('(') {
+                    self.call(&name)
+                } else {
+                    Series::parse(&name)
+                        .map(Expr::Series)
+                        .ok_or_else(|| format!("unknown series '{}'", name))
+                }
+            }
+            Some(Token::Op(c)) => Err(format!("unexpected '{}'", c)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    /// Function call; the opening parenthesis is already consumed
+    fn call(&mut self, name: &str) -> Result<Expr, String> {
+        let first = self.expr()?;
+        let call = match name {
+            "abs" | "tanh" | "ln" => {
+                let op = match name {
+                    "abs" => Unary::Abs,
+                    "tanh" => Unary::Tanh,
+                    _ => Unary::Ln,
+                };
+                Expr::Unary(op, Box::new(first))
+            }
+            "min" | "max" => {
+                self.expect(',')?;
+                let second = Box::new(self.expr()?);
+                if name == "min" {
+                    Expr::Min(Box::new(first), second)
+                } else {
+                    Expr::Max(Box::new(first), second)
+                }
+            }
+            "lag" | "diff" | "pct_change" | "mean" | "std" | "rolling_min" | "rolling_max" => {
+                let op = match name {
+                    "lag" => Rolling::Lag,
+                    "diff" => Rolling::Diff,
+                    "pct_change" => Rolling::PctChange,
+                    "mean" => Rolling::Mean,
+                    "std" => Rolling::Std,
+                    "rolling_min" => Rolling::Min,
+                    _ => Rolling::Max,
+                };
+                self.expect(',')?;
+                let n = match self.tokens.get(self.pos) {
+                    Some(Token::Num(n)) if n.fract() == 0.0 && *n >= 1.0 && *n <= MAX_WINDOW as f64 => *n as usize,
+                    _ => return Err(format!("{}: window must be an integer in 1..={}", name, MAX_WINDOW)),
+                };
+                self.pos += 1;
+                if op == Rolling::Std && n < 2 {
+                    return Err("std: window must be at least 2".to_string());
+                }
+                Expr::Rolling(op, Box::new(first), n)
+            }
+            _ => return Err(format!("unknown function '{}'", name)),
+        };
+        self.expect(')')?;
+        Ok(call)
+    }
+}
+
+fn parse(src: &str) -> Result<Expr, String> {
+    if src.len() > MAX_EXPR_LEN {
+        return Err(format!("expression longer than {} bytes", MAX_EXPR_LEN));
+    }
+    let mut parser = Parser { tokens: tokenize(src)?, pos: 0, depth: 0 };
+    let expr = parser.expr()?;
+    if parser.pos != parser.tokens.len() {
+        return Err("trailing input".to_string());
+    }
+    Ok(expr)
+}
+
+#[derive(Debug, Clone)]
+struct CompiledComponent {
+    def: ComponentDef,
+    expr: Expr,
+}
+
+/// Compiled set of custom components
+#[derive(Debug, Clone, Default)]
+pub struct ComponentRegistry {
+    components: Vec<CompiledComponent>,
+}
+
+#[derive(Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    components: Vec<ComponentDef>,
+}
+
+impl ComponentRegistry {
+    /// Compile component definitions, rejecting bad names and expressions
+    pub fn compile(defs: Vec<ComponentDef>) -> Result<Self, RegistryError> {
+        if defs.len() > MAX_COMPONENTS {
+            return Err(RegistryError::Invalid(format!("at most {} components", MAX_COMPONENTS)));
+        }
+        let mut components: Vec<CompiledComponent> = Vec::with_capacity(defs.len());
+        for def in defs {
+            let valid_name = !def.name.is_empty()
+                && def.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid_name {
+                return Err(RegistryError::Invalid(format!("bad component name '{}'", def.name)));
+            }
+            if components.iter().any(|c| c.def.name == def.name) {
+                return Err(RegistryError::Invalid(format!("duplicate component '{}'", def.name)));
+            }
+            let expr = parse(&def.expr).map_err(|message| RegistryError::Parse {
+                component: def.name.clone(),
+                message,
+            })?;
+            components.push(CompiledComponent { def, expr });
+        }
+        Ok(Self { components })
+    }
+
+    /// Load from a JSON config file
+    pub fn load(path: &str) -> Result<Self, RegistryError> {
+        let raw = std::fs::read_to_string(path).map_err(|e| RegistryError::Io(format!("{}: {}", path, e)))?;
+        let file: RegistryFile =
+            serde_json::from_str(&raw).map_err(|e| RegistryError::Invalid(e.to_string()))?;
+        Self::compile(file.components)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    pub fn definitions(&self) -> Vec<ComponentDef> {
+        self.components.iter().map(|c| c.def.clone()).collect()
+    }
+
+    /// Evaluate every component over `data`; result is indexed [month][component]
+    pub fn evaluate(&self, data: &[EconomicData]) -> Vec<Vec<CustomTerm>> {
+        let columns: Vec<Vec<f64>> = self.components.iter().map(|c| c.expr.eval(data)).collect();
+        (0..data.len())
+            .map(|i| {
+                self.components
+                    .iter()
+                    .zip(&columns)
+                    .map(|(c, col)| CustomTerm {
+                        name: c.def.name.clone(),
+                        role: c.def.role,
+                        value: Some(col[i]).filter(|v| v.is_finite()),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    fn def(name: &str, role: TermRole, expr: &str) -> ComponentDef {
+        ComponentDef { name: name.to_string(), role, expr: expr.to_string() }
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let data = mock::generate_mock_data(2000, 2001);
+        assert_eq!(parse("1 + 2 * 3").unwrap().eval(&data)[0], 7.0);
+        assert_eq!(parse("(1 + 2) * 3").unwrap().eval(&data)[0], 9.0);
+        assert_eq!(parse("-2 ^ 2").unwrap().eval(&data)[0], -4.0);
+        assert_eq!(parse("max(1, min(5, 3)) / 2").unwrap().eval(&data)[0], 1.5);
+        assert_eq!(parse("gdp - gdp").unwrap().eval(&data)[5], 0.0);
+    }
+
+    #[test]
+    fn test_rolling_functions() {
+        let data = mock::generate_mock_data(2000, 2002);
+        let lag = parse("lag(fed_funds_rate, 3)").unwrap().eval(&data);
+        assert!(lag[2].is_nan());
+        assert_eq!(lag[10], data[7].fed_funds_rate);
+
+        let mean = parse("mean(capacity_util, 12)").unwrap().eval(&data);
+        let expected = data[..12].iter().map(|d| d.capacity_util).sum::<f64>() / 12.0;
+        assert!(mean[10].is_nan());
+        assert!((mean[11] - expected).abs() < 1e-9);
+
+        let pct = parse("pct_change(m2_supply, 12)").unwrap().eval(&data);
+        let expected = (data[20].m2_supply / data[8].m2_supply - 1.0) * 100.0;
+        assert!((pct[20] - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let bad = |expr: &str| ComponentRegistry::compile(vec![def("x", TermRole::Numerator, expr)]).is_err();
+        assert!(bad("gdpp"));
+        assert!(bad("mean(gdp)"));
+        assert!(bad("mean(gdp, 1.5)"));
+        assert!(bad("std(gdp, 1)"));
+        assert!(bad("1 +"));
+        assert!(bad("sqrt(gdp)"));
+        assert!(bad("gdp)"));
+
+        let dup = vec![def("a", TermRole::Numerator, "1"), def("a", TermRole::Denominator, "1")];
+        assert!(ComponentRegistry::compile(dup).is_err());
+        assert!(ComponentRegistry::compile(vec![def("a b", TermRole::Numerator, "1")]).is_err());
+    }
+
+    #[test]
+    fn test_depth_and_length_limits() {
+        let nested = |levels: usize| format!("{}1{}", "(".repeat(levels), ")".repeat(levels));
+        let data = mock::generate_mock_data(2000, 2001);
+        assert_eq!(parse(&nested(MAX_DEPTH - 1)).unwrap().eval(&data)[0], 1.0);
+        assert!(parse(&nested(MAX_DEPTH)).unwrap_err().contains("nested"));
+        assert!(parse(&"-".repeat(MAX_DEPTH + 1)).unwrap_err().contains("nested"));
+        assert!(parse(&format!("{}1", "abs(".repeat(MAX_DEPTH))).unwrap_err().contains("nested"));
+
+        // Far past the limits fails cleanly rather than overflowing the stack
+        let deep = format!("{}1{}", "(".repeat(50_000), ")".repeat(50_000));
+        let err = ComponentRegistry::compile(vec![def("x", TermRole::Numerator, &deep)]).unwrap_err();
+        assert!(err.to_string().contains("longer than"));
+        assert!(parse(&vec!["1"; MAX_EXPR_LEN / 2 + 1].join("+")).is_err());
+        assert!(parse(&vec!["1"; MAX_EXPR_LEN / 4].join("+")).is_ok());
+    }
+
+    #[test]
+    fn test_unavailable_values_are_none() {
+        let data = mock::generate_mock_data(2000, 2002);
+        let registry = ComponentRegistry::compile(vec![
+            def("rd", TermRole::Numerator, "rd_investment / gdp"),
+            def("vol", TermRole::Denominator, "std(fed_funds_rate, 6) / 100"),
+        ])
+        .unwrap();
+        let terms = registry.evaluate(&data);
+
+        assert_eq!(terms.len(), data.len());
+        assert!(terms.iter().all(|t| t[0].value.is_none()));
+        assert!(terms[4][1].value.is_none());
+        assert!(terms[5][1].value.is_some());
+    }
+}
@@ -0,0 +1,504 @@
+//! Replay Mode
+//!
+//! Steps through the computed history as if it were a live feed, so integrators
+//! can exercise their alert handling against real sequences (e.g. 2006-2009)
+//! without waiting for monthly data releases.
+//!
+//! Each replayed month publishes a `refresh` event and, when the alert level
+//! changes, an `alert_transition` event. Events fan out to:
+//! - Server-Sent Events subscribers (`/api/v1/replay/{id}/events`), in the
+//!   subscribing request's format (see format.rs)
+//! - An optional webhook URL (JSON POST per event), unrounded with
+//!   probabilities as fractions
+//!
+//! Speed `Nx` replays N historical months per minute of wall-clock time.
+//!
+//! A webhook must resolve only to public addresses: loopback, private,
+//! link-local (cloud metadata), shared, multicast and reserved ranges are
+//! rejected. Delivery is pinned to the address checked and does not follow
+//! redirects, so a later lookup or a redirect cannot reach them either.
+//!
+//! Stopping or completing a replay closes its event stream, so SSE
+//! subscribers see the end. A replay stays queryable for RETENTION after it
+//! ends, then is evicted.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::format::Format;
+use crate::niv::{AlertLevel, NIVResult};
+use crate::tasks::TaskRun;
+
+/// Maximum replay speed (months per minute)
+pub const MAX_SPEED: u32 = 600;
+
+/// HTTP client for webhook delivery
+#[cfg(feature = "webhooks")]
+pub type WebhookClient = reqwest::Client;
+
+/// Placeholder client when built without the `webhooks` feature
+#[cfg(not(feature = "webhooks"))]
+#[derive(Debug, Clone, Default)]
+pub struct WebhookClient;
+
+#[cfg(not(feature = "webhooks"))]
+impl WebhookClient {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Buffered events per replay for slow SSE subscribers
+const EVENT_BUFFER: usize = 256;
+
+/// How long an ended replay stays queryable
+pub const RETENTION: Duration = Duration::from_secs(3600);
+
+/// Per-event webhook delivery timeout
+#[cfg(feature = "webhooks")]
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A checked webhook target and the client pinned to its address
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+    #[cfg_attr(not(feature = "webhooks"), allow(dead_code))]
+    client: WebhookClient,
+}
+
+/// Event published for each replayed step
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplayEvent {
+    Refresh {
+        replay_id: String,
+        date: NaiveDate,
+        niv_score: f64,
+        recession_probability: f64,
+        alert_level: AlertLevel,
+    },
+    AlertTransition {
+        replay_id: String,
+        date: NaiveDate,
+        from: AlertLevel,
+        to: AlertLevel,
+        recession_probability: f64,
+    },
+    Completed {
+        replay_id: String,
+        steps: usize,
+    },
+}
+
+impl ReplayEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReplayEvent::Refresh { .. } => "refresh",
+            ReplayEvent::AlertTransition { .. } => "alert_transition",
+            ReplayEvent::Completed { .. } => "completed",
+        }
+    }
+
+    /// The event with its score and probability written in `format`
+    pub fn formatted(mut self, format: Format) -> Self {
+        match &mut self {
+            ReplayEvent::Refresh { niv_score, recession_probability, .. } => {
+                *niv_score = format.round(*niv_score, 2);
+                *recession_probability = format.probability_to(*recession_probability, 2);
+            }
+            ReplayEvent::AlertTransition { recession_probability, .. } => {
+                *recession_probability = format.probability_to(*recession_probability, 2);
+            }
+            ReplayEvent::Completed { .. } => {}
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayState {
+    Running,
+    Completed,
+    Stopped,
+}
+
+/// Live status of a replay
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStatus {
+    pub id: String,
+    pub state: ReplayState,
+    pub speed: u32,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub current_date: Option<NaiveDate>,
+    pub steps_total: usize,
+    pub steps_emitted: usize,
+    pub transitions_emitted: usize,
+    pub webhook: Option<String>,
+    pub webhook_failures: usize,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+/// Event sender, taken (closing the stream) when the replay ends
+type Events = Arc<Mutex<Option<broadcast::Sender<ReplayEvent>>>>;
+
+/// Handle to a running replay
+pub struct ReplayHandle {
+    pub status: Arc<RwLock<ReplayStatus>>,
+    events: Events,
+    task: tokio::task::AbortHandle,
+}
+
+impl ReplayHandle {
+    /// Stop the replay; subscribers see the channel close
+    pub async fn stop(&self) {
+        self.task.abort();
+        close(&self.events);
+        let mut status = self.status.write().await;
+        if status.state == ReplayState::Running {
+            status.state = ReplayState::Stopped;
+            status.ended_at = Some(Utc::now());
+        }
+    }
+
+    /// Receive the replay's events; already closed once it has ended
+    pub fn subscribe(&self) -> broadcast::Receiver<ReplayEvent> {
+        match self.events.lock().expect("replay events lock").as_ref() {
+            Some(sender) => sender.subscribe(),
+            None => broadcast::channel(1).1, // Sender dropped: closed
+        }
+    }
+
+    /// Ended more than RETENTION before `now`
+    pub async fn expired(&self, now: DateTime<Utc>) -> bool {
+        let ended_at = self.status.read().await.ended_at;
+        ended_at.is_some_and(|at| (now - at).to_std().unwrap_or_default() > RETENTION)
+    }
+}
+
+fn close(events: &Events) {
+    events.lock().expect("replay events lock").take();
+}
+
+/// Whether `ip` is a globally routable unicast address
+#[cfg_attr(not(feature = "webhooks"), allow(dead_code))]
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_public_v4(v4),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_public_v4(v4),
+            None => is_public_v6(v6),
+        },
+    }
+}
+
+#[cfg_attr(not(feature = "webhooks"), allow(dead_code))]
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)) // Shared (CGNAT) 100.64.0.0/10
+        || (a == 192 && b == 0 && c == 0) // IETF protocol assignments
+        || (a == 198 && (b == 18 || b == 19)) // Benchmarking
+        || a >= 240) // Reserved
+}
+
+#[cfg_attr(not(feature = "webhooks"), allow(dead_code))]
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00 // Unique local fc00::/7
+        || (first & 0xffc0) == 0xfe80 // Link-local fe80::/10
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8) // Documentation
+        || (first == 0x64 && ip.segments()[1] == 0xff9b)) // NAT64, embeds an IPv4 address
+}
+
+/// Check `url` and resolve its host; every address must be public, and the
+/// returned client is pinned to the first of them
+#[cfg(feature = "webhooks")]
+pub async fn webhook(url: &str) -> Result<Webhook, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("webhook is not a valid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("webhook must be an http(s) URL".to_string());
+    }
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    let builder = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(DELIVERY_TIMEOUT);
+    let host = parsed.host_str().ok_or("webhook URL has no host")?;
+    let (addresses, builder) = match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => (vec![ip], builder),
+        Err(_) => {
+            let domain = host;
+            let resolved: Vec<std::net::SocketAddr> = tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| format!("webhook host {} does not resolve: {}", domain, e))?
+                .collect();
+            let pinned = resolved.first().copied();
+            let builder = match pinned {
+                Some(address) => builder.resolve(domain, address),
+                None => builder,
+            };
+            (resolved.iter().map(|a| a.ip()).collect(), builder)
+        }
+    };
+    if addresses.is_empty() || !addresses.iter().all(|ip| is_public(*ip)) {
+        return Err("webhook must resolve to public addresses only".to_string());
+    }
+    let client = builder.build().map_err(|e| format!("webhook client: {}", e))?;
+    Ok(Webhook { url: url.to_string(), client })
+}
+
+/// Parse a speed like "12x" or "12"
+pub fn parse_speed(raw: &str) -> Option<u32> {
+    let n: u32 = raw.trim().trim_end_matches(['x', 'X']).parse().ok()?;
+    (1..=MAX_SPEED).contains(&n).then_some(n)
+}
+
+/// Build the event sequence for a slice of history (no timing)
+pub fn events_for(replay_id: &str, steps: &[NIVResult], previous: Option<AlertLevel>) -> Vec<ReplayEvent> {
+    let mut events = Vec::with_capacity(steps.len() + 1);
+    let mut last_level = previous;
+
+    for r in steps {
+        events.push(ReplayEvent::Refresh {
+            replay_id: replay_id.to_string(),
+            date: r.date,
+            niv_score: r.niv_score,
+            recession_probability: r.recession_probability,
+            alert_level: r.alert_level,
+        });
+        if let Some(from) = last_level.filter(|l| *l != r.alert_level) {
+            events.push(ReplayEvent::AlertTransition {
+                replay_id: replay_id.to_string(),
+                date: r.date,
+                from,
+                to: r.alert_level,
+                recession_probability: r.recession_probability,
+            });
+        }
+        last_level = Some(r.alert_level);
+    }
+
+    events
+}
+
+/// Start a replay task over `steps`
+/// `previous` is the alert level in force just before the first step; `run`
+/// records the task's outcome (stopping it records a failed run)
+pub fn start(
+    id: String,
+    steps: Vec<NIVResult>,
+    previous: Option<AlertLevel>,
+    speed: u32,
+    webhook: Option<Webhook>,
+    run: TaskRun,
+) -> ReplayHandle {
+    let (sender, _) = broadcast::channel(EVENT_BUFFER);
+    let status = Arc::new(RwLock::new(ReplayStatus {
+        id: id.clone(),
+        state: ReplayState::Running,
+        speed,
+        from: steps.first().map(|r| r.date).unwrap_or_default(),
+        to: steps.last().map(|r| r.date).unwrap_or_default(),
+        current_date: None,
+        steps_total: steps.len(),
+        steps_emitted: 0,
+        transitions_emitted: 0,
+        webhook: webhook.as_ref().map(|w| w.url.clone()),
+        webhook_failures: 0,
+        started_at: Utc::now(),
+        ended_at: None,
+    }));
+
+    let interval = Duration::from_secs_f64(60.0 / speed as f64);
+    let task_status = status.clone();
+    let task_sender = sender.clone();
+    let events: Events = Arc::new(Mutex::new(Some(sender)));
+    let task_events = events.clone();
+
+    let task = tokio::spawn(async move {
+        let mut level = previous;
+        for (i, step) in steps.iter().enumerate() {
+            if i > 0 {
+                tokio::time::sleep(interval).await;
+            }
+            let events = events_for(&id, std::slice::from_ref(step), level);
+            level = Some(step.alert_level);
+
+            for event in events {
+                let is_transition = matches!(event, ReplayEvent::AlertTransition { .. });
+                let delivered = deliver(webhook.as_ref(), &event).await;
+                let _ = task_sender.send(event);
+
+                let mut s = task_status.write().await;
+                if is_transition {
+                    s.transitions_emitted += 1;
+                }
+                if !delivered {
+                    s.webhook_failures += 1;
+                }
+            }
+
+            let mut s = task_status.write().await;
+            s.current_date = Some(step.date);
+            s.steps_emitted = i + 1;
+        }
+
+        let done = ReplayEvent::Completed { replay_id: id.clone(), steps: steps.len() };
+        deliver(webhook.as_ref(), &done).await;
+        let _ = task_sender.send(done);
+        close(&task_events);
+        drop(task_sender);
+        let failures = {
+            let mut s = task_status.write().await;
+            s.state = ReplayState::Completed;
+            s.ended_at = Some(Utc::now());
+            s.webhook_failures
+        };
+        tracing::info!("Replay {} completed ({} steps)", id, steps.len());
+        run.finish(match failures {
+            0 => Ok(()),
+            n => Err(format!("replay {}: {} webhook deliveries failed", id, n)),
+        });
+    });
+
+    ReplayHandle {
+        status,
+        events,
+        task: task.abort_handle(),
+    }
+}
+
+/// POST an event to the webhook; returns false on failure
+#[cfg(feature = "webhooks")]
+async fn deliver(webhook: Option<&Webhook>, event: &ReplayEvent) -> bool {
+    let Some(Webhook { url, client }) = webhook else {
+        return true;
+    };
+    match client.post(url).json(event).send().await {
+        Ok(resp) if resp.status().is_success() => true,
+        Ok(resp) => {
+            tracing::warn!("Replay webhook {} returned {}", url, resp.status());
+            false
+        }
+        Err(e) => {
+            tracing::warn!("Replay webhook {} failed: {}", url, e);
+            false
+        }
+    }
+}
+
+/// Webhooks are unavailable; only a replay without one counts as delivered
+#[cfg(not(feature = "webhooks"))]
+async fn deliver(webhook: Option<&Webhook>, _event: &ReplayEvent) -> bool {
+    webhook.is_none()
+}
+
+/// Webhooks are unavailable without the feature (rejected before this)
+#[cfg(not(feature = "webhooks"))]
+pub async fn webhook(_url: &str) -> Result<Webhook, String> {
+    unreachable!("webhooks are rejected without the webhooks feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!(parse_speed("12x"), Some(12));
+        assert_eq!(parse_speed("60"), Some(60));
+        assert_eq!(parse_speed("0x"), None);
+        assert_eq!(parse_speed("fast"), None);
+        assert_eq!(parse_speed(&format!("{}x", MAX_SPEED + 1)), None);
+    }
+
+    #[test]
+    fn test_only_public_addresses() {
+        let public = |ip: &str| is_public(ip.parse().unwrap());
+        assert!(public("93.184.216.34"));
+        assert!(public("2606:2800:220:1::1"));
+        for internal in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0",
+            "::1", "fe80::1", "fd00::1", "::ffff:169.254.169.254", "::ffff:127.0.0.1",
+        ] {
+            assert!(!public(internal), "{} accepted", internal);
+        }
+    }
+
+    #[cfg(feature = "webhooks")]
+    #[tokio::test]
+    async fn test_webhook_rejects_internal_targets() {
+        for url in ["http://127.0.0.1:8080/hook", "http://[::1]/hook", "http://169.254.169.254/latest", "http://localhost/x"] {
+            assert!(webhook(url).await.is_err(), "{} accepted", url);
+        }
+        assert_eq!(webhook("ftp://93.184.216.34/x").await.unwrap_err(), "webhook must be an http(s) URL");
+        assert_eq!(webhook("https://93.184.216.34/hook").await.unwrap().url, "https://93.184.216.34/hook");
+    }
+
+    #[tokio::test]
+    async fn test_stop_closes_the_event_stream() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2000, 2002));
+        let tasks = crate::tasks::Tasks::default();
+        let handle = start("t".to_string(), results, None, 1, None, tasks.start(crate::tasks::REPLAY));
+        let mut events = handle.subscribe();
+        assert_eq!(events.recv().await.unwrap().name(), "refresh");
+
+        handle.stop().await;
+        assert!(matches!(events.recv().await, Err(broadcast::error::RecvError::Closed)));
+        assert!(matches!(handle.subscribe().recv().await, Err(broadcast::error::RecvError::Closed)));
+        let ended = handle.status.read().await.ended_at.unwrap();
+        assert!(!handle.expired(ended).await);
+        assert!(handle.expired(ended + chrono::Duration::from_std(RETENTION * 2).unwrap()).await);
+    }
+
+    #[test]
+    fn test_events_include_transitions() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1960, 2024));
+        let events = events_for("t", &results, None);
+
+        let refreshes = events.iter().filter(|e| e.name() == "refresh").count();
+        assert_eq!(refreshes, results.len());
+
+        let changes = results.windows(2).filter(|w| w[0].alert_level != w[1].alert_level).count();
+        let transitions = events.iter().filter(|e| e.name() == "alert_transition").count();
+        assert_eq!(transitions, changes);
+    }
+
+    #[test]
+    fn test_sse_events_follow_the_request_format() {
+        let event = ReplayEvent::Refresh {
+            replay_id: "t".to_string(),
+            date: NaiveDate::from_ymd_opt(2008, 9, 1).unwrap(),
+            niv_score: -12.3456,
+            recession_probability: 0.351234,
+            alert_level: AlertLevel::Warning,
+        };
+        let probability = |e: ReplayEvent| match e {
+            ReplayEvent::Refresh { niv_score, recession_probability, .. } => (niv_score, recession_probability),
+            _ => unreachable!(),
+        };
+        let fraction = Format { precision: None, units: crate::format::ProbabilityUnits::Fraction };
+
+        assert_eq!(probability(event.clone().formatted(Format::default())), (-12.35, 35.12));
+        assert_eq!(probability(event.clone().formatted(fraction)), (-12.35, 0.3512));
+        assert_eq!(probability(event), (-12.3456, 0.351234)); // Webhooks send the event as published
+    }
+}
@@ -0,0 +1,181 @@
+//! Replication Artifact
+//!
+//! The backtest behind the published AUC as a tidy CSV, one row per month,
+//! plus a JSON metadata document describing it, so a paper can attach
+//! exactly what the crate computed:
+//!
+//! - GET /api/v1/backtest/replication.csv - the rows (columns below)
+//! - GET /api/v1/backtest/replication - the metadata: schema version,
+//!   column definitions, pipeline hash, parameters, label set and horizon,
+//!   per-fold and overall metrics, and the CSV's SHA-256
+//!
+//! A month is labelled positive when it lies inside a recession or within
+//! LABEL_HORIZON_MONTHS before one starts (backtest::recession_labels).
+//! Warm-up months are left out (backtest::evaluated).
+//!
+//! Folds are recession blocks: fold k runs from the month after recession
+//! k-1 ends through the end of recession k, and months after the last
+//! recession form a final fold without positives. The engine's constants
+//! are fixed rather than refitted per fold, so folds partition the
+//! evaluation only; a fold's metrics show how the score holds up cycle by
+//! cycle. AUC is None for a fold with a single class.
+
+use std::fmt::Write;
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::backtest;
+use crate::niv::NIVResult;
+
+/// Bumped whenever a column or metadata field changes meaning
+pub const SCHEMA_VERSION: &str = "niv-replication/1";
+
+/// (name, type, description) of each CSV column, in order
+pub const COLUMNS: [(&str, &str, &str); 6] = [
+    ("date", "date", "First day of the month (ISO 8601)"),
+    ("fold", "integer", "Recession-block fold, from 1"),
+    ("niv_score", "number", "NIV score"),
+    ("recession_probability", "number", "Predicted recession probability, as a fraction"),
+    ("label", "integer", "1 inside a recession or within the label horizon before one starts, else 0"),
+    ("in_recession", "integer", "1 inside a recession, else 0"),
+];
+
+/// One month of the backtest
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub date: NaiveDate,
+    pub fold: usize,
+    pub niv_score: f64,
+    pub recession_probability: f64,
+    pub label: bool,
+    pub in_recession: bool,
+}
+
+/// AUC and Brier score over a set of months
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Metrics {
+    pub months: usize,
+    pub positives: usize,
+    pub auc: Option<f64>,
+    pub brier: Option<f64>,
+}
+
+/// One fold's span and metrics
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Fold {
+    pub fold: usize,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub recession_start: Option<NaiveDate>, // None for the trailing fold
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+/// Label and fold every month of `results` against `chronology` (sorted by start)
+pub fn rows(results: &[NIVResult], chronology: &[(NaiveDate, NaiveDate)], horizon_months: u32) -> Vec<Row> {
+    let dates: Vec<NaiveDate> = results.iter().map(|r| r.date).collect();
+    let labels = backtest::recession_labels(&dates, chronology, horizon_months);
+    results
+        .iter()
+        .zip(labels)
+        .map(|(r, label)| Row {
+            date: r.date,
+            fold: 1 + chronology.iter().filter(|(_, end)| *end < r.date).count(),
+            niv_score: r.niv_score,
+            recession_probability: r.recession_probability,
+            label,
+            in_recession: chronology.iter().any(|&(start, end)| r.date >= start && r.date <= end),
+        })
+        .collect()
+}
+
+/// Metrics over `rows`
+pub fn metrics(rows: &[Row]) -> Metrics {
+    let probs: Vec<f64> = rows.iter().map(|r| r.recession_probability).collect();
+    let labels: Vec<bool> = rows.iter().map(|r| r.label).collect();
+    Metrics {
+        months: rows.len(),
+        positives: labels.iter().filter(|&&l| l).count(),
+        auc: backtest::auc(&probs, &labels),
+        brier: backtest::brier(&probs, &labels),
+    }
+}
+
+/// Metrics per fold present in `rows`, in fold order
+pub fn folds(rows: &[Row], chronology: &[(NaiveDate, NaiveDate)]) -> Vec<Fold> {
+    rows.chunk_by(|a, b| a.fold == b.fold)
+        .map(|block| Fold {
+            fold: block[0].fold,
+            start: block[0].date,
+            end: block[block.len() - 1].date,
+            recession_start: chronology.get(block[0].fold - 1).map(|&(start, _)| start),
+            metrics: metrics(block),
+        })
+        .collect()
+}
+
+/// The tidy CSV, header included
+pub fn csv(rows: &[Row]) -> String {
+    let header: Vec<&str> = COLUMNS.iter().map(|(name, _, _)| *name).collect();
+    let mut out = header.join(",");
+    out.push('\n');
+    for r in rows {
+        let _ = writeln!(
+            out,
+            "{},{},{:.6},{:.6},{},{}",
+            r.date, r.fold, r.niv_score, r.recession_probability, r.label as u8, r.in_recession as u8
+        );
+    }
+    out
+}
+
+/// Hex SHA-256 of `csv`, for the metadata to pin the file it describes
+pub fn sha256(csv: &str) -> String {
+    Sha256::digest(csv.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::{NIVEngine, RecessionPeriods};
+
+    fn date(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    #[test]
+    fn test_folds_are_recession_blocks() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1995, 2012));
+        let chronology = [(date(2001, 3), date(2001, 11)), (date(2007, 12), date(2009, 6))];
+        let rows = rows(&results, &chronology, 12);
+        let folds = folds(&rows, &chronology);
+
+        assert_eq!(folds.iter().map(|f| f.fold).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!((folds[0].end, folds[1].start), (date(2001, 11), date(2001, 12)));
+        assert_eq!(folds[1].recession_start, Some(date(2007, 12)));
+        assert_eq!((folds[2].recession_start, folds[2].metrics.positives, folds[2].metrics.auc), (None, 0, None));
+        assert_eq!(folds.iter().map(|f| f.metrics.months).sum::<usize>(), rows.len());
+        let labelled = rows.iter().find(|r| r.label).unwrap();
+        assert_eq!((labelled.date, labelled.in_recession), (date(2000, 3), false));
+    }
+
+    #[test]
+    fn test_csv_matches_columns_and_overall_metrics() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(1970, 2020));
+        let chronology = RecessionPeriods::known_recessions();
+        let rows = rows(&results, &chronology, 12);
+        let csv = csv(&rows);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), rows.len() + 1);
+        assert!(lines.iter().all(|l| l.split(',').count() == COLUMNS.len()));
+        assert_eq!(sha256(&csv).len(), 64);
+        let probs: Vec<f64> = results.iter().map(|r| r.recession_probability).collect();
+        let dates: Vec<NaiveDate> = results.iter().map(|r| r.date).collect();
+        let labels = backtest::recession_labels(&dates, &chronology, 12);
+        assert_eq!(metrics(&rows).auc, backtest::auc(&probs, &labels));
+    }
+}
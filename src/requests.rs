@@ -0,0 +1,277 @@
+//! Compute Request Bodies
+//!
+//! Bodies of the endpoints that run the engine with caller-chosen inputs
+//! (simulate, canary registration, Monte Carlo) or take more than a query
+//! string holds (date batches), and their validation. Kept
+//! free of HTTP types so the fuzz targets in `fuzz/` exercise exactly the
+//! parsing and checks the handlers run.
+
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::interval::InputUncertainty;
+use crate::montecarlo::MonteCarloConfig;
+use crate::niv::{
+    self, ComponentWeights, EfficiencySpec, GdpSpec, InflationSpec, DenominatorPolicy, NIVEngine, NonFinitePolicy, ProbabilityInput,
+    ScoreMode, SlackSpec, SpreadSpec, ThrustScaling, WarmupPolicy,
+};
+use crate::qmc::Sampling;
+use crate::registry::{ComponentDef, ComponentRegistry};
+use crate::scenario::Scenario;
+
+pub const MAX_MC_DRAWS: usize = 5000;
+pub const MAX_MC_HORIZON: usize = 36;
+pub const DEFAULT_MC_DRAWS: usize = 500;
+pub const DEFAULT_MC_HORIZON: usize = 12;
+pub const MAX_BATCH_DATES: usize = 120;
+pub const MAX_ETA: f64 = 5.0; // eta is in (0, MAX_ETA]
+pub const MAX_EPSILON: f64 = 1.0; // epsilon is in [0, MAX_EPSILON]
+
+/// A rejected request: machine-readable code and message (400 at the API)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl RequestError {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl std::fmt::Display for RequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+/// Engine parameters, as accepted by simulate and canary registration
+#[derive(Debug, Deserialize)]
+pub struct EngineSpec {
+    #[serde(default = "default_eta")]
+    pub eta: f64,
+    #[serde(default = "default_epsilon")]
+    pub epsilon: f64,
+    pub weights: Option<ComponentWeights>, // defaults to the validated weights (or the caller's preferences)
+    #[serde(default)]
+    pub probability_input: ProbabilityInput,
+    #[serde(default)]
+    pub thrust_scaling: ThrustScaling,
+    #[serde(default)]
+    pub efficiency: EfficiencySpec,
+    pub gdp: Option<GdpSpec>,     // defaults to the server's configured spec
+    pub inflation: Option<InflationSpec>, // defaults to the server's configured spec
+    pub spread: Option<SpreadSpec>, // defaults to the server's configured spec
+    pub slack: Option<SlackSpec>, // defaults to the server's configured spec
+    pub nonfinite: Option<NonFinitePolicy>, // defaults to the server's configured policy
+    pub denominator: Option<DenominatorPolicy>, // defaults to the serving engine's policy
+    pub warmup: Option<WarmupPolicy>, // defaults to the serving engine's policy
+    pub expansion_age_weight: Option<f64>, // defaults to the serving engine's weight
+    pub components: Option<Vec<ComponentDef>>, // defaults to the server's registry
+}
+
+fn default_eta() -> f64 {
+    niv::ETA
+}
+
+fn default_epsilon() -> f64 {
+    niv::EPSILON
+}
+
+impl EngineSpec {
+    /// Validate and build the engine; unset specs follow `serving`
+    pub fn build(self, serving: &NIVEngine) -> Result<NIVEngine, RequestError> {
+        if !(self.eta > 0.0 && self.eta <= MAX_ETA) {
+            return Err(RequestError::new("INVALID_ETA", format!("eta must be in (0, {}]", MAX_ETA)));
+        }
+        if !(0.0..=MAX_EPSILON).contains(&self.epsilon) {
+            return Err(RequestError::new("INVALID_EPSILON", format!("epsilon must be in [0, {}]", MAX_EPSILON)));
+        }
+        let weights = self.weights.unwrap_or_default();
+        if !weights.is_finite() {
+            return Err(RequestError::new("INVALID_WEIGHTS", "weights must be finite numbers"));
+        }
+        if !self.thrust_scaling.is_valid() {
+            return Err(RequestError::new(
+                "INVALID_THRUST_SCALING",
+                "thrust_scaling divisor must be positive; rolling_std window must be 2-600 months",
+            ));
+        }
+        let expansion_age_weight = self.expansion_age_weight.unwrap_or(serving.expansion_age_weight());
+        if !(-niv::MAX_EXPANSION_AGE_WEIGHT..=niv::MAX_EXPANSION_AGE_WEIGHT).contains(&expansion_age_weight) {
+            return Err(RequestError::new(
+                "INVALID_EXPANSION_AGE_WEIGHT",
+                format!("expansion_age_weight must be in [-{0}, {0}]", niv::MAX_EXPANSION_AGE_WEIGHT),
+            ));
+        }
+
+        let registry = match self.components {
+            Some(defs) => Arc::new(
+                ComponentRegistry::compile(defs).map_err(|e| RequestError::new("INVALID_COMPONENT", e.to_string()))?,
+            ),
+            None => serving.registry().clone(),
+        };
+
+        Ok(NIVEngine::with_params(self.eta, self.epsilon)
+            .with_weights(weights)
+            .with_probability_input(self.probability_input)
+            .with_thrust_scaling(self.thrust_scaling)
+            .with_efficiency_spec(self.efficiency)
+            .with_gdp_spec(self.gdp.unwrap_or(serving.gdp_spec()))
+            .with_inflation_spec(self.inflation.unwrap_or(serving.inflation_spec()))
+            .with_spread_spec(self.spread.unwrap_or(serving.spread_spec()))
+            .with_slack_spec(self.slack.unwrap_or(serving.slack_spec()))
+            .with_nonfinite_policy(self.nonfinite.unwrap_or(serving.nonfinite_policy()))
+            .with_denominator_policy(self.denominator.unwrap_or(serving.denominator_policy()))
+            .with_warmup_policy(self.warmup.unwrap_or(serving.warmup_policy()))
+            .with_expansion_age_weight(expansion_age_weight)
+            .with_registry(registry)
+            .with_pipelines(serving.pipelines().clone()))
+    }
+}
+
+/// Request body for the simulate endpoint
+#[derive(Debug, Deserialize)]
+pub struct SimulateRequest {
+    #[serde(flatten)]
+    pub engine: EngineSpec,
+    pub start: Option<String>,  // YYYY-MM-DD
+    pub end: Option<String>,    // YYYY-MM-DD
+    pub uncertainty: Option<InputUncertainty>, // Adds NIV/probability ranges (see interval.rs)
+}
+
+impl SimulateRequest {
+    /// Validated measurement uncertainty, when ranges were requested
+    pub fn uncertainty(&self) -> Result<Option<InputUncertainty>, RequestError> {
+        match self.uncertainty {
+            Some(u) if !u.is_valid() => Err(RequestError::new(
+                "INVALID_UNCERTAINTY",
+                "uncertainty half-widths must be finite and non-negative",
+            )),
+            u => Ok(u),
+        }
+    }
+}
+
+/// Request body for the Monte Carlo endpoint
+#[derive(Debug, Deserialize)]
+pub struct MonteCarloRequest {
+    #[serde(default = "Scenario::baseline")]
+    pub scenario: Scenario,
+    pub favorite: Option<String>, // A saved scenario (see preferences.rs), in place of `scenario`
+    #[serde(default = "default_mc_horizon")]
+    pub horizon_months: usize,
+    #[serde(default = "default_mc_draws")]
+    pub draws: usize,
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub sampling: Sampling,
+}
+
+fn default_mc_horizon() -> usize {
+    DEFAULT_MC_HORIZON
+}
+
+fn default_mc_draws() -> usize {
+    DEFAULT_MC_DRAWS
+}
+
+impl MonteCarloRequest {
+    /// Validated run config; a missing seed is drawn at random
+    pub fn config(&self) -> Result<MonteCarloConfig, RequestError> {
+        if self.draws == 0 || self.draws > MAX_MC_DRAWS {
+            return Err(RequestError::new("INVALID_DRAWS", format!("draws must be between 1 and {}", MAX_MC_DRAWS)));
+        }
+        if self.horizon_months == 0 || self.horizon_months > MAX_MC_HORIZON {
+            return Err(RequestError::new(
+                "INVALID_HORIZON",
+                format!("horizon_months must be between 1 and {}", MAX_MC_HORIZON),
+            ));
+        }
+        Ok(MonteCarloConfig {
+            horizon_months: self.horizon_months,
+            draws: self.draws,
+            seed: self.seed.unwrap_or_else(rand::random),
+            sampling: self.sampling,
+        })
+    }
+}
+
+/// Request body for the multi-date lookup
+#[derive(Debug, Deserialize)]
+pub struct BatchAtRequest {
+    pub dates: Vec<String>, // YYYY-MM or YYYY-MM-DD, answered in this order
+    #[serde(default)]
+    pub score: ScoreMode,
+    pub labels: Option<String>, // Recession label set for `is_recession` (default nber)
+}
+
+impl BatchAtRequest {
+    pub fn validate(&self) -> Result<(), RequestError> {
+        if self.dates.is_empty() || self.dates.len() > MAX_BATCH_DATES {
+            return Err(RequestError::new(
+                "INVALID_DATES",
+                format!("dates must list between 1 and {} dates", MAX_BATCH_DATES),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_spec_validation() {
+        let spec = |body: &str| serde_json::from_str::<SimulateRequest>(body).unwrap().engine;
+        let serving = NIVEngine::new();
+
+        assert_eq!(spec("{}").build(&serving).unwrap().eta(), niv::ETA);
+        assert_eq!(spec(r#"{"eta":0}"#).build(&serving).err().unwrap().code, "INVALID_ETA");
+        assert_eq!(spec(r#"{"epsilon":1.5}"#).build(&serving).err().unwrap().code, "INVALID_EPSILON");
+        assert!(serde_json::from_str::<SimulateRequest>(r#"{"eta":"high"}"#).is_err());
+        let uncertainty = |body: &str| serde_json::from_str::<SimulateRequest>(body).unwrap().uncertainty();
+        assert_eq!(uncertainty(r#"{"uncertainty":{"capacity_util":0.2}}"#).unwrap().unwrap().capacity_util, 0.2);
+        assert_eq!(uncertainty(r#"{"uncertainty":{"gdp":-1}}"#).unwrap_err().code, "INVALID_UNCERTAINTY");
+        let reflect = spec(r#"{"denominator":"reflect"}"#).build(&serving).unwrap();
+        assert_eq!(reflect.denominator_policy(), niv::DenominatorPolicy::Reflect);
+        let aged = serving.with_expansion_age_weight(0.5);
+        assert_eq!(spec("{}").build(&aged).unwrap().expansion_age_weight(), 0.5);
+        assert_eq!(spec(r#"{"expansion_age_weight":3}"#).build(&aged).err().unwrap().code, "INVALID_EXPANSION_AGE_WEIGHT");
+        // Deeply nested expressions are rejected, not parsed into a stack overflow
+        let nested = format!("{}1{}", "(".repeat(50_000), ")".repeat(50_000));
+        let body = serde_json::json!({"components": [{"name": "x", "role": "numerator", "expr": nested}]}).to_string();
+        assert_eq!(spec(&body).build(&aged).err().unwrap().code, "INVALID_COMPONENT");
+    }
+
+    #[test]
+    fn test_engine_spec_keeps_serving_pipelines() {
+        let pipelines: crate::transform::Pipelines =
+            serde_json::from_str(r#"{"investment":["deflate","log",{"diff":1}]}"#).unwrap();
+        let serving = NIVEngine::new().with_pipelines(Arc::new(pipelines));
+        let built = serde_json::from_str::<SimulateRequest>("{}").unwrap().engine.build(&serving).unwrap();
+        assert_eq!(built.parameters()["pipelines"], serving.parameters()["pipelines"]);
+        assert_ne!(built.parameters()["pipelines"], NIVEngine::new().parameters()["pipelines"]);
+    }
+
+    #[test]
+    fn test_monte_carlo_config_bounds() {
+        let request = |body: &str| serde_json::from_str::<MonteCarloRequest>(body).unwrap();
+
+        let config = request(r#"{"seed":3}"#).config().unwrap();
+        assert_eq!((config.horizon_months, config.draws, config.seed), (DEFAULT_MC_HORIZON, DEFAULT_MC_DRAWS, 3));
+        assert_eq!(request(r#"{"draws":0}"#).config().unwrap_err().code, "INVALID_DRAWS");
+        assert_eq!(request(r#"{"draws":5001}"#).config().unwrap_err().code, "INVALID_DRAWS");
+        assert_eq!(request(r#"{"horizon_months":37}"#).config().unwrap_err().code, "INVALID_HORIZON");
+
+        let batch = |body: &str| serde_json::from_str::<BatchAtRequest>(body).unwrap().validate();
+        assert_eq!(batch(r#"{"dates":["2008-09","2020-04-01"]}"#), Ok(()));
+        assert_eq!(batch(r#"{"dates":[]}"#).unwrap_err().code, "INVALID_DATES");
+        let many = format!(r#"{{"dates":[{}]}}"#, vec![r#""2008-09""#; MAX_BATCH_DATES + 1].join(","));
+        assert_eq!(batch(&many).unwrap_err().code, "INVALID_DATES");
+    }
+}
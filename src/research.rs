@@ -0,0 +1,253 @@
+//! Variant Leaderboard
+//!
+//! Runs the recession backtest (NBER, or any imported label set) across
+//! every registered engine variant so the methodology page can show how each
+//! specification choice moves the headline metrics. Each variant changes one setting from the v6 baseline:
+//! - slack specs (`output_gap`, `unemployment_gap`)
+//! - efficiency spec (`observed`)
+//! - thrust scaling (`rolling_std`)
+//! - probability model (`percentile`)
+//! - each custom registry component on its own
+//!
+//! Variants whose optional series are missing fall back to the baseline
+//! formula month by month, so they score identically on such data.
+//!
+//! With a `RegimeFilter` (see regimes.rs), AUC and Brier cover only the
+//! regime's months and lead times only the recessions entered from it;
+//! false alarms are still counted over the whole history.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::backtest;
+use crate::budget::{CancelToken, Cancelled};
+use crate::niv::{
+    ComponentWeights, EconomicData, EfficiencySpec, InflationSpec, NIVEngine, ProbabilityInput, SlackSpec, SpreadSpec, ThrustScaling,
+};
+use crate::regimes::RegimeFilter;
+use crate::registry::{ComponentDef, ComponentRegistry};
+
+/// Months before a recession start labelled positive for AUC/Brier
+pub const LABEL_HORIZON_MONTHS: u32 = 12;
+
+/// Rolling window for the `rolling_std` thrust variant
+pub const ROLLING_THRUST_WINDOW: usize = 120;
+
+/// Backtest metrics for one engine variant
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantScore {
+    pub name: String,
+    pub description: String,
+    pub auc: Option<f64>,
+    pub brier: Option<f64>,
+    pub mean_lead_months: Option<f64>,
+    pub detected: usize,
+    pub missed: usize,
+    pub false_alarms: usize,
+}
+
+/// Named engine configuration
+pub struct Variant {
+    pub name: String,
+    pub description: String,
+    pub engine: NIVEngine,
+}
+
+impl Variant {
+    fn new(name: impl Into<String>, description: impl Into<String>, engine: NIVEngine) -> Self {
+        Self { name: name.into(), description: description.into(), engine }
+    }
+}
+
+/// Baseline plus one-change-at-a-time variants, including each custom component
+pub fn variants(custom: &[ComponentDef]) -> Vec<Variant> {
+    let mut variants = vec![
+        Variant::new("v6", "Published v6 specification", NIVEngine::new()),
+        Variant::new(
+            "slack=output_gap",
+            "Slack from CBO potential GDP gap",
+            NIVEngine::new().with_slack_spec(SlackSpec::OutputGap),
+        ),
+        Variant::new(
+            "slack=unemployment_gap",
+            "Slack from unemployment gap vs NAIRU",
+            NIVEngine::new().with_slack_spec(SlackSpec::UnemploymentGap),
+        ),
+        Variant::new(
+            "efficiency=observed",
+            "Efficiency from investment plus real education spending",
+            NIVEngine::new().with_efficiency_spec(EfficiencySpec::Observed),
+        ),
+        Variant::new(
+            "inflation=pce",
+            "Real rate deflated by PCE inflation",
+            NIVEngine::new().with_inflation_spec(InflationSpec::Pce),
+        ),
+        Variant::new(
+            "inflation=expected",
+            "Ex-ante real rate from 5-year breakeven inflation",
+            NIVEngine::new().with_inflation_spec(InflationSpec::Expected),
+        ),
+        Variant::new(
+            "spread=level",
+            "Spread drag continuous in the T10Y3M level",
+            NIVEngine::new().with_spread_spec(SpreadSpec::Level),
+        ),
+        Variant::new(
+            "spread=change_12m",
+            "Spread drag from the 12-month change in T10Y3M",
+            NIVEngine::new().with_spread_spec(SpreadSpec::Change12m),
+        ),
+        Variant::new(
+            "thrust+m2_accel",
+            "Thrust with the M2 acceleration (second derivative) term",
+            NIVEngine::new().with_weights(ComponentWeights { thrust_m2_accel: 1.0, ..ComponentWeights::default() }),
+        ),
+        Variant::new(
+            "thrust=rolling_std",
+            "Thrust input scaled by its 10-year rolling std dev",
+            NIVEngine::new().with_thrust_scaling(ThrustScaling::RollingStd { window: ROLLING_THRUST_WINDOW }),
+        ),
+        Variant::new(
+            "probability=percentile",
+            "Probability from the rolling NIV percentile",
+            NIVEngine::new().with_probability_input(ProbabilityInput::Percentile),
+        ),
+    ];
+
+    for def in custom {
+        // Definitions come from an already-compiled registry
+        if let Ok(registry) = ComponentRegistry::compile(vec![def.clone()]) {
+            variants.push(Variant::new(
+                format!("custom:{}", def.name),
+                format!("v6 with {} term {}", def.role.as_str(), def.expr),
+                NIVEngine::new().with_registry(Arc::new(registry)),
+            ));
+        }
+    }
+
+    variants
+}
+
+/// Backtest one variant against a recession chronology
+pub fn score(
+    variant: &Variant,
+    data: &[EconomicData],
+    chronology: &[(NaiveDate, NaiveDate)],
+    threshold: f64,
+    lookback_months: u32,
+    regime: Option<&RegimeFilter>,
+) -> VariantScore {
+    let results = variant.engine.calculate_series(data);
+    let results = backtest::evaluated(&results);
+
+    let dates: Vec<_> = results.iter().map(|r| r.date).collect();
+    let labels = backtest::recession_labels(&dates, chronology, LABEL_HORIZON_MONTHS);
+    let (probs, labels): (Vec<f64>, Vec<bool>) = results
+        .iter()
+        .zip(labels)
+        .filter(|(r, _)| regime.is_none_or(|f| f.contains(r.date)))
+        .map(|(r, label)| (r.recession_probability, label))
+        .unzip();
+    let entered = regime.map(|f| f.recessions(chronology));
+    let leads = backtest::lead_time_distribution_for(results, entered.as_deref().unwrap_or(chronology), threshold, lookback_months);
+
+    VariantScore {
+        name: variant.name.clone(),
+        description: variant.description.clone(),
+        auc: backtest::auc(&probs, &labels),
+        brier: backtest::brier(&probs, &labels),
+        mean_lead_months: leads.mean_lead_months,
+        detected: leads.detected,
+        missed: leads.missed,
+        false_alarms: backtest::false_alarms(results, chronology, threshold, lookback_months),
+    }
+}
+
+/// Score every variant, best AUC first
+pub fn leaderboard(
+    variants: &[Variant],
+    data: &[EconomicData],
+    chronology: &[(NaiveDate, NaiveDate)],
+    threshold: f64,
+    lookback_months: u32,
+    regime: Option<&RegimeFilter>,
+    cancel: &CancelToken,
+) -> Result<Vec<VariantScore>, Cancelled> {
+    let mut scores: Vec<VariantScore> = variants
+        .iter()
+        .map(|v| {
+            cancel.check()?;
+            Ok(score(v, data, chronology, threshold, lookback_months, regime))
+        })
+        .collect::<Result<_, Cancelled>>()?;
+    scores.sort_by(|a, b| {
+        let key = |s: &VariantScore| s.auc.unwrap_or(f64::NEG_INFINITY);
+        key(b).total_cmp(&key(a))
+    });
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::registry::TermRole;
+
+    #[test]
+    fn test_variants_include_custom_components() {
+        let custom = vec![ComponentDef {
+            name: "spread".to_string(),
+            role: TermRole::Denominator,
+            expr: "max(0, -yield_spread) / 100".to_string(),
+        }];
+        let names: Vec<String> = variants(&custom).into_iter().map(|v| v.name).collect();
+        assert_eq!(names[0], "v6");
+        assert!(names.contains(&"probability=percentile".to_string()));
+        assert_eq!(names.last().unwrap(), "custom:spread");
+    }
+
+    #[test]
+    fn test_leaderboard_sorted_by_auc() {
+        let data = mock::generate_mock_data(1960, 2024);
+        let board = leaderboard(
+            &variants(&[]),
+            &data,
+            &crate::niv::RecessionPeriods::known_recessions(),
+            0.5,
+            backtest::DEFAULT_LOOKBACK_MONTHS,
+            None,
+            &CancelToken::default(),
+        )
+        .unwrap();
+
+        assert_eq!(board.len(), 11);
+        assert!(board.windows(2).all(|w| w[0].auc.unwrap() >= w[1].auc.unwrap()));
+        assert!(board.iter().all(|s| s.brier.is_some_and(|b| (0.0..=1.0).contains(&b))));
+    }
+
+    #[test]
+    fn test_regime_filter_restricts_scoring() {
+        use crate::regimes::{self, Method};
+
+        let data = mock::generate_mock_data(1960, 2024);
+        let chronology = crate::niv::RecessionPeriods::known_recessions();
+        let baseline = &variants(&[])[..1];
+        let results = baseline[0].engine.calculate_series(&data);
+        let fitted = regimes::cluster(&results, 4, Method::Kmeans);
+        let score = |regime: Option<&RegimeFilter>| leaderboard(baseline, &data, &chronology, 0.5, 12, regime, &CancelToken::default()).unwrap().remove(0);
+
+        // Each recession is entered from exactly one regime
+        let all = score(None);
+        let mut evaluated = 0;
+        for regime in 0..fitted.regimes.len() {
+            let filter = RegimeFilter::new(&results, &fitted.labels, regime);
+            let within = score(Some(&filter));
+            assert!(within.detected + within.missed <= filter.recessions(&chronology).len());
+            assert_eq!(within.false_alarms, all.false_alarms);
+            evaluated += within.detected + within.missed;
+        }
+        assert_eq!(evaluated, all.detected + all.missed);
+    }
+}
@@ -0,0 +1,246 @@
+//! Scenario Shock Engine
+//!
+//! Projects the economy forward from the latest observation and applies
+//! deterministic shocks to individual FRED inputs:
+//! - Level series (GPDIC1, M2SL, GDPC1) extend at their trailing 12-month growth rate
+//! - Rate series (FEDFUNDS, TCU, T10Y3M, CPI YoY) are held flat at their last value
+//!
+//! Shocks are expressed in the natural unit of each series: percent for levels,
+//! percentage points for rates. They start at `start_month` (1 = first projected
+//! month) and persist for `duration_months`, or until the horizon if omitted.
+
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::niv::EconomicData;
+
+/// Input series a shock can be applied to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShockTarget {
+    Investment,     // GPDIC1 - % shock
+    M2Supply,       // M2SL - % shock
+    FedFundsRate,   // FEDFUNDS - pp shock
+    Gdp,            // GDPC1 - % shock
+    CapacityUtil,   // TCU - pp shock
+    YieldSpread,    // T10Y3M - pp shock
+    CpiInflation,   // CPI YoY - pp shock
+}
+
+impl ShockTarget {
+    pub const COUNT: usize = 7;
+
+    pub fn all() -> [ShockTarget; Self::COUNT] {
+        [
+            ShockTarget::Investment,
+            ShockTarget::M2Supply,
+            ShockTarget::FedFundsRate,
+            ShockTarget::Gdp,
+            ShockTarget::CapacityUtil,
+            ShockTarget::YieldSpread,
+            ShockTarget::CpiInflation,
+        ]
+    }
+
+    /// Level series grow multiplicatively; rate series move additively
+    pub fn is_level(&self) -> bool {
+        matches!(self, ShockTarget::Investment | ShockTarget::M2Supply | ShockTarget::Gdp)
+    }
+
+    pub fn get(&self, data: &EconomicData) -> f64 {
+        match self {
+            ShockTarget::Investment => data.investment,
+            ShockTarget::M2Supply => data.m2_supply,
+            ShockTarget::FedFundsRate => data.fed_funds_rate,
+            ShockTarget::Gdp => data.gdp,
+            ShockTarget::CapacityUtil => data.capacity_util,
+            ShockTarget::YieldSpread => data.yield_spread,
+            ShockTarget::CpiInflation => data.cpi_inflation,
+        }
+    }
+
+    pub fn set(&self, data: &mut EconomicData, value: f64) {
+        match self {
+            ShockTarget::Investment => data.investment = value,
+            ShockTarget::M2Supply => data.m2_supply = value,
+            ShockTarget::FedFundsRate => data.fed_funds_rate = value.max(0.0),
+            ShockTarget::Gdp => data.gdp = value,
+            ShockTarget::CapacityUtil => data.capacity_util = value.clamp(0.0, 100.0),
+            ShockTarget::YieldSpread => data.yield_spread = value,
+            ShockTarget::CpiInflation => data.cpi_inflation = value,
+        }
+    }
+}
+
+/// A deterministic shock to one input series
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shock {
+    pub target: ShockTarget,
+    pub magnitude: f64,               // % for level series, pp for rate series
+    #[serde(default = "default_start_month")]
+    pub start_month: usize,           // 1 = first projected month
+    #[serde(default)]
+    pub duration_months: Option<usize>, // None = persists to the horizon
+}
+
+fn default_start_month() -> usize {
+    1
+}
+
+impl Shock {
+    /// Whether the shock is active in projected month `month` (1-indexed)
+    pub fn is_active(&self, month: usize) -> bool {
+        let started = month >= self.start_month;
+        let not_ended = self.duration_months
+            .map(|d| month < self.start_month + d)
+            .unwrap_or(true);
+        started && not_ended
+    }
+}
+
+/// A named set of shocks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    #[serde(default = "default_scenario_name")]
+    pub name: String,
+    #[serde(default)]
+    pub shocks: Vec<Shock>,
+}
+
+fn default_scenario_name() -> String {
+    "custom".to_string()
+}
+
+impl Scenario {
+    /// Unshocked baseline (useful as the "no scenario" reference)
+    pub fn baseline() -> Self {
+        Self {
+            name: "baseline".to_string(),
+            shocks: Vec::new(),
+        }
+    }
+
+    /// Project `horizon` months past the end of `history` and apply the shocks
+    pub fn project(&self, history: &[EconomicData], horizon: usize) -> Vec<EconomicData> {
+        let mut path = baseline_path(history, horizon);
+        self.apply(&mut path);
+        path
+    }
+
+    /// Apply shocks in place to a projected path (index 0 = month 1)
+    pub fn apply(&self, path: &mut [EconomicData]) {
+        for (i, point) in path.iter_mut().enumerate() {
+            let month = i + 1;
+            for shock in self.shocks.iter().filter(|s| s.is_active(month)) {
+                let current = shock.target.get(point);
+                let shocked = if shock.target.is_level() {
+                    current * (1.0 + shock.magnitude / 100.0)
+                } else {
+                    current + shock.magnitude
+                };
+                shock.target.set(point, shocked);
+            }
+        }
+    }
+}
+
+/// Extend history forward without shocks
+/// Level series compound at their trailing 12-month average monthly growth, rates stay flat
+pub fn baseline_path(history: &[EconomicData], horizon: usize) -> Vec<EconomicData> {
+    let last = match history.last() {
+        Some(l) => l,
+        None => return Vec::new(),
+    };
+
+    let growth = |target: ShockTarget| -> f64 {
+        if history.len() < 13 {
+            return 0.0;
+        }
+        let year_ago = target.get(&history[history.len() - 13]);
+        let current = target.get(last);
+        if year_ago > 0.0 && current > 0.0 {
+            (current / year_ago).powf(1.0 / 12.0) - 1.0
+        } else {
+            0.0
+        }
+    };
+    let g_investment = growth(ShockTarget::Investment);
+    let g_m2 = growth(ShockTarget::M2Supply);
+    let g_gdp = growth(ShockTarget::Gdp);
+
+    (1..=horizon)
+        .map(|m| {
+            let n = m as i32;
+            EconomicData {
+                date: add_months(last.date, m),
+                investment: last.investment * (1.0 + g_investment).powi(n),
+                m2_supply: last.m2_supply * (1.0 + g_m2).powi(n),
+                gdp: last.gdp * (1.0 + g_gdp).powi(n),
+                ..last.clone()
+            }
+        })
+        .collect()
+}
+
+fn add_months(date: NaiveDate, months: usize) -> NaiveDate {
+    date.checked_add_months(Months::new(months as u32)).unwrap_or(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    #[test]
+    fn test_baseline_path_extends_dates() {
+        let history = mock::generate_mock_data(2015, 2019);
+        let path = baseline_path(&history, 6);
+
+        assert_eq!(path.len(), 6);
+        assert_eq!(path[0].date, NaiveDate::from_ymd_opt(2020, 1, 1).unwrap());
+        assert_eq!(path[5].date, NaiveDate::from_ymd_opt(2020, 6, 1).unwrap());
+        // Rates held flat
+        assert_eq!(path[5].fed_funds_rate, history.last().unwrap().fed_funds_rate);
+    }
+
+    #[test]
+    fn test_shock_window() {
+        let history = mock::generate_mock_data(2015, 2019);
+        let scenario = Scenario {
+            name: "rate hike".to_string(),
+            shocks: vec![Shock {
+                target: ShockTarget::FedFundsRate,
+                magnitude: 2.0,
+                start_month: 3,
+                duration_months: Some(2),
+            }],
+        };
+        let base = baseline_path(&history, 6);
+        let shocked = scenario.project(&history, 6);
+
+        assert_eq!(shocked[1].fed_funds_rate, base[1].fed_funds_rate);
+        assert!((shocked[2].fed_funds_rate - base[2].fed_funds_rate - 2.0).abs() < 1e-9);
+        assert!((shocked[3].fed_funds_rate - base[3].fed_funds_rate - 2.0).abs() < 1e-9);
+        assert_eq!(shocked[4].fed_funds_rate, base[4].fed_funds_rate);
+    }
+
+    #[test]
+    fn test_level_shock_is_percent() {
+        let history = mock::generate_mock_data(2015, 2019);
+        let scenario = Scenario {
+            name: "investment bust".to_string(),
+            shocks: vec![Shock {
+                target: ShockTarget::Investment,
+                magnitude: -20.0,
+                start_month: 1,
+                duration_months: None,
+            }],
+        };
+        let base = baseline_path(&history, 3);
+        let shocked = scenario.project(&history, 3);
+
+        for (b, s) in base.iter().zip(&shocked) {
+            assert!((s.investment / b.investment - 0.8).abs() < 1e-9);
+        }
+    }
+}
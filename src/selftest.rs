@@ -0,0 +1,322 @@
+//! Startup Self-Test
+//!
+//! Runs once the dataset has loaded, before the server reports ready:
+//! - Invariants: scores finite and within ±NIV_CLAMP, probabilities in [0, 1],
+//!   dates strictly ascending
+//! - Benchmarks: the historical episode checks (`validate_against_benchmarks`)
+//! - Storage: the snapshot file, when configured, can be opened
+//! - Provider: FRED answers a small request, when FRED_API_KEY is set
+//!
+//! The failure policy (`NIV_SELFTEST_POLICY`) decides what a failed check
+//! means: `warn` logs it and serves anyway, `refuse` keeps the server unready.
+//!
+//! The invariant and benchmark checks only say something if they hold on
+//! real data. `sensitivity` lines up their outcomes on the embedded dataset
+//! and on freshly fetched FRED data (`POST /api/v1/admin/validation/live`)
+//! and flags every check whose outcome differs, in particular checks that
+//! pass only on the embedded data, as they would if the mock generator had
+//! been tuned to the engine.
+
+use std::path::Path;
+#[cfg(feature = "fred")]
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::fred::FredAccess;
+#[cfg(feature = "fred")]
+use crate::fred::{FredClient, FredError};
+use crate::niv::{NIVResult, ValidationResult, NIV_CLAMP};
+
+/// Slack for rounding in the smoothed window means
+const CLAMP_TOLERANCE: f64 = 1e-9;
+
+/// Timeout for the provider reachability check
+#[cfg(feature = "fred")]
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a check covers
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckCategory {
+    Invariant,
+    Benchmark,
+    Storage,
+    Provider,
+}
+
+/// One self-test check
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    pub category: CheckCategory,
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+    pub passed: bool,
+}
+
+impl SelfTestCheck {
+    fn new(category: CheckCategory, name: &str, expected: &str, actual: String, passed: bool) -> Self {
+        Self {
+            category,
+            name: name.to_string(),
+            expected: expected.to_string(),
+            actual,
+            passed,
+        }
+    }
+}
+
+/// What to do when a check fails
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FailurePolicy {
+    #[default]
+    Warn,
+    Refuse,
+}
+
+impl std::str::FromStr for FailurePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "warn" => Ok(FailurePolicy::Warn),
+            "refuse" => Ok(FailurePolicy::Refuse),
+            other => Err(format!("unknown self-test policy '{}' (expected warn | refuse)", other)),
+        }
+    }
+}
+
+/// Outcome of the startup self-test
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub policy: FailurePolicy,
+    pub serving: bool, // False when a check failed under the refuse policy
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    pub fn new(policy: FailurePolicy, checks: Vec<SelfTestCheck>) -> Self {
+        let passed = checks.iter().all(|c| c.passed);
+        Self {
+            passed,
+            policy,
+            serving: passed || policy == FailurePolicy::Warn,
+            checks,
+        }
+    }
+}
+
+/// One check's outcome on the embedded and the live dataset; None where the
+/// dataset does not cover the check (e.g. live data ending before 2020)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CheckComparison {
+    pub category: CheckCategory,
+    pub name: String,
+    pub expected: String,
+    pub embedded: Option<String>,
+    pub live: Option<String>,
+    pub embedded_passed: Option<bool>,
+    pub live_passed: Option<bool>,
+    pub diverges: bool,
+}
+
+/// Checks compared across datasets
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SensitivityReport {
+    pub divergences: usize,
+    pub embedded_only_passes: usize, // Passed on embedded data, failed or not evaluated on live
+    pub checks: Vec<CheckComparison>,
+}
+
+/// Pair checks by name, embedded order first, then checks only run on live data
+pub fn sensitivity(embedded: &[SelfTestCheck], live: &[SelfTestCheck]) -> SensitivityReport {
+    let mut checks: Vec<CheckComparison> = embedded
+        .iter()
+        .map(|e| (e, live.iter().find(|l| l.name == e.name)))
+        .map(|(e, l)| CheckComparison {
+            category: e.category,
+            name: e.name.clone(),
+            expected: e.expected.clone(),
+            embedded: Some(e.actual.clone()),
+            live: l.map(|l| l.actual.clone()),
+            embedded_passed: Some(e.passed),
+            live_passed: l.map(|l| l.passed),
+            diverges: l.is_none_or(|l| l.passed != e.passed),
+        })
+        .collect();
+    checks.extend(live.iter().filter(|l| !embedded.iter().any(|e| e.name == l.name)).map(|l| CheckComparison {
+        category: l.category,
+        name: l.name.clone(),
+        expected: l.expected.clone(),
+        embedded: None,
+        live: Some(l.actual.clone()),
+        embedded_passed: None,
+        live_passed: Some(l.passed),
+        diverges: true,
+    }));
+    SensitivityReport {
+        divergences: checks.iter().filter(|c| c.diverges).count(),
+        embedded_only_passes: checks
+            .iter()
+            .filter(|c| c.embedded_passed == Some(true) && c.live_passed != Some(true))
+            .count(),
+        checks,
+    }
+}
+
+/// Formula invariants over the computed history
+pub fn invariants(results: &[NIVResult]) -> Vec<SelfTestCheck> {
+    let bad_scores = results
+        .iter()
+        .filter(|r| !r.niv_score.is_finite() || r.niv_score.abs() > NIV_CLAMP + CLAMP_TOLERANCE)
+        .count();
+    let bad_probabilities = results
+        .iter()
+        .filter(|r| !(0.0..=1.0).contains(&r.recession_probability))
+        .count();
+    let unordered = results.windows(2).filter(|w| w[0].date >= w[1].date).count();
+
+    vec![
+        SelfTestCheck::new(
+            CheckCategory::Invariant,
+            "Non-empty history",
+            "At least one result",
+            format!("{} results", results.len()),
+            !results.is_empty(),
+        ),
+        SelfTestCheck::new(
+            CheckCategory::Invariant,
+            "Score bounds",
+            "Every NIV score finite and within ±100",
+            format!("{} out of bounds", bad_scores),
+            bad_scores == 0,
+        ),
+        SelfTestCheck::new(
+            CheckCategory::Invariant,
+            "Probability bounds",
+            "Every recession probability in [0, 1]",
+            format!("{} out of bounds", bad_probabilities),
+            bad_probabilities == 0,
+        ),
+        SelfTestCheck::new(
+            CheckCategory::Invariant,
+            "Date order",
+            "Dates strictly ascending",
+            format!("{} out of order", unordered),
+            unordered == 0,
+        ),
+    ]
+}
+
+/// Historical benchmark checks
+pub fn benchmarks(validation: &ValidationResult) -> Vec<SelfTestCheck> {
+    validation
+        .checks
+        .iter()
+        .map(|c| SelfTestCheck {
+            category: CheckCategory::Benchmark,
+            name: c.name.clone(),
+            expected: c.expected.clone(),
+            actual: c.actual.clone(),
+            passed: c.passed,
+        })
+        .collect()
+}
+
+/// Snapshot storage is reachable
+pub fn storage(snapshot_path: Option<&Path>) -> SelfTestCheck {
+    let expected = "Snapshot file readable";
+    let (actual, passed) = match snapshot_path {
+        None => ("skipped: NIV_SNAPSHOT_FILE not set".to_string(), true),
+        Some(path) => match std::fs::File::open(path).and_then(|f| f.metadata()) {
+            Ok(meta) => (format!("{} ({} bytes)", path.display(), meta.len()), true),
+            Err(e) => (format!("{}: {}", path.display(), e), false),
+        },
+    };
+    SelfTestCheck::new(CheckCategory::Storage, "Snapshot storage", expected, actual, passed)
+}
+
+/// FRED is reachable with the configured key
+#[cfg(feature = "fred")]
+pub async fn provider(access: FredAccess) -> SelfTestCheck {
+    let expected = "FRED returns observations";
+    let (actual, passed) = match FredClient::new(access) {
+        Err(FredError::Offline) => ("skipped: FRED access is offline".to_string(), true),
+        Err(_) => ("skipped: FRED_API_KEY not set".to_string(), true),
+        Ok(client) => match tokio::time::timeout(PROVIDER_TIMEOUT, client.ping()).await {
+            Ok(Ok(observations)) if observations > 0 => (format!("{} observations", observations), true),
+            Ok(Ok(_)) => ("no observations returned".to_string(), false),
+            Ok(Err(e)) => (e.to_string(), false),
+            Err(_) => (format!("timed out after {}s", PROVIDER_TIMEOUT.as_secs()), false),
+        },
+    };
+    SelfTestCheck::new(CheckCategory::Provider, "FRED reachability", expected, actual, passed)
+}
+
+/// Built without the `fred` feature: nothing to reach
+#[cfg(not(feature = "fred"))]
+pub async fn provider(_access: FredAccess) -> SelfTestCheck {
+    SelfTestCheck::new(
+        CheckCategory::Provider,
+        "FRED reachability",
+        "FRED returns observations",
+        "skipped: built without the fred feature".to_string(),
+        true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_invariants_flag_bad_results() {
+        let mut results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2000, 2010));
+        assert!(invariants(&results).iter().all(|c| c.passed));
+
+        results[5].niv_score = f64::NAN;
+        results[6].recession_probability = 1.5;
+        results.swap(10, 11);
+        let failed: Vec<_> = invariants(&results)
+            .into_iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(failed, ["Score bounds", "Probability bounds", "Date order"]);
+        assert!(!invariants(&[])[0].passed);
+    }
+
+    #[test]
+    fn test_policy_decides_serving() {
+        let failing = vec![SelfTestCheck::new(CheckCategory::Storage, "x", "y", String::new(), false)];
+        assert!(SelfTestReport::new(FailurePolicy::Warn, failing.clone()).serving);
+        assert!(!SelfTestReport::new(FailurePolicy::Refuse, failing).serving);
+        assert!(SelfTestReport::new(FailurePolicy::Refuse, Vec::new()).serving);
+
+        assert_eq!("REFUSE".parse::<FailurePolicy>(), Ok(FailurePolicy::Refuse));
+        assert!("strict".parse::<FailurePolicy>().is_err());
+        assert!(!storage(Some(Path::new("/nonexistent/niv.arrow"))).passed);
+    }
+
+    #[test]
+    fn test_sensitivity_flags_embedded_only_passes() {
+        let check = |name: &str, passed: bool| SelfTestCheck::new(CheckCategory::Benchmark, name, "x", String::new(), passed);
+        let embedded = [check("2008 GFC Detection", true), check("2020 COVID Response", true), check("Stability", false)];
+        let live = [check("2008 GFC Detection", true), check("Stability", true), check("Live only", false)];
+        let report = sensitivity(&embedded, &live);
+        let diverging: Vec<&str> = report.checks.iter().filter(|c| c.diverges).map(|c| c.name.as_str()).collect();
+
+        assert_eq!(diverging, ["2020 COVID Response", "Stability", "Live only"]);
+        assert_eq!((report.divergences, report.embedded_only_passes), (3, 1));
+        assert_eq!(report.checks[1].live_passed, None);
+
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2000, 2010));
+        let same = invariants(&results);
+        assert_eq!(sensitivity(&same, &same).divergences, 0);
+    }
+}
@@ -0,0 +1,202 @@
+//! Recession Severity
+//!
+//! How deep a recession would be if one came, fitted on past episodes:
+//!
+//!   severity_k = a + b × p_k
+//!
+//! where p_k is the recession probability in the month before episode k
+//! began, and severity is either
+//! - the peak-to-trough decline in real GDP (GDPC1, percent): the peak over
+//!   the PEAK_LEAD_MONTHS before the start, the trough from the start to
+//!   TROUGH_LAG_MONTHS after the end
+//! - the rise in the slack component over the same window (percentage points
+//!   of slack × 100)
+//!
+//! The band is the fitted value ± BAND_Z residual standard deviations
+//! (a p10-p90 range under normal residuals), floored at zero. Probabilities
+//! outside the range the episodes cover are held to its ends rather than
+//! extrapolated. With a handful of episodes this is coarse: it says whether
+//! the signal level has historically gone with deeper or shallower downturns,
+//! not how deep the next one will be.
+
+use std::collections::HashMap;
+
+use chrono::{Months, NaiveDate};
+use serde::Serialize;
+
+use crate::niv::{EconomicData, NIVResult};
+
+/// Months before the start searched for the GDP peak
+pub const PEAK_LEAD_MONTHS: u32 = 6;
+/// Months after the end searched for the trough
+pub const TROUGH_LAG_MONTHS: u32 = 6;
+/// Fewest episodes a fit needs (two parameters plus a residual)
+pub const MIN_EPISODES: usize = 3;
+
+const BAND_Z: f64 = 1.2816; // Standard normal 90th percentile
+
+/// One past recession with its signal and outcome
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeverityEpisode {
+    pub start: NaiveDate,
+    pub probability: f64, // Month before the start
+    pub gdp_decline: f64, // Percent, peak to trough
+    pub slack_increase: f64, // Percentage points
+}
+
+/// Least-squares line with its residual spread
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Fit {
+    intercept: f64,
+    slope: f64,
+    residual_std: f64,
+}
+
+impl Fit {
+    fn new(points: &[(f64, f64)]) -> Self {
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+        let intercept = mean_y - slope * mean_x;
+        let sse: f64 = points.iter().map(|(x, y)| (y - intercept - slope * x).powi(2)).sum();
+        Self { intercept, slope, residual_std: (sse / (n - 2.0)).sqrt() }
+    }
+
+    fn band(&self, x: f64) -> SeverityBand {
+        let expected = (self.intercept + self.slope * x).max(0.0);
+        let spread = BAND_Z * self.residual_std;
+        SeverityBand { expected, p10: (expected - spread).max(0.0), p90: expected + spread }
+    }
+}
+
+/// Expected severity and its p10-p90 range
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SeverityBand {
+    pub expected: f64,
+    pub p10: f64,
+    pub p90: f64,
+}
+
+/// Severity of a recession, were one to start after a month at `probability`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SeverityForecast {
+    pub gdp_decline: SeverityBand,
+    pub slack_increase: SeverityBand,
+}
+
+/// Fitted severity model
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeverityModel {
+    gdp: Fit,
+    slack: Fit,
+    pub episodes: usize,
+    pub probabilities: (f64, f64), // Range of p_k over the episodes
+}
+
+impl SeverityModel {
+    /// Fit on the recessions in `chronology` fully covered by the data; None
+    /// with fewer than MIN_EPISODES
+    pub fn fit(results: &[NIVResult], inputs: &[EconomicData], chronology: &[(NaiveDate, NaiveDate)]) -> Option<Self> {
+        let episodes = episodes(results, inputs, chronology);
+        if episodes.len() < MIN_EPISODES {
+            return None;
+        }
+        let points = |outcome: fn(&SeverityEpisode) -> f64| -> Vec<(f64, f64)> {
+            episodes.iter().map(|e| (e.probability, outcome(e))).collect()
+        };
+        let probabilities = episodes
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), e| (lo.min(e.probability), hi.max(e.probability)));
+        Some(Self {
+            gdp: Fit::new(&points(|e| e.gdp_decline)),
+            slack: Fit::new(&points(|e| e.slack_increase)),
+            episodes: episodes.len(),
+            probabilities,
+        })
+    }
+
+    /// Bands at `probability`, held to the range the episodes cover
+    pub fn predict(&self, probability: f64) -> SeverityForecast {
+        let x = probability.clamp(self.probabilities.0, self.probabilities.1);
+        SeverityForecast { gdp_decline: self.gdp.band(x), slack_increase: self.slack.band(x) }
+    }
+}
+
+/// Past recessions with a prior month and the whole trough window in the data
+pub fn episodes(
+    results: &[NIVResult],
+    inputs: &[EconomicData],
+    chronology: &[(NaiveDate, NaiveDate)],
+) -> Vec<SeverityEpisode> {
+    let gdp: HashMap<NaiveDate, f64> = inputs.iter().map(|d| (d.date, d.gdp)).collect();
+    let slack: HashMap<NaiveDate, f64> = results.iter().map(|r| (r.date, r.components.slack)).collect();
+    let probability: HashMap<NaiveDate, f64> = results.iter().map(|r| (r.date, r.recession_probability)).collect();
+    let months = |from: NaiveDate, to: NaiveDate| {
+        std::iter::successors(Some(from), |d| d.checked_add_months(Months::new(1))).take_while(move |d| *d <= to)
+    };
+
+    let mut episodes: Vec<SeverityEpisode> = chronology
+        .iter()
+        .filter_map(|&(start, end)| {
+            let before = start.checked_sub_months(Months::new(1))?;
+            let lead = start.checked_sub_months(Months::new(PEAK_LEAD_MONTHS))?;
+            let lag = end.checked_add_months(Months::new(TROUGH_LAG_MONTHS))?;
+            // Every month of the window must be present
+            let peak_gdp = months(lead, start).map(|d| gdp.get(&d).copied()).collect::<Option<Vec<_>>>()?;
+            let trough_gdp = months(start, lag).map(|d| gdp.get(&d).copied()).collect::<Option<Vec<_>>>()?;
+            let window_slack = months(start, lag).map(|d| slack.get(&d).copied()).collect::<Option<Vec<_>>>()?;
+
+            let peak = peak_gdp.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let trough = trough_gdp.iter().copied().fold(f64::INFINITY, f64::min);
+            let slack_peak = window_slack.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            Some(SeverityEpisode {
+                start,
+                probability: *probability.get(&before)?,
+                gdp_decline: ((peak - trough) / peak * 100.0).max(0.0),
+                slack_increase: ((slack_peak - slack.get(&before)?) * 100.0).max(0.0),
+            })
+        })
+        .filter(|e| e.gdp_decline.is_finite() && e.slack_increase.is_finite())
+        .collect();
+    episodes.sort_by_key(|e| e.start);
+    episodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::{NIVEngine, RecessionPeriods};
+
+    #[test]
+    fn test_fit_recovers_line() {
+        let fit = Fit::new(&[(0.1, 1.0), (0.2, 2.0), (0.3, 3.0), (0.4, 4.0)]);
+        assert!((fit.slope - 10.0).abs() < 1e-9 && fit.intercept.abs() < 1e-9);
+        assert!(fit.residual_std < 1e-9);
+
+        let band = fit.band(0.25);
+        assert!((band.expected - 2.5).abs() < 1e-9);
+        assert_eq!(fit.band(-1.0).expected, 0.0); // Floored
+    }
+
+    #[test]
+    fn test_model_on_mock_history() {
+        let inputs = mock::generate_mock_data(1985, 2026);
+        let results = NIVEngine::new().calculate_series(&inputs);
+        let chronology = RecessionPeriods::known_recessions();
+        let episodes = episodes(&results, &inputs, &chronology);
+
+        // Recessions before the data starts are skipped
+        assert!(episodes.len() >= MIN_EPISODES && episodes.len() < chronology.len());
+        assert!(episodes.windows(2).all(|w| w[0].start < w[1].start));
+
+        let model = SeverityModel::fit(&results, &inputs, &chronology).unwrap();
+        let band = model.predict(0.3).gdp_decline;
+        assert!(band.p10 <= band.expected && band.expected <= band.p90);
+        assert_eq!(model.predict(2.0), model.predict(model.probabilities.1)); // No extrapolation
+        assert!(SeverityModel::fit(&results, &inputs, &chronology[..1]).is_none());
+    }
+}
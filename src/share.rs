@@ -0,0 +1,144 @@
+//! Share Links
+//!
+//! Read-only links to what-if results: `POST /api/v1/share` signs a
+//! simulation or Monte Carlo request into an expiring token, and
+//! `GET /s/{token}` serves its result to anyone holding the link.
+//!
+//! The token is `base64url(claims JSON).base64url(HMAC-SHA256(claims))`,
+//! keyed by NIV_SHARE_SECRET. Without the secret a random per-process key
+//! is used, so links stop working on restart. The claims pin the pipeline
+//! hash (model, engine parameters and dataset revision) the result was
+//! computed on, so a link shows the same numbers every time it is opened:
+//! once that pipeline is no longer served it answers 410 SHARE_STALE
+//! rather than recompute against different data. A Monte Carlo link's run
+//! is stored when the link is created and served from the run store; it is
+//! only rerun (with its pinned seed) if the store has evicted it.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+
+/// Link lifetime when none is requested
+pub const DEFAULT_TTL_HOURS: u32 = 7 * 24;
+pub const MAX_TTL_HOURS: u32 = 30 * 24;
+
+/// What the link re-runs
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareKind {
+    Simulation, // POST /api/v1/simulate
+    MonteCarlo, // POST /api/v1/montecarlo
+}
+
+/// Signed token contents
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Claims {
+    pub kind: ShareKind,
+    pub request: Value, // The endpoint's request body
+    pub pipeline: String, // Pipeline hash the result was computed on
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ShareError {
+    Malformed,
+    BadSignature,
+    Expired(DateTime<Utc>),
+}
+
+impl std::fmt::Display for ShareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareError::Malformed => write!(f, "Malformed share token"),
+            ShareError::BadSignature => write!(f, "Share token signature does not match"),
+            ShareError::Expired(at) => write!(f, "Share link expired at {}", at.to_rfc3339()),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+/// Signs and verifies share tokens
+pub struct ShareSigner {
+    key: Vec<u8>,
+}
+
+impl ShareSigner {
+    pub fn new(secret: &[u8]) -> Self {
+        Self { key: secret.to_vec() }
+    }
+
+    /// Per-process key; tokens do not survive a restart
+    pub fn random() -> Self {
+        Self::new(&rand::random::<[u8; 32]>())
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(payload);
+        mac
+    }
+
+    pub fn sign(&self, claims: &Claims) -> String {
+        let payload = serde_json::to_vec(claims).expect("claims serialize");
+        let signature = self.mac(&payload).finalize().into_bytes();
+        format!("{}.{}", URL_SAFE_NO_PAD.encode(&payload), URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    /// Claims of a genuine, unexpired token
+    pub fn verify(&self, token: &str, now: DateTime<Utc>) -> Result<Claims, ShareError> {
+        let (payload, signature) = token.split_once('.').ok_or(ShareError::Malformed)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| ShareError::Malformed)?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| ShareError::Malformed)?;
+        self.mac(&payload).verify_slice(&signature).map_err(|_| ShareError::BadSignature)?;
+
+        let claims: Claims = serde_json::from_slice(&payload).map_err(|_| ShareError::Malformed)?;
+        if claims.expires_at <= now {
+            return Err(ShareError::Expired(claims.expires_at));
+        }
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn claims(expires_at: DateTime<Utc>) -> Claims {
+        Claims {
+            kind: ShareKind::MonteCarlo,
+            request: serde_json::json!({ "draws": 100, "seed": 7 }),
+            pipeline: "0123abcd".to_string(),
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_expiry() {
+        let signer = ShareSigner::new(b"secret");
+        let now = Utc::now();
+        let token = signer.sign(&claims(now + Duration::hours(1)));
+
+        assert!(!token.contains(['/', '+', '=']));
+        assert_eq!(signer.verify(&token, now).unwrap(), claims(now + Duration::hours(1)));
+        assert!(matches!(signer.verify(&token, now + Duration::hours(2)), Err(ShareError::Expired(_))));
+    }
+
+    #[test]
+    fn test_rejects_tampered_and_foreign_tokens() {
+        let signer = ShareSigner::new(b"secret");
+        let now = Utc::now();
+        let token = signer.sign(&claims(now + Duration::hours(1)));
+        let (_, signature) = token.split_once('.').unwrap();
+
+        let forged = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims(now + Duration::days(365))).unwrap());
+        assert_eq!(signer.verify(&format!("{}.{}", forged, signature), now), Err(ShareError::BadSignature));
+        assert_eq!(ShareSigner::new(b"other").verify(&token, now), Err(ShareError::BadSignature));
+        assert_eq!(signer.verify("not-a-token", now), Err(ShareError::Malformed));
+    }
+}
@@ -0,0 +1,674 @@
+//! Dataset Snapshot
+//!
+//! Persists the startup dataset (inputs, raw and smoothed results) to an
+//! Arrow IPC file and loads it back, so a restart skips recomputing the
+//! history. The file is read in one go and its record batches decoded from
+//! that buffer; the columns are then copied into the engine's structs, so
+//! mapping the file instead would save nothing.
+//!
+//! Layout: one row per input month. Result columns (`raw_*`, `smoothed_*`)
+//! are null for months without a result (the YoY warm-up). Registry terms are
+//! stored as `raw_custom:<name>` / `smoothed_custom:<name>`, quality flags
+//! as a `<prefix>_quality` bitmask over `QualityFlag::ALL`, and estimated
+//! inputs (partial data) as `estimated` / `<prefix>_coverage` bitmasks over
+//! `InputSeries::ALL`. `<prefix>_warmup` marks months before the smoothing
+//! window fills.
+//!
+//! The schema metadata records a fingerprint of the engine specification;
+//! a snapshot written under a different specification is rejected. Spec keys
+//! added since a snapshot was written count as their defaults, so a new
+//! option does not invalidate snapshots that never used it.
+//!
+//! The metadata also records the layout version. Older files are upgraded on
+//! load by running each batch through `MIGRATIONS` in order; to change the
+//! layout, bump `SCHEMA_VERSION` and append the step from the previous one.
+//! Files from a newer build are refused rather than misread.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::builder::{Float64Builder, UInt16Builder, UInt8Builder};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Date32Type, Float64Type, UInt16Type, UInt8Type};
+use arrow_array::{Array, ArrayRef, BooleanArray, Date32Array, PrimitiveArray, RecordBatch};
+use arrow_buffer::Buffer;
+use arrow_ipc::reader::{read_footer_length, FileDecoder};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema};
+use chrono::NaiveDate;
+
+use crate::niv::{
+    AlertLevel, Dataset, EconomicData, InputSeries, NIVComponents, NIVEngine, NIVResult, QualityFlag, SMOOTH_WINDOW,
+};
+use crate::registry::{CustomTerm, TermRole};
+
+/// Schema metadata key holding the engine fingerprint
+const FINGERPRINT_KEY: &str = "niv.fingerprint";
+
+/// Schema metadata key holding the layout version (absent in version 1)
+const SCHEMA_VERSION_KEY: &str = "niv.schema_version";
+
+/// Layout version written by this build:
+/// 1. inputs, results, alert level, nowcast flag and registry terms
+/// 2. adds `<prefix>_quality`
+/// 3. adds `estimated` and `<prefix>_coverage`
+/// 4. adds `<prefix>_warmup`
+const SCHEMA_VERSION: u32 = 4;
+
+/// Upgrades a decoded batch by one layout version
+type Migration = fn(RecordBatch) -> Result<RecordBatch, SnapshotError>;
+
+/// `MIGRATIONS[i]` upgrades a batch from version `i + 1` to `i + 2`
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize - 1] = [add_quality_columns, add_coverage_columns, add_warmup_columns];
+
+/// Arrow IPC trailer: 4-byte footer length + "ARROW1"
+const TRAILER_LEN: usize = 10;
+
+/// Snapshot read/write failure
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    Arrow(ArrowError),
+    Invalid(String),
+    Stale,
+    Newer(u32),
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "snapshot I/O error: {}", e),
+            SnapshotError::Arrow(e) => write!(f, "snapshot decode error: {}", e),
+            SnapshotError::Invalid(e) => write!(f, "invalid snapshot: {}", e),
+            SnapshotError::Stale => write!(f, "snapshot was written by a different engine specification"),
+            SnapshotError::Newer(v) => {
+                write!(f, "snapshot layout v{} is newer than this build supports (v{})", v, SCHEMA_VERSION)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(e: std::io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+impl From<ArrowError> for SnapshotError {
+    fn from(e: ArrowError) -> Self {
+        SnapshotError::Arrow(e)
+    }
+}
+
+/// Identifies the engine specification a dataset was computed under
+pub fn fingerprint(engine: &NIVEngine) -> String {
+    let mut parameters = engine.parameters();
+    parameters.insert("crate_version".to_string(), env!("CARGO_PKG_VERSION").into());
+    serde_json::Value::Object(parameters).to_string()
+}
+
+/// Whether a stored fingerprint matches `engine`; keys missing from the
+/// stored one must be at their default, except `method_revision`, which
+/// snapshots from before revision 2 lack
+fn fingerprint_matches(stored: &str, engine: &NIVEngine) -> bool {
+    let parse = |s: &str| serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(s).ok();
+    let (Some(stored), Some(current), Some(mut defaults)) =
+        (parse(stored), parse(&fingerprint(engine)), parse(&fingerprint(&NIVEngine::new())))
+    else {
+        return false;
+    };
+    defaults.remove("method_revision");
+    stored.keys().all(|key| current.contains_key(key))
+        && current.iter().all(|(key, value)| stored.get(key).or_else(|| defaults.get(key)) == Some(value))
+}
+
+type InputColumn = (&'static str, fn(&EconomicData) -> Option<f64>);
+
+const INPUT_COLUMNS: [InputColumn; 16] = [
+    ("investment", |d| Some(d.investment)),
+    ("m2_supply", |d| Some(d.m2_supply)),
+    ("fed_funds_rate", |d| Some(d.fed_funds_rate)),
+    ("gdp", |d| Some(d.gdp)),
+    ("capacity_util", |d| Some(d.capacity_util)),
+    ("yield_spread", |d| Some(d.yield_spread)),
+    ("cpi_inflation", |d| Some(d.cpi_inflation)),
+    ("rd_investment", |d| d.rd_investment),
+    ("education_spending", |d| d.education_spending),
+    ("potential_gdp", |d| d.potential_gdp),
+    ("unemployment_rate", |d| d.unemployment_rate),
+    ("nairu", |d| d.nairu),
+    ("gdp_nowcast", |d| d.gdp_nowcast),
+    ("core_cpi_inflation", |d| d.core_cpi_inflation),
+    ("pce_inflation", |d| d.pce_inflation),
+    ("expected_inflation", |d| d.expected_inflation),
+];
+
+type ResultColumn = (&'static str, fn(&NIVResult) -> f64);
+
+const RESULT_COLUMNS: [ResultColumn; 11] = [
+    ("niv_score", |r| r.niv_score),
+    ("recession_probability", |r| r.recession_probability),
+    ("niv_percentile", |r| r.niv_percentile),
+    ("thrust", |r| r.components.thrust),
+    ("efficiency", |r| r.components.efficiency),
+    ("efficiency_squared", |r| r.components.efficiency_squared),
+    ("slack", |r| r.components.slack),
+    ("drag", |r| r.components.drag),
+    ("drag_spread", |r| r.components.drag_spread),
+    ("drag_real_rate", |r| r.components.drag_real_rate),
+    ("drag_volatility", |r| r.components.drag_volatility),
+];
+
+const ALERT_LEVELS: [AlertLevel; 4] = [
+    AlertLevel::Normal,
+    AlertLevel::Elevated,
+    AlertLevel::Warning,
+    AlertLevel::Critical,
+];
+
+fn alert_code(level: AlertLevel) -> u8 {
+    ALERT_LEVELS.iter().position(|l| *l == level).unwrap_or(0) as u8
+}
+
+/// Append the result columns for one prefix, aligned to `dates`
+fn result_columns(
+    prefix: &str,
+    dates: &[NaiveDate],
+    results: &[NIVResult],
+    custom: &[String],
+    fields: &mut Vec<Field>,
+    columns: &mut Vec<ArrayRef>,
+) {
+    let by_date: HashMap<NaiveDate, &NIVResult> = results.iter().map(|r| (r.date, r)).collect();
+    let rows: Vec<Option<&NIVResult>> = dates.iter().map(|d| by_date.get(d).copied()).collect();
+
+    for (name, get) in RESULT_COLUMNS {
+        let mut builder = Float64Builder::with_capacity(rows.len());
+        for row in &rows {
+            builder.append_option(row.map(get));
+        }
+        fields.push(Field::new(format!("{}_{}", prefix, name), DataType::Float64, true));
+        columns.push(Arc::new(builder.finish()));
+    }
+
+    let mut alerts = UInt8Builder::with_capacity(rows.len());
+    for row in &rows {
+        alerts.append_option(row.map(|r| alert_code(r.alert_level)));
+    }
+    fields.push(Field::new(format!("{}_alert_level", prefix), DataType::UInt8, true));
+    columns.push(Arc::new(alerts.finish()));
+
+    let nowcast: BooleanArray = rows.iter().map(|row| row.map(|r| r.components.gdp_nowcast)).collect();
+    fields.push(Field::new(format!("{}_gdp_nowcast", prefix), DataType::Boolean, true));
+    columns.push(Arc::new(nowcast));
+
+    let mut quality = UInt16Builder::with_capacity(rows.len());
+    for row in &rows {
+        quality.append_option(row.map(|r| quality_bits(&r.quality)));
+    }
+    fields.push(Field::new(format!("{}_quality", prefix), DataType::UInt16, true));
+    columns.push(Arc::new(quality.finish()));
+
+    let mut coverage = UInt8Builder::with_capacity(rows.len());
+    for row in &rows {
+        coverage.append_option(row.map(|r| series_bits(&r.coverage)));
+    }
+    fields.push(Field::new(format!("{}_coverage", prefix), DataType::UInt8, true));
+    columns.push(Arc::new(coverage.finish()));
+
+    let warmup: BooleanArray = rows.iter().map(|row| row.map(|r| r.warmup)).collect();
+    fields.push(Field::new(format!("{}_warmup", prefix), DataType::Boolean, true));
+    columns.push(Arc::new(warmup));
+
+    for term in custom {
+        let mut builder = Float64Builder::with_capacity(rows.len());
+        for row in &rows {
+            let value = row.and_then(|r| r.components.custom.iter().find(|c| &c.name == term)?.value);
+            builder.append_option(value);
+        }
+        fields.push(Field::new(format!("{}_custom:{}", prefix, term), DataType::Float64, true));
+        columns.push(Arc::new(builder.finish()));
+    }
+}
+
+fn quality_bits(flags: &[QualityFlag]) -> u16 {
+    flags.iter().fold(0, |bits, f| bits | 1 << (*f as u16))
+}
+
+fn quality_flags(bits: u16) -> Vec<QualityFlag> {
+    QualityFlag::ALL.into_iter().filter(|f| bits & 1 << (*f as u16) != 0).collect()
+}
+
+fn series_bits(series: &[InputSeries]) -> u8 {
+    series.iter().fold(0, |bits, s| bits | 1 << (*s as u8))
+}
+
+fn series_list(bits: u8) -> Vec<InputSeries> {
+    InputSeries::ALL.into_iter().filter(|s| bits & 1 << (*s as u8) != 0).collect()
+}
+
+/// Write the dataset to `path` (via a temporary file and rename)
+pub fn write(path: &Path, engine: &NIVEngine, dataset: &Dataset) -> Result<(), SnapshotError> {
+    let dates: Vec<NaiveDate> = dataset.inputs.iter().map(|d| d.date).collect();
+    let custom: Vec<String> = engine.registry().definitions().into_iter().map(|d| d.name).collect();
+
+    let mut fields = vec![Field::new("date", DataType::Date32, false)];
+    let mut columns: Vec<ArrayRef> = vec![Arc::new(Date32Array::from_iter_values(
+        dates.iter().map(|d| Date32Type::from_naive_date(*d)),
+    ))];
+    for (name, get) in INPUT_COLUMNS {
+        let values: PrimitiveArray<Float64Type> = dataset.inputs.iter().map(get).collect();
+        fields.push(Field::new(name, DataType::Float64, true));
+        columns.push(Arc::new(values));
+    }
+    let estimated: PrimitiveArray<UInt8Type> =
+        dataset.inputs.iter().map(|d| Some(series_bits(&d.estimated))).collect();
+    fields.push(Field::new("estimated", DataType::UInt8, false));
+    columns.push(Arc::new(estimated));
+    result_columns("raw", &dates, &dataset.raw, &custom, &mut fields, &mut columns);
+    result_columns("smoothed", &dates, &dataset.smoothed, &custom, &mut fields, &mut columns);
+
+    let metadata = HashMap::from([
+        (FINGERPRINT_KEY.to_string(), fingerprint(engine)),
+        (SCHEMA_VERSION_KEY.to_string(), SCHEMA_VERSION.to_string()),
+    ]);
+    let schema = Arc::new(Schema::new_with_metadata(fields, metadata));
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let tmp = path.with_extension("tmp");
+    let mut writer = FileWriter::try_new(File::create(&tmp)?, &schema)?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Read the snapshot at `path` and decode its record batches
+fn read_batches(path: &Path) -> Result<(Arc<Schema>, Vec<RecordBatch>), SnapshotError> {
+    let buffer = Buffer::from(std::fs::read(path)?);
+
+    let trailer_start = buffer
+        .len()
+        .checked_sub(TRAILER_LEN)
+        .ok_or_else(|| SnapshotError::Invalid("file is too short".to_string()))?;
+    let trailer: [u8; TRAILER_LEN] = buffer[trailer_start..].try_into().expect("trailer length");
+    let footer_start = trailer_start
+        .checked_sub(read_footer_length(trailer)?)
+        .ok_or_else(|| SnapshotError::Invalid("footer length exceeds file".to_string()))?;
+    let footer = arrow_ipc::root_as_footer(&buffer[footer_start..trailer_start])
+        .map_err(|e| SnapshotError::Invalid(e.to_string()))?;
+
+    let schema = Arc::new(arrow_ipc::convert::fb_to_schema(
+        footer.schema().ok_or_else(|| SnapshotError::Invalid("missing schema".to_string()))?,
+    ));
+    let decoder = FileDecoder::new(schema.clone(), footer.version());
+
+    let mut batches = Vec::new();
+    for block in footer.recordBatches().into_iter().flatten() {
+        let len = block.bodyLength() as usize + block.metaDataLength() as usize;
+        let data = buffer.slice_with_length(block.offset() as usize, len);
+        if let Some(batch) = decoder.read_record_batch(block, &data)? {
+            batches.push(batch);
+        }
+    }
+    Ok((schema, batches))
+}
+
+/// Layout version of a snapshot schema
+fn schema_version(schema: &Schema) -> Result<u32, SnapshotError> {
+    match schema.metadata().get(SCHEMA_VERSION_KEY) {
+        None => Ok(1),
+        Some(raw) => raw
+            .parse()
+            .ok()
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| SnapshotError::Invalid(format!("bad schema version '{}'", raw))),
+    }
+}
+
+/// v1 → v2: results gain a quality bitmask; existing months are unflagged
+fn add_quality_columns(batch: RecordBatch) -> Result<RecordBatch, SnapshotError> {
+    let schema = batch.schema();
+    let mut fields: Vec<Field> = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+    let mut columns = batch.columns().to_vec();
+    for prefix in ["raw", "smoothed"] {
+        let score = f64_column(&batch, &format!("{}_niv_score", prefix))?;
+        let quality: PrimitiveArray<UInt16Type> = score.iter().map(|s| s.map(|_| 0)).collect();
+        fields.push(Field::new(format!("{}_quality", prefix), DataType::UInt16, true));
+        columns.push(Arc::new(quality));
+    }
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// v2 → v3: inputs and results gain coverage bitmasks; existing months were observed
+fn add_coverage_columns(batch: RecordBatch) -> Result<RecordBatch, SnapshotError> {
+    let schema = batch.schema();
+    let mut fields: Vec<Field> = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+    let mut columns = batch.columns().to_vec();
+    fields.push(Field::new("estimated", DataType::UInt8, false));
+    columns.push(Arc::new(PrimitiveArray::<UInt8Type>::from_value(0, batch.num_rows())));
+    for prefix in ["raw", "smoothed"] {
+        let score = f64_column(&batch, &format!("{}_niv_score", prefix))?;
+        let coverage: PrimitiveArray<UInt8Type> = score.iter().map(|s| s.map(|_| 0)).collect();
+        fields.push(Field::new(format!("{}_coverage", prefix), DataType::UInt8, true));
+        columns.push(Arc::new(coverage));
+    }
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+/// v3 → v4: results gain a warm-up flag; the snapshot was smoothed over
+/// SMOOTH_WINDOW, so its first SMOOTH_WINDOW - 1 smoothed months are warm-up
+fn add_warmup_columns(batch: RecordBatch) -> Result<RecordBatch, SnapshotError> {
+    let schema = batch.schema();
+    let mut fields: Vec<Field> = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+    let mut columns = batch.columns().to_vec();
+    for prefix in ["raw", "smoothed"] {
+        let score = f64_column(&batch, &format!("{}_niv_score", prefix))?;
+        let mut seen = 0;
+        let warmup: BooleanArray = score
+            .iter()
+            .map(|s| {
+                s.map(|_| {
+                    seen += 1;
+                    prefix == "smoothed" && seen < SMOOTH_WINDOW
+                })
+            })
+            .collect();
+        fields.push(Field::new(format!("{}_warmup", prefix), DataType::Boolean, true));
+        columns.push(Arc::new(warmup));
+    }
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    Ok(RecordBatch::try_new(Arc::new(schema), columns)?)
+}
+
+fn u8_column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a PrimitiveArray<UInt8Type>, SnapshotError> {
+    batch
+        .column_by_name(name)
+        .and_then(|c| c.as_primitive_opt::<UInt8Type>())
+        .ok_or_else(|| SnapshotError::Invalid(format!("missing {} column", name)))
+}
+
+fn f64_column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a PrimitiveArray<Float64Type>, SnapshotError> {
+    batch
+        .column_by_name(name)
+        .and_then(|c| c.as_primitive_opt::<Float64Type>())
+        .ok_or_else(|| SnapshotError::Invalid(format!("missing Float64 column '{}'", name)))
+}
+
+/// Decode the results for one prefix; rows with a null score have no result
+fn read_results(
+    batch: &RecordBatch,
+    prefix: &str,
+    dates: &[NaiveDate],
+    custom: &[(String, TermRole)],
+) -> Result<Vec<NIVResult>, SnapshotError> {
+    let column = |name: &str| f64_column(batch, &format!("{}_{}", prefix, name));
+    let score = column("niv_score")?;
+    let (probability, percentile) = (column("recession_probability")?, column("niv_percentile")?);
+    let (thrust, efficiency, efficiency_squared) =
+        (column("thrust")?, column("efficiency")?, column("efficiency_squared")?);
+    let (slack, drag) = (column("slack")?, column("drag")?);
+    let (drag_spread, drag_real_rate, drag_volatility) =
+        (column("drag_spread")?, column("drag_real_rate")?, column("drag_volatility")?);
+    let alerts = u8_column(batch, &format!("{}_alert_level", prefix))?;
+    let coverage = u8_column(batch, &format!("{}_coverage", prefix))?;
+    let warmup = batch
+        .column_by_name(&format!("{}_warmup", prefix))
+        .and_then(|c| c.as_boolean_opt())
+        .ok_or_else(|| SnapshotError::Invalid(format!("missing {}_warmup column", prefix)))?;
+    let nowcast = batch
+        .column_by_name(&format!("{}_gdp_nowcast", prefix))
+        .and_then(|c| c.as_boolean_opt())
+        .ok_or_else(|| SnapshotError::Invalid(format!("missing {}_gdp_nowcast column", prefix)))?;
+    let quality = batch
+        .column_by_name(&format!("{}_quality", prefix))
+        .and_then(|c| c.as_primitive_opt::<UInt16Type>())
+        .ok_or_else(|| SnapshotError::Invalid(format!("missing {}_quality column", prefix)))?;
+    let custom_columns = custom
+        .iter()
+        .map(|(name, role)| Ok((name, *role, column(&format!("custom:{}", name))?)))
+        .collect::<Result<Vec<_>, SnapshotError>>()?;
+
+    let mut results = Vec::new();
+    for (row, date) in dates.iter().enumerate() {
+        if score.is_null(row) {
+            continue;
+        }
+        let alert_level = *ALERT_LEVELS
+            .get(alerts.value(row) as usize)
+            .ok_or_else(|| SnapshotError::Invalid(format!("bad alert level at {}", date)))?;
+        results.push(NIVResult {
+            date: *date,
+            niv_score: score.value(row),
+            recession_probability: probability.value(row),
+            components: NIVComponents {
+                thrust: thrust.value(row),
+                efficiency: efficiency.value(row),
+                efficiency_squared: efficiency_squared.value(row),
+                slack: slack.value(row),
+                drag: drag.value(row),
+                drag_spread: drag_spread.value(row),
+                drag_real_rate: drag_real_rate.value(row),
+                drag_volatility: drag_volatility.value(row),
+                gdp_nowcast: nowcast.value(row),
+                custom: custom_columns
+                    .iter()
+                    .map(|(name, role, values)| CustomTerm {
+                        name: name.to_string(),
+                        role: *role,
+                        value: (!values.is_null(row)).then(|| values.value(row)),
+                    })
+                    .collect(),
+            },
+            alert_level,
+            niv_percentile: percentile.value(row),
+            quality: quality_flags(quality.value(row)),
+            coverage: series_list(coverage.value(row)),
+            warmup: warmup.value(row),
+        });
+    }
+    Ok(results)
+}
+
+/// Load a snapshot written under the same engine specification, upgrading
+/// an older layout
+pub fn load(path: &Path, engine: &NIVEngine) -> Result<Dataset, SnapshotError> {
+    let (schema, batches) = read_batches(path)?;
+    if !schema.metadata().get(FINGERPRINT_KEY).is_some_and(|stored| fingerprint_matches(stored, engine)) {
+        return Err(SnapshotError::Stale);
+    }
+    let version = schema_version(&schema)?;
+    if version > SCHEMA_VERSION {
+        return Err(SnapshotError::Newer(version));
+    }
+    let custom: Vec<_> = engine
+        .registry()
+        .definitions()
+        .into_iter()
+        .map(|d| (d.name, d.role))
+        .collect();
+
+    let mut dataset = Dataset { inputs: Vec::new(), raw: Vec::new(), smoothed: Vec::new() };
+    for batch in batches {
+        let batch = MIGRATIONS[version as usize - 1..].iter().try_fold(batch, |batch, migrate| migrate(batch))?;
+        let dates: Vec<NaiveDate> = batch
+            .column_by_name("date")
+            .and_then(|c| c.as_primitive_opt::<Date32Type>())
+            .ok_or_else(|| SnapshotError::Invalid("missing date column".to_string()))?
+            .values()
+            .iter()
+            .map(|&days| Date32Type::to_naive_date(days))
+            .collect();
+
+        let inputs = INPUT_COLUMNS
+            .iter()
+            .map(|(name, _)| f64_column(&batch, name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let estimated = u8_column(&batch, "estimated")?;
+        let value = |col: usize, row: usize| (!inputs[col].is_null(row)).then(|| inputs[col].value(row));
+        let required = |col: usize, row: usize, date: &NaiveDate| {
+            value(col, row).ok_or_else(|| {
+                SnapshotError::Invalid(format!("null {} at {}", INPUT_COLUMNS[col].0, date))
+            })
+        };
+        for (row, date) in dates.iter().enumerate() {
+            dataset.inputs.push(EconomicData {
+                date: *date,
+                investment: required(0, row, date)?,
+                m2_supply: required(1, row, date)?,
+                fed_funds_rate: required(2, row, date)?,
+                gdp: required(3, row, date)?,
+                capacity_util: required(4, row, date)?,
+                yield_spread: required(5, row, date)?,
+                cpi_inflation: required(6, row, date)?,
+                rd_investment: value(7, row),
+                education_spending: value(8, row),
+                potential_gdp: value(9, row),
+                unemployment_rate: value(10, row),
+                nairu: value(11, row),
+                gdp_nowcast: value(12, row),
+                core_cpi_inflation: value(13, row),
+                pce_inflation: value(14, row),
+                expected_inflation: value(15, row),
+                estimated: series_list(estimated.value(row)),
+            });
+        }
+
+        dataset.raw.extend(read_results(&batch, "raw", &dates, &custom)?);
+        dataset.smoothed.extend(read_results(&batch, "smoothed", &dates, &custom)?);
+    }
+    Ok(dataset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::SlackSpec;
+    use crate::registry::{ComponentDef, ComponentRegistry};
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("niv-snapshot-{}-{}.arrow", name, std::process::id()))
+    }
+
+    fn dataset(engine: &NIVEngine) -> Dataset {
+        let inputs = mock::generate_mock_data(1990, 2020);
+        let raw = engine.calculate_raw(&inputs);
+        let smoothed = engine.smooth(&raw, crate::niv::SMOOTH_WINDOW);
+        Dataset { inputs, raw, smoothed }
+    }
+
+    #[test]
+    fn test_round_trip_preserves_dataset() {
+        let registry = ComponentRegistry::compile(vec![ComponentDef {
+            name: "curve".to_string(),
+            role: TermRole::Denominator,
+            expr: "max(0, -yield_spread) / 10".to_string(),
+        }])
+        .unwrap();
+        let engine = NIVEngine::new().with_registry(Arc::new(registry));
+        let mut original = dataset(&engine);
+        original.raw[100].quality = vec![QualityFlag::Input, QualityFlag::Drag];
+        original.raw[100].coverage = vec![InputSeries::M2Supply, InputSeries::CpiInflation];
+        original.inputs.last_mut().unwrap().estimated = vec![InputSeries::CapacityUtil];
+        let path = temp_path("round-trip");
+
+        write(&path, &engine, &original).unwrap();
+        let loaded = load(&path, &engine).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.inputs.len(), original.inputs.len());
+        assert_eq!(loaded.raw.len(), original.raw.len());
+        assert_eq!(loaded.smoothed.len(), original.smoothed.len());
+        assert_eq!(
+            serde_json::to_value(&loaded.smoothed).unwrap(),
+            serde_json::to_value(&original.smoothed).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&loaded.inputs).unwrap(),
+            serde_json::to_value(&original.inputs).unwrap()
+        );
+        assert_eq!(loaded.raw[100].components.custom, original.raw[100].components.custom);
+        assert_eq!(loaded.raw[100].quality, original.raw[100].quality);
+        assert_eq!(loaded.raw[100].coverage, original.raw[100].coverage);
+    }
+
+    #[test]
+    fn test_snapshot_from_other_specification_is_stale() {
+        let engine = NIVEngine::new();
+        let path = temp_path("stale");
+        write(&path, &engine, &dataset(&engine)).unwrap();
+
+        let other = NIVEngine::new().with_slack_spec(SlackSpec::OutputGap);
+        let result = load(&path, &other);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(SnapshotError::Stale)));
+    }
+
+    #[test]
+    fn test_snapshot_from_earlier_method_revision_is_stale() {
+        let engine = NIVEngine::new();
+        let path = temp_path("revision");
+        write(&path, &engine, &dataset(&engine)).unwrap();
+        rewrite(&path, |_| true, |metadata| {
+            let mut stored: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_str(&metadata[FINGERPRINT_KEY]).unwrap();
+            stored.remove("method_revision");
+            metadata.insert(FINGERPRINT_KEY.to_string(), serde_json::Value::Object(stored).to_string());
+        });
+        let result = load(&path, &engine);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(SnapshotError::Stale)));
+    }
+
+    /// Rewrite the snapshot at `path` with columns and metadata edited
+    fn rewrite(path: &Path, keep: impl Fn(&str) -> bool, metadata: impl Fn(&mut HashMap<String, String>)) {
+        let (schema, batches) = read_batches(path).unwrap();
+        let indices: Vec<usize> = (0..schema.fields().len()).filter(|i| keep(schema.field(*i).name())).collect();
+        let mut edited = schema.metadata().clone();
+        metadata(&mut edited);
+        let schema = Arc::new(schema.project(&indices).unwrap().with_metadata(edited));
+        let tmp = path.with_extension("tmp"); // The batches still map `path`
+        let mut writer = FileWriter::try_new(File::create(&tmp).unwrap(), &schema).unwrap();
+        for batch in batches {
+            let columns = batch.project(&indices).unwrap().columns().to_vec();
+            writer.write(&RecordBatch::try_new(schema.clone(), columns).unwrap()).unwrap();
+        }
+        writer.finish().unwrap();
+        std::fs::rename(&tmp, path).unwrap();
+    }
+
+    #[test]
+    fn test_older_layout_migrates_and_newer_is_refused() {
+        let engine = NIVEngine::new();
+        let original = dataset(&engine);
+        let path = temp_path("migrate");
+
+        // Version 1: no quality columns, no version key, fingerprint predating the policy options
+        write(&path, &engine, &original).unwrap();
+        rewrite(&path, |name| !name.ends_with("_quality"), |metadata| {
+            metadata.remove(SCHEMA_VERSION_KEY);
+            let mut stored: serde_json::Value = serde_json::from_str(&metadata[FINGERPRINT_KEY]).unwrap();
+            stored.as_object_mut().unwrap().retain(|key, _| key != "nonfinite" && key != "denominator");
+            metadata.insert(FINGERPRINT_KEY.to_string(), stored.to_string());
+        });
+        let loaded = load(&path, &engine).unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded.smoothed).unwrap(),
+            serde_json::to_value(&original.smoothed).unwrap()
+        );
+        let reflect = NIVEngine::new().with_denominator_policy(crate::niv::DenominatorPolicy::Reflect);
+        assert!(matches!(load(&path, &reflect), Err(SnapshotError::Stale)));
+
+        rewrite(&path, |_| true, |metadata| {
+            metadata.insert(SCHEMA_VERSION_KEY.to_string(), (SCHEMA_VERSION + 1).to_string());
+        });
+        let result = load(&path, &engine);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(result, Err(SnapshotError::Newer(v)) if v == SCHEMA_VERSION + 1));
+    }
+}
@@ -0,0 +1,216 @@
+//! Streaming Exports
+//!
+//! `/api/v1/export?format=csv` writes history to the response body in chunks
+//! rather than building the file in memory. A producer task reads up to
+//! `chunk_rows` months from the installed dataset, encodes them and hands
+//! them to the body through a one-slot channel, so the next chunk is only
+//! read once the client has taken the last. A request holds at most two
+//! chunks (one queued, one being sent), sized to fit the per-request ceiling
+//! NIV_EXPORT_MEMORY_BYTES.
+//!
+//! An export is pinned to the pipeline hash it started on (see namespace.rs):
+//! a refresh or promotion mid-export ends the body with an error instead of
+//! mixing datasets. The hash is in the X-NIV-Pipeline header and, shortened,
+//! in the file name.
+//!
+//! The `recession` column flags the months of the request's label set
+//! (`labels=`, see labels.rs).
+//!
+//! Rows carry six decimals, or the request's `precision=`, and the
+//! probability column follows `probability_units=` (see format.rs). The
+//! producer runs after the handler has returned, so it is handed the
+//! request's format rather than reading it from the request scope.
+//!
+//! Workbooks (xlsx) are zipped whole and cannot stream, so one whose
+//! estimated size exceeds the ceiling is refused in favour of CSV. Parquet
+//! output would use the same producer once a Parquet writer is a dependency.
+
+use std::fmt::Write;
+
+use axum::http::HeaderValue;
+
+use crate::format::{Format, ProbabilityUnits};
+use crate::labels::LabelSet;
+use crate::niv::NIVResult;
+
+pub const CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Per-request memory ceiling unless configured otherwise
+pub const DEFAULT_EXPORT_MEMORY_BYTES: usize = 16 << 20;
+
+/// Decimals per value unless the request sets `precision=`
+pub const CSV_DECIMALS: u32 = 6;
+
+/// Upper bound on an encoded row: at most MAX_PRECISION (+2 for fractions)
+/// decimals and components within ±COMPONENT_LIMIT keep every field short
+pub const MAX_CSV_ROW_BYTES: usize = 160;
+
+/// Estimated workbook bytes per month while it is built (two sheets of cells
+/// plus the zipped output)
+pub const XLSX_ROW_BYTES: usize = 1024;
+
+/// Hex digits of the pipeline hash in export file names
+const FILENAME_HASH_LEN: usize = 12;
+
+/// `<model>-<pipeline prefix>.<extension>`
+pub fn filename(model_version: &str, pipeline: &str, extension: &str) -> String {
+    let prefix = pipeline.get(..FILENAME_HASH_LEN).unwrap_or(pipeline);
+    format!("{}-{}.{}", model_version.to_lowercase(), prefix, extension)
+}
+
+/// File name stem when a name cannot go in a header
+pub const FALLBACK_STEM: &str = "niv-export";
+
+/// `Content-Disposition` attaching `filename`, or `FALLBACK_STEM.<extension>`
+/// when the name would not make a valid quoted header value
+pub fn attachment(filename: &str, extension: &str) -> HeaderValue {
+    Some(filename)
+        .filter(|name| !name.contains(['"', '\\']))
+        .and_then(|name| HeaderValue::from_str(&format!("attachment; filename=\"{}\"", name)).ok())
+        .unwrap_or_else(|| {
+            HeaderValue::from_str(&format!("attachment; filename=\"{}.{}\"", FALLBACK_STEM, extension))
+                .unwrap_or(HeaderValue::from_static("attachment"))
+        })
+}
+
+/// Rows per chunk so that the two chunks in flight fit `ceiling`
+pub fn chunk_rows(ceiling: usize) -> usize {
+    (ceiling / 2 / MAX_CSV_ROW_BYTES).max(1)
+}
+
+/// Whether a workbook of `rows` months fits `ceiling`
+pub fn xlsx_fits(rows: usize, ceiling: usize) -> bool {
+    rows.saturating_mul(XLSX_ROW_BYTES) <= ceiling
+}
+
+/// Header row; the probability column is named for `format`'s units
+pub fn csv_header(format: Format) -> String {
+    let probability = match format.units {
+        ProbabilityUnits::Percent => "recession_probability_pct",
+        ProbabilityUnits::Fraction => "recession_probability",
+    };
+    format!("date,niv_score,{},alert_level,thrust,efficiency,slack,drag,recession\n", probability)
+}
+
+/// Encoded rows in `format`, without the header; `recession` flags the
+/// months `recessions` covers
+pub fn csv_chunk(rows: &[NIVResult], format: Format, recessions: &LabelSet) -> String {
+    let digits = format.decimals(CSV_DECIMALS) as usize;
+    let (probability_scale, probability_digits) = match format.units {
+        ProbabilityUnits::Percent => (100.0, digits),
+        ProbabilityUnits::Fraction => (1.0, digits + 2),
+    };
+    let mut out = String::with_capacity(rows.len() * MAX_CSV_ROW_BYTES);
+    for r in rows {
+        let c = &r.components;
+        let _ = writeln!(
+            out,
+            "{},{:.d$},{:.p$},{},{:.d$},{:.d$},{:.d$},{:.d$},{}",
+            r.date,
+            r.niv_score,
+            r.recession_probability * probability_scale,
+            r.alert_level.label(),
+            c.thrust,
+            c.efficiency,
+            c.slack,
+            c.drag,
+            recessions.contains(r.date) as u8,
+            d = digits,
+            p = probability_digits,
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::format::MAX_PRECISION;
+    use crate::labels::{self, LabelFormat, LabelSets};
+    use crate::niv::{NIVComponents, NIVEngine, COMPONENT_LIMIT, NIV_CLAMP};
+
+    #[test]
+    fn test_chunk_sizes_fit_ceiling() {
+        assert_eq!(chunk_rows(0), 1);
+        let rows = chunk_rows(DEFAULT_EXPORT_MEMORY_BYTES);
+        assert!(2 * rows * MAX_CSV_ROW_BYTES <= DEFAULT_EXPORT_MEMORY_BYTES);
+        assert!(xlsx_fits(800, DEFAULT_EXPORT_MEMORY_BYTES));
+        assert!(!xlsx_fits(usize::MAX, DEFAULT_EXPORT_MEMORY_BYTES));
+        assert_eq!(filename("NIV-v6", &"ab".repeat(32), "csv"), "niv-v6-abababababab.csv");
+    }
+
+    #[test]
+    fn test_attachment_falls_back_on_unsafe_names() {
+        assert_eq!(attachment("niv-v6-abab.csv", "csv"), "attachment; filename=\"niv-v6-abab.csv\"");
+        for unsafe_name in ["v6\r\nSet-Cookie: x.csv", "v6\".csv", "v6\\.csv", "v6\u{7f}.csv"] {
+            assert_eq!(attachment(unsafe_name, "csv"), "attachment; filename=\"niv-export.csv\"", "{:?}", unsafe_name);
+        }
+    }
+
+    #[test]
+    fn test_csv_rows_stay_under_bound() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2005, 2012));
+        let nber = LabelSets::default().get(None).unwrap();
+        let csv = csv_chunk(&results, Format::default(), &nber);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), results.len());
+        assert!(lines.iter().all(|l| l.len() < MAX_CSV_ROW_BYTES));
+        let columns = csv_header(Format::default()).trim_end().split(',').count();
+        assert!(lines.iter().all(|l| l.split(',').count() == columns));
+        assert!(lines[0].starts_with(&results[0].date.to_string()));
+
+        // Widest fields at the widest format
+        let mut extreme = results[0].clone();
+        extreme.niv_score = -NIV_CLAMP;
+        extreme.recession_probability = 1.0;
+        extreme.components = NIVComponents {
+            thrust: -COMPONENT_LIMIT,
+            efficiency: -COMPONENT_LIMIT,
+            slack: -COMPONENT_LIMIT,
+            drag: -COMPONENT_LIMIT,
+            ..extreme.components
+        };
+        for units in [ProbabilityUnits::Percent, ProbabilityUnits::Fraction] {
+            let widest = Format { precision: Some(MAX_PRECISION), units };
+            assert!(csv_chunk(std::slice::from_ref(&extreme), widest, &nber).len() < MAX_CSV_ROW_BYTES);
+        }
+    }
+
+    #[test]
+    fn test_csv_follows_the_request_format() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2005, 2006));
+        let row = &results[0];
+        let nber = LabelSets::default().get(None).unwrap();
+        let fraction = Format { precision: Some(3), units: ProbabilityUnits::Fraction };
+
+        assert!(csv_header(Format::default()).contains(",recession_probability_pct,"));
+        assert!(csv_header(fraction).contains(",recession_probability,"));
+        let fields: Vec<String> = csv_chunk(std::slice::from_ref(row), fraction, &nber).trim_end().split(',').map(String::from).collect();
+        assert_eq!(fields[1], format!("{:.3}", row.niv_score));
+        assert_eq!(fields[2], format!("{:.5}", row.recession_probability));
+        let default: Vec<String> = csv_chunk(std::slice::from_ref(row), Format::default(), &nber).split(',').map(String::from).collect();
+        assert_eq!(default[2], format!("{:.6}", row.recession_probability * 100.0));
+    }
+
+    #[test]
+    fn test_csv_flags_the_chosen_labels() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2008, 2010));
+        let nber = LabelSets::default().get(None).unwrap();
+        let late = labels::parse("late", "start,end\n2009-01,2010-06\n", LabelFormat::Csv).unwrap();
+        let flags = |set: &LabelSet| -> Vec<(String, bool)> {
+            csv_chunk(&results, Format::default(), set)
+                .lines()
+                .map(|l| (l[..10].to_string(), l.ends_with(",1")))
+                .collect()
+        };
+
+        for ((date, nber_flag), (_, late_flag)) in flags(&nber).into_iter().zip(flags(&late)) {
+            let date: chrono::NaiveDate = date.parse().unwrap();
+            assert_eq!(nber_flag, nber.contains(date));
+            assert_eq!(late_flag, late.contains(date));
+        }
+        assert_ne!(flags(&nber), flags(&late));
+    }
+}
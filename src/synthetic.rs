@@ -0,0 +1,312 @@
+//! Synthetic Economy Generator
+//!
+//! Unlike `fred::mock`, which replays a hand-tuned version of US history, this
+//! module generates economies from a controllable recession-generating process,
+//! so detection power can be measured against known ground truth.
+//!
+//! Process:
+//! - Expansions last `min_expansion_months` plus a geometric tail with the
+//!   monthly `recession_hazard`
+//! - The final `lead_months` of every expansion are a pre-recession phase:
+//!   M2 growth decelerates, investment growth fades, the Fed hikes and the
+//!   yield curve flattens into inversion, all scaled by `signal_strength`
+//! - Recessions last a uniform number of months in `recession_months`:
+//!   investment and GDP contract, capacity utilization falls, the Fed cuts
+//! - Every series carries Gaussian noise scaled by `noise`
+
+use chrono::{Months, NaiveDate};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand::distributions::Distribution;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::Normal;
+
+use crate::backtest;
+use crate::budget::{CancelToken, Cancelled};
+use crate::niv::{EconomicData, NIVEngine};
+
+/// Parameters of the recession-generating process
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SyntheticConfig {
+    pub months: usize,
+    pub start_year: i32,
+    pub seed: u64,
+    pub recession_hazard: f64,       // Monthly probability an eligible expansion ends
+    pub min_expansion_months: usize,
+    pub recession_months: (usize, usize), // Inclusive duration range
+    pub lead_months: usize,          // Length of the pre-recession phase
+    pub signal_strength: f64,        // 0 = recessions arrive without warning, 1 = full deterioration
+    pub noise: f64,                  // Multiplier on per-series noise
+}
+
+impl Default for SyntheticConfig {
+    fn default() -> Self {
+        Self {
+            months: 720,
+            start_year: 1960,
+            seed: 1,
+            recession_hazard: 0.02,
+            min_expansion_months: 36,
+            recession_months: (6, 18),
+            lead_months: 12,
+            signal_strength: 1.0,
+            noise: 1.0,
+        }
+    }
+}
+
+/// Generated data with ground-truth recession dates
+#[derive(Debug, Clone)]
+pub struct SyntheticEconomy {
+    pub data: Vec<EconomicData>,
+    pub recessions: Vec<(NaiveDate, NaiveDate)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Expansion,
+    PreRecession(usize), // Months into the lead window
+    Recession,
+}
+
+/// Generate one synthetic economy
+pub fn generate(config: &SyntheticConfig) -> SyntheticEconomy {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let normal = Normal::new(0.0, 1.0).expect("standard normal");
+    let start = NaiveDate::from_ymd_opt(config.start_year, 1, 1).unwrap_or_default();
+
+    // Recession schedule (month indices, inclusive)
+    let mut schedule: Vec<(usize, usize)> = Vec::new();
+    let mut t = 0;
+    loop {
+        let mut expansion = config.min_expansion_months.max(config.lead_months + 1);
+        while !rng.gen_bool(config.recession_hazard.clamp(1e-6, 1.0)) {
+            expansion += 1;
+        }
+        let (lo, hi) = config.recession_months;
+        let duration = rng.gen_range(lo.max(1)..=hi.max(lo.max(1)));
+        let begin = t + expansion;
+        if begin >= config.months {
+            break;
+        }
+        let end = (begin + duration - 1).min(config.months - 1);
+        schedule.push((begin, end));
+        t = end + 1;
+    }
+
+    let phase_at = |m: usize| -> Phase {
+        for &(begin, end) in &schedule {
+            if (begin..=end).contains(&m) {
+                return Phase::Recession;
+            }
+            if m < begin && m + config.lead_months >= begin {
+                return Phase::PreRecession(config.lead_months - (begin - m));
+            }
+        }
+        Phase::Expansion
+    };
+
+    let s = config.signal_strength.clamp(0.0, 2.0);
+    let lead = config.lead_months.max(1) as f64;
+    let mut eps = |scale: f64| scale * config.noise * normal.sample(&mut rng);
+
+    let mut investment: f64 = 1000.0;
+    let mut m2: f64 = 500.0;
+    let mut gdp: f64 = 5000.0;
+    let mut capacity: f64 = 80.0;
+    let mut fed_funds: f64 = 4.0;
+    let mut spread: f64 = 1.5;
+    let mut inflation: f64 = 2.5;
+
+    let mut data = Vec::with_capacity(config.months);
+    for m in 0..config.months {
+        let phase = phase_at(m);
+        let progress = match phase {
+            Phase::PreRecession(k) => (k + 1) as f64 / lead,
+            _ => 0.0,
+        };
+
+        // Monthly growth rates (fractions)
+        let (g_inv, g_m2, g_gdp) = match phase {
+            Phase::Expansion => (0.0030, 0.0050, 0.0022),
+            Phase::PreRecession(_) => (
+                0.0030 - s * 0.0150 * progress,
+                0.0050 - s * 0.0150 * progress,
+                0.0022 - s * 0.0010 * progress,
+            ),
+            Phase::Recession => (-s * 0.0150 - 0.0020, -s * 0.0040, -s * 0.0030 - 0.0005),
+        };
+        investment *= 1.0 + g_inv + eps(0.004);
+        m2 *= 1.0 + g_m2 + eps(0.002);
+        gdp *= 1.0 + g_gdp + eps(0.001);
+
+        // Rates and utilization: mean reversion plus phase drift
+        let (d_cap, d_ff, d_spread) = match phase {
+            Phase::Expansion => (0.1 * (80.0 - capacity), 0.05 * (4.0 - fed_funds), 0.08 * (1.5 - spread)),
+            Phase::PreRecession(_) => (0.0, s * 0.15, -s * 2.5 / lead),
+            Phase::Recession => (-s * 1.0 - 0.2, -s * 0.40, s * 0.25),
+        };
+        capacity = (capacity + d_cap + eps(0.3)).clamp(55.0, 92.0);
+        fed_funds = (fed_funds + d_ff + eps(0.05)).max(0.0);
+        spread += d_spread + eps(0.08);
+        inflation += 0.05 * (2.5 - inflation) + eps(0.15);
+
+        data.push(EconomicData {
+            date: start.checked_add_months(Months::new(m as u32)).unwrap_or(start),
+            investment,
+            m2_supply: m2,
+            fed_funds_rate: fed_funds,
+            gdp,
+            capacity_util: capacity,
+            yield_spread: spread,
+            cpi_inflation: inflation,
+            rd_investment: None,
+            education_spending: None,
+            potential_gdp: None,
+            unemployment_rate: None,
+            nairu: None,
+            gdp_nowcast: None,
+            core_cpi_inflation: None,
+            pce_inflation: None,
+            expected_inflation: None,
+            estimated: Vec::new(),
+        });
+    }
+
+    let recessions = schedule
+        .iter()
+        .map(|&(begin, end)| (data[begin].date, data[end].date))
+        .collect();
+
+    SyntheticEconomy { data, recessions }
+}
+
+/// Detection metrics for one synthetic economy
+#[derive(Debug, Clone, Serialize)]
+pub struct SyntheticScore {
+    pub seed: u64,
+    pub recessions: usize,
+    pub auc: Option<f64>,
+    pub detected: usize,
+    pub mean_lead_months: Option<f64>,
+}
+
+/// Aggregate detection power across many synthetic economies
+#[derive(Debug, Clone, Serialize)]
+pub struct SyntheticBenchmark {
+    pub config: SyntheticConfig,
+    pub economies: usize,
+    pub total_recessions: usize,
+    pub mean_auc: Option<f64>,
+    pub detection_rate: Option<f64>,
+    pub mean_lead_months: Option<f64>,
+    pub runs: Vec<SyntheticScore>,
+}
+
+/// Months within `lead_months` before a recession start or inside a recession
+pub fn ground_truth_labels(economy: &SyntheticEconomy, dates: &[NaiveDate], lead_months: u32) -> Vec<bool> {
+    backtest::recession_labels(dates, &economy.recessions, lead_months)
+}
+
+/// Score the engine against one synthetic economy at `threshold`
+pub fn score(engine: &NIVEngine, config: &SyntheticConfig, threshold: f64) -> SyntheticScore {
+    let economy = generate(config);
+    let results = engine.calculate_series(&economy.data);
+
+    let dates: Vec<NaiveDate> = results.iter().map(|r| r.date).collect();
+    let scores: Vec<f64> = results.iter().map(|r| r.recession_probability).collect();
+    let labels = ground_truth_labels(&economy, &dates, config.lead_months as u32);
+
+    let leads = backtest::lead_time_distribution_for(
+        &results,
+        &economy.recessions,
+        threshold,
+        config.lead_months as u32 * 2,
+    );
+
+    SyntheticScore {
+        seed: config.seed,
+        recessions: leads.recessions_evaluated,
+        auc: backtest::auc(&scores, &labels),
+        detected: leads.detected,
+        mean_lead_months: leads.mean_lead_months,
+    }
+}
+
+/// Run the benchmark over `economies` seeds starting at `config.seed`
+pub fn benchmark(
+    engine: &NIVEngine,
+    config: &SyntheticConfig,
+    economies: usize,
+    threshold: f64,
+    cancel: &CancelToken,
+) -> Result<SyntheticBenchmark, Cancelled> {
+    let runs: Vec<SyntheticScore> = (0..economies as u64)
+        .map(|i| {
+            cancel.check()?;
+            let cfg = SyntheticConfig { seed: config.seed.wrapping_add(i), ..*config };
+            Ok(score(engine, &cfg, threshold))
+        })
+        .collect::<Result<_, Cancelled>>()?;
+
+    let aucs: Vec<f64> = runs.iter().filter_map(|r| r.auc).collect();
+    let total_recessions: usize = runs.iter().map(|r| r.recessions).sum();
+    let detected: usize = runs.iter().map(|r| r.detected).sum();
+    let leads: Vec<f64> = runs.iter().filter_map(|r| r.mean_lead_months).collect();
+
+    Ok(SyntheticBenchmark {
+        config: *config,
+        economies,
+        total_recessions,
+        mean_auc: (!aucs.is_empty()).then(|| aucs.iter().sum::<f64>() / aucs.len() as f64),
+        detection_rate: (total_recessions > 0).then(|| detected as f64 / total_recessions as f64),
+        mean_lead_months: (!leads.is_empty()).then(|| leads.iter().sum::<f64>() / leads.len() as f64),
+        runs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let config = SyntheticConfig::default();
+        let a = generate(&config);
+        let b = generate(&config);
+
+        assert_eq!(a.data.len(), config.months);
+        assert_eq!(a.recessions, b.recessions);
+        assert_eq!(a.data[100].m2_supply, b.data[100].m2_supply);
+    }
+
+    #[test]
+    fn test_recessions_respect_min_expansion() {
+        let config = SyntheticConfig { seed: 11, ..SyntheticConfig::default() };
+        let economy = generate(&config);
+
+        assert!(!economy.recessions.is_empty());
+        let mut prev_end = economy.data[0].date;
+        for (i, &(start, end)) in economy.recessions.iter().enumerate() {
+            assert!(start <= end);
+            let gap = backtest::months_between(prev_end, start);
+            let min = if i == 0 { config.min_expansion_months } else { config.min_expansion_months + 1 };
+            assert!(gap >= min as i32, "expansion of {} months", gap);
+            prev_end = end;
+        }
+    }
+
+    #[test]
+    fn test_engine_detects_strong_signal_better_than_none() {
+        let engine = NIVEngine::new();
+        let strong = SyntheticConfig { signal_strength: 1.0, ..SyntheticConfig::default() };
+        let silent = SyntheticConfig { signal_strength: 0.0, ..SyntheticConfig::default() };
+
+        let cancel = CancelToken::default();
+        let strong_auc = benchmark(&engine, &strong, 5, 0.5, &cancel).unwrap().mean_auc.unwrap();
+        let silent_auc = benchmark(&engine, &silent, 5, 0.5, &cancel).unwrap().mean_auc.unwrap();
+
+        assert!(strong_auc > silent_auc, "strong {} vs silent {}", strong_auc, silent_auc);
+        assert!(strong_auc > 0.6, "strong-signal AUC was {}", strong_auc);
+    }
+}
@@ -0,0 +1,232 @@
+//! Background Tasks
+//!
+//! Work the server does outside a request, under fixed names:
+//!
+//! | Task                 | Runs |
+//! |----------------------|------|
+//! | `dataset_load`       | once at startup |
+//! | `refresh`            | every NIV_REFRESH_SECS, and on admin refresh or canary promotion |
+//! | `intraday`           | every NIV_INTRADAY_SECS |
+//! | `tsdb_export`        | after each installed dataset (NIV_TSDB_CONFIG) |
+//! | `incident_webhook`   | after each blocked refresh (NIV_INCIDENT_WEBHOOK) |
+//! | `replay`             | per started replay, until it completes or is stopped |
+//!
+//! Each run is recorded through a `TaskRun` guard: start time, duration and
+//! outcome. A guard dropped without `finish` (an aborted replay, a panic)
+//! counts as a failed run. A periodic task's next run is the next tick of
+//! its interval from when its loop started (a tick missed while running is
+//! skipped, as the loops skip it), and `waiting` says why a due run has not
+//! started (dataset not loaded, deferred under load).
+//!
+//! GET /api/v1/admin/scheduler lists every task, so an operator can see why
+//! data stopped updating without reading the logs.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+pub const DATASET_LOAD: &str = "dataset_load";
+pub const REFRESH: &str = "refresh";
+pub const INTRADAY: &str = "intraday";
+pub const TSDB_EXPORT: &str = "tsdb_export";
+pub const INCIDENT_WEBHOOK: &str = "incident_webhook";
+pub const REPLAY: &str = "replay";
+
+/// When a task runs
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Schedule {
+    Every { secs: u64 },
+    Startup,
+    OnDemand,
+    Disabled,
+}
+
+/// A failed run
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TaskError {
+    pub at: DateTime<Utc>,
+    pub message: String,
+}
+
+/// One task's schedule and recent runs
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TaskStatus {
+    pub name: &'static str,
+    pub schedule: Schedule,
+    pub running: usize,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_started: Option<DateTime<Utc>>,
+    pub last_finished: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<u64>,
+    pub last_error: Option<TaskError>,
+    pub next_run: Option<DateTime<Utc>>,
+    pub waiting: Option<String>,
+}
+
+struct Entry {
+    status: TaskStatus,
+    anchor: DateTime<Utc>, // Interval start, for next_run
+}
+
+/// Registry of background tasks, in registration order
+#[derive(Clone, Default)]
+pub struct Tasks {
+    entries: Arc<Mutex<Vec<Entry>>>,
+}
+
+/// An in-flight run; record its outcome with `finish`
+pub struct TaskRun {
+    tasks: Tasks,
+    name: &'static str,
+    started: Instant,
+    finished: bool,
+}
+
+impl Tasks {
+    /// Add `name`, or replace its schedule; a periodic schedule starts now
+    pub fn register(&self, name: &'static str, schedule: Schedule) {
+        self.update(name, |entry| {
+            entry.status.schedule = schedule;
+            entry.anchor = Utc::now();
+        });
+    }
+
+    /// Begin a run of `name` (registered on demand if it is new)
+    pub fn start(&self, name: &'static str) -> TaskRun {
+        self.update(name, |entry| {
+            entry.status.running += 1;
+            entry.status.last_started = Some(Utc::now());
+            entry.status.waiting = None;
+        });
+        TaskRun { tasks: self.clone(), name, started: Instant::now(), finished: false }
+    }
+
+    /// Why a due run of `name` has not started, or None once it can
+    pub fn waiting(&self, name: &'static str, reason: Option<String>) {
+        self.update(name, |entry| entry.status.waiting = reason);
+    }
+
+    /// Every task as of `now`
+    pub fn list(&self, now: DateTime<Utc>) -> Vec<TaskStatus> {
+        let entries = self.entries.lock().expect("tasks lock");
+        entries
+            .iter()
+            .map(|entry| TaskStatus { next_run: next_run(entry, now), ..entry.status.clone() })
+            .collect()
+    }
+
+    fn update(&self, name: &'static str, f: impl FnOnce(&mut Entry)) {
+        let mut entries = self.entries.lock().expect("tasks lock");
+        let index = match entries.iter().position(|e| e.status.name == name) {
+            Some(index) => index,
+            None => {
+                entries.push(Entry {
+                    status: TaskStatus {
+                        name,
+                        schedule: Schedule::OnDemand,
+                        running: 0,
+                        runs: 0,
+                        failures: 0,
+                        last_started: None,
+                        last_finished: None,
+                        last_duration_ms: None,
+                        last_error: None,
+                        next_run: None,
+                        waiting: None,
+                    },
+                    anchor: Utc::now(),
+                });
+                entries.len() - 1
+            }
+        };
+        f(&mut entries[index]);
+    }
+
+    fn record(&self, name: &'static str, elapsed: Duration, result: Result<(), String>) {
+        let now = Utc::now();
+        self.update(name, |entry| {
+            let status = &mut entry.status;
+            status.running = status.running.saturating_sub(1);
+            status.runs += 1;
+            status.last_finished = Some(now);
+            status.last_duration_ms = Some(elapsed.as_millis() as u64);
+            if let Err(message) = result {
+                status.failures += 1;
+                status.last_error = Some(TaskError { at: now, message });
+            }
+        });
+    }
+}
+
+/// Next interval tick after `now` for a periodic task
+fn next_run(entry: &Entry, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let Schedule::Every { secs } = entry.status.schedule else {
+        return None;
+    };
+    let period = secs.max(1) as i64;
+    let elapsed = (now - entry.anchor).num_seconds().max(0);
+    Some(entry.anchor + chrono::Duration::seconds((elapsed / period + 1) * period))
+}
+
+impl TaskRun {
+    pub fn finish(mut self, result: Result<(), String>) {
+        self.finished = true;
+        self.tasks.record(self.name, self.started.elapsed(), result);
+    }
+}
+
+impl Drop for TaskRun {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.tasks.record(self.name, self.started.elapsed(), Err("ended before completing".to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs_record_duration_and_last_error() {
+        let tasks = Tasks::default();
+        tasks.register(REFRESH, Schedule::OnDemand);
+        tasks.waiting(REFRESH, Some("dataset not loaded".to_string()));
+
+        let run = tasks.start(REFRESH);
+        let running = &tasks.list(Utc::now())[0];
+        assert_eq!((running.running, running.waiting.as_deref()), (1, None));
+        run.finish(Err("swap checks failed".to_string()));
+        tasks.start(REFRESH).finish(Ok(()));
+        drop(tasks.start(REPLAY));
+
+        let listed = tasks.list(Utc::now());
+        assert_eq!(listed.iter().map(|t| t.name).collect::<Vec<_>>(), [REFRESH, REPLAY]);
+        let refresh = &listed[0];
+        assert_eq!((refresh.running, refresh.runs, refresh.failures), (0, 2, 1));
+        assert_eq!(refresh.last_error.as_ref().unwrap().message, "swap checks failed");
+        assert!(refresh.last_duration_ms.is_some() && refresh.next_run.is_none());
+        assert_eq!(listed[1].last_error.as_ref().unwrap().message, "ended before completing");
+    }
+
+    #[test]
+    fn test_next_run_is_the_next_interval_tick() {
+        let tasks = Tasks::default();
+        tasks.register(INTRADAY, Schedule::Every { secs: 60 });
+        let anchor = tasks.entries.lock().unwrap()[0].anchor;
+
+        let at = |secs| tasks.list(anchor + chrono::Duration::seconds(secs))[0].next_run;
+        assert_eq!(at(0), Some(anchor + chrono::Duration::seconds(60)));
+        assert_eq!(at(150), Some(anchor + chrono::Duration::seconds(180)));
+        tasks.register(INTRADAY, Schedule::Disabled);
+        assert_eq!(at(150), None);
+        assert_eq!(
+            serde_json::to_value(Schedule::Every { secs: 60 }).unwrap(),
+            serde_json::json!({"kind": "every", "secs": 60})
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Workspaces
+//!
+//! Lightweight tenancy for shared deployments: API keys (NIV_API_KEYS_FILE)
+//! map to a workspace, and per-client state is keyed by it so teams cannot
+//! see or stop each other's work. Today that state is replays and their
+//! webhooks, and preference profiles (see preferences.rs); anything stored
+//! per client later should be keyed the same way.
+//!
+//! ```json
+//! { "keys": [
+//!     { "key": "k-3f9a...", "workspace": "research" },
+//!     { "key": "k-71c2...", "workspace": "desk" }
+//! ] }
+//! ```
+//!
+//! Clients send the key as `X-API-Key`. Requests without one use the shared
+//! `default` workspace, so deployments without keys behave as before; an
+//! unknown key is rejected rather than silently downgraded. Without a keys
+//! file there is nothing to resolve against, so the header is ignored and
+//! every request uses `default`.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Workspace of requests without an API key
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Request header carrying the API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct Workspace(pub String);
+
+impl Workspace {
+    /// Whether this is the workspace of requests without a key
+    pub fn is_keyless(&self) -> bool {
+        self.0 == DEFAULT_WORKSPACE
+    }
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Workspace(DEFAULT_WORKSPACE.to_string())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyEntry {
+    key: String,
+    workspace: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeysFile {
+    keys: Vec<KeyEntry>,
+}
+
+/// API key → workspace; keys are held as SHA-256 digests
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    keys: HashMap<[u8; 32], Workspace>,
+}
+
+fn digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+impl ApiKeys {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&raw)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let file: KeysFile = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+        let mut keys = HashMap::new();
+        for (i, entry) in file.keys.into_iter().enumerate() {
+            if entry.key.is_empty() || entry.workspace.is_empty() {
+                return Err(format!("keys[{}]: key and workspace must be non-empty", i));
+            }
+            if entry.workspace == DEFAULT_WORKSPACE {
+                return Err(format!("keys[{}]: '{}' is reserved for requests without a key", i, DEFAULT_WORKSPACE));
+            }
+            if keys.insert(digest(&entry.key), Workspace(entry.workspace)).is_some() {
+                return Err(format!("keys[{}]: duplicate key", i));
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Workspace for a presented key (the default without one, or when no
+    /// keys are configured); None for an unknown key
+    pub fn resolve(&self, key: Option<&str>) -> Option<Workspace> {
+        match key {
+            _ if self.keys.is_empty() => Some(Workspace::default()),
+            None => Some(Workspace::default()),
+            Some(key) => self.keys.get(&digest(key)).cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let keys = ApiKeys::parse(r#"{"keys":[{"key":"a1","workspace":"research"},{"key":"b2","workspace":"desk"}]}"#).unwrap();
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys.resolve(Some("a1")), Some(Workspace("research".into())));
+        assert_eq!(keys.resolve(Some("b2")), Some(Workspace("desk".into())));
+        assert_eq!(keys.resolve(None), Some(Workspace::default()));
+        assert_eq!(keys.resolve(Some("nope")), None);
+    }
+
+    #[test]
+    fn test_key_ignored_without_keys_file() {
+        assert_eq!(ApiKeys::default().resolve(Some("a1")), Some(Workspace::default()));
+        assert_eq!(ApiKeys::default().resolve(None), Some(Workspace::default()));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_files() {
+        assert!(ApiKeys::parse(r#"{"keys":[{"key":"","workspace":"x"}]}"#).is_err());
+        assert!(ApiKeys::parse(r#"{"keys":[{"key":"a","workspace":"default"}]}"#).is_err());
+        assert!(ApiKeys::parse(r#"{"keys":[{"key":"a","workspace":"x"},{"key":"a","workspace":"y"}]}"#).is_err());
+        assert!(ApiKeys::parse(r#"{"keys":[{"key":"a","workspace":"x","role":"admin"}]}"#).is_err());
+    }
+}
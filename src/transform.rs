@@ -0,0 +1,249 @@
+//! Series Transformations
+//!
+//! The engine derives four of its inputs from raw series before computing
+//! components. Each is a pipeline of steps over its source series:
+//!
+//! | Series           | Engine input | Default                |
+//! |------------------|--------------|------------------------|
+//! | `investment`     | dG           | `[{"pct_change": 1}]`  |
+//! | `m2_supply`      | dA (and d²A, its change over M2_ACCEL_LAG months) | `[{"pct_change": 12}]` |
+//! | `fed_funds_rate` | dr           | `[{"diff": 1}]`        |
+//! | `yield_spread`   | ΔS (the `change_12m` spread drag) | `[{"diff": 12}]` |
+//!
+//! Steps, applied in order:
+//! - `"log"`: natural log; unavailable for non-positive values
+//! - `{"diff": n}`: x(t) - x(t-n)
+//! - `{"pct_change": n}`: 100 × (x(t) - x(t-n)) / x(t-n); unavailable when x(t-n) <= 0
+//! - `"yoy"`: `{"pct_change": 12}`
+//! - `"deflate"`: divide by a price level chained from `cpi_inflation`
+//!   (each month carries a twelfth of the year-on-year log rate), 1 at the
+//!   first month
+//! - `"seasonal_adjust"`: subtract additive calendar-month factors: the mean
+//!   deviation from the trailing 12-month mean for each calendar month over
+//!   the whole series, centred to sum to zero. Factors use the full sample,
+//!   so new data revises them; FRED inputs are already adjusted and the
+//!   defaults do not use it.
+//!
+//! Months a pipeline leaves unavailable (not enough history, log of zero)
+//! enter the engine as 0, as the first months always have.
+//!
+//! Loaded at startup from the JSON file named by `NIV_PIPELINES_FILE`;
+//! series left out keep their default:
+//! ```json
+//! { "investment": ["deflate", "log", {"diff": 1}] }
+//! ```
+//!
+//! The applied pipelines are engine parameters, so they are recorded with
+//! the others in the changelog, model card, pipeline hash and snapshot
+//! fingerprint.
+
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+
+use crate::niv::EconomicData;
+
+/// Longest lag a step accepts
+pub const MAX_LAG: usize = 120;
+
+/// Most steps in one pipeline
+pub const MAX_STEPS: usize = 8;
+
+/// One transformation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    Log,
+    Diff(usize),
+    PctChange(usize),
+    Yoy,
+    Deflate,
+    SeasonalAdjust,
+}
+
+/// Pipeline per source series
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Pipelines {
+    pub investment: Vec<Step>,
+    pub m2_supply: Vec<Step>,
+    pub fed_funds_rate: Vec<Step>,
+    pub yield_spread: Vec<Step>,
+}
+
+impl Default for Pipelines {
+    fn default() -> Self {
+        Self {
+            investment: vec![Step::PctChange(1)],
+            m2_supply: vec![Step::PctChange(12)],
+            fed_funds_rate: vec![Step::Diff(1)],
+            yield_spread: vec![Step::Diff(12)],
+        }
+    }
+}
+
+/// Engine inputs per month, None where unavailable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Derived {
+    pub dg: Vec<Option<f64>>,
+    pub da: Vec<Option<f64>>,
+    pub dr: Vec<Option<f64>>,
+    pub spread_change: Vec<Option<f64>>,
+}
+
+impl Pipelines {
+    /// Read and validate a pipelines file
+    pub fn load(path: &str) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let pipelines: Self = serde_json::from_str(&raw).map_err(|e| format!("Invalid pipelines file {}: {}", path, e))?;
+        pipelines.validate()?;
+        Ok(pipelines)
+    }
+
+    /// Each pipeline has at most MAX_STEPS steps with lags in 1..=MAX_LAG
+    pub fn validate(&self) -> Result<(), String> {
+        for (series, steps) in self.named() {
+            if steps.len() > MAX_STEPS {
+                return Err(format!("{}: at most {} steps", series, MAX_STEPS));
+            }
+            for step in steps {
+                if let Step::Diff(n) | Step::PctChange(n) = step {
+                    if *n == 0 || *n > MAX_LAG {
+                        return Err(format!("{}: lag must be between 1 and {}", series, MAX_LAG));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn named(&self) -> [(&'static str, &[Step]); 4] {
+        [
+            ("investment", &self.investment),
+            ("m2_supply", &self.m2_supply),
+            ("fed_funds_rate", &self.fed_funds_rate),
+            ("yield_spread", &self.yield_spread),
+        ]
+    }
+
+    /// Run every pipeline over `data`
+    pub fn derive(&self, data: &[EconomicData]) -> Derived {
+        Derived {
+            dg: apply(&self.investment, data, |d| d.investment),
+            da: apply(&self.m2_supply, data, |d| d.m2_supply),
+            dr: apply(&self.fed_funds_rate, data, |d| d.fed_funds_rate),
+            spread_change: apply(&self.yield_spread, data, |d| d.yield_spread),
+        }
+    }
+}
+
+/// Apply `steps` to the series `get` reads from `data`
+pub fn apply(steps: &[Step], data: &[EconomicData], get: fn(&EconomicData) -> f64) -> Vec<Option<f64>> {
+    let mut values: Vec<Option<f64>> = data.iter().map(|d| Some(get(d))).collect();
+    for step in steps {
+        values = match *step {
+            Step::Log => values.iter().map(|v| v.filter(|&x| x > 0.0).map(f64::ln)).collect(),
+            Step::Diff(n) => lagged(&values, n, |x, base| Some(x - base)),
+            Step::PctChange(n) => pct_change(&values, n),
+            Step::Yoy => pct_change(&values, 12),
+            Step::Deflate => {
+                let prices = price_level(data);
+                values.iter().zip(prices).map(|(v, p)| v.map(|x| x / p)).collect()
+            }
+            Step::SeasonalAdjust => seasonal_adjust(&values, data),
+        };
+    }
+    values
+}
+
+fn lagged(values: &[Option<f64>], n: usize, f: impl Fn(f64, f64) -> Option<f64>) -> Vec<Option<f64>> {
+    (0..values.len())
+        .map(|i| match (values[i], i.checked_sub(n).and_then(|j| values[j])) {
+            (Some(x), Some(base)) => f(x, base),
+            _ => None,
+        })
+        .collect()
+}
+
+fn pct_change(values: &[Option<f64>], n: usize) -> Vec<Option<f64>> {
+    lagged(values, n, |x, base| (base > 0.0).then(|| (x - base) / base * 100.0))
+}
+
+/// Price level from year-on-year CPI inflation, 1 at the first month
+fn price_level(data: &[EconomicData]) -> Vec<f64> {
+    let mut level = 1.0;
+    data.iter()
+        .enumerate()
+        .map(|(i, d)| {
+            if i > 0 {
+                level *= ((1.0 + d.cpi_inflation / 100.0).max(f64::MIN_POSITIVE).ln() / 12.0).exp();
+            }
+            level
+        })
+        .collect()
+}
+
+fn seasonal_adjust(values: &[Option<f64>], data: &[EconomicData]) -> Vec<Option<f64>> {
+    // Deviation from the trailing 12-month mean, summed per calendar month
+    let mut sums = [(0.0, 0usize); 12];
+    for i in 11..values.len() {
+        let window: Option<Vec<f64>> = values[i - 11..=i].iter().copied().collect();
+        if let (Some(window), Some(x)) = (window, values[i]) {
+            let slot = &mut sums[data[i].date.month0() as usize];
+            slot.0 += x - window.iter().sum::<f64>() / 12.0;
+            slot.1 += 1;
+        }
+    }
+    let mut factors = sums.map(|(sum, n)| if n > 0 { sum / n as f64 } else { 0.0 });
+    let mean = factors.iter().sum::<f64>() / 12.0;
+    for f in factors.iter_mut() {
+        *f -= mean;
+    }
+    values
+        .iter()
+        .zip(data)
+        .map(|(v, d)| v.map(|x| x - factors[d.date.month0() as usize]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+
+    #[test]
+    fn test_default_pipelines_match_engine_formulas() {
+        let data = mock::generate_mock_data(2000, 2005);
+        let derived = Pipelines::default().derive(&data);
+
+        let i = 30;
+        let dg = (data[i].investment - data[i - 1].investment) / data[i - 1].investment * 100.0;
+        assert_eq!(derived.dg[i], Some(dg));
+        assert_eq!(derived.dr[i], Some(data[i].fed_funds_rate - data[i - 1].fed_funds_rate));
+        assert_eq!(derived.spread_change[i], Some(data[i].yield_spread - data[i - 12].yield_spread));
+        assert_eq!((derived.da[11], derived.dg[0]), (None, None));
+
+        let log_diff = apply(&[Step::Log, Step::Diff(1)], &data, |d| d.investment);
+        assert!((log_diff[i].unwrap() - (data[i].investment / data[i - 1].investment).ln()).abs() < 1e-12);
+        assert_eq!(apply(&[Step::Yoy], &data, |d| d.m2_supply), derived.da);
+    }
+
+    #[test]
+    fn test_parse_validate_and_seasonal_adjust() {
+        let parsed: Pipelines = serde_json::from_str(r#"{"investment":["deflate","log",{"diff":1}]}"#).unwrap();
+        assert_eq!(parsed.investment, [Step::Deflate, Step::Log, Step::Diff(1)]);
+        assert_eq!(parsed.m2_supply, Pipelines::default().m2_supply);
+        assert!(parsed.validate().is_ok());
+        assert!(serde_json::from_str::<Pipelines>(r#"{"gdp":["log"]}"#).is_err());
+        let bad: Pipelines = serde_json::from_str(r#"{"fed_funds_rate":[{"diff":0}]}"#).unwrap();
+        assert!(bad.validate().is_err());
+
+        // A pure calendar pattern around a constant is removed
+        let mut data = mock::generate_mock_data(2000, 2005);
+        for d in data.iter_mut() {
+            d.capacity_util = 80.0 + if d.date.month() == 12 { 3.0 } else { 0.0 };
+        }
+        let adjusted = apply(&[Step::SeasonalAdjust], &data, |d| d.capacity_util);
+        let first = adjusted[0].unwrap();
+        assert!(adjusted.iter().all(|v| (v.unwrap() - first).abs() < 1e-9));
+    }
+}
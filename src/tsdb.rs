@@ -0,0 +1,201 @@
+//! Time-Series Database Export
+//!
+//! Pushes every refresh's results to a TSDB in InfluxDB line protocol, for
+//! organizations that centralize time series. Configured with a TOML file
+//! (NIV_TSDB_CONFIG):
+//!
+//! ```toml
+//! url = "http://influx:8086/api/v2/write?org=acme&bucket=macro"
+//! token = "..."             # optional, sent as `Authorization: Token ...`
+//! measurement = "niv"       # optional, default "niv"
+//! batch_size = 5000         # optional, lines per request
+//!
+//! [tags]                    # optional, added to every point
+//! env = "prod"
+//! ```
+//!
+//! Prometheus remote-write itself is protobuf; for Prometheus-compatible
+//! stores point `url` at a line-protocol receiver (VictoriaMetrics
+//! `/influx/write`, Telegraf's influxdb_listener).
+//!
+//! One point per month, timestamped at the month start in nanoseconds, so a
+//! re-push of the full history overwrites rather than duplicates.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::niv::NIVResult;
+
+const DEFAULT_MEASUREMENT: &str = "niv";
+const DEFAULT_BATCH_SIZE: usize = 5000;
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TsdbConfig {
+    pub url: String,
+    pub token: Option<String>,
+    #[serde(default = "default_measurement")]
+    pub measurement: String,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+fn default_measurement() -> String {
+    DEFAULT_MEASUREMENT.to_string()
+}
+
+fn default_batch_size() -> usize {
+    DEFAULT_BATCH_SIZE
+}
+
+#[derive(Debug)]
+pub enum TsdbError {
+    Config(String),
+    Io(String),
+    Network(String),
+    Rejected(String),
+}
+
+impl std::fmt::Display for TsdbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TsdbError::Config(e) => write!(f, "Invalid TSDB config: {}", e),
+            TsdbError::Io(e) => write!(f, "TSDB config I/O error: {}", e),
+            TsdbError::Network(e) => write!(f, "TSDB network error: {}", e),
+            TsdbError::Rejected(e) => write!(f, "TSDB rejected write: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TsdbError {}
+
+impl TsdbConfig {
+    pub fn load(path: &str) -> Result<Self, TsdbError> {
+        let raw = std::fs::read_to_string(path).map_err(|e| TsdbError::Io(format!("{}: {}", path, e)))?;
+        Self::parse(&raw)
+    }
+
+    pub fn parse(raw: &str) -> Result<Self, TsdbError> {
+        let config: TsdbConfig = toml::from_str(raw).map_err(|e| TsdbError::Config(e.to_string()))?;
+        if !(config.url.starts_with("http://") || config.url.starts_with("https://")) {
+            return Err(TsdbError::Config("url must be an http(s) URL".into()));
+        }
+        if config.measurement.is_empty() || config.batch_size == 0 {
+            return Err(TsdbError::Config("measurement must be non-empty and batch_size positive".into()));
+        }
+        Ok(config)
+    }
+}
+
+/// Escape a measurement name (commas and spaces)
+fn escape_measurement(s: &str) -> String {
+    s.replace(',', "\\,").replace(' ', "\\ ")
+}
+
+/// Escape a tag key or value (commas, equals signs and spaces)
+fn escape_tag(s: &str) -> String {
+    s.replace(',', "\\,").replace('=', "\\=").replace(' ', "\\ ")
+}
+
+/// One line per result
+pub fn line_protocol(config: &TsdbConfig, results: &[NIVResult], model_version: &str) -> Vec<String> {
+    let mut prefix = escape_measurement(&config.measurement);
+    let mut tags = config.tags.clone();
+    tags.insert("model_version".into(), model_version.into());
+    for (key, value) in &tags {
+        prefix.push_str(&format!(",{}={}", escape_tag(key), escape_tag(value)));
+    }
+
+    results
+        .iter()
+        .filter_map(|r| {
+            let ts = r.date.and_hms_opt(0, 0, 0)?.and_utc().timestamp_nanos_opt()?;
+            let fields = [
+                ("niv_score", r.niv_score),
+                ("niv_percentile", r.niv_percentile),
+                ("recession_probability", r.recession_probability),
+                ("thrust", r.components.thrust),
+                ("efficiency", r.components.efficiency),
+                ("slack", r.components.slack),
+                ("drag", r.components.drag),
+            ]
+            .into_iter()
+            .filter(|(_, v)| v.is_finite())
+            .map(|(k, v)| format!("{}={}", k, v))
+            .chain(std::iter::once(format!("alert_level=\"{}\"", r.alert_level.label())))
+            .collect::<Vec<_>>()
+            .join(",");
+            Some(format!("{} {} {}", prefix, fields, ts))
+        })
+        .collect()
+}
+
+/// Write `lines` in batches; returns the number of lines written
+pub async fn push(client: &reqwest::Client, config: &TsdbConfig, lines: &[String]) -> Result<usize, TsdbError> {
+    for batch in lines.chunks(config.batch_size) {
+        let mut request = client
+            .post(&config.url)
+            .timeout(TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(batch.join("\n"));
+        if let Some(token) = &config.token {
+            request = request.header(reqwest::header::AUTHORIZATION, format!("Token {}", token));
+        }
+        let response = request.send().await.map_err(|e| TsdbError::Network(e.to_string()))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(TsdbError::Rejected(format!("{}: {}", status, body.trim())));
+        }
+    }
+    Ok(lines.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_parse_config() {
+        let config = TsdbConfig::parse(
+            r#"
+            url = "http://localhost:8086/api/v2/write?org=o&bucket=b"
+            token = "secret"
+
+            [tags]
+            env = "prod"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.measurement, "niv");
+        assert_eq!(config.batch_size, DEFAULT_BATCH_SIZE);
+        assert_eq!(config.tags["env"], "prod");
+
+        assert!(TsdbConfig::parse(r#"url = "ftp://x""#).is_err());
+        assert!(TsdbConfig::parse(r#"url = "http://x"
+                                      batch = 3"#).is_err());
+    }
+
+    #[test]
+    fn test_line_protocol_escapes_and_timestamps() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2020, 2021));
+        let mut config = TsdbConfig::parse(r#"url = "http://x""#).unwrap();
+        config.measurement = "niv score".into();
+        config.tags.insert("region".into(), "us,east".into());
+
+        let lines = line_protocol(&config, &results, "NIV-v6");
+        assert_eq!(lines.len(), results.len());
+        let first = &lines[0];
+        assert!(first.starts_with("niv\\ score,model_version=NIV-v6,region=us\\,east "));
+        assert!(first.contains("alert_level=\""));
+        let ts = results[0].date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp_nanos_opt().unwrap();
+        assert!(first.ends_with(&format!(" {}", ts)));
+    }
+}
@@ -0,0 +1,161 @@
+//! Embeddable Widget
+//!
+//! A small self-contained HTML page for third-party iframes, rendered
+//! server-side from the latest results:
+//! - current NIV score and alert label
+//! - semicircle gauge of the recession probability, in the alert colour
+//! - sparkline of the last SPARKLINE_MONTHS NIV scores
+//!
+//! No external assets. The page subscribes to `/widget/events` (SSE) and
+//! reloads itself when a refresh installs new data. Every interpolated
+//! value is HTML-escaped, numbers included.
+
+use crate::niv::NIVResult;
+
+/// Months shown on the sparkline
+pub const SPARKLINE_MONTHS: usize = 24;
+
+const SPARK_WIDTH: f64 = 200.0;
+const SPARK_HEIGHT: f64 = 40.0;
+const GAUGE_RADIUS: f64 = 50.0;
+
+/// `raw` safe inside HTML text and quoted attribute values
+fn escape(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// SVG path of a semicircle arc from the left end covering `fraction` of it
+fn gauge_arc(fraction: f64) -> String {
+    let angle = std::f64::consts::PI * (1.0 - fraction.clamp(0.0, 1.0));
+    let (x, y) = (60.0 + GAUGE_RADIUS * angle.cos(), 60.0 - GAUGE_RADIUS * angle.sin());
+    format!("M 10 60 A {r} {r} 0 0 1 {x:.2} {y:.2}", r = GAUGE_RADIUS)
+}
+
+/// Polyline points for `values`, scaled to the sparkline box
+fn sparkline_points(values: &[f64]) -> String {
+    let (min, max) = values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let span = if max > min { max - min } else { 1.0 };
+    let step = SPARK_WIDTH / (values.len().max(2) - 1) as f64;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| format!("{:.1},{:.1}", i as f64 * step, SPARK_HEIGHT - (v - min) / span * SPARK_HEIGHT))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Render the widget page, or None without data
+pub fn render(results: &[NIVResult], model_version: &str) -> Option<String> {
+    let latest = results.last()?;
+    let recent: Vec<f64> = results[results.len().saturating_sub(SPARKLINE_MONTHS)..]
+        .iter()
+        .map(|r| r.niv_score)
+        .collect();
+    let color = latest.alert_level.color();
+
+    Some(format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>NIV {score} · {label}</title>
+<style>
+  body {{ margin: 0; font: 14px/1.3 system-ui, sans-serif; color: #111827; background: #fff; }}
+  .niv {{ display: flex; align-items: center; gap: 16px; padding: 12px; border: 1px solid #e5e7eb; border-radius: 8px; }}
+  .score {{ font-size: 32px; font-weight: 700; }}
+  .label {{ color: {color}; font-weight: 600; }}
+  .muted {{ color: #6b7280; font-size: 12px; }}
+</style>
+</head>
+<body>
+<div class="niv">
+  <svg width="120" height="70" viewBox="0 0 120 70" role="img" aria-label="Recession probability {probability}%">
+    <path d="{track}" fill="none" stroke="#e5e7eb" stroke-width="10"/>
+    <path d="{arc}" fill="none" stroke="{color}" stroke-width="10"/>
+    <text x="60" y="58" text-anchor="middle" font-size="14" font-weight="600">{probability_rounded}%</text>
+  </svg>
+  <div>
+    <div class="score">{score}</div>
+    <div class="label">{label}</div>
+    <svg width="{spark_width}" height="{spark_height}" viewBox="0 0 {spark_width} {spark_height}" role="img" aria-label="NIV score, last {months} months">
+      <polyline points="{spark}" fill="none" stroke="#2563eb" stroke-width="1.5"/>
+    </svg>
+    <div class="muted">NIV score · {date} · {model_version}</div>
+  </div>
+</div>
+<script>
+  if (window.EventSource) {{
+    new EventSource("widget/events").addEventListener("update", function () {{ location.reload(); }});
+  }}
+</script>
+</body>
+</html>
+"##,
+        score = escape(&format!("{:.1}", latest.niv_score)),
+        label = escape(latest.alert_level.label()),
+        probability = escape(&format!("{:.1}", latest.recession_probability * 100.0)),
+        probability_rounded = escape(&format!("{:.0}", latest.recession_probability * 100.0)),
+        track = escape(&gauge_arc(1.0)),
+        arc = escape(&gauge_arc(latest.recession_probability)),
+        spark = escape(&sparkline_points(&recent)),
+        spark_width = escape(&SPARK_WIDTH.to_string()),
+        spark_height = escape(&SPARK_HEIGHT.to_string()),
+        months = escape(&recent.len().to_string()),
+        date = escape(&latest.date.to_string()),
+        color = escape(color),
+        model_version = escape(model_version),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::NIVEngine;
+
+    #[test]
+    fn test_render_includes_score_gauge_and_sparkline() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2015, 2024));
+        let html = render(&results, "NIV-test").unwrap();
+        let latest = results.last().unwrap();
+
+        assert!(html.contains(&format!("{:.1}", latest.niv_score)));
+        assert!(html.contains(latest.alert_level.color()));
+        assert!(html.contains("widget/events"));
+        let points = html.split("points=\"").nth(1).unwrap().split('"').next().unwrap();
+        assert_eq!(points.split(' ').count(), SPARKLINE_MONTHS);
+        assert!(render(&[], "NIV-test").is_none());
+    }
+
+    #[test]
+    fn test_interpolated_values_are_escaped() {
+        let results = NIVEngine::new().calculate_series(&mock::generate_mock_data(2015, 2024));
+        let html = render(&results, "<script>alert('x')</script>\"").unwrap();
+
+        assert!(!html.contains("<script>alert"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt;&quot;"));
+        assert_eq!(escape("a&b"), "a&amp;b");
+    }
+
+    #[test]
+    fn test_gauge_and_sparkline_geometry() {
+        assert_eq!(gauge_arc(1.0), "M 10 60 A 50 50 0 0 1 110.00 60.00");
+        assert!(gauge_arc(0.5).ends_with("60.00 10.00"));
+        assert_eq!(sparkline_points(&[1.0, 3.0, 2.0]), "0.0,40.0 100.0,0.0 200.0,20.0");
+        assert_eq!(sparkline_points(&[5.0]), "0.0,40.0");
+    }
+}
@@ -0,0 +1,111 @@
+//! Yield Curve Inversion Episodes
+//!
+//! Detects inversions of the 10Y-3M spread (T10Y3M) in the monthly inputs:
+//! - an episode is a run of months with a negative spread; re-steepenings of
+//!   up to MAX_GAP_MONTHS are bridged so one inversion is not split in two
+//! - depth is the most negative spread in the episode, in percentage points
+//! - the subsequent recession is the first NBER recession starting after the
+//!   episode starts and within RECESSION_HORIZON_MONTHS of its end
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::backtest::months_between;
+
+/// Longest run of non-inverted months merged into a surrounding episode
+pub const MAX_GAP_MONTHS: i32 = 3;
+
+/// How long after an episode ends a recession still counts as following it
+pub const RECESSION_HORIZON_MONTHS: i32 = 24;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FollowingRecession {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub lead_months: i32, // Episode start to recession start
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct InversionEpisode {
+    pub start: NaiveDate,
+    pub end: NaiveDate,  // Last inverted month
+    pub months: usize,   // Inverted months (bridged gaps excluded)
+    pub max_depth: f64,  // Most negative spread (pp)
+    pub trough_date: NaiveDate,
+    pub ongoing: bool,   // Still inverted at the last observation
+    pub recession: Option<FollowingRecession>,
+}
+
+/// Inversion episodes in a monthly `(date, spread)` series, oldest first
+pub fn episodes(spreads: &[(NaiveDate, f64)], recessions: &[(NaiveDate, NaiveDate)]) -> Vec<InversionEpisode> {
+    let mut found: Vec<InversionEpisode> = Vec::new();
+    for &(date, spread) in spreads.iter().filter(|(_, s)| *s < 0.0) {
+        match found.last_mut() {
+            Some(episode) if months_between(episode.end, date) <= MAX_GAP_MONTHS + 1 => {
+                episode.end = date;
+                episode.months += 1;
+                if spread < episode.max_depth {
+                    episode.max_depth = spread;
+                    episode.trough_date = date;
+                }
+            }
+            _ => found.push(InversionEpisode {
+                start: date,
+                end: date,
+                months: 1,
+                max_depth: spread,
+                trough_date: date,
+                ongoing: false,
+                recession: None,
+            }),
+        }
+    }
+
+    let last = spreads.last().map(|(d, _)| *d);
+    for episode in &mut found {
+        episode.ongoing = Some(episode.end) == last;
+        episode.recession = recessions
+            .iter()
+            .filter(|(start, _)| *start >= episode.start && months_between(episode.end, *start) <= RECESSION_HORIZON_MONTHS)
+            .min_by_key(|(start, _)| *start)
+            .map(|&(start, end)| FollowingRecession { start, end, lead_months: months_between(episode.start, start) });
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fred::mock;
+    use crate::niv::RecessionPeriods;
+
+    fn month(y: i32, m: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, 1).unwrap()
+    }
+
+    #[test]
+    fn test_episodes_bridge_short_gaps() {
+        let raw = [0.5, -0.2, -0.6, 0.1, -0.3, 0.4, 0.4, 0.4, 0.4, 0.4, -0.1];
+        let spreads: Vec<_> = raw.iter().enumerate().map(|(i, s)| (month(2000, 1 + i as u32), *s)).collect();
+        let found = episodes(&spreads, &[(month(2000, 8), month(2000, 10))]);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!((found[0].start, found[0].end, found[0].months), (month(2000, 2), month(2000, 5), 3));
+        assert_eq!((found[0].max_depth, found[0].trough_date), (-0.6, month(2000, 3)));
+        assert_eq!(found[0].recession.as_ref().unwrap().lead_months, 6);
+        assert!(!found[0].ongoing);
+        assert!(found[1].ongoing);
+        assert!(found[1].recession.is_none());
+    }
+
+    #[test]
+    fn test_mock_history_inversions_precede_recessions() {
+        let data = mock::generate_mock_data(1990, 2010);
+        let spreads: Vec<_> = data.iter().map(|d| (d.date, d.yield_spread)).collect();
+        let found = episodes(&spreads, &RecessionPeriods::known_recessions());
+
+        assert!(!found.is_empty());
+        assert!(found.iter().all(|e| e.max_depth < 0.0 && e.start <= e.trough_date && e.trough_date <= e.end));
+        assert!(found.iter().any(|e| e.recession.is_some()));
+    }
+}